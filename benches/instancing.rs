@@ -0,0 +1,96 @@
+//! Renders 1000 cubes sharing one mesh and material with the headless
+//! renderer and reports frame time and draw call counts.
+//!
+//! Run with `cargo bench --bench instancing`.
+
+use std::time::Instant;
+
+use glam::{Quat, Vec3};
+use wgpu_cube::renderer::{cube_mesh, Material, RenderBatcher, Renderer};
+use wgpu_cube::scene::components::{DirectionalLight, TransformComponent};
+use wgpu_cube::scene::{EntityBuilder, Scene, Transform};
+use wgpu_cube::settings::RenderSettings;
+
+const GRID: i32 = 10;
+const WARMUP_FRAMES: u32 = 10;
+const FRAMES: u32 = 100;
+
+fn main() {
+    let Some(mut renderer) =
+        pollster::block_on(Renderer::new_headless(512, 512, RenderSettings::default()))
+    else {
+        eprintln!("Skipping instancing benchmark: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let mesh = scene
+        .assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+    let offset = (GRID - 1) as f32;
+    for x in 0..GRID {
+        for y in 0..GRID {
+            for z in 0..GRID {
+                EntityBuilder::new(&mut scene.world)
+                    .with_mesh(mesh)
+                    .with_material(Material::red())
+                    .with_transform(Transform::from_trs(
+                        Vec3::new(x as f32, y as f32, z as f32) * 2.0 - Vec3::splat(offset),
+                        Quat::IDENTITY,
+                        Vec3::splat(0.5),
+                    ))
+                    .visible(true)
+                    .spawn();
+            }
+        }
+    }
+    scene.world.spawn((
+        TransformComponent(Transform::from_trs(
+            Vec3::ZERO,
+            Quat::from_rotation_arc(Vec3::NEG_Z, Vec3::new(-0.4, -1.0, -0.6).normalize()),
+            Vec3::ONE,
+        )),
+        DirectionalLight::new(Vec3::ONE, 3.0),
+    ));
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(30.0, 25.0, 40.0);
+    camera.target = Vec3::ZERO;
+
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+
+    let mut batcher = RenderBatcher::new();
+    let mut render_frame = |renderer: &mut Renderer| {
+        scene
+            .render(renderer, &mut batcher)
+            .expect("headless render failed")
+            .present();
+    };
+
+    for _ in 0..WARMUP_FRAMES {
+        render_frame(&mut renderer);
+    }
+    renderer.read_back_frame();
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        render_frame(&mut renderer);
+    }
+    renderer.read_back_frame();
+    let elapsed = start.elapsed();
+
+    let stats = renderer.last_frame_stats();
+    println!(
+        "{} instances: {:.3} ms/frame, {} draw calls ({} instanced, {} shadow)",
+        stats.instance_count,
+        elapsed.as_secs_f64() * 1000.0 / FRAMES as f64,
+        stats.total_draw_calls(),
+        stats.instance_draw_calls,
+        stats.shadow_draw_calls,
+    );
+}
@@ -1,8 +1,14 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
 use glam::Vec3;
 use log::info;
-use wgpu_cube::app::{AppBuilder, StartupContext, UpdateContext};
+use wgpu_cube::app::{AppBuilder, GpuUpdateContext, StartupContext};
 use wgpu_cube::render_application::{run_application, RenderApplication};
-use wgpu_cube::scene::SceneLoader;
+use wgpu_cube::renderer::Renderer;
+use wgpu_cube::scene::{GltfLoadHandle, GltfLoadStatus, OrbitCameraPlugin, SceneLoader};
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -17,41 +23,121 @@ impl RenderApplication for ExampleApp {
         builder.disable_default_textures();
         builder.disable_default_lighting();
         builder.skip_initial_frames(5);
-    }
+        builder.add_plugin(OrbitCameraPlugin::default());
 
-    fn setup(&mut self, ctx: &mut StartupContext) {
-        load_chess_scene(ctx);
+        // Stream the board in over the first frames instead of blocking startup.
+        let mut loading = Some(load_chess_scene());
+        builder.add_gpu_system(move |ctx| poll_chess_scene(ctx, &mut loading));
+
+        // F12 saves a screenshot of the next frame.
+        let screenshot_requested = Rc::new(Cell::new(false));
+        let pressed = screenshot_requested.clone();
+        builder.add_window_event_handler(move |event| {
+            if let WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::F12),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } = event
+            {
+                pressed.set(true);
+            }
+        });
+        builder.add_gpu_system(move |ctx| {
+            if screenshot_requested.replace(false) {
+                request_screenshot(ctx.renderer);
+            }
+        });
     }
 
-    fn update(&mut self, ctx: &mut UpdateContext) {
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        // The orbit controller picks up its initial yaw/pitch/distance from here.
         let factor = CHESS_SCALE.log10().max(0.5);
-        orbit_camera(ctx, 5.0 * factor, 2.0 * factor);
+        let camera = ctx.scene.camera_mut();
+        camera.eye = Vec3::new(5.0 * factor, 2.0 * factor, 0.0);
+        camera.target = Vec3::ZERO;
+        camera.up = Vec3::Y;
     }
 }
 
-fn load_chess_scene(ctx: &mut StartupContext<'_>) {
-    let renderer = &mut *ctx.renderer;
-    let scene = &mut *ctx.scene;
-
+fn load_chess_scene() -> GltfLoadHandle {
     info!("Loading glTF: {} (scale: {})", GLTF_PATH, CHESS_SCALE);
 
-    match SceneLoader::load_gltf(GLTF_PATH, scene, renderer, CHESS_SCALE) {
-        Ok(_) => {
-            scene.add_default_lighting();
-            info!("glTF loaded: {} entities", scene.world.len());
-        }
-        Err(err) => {
-            log::error!("Failed to load glTF: {}", err);
+    SceneLoader::load_gltf_async(GLTF_PATH, CHESS_SCALE).with_on_complete(|scene| {
+        scene.add_default_lighting();
+        info!("glTF loaded: {} entities", scene.world.len());
+    })
+}
+
+fn poll_chess_scene(ctx: &mut GpuUpdateContext<'_>, loading: &mut Option<GltfLoadHandle>) {
+    let Some(handle) = loading.as_mut() else {
+        return;
+    };
+
+    match handle.poll(ctx.scene, ctx.renderer) {
+        GltfLoadStatus::Pending => {
+            let progress = handle.progress();
+            log::debug!(
+                "Loading chess scene: {:?}, {} bytes, {}/{} textures, {}/{} meshes",
+                progress.stage,
+                progress.bytes_loaded,
+                progress.textures_decoded,
+                progress.textures_total,
+                progress.meshes_uploaded,
+                progress.meshes_total
+            );
         }
+        // Failures are logged by the handle.
+        GltfLoadStatus::Complete | GltfLoadStatus::Failed(_) => *loading = None,
     }
 }
 
-fn orbit_camera(ctx: &mut UpdateContext<'_>, radius: f32, height: f32) {
-    let t = ctx.scene.time() as f32 * 0.25;
-    let camera = ctx.scene.camera_mut();
-    camera.eye = Vec3::new(t.cos() * radius, height, t.sin() * radius);
-    camera.target = Vec3::ZERO;
-    camera.up = Vec3::Y;
+#[cfg(not(target_arch = "wasm32"))]
+fn request_screenshot(renderer: &mut Renderer) {
+    let path = format!(
+        "chess_{}.png",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default()
+    );
+    info!("Capturing screenshot to {}", path);
+    renderer.request_screenshot_to_file(path);
+}
+
+/// Passes the PNG bytes to `window.onScreenshot(width, height, bytes)` when
+/// the page defines it.
+#[cfg(target_arch = "wasm32")]
+fn request_screenshot(renderer: &mut Renderer) {
+    renderer.request_screenshot(|screenshot| {
+        let png = match screenshot.encode_png() {
+            Ok(png) => png,
+            Err(err) => {
+                log::warn!("{}", err);
+                return;
+            }
+        };
+        let callback = js_sys::Reflect::get(&js_sys::global(), &"onScreenshot".into())
+            .ok()
+            .and_then(|value| value.dyn_into::<js_sys::Function>().ok());
+        let Some(callback) = callback else {
+            log::warn!("Define window.onScreenshot(width, height, bytes) to receive screenshots");
+            return;
+        };
+        let bytes = js_sys::Uint8Array::from(png.as_slice());
+        if let Err(err) = callback.call3(
+            &JsValue::NULL,
+            &screenshot.width.into(),
+            &screenshot.height.into(),
+            &bytes,
+        ) {
+            log::warn!("onScreenshot failed: {:?}", err);
+        }
+    });
 }
 
 #[cfg(not(target_arch = "wasm32"))]
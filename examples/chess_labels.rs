@@ -0,0 +1,146 @@
+use glam::{Quat, Vec3};
+use log::info;
+use wgpu_cube::app::{AppBuilder, StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::scene::components::{BoundingBox, WorldTransform};
+use wgpu_cube::scene::{
+    Name, OrbitCameraPlugin, SceneLoader, TextBillboard, Transform, TransformComponent,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const GLTF_PATH: &str = "web/assets/chessboard/ABeautifulGame.gltf";
+const CHESS_SCALE: f32 = 15.0;
+const HEALTH_SEGMENTS: usize = 10;
+/// Seconds between health bar changes.
+const DAMAGE_INTERVAL: f64 = 0.5;
+
+/// Floating name tags and text health bars over every chess piece. The bars
+/// drain one segment at a time, so the label meshes are rebuilt while the
+/// scene runs.
+struct ExampleApp {
+    labels: Vec<Label>,
+    timer: f64,
+    next: usize,
+}
+
+struct Label {
+    entity: hecs::Entity,
+    name: String,
+    health: usize,
+}
+
+impl Label {
+    fn text(&self) -> String {
+        format!(
+            "{}\n{}{}",
+            self.name,
+            "#".repeat(self.health),
+            ".".repeat(HEALTH_SEGMENTS - self.health)
+        )
+    }
+}
+
+impl RenderApplication for ExampleApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+        builder.add_plugin(OrbitCameraPlugin::default());
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let factor = CHESS_SCALE.log10().max(0.5);
+        let camera = ctx.scene.camera_mut();
+        camera.eye = Vec3::new(5.0 * factor, 2.0 * factor, 0.0);
+        camera.target = Vec3::ZERO;
+
+        if let Err(err) = SceneLoader::load_gltf(GLTF_PATH, ctx.scene, ctx.renderer, CHESS_SCALE) {
+            log::error!("{}", err);
+            return;
+        }
+        ctx.scene.add_default_lighting();
+        // Resolve world transforms so the labels can sit on top of each piece.
+        ctx.scene.update(0.0);
+
+        let pieces: Vec<(String, Vec3)> = ctx
+            .scene
+            .world
+            .query::<(&Name, &WorldTransform, &BoundingBox)>()
+            .iter()
+            .filter(|(_, (name, _, _))| !name.0.contains("Body") && name.0 != "Chessboard")
+            .map(|(_, (name, transform, bounds))| {
+                let bounds = bounds.transformed(transform.0.matrix());
+                let top = Vec3::new(bounds.center().x, bounds.max.y, bounds.center().z);
+                (display_name(&name.0), top)
+            })
+            .collect();
+
+        for (name, top) in pieces {
+            let entity = ctx.scene.world.spawn((
+                Name::new(format!("{} Label", name)),
+                TransformComponent(Transform::from_trs(
+                    top + Vec3::Y * 0.1,
+                    Quat::IDENTITY,
+                    Vec3::ONE,
+                )),
+            ));
+            let label = Label {
+                entity,
+                name,
+                health: HEALTH_SEGMENTS,
+            };
+            let text = TextBillboard::new(label.text()).with_font_size(0.12);
+            ctx.scene.world.insert_one(entity, text).ok();
+            self.labels.push(label);
+        }
+        info!("Spawned {} labels", self.labels.len());
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        if self.labels.is_empty() {
+            return;
+        }
+        self.timer += ctx.dt;
+        if self.timer < DAMAGE_INTERVAL {
+            return;
+        }
+        self.timer = 0.0;
+
+        let label = &mut self.labels[self.next];
+        label.health = label.health.checked_sub(1).unwrap_or(HEALTH_SEGMENTS);
+        if let Ok(mut text) = ctx.scene.world.get::<&mut TextBillboard>(label.entity) {
+            text.text = label.text();
+        }
+        self.next = (self.next + 7) % self.labels.len();
+    }
+}
+
+/// "Pawn_Top_W1" -> "Pawn W1".
+fn display_name(node: &str) -> String {
+    node.split('_')
+        .filter(|part| *part != "Top")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn app() -> ExampleApp {
+    ExampleApp {
+        labels: Vec::new(),
+        timer: 0.0,
+        next: 0,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(app()).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    run_application(app()).unwrap();
+}
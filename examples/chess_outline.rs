@@ -0,0 +1,139 @@
+//! Click a chess piece to outline it. The piece marks its silhouette in the
+//! stencil buffer and a slightly larger copy draws the rim around it.
+//! Clicking the board or empty space clears the selection.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use glam::{Vec2, Vec3};
+use log::info;
+use wgpu_cube::app::{AppBuilder, GpuUpdateContext, StartupContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::scene::components::Name;
+use wgpu_cube::scene::{
+    GltfLoadHandle, GltfLoadStatus, OrbitCameraPlugin, Outline, Scene, SceneLoader,
+};
+use winit::event::{ElementState, MouseButton, WindowEvent};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const GLTF_PATH: &str = "web/assets/chessboard/ABeautifulGame.gltf";
+const CHESS_SCALE: f32 = 15.0;
+const OUTLINE_COLOR: [u8; 4] = [255, 170, 0, 255];
+
+struct ExampleApp;
+
+impl RenderApplication for ExampleApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_textures();
+        builder.disable_default_lighting();
+        builder.add_plugin(OrbitCameraPlugin::default());
+
+        let mut loading = Some(load_chess_scene());
+        builder.add_gpu_system(move |ctx| poll_chess_scene(ctx, &mut loading));
+
+        // Cursor position in physical pixels, and a click waiting to be picked.
+        let cursor = Rc::new(Cell::new(Vec2::ZERO));
+        let clicked = Rc::new(Cell::new(false));
+        let (cursor_events, clicked_events) = (cursor.clone(), clicked.clone());
+        builder.add_window_event_handler(move |event| match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                cursor_events.set(Vec2::new(position.x as f32, position.y as f32));
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => clicked_events.set(true),
+            _ => {}
+        });
+
+        let mut selected = None;
+        builder.add_gpu_system(move |ctx| {
+            if clicked.replace(false) {
+                select_piece(ctx, cursor.get(), &mut selected);
+            }
+        });
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let factor = CHESS_SCALE.log10().max(0.5);
+        let camera = ctx.scene.camera_mut();
+        camera.eye = Vec3::new(5.0 * factor, 2.0 * factor, 0.0);
+        camera.target = Vec3::ZERO;
+        camera.up = Vec3::Y;
+
+        // Compile every pipeline variant before the first frame.
+        let compiled = ctx.renderer.warm_up();
+        info!("Warmed up {} pipeline variants", compiled);
+    }
+}
+
+/// Moves the outline to the piece under `cursor`, if any.
+fn select_piece(ctx: &mut GpuUpdateContext<'_>, cursor: Vec2, selected: &mut Option<hecs::Entity>) {
+    let size = ctx.renderer.surface_size();
+    let viewport = Vec2::new(size.width as f32, size.height as f32);
+    let hit = ctx
+        .scene
+        .pick(ctx.scene.camera(), cursor, viewport)
+        .map(|hit| hit.entity)
+        .filter(|&entity| !is_board(ctx.scene, entity));
+
+    if let Some(previous) = selected.take() {
+        let _ = ctx.scene.world.remove_one::<Outline>(previous);
+    }
+    if let Some(entity) = hit {
+        if ctx
+            .scene
+            .world
+            .insert_one(entity, Outline::new(OUTLINE_COLOR))
+            .is_ok()
+        {
+            *selected = Some(entity);
+        }
+    }
+}
+
+fn is_board(scene: &Scene, entity: hecs::Entity) -> bool {
+    scene
+        .world
+        .get::<&Name>(entity)
+        .is_ok_and(|name| name.0.starts_with("Chessboard"))
+}
+
+fn load_chess_scene() -> GltfLoadHandle {
+    info!("Loading glTF: {} (scale: {})", GLTF_PATH, CHESS_SCALE);
+
+    SceneLoader::load_gltf_async(GLTF_PATH, CHESS_SCALE).with_on_complete(|scene| {
+        scene.add_default_lighting();
+        info!("glTF loaded: {} entities", scene.world.len());
+    })
+}
+
+fn poll_chess_scene(ctx: &mut GpuUpdateContext<'_>, loading: &mut Option<GltfLoadHandle>) {
+    let Some(handle) = loading.as_mut() else {
+        return;
+    };
+    match handle.poll(ctx.scene, ctx.renderer) {
+        GltfLoadStatus::Pending => {}
+        // Failures are logged by the handle.
+        GltfLoadStatus::Complete | GltfLoadStatus::Failed(_) => *loading = None,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(ExampleApp).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    if let Err(e) = run_application(ExampleApp) {
+        web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+    }
+}
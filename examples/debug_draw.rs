@@ -0,0 +1,176 @@
+use glam::{EulerRot, Quat, Vec3, Vec4};
+use log::info;
+use wgpu_cube::app::{AppBuilder, GpuUpdateContext, StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::components::{CanCastShadow, DirectionalLight};
+use wgpu_cube::scene::{
+    MaterialComponent, MeshComponent, Name, Transform, TransformComponent, Visible,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const SHADOW_SIZE: f32 = 15.0;
+const CASCADE_COLORS: [Vec4; 4] = [
+    Vec4::new(1.0, 0.3, 0.3, 1.0),
+    Vec4::new(1.0, 0.85, 0.2, 1.0),
+    Vec4::new(0.3, 1.0, 0.4, 1.0),
+    Vec4::new(0.3, 0.6, 1.0, 1.0),
+];
+
+/// Outlines the directional light's shadow cascades, the cubes' bounds and a
+/// bounding sphere with debug lines while the sun slowly circles the scene.
+#[derive(Default)]
+struct DebugDrawApp {
+    sun: Option<hecs::Entity>,
+    cubes: Vec<(Vec3, f32)>,
+}
+
+impl RenderApplication for DebugDrawApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        self.setup_scene(ctx);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let Some(sun) = self.sun else {
+            return;
+        };
+        let scene = &mut *ctx.scene;
+
+        let angle = scene.time() as f32 * 0.3;
+        if let Ok(mut transform) = scene.world.get::<&mut TransformComponent>(sun) {
+            transform.0.rotation = sun_rotation(angle);
+        }
+
+        let debug = scene.debug_draw();
+        for &(center, half) in &self.cubes {
+            debug.draw_aabb(
+                center - Vec3::splat(half),
+                center + Vec3::splat(half),
+                Vec4::new(0.2, 1.0, 0.4, 1.0),
+            );
+        }
+        debug.draw_sphere_wireframe(
+            Vec3::new(0.0, 1.0, 0.0),
+            2.5,
+            32,
+            Vec4::new(0.3, 0.6, 1.0, 0.6),
+        );
+
+        // World axes stay visible through the floor.
+        debug.set_depth_test(false);
+        debug.draw_line(Vec3::ZERO, Vec3::X, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        debug.draw_line(Vec3::ZERO, Vec3::Y, Vec4::new(0.0, 1.0, 0.0, 1.0));
+        debug.draw_line(Vec3::ZERO, Vec3::Z, Vec4::new(0.0, 0.0, 1.0, 1.0));
+    }
+
+    // Cascades depend on the renderer's aspect ratio and cascade count.
+    fn gpu_update(&mut self, ctx: &mut GpuUpdateContext) {
+        let Some(sun) = self.sun else {
+            return;
+        };
+        let Some(shadow) = ctx.scene.directional_shadow_cascades(sun, ctx.renderer) else {
+            return;
+        };
+
+        let debug = ctx.scene.debug_draw();
+        debug.set_depth_test(true);
+        for ((_, view_proj), color) in shadow.cascades().zip(CASCADE_COLORS) {
+            debug.draw_frustum(view_proj, color);
+        }
+    }
+}
+
+fn sun_rotation(angle: f32) -> Quat {
+    Quat::from_euler(EulerRot::YXZ, angle, -0.9, 0.0)
+}
+
+impl DebugDrawApp {
+    fn setup_scene(&mut self, ctx: &mut StartupContext<'_>) {
+        let renderer = &mut *ctx.renderer;
+        let scene = &mut *ctx.scene;
+
+        info!("Creating debug draw scene...");
+
+        let (verts, idx) = wgpu_cube::renderer::cube_mesh();
+        let cube_mesh = renderer.create_mesh(&verts, &idx);
+        let cube_handle = scene.assets.meshes.insert(cube_mesh);
+
+        scene.world.spawn((
+            Name::new("Debug Floor"),
+            TransformComponent(Transform::from_trs(
+                Vec3::new(0.0, -0.05, 0.0),
+                Quat::IDENTITY,
+                Vec3::new(12.0, 0.1, 12.0),
+            )),
+            MeshComponent(cube_handle),
+            MaterialComponent(Material::new([200, 200, 200, 255]).with_roughness(0.9)),
+            Visible(true),
+        ));
+
+        self.cubes = vec![
+            (Vec3::new(-1.5, 0.5, 0.0), 0.5),
+            (Vec3::new(1.2, 0.75, -1.0), 0.75),
+            (Vec3::new(0.3, 0.35, 1.6), 0.35),
+        ];
+        for (i, &(center, half)) in self.cubes.iter().enumerate() {
+            scene.world.spawn((
+                Name::new(format!("Debug Cube {i}")),
+                TransformComponent(Transform::from_trs(
+                    center,
+                    Quat::IDENTITY,
+                    Vec3::splat(half * 2.0),
+                )),
+                MeshComponent(cube_handle),
+                MaterialComponent(Material::new([180, 90, 60, 255]).with_roughness(0.6)),
+                Visible(true),
+                CanCastShadow(true),
+            ));
+        }
+
+        let sun = scene.world.spawn((
+            Name::new("Debug Sun"),
+            TransformComponent(Transform::from_trs(
+                Vec3::ZERO,
+                sun_rotation(0.0),
+                Vec3::ONE,
+            )),
+            DirectionalLight::new(Vec3::new(1.0, 0.95, 0.85), 2.5).with_shadow_size(SHADOW_SIZE),
+            CanCastShadow(true),
+        ));
+        self.sun = Some(sun);
+
+        let camera = scene.camera_mut();
+        camera.eye = Vec3::new(9.0, 8.0, 12.0);
+        camera.target = Vec3::ZERO;
+        camera.up = Vec3::Y;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(DebugDrawApp::default()).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    web_sys::console::log_1(&"[Rust] start_app() called".into());
+
+    match run_application(DebugDrawApp::default()) {
+        Ok(_) => {
+            web_sys::console::log_1(&"[Rust] Application started successfully".into());
+        }
+        Err(e) => {
+            web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+        }
+    }
+}
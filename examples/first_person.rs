@@ -0,0 +1,105 @@
+use glam::{Quat, Vec3};
+use wgpu_cube::app::{StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::{
+    FirstPersonController, MaterialComponent, MeshComponent, Name, Transform, TransformComponent,
+    Visible,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const PILLAR_GRID: i32 = 6;
+const PILLAR_SPACING: f32 = 4.0;
+
+/// Walk around a field of pillars: WASD moves, the mouse looks around and
+/// E/Q fly up and down. Escape quits.
+struct ExampleApp {
+    cursor_grabbed: bool,
+}
+
+impl RenderApplication for ExampleApp {
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let (verts, idx) = wgpu_cube::renderer::cube_mesh();
+        let cube = ctx.renderer.create_mesh(&verts, &idx);
+        let cube = ctx.scene.assets.meshes.insert(cube);
+
+        let floor_size = PILLAR_GRID as f32 * PILLAR_SPACING * 2.0 + PILLAR_SPACING;
+        ctx.scene.world.spawn((
+            Name::new("Floor"),
+            TransformComponent(Transform::from_trs(
+                Vec3::new(0.0, -0.5, 0.0),
+                Quat::IDENTITY,
+                Vec3::new(floor_size, 1.0, floor_size),
+            )),
+            MeshComponent(cube),
+            MaterialComponent(Material::new([90, 95, 100, 255]).with_roughness(0.9)),
+            Visible(true),
+        ));
+
+        for x in -PILLAR_GRID..=PILLAR_GRID {
+            for z in -PILLAR_GRID..=PILLAR_GRID {
+                if (x + z) % 2 != 0 {
+                    continue;
+                }
+                let height = 1.0 + ((x * 7 + z * 13).rem_euclid(5)) as f32;
+                ctx.scene.world.spawn((
+                    Name::new(format!("Pillar_{}_{}", x, z)),
+                    TransformComponent(Transform::from_trs(
+                        Vec3::new(
+                            x as f32 * PILLAR_SPACING,
+                            height * 0.5,
+                            z as f32 * PILLAR_SPACING,
+                        ),
+                        Quat::IDENTITY,
+                        Vec3::new(0.6, height, 0.6),
+                    )),
+                    MeshComponent(cube),
+                    MaterialComponent(Material::new([200, 120, 80, 255]).with_roughness(0.6)),
+                    Visible(true),
+                ));
+            }
+        }
+
+        ctx.scene.world.spawn((
+            Name::new("Player"),
+            TransformComponent(Transform::from_trs(
+                Vec3::new(0.0, 1.7, PILLAR_GRID as f32 * PILLAR_SPACING),
+                Quat::IDENTITY,
+                Vec3::ONE,
+            )),
+            FirstPersonController {
+                speed: 6.0,
+                ..FirstPersonController::default()
+            },
+        ));
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        if !self.cursor_grabbed {
+            ctx.commands.set_cursor_grab(true);
+            self.cursor_grabbed = true;
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(ExampleApp {
+        cursor_grabbed: false,
+    })
+    .unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    run_application(ExampleApp {
+        cursor_grabbed: false,
+    })
+    .unwrap();
+}
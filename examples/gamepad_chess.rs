@@ -0,0 +1,114 @@
+//! Orbits the chess board with a gamepad: the right stick turns the camera,
+//! the triggers zoom and South (A / cross) resets the view. Mouse and arrow
+//! keys keep working. Run with `--features gamepad` to enable controllers.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glam::Vec3;
+use log::info;
+use wgpu_cube::app::{AppBuilder, GpuUpdateContext, StartupContext, UpdateContext};
+use wgpu_cube::input::{GamepadAxis, GamepadButton};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::scene::{
+    Camera, GltfLoadHandle, GltfLoadStatus, OrbitCameraController, OrbitCameraSettings, SceneLoader,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const GLTF_PATH: &str = "web/assets/chessboard/ABeautifulGame.gltf";
+const CHESS_SCALE: f32 = 15.0;
+/// Radians per second at full stick deflection.
+const STICK_ROTATE_SPEED: f32 = 2.0;
+/// Scroll notches per second at full trigger travel.
+const TRIGGER_ZOOM_SPEED: f32 = 8.0;
+
+struct ExampleApp;
+
+impl RenderApplication for ExampleApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_textures();
+        builder.disable_default_lighting();
+        builder.skip_initial_frames(5);
+
+        let mut loading = Some(load_chess_scene());
+        builder.add_gpu_system(move |ctx| poll_chess_scene(ctx, &mut loading));
+
+        let controller = Rc::new(RefCell::new(OrbitCameraController::new(
+            OrbitCameraSettings::default(),
+        )));
+        let events = controller.clone();
+        builder.add_window_event_handler(move |event| {
+            events.borrow_mut().handle_event(event);
+        });
+        builder.add_system(move |ctx| drive_camera(ctx, &controller));
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        place_camera(ctx.scene.camera_mut());
+    }
+}
+
+fn place_camera(camera: &mut Camera) {
+    let factor = CHESS_SCALE.log10().max(0.5);
+    camera.eye = Vec3::new(5.0 * factor, 2.0 * factor, 0.0);
+    camera.target = Vec3::ZERO;
+    camera.up = Vec3::Y;
+}
+
+fn drive_camera(ctx: &mut UpdateContext<'_>, controller: &Rc<RefCell<OrbitCameraController>>) {
+    let dt = ctx.dt as f32;
+
+    if ctx.input.is_button_pressed(GamepadButton::South) {
+        // A fresh controller picks its orbit up from the camera.
+        place_camera(ctx.scene.camera_mut());
+        *controller.borrow_mut() = OrbitCameraController::new(OrbitCameraSettings::default());
+    }
+
+    let mut controller = controller.borrow_mut();
+    let stick = ctx.input.right_stick();
+    controller.rotate(
+        stick.x * STICK_ROTATE_SPEED * dt,
+        -stick.y * STICK_ROTATE_SPEED * dt,
+    );
+    let zoom = ctx.input.axis(GamepadAxis::RightTrigger) - ctx.input.axis(GamepadAxis::LeftTrigger);
+    controller.zoom(zoom * TRIGGER_ZOOM_SPEED * dt);
+    controller.update(ctx.scene.camera_mut(), dt);
+}
+
+fn load_chess_scene() -> GltfLoadHandle {
+    info!("Loading glTF: {} (scale: {})", GLTF_PATH, CHESS_SCALE);
+
+    SceneLoader::load_gltf_async(GLTF_PATH, CHESS_SCALE).with_on_complete(|scene| {
+        scene.add_default_lighting();
+        info!("glTF loaded: {} entities", scene.world.len());
+    })
+}
+
+fn poll_chess_scene(ctx: &mut GpuUpdateContext<'_>, loading: &mut Option<GltfLoadHandle>) {
+    let Some(handle) = loading.as_mut() else {
+        return;
+    };
+    match handle.poll(ctx.scene, ctx.renderer) {
+        GltfLoadStatus::Pending => {}
+        // Failures are logged by the handle.
+        GltfLoadStatus::Complete | GltfLoadStatus::Failed(_) => *loading = None,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(ExampleApp).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    if let Err(e) = run_application(ExampleApp) {
+        web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+    }
+}
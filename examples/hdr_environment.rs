@@ -0,0 +1,130 @@
+use glam::{Quat, Vec3};
+use log::{info, warn};
+use wgpu_cube::app::{AppBuilder, StartupContext, UpdateContext};
+use wgpu_cube::asset::Handle;
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::{
+    MaterialComponent, MeshComponent, Name, SceneLoader, Transform, TransformComponent, Visible,
+};
+use wgpu_cube::EnvironmentMap;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const ENVIRONMENTS: [&str; 2] = [
+    "web/assets/hdr/citrus_orchard_puresky_4k.hdr",
+    "web/assets/hdr/christmas_photo_studio_01_4k.hdr",
+];
+const SWAP_INTERVAL: f64 = 5.0;
+
+/// Bakes a few HDR environments up front and swaps the scene between them
+/// every few seconds; the spheres are lit by image-based lighting alone.
+#[derive(Default)]
+struct HdrEnvironmentApp {
+    maps: Vec<Handle<EnvironmentMap>>,
+    current: Option<usize>,
+    elapsed: f64,
+}
+
+impl RenderApplication for HdrEnvironmentApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        setup_scene(ctx);
+
+        for path in ENVIRONMENTS {
+            match SceneLoader::load_hdr_environment(path, ctx.renderer) {
+                Ok(map) => self.maps.push(map),
+                Err(err) => warn!("Failed to load environment: {}", err),
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        if self.maps.is_empty() {
+            return;
+        }
+        self.elapsed += ctx.dt;
+        if self.current.is_some() && self.elapsed < SWAP_INTERVAL {
+            return;
+        }
+        self.elapsed = 0.0;
+
+        let next = self
+            .current
+            .map_or(0, |index| (index + 1) % self.maps.len());
+        ctx.scene.set_environment_map(self.maps[next]);
+        info!("Environment {} of {}", next + 1, self.maps.len());
+        self.current = Some(next);
+    }
+}
+
+fn setup_scene(ctx: &mut StartupContext<'_>) {
+    let renderer = &mut *ctx.renderer;
+    let scene = &mut *ctx.scene;
+
+    let (verts, idx) = wgpu_cube::renderer::sphere_mesh(64, 32);
+    let sphere_mesh = renderer.create_mesh(&verts, &idx);
+    let sphere_handle = scene.assets.meshes.insert(sphere_mesh);
+
+    let count = 7;
+    let spacing = 1.5;
+    let start_x = -((count - 1) as f32 * spacing) * 0.5;
+
+    // Top row metallic, bottom row dielectric, roughness increasing to the
+    // right. The fully rough spheres show the environment's ambient colour.
+    for (row, metallic) in [(0, 1.0), (1, 0.0)] {
+        for i in 0..count {
+            let roughness = i as f32 / (count - 1) as f32;
+            let material = Material::new([235, 235, 235, 255])
+                .with_metallic(metallic)
+                .with_roughness(roughness);
+
+            scene.world.spawn((
+                Name::new(format!("Sphere_M{metallic:.0}_R{roughness:.2}")),
+                TransformComponent(Transform::from_trs(
+                    Vec3::new(
+                        start_x + i as f32 * spacing,
+                        0.8 - row as f32 * spacing,
+                        0.0,
+                    ),
+                    Quat::IDENTITY,
+                    Vec3::splat(0.6),
+                )),
+                MeshComponent(sphere_handle),
+                MaterialComponent(material),
+                Visible(true),
+            ));
+        }
+    }
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(0.0, 0.5, 8.0);
+    camera.target = Vec3::ZERO;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(HdrEnvironmentApp::default()).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    web_sys::console::log_1(&"[Rust] start_app() called".into());
+
+    match run_application(HdrEnvironmentApp::default()) {
+        Ok(_) => {
+            web_sys::console::log_1(&"[Rust] Application started successfully".into());
+        }
+        Err(e) => {
+            web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use glam::{Quat, Vec3};
+use log::info;
+use wgpu_cube::app::{AppBuilder, StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::components::{CanCastShadow, DirectionalLight};
+use wgpu_cube::scene::{
+    CameraProjection, MaterialComponent, MeshComponent, Name, Transform, TransformComponent,
+    Visible,
+};
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::Key;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const BOARD_SIZE: i32 = 8;
+const TILE_SIZE: f32 = 1.0;
+const NEAR: f32 = 0.1;
+const FAR: f32 = 60.0;
+
+/// A tiled board with a few pillars seen from an isometric angle. Press `P`
+/// to switch between perspective and orthographic projection.
+#[derive(Default)]
+struct OrthographicApp {
+    toggle_requested: Rc<Cell<bool>>,
+}
+
+impl RenderApplication for OrthographicApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+
+        let toggle = self.toggle_requested.clone();
+        builder.add_window_event_handler(move |event| {
+            if let WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        logical_key: Key::Character(c),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } = event
+            {
+                if c.eq_ignore_ascii_case("p") {
+                    toggle.set(true);
+                }
+            }
+        });
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        setup_board(ctx);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        if !self.toggle_requested.replace(false) {
+            return;
+        }
+
+        let camera = ctx.scene.camera_mut();
+        camera.projection = match camera.projection {
+            CameraProjection::Perspective { .. } => {
+                info!("Switching to orthographic projection");
+                board_orthographic()
+            }
+            CameraProjection::Orthographic { .. } => {
+                info!("Switching to perspective projection");
+                board_perspective()
+            }
+        };
+    }
+}
+
+fn board_orthographic() -> CameraProjection {
+    let extent = BOARD_SIZE as f32 * TILE_SIZE * 1.5;
+    CameraProjection::Orthographic {
+        width: extent,
+        height: extent,
+        near: NEAR,
+        far: FAR,
+    }
+}
+
+fn board_perspective() -> CameraProjection {
+    CameraProjection::Perspective {
+        fov: 45f32.to_radians(),
+        near: NEAR,
+        far: FAR,
+    }
+}
+
+fn setup_board(ctx: &mut StartupContext<'_>) {
+    let renderer = &mut *ctx.renderer;
+    let scene = &mut *ctx.scene;
+
+    info!("Creating orthographic board scene (press P to toggle projection)...");
+
+    let (verts, idx) = wgpu_cube::renderer::cube_mesh();
+    let cube_mesh = renderer.create_mesh(&verts, &idx);
+    let cube_handle = scene.assets.meshes.insert(cube_mesh);
+
+    let offset = (BOARD_SIZE as f32 - 1.0) * TILE_SIZE * 0.5;
+    for x in 0..BOARD_SIZE {
+        for z in 0..BOARD_SIZE {
+            let shade = if (x + z) % 2 == 0 { 230 } else { 60 };
+            scene.world.spawn((
+                Name::new(format!("Tile {x},{z}")),
+                TransformComponent(Transform::from_trs(
+                    Vec3::new(
+                        x as f32 * TILE_SIZE - offset,
+                        -0.1,
+                        z as f32 * TILE_SIZE - offset,
+                    ),
+                    Quat::IDENTITY,
+                    Vec3::new(TILE_SIZE, 0.2, TILE_SIZE),
+                )),
+                MeshComponent(cube_handle),
+                MaterialComponent(Material::new([shade, shade, shade, 255]).with_roughness(0.7)),
+                Visible(true),
+            ));
+        }
+    }
+
+    let pillars = [(1, 2, 1.0), (5, 3, 2.0), (3, 6, 1.5), (6, 6, 0.75)];
+    for (i, (x, z, height)) in pillars.into_iter().enumerate() {
+        scene.world.spawn((
+            Name::new(format!("Pillar {i}")),
+            TransformComponent(Transform::from_trs(
+                Vec3::new(
+                    x as f32 * TILE_SIZE - offset,
+                    height * 0.5,
+                    z as f32 * TILE_SIZE - offset,
+                ),
+                Quat::IDENTITY,
+                Vec3::new(0.5, height, 0.5),
+            )),
+            MeshComponent(cube_handle),
+            MaterialComponent(Material::new([200, 120, 60, 255]).with_roughness(0.5)),
+            Visible(true),
+            CanCastShadow(true),
+        ));
+    }
+
+    let sun_direction = Vec3::new(-0.5, -1.0, -0.3).normalize();
+    scene.world.spawn((
+        Name::new("Board Sun"),
+        TransformComponent(Transform::from_trs(
+            Vec3::ZERO,
+            Quat::from_rotation_arc(Vec3::NEG_Z, sun_direction),
+            Vec3::ONE,
+        )),
+        DirectionalLight::new(Vec3::new(1.0, 0.96, 0.9), 3.0).with_shadow_size(12.0),
+        CanCastShadow(true),
+    ));
+
+    // True isometric angle: 45° around Y, ~35.26° down.
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::splat(12.0);
+    camera.target = Vec3::ZERO;
+    camera.up = Vec3::Y;
+    camera.projection = board_orthographic();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(OrthographicApp::default()).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    web_sys::console::log_1(&"[Rust] start_app() called".into());
+
+    match run_application(OrthographicApp::default()) {
+        Ok(_) => {
+            web_sys::console::log_1(&"[Rust] Application started successfully".into());
+        }
+        Err(e) => {
+            web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+        }
+    }
+}
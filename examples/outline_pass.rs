@@ -0,0 +1,246 @@
+//! Outlines one entity from a custom frame pass. After the main pass it
+//! marks the sphere's silhouette in the shared stencil buffer, then draws a
+//! slightly inflated copy around the mark. The rim depth tests against the
+//! scene, so the cylinder in front of the sphere hides part of it.
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Quat, Vec3};
+use wgpu::util::DeviceExt;
+use wgpu_cube::app::{AppBuilder, StartupContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::{self, FramePassContext, Material, PipelineBuilder, Vertex};
+use wgpu_cube::scene::{EntityBuilder, OrbitCameraPlugin, Transform};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const OUTLINE_COLOR: [f32; 4] = [1.0, 0.65, 0.0, 1.0];
+const OUTLINE_WIDTH: f32 = 0.06;
+const STENCIL_MARK: u32 = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct OutlineUniform {
+    model: [[f32; 4]; 4],
+    color: [f32; 4],
+    width: f32,
+    _padding: [f32; 3],
+}
+
+struct OutlinePipelines {
+    mark: wgpu::RenderPipeline,
+    rim: wgpu::RenderPipeline,
+    uniform: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl OutlinePipelines {
+    fn new(ctx: &FramePassContext<'_>) -> Self {
+        let renderer = ctx.renderer;
+        let device = renderer.get_device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Outline Pass Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/outline_pass.wgsl").into()),
+        });
+
+        let uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Outline Uniform"),
+            contents: bytemuck::bytes_of(&OutlineUniform::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Outline Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Outline Bind Group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Outline Pipeline Layout"),
+            bind_group_layouts: &[renderer.camera_bind_layout(), &layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline =
+            |label, vertex_entry, write_mask, depth_compare, stencil_compare, pass_op| {
+                let face = wgpu::StencilFaceState {
+                    compare: stencil_compare,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op,
+                };
+                PipelineBuilder::new(device, &pipeline_layout, &shader)
+                    .with_label(label)
+                    .with_vertex_entry(vertex_entry)
+                    .with_vertex_buffer(Vertex::layout())
+                    .with_color_target_state(wgpu::ColorTargetState {
+                        format: renderer.surface_format(),
+                        blend: None,
+                        write_mask,
+                    })
+                    .with_depth_stencil(renderer.depth_format(), false, depth_compare)
+                    .with_stencil(wgpu::StencilState {
+                        front: face,
+                        back: face,
+                        read_mask: 0xff,
+                        write_mask: 0xff,
+                    })
+                    .with_multisample(ctx.sample_count)
+                    .build()
+            };
+        // The mark covers the whole silhouette, hidden parts included, so
+        // the rim never draws over the entity itself.
+        let mark = pipeline(
+            "Outline Mark Pipeline",
+            "vs_mark",
+            wgpu::ColorWrites::empty(),
+            wgpu::CompareFunction::Always,
+            wgpu::CompareFunction::Always,
+            wgpu::StencilOperation::Replace,
+        );
+        let rim = pipeline(
+            "Outline Rim Pipeline",
+            "vs_rim",
+            wgpu::ColorWrites::ALL,
+            wgpu::CompareFunction::LessEqual,
+            wgpu::CompareFunction::NotEqual,
+            wgpu::StencilOperation::Keep,
+        );
+
+        Self {
+            mark,
+            rim,
+            uniform,
+            bind_group,
+        }
+    }
+
+    fn draw(&self, ctx: &mut FramePassContext<'_>, target: hecs::Entity) {
+        // The entity's mesh and world transform as batched this frame.
+        let Some((mesh, instance)) = ctx.batches.opaque().find_map(|batch| {
+            batch
+                .instances()
+                .iter()
+                .find(|instance| instance.entity == Some(target))
+                .map(|instance| (batch.mesh(), instance))
+        }) else {
+            return;
+        };
+        let (renderer, assets) = (ctx.renderer, ctx.assets);
+        let Some(mesh) = assets.meshes.get(mesh) else {
+            return;
+        };
+
+        let uniform = OutlineUniform {
+            model: instance.transform.matrix().to_cols_array_2d(),
+            color: OUTLINE_COLOR,
+            width: OUTLINE_WIDTH,
+            _padding: [0.0; 3],
+        };
+        renderer
+            .get_queue()
+            .write_buffer(&self.uniform, 0, bytemuck::bytes_of(&uniform));
+
+        let mut pass = ctx.begin_render_pass("OutlinePass");
+        pass.set_bind_group(0, renderer.camera_bind_group(), &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, mesh.vertex_buffer().slice(..));
+        pass.set_index_buffer(mesh.index_buffer().slice(..), mesh.index_format());
+        pass.set_stencil_reference(STENCIL_MARK);
+        for pipeline in [&self.mark, &self.rim] {
+            pass.set_pipeline(pipeline);
+            pass.draw_indexed(0..mesh.index_count(), 0, 0..1);
+        }
+    }
+}
+
+struct OutlinePassApp;
+
+impl RenderApplication for OutlinePassApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.add_plugin(OrbitCameraPlugin::default());
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let device = ctx.renderer.get_device();
+        let plane = renderer::create_plane(device, 12.0, 12.0, 1, 1);
+        let sphere = renderer::create_sphere(device, 1.0, 24, 48);
+        let cylinder = renderer::create_cylinder(device, 0.5, 0.5, 2.0, 32);
+
+        let scene = &mut *ctx.scene;
+        let shapes = [
+            ("Ground", plane, Vec3::ZERO, Material::checker()),
+            (
+                "Sphere",
+                sphere,
+                Vec3::new(0.0, 1.0, 0.0),
+                Material::new([220, 80, 60, 255]).with_roughness(0.4),
+            ),
+            (
+                "Cylinder",
+                cylinder,
+                Vec3::new(0.8, 1.0, 2.0),
+                Material::new([80, 160, 220, 255]).with_roughness(0.6),
+            ),
+        ];
+        let mut target = None;
+        for (name, mesh, position, material) in shapes {
+            let mesh = scene.assets.meshes.insert(mesh);
+            let entity = EntityBuilder::new(&mut scene.world)
+                .with_name(name)
+                .with_transform(Transform::from_trs(position, Quat::IDENTITY, Vec3::ONE))
+                .with_mesh(mesh)
+                .with_material(material)
+                .visible(true)
+                .spawn();
+            if name == "Sphere" {
+                target = Some(entity);
+            }
+        }
+        let target = target.expect("sphere spawned");
+
+        let mut pipelines = None;
+        ctx.renderer
+            .add_pass_after("Main", "outline", move |ctx| {
+                pipelines
+                    .get_or_insert_with(|| OutlinePipelines::new(ctx))
+                    .draw(ctx, target);
+            })
+            .expect("Main is a built-in pass");
+        log::info!("Frame passes: {:?}", ctx.renderer.frame_pass_order());
+
+        let camera = scene.camera_mut();
+        camera.eye = Vec3::new(2.0, 4.0, 8.0);
+        camera.target = Vec3::new(0.0, 1.0, 0.0);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(OutlinePassApp).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    if let Err(e) = run_application(OutlinePassApp) {
+        web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+    }
+}
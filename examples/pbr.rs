@@ -37,8 +37,7 @@ fn setup_pbr_scene(ctx: &mut StartupContext<'_>) {
 
     info!("Creating PBR test scene...");
 
-    let (verts, idx) = wgpu_cube::renderer::sphere_mesh(64, 32);
-    let sphere_mesh = renderer.create_mesh(&verts, &idx);
+    let sphere_mesh = wgpu_cube::renderer::create_sphere(renderer.get_device(), 0.8, 32, 64);
     let sphere_handle = scene.assets.meshes.insert(sphere_mesh);
 
     let unit_mr = Texture::from_color_linear(
@@ -83,7 +82,7 @@ fn setup_pbr_scene(ctx: &mut StartupContext<'_>) {
                 TransformComponent(Transform::from_trs(
                     Vec3::new(x, 0.0, z),
                     Quat::IDENTITY,
-                    Vec3::splat(0.8),
+                    Vec3::ONE,
                 )),
                 MeshComponent(sphere_handle),
                 MaterialComponent(material),
@@ -0,0 +1,117 @@
+use glam::{Quat, Vec3};
+use log::info;
+use wgpu_cube::app::{AppBuilder, StartupContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::components::{AttenuationOverride, PointLight};
+use wgpu_cube::scene::{
+    MaterialComponent, MeshComponent, Name, Transform, TransformComponent, Visible,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const LIGHT_HEIGHT: f32 = 1.0;
+const LIGHT_SPACING: f32 = 3.5;
+
+struct ExampleApp;
+
+impl RenderApplication for ExampleApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        setup_falloff_scene(ctx);
+    }
+}
+
+/// Two identical point lights over a floor: the left one fades slowly
+/// across its whole range, the right one drops off right next to the bulb.
+fn setup_falloff_scene(ctx: &mut StartupContext<'_>) {
+    let renderer = &mut *ctx.renderer;
+    let scene = &mut *ctx.scene;
+
+    info!("Creating point light falloff scene...");
+
+    let (verts, idx) = wgpu_cube::renderer::cube_mesh();
+    let cube_mesh = renderer.create_mesh(&verts, &idx);
+    let cube_handle = scene.assets.meshes.insert(cube_mesh);
+
+    let (verts, idx) = wgpu_cube::renderer::sphere_mesh(32, 16);
+    let sphere_mesh = renderer.create_mesh(&verts, &idx);
+    let sphere_handle = scene.assets.meshes.insert(sphere_mesh);
+
+    scene.world.spawn((
+        Name::new("Falloff Floor"),
+        TransformComponent(Transform::from_trs(
+            Vec3::new(0.0, -0.05, 0.0),
+            Quat::IDENTITY,
+            Vec3::new(16.0, 0.1, 10.0),
+        )),
+        MeshComponent(cube_handle),
+        MaterialComponent(Material::new([210, 210, 210, 255]).with_roughness(0.8)),
+        Visible(true),
+    ));
+
+    let curves = [
+        ("Soft", -LIGHT_SPACING, AttenuationOverride::new(0.05, 0.02)),
+        ("Hard", LIGHT_SPACING, AttenuationOverride::new(0.8, 2.0)),
+    ];
+
+    for (label, x, attenuation) in curves {
+        let position = Vec3::new(x, LIGHT_HEIGHT, 0.0);
+
+        scene.world.spawn((
+            Name::new(format!("{label} Falloff Light")),
+            TransformComponent(Transform::from_trs(position, Quat::IDENTITY, Vec3::ONE)),
+            PointLight {
+                color: Vec3::new(1.0, 0.85, 0.6),
+                intensity: 6.0,
+                range: 8.0,
+            },
+            attenuation,
+        ));
+
+        // Emissive marker at the light position.
+        scene.world.spawn((
+            Name::new(format!("{label} Falloff Bulb")),
+            TransformComponent(Transform::from_trs(
+                position,
+                Quat::IDENTITY,
+                Vec3::splat(0.15),
+            )),
+            MeshComponent(sphere_handle),
+            MaterialComponent(Material::new([255, 220, 160, 255]).with_unlit()),
+            Visible(true),
+        ));
+    }
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(0.0, 7.0, 9.0);
+    camera.target = Vec3::ZERO;
+    camera.up = Vec3::Y;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(ExampleApp).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    web_sys::console::log_1(&"[Rust] start_app() called".into());
+
+    match run_application(ExampleApp) {
+        Ok(_) => {
+            web_sys::console::log_1(&"[Rust] Application started successfully".into());
+        }
+        Err(e) => {
+            web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use glam::{Quat, Vec3, Vec4};
+use wgpu_cube::app::{AppBuilder, StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::{self, Material};
+use wgpu_cube::scene::{EntityBuilder, OrbitCameraPlugin, Transform};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const GROUND_SIZE: f32 = 20.0;
+const GRID_CELLS: u32 = 20;
+const GRID_COLOR: Vec4 = Vec4::new(0.35, 0.35, 0.4, 1.0);
+
+/// A tessellated, checkered ground plane under a sphere, a cylinder and a
+/// cone, with an editor grid drawn just above it.
+#[derive(Default)]
+struct PrimitivesApp {
+    grid: Vec<Vec3>,
+}
+
+impl RenderApplication for PrimitivesApp {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.add_plugin(OrbitCameraPlugin::default());
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let device = ctx.renderer.get_device();
+        let plane = renderer::create_plane(device, GROUND_SIZE, GROUND_SIZE, 32, 32);
+        let sphere = renderer::create_sphere(device, 1.0, 24, 48);
+        let cylinder = renderer::create_cylinder(device, 0.8, 0.8, 2.0, 32);
+        let cone = renderer::create_cylinder(device, 0.0, 1.0, 2.0, 32);
+
+        let scene = &mut *ctx.scene;
+        let shapes = [
+            ("Ground", plane, Vec3::ZERO, Material::checker()),
+            (
+                "Sphere",
+                sphere,
+                Vec3::new(-3.0, 1.0, 0.0),
+                Material::new([220, 80, 60, 255]).with_roughness(0.4),
+            ),
+            (
+                "Cylinder",
+                cylinder,
+                Vec3::new(0.0, 1.0, 0.0),
+                Material::new([80, 160, 220, 255]).with_roughness(0.6),
+            ),
+            (
+                "Cone",
+                cone,
+                Vec3::new(3.0, 1.0, 0.0),
+                Material::new([240, 200, 80, 255]).with_metallic(0.8),
+            ),
+        ];
+        for (name, mesh, position, material) in shapes {
+            let mesh = scene.assets.meshes.insert(mesh);
+            EntityBuilder::new(&mut scene.world)
+                .with_name(name)
+                .with_transform(Transform::from_trs(position, Quat::IDENTITY, Vec3::ONE))
+                .with_mesh(mesh)
+                .with_material(material)
+                .visible(true)
+                .spawn();
+        }
+
+        // The grid is a line list; debug lines draw it through the scene.
+        let (vertices, _) = renderer::grid_lines_mesh(GROUND_SIZE, GRID_CELLS);
+        self.grid = vertices
+            .iter()
+            .map(|vertex| Vec3::from(vertex.pos) + Vec3::Y * 0.01)
+            .collect();
+
+        let camera = scene.camera_mut();
+        camera.eye = Vec3::new(0.0, 6.0, 12.0);
+        camera.target = Vec3::new(0.0, 1.0, 0.0);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let debug = ctx.scene.debug_draw();
+        for line in self.grid.chunks_exact(2) {
+            debug.draw_line(line[0], line[1], GRID_COLOR);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(PrimitivesApp::default()).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    run_application(PrimitivesApp::default()).unwrap();
+}
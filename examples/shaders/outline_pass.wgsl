@@ -0,0 +1,39 @@
+// Silhouette mask and rim for examples/outline_pass.rs. Only the leading
+// `view_proj` of the renderer's camera uniform is declared.
+struct Camera {
+    view_proj: mat4x4<f32>,
+};
+
+struct Outline {
+    model: mat4x4<f32>,
+    color: vec4<f32>,
+    // Object space distance the rim is pushed out along the normals.
+    width: f32,
+};
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(1) @binding(0) var<uniform> outline: Outline;
+
+struct VsIn {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+fn clip_position(position: vec3<f32>) -> vec4<f32> {
+    return camera.view_proj * outline.model * vec4<f32>(position, 1.0);
+}
+
+@vertex
+fn vs_mark(in: VsIn) -> @builtin(position) vec4<f32> {
+    return clip_position(in.position);
+}
+
+@vertex
+fn vs_rim(in: VsIn) -> @builtin(position) vec4<f32> {
+    return clip_position(in.position + normalize(in.normal) * outline.width);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return outline.color;
+}
@@ -0,0 +1,165 @@
+use glam::{Quat, Vec3};
+use log::info;
+use wgpu_cube::app::{StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::{Material, Texture};
+use wgpu_cube::scene::components::{CanCastShadow, SpotLight};
+use wgpu_cube::scene::{
+    MaterialComponent, MeshComponent, Name, Transform, TransformComponent, Visible,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+const COOKIE_SIZE: u32 = 256;
+const STARBURST_RAYS: f32 = 12.0;
+
+struct ExampleApp;
+
+impl RenderApplication for ExampleApp {
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        setup_cookie_scene(ctx);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let t = ctx.scene.time() as f32 * 0.4;
+        for (_, (light, transform)) in ctx
+            .scene
+            .world
+            .query_mut::<(&SpotLight, &mut TransformComponent)>()
+        {
+            if light.cookie_texture.is_some() {
+                transform.0.rotation = spot_rotation(t);
+            }
+        }
+    }
+}
+
+/// Points the spot straight down, spinning around its axis so the
+/// starburst turns on the floor.
+fn spot_rotation(angle: f32) -> Quat {
+    Quat::from_rotation_y(angle) * Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)
+}
+
+/// A warm starburst: bright rays fading towards the edge, with a soft core.
+fn starburst_pixels(size: u32) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    let half = size as f32 * 0.5;
+    for y in 0..size {
+        for x in 0..size {
+            let dx = (x as f32 + 0.5 - half) / half;
+            let dy = (y as f32 + 0.5 - half) / half;
+            let radius = (dx * dx + dy * dy).sqrt();
+            let angle = dy.atan2(dx);
+
+            let rays = (angle * STARBURST_RAYS).cos().abs().powf(8.0);
+            let core = (1.0 - radius * 3.0).max(0.0);
+            let falloff = (1.0 - radius).clamp(0.0, 1.0);
+            let value = ((rays * falloff + core) * 255.0).min(255.0);
+
+            pixels.extend_from_slice(&[
+                value as u8,
+                (value * 0.85) as u8,
+                (value * 0.55) as u8,
+                255,
+            ]);
+        }
+    }
+    pixels
+}
+
+fn setup_cookie_scene(ctx: &mut StartupContext<'_>) {
+    let renderer = &mut *ctx.renderer;
+    let scene = &mut *ctx.scene;
+
+    info!("Creating spot cookie scene...");
+
+    let (verts, idx) = wgpu_cube::renderer::cube_mesh();
+    let cube_mesh = renderer.create_mesh(&verts, &idx);
+    let cube_handle = scene.assets.meshes.insert(cube_mesh);
+
+    let cookie = Texture::from_rgba(
+        renderer.get_device(),
+        renderer.get_queue(),
+        &starburst_pixels(COOKIE_SIZE),
+        COOKIE_SIZE,
+        COOKIE_SIZE,
+        true,
+        Some("Starburst Cookie"),
+    );
+    let cookie_handle = scene.assets.textures.insert(cookie);
+
+    scene.world.spawn((
+        Name::new("Cookie Floor"),
+        TransformComponent(Transform::from_trs(
+            Vec3::new(0.0, -0.05, 0.0),
+            Quat::IDENTITY,
+            Vec3::new(20.0, 0.1, 20.0),
+        )),
+        MeshComponent(cube_handle),
+        MaterialComponent(Material::new([200, 200, 200, 255]).with_roughness(0.9)),
+        Visible(true),
+    ));
+
+    scene.world.spawn((
+        Name::new("Cookie Cube"),
+        TransformComponent(Transform::from_trs(
+            Vec3::new(1.5, 0.75, 1.0),
+            Quat::from_rotation_y(0.6),
+            Vec3::splat(1.5),
+        )),
+        MeshComponent(cube_handle),
+        MaterialComponent(Material::new([180, 190, 220, 255]).with_roughness(0.4)),
+        Visible(true),
+    ));
+
+    scene.world.spawn((
+        Name::new("Starburst Spot"),
+        TransformComponent(Transform::from_trs(
+            Vec3::new(0.0, 8.0, 0.0),
+            spot_rotation(0.0),
+            Vec3::ONE,
+        )),
+        SpotLight {
+            color: Vec3::new(1.0, 0.95, 0.85),
+            intensity: 250.0,
+            inner_angle: 0.55,
+            outer_angle: 0.7,
+            range: 20.0,
+            cookie_texture: Some(cookie_handle.index() as u32),
+        },
+        CanCastShadow(true),
+    ));
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(0.0, 9.0, 11.0);
+    camera.target = Vec3::ZERO;
+    camera.up = Vec3::Y;
+
+    renderer.update_texture_bind_group(&scene.assets);
+
+    info!("Spot cookie scene created: {} entities", scene.world.len());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(ExampleApp).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    web_sys::console::log_1(&"[Rust] start_app() called".into());
+
+    match run_application(ExampleApp) {
+        Ok(_) => {
+            web_sys::console::log_1(&"[Rust] Application started successfully".into());
+        }
+        Err(e) => {
+            web_sys::console::error_1(&format!("[Rust] Error: {:?}", e).into());
+        }
+    }
+}
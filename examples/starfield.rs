@@ -9,7 +9,8 @@ use wgpu_cube::render_application::{run_application, RenderApplication};
 use wgpu_cube::renderer::Material;
 use wgpu_cube::scene::components::{CanCastShadow, DirectionalLight};
 use wgpu_cube::scene::{
-    Camera, MaterialComponent, MeshComponent, Name, Transform, TransformComponent, Visible,
+    Camera, CameraProjection, MaterialComponent, MeshComponent, Name, Transform,
+    TransformComponent, Visible,
 };
 
 const STAR_COUNT: usize = 100_000;
@@ -82,9 +83,11 @@ impl RenderApplication for StarfieldApp {
             eye: Vec3::ZERO,
             target: Vec3::new(0.0, 0.0, -1.0),
             up: Vec3::Y,
-            near: NEAR_PLANE,
-            far: FAR_PLANE,
-            ..Camera::default()
+            projection: CameraProjection::Perspective {
+                fov: 60f32.to_radians(),
+                near: NEAR_PLANE,
+                far: FAR_PLANE,
+            },
         });
 
         let sun1_direction = Vec3::new(0.3, -1.0, -1.1).normalize();
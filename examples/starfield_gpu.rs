@@ -63,9 +63,11 @@ impl RenderApplication for StarfieldGpuApp {
             eye: Vec3::ZERO,
             target: Vec3::new(0.0, 0.0, -1.0),
             up: Vec3::Y,
-            near: NEAR_PLANE,
-            far: FAR_PLANE,
-            ..Default::default()
+            projection: wgpu_cube::scene::CameraProjection::Perspective {
+                fov: 60f32.to_radians(),
+                near: NEAR_PLANE,
+                far: FAR_PLANE,
+            },
         });
 
         let sun1_direction = Vec3::new(0.3, -1.0, -1.1).normalize();
@@ -0,0 +1,94 @@
+use glam::{Quat, Vec3};
+use log::info;
+use wgpu_cube::app::{AppBuilder, StartupContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::postprocess::ToneMapping;
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::components::{DirectionalLight, Name, TransformComponent};
+use wgpu_cube::scene::{EntityBuilder, OrbitCameraPlugin, Transform};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// An overbright emissive sphere above grey spheres of rising albedo. Switch
+/// operators and exposure in the post-processing window to compare how each
+/// curve handles the highlight.
+struct ToneMappingExample;
+
+impl RenderApplication for ToneMappingExample {
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+        builder.add_plugin(OrbitCameraPlugin::default());
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let renderer = &mut *ctx.renderer;
+        let scene = &mut *ctx.scene;
+
+        let (verts, idx) = wgpu_cube::renderer::sphere_mesh(48, 24);
+        let sphere = renderer.create_mesh(&verts, &idx);
+        let sphere = scene.assets.meshes.insert(sphere);
+
+        EntityBuilder::new(&mut scene.world)
+            .with_name("Sun")
+            .with_transform(Transform::from_trs(
+                Vec3::new(0.0, 1.5, 0.0),
+                Quat::IDENTITY,
+                Vec3::splat(1.2),
+            ))
+            .with_mesh(sphere)
+            .with_material(Material::pbr().with_emissive_color([40.0, 24.0, 12.0]))
+            .visible(true)
+            .spawn();
+
+        for step in 0..5 {
+            let grey = 40 + step * 50;
+            EntityBuilder::new(&mut scene.world)
+                .with_name(format!("Grey {}", step))
+                .with_transform(Transform::from_trs(
+                    Vec3::new(-4.0 + step as f32 * 2.0, -0.5, 2.5),
+                    Quat::IDENTITY,
+                    Vec3::splat(0.8),
+                ))
+                .with_mesh(sphere)
+                .with_material(Material::new([grey as u8, grey as u8, grey as u8, 255]))
+                .visible(true)
+                .spawn();
+        }
+
+        let direction = Vec3::new(-0.4, -1.0, -0.5).normalize();
+        scene.world.spawn((
+            Name::new("Key Light"),
+            TransformComponent(Transform::from_trs(
+                Vec3::ZERO,
+                Quat::from_rotation_arc(Vec3::NEG_Z, direction),
+                Vec3::ONE,
+            )),
+            DirectionalLight::new(Vec3::ONE, 3.0),
+        ));
+
+        let camera = scene.camera_mut();
+        camera.eye = Vec3::new(0.0, 2.0, 9.0);
+        camera.target = Vec3::new(0.0, 0.5, 0.0);
+
+        renderer.set_tonemapping(ToneMapping::Aces, 1.0);
+        info!(
+            "Tone mapping: {}; change it in the post-processing window",
+            renderer.tonemapping().label()
+        );
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(ToneMappingExample).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_app() {
+    run_application(ToneMappingExample).unwrap();
+}
@@ -0,0 +1,104 @@
+use glam::{Quat, Vec3};
+use wgpu_cube::app::{AppBuilder, StartupContext, UpdateContext};
+use wgpu_cube::render_application::{run_application, RenderApplication};
+use wgpu_cube::renderer::texture::DEFAULT_WHITE_TEXTURE_INDEX;
+use wgpu_cube::renderer::Material;
+use wgpu_cube::scene::components::{CanCastShadow, PointLight};
+use wgpu_cube::scene::{
+    EntityBuilder, Name, PulseAnimation, PulseProperty, Transform, TransformComponent,
+};
+
+const TORCH_POSITION: Vec3 = Vec3::new(0.0, 1.5, 0.0);
+
+struct TorchApp;
+
+impl RenderApplication for TorchApp {
+    fn name(&self) -> &str {
+        "Flickering Torch"
+    }
+
+    fn configure(&self, builder: &mut AppBuilder) {
+        builder.disable_default_lighting();
+    }
+
+    fn setup(&mut self, ctx: &mut StartupContext) {
+        let (verts, idx) = wgpu_cube::renderer::cube_mesh();
+        let cube = ctx.renderer.create_mesh(&verts, &idx);
+        let cube = ctx.scene.assets.meshes.insert(cube);
+
+        EntityBuilder::new(&mut ctx.scene.world)
+            .with_name("Floor")
+            .with_transform(Transform::from_trs(
+                Vec3::new(0.0, -0.5, 0.0),
+                Quat::IDENTITY,
+                Vec3::new(10.0, 0.2, 10.0),
+            ))
+            .with_mesh(cube)
+            .with_material(Material::pbr())
+            .visible(true)
+            .spawn();
+
+        // The flame: an emissive block whose glow follows the light's flicker.
+        EntityBuilder::new(&mut ctx.scene.world)
+            .with_name("Flame")
+            .with_transform(Transform::from_trs(
+                TORCH_POSITION,
+                Quat::IDENTITY,
+                Vec3::splat(0.2),
+            ))
+            .with_mesh(cube)
+            .with_material(
+                Material::rgb(255, 140, 40).with_emissive_texture(DEFAULT_WHITE_TEXTURE_INDEX),
+            )
+            .with_pulse_animation(
+                PulseAnimation::new(PulseProperty::EmissiveStrength, 0.7, 0.3, 7.0)
+                    .with_offset(0.4),
+            )
+            .visible(true)
+            .spawn();
+
+        // Two pulses at unrelated frequencies read as an irregular flicker.
+        ctx.scene.world.spawn((
+            Name::new("Torch Light"),
+            TransformComponent(Transform::from_trs(
+                TORCH_POSITION,
+                Quat::IDENTITY,
+                Vec3::ONE,
+            )),
+            PointLight {
+                color: Vec3::new(1.0, 0.6, 0.25),
+                intensity: 60.0,
+                range: 12.0,
+            },
+            CanCastShadow(true),
+            PulseAnimation::new(PulseProperty::LightIntensity, 60.0, 15.0, 7.0).with_offset(0.4),
+        ));
+
+        ctx.scene.world.spawn((
+            Name::new("Exposure Breathing"),
+            PulseAnimation::new(PulseProperty::PostExposure, 1.0, 0.1, 0.2),
+        ));
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let t = ctx.scene.time() as f32 * 0.2;
+        let camera = ctx.scene.camera_mut();
+        camera.eye = Vec3::new(t.cos() * 6.0, 3.0, t.sin() * 6.0);
+        camera.target = Vec3::ZERO;
+        camera.up = Vec3::Y;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    run_application(TorchApp).unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn start_app() {
+    run_application(TorchApp).unwrap();
+}
@@ -4,7 +4,7 @@ use winit::{
     event::*,
     event_loop::ActiveEventLoop,
     keyboard::{Key, NamedKey},
-    window::{Window, WindowId},
+    window::{CursorGrabMode, Fullscreen, Window, WindowId},
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -20,7 +20,7 @@ use crate::renderer::{
         DEFAULT_CHECKER_TEXTURE_INDEX, DEFAULT_METALLIC_ROUGHNESS_TEXTURE_INDEX,
         DEFAULT_NORMAL_TEXTURE_INDEX, DEFAULT_WHITE_TEXTURE_INDEX,
     },
-    CustomRenderContext, RenderBatcher, Renderer, Texture,
+    CustomRenderContext, ManualDrawList, RenderBatcher, Renderer, Texture,
 };
 use crate::settings::RenderSettings;
 
@@ -31,14 +31,23 @@ type WindowHandle = std::sync::Arc<Window>;
 #[cfg(target_arch = "wasm32")]
 type PendingRenderer = Rc<RefCell<Option<Renderer>>>;
 
+#[cfg(feature = "egui")]
+use crate::renderer::postprocess::PostProcessEffects;
 #[cfg(feature = "egui")]
 use crate::ui::{
-    egui, EguiRenderTarget, EguiUiCallback, FrameStatsHandle, FrameStatsHistory,
-    PostProcessEffectsHandle, PostProcessWindow,
+    egui, DebugViewWindow, EguiRenderTarget, EguiUiCallback, FrameStatsHandle, FrameStatsHistory,
+    PostProcessEffectsHandle, PostProcessWindow, RenderModeHandle, SampleCountHandle,
+    ShadowSettingsHandle, ShadowWindow,
 };
 
-use crate::scene::{Children, MeshComponent, Name, Parent, Scene, TransformComponent};
-use crate::time::Instant;
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+use crate::input::Gamepads;
+use crate::input::InputState;
+use crate::scene::{
+    system_first_person_camera, Children, MeshComponent, Name, Parent, Scene, TransformComponent,
+};
+use crate::time::{FixedTimestep, Instant};
+use glam::Vec2;
 
 const DEFAULT_HDR_ENVIRONMENT: &str = "web/assets/hdr/kloppenheim_06_puresky_4k.hdr";
 //const DEFAULT_HDR_ENVIRONMENT: &str = "web/assets/hdr/citrus_orchard_puresky_4k.hdr";
@@ -50,27 +59,167 @@ pub struct StartupContext<'a> {
 
 pub struct UpdateContext<'a> {
     pub scene: &'a mut Scene,
+    pub commands: &'a mut AppCommands,
+    /// Keyboard, mouse and gamepad state for this frame.
+    pub input: &'a InputState,
     pub dt: f64,
 }
 
 pub struct GpuUpdateContext<'a> {
     pub scene: &'a mut Scene,
     pub renderer: &'a mut Renderer,
+    pub commands: &'a mut AppCommands,
+    pub input: &'a InputState,
     pub dt: f64,
 }
 
+impl GpuUpdateContext<'_> {
+    /// The renderer's retained draws, for geometry managed without entities.
+    pub fn manual_draws(&mut self) -> &mut ManualDrawList {
+        self.renderer.manual_draws_mut()
+    }
+}
+
+/// An app or window change requested by a system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppCommand {
+    Exit,
+    SetTitle(String),
+    SetFullscreen(bool),
+    SetCursorGrab(bool),
+}
+
+/// Commands buffered during a frame. Systems run without access to the
+/// window or event loop, so the app applies the queue after they finish.
+/// Commands issued before the window and renderer exist (asynchronous wasm
+/// initialization) stay queued until they do.
+#[derive(Debug, Default)]
+pub struct AppCommands {
+    queue: Vec<AppCommand>,
+}
+
+impl AppCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exit(&mut self) {
+        self.push(AppCommand::Exit);
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.push(AppCommand::SetTitle(title.into()));
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.push(AppCommand::SetFullscreen(fullscreen));
+    }
+
+    /// Locks (or confines, where locking is unsupported) and hides the cursor.
+    pub fn set_cursor_grab(&mut self, grab: bool) {
+        self.push(AppCommand::SetCursorGrab(grab));
+    }
+
+    pub fn push(&mut self, command: AppCommand) {
+        self.queue.push(command);
+    }
+
+    pub fn pending(&self) -> &[AppCommand] {
+        &self.queue
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Removes and returns all queued commands in submission order.
+    pub fn drain(&mut self) -> Vec<AppCommand> {
+        std::mem::take(&mut self.queue)
+    }
+}
+
 pub type StartupSystem = Box<dyn for<'a> FnMut(&mut StartupContext<'a>) + 'static>;
 pub type UpdateSystem = Box<dyn for<'a> FnMut(&mut UpdateContext<'a>) + 'static>;
 pub type GpuUpdateSystem = Box<dyn for<'a> FnMut(&mut GpuUpdateContext<'a>) + 'static>;
+/// Receives window events that were not consumed by egui.
+pub type WindowEventHandler = Box<dyn FnMut(&WindowEvent) + 'static>;
 
 pub trait Plugin {
     fn build(&self, app: &mut AppBuilder);
+
+    /// Where this plugin's systems run relative to others in the same stage:
+    /// lower priorities run first, equal ones in registration order. Systems
+    /// added outside a plugin have priority 0.
+    fn priority(&self) -> i32 {
+        0
+    }
+}
+
+/// Plugins added as one, each at its own [`Plugin::priority`] unless the
+/// group overrides it.
+#[derive(Default)]
+pub struct PluginGroup {
+    plugins: Vec<(Box<dyn Plugin>, Option<i32>)>,
+}
+
+impl PluginGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<P: Plugin + 'static>(mut self, plugin: P) -> Self {
+        self.plugins.push((Box::new(plugin), None));
+        self
+    }
+
+    /// Adds `plugin` with its systems at `priority` instead of its own.
+    pub fn with_priority<P: Plugin + 'static>(mut self, plugin: P, priority: i32) -> Self {
+        self.plugins.push((Box::new(plugin), Some(priority)));
+        self
+    }
+}
+
+impl Plugin for PluginGroup {
+    fn build(&self, app: &mut AppBuilder) {
+        for (plugin, priority) in &self.plugins {
+            let priority = priority.unwrap_or_else(|| plugin.priority());
+            app.build_plugin(plugin.as_ref(), priority);
+        }
+    }
+}
+
+/// A system tagged with the priority it was registered at.
+struct Prioritized<S> {
+    priority: i32,
+    system: S,
+}
+
+/// Stable sort by priority, so equal priorities keep registration order.
+fn by_priority<S>(mut systems: Vec<Prioritized<S>>) -> Vec<S> {
+    systems.sort_by_key(|entry| entry.priority);
+    systems.into_iter().map(|entry| entry.system).collect()
+}
+
+/// Mouse button or key release.
+#[cfg(feature = "egui")]
+fn is_release(event: &WindowEvent) -> bool {
+    match event {
+        WindowEvent::MouseInput { state, .. } => *state == ElementState::Released,
+        WindowEvent::KeyboardInput { event, .. } => event.state == ElementState::Released,
+        _ => false,
+    }
 }
 
 pub struct AppBuilder {
-    startup_systems: Vec<StartupSystem>,
-    update_systems: Vec<UpdateSystem>,
-    gpu_systems: Vec<GpuUpdateSystem>,
+    startup_systems: Vec<Prioritized<StartupSystem>>,
+    update_systems: Vec<Prioritized<UpdateSystem>>,
+    fixed_systems: Vec<Prioritized<UpdateSystem>>,
+    fixed_rate: Option<f64>,
+    gpu_systems: Vec<Prioritized<GpuUpdateSystem>>,
+    /// Priority of the plugin currently building, tagged onto the systems
+    /// it adds.
+    priority: i32,
+    window_event_handlers: Vec<WindowEventHandler>,
     auto_init_default_textures: bool,
     auto_add_default_lighting: bool,
     skip_initial_frames: Option<u32>,
@@ -82,7 +231,11 @@ impl Default for AppBuilder {
         Self {
             startup_systems: Vec::new(),
             update_systems: Vec::new(),
+            fixed_systems: Vec::new(),
+            fixed_rate: None,
             gpu_systems: Vec::new(),
+            priority: 0,
+            window_event_handlers: Vec::new(),
             auto_init_default_textures: true,
             auto_add_default_lighting: true,
             skip_initial_frames: None,
@@ -100,7 +253,8 @@ impl AppBuilder {
     where
         F: for<'a> FnMut(&mut StartupContext<'a>) + 'static,
     {
-        self.startup_systems.push(Box::new(system));
+        self.startup_systems
+            .push(self.prioritized(Box::new(system)));
         self
     }
 
@@ -108,7 +262,26 @@ impl AppBuilder {
     where
         F: for<'a> FnMut(&mut UpdateContext<'a>) + 'static,
     {
-        self.update_systems.push(Box::new(system));
+        self.update_systems.push(self.prioritized(Box::new(system)));
+        self
+    }
+
+    /// Adds a system that runs once per fixed tick with the tick length as
+    /// `dt`. Adding one turns on the fixed timestep.
+    pub fn add_fixed_system<F>(&mut self, system: F) -> &mut Self
+    where
+        F: for<'a> FnMut(&mut UpdateContext<'a>) + 'static,
+    {
+        self.fixed_systems.push(self.prioritized(Box::new(system)));
+        self
+    }
+
+    /// Runs scene animations and fixed systems at `rate_hz` ticks per second
+    /// instead of once per frame, blending transforms between ticks for
+    /// rendering. Without this, fixed systems tick at
+    /// [`FixedTimestep::DEFAULT_RATE`].
+    pub fn set_fixed_timestep(&mut self, rate_hz: f64) -> &mut Self {
+        self.fixed_rate = Some(rate_hz);
         self
     }
 
@@ -116,20 +289,58 @@ impl AppBuilder {
     where
         F: for<'a> FnMut(&mut GpuUpdateContext<'a>) + 'static,
     {
-        self.gpu_systems.push(Box::new(system));
+        self.gpu_systems.push(self.prioritized(Box::new(system)));
+        self
+    }
+
+    /// Registers a handler for window events egui does not consume. Button
+    /// and key releases always reach it, so nothing it tracks stays held.
+    pub fn add_window_event_handler<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&WindowEvent) + 'static,
+    {
+        self.window_event_handlers.push(Box::new(handler));
         self
     }
 
     pub fn add_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
-        plugin.build(self);
+        let priority = plugin.priority();
+        self.build_plugin(&plugin, priority);
         self
     }
 
+    fn build_plugin(&mut self, plugin: &dyn Plugin, priority: i32) {
+        let outer = std::mem::replace(&mut self.priority, priority);
+        plugin.build(self);
+        self.priority = outer;
+    }
+
+    fn prioritized<S>(&self, system: S) -> Prioritized<S> {
+        Prioritized {
+            priority: self.priority,
+            system,
+        }
+    }
+
     pub fn set_settings(&mut self, settings: RenderSettings) -> &mut Self {
         self.settings = settings;
         self
     }
 
+    /// Requests an MSAA sample count (1, 2, 4 or 8). The renderer may lower it
+    /// if the adapter does not support the value for the surface format.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> &mut Self {
+        self.settings.sample_count = RenderSettings::sanitize_sample_count(sample_count);
+        self
+    }
+
+    /// Renders the scene at `scale` times the window size; see
+    /// [`RenderSettings::resolution_scale`].
+    pub fn set_resolution_scale(&mut self, scale: f32) -> &mut Self {
+        self.settings.resolution_scale = scale;
+        self
+    }
+
     pub fn disable_default_textures(&mut self) -> &mut Self {
         self.auto_init_default_textures = false;
         self
@@ -140,18 +351,42 @@ impl AppBuilder {
         self
     }
 
+    /// Leaves the first `frames` frames unrendered. Pipeline compilation no
+    /// longer needs hiding this way; call [`Renderer::warm_up`] from a
+    /// startup system to compile every variant up front instead.
     pub fn skip_initial_frames(&mut self, frames: u32) -> &mut Self {
         self.skip_initial_frames = Some(frames);
         self
     }
 
     pub fn build(self) -> App {
+        #[cfg(feature = "egui")]
+        let shadow_settings = ShadowWindow::handle(self.settings.shadows.validate());
+        #[cfg(feature = "egui")]
+        let sample_count = PostProcessWindow::sample_count_handle(self.settings.sample_count);
+        #[cfg(feature = "egui")]
+        let postprocess_effects = PostProcessWindow::handle(PostProcessEffects {
+            ssao_quality: self.settings.ssao_quality,
+            ..PostProcessEffects::default()
+        });
+
+        let fixed_timestep = (self.fixed_rate.is_some() || !self.fixed_systems.is_empty())
+            .then(|| FixedTimestep::new(self.fixed_rate.unwrap_or(FixedTimestep::DEFAULT_RATE)));
+
+        #[allow(unused_mut)]
+        let mut input = InputState::new();
+        #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+        let gamepads = Gamepads::new(&mut input);
+
         App {
             scene: Scene::new(),
             batcher: RenderBatcher::new(),
-            startup_systems: self.startup_systems,
-            update_systems: self.update_systems,
-            gpu_systems: self.gpu_systems,
+            startup_systems: by_priority(self.startup_systems),
+            update_systems: by_priority(self.update_systems),
+            fixed_systems: by_priority(self.fixed_systems),
+            fixed_timestep,
+            gpu_systems: by_priority(self.gpu_systems),
+            window_event_handlers: self.window_event_handlers,
             auto_init_default_textures: self.auto_init_default_textures,
             auto_add_default_lighting: self.auto_add_default_lighting,
             startup_ran: false,
@@ -167,10 +402,20 @@ impl AppBuilder {
             #[cfg(feature = "egui")]
             frame_stats: FrameStatsHistory::handle(),
             #[cfg(feature = "egui")]
-            postprocess_effects: PostProcessWindow::handle(),
+            postprocess_effects,
+            #[cfg(feature = "egui")]
+            shadow_settings,
+            #[cfg(feature = "egui")]
+            sample_count,
+            #[cfg(feature = "egui")]
+            render_mode: DebugViewWindow::handle(),
             window: None,
             window_id: None,
             renderer: None,
+            commands: AppCommands::new(),
+            input,
+            #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+            gamepads,
             custom_render_callback: None,
         }
     }
@@ -178,6 +423,8 @@ impl AppBuilder {
 
 struct FrameStep {
     dt: f64,
+    /// Fixed ticks due this frame; always 0 without a fixed timestep.
+    fixed_ticks: u32,
     skip_rendering: bool,
 }
 
@@ -186,6 +433,10 @@ impl FrameStep {
         self.dt
     }
 
+    fn fixed_ticks(&self) -> u32 {
+        self.fixed_ticks
+    }
+
     fn should_render(&self) -> bool {
         !self.skip_rendering
     }
@@ -197,7 +448,10 @@ pub struct App {
     batcher: RenderBatcher,
     startup_systems: Vec<StartupSystem>,
     update_systems: Vec<UpdateSystem>,
+    fixed_systems: Vec<UpdateSystem>,
+    fixed_timestep: Option<FixedTimestep>,
     gpu_systems: Vec<GpuUpdateSystem>,
+    window_event_handlers: Vec<WindowEventHandler>,
     auto_init_default_textures: bool,
     auto_add_default_lighting: bool,
     startup_ran: bool,
@@ -214,8 +468,18 @@ pub struct App {
     frame_stats: FrameStatsHandle,
     #[cfg(feature = "egui")]
     postprocess_effects: PostProcessEffectsHandle,
+    #[cfg(feature = "egui")]
+    shadow_settings: ShadowSettingsHandle,
+    #[cfg(feature = "egui")]
+    sample_count: SampleCountHandle,
+    #[cfg(feature = "egui")]
+    render_mode: RenderModeHandle,
     scene: Scene,
     renderer: Option<Renderer>,
+    commands: AppCommands,
+    input: InputState,
+    #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+    gamepads: Option<Gamepads>,
     custom_render_callback: Option<Box<dyn FnMut(&mut CustomRenderContext)>>,
 }
 
@@ -269,6 +533,48 @@ impl App {
         }
     }
 
+    #[cfg(feature = "egui")]
+    pub fn shadow_settings_handle(&self) -> ShadowSettingsHandle {
+        self.shadow_settings.clone()
+    }
+
+    #[cfg(feature = "egui")]
+    fn apply_shadow_settings(handle: &ShadowSettingsHandle, renderer: &mut Renderer) {
+        if let Ok(settings) = handle.lock() {
+            renderer.set_shadow_settings(*settings);
+        }
+    }
+
+    #[cfg(feature = "egui")]
+    pub fn sample_count_handle(&self) -> SampleCountHandle {
+        self.sample_count.clone()
+    }
+
+    /// The egui renderer draws straight into the single-sampled surface, so
+    /// only the scene targets and pipelines depend on the MSAA sample count.
+    #[cfg(feature = "egui")]
+    fn apply_sample_count(handle: &SampleCountHandle, renderer: &mut Renderer) {
+        if let Ok(mut requested) = handle.lock() {
+            if *requested != renderer.sample_count() {
+                *requested = renderer.set_sample_count(*requested);
+            }
+        }
+    }
+
+    #[cfg(feature = "egui")]
+    pub fn render_mode_handle(&self) -> RenderModeHandle {
+        self.render_mode.clone()
+    }
+
+    #[cfg(feature = "egui")]
+    fn apply_render_mode(handle: &RenderModeHandle, renderer: &mut Renderer) {
+        if let Ok(mut requested) = handle.lock() {
+            if *requested != renderer.render_mode() {
+                *requested = renderer.set_render_mode(*requested);
+            }
+        }
+    }
+
     fn begin_frame(&mut self) -> FrameStep {
         self.frame_counter += 1;
 
@@ -286,8 +592,16 @@ impl App {
         let now = Instant::now();
         let dt = (now - self.scene.last_frame()).as_secs_f64();
         self.scene.set_last_frame(now);
-
-        FrameStep { dt, skip_rendering }
+        let fixed_ticks = self
+            .fixed_timestep
+            .as_mut()
+            .map_or(0, |fixed| fixed.advance(dt));
+
+        FrameStep {
+            dt,
+            fixed_ticks,
+            skip_rendering,
+        }
     }
 
     fn init_default_textures(&mut self, renderer: &mut Renderer) {
@@ -367,7 +681,8 @@ impl App {
                         renderer.surface_format(),
                         renderer.sample_count(),
                         window.as_ref(),
-                    );
+                    )
+                    .with_ui_scale(renderer.settings().ui_scale);
                     self.install_egui_context(egui);
                     log::info!("Egui context initialized (async)");
                 }
@@ -379,6 +694,12 @@ impl App {
 
             #[cfg(feature = "egui")]
             Self::apply_postprocess_effects(&self.postprocess_effects, &mut renderer);
+            #[cfg(feature = "egui")]
+            Self::apply_shadow_settings(&self.shadow_settings, &mut renderer);
+            #[cfg(feature = "egui")]
+            Self::apply_sample_count(&self.sample_count, &mut renderer);
+            #[cfg(feature = "egui")]
+            Self::apply_render_mode(&self.render_mode, &mut renderer);
 
             self.renderer = Some(renderer);
             self.pending_renderer = None;
@@ -496,34 +817,114 @@ impl App {
         }
     }
 
-    fn run_update_stage(&mut self, dt: f64) {
-        self.scene.update(dt);
+    /// Raw mouse movement, delivered as a device event so it keeps coming
+    /// while the cursor is grabbed.
+    fn on_mouse_motion(&mut self, delta: Vec2) {
+        self.input.on_mouse_motion(delta);
+    }
+
+    /// Runs `fixed_ticks` fixed ticks (when the app has a fixed timestep)
+    /// and then the per-frame systems with the real `dt`.
+    fn run_update_stage(&mut self, dt: f64, fixed_ticks: u32) {
+        #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+        if let Some(gamepads) = &mut self.gamepads {
+            gamepads.poll(&mut self.input);
+        }
+        system_first_person_camera(&mut self.scene, &self.input, dt);
+
+        match &self.fixed_timestep {
+            Some(fixed) => {
+                let step = fixed.step();
+                for _ in 0..fixed_ticks {
+                    let snapshot = self.scene.begin_fixed_tick();
+                    self.scene.fixed_update(step);
+                    for system in &mut self.fixed_systems {
+                        let mut ctx = UpdateContext {
+                            scene: &mut self.scene,
+                            commands: &mut self.commands,
+                            input: &self.input,
+                            dt: step,
+                        };
+                        (system)(&mut ctx);
+                    }
+                    self.scene.end_fixed_tick(snapshot);
+                }
+            }
+            None => self.scene.update(dt),
+        }
 
         for system in &mut self.update_systems {
             let mut ctx = UpdateContext {
                 scene: &mut self.scene,
+                commands: &mut self.commands,
+                input: &self.input,
                 dt,
             };
             (system)(&mut ctx);
         }
+        if let Some(fixed) = &self.fixed_timestep {
+            self.scene.interpolate(fixed.alpha());
+        }
     }
 
     fn run_gpu_systems(
         scene: &mut Scene,
         systems: &mut [GpuUpdateSystem],
         renderer: &mut Renderer,
+        commands: &mut AppCommands,
+        input: &InputState,
         dt: f64,
     ) {
+        scene.apply_renderer_commands(renderer);
+
         for system in systems {
             let mut ctx = GpuUpdateContext {
                 scene,
                 renderer,
+                commands,
+                input,
                 dt,
             };
             (system)(&mut ctx);
         }
     }
 
+    /// Applies queued [`AppCommand`]s once the window and renderer exist.
+    /// Returns `false` when a command asked the app to exit.
+    fn apply_app_commands(&mut self, event_loop: &ActiveEventLoop) -> bool {
+        let Some(window) = self.window.as_ref().filter(|_| self.renderer.is_some()) else {
+            return true;
+        };
+
+        for command in self.commands.drain() {
+            match command {
+                AppCommand::Exit => {
+                    log::info!("Exit requested by a system");
+                    event_loop.exit();
+                    return false;
+                }
+                AppCommand::SetTitle(title) => window.set_title(&title),
+                AppCommand::SetFullscreen(fullscreen) => {
+                    window.set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)));
+                }
+                AppCommand::SetCursorGrab(grab) => {
+                    let result = if grab {
+                        window
+                            .set_cursor_grab(CursorGrabMode::Locked)
+                            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+                    } else {
+                        window.set_cursor_grab(CursorGrabMode::None)
+                    };
+                    match result {
+                        Ok(()) => window.set_cursor_visible(!grab),
+                        Err(err) => log::warn!("Failed to change cursor grab: {}", err),
+                    }
+                }
+            }
+        }
+        true
+    }
+
     fn handle_surface_error(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -568,6 +969,12 @@ impl App {
 
         #[cfg(feature = "egui")]
         Self::apply_postprocess_effects(&self.postprocess_effects, renderer);
+        #[cfg(feature = "egui")]
+        Self::apply_shadow_settings(&self.shadow_settings, renderer);
+        #[cfg(feature = "egui")]
+        Self::apply_sample_count(&self.sample_count, renderer);
+        #[cfg(feature = "egui")]
+        Self::apply_render_mode(&self.render_mode, renderer);
 
         #[cfg(feature = "egui")]
         let egui_output = {
@@ -585,8 +992,7 @@ impl App {
         // Call custom render callback
         if let Some(callback) = &mut self.custom_render_callback {
             let view = render_frame
-                .frame
-                .texture
+                .texture()
                 .create_view(&wgpu::TextureViewDescriptor::default());
             let mut encoder =
                 renderer
@@ -610,8 +1016,7 @@ impl App {
                 (&mut self.egui_context, &self.window, egui_output)
             {
                 let view = render_frame
-                    .frame
-                    .texture
+                    .texture()
                     .create_view(&wgpu::TextureViewDescriptor::default());
 
                 let mut encoder =
@@ -627,7 +1032,7 @@ impl App {
                     queue: renderer.get_queue(),
                     encoder: &mut encoder,
                     window: window.as_ref(),
-                    view: &view,
+                    surface_view: &view,
                     surface_size: [surface_size.width, surface_size.height],
                 };
                 egui.render(&mut target, egui_output);
@@ -636,7 +1041,7 @@ impl App {
             }
         }
 
-        render_frame.frame.present();
+        render_frame.present();
 
         #[cfg(feature = "egui")]
         if let Ok(mut history) = self.frame_stats.lock() {
@@ -697,7 +1102,8 @@ impl ApplicationHandler for App {
                         renderer.surface_format(),
                         renderer.sample_count(),
                         window.as_ref(),
-                    );
+                    )
+                    .with_ui_scale(renderer.settings().ui_scale);
                     self.install_egui_context(egui);
                     log::info!("Egui context initialized");
                 }
@@ -713,6 +1119,12 @@ impl ApplicationHandler for App {
 
                 #[cfg(feature = "egui")]
                 Self::apply_postprocess_effects(&self.postprocess_effects, &mut renderer);
+                #[cfg(feature = "egui")]
+                Self::apply_shadow_settings(&self.shadow_settings, &mut renderer);
+                #[cfg(feature = "egui")]
+                Self::apply_sample_count(&self.sample_count, &mut renderer);
+                #[cfg(feature = "egui")]
+                Self::apply_render_mode(&self.render_mode, &mut renderer);
 
                 self.window = Some(window);
                 self.window_id = Some(id);
@@ -764,16 +1176,28 @@ impl ApplicationHandler for App {
         #[cfg(target_arch = "wasm32")]
         self.try_finish_async_initialization();
 
+        // Track keys even when egui takes the event so releases are never lost.
+        self.input.handle_window_event(&event);
+
         // Let egui handle the event first
         #[cfg(feature = "egui")]
         {
             if let (Some(egui), Some(window)) = (&mut self.egui_context, &self.window) {
                 if egui.handle_event(window.as_ref(), &event) {
+                    if is_release(&event) {
+                        for handler in &mut self.window_event_handlers {
+                            handler(&event);
+                        }
+                    }
                     return; // Event was consumed by egui
                 }
             }
         }
 
+        for handler in &mut self.window_event_handlers {
+            handler(&event);
+        }
+
         match event {
             WindowEvent::CloseRequested | WindowEvent::Destroyed => {
                 log::info!("Closing application");
@@ -783,6 +1207,10 @@ impl ApplicationHandler for App {
             WindowEvent::Resized(new_size) => {
                 if let Some(renderer) = self.renderer.as_mut() {
                     renderer.resize(new_size);
+                    #[cfg(feature = "egui")]
+                    if let Some(egui) = &mut self.egui_context {
+                        egui.resize(renderer.get_device(), [new_size.width, new_size.height]);
+                    }
                 }
             }
 
@@ -801,15 +1229,23 @@ impl ApplicationHandler for App {
                 let frame = self.begin_frame();
 
                 // --------- 1) Update scene logic first ----------
-                self.run_update_stage(frame.dt());
+                self.run_update_stage(frame.dt(), frame.fixed_ticks());
 
-                if let Some(mut renderer) = self.renderer.take() {
+                let mut renderer = self.renderer.take();
+                if let Some(renderer) = renderer.as_mut() {
+                    renderer.reload_changed_shaders();
                     Self::run_gpu_systems(
                         &mut self.scene,
                         &mut self.gpu_systems,
-                        &mut renderer,
+                        renderer,
+                        &mut self.commands,
+                        &self.input,
                         frame.dt(),
                     );
+                }
+                self.input.end_frame();
+
+                if let Some(mut renderer) = renderer {
                     let should_continue = match self.render_scene(&mut renderer, &frame) {
                         Ok(()) => true,
                         Err(err) => self.handle_surface_error(event_loop, &mut renderer, err),
@@ -820,6 +1256,10 @@ impl ApplicationHandler for App {
                     }
                 }
 
+                if !self.apply_app_commands(event_loop) {
+                    return;
+                }
+
                 if let Some(window) = &self.window {
                     window.request_redraw();
                 }
@@ -846,4 +1286,162 @@ impl ApplicationHandler for App {
             _ => {}
         }
     }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        if let DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            self.on_mouse_motion(Vec2::new(dx as f32, dy as f32));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_commands_drain_in_submission_order() {
+        let mut commands = AppCommands::new();
+        commands.set_title("Demo");
+        commands.set_fullscreen(true);
+        commands.set_cursor_grab(true);
+        commands.exit();
+
+        assert_eq!(
+            commands.pending(),
+            &[
+                AppCommand::SetTitle("Demo".to_string()),
+                AppCommand::SetFullscreen(true),
+                AppCommand::SetCursorGrab(true),
+                AppCommand::Exit,
+            ]
+        );
+
+        let drained = commands.drain();
+        assert_eq!(drained.len(), 4);
+        assert_eq!(drained[0], AppCommand::SetTitle("Demo".to_string()));
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn system_commands_stay_queued_without_a_window() {
+        let mut builder = AppBuilder::new();
+        builder.add_system(|ctx| ctx.commands.set_title("Queued"));
+        let mut app = builder.build();
+
+        app.run_update_stage(0.0, 0);
+        app.run_update_stage(0.0, 0);
+
+        assert!(app.window.is_none());
+        assert_eq!(
+            app.commands.pending(),
+            &[
+                AppCommand::SetTitle("Queued".to_string()),
+                AppCommand::SetTitle("Queued".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn systems_see_input_edges_of_the_frame() {
+        use crate::input::{GamepadButton, GamepadEvent};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let pressed = Rc::new(RefCell::new(Vec::new()));
+        let mut builder = AppBuilder::new();
+        let recorded = pressed.clone();
+        builder.add_system(move |ctx| {
+            recorded
+                .borrow_mut()
+                .push(ctx.input.is_button_pressed(GamepadButton::South))
+        });
+        let mut app = builder.build();
+
+        app.input
+            .handle_gamepad_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        app.run_update_stage(0.0, 0);
+        app.input.end_frame();
+        app.run_update_stage(0.0, 0);
+
+        assert_eq!(*pressed.borrow(), vec![true, false]);
+        assert!(app.input.is_button_held(GamepadButton::South));
+    }
+
+    #[test]
+    fn fixed_systems_run_once_per_tick_with_the_tick_length() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let fixed_dts = Rc::new(RefCell::new(Vec::new()));
+        let frames = Rc::new(RefCell::new(0));
+        let mut builder = AppBuilder::new();
+        builder.set_fixed_timestep(50.0);
+        let recorded = fixed_dts.clone();
+        builder.add_fixed_system(move |ctx| recorded.borrow_mut().push(ctx.dt));
+        let counted = frames.clone();
+        builder.add_system(move |_| *counted.borrow_mut() += 1);
+        let mut app = builder.build();
+
+        app.run_update_stage(0.05, 2);
+        app.run_update_stage(0.01, 0);
+
+        assert_eq!(*fixed_dts.borrow(), vec![0.02, 0.02]);
+        assert_eq!(*frames.borrow(), 2);
+        assert!((app.scene.time() - 0.04).abs() < 1e-9);
+    }
+
+    #[test]
+    fn plugin_systems_run_in_priority_order() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        type Log = Rc<RefCell<Vec<&'static str>>>;
+
+        struct Recorder {
+            name: &'static str,
+            priority: i32,
+            log: Log,
+        }
+
+        impl Plugin for Recorder {
+            fn build(&self, app: &mut AppBuilder) {
+                let (name, log) = (self.name, self.log.clone());
+                app.add_system(move |_| log.borrow_mut().push(name));
+            }
+
+            fn priority(&self) -> i32 {
+                self.priority
+            }
+        }
+
+        let log = Log::default();
+        let recorder = |name, priority| Recorder {
+            name,
+            priority,
+            log: log.clone(),
+        };
+        let mut builder = AppBuilder::new();
+        builder.add_plugin(recorder("lighting", 10));
+        builder.add_plugin(recorder("mesh", -10));
+        let unplugged = log.clone();
+        builder.add_system(move |_| unplugged.borrow_mut().push("app"));
+        builder.add_plugin(
+            PluginGroup::new()
+                .with(recorder("grouped", 0))
+                .with_priority(recorder("overridden", 10), -20),
+        );
+        let mut app = builder.build();
+
+        app.run_update_stage(0.0, 0);
+
+        assert_eq!(
+            *log.borrow(),
+            vec!["overridden", "mesh", "app", "grouped", "lighting"]
+        );
+    }
 }
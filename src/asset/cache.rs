@@ -1,34 +1,167 @@
+use std::collections::HashMap;
+
 use super::Handle;
 
+struct Slot<T> {
+    generation: u32,
+    item: Option<T>,
+}
+
+/// Slot storage for assets. Removed slots are recycled with a new
+/// generation, so handles to removed assets stop resolving.
 pub struct AssetCache<T> {
-    items: Vec<T>,
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+    revision: u64,
+    keys: HashMap<String, Handle<T>>,
+    key_hits: usize,
+    key_misses: usize,
 }
 
 impl<T> AssetCache<T> {
     pub fn new() -> Self {
-        Self { items: Vec::new() }
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            revision: 0,
+            keys: HashMap::new(),
+            key_hits: 0,
+            key_misses: 0,
+        }
     }
 
     pub fn insert(&mut self, item: T) -> Handle<T> {
-        let index = self.items.len();
-        self.items.push(item);
+        self.len += 1;
+        self.revision += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index];
+            slot.generation += 1;
+            slot.item = Some(item);
+            return Handle::with_generation(index, slot.generation);
+        }
+
+        let index = self.slots.len();
+        self.slots.push(Slot {
+            generation: 0,
+            item: Some(item),
+        });
         Handle::new(index)
     }
 
+    /// Returns the asset stored under `key`, creating it with `create` the
+    /// first time the key is seen.
+    pub fn get_or_insert_with(&mut self, key: &str, create: impl FnOnce() -> T) -> Handle<T> {
+        if let Some(&handle) = self.keys.get(key) {
+            self.key_hits += 1;
+            return handle;
+        }
+        self.key_misses += 1;
+        let handle = self.insert(create());
+        self.keys.insert(key.to_owned(), handle);
+        handle
+    }
+
+    pub fn get_by_key(&self, key: &str) -> Option<Handle<T>> {
+        self.keys.get(key).copied()
+    }
+
+    /// The key `handle` was inserted under by [`Self::get_or_insert_with`].
+    pub fn key_of(&self, handle: Handle<T>) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|(_, &keyed)| keyed == handle)
+            .map(|(key, _)| key.as_str())
+    }
+
+    /// Keyed lookups that reused an existing asset and that created a new one.
+    pub fn key_stats(&self) -> (usize, usize) {
+        (self.key_hits, self.key_misses)
+    }
+
+    /// Removes the asset, returning it so GPU resources drop with it.
+    /// Returns `None` for handles that were already removed.
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index())?;
+        if slot.generation != handle.generation() {
+            return None;
+        }
+        let item = slot.item.take()?;
+        self.free.push(handle.index());
+        self.keys.retain(|_, keyed| *keyed != handle);
+        self.len -= 1;
+        self.revision += 1;
+        Some(item)
+    }
+
+    /// Removes every asset for which `keep` returns `false`.
+    pub fn retain(&mut self, mut keep: impl FnMut(Handle<T>, &T) -> bool) {
+        let removed: Vec<Handle<T>> = self
+            .iter()
+            .filter(|&(handle, item)| !keep(handle, item))
+            .map(|(handle, _)| handle)
+            .collect();
+        for handle in removed {
+            self.remove(handle);
+        }
+    }
+
     pub fn get(&self, handle: Handle<T>) -> Option<&T> {
-        self.items.get(handle.index())
+        self.slots
+            .get(handle.index())
+            .filter(|slot| slot.generation == handle.generation())
+            .and_then(|slot| slot.item.as_ref())
     }
 
     pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
-        self.items.get_mut(handle.index())
+        self.slots
+            .get_mut(handle.index())
+            .filter(|slot| slot.generation == handle.generation())
+            .and_then(|slot| slot.item.as_mut())
+    }
+
+    /// Live handle for slot `index`. Materials and lights refer to textures
+    /// by bare slot index; this resolves them regardless of generation.
+    pub fn handle_at(&self, index: usize) -> Option<Handle<T>> {
+        let slot = self.slots.get(index)?;
+        slot.item
+            .as_ref()
+            .map(|_| Handle::with_generation(index, slot.generation))
+    }
+
+    /// Asset in slot `index`, or `None` when the slot is empty.
+    pub fn get_by_index(&self, index: usize) -> Option<&T> {
+        self.slots.get(index).and_then(|slot| slot.item.as_ref())
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.item
+                .as_ref()
+                .map(|item| (Handle::with_generation(index, slot.generation), item))
+        })
+    }
+
+    /// Number of live assets.
     pub fn len(&self) -> usize {
-        self.items.len()
+        self.len
     }
 
     pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+        self.len == 0
+    }
+
+    /// Number of slots, live or free. Every handle the cache returned has
+    /// an index below this.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Changes with every insert and removal, so consumers that mirror the
+    /// cache on the GPU can tell when to rebuild.
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 }
 
@@ -37,3 +170,63 @@ impl<T> Default for AssetCache<T> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyed_inserts_reuse_existing_handles() {
+        let mut cache: AssetCache<String> = AssetCache::new();
+        let first = cache.get_or_insert_with("a", || "first".to_owned());
+        let again = cache.get_or_insert_with("a", || unreachable!());
+        let other = cache.get_or_insert_with("b", || "second".to_owned());
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_by_key("b"), Some(other));
+        assert_eq!(cache.key_of(other), Some("b"));
+        assert_eq!(cache.key_stats(), (1, 2));
+    }
+
+    #[test]
+    fn removed_handles_do_not_alias_recycled_slots() {
+        let mut cache: AssetCache<&str> = AssetCache::new();
+        let old = cache.get_or_insert_with("old", || "old");
+        let kept = cache.insert("kept");
+
+        assert_eq!(cache.remove(old), Some("old"));
+        assert_eq!(cache.remove(old), None);
+        assert_eq!(cache.get(old), None);
+        assert_eq!(cache.get_by_key("old"), None);
+        assert_eq!(cache.key_of(old), None);
+        assert_eq!(cache.key_of(kept), None);
+        assert_eq!(cache.len(), 1);
+
+        let new = cache.insert("new");
+        assert_eq!(new.index(), old.index());
+        assert_ne!(new, old);
+        assert_eq!(cache.get(old), None);
+        assert_eq!(cache.get(new), Some(&"new"));
+        assert_eq!(cache.get_by_index(old.index()), Some(&"new"));
+        assert_eq!(cache.handle_at(old.index()), Some(new));
+        assert_eq!(cache.get(kept), Some(&"kept"));
+    }
+
+    #[test]
+    fn retain_removes_rejected_assets_and_bumps_revision() {
+        let mut cache: AssetCache<u32> = AssetCache::new();
+        for value in 0..6 {
+            cache.insert(value);
+        }
+        let revision = cache.revision();
+
+        cache.retain(|_, value| value % 2 == 0);
+
+        assert_eq!(cache.len(), 3);
+        assert!(cache.revision() > revision);
+        let values: Vec<u32> = cache.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, vec![0, 2, 4]);
+    }
+}
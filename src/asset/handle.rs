@@ -1,8 +1,12 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 
-#[derive(Debug, PartialEq, Eq, Hash)] // Only derive these
 pub struct Handle<T> {
     index: usize,
+    /// Bumped each time the slot is reused, so handles to removed assets
+    /// never resolve to whatever took their place.
+    generation: u32,
     _marker: PhantomData<*const T>,
 }
 
@@ -16,14 +20,45 @@ impl<T> Clone for Handle<T> {
 // Manually implement Copy without requiring T: Copy
 impl<T> Copy for Handle<T> {}
 
+// Likewise for comparison, hashing and formatting, which derive would bound
+// on T even though only the index and generation take part.
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
 // Manually implement Send and Sync since we're using raw pointers
 unsafe impl<T> Send for Handle<T> {}
 unsafe impl<T> Sync for Handle<T> {}
 
 impl<T> Handle<T> {
     pub fn new(index: usize) -> Self {
+        Self::with_generation(index, 0)
+    }
+
+    pub fn with_generation(index: usize, generation: u32) -> Self {
         Self {
             index,
+            generation,
             _marker: PhantomData,
         }
     }
@@ -31,6 +66,10 @@ impl<T> Handle<T> {
     pub fn index(&self) -> usize {
         self.index
     }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
 }
 
 #[cfg(test)]
@@ -1,11 +1,95 @@
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use glam::Vec3;
 use wgpu::util::DeviceExt;
 
+use crate::scene::components::BoundingBox;
+
+/// Where a mesh's morph target deltas live in the renderer's shared delta
+/// buffer. Deltas are stored target by target, `vertex_count` per target.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub struct MorphTargetRange {
+    pub delta_offset: u32,
+    pub target_count: u32,
+    pub vertex_count: u32,
+}
+
+/// CPU copy of a mesh's triangles in its bind pose, kept for raycasts.
+#[derive(Debug, Default)]
+pub struct MeshGeometry {
+    positions: Vec<Vec3>,
+    indices: Vec<u32>,
+    bounds: Option<BoundingBox>,
+}
+
+impl MeshGeometry {
+    pub fn new(positions: Vec<Vec3>, indices: Vec<u32>) -> Self {
+        let bounds = BoundingBox::from_points(positions.iter().copied());
+        Self {
+            positions,
+            indices,
+            bounds,
+        }
+    }
+
+    /// Local-space bounds of the vertices, `None` for an empty mesh.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        self.bounds
+    }
+
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Triangle corners; triangles with out-of-range indices are skipped.
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.indices.chunks_exact(3).filter_map(|tri| {
+            Some([
+                *self.positions.get(tri[0] as usize)?,
+                *self.positions.get(tri[1] as usize)?,
+                *self.positions.get(tri[2] as usize)?,
+            ])
+        })
+    }
+}
+
+impl PartialEq for MeshGeometry {
+    fn eq(&self, other: &Self) -> bool {
+        self.indices == other.indices
+            && self.positions.len() == other.positions.len()
+            && self
+                .positions
+                .iter()
+                .zip(&other.positions)
+                .all(|(a, b)| a.to_array().map(f32::to_bits) == b.to_array().map(f32::to_bits))
+    }
+}
+
+impl Eq for MeshGeometry {}
+
+impl Hash for MeshGeometry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.indices.hash(state);
+        for position in &self.positions {
+            position.to_array().map(f32::to_bits).hash(state);
+        }
+    }
+}
+
 #[derive(Clone, Hash, Eq, PartialEq, std::fmt::Debug)]
 pub struct Mesh {
     vertex_buffer: wgpu::Buffer,
+    skin_buffer: Option<wgpu::Buffer>,
+    morph_targets: Option<MorphTargetRange>,
     index_buffer: wgpu::Buffer,
     index_count: u32,
     index_format: wgpu::IndexFormat,
+    geometry: Arc<MeshGeometry>,
 }
 
 impl Mesh {
@@ -45,16 +129,61 @@ impl Mesh {
 
         Self {
             vertex_buffer,
+            skin_buffer: None,
+            morph_targets: None,
             index_buffer,
             index_count: indices.len() as u32,
             index_format,
+            geometry: Arc::new(MeshGeometry::new(
+                vertices.iter().map(|v| Vec3::from(v.pos)).collect(),
+                indices.to_vec(),
+            )),
         }
     }
 
+    /// Creates a mesh with a second vertex buffer holding joint indices and
+    /// weights. `skin` must have one entry per vertex.
+    pub fn from_skinned_vertices(
+        device: &wgpu::Device,
+        vertices: &[crate::renderer::Vertex],
+        skin: &[crate::renderer::SkinVertex],
+        indices: &[u32],
+    ) -> Self {
+        debug_assert_eq!(vertices.len(), skin.len());
+        let mut mesh = Self::from_vertices(device, vertices, indices);
+        mesh.skin_buffer = Some(
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("SkinVertexBuffer"),
+                contents: bytemuck::cast_slice(skin),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+        );
+        mesh
+    }
+
+    /// Marks the mesh as morphable. The deltas must already be uploaded, see
+    /// [`Renderer::create_morphed_mesh`](crate::renderer::Renderer::create_morphed_mesh).
+    pub fn with_morph_targets(mut self, morph_targets: MorphTargetRange) -> Self {
+        self.morph_targets = Some(morph_targets);
+        self
+    }
+
+    pub fn morph_targets(&self) -> Option<MorphTargetRange> {
+        self.morph_targets
+    }
+
     pub fn vertex_buffer(&self) -> &wgpu::Buffer {
         &self.vertex_buffer
     }
 
+    pub fn skin_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.skin_buffer.as_ref()
+    }
+
+    pub fn is_skinned(&self) -> bool {
+        self.skin_buffer.is_some()
+    }
+
     pub fn index_buffer(&self) -> &wgpu::Buffer {
         &self.index_buffer
     }
@@ -66,4 +195,9 @@ impl Mesh {
     pub fn index_format(&self) -> wgpu::IndexFormat {
         self.index_format
     }
+
+    /// Bind-pose triangles; skinning and morph targets are not applied.
+    pub fn geometry(&self) -> &MeshGeometry {
+        &self.geometry
+    }
 }
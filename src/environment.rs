@@ -2,6 +2,8 @@ use std::path::{Path, PathBuf};
 
 use wgpu::Color;
 
+use crate::asset::Handle;
+
 /// Describes high-level environment settings applied while rendering a scene.
 ///
 /// The environment controls global rendering parameters such as the clear
@@ -13,8 +15,15 @@ pub struct Environment {
     clear_color: Color,
     ambient_intensity: f32,
     hdr_background: Option<HdrBackground>,
+    environment_map: Option<Handle<EnvironmentMap>>,
 }
 
+/// Image-based lighting cubemaps baked and owned by the renderer, see
+/// `SceneLoader::load_hdr_environment`. Environments refer to them through
+/// a `Handle<EnvironmentMap>`.
+#[derive(Debug)]
+pub struct EnvironmentMap;
+
 #[derive(Debug, Clone)]
 pub struct HdrBackground {
     enabled: bool,
@@ -29,6 +38,7 @@ impl Environment {
             clear_color,
             ambient_intensity: 0.03,
             hdr_background: None,
+            environment_map: None,
         }
     }
 
@@ -111,9 +121,27 @@ impl Environment {
             .filter(|background| background.enabled())
     }
 
-    /// Returns true if an HDR background exists and is enabled.
+    /// Uses a pre-baked environment map for lighting and the skybox. It
+    /// takes precedence over the HDR background, whose intensity still
+    /// applies.
+    pub fn set_environment_map(&mut self, map: Option<Handle<EnvironmentMap>>) {
+        self.environment_map = map;
+    }
+
+    /// Returns a copy of the environment using the given environment map.
+    pub fn with_environment_map(mut self, map: Handle<EnvironmentMap>) -> Self {
+        self.environment_map = Some(map);
+        self
+    }
+
+    pub fn environment_map(&self) -> Option<Handle<EnvironmentMap>> {
+        self.environment_map
+    }
+
+    /// Returns true if an environment map is set or an HDR background
+    /// exists and is enabled.
     pub fn is_hdr_enabled(&self) -> bool {
-        self.active_hdr_background().is_some()
+        self.environment_map.is_some() || self.active_hdr_background().is_some()
     }
 }
 
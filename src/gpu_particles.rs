@@ -4,6 +4,7 @@ use bytemuck::{Pod, Zeroable};
 use glam::{Quat, Vec3};
 use wgpu::util::DeviceExt;
 
+use crate::renderer::internal::pipeline::{with_texture_capacity, MIN_BINDLESS_TEXTURES};
 use crate::renderer::{Material, PipelineBuilder, Renderer, Vertex};
 
 const WORKGROUP_SIZE: u32 = 256;
@@ -303,7 +304,12 @@ impl GpuParticleSystem {
             "{}\n{}\n{}",
             include_str!("shader/constants.wgsl"),
             include_str!("shader/pbr_lighting.wgsl"),
-            include_str!("shader/gpu_particle_render.wgsl")
+            with_texture_capacity(
+                include_str!("shader/gpu_particle_render.wgsl"),
+                renderer
+                    .bindless_texture_capacity()
+                    .unwrap_or(MIN_BINDLESS_TEXTURES),
+            )
         );
 
         let render_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -374,7 +380,7 @@ impl GpuParticleSystem {
             .with_vertex_buffer(Vertex::layout())
             .with_color_target(renderer.surface_format(), Some(wgpu::BlendState::REPLACE))
             .with_depth_stencil(
-                wgpu::TextureFormat::Depth32Float,
+                renderer.depth_format(),
                 true,
                 wgpu::CompareFunction::LessEqual,
             )
@@ -0,0 +1,111 @@
+//! Gamepad events polled from gilrs and translated for [`InputState`].
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use super::{GamepadAxis, GamepadButton, GamepadEvent, InputState};
+
+/// The gilrs context, created once. Controllers already plugged in are
+/// reported as connected on creation; later ones arrive as events.
+pub(crate) struct Gamepads {
+    gilrs: Gilrs,
+}
+
+impl Gamepads {
+    /// `None` when the platform's gamepad backend is unavailable; the app
+    /// then runs without controller input.
+    pub(crate) fn new(input: &mut InputState) -> Option<Self> {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(gilrs::Error::NotImplemented(gilrs)) => {
+                log::warn!("Gamepads are not supported on this platform");
+                gilrs
+            }
+            Err(err) => {
+                log::warn!("Gamepad support disabled: {}", err);
+                return None;
+            }
+        };
+        for (_, gamepad) in gilrs.gamepads() {
+            log::info!("Gamepad connected: {}", gamepad.name());
+            input.handle_gamepad_event(GamepadEvent::Connected);
+        }
+        Some(Self { gilrs })
+    }
+
+    /// Drains pending gilrs events into `input`. Call once per frame before
+    /// the update systems run.
+    pub(crate) fn poll(&mut self, input: &mut InputState) {
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::Connected => {
+                    log::info!("Gamepad connected: {}", self.gilrs.gamepad(event.id).name())
+                }
+                EventType::Disconnected => {
+                    log::info!(
+                        "Gamepad disconnected: {}",
+                        self.gilrs.gamepad(event.id).name()
+                    )
+                }
+                _ => {}
+            }
+            if let Some(event) = translate(event.event) {
+                input.handle_gamepad_event(event);
+            }
+        }
+    }
+}
+
+fn translate(event: EventType) -> Option<GamepadEvent> {
+    match event {
+        EventType::Connected => Some(GamepadEvent::Connected),
+        EventType::Disconnected => Some(GamepadEvent::Disconnected),
+        EventType::ButtonPressed(button, _) => button_of(button).map(GamepadEvent::ButtonPressed),
+        EventType::ButtonReleased(button, _) => button_of(button).map(GamepadEvent::ButtonReleased),
+        // Analog triggers report their travel as button changes.
+        EventType::ButtonChanged(Button::LeftTrigger2, value, _) => {
+            Some(GamepadEvent::AxisChanged(GamepadAxis::LeftTrigger, value))
+        }
+        EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+            Some(GamepadEvent::AxisChanged(GamepadAxis::RightTrigger, value))
+        }
+        EventType::AxisChanged(axis, value, _) => {
+            axis_of(axis).map(|axis| GamepadEvent::AxisChanged(axis, value))
+        }
+        _ => None,
+    }
+}
+
+fn button_of(button: Button) -> Option<GamepadButton> {
+    Some(match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+fn axis_of(axis: Axis) -> Option<GamepadAxis> {
+    Some(match axis {
+        Axis::LeftStickX => GamepadAxis::LeftStickX,
+        Axis::LeftStickY => GamepadAxis::LeftStickY,
+        Axis::RightStickX => GamepadAxis::RightStickX,
+        Axis::RightStickY => GamepadAxis::RightStickY,
+        Axis::LeftZ => GamepadAxis::LeftTrigger,
+        Axis::RightZ => GamepadAxis::RightTrigger,
+        _ => return None,
+    })
+}
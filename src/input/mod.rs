@@ -0,0 +1,384 @@
+//! Keyboard, mouse and gamepad state collected from winit and gilrs events.
+
+use std::collections::HashSet;
+
+use glam::Vec2;
+use winit::event::{ElementState, WindowEvent};
+use winit::keyboard::PhysicalKey;
+
+pub use winit::event::MouseButton;
+pub use winit::keyboard::KeyCode;
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+mod gamepads;
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+pub(crate) use gamepads::Gamepads;
+
+/// Stick deflection below which gamepad axes read as zero.
+pub const DEFAULT_DEAD_ZONE: f32 = 0.15;
+
+/// Gamepad buttons by position, following the Xbox layout for the face
+/// buttons' names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    /// A on Xbox, cross on PlayStation.
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    /// Positive up.
+    LeftStickY,
+    RightStickX,
+    /// Positive up.
+    RightStickY,
+    /// Analog trigger travel, 0 to 1.
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub const ALL: [GamepadAxis; 6] = [
+        GamepadAxis::LeftStickX,
+        GamepadAxis::LeftStickY,
+        GamepadAxis::RightStickX,
+        GamepadAxis::RightStickY,
+        GamepadAxis::LeftTrigger,
+        GamepadAxis::RightTrigger,
+    ];
+}
+
+/// A gamepad change, as fed to [`InputState::handle_gamepad_event`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadEvent {
+    Connected,
+    Disconnected,
+    ButtonPressed(GamepadButton),
+    ButtonReleased(GamepadButton),
+    /// Raw axis value in -1 to 1 (0 to 1 for triggers), before the dead zone.
+    AxisChanged(GamepadAxis, f32),
+}
+
+/// Pressed, just pressed and just released sets for one kind of button.
+#[derive(Debug, Clone)]
+struct Buttons<T> {
+    held: HashSet<T>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+
+impl<T> Default for Buttons<T> {
+    fn default() -> Self {
+        Self {
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + std::hash::Hash> Buttons<T> {
+    /// Repeated presses of a held button are not new presses.
+    fn press(&mut self, button: T) {
+        if self.held.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: T) {
+        if self.held.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    fn release_all(&mut self) {
+        self.released.extend(self.held.drain());
+    }
+
+    fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+}
+
+/// Keys, mouse buttons and gamepad state for the current frame.
+///
+/// Keys are tracked by physical position so WASD-style bindings work on
+/// any keyboard layout. Mouse movement comes from device events and keeps
+/// accumulating while the cursor is grabbed. Gamepad state is merged across
+/// every connected controller. "Pressed" and "released" queries report the
+/// edges since the last [`Self::end_frame`].
+#[derive(Debug, Clone)]
+pub struct InputState {
+    keys: Buttons<KeyCode>,
+    mouse_buttons: Buttons<MouseButton>,
+    mouse_delta: Vec2,
+    gamepad_buttons: Buttons<GamepadButton>,
+    axes: [f32; GamepadAxis::ALL.len()],
+    dead_zone: f32,
+    gamepads_connected: usize,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            keys: Buttons::default(),
+            mouse_buttons: Buttons::default(),
+            mouse_delta: Vec2::ZERO,
+            gamepad_buttons: Buttons::default(),
+            axes: [0.0; GamepadAxis::ALL.len()],
+            dead_zone: DEFAULT_DEAD_ZONE,
+            gamepads_connected: 0,
+        }
+    }
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let PhysicalKey::Code(code) = event.physical_key {
+                    match event.state {
+                        ElementState::Pressed => self.keys.press(code),
+                        ElementState::Released => self.keys.release(code),
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => self.mouse_buttons.press(*button),
+                ElementState::Released => self.mouse_buttons.release(*button),
+            },
+            // Releases are not delivered to unfocused windows.
+            WindowEvent::Focused(false) => {
+                self.keys.release_all();
+                self.mouse_buttons.release_all();
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds raw mouse movement in device units.
+    pub fn on_mouse_motion(&mut self, delta: Vec2) {
+        if delta.is_finite() {
+            self.mouse_delta += delta;
+        }
+    }
+
+    /// Applies one gamepad change. Disconnecting a controller releases its
+    /// buttons and recenters the axes so nothing stays stuck.
+    pub fn handle_gamepad_event(&mut self, event: GamepadEvent) {
+        match event {
+            GamepadEvent::Connected => self.gamepads_connected += 1,
+            GamepadEvent::Disconnected => {
+                self.gamepads_connected = self.gamepads_connected.saturating_sub(1);
+                self.gamepad_buttons.release_all();
+                self.axes = [0.0; GamepadAxis::ALL.len()];
+            }
+            GamepadEvent::ButtonPressed(button) => self.gamepad_buttons.press(button),
+            GamepadEvent::ButtonReleased(button) => self.gamepad_buttons.release(button),
+            GamepadEvent::AxisChanged(axis, value) => {
+                if value.is_finite() {
+                    self.axes[axis as usize] = value.clamp(-1.0, 1.0);
+                }
+            }
+        }
+    }
+
+    pub fn is_key_held(&self, key: KeyCode) -> bool {
+        self.keys.held.contains(&key)
+    }
+
+    /// Whether `key` went down this frame. Key repeats do not count.
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.keys.pressed.contains(&key)
+    }
+
+    pub fn is_key_released(&self, key: KeyCode) -> bool {
+        self.keys.released.contains(&key)
+    }
+
+    pub fn is_mouse_held(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.held.contains(&button)
+    }
+
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.pressed.contains(&button)
+    }
+
+    pub fn is_mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.released.contains(&button)
+    }
+
+    /// Mouse movement accumulated since the last [`Self::end_frame`].
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+
+    pub fn is_button_held(&self, button: GamepadButton) -> bool {
+        self.gamepad_buttons.held.contains(&button)
+    }
+
+    pub fn is_button_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepad_buttons.pressed.contains(&button)
+    }
+
+    pub fn is_button_released(&self, button: GamepadButton) -> bool {
+        self.gamepad_buttons.released.contains(&button)
+    }
+
+    /// Axis value with the dead zone removed and the remaining travel
+    /// rescaled, so it still reaches ±1 at full deflection.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        apply_dead_zone(self.axes[axis as usize], self.dead_zone)
+    }
+
+    /// Left stick with a radial dead zone, which keeps diagonal directions
+    /// accurate near the center.
+    pub fn left_stick(&self) -> Vec2 {
+        self.stick(GamepadAxis::LeftStickX, GamepadAxis::LeftStickY)
+    }
+
+    pub fn right_stick(&self) -> Vec2 {
+        self.stick(GamepadAxis::RightStickX, GamepadAxis::RightStickY)
+    }
+
+    fn stick(&self, x: GamepadAxis, y: GamepadAxis) -> Vec2 {
+        let raw = Vec2::new(self.axes[x as usize], self.axes[y as usize]);
+        let length = raw.length();
+        if length <= self.dead_zone {
+            return Vec2::ZERO;
+        }
+        raw / length * apply_dead_zone(length.min(1.0), self.dead_zone)
+    }
+
+    pub fn dead_zone(&self) -> f32 {
+        self.dead_zone
+    }
+
+    pub fn set_dead_zone(&mut self, dead_zone: f32) {
+        self.dead_zone = dead_zone.clamp(0.0, 0.99);
+    }
+
+    pub fn gamepads_connected(&self) -> usize {
+        self.gamepads_connected
+    }
+
+    /// Clears per-frame state; call after the update systems ran.
+    pub fn end_frame(&mut self) {
+        self.keys.end_frame();
+        self.mouse_buttons.end_frame();
+        self.gamepad_buttons.end_frame();
+        self.mouse_delta = Vec2::ZERO;
+    }
+}
+
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude <= dead_zone {
+        return 0.0;
+    }
+    value.signum() * ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_motion_accumulates_until_end_of_frame() {
+        let mut input = InputState::new();
+        input.on_mouse_motion(Vec2::new(3.0, -1.0));
+        input.on_mouse_motion(Vec2::new(2.0, 4.0));
+        input.on_mouse_motion(Vec2::new(f32::NAN, 1.0));
+        assert_eq!(input.mouse_delta(), Vec2::new(5.0, 3.0));
+
+        input.end_frame();
+        assert_eq!(input.mouse_delta(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn losing_focus_releases_keys() {
+        let mut input = InputState::new();
+        input.keys.press(KeyCode::KeyW);
+        assert!(input.is_key_held(KeyCode::KeyW));
+
+        input.handle_window_event(&WindowEvent::Focused(false));
+        assert!(!input.is_key_held(KeyCode::KeyW));
+        assert!(input.is_key_released(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn button_presses_are_reported_for_one_frame() {
+        let mut input = InputState::new();
+        input.handle_gamepad_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        assert!(input.is_button_pressed(GamepadButton::South));
+        assert!(input.is_button_held(GamepadButton::South));
+
+        input.end_frame();
+        input.handle_gamepad_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        assert!(!input.is_button_pressed(GamepadButton::South));
+        assert!(input.is_button_held(GamepadButton::South));
+
+        input.handle_gamepad_event(GamepadEvent::ButtonReleased(GamepadButton::South));
+        assert!(input.is_button_released(GamepadButton::South));
+        assert!(!input.is_button_held(GamepadButton::South));
+        input.end_frame();
+        assert!(!input.is_button_released(GamepadButton::South));
+    }
+
+    #[test]
+    fn dead_zone_zeroes_small_deflection_and_rescales_the_rest() {
+        let mut input = InputState::new();
+        input.set_dead_zone(0.2);
+        input.handle_gamepad_event(GamepadEvent::AxisChanged(GamepadAxis::LeftStickX, 0.15));
+        assert_eq!(input.axis(GamepadAxis::LeftStickX), 0.0);
+
+        input.handle_gamepad_event(GamepadEvent::AxisChanged(GamepadAxis::LeftStickX, -0.6));
+        assert!((input.axis(GamepadAxis::LeftStickX) + 0.5).abs() < 1e-6);
+        input.handle_gamepad_event(GamepadEvent::AxisChanged(GamepadAxis::LeftStickX, 1.0));
+        assert_eq!(input.axis(GamepadAxis::LeftStickX), 1.0);
+
+        // Radially, (0.15, 0.15) is past a 0.2 dead zone but each axis is not.
+        input.handle_gamepad_event(GamepadEvent::AxisChanged(GamepadAxis::RightStickX, 0.15));
+        input.handle_gamepad_event(GamepadEvent::AxisChanged(GamepadAxis::RightStickY, 0.15));
+        let stick = input.right_stick();
+        assert!(stick.x > 0.0 && (stick.x - stick.y).abs() < 1e-6);
+        assert_eq!(input.axis(GamepadAxis::RightStickX), 0.0);
+    }
+
+    #[test]
+    fn disconnecting_releases_buttons_and_recenters_axes() {
+        let mut input = InputState::new();
+        input.handle_gamepad_event(GamepadEvent::Connected);
+        input.handle_gamepad_event(GamepadEvent::ButtonPressed(GamepadButton::Start));
+        input.handle_gamepad_event(GamepadEvent::AxisChanged(GamepadAxis::RightTrigger, 0.9));
+        assert_eq!(input.gamepads_connected(), 1);
+
+        input.handle_gamepad_event(GamepadEvent::Disconnected);
+        input.handle_gamepad_event(GamepadEvent::Disconnected);
+        assert_eq!(input.gamepads_connected(), 0);
+        assert!(!input.is_button_held(GamepadButton::Start));
+        assert_eq!(input.axis(GamepadAxis::RightTrigger), 0.0);
+    }
+}
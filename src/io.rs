@@ -76,6 +76,50 @@ pub(crate) fn load_binary_from_str(path: &str) -> Result<Vec<u8>, String> {
     load_web_bytes(&path_buf)
 }
 
+#[cfg(target_arch = "wasm32")]
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let window = web_sys::window().ok_or_else(|| "No window available for fetch".to_string())?;
+    let response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .map_err(|err| format!("Failed to fetch {}: {:?}", url, err))?;
+    let response: web_sys::Response = response
+        .dyn_into()
+        .map_err(|err| format!("Unexpected fetch result for {}: {:?}", url, err))?;
+
+    if !response.ok() {
+        return Err(format!(
+            "HTTP {} when requesting {}",
+            response.status(),
+            url
+        ));
+    }
+
+    let buffer = response
+        .array_buffer()
+        .map_err(|err| format!("Failed to read response body for {}: {:?}", url, err))?;
+    let buffer = JsFuture::from(buffer)
+        .await
+        .map_err(|err| format!("Failed to read response body for {}: {:?}", url, err))?;
+
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+/// Asynchronous counterpart of [`load_binary`] using the `fetch` API.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn fetch_binary(path: &Path) -> Result<Vec<u8>, String> {
+    let url = normalize_web_path(path)?;
+    fetch_bytes(&url).await
+}
+
+/// Asynchronous counterpart of [`load_binary_from_str`].
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn fetch_binary_from_str(path: &str) -> Result<Vec<u8>, String> {
+    fetch_binary(&PathBuf::from(path)).await
+}
+
 pub(crate) fn load_binary(path: &Path) -> Result<Vec<u8>, String> {
     #[cfg(target_arch = "wasm32")]
     {
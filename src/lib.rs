@@ -2,6 +2,7 @@ pub mod app;
 pub mod asset;
 pub mod environment;
 pub mod gpu_particles;
+pub mod input;
 pub mod io;
 pub mod render_application;
 pub mod renderer;
@@ -16,11 +17,12 @@ pub mod ui;
 pub use render_application::DefaultUI;
 pub use render_application::{run_application, RenderApplication};
 
-pub use environment::{Environment, HdrBackground};
+pub use environment::{Environment, EnvironmentMap, HdrBackground};
+pub use input::InputState;
 
 pub use app::{
-    App, AppBuilder, GpuUpdateContext, GpuUpdateSystem, Plugin, StartupContext, StartupSystem,
-    UpdateContext, UpdateSystem,
+    App, AppBuilder, AppCommand, AppCommands, GpuUpdateContext, GpuUpdateSystem, Plugin,
+    PluginGroup, StartupContext, StartupSystem, UpdateContext, UpdateSystem,
 };
 
 #[cfg(target_arch = "wasm32")]
@@ -6,8 +6,9 @@ use crate::app::{AppBuilder, GpuUpdateContext, StartupContext, UpdateContext};
 use crate::renderer::CustomRenderContext;
 #[cfg(feature = "egui")]
 use crate::ui::{
-    init_log_recorder, FrameStatsHandle, LogBufferHandle, LogWindow, PostProcessEffectsHandle,
-    PostProcessWindow, StatsWindow,
+    init_log_recorder, DebugViewWindow, FrameStatsHandle, LogBufferHandle, LogWindow,
+    PostProcessEffectsHandle, PostProcessWindow, RenderModeHandle, SampleCountHandle,
+    ShadowSettingsHandle, ShadowWindow, StatsWindow,
 };
 
 use std::cell::RefCell;
@@ -61,9 +62,13 @@ pub struct DefaultUI {
     stats_window: StatsWindow,
     log_window: LogWindow,
     postprocess_window: PostProcessWindow,
+    shadow_window: ShadowWindow,
+    debug_view_window: Option<DebugViewWindow>,
     stats_open: bool,
     log_open: bool,
     postprocess_open: bool,
+    shadow_open: bool,
+    debug_view_open: bool,
 }
 
 #[cfg(feature = "egui")]
@@ -72,21 +77,42 @@ impl DefaultUI {
         stats_handle: FrameStatsHandle,
         log_handle: LogBufferHandle,
         post_handle: PostProcessEffectsHandle,
+        shadow_handle: ShadowSettingsHandle,
     ) -> Self {
         Self {
             stats_window: StatsWindow::new(stats_handle),
             log_window: LogWindow::new(log_handle),
             postprocess_window: PostProcessWindow::new(post_handle),
+            shadow_window: ShadowWindow::new(shadow_handle),
+            debug_view_window: None,
             stats_open: true,
             log_open: false,
             postprocess_open: true,
+            shadow_open: true,
+            debug_view_open: false,
         }
     }
 
+    /// Shows an MSAA dropdown in the post-processing window.
+    pub fn with_sample_count(mut self, handle: SampleCountHandle) -> Self {
+        self.postprocess_window = self.postprocess_window.with_sample_count(handle);
+        self
+    }
+
+    /// Adds a debug view window that selects the render mode.
+    pub fn with_render_mode(mut self, handle: RenderModeHandle) -> Self {
+        self.debug_view_window = Some(DebugViewWindow::new(handle));
+        self
+    }
+
     pub fn show(&mut self, ctx: &egui::Context) {
         self.stats_window.show(ctx, Some(&mut self.stats_open));
         self.postprocess_window
             .show(ctx, Some(&mut self.postprocess_open));
+        self.shadow_window.show(ctx, Some(&mut self.shadow_open));
+        if let Some(window) = &mut self.debug_view_window {
+            window.show(ctx, Some(&mut self.debug_view_open));
+        }
         self.log_window.show(ctx, Some(&mut self.log_open));
     }
 
@@ -94,6 +120,10 @@ impl DefaultUI {
         self.stats_window.show(ctx, Some(&mut self.stats_open));
         self.postprocess_window
             .show(ctx, Some(&mut self.postprocess_open));
+        self.shadow_window.show(ctx, Some(&mut self.shadow_open));
+        if let Some(window) = &mut self.debug_view_window {
+            window.show(ctx, Some(&mut self.debug_view_open));
+        }
     }
 
     pub fn show_logs(&mut self, ctx: &egui::Context) {
@@ -111,6 +141,10 @@ impl DefaultUI {
     pub fn postprocess_window_mut(&mut self) -> &mut PostProcessWindow {
         &mut self.postprocess_window
     }
+
+    pub fn shadow_window_mut(&mut self) -> &mut ShadowWindow {
+        &mut self.shadow_window
+    }
 }
 
 /// Run an application that implements RenderApplication
@@ -162,9 +196,15 @@ where
         let stats_handle = app.frame_stats_handle();
         let log_handle = init_log_recorder();
         let post_handle = app.postprocess_effects_handle();
+        let shadow_handle = app.shadow_settings_handle();
+        let sample_count_handle = app.sample_count_handle();
+        let render_mode_handle = app.render_mode_handle();
 
         if show_default {
-            let mut default_ui = DefaultUI::new(stats_handle, log_handle, post_handle);
+            let mut default_ui =
+                DefaultUI::new(stats_handle, log_handle, post_handle, shadow_handle)
+                    .with_sample_count(sample_count_handle)
+                    .with_render_mode(render_mode_handle);
             let app_ref = app_rc.clone();
 
             app.set_egui_ui(move |ctx| {
@@ -172,7 +212,10 @@ where
                 app_ref.borrow_mut().ui(ctx, &mut default_ui);
             });
         } else {
-            let mut default_ui = DefaultUI::new(stats_handle, log_handle, post_handle);
+            let mut default_ui =
+                DefaultUI::new(stats_handle, log_handle, post_handle, shadow_handle)
+                    .with_sample_count(sample_count_handle)
+                    .with_render_mode(render_mode_handle);
             let app_ref = app_rc.clone();
 
             app.set_egui_ui(move |ctx| {
@@ -233,9 +276,15 @@ where
         let stats_handle = app.frame_stats_handle();
         let log_handle = init_log_recorder();
         let post_handle = app.postprocess_effects_handle();
+        let shadow_handle = app.shadow_settings_handle();
+        let sample_count_handle = app.sample_count_handle();
+        let render_mode_handle = app.render_mode_handle();
 
         if show_default {
-            let mut default_ui = DefaultUI::new(stats_handle, log_handle, post_handle);
+            let mut default_ui =
+                DefaultUI::new(stats_handle, log_handle, post_handle, shadow_handle)
+                    .with_sample_count(sample_count_handle)
+                    .with_render_mode(render_mode_handle);
             let app_ref = app_rc.clone();
 
             app.set_egui_ui(move |ctx| {
@@ -243,7 +292,10 @@ where
                 app_ref.borrow_mut().ui(ctx, &mut default_ui);
             });
         } else {
-            let mut default_ui = DefaultUI::new(stats_handle, log_handle, post_handle);
+            let mut default_ui =
+                DefaultUI::new(stats_handle, log_handle, post_handle, shadow_handle)
+                    .with_sample_count(sample_count_handle)
+                    .with_render_mode(render_mode_handle);
             let app_ref = app_rc.clone();
 
             app.set_egui_ui(move |ctx| {
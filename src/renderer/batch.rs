@@ -5,7 +5,11 @@ use crate::{
     scene::components::DepthState,
     scene::transform::Transform,
 };
+use glam::{Mat4, Vec3};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RenderPass {
@@ -47,6 +51,59 @@ pub struct RenderObject {
     pub force_overlay: bool,
     pub instance_source: InstanceSource,
     pub gpu_index: Option<u32>,
+    /// Offset of this object's joint palette in the batcher, for skinned meshes.
+    pub joint_offset: Option<u32>,
+    /// Offset of this object's morph target weights in the batcher.
+    pub morph_weight_offset: Option<u32>,
+    /// Entity this object was built from, `None` for manual draws. Retained
+    /// batching uses it to tell whether the set of drawn objects changed
+    /// between frames.
+    pub entity: Option<hecs::Entity>,
+    /// Draw order hint. Blended objects store their view depth, larger is
+    /// farther; opaque objects store a hash of their material so equal
+    /// materials sort next to each other. See [`update_sort_key`](Self::update_sort_key).
+    pub sort_key: f32,
+}
+
+impl RenderObject {
+    /// The pass this object is batched into.
+    pub fn pass(&self) -> RenderPass {
+        if self.force_overlay {
+            RenderPass::Overlay
+        } else if self.material.requires_separate_pass() {
+            RenderPass::Transparent
+        } else {
+            RenderPass::Opaque
+        }
+    }
+
+    /// Recomputes [`sort_key`](Self::sort_key) for a camera with the given
+    /// `view` matrix. `centroid` is the world-space centre of the object's
+    /// bounds.
+    pub fn update_sort_key(&mut self, view: Mat4, centroid: Vec3) {
+        self.sort_key = if self.pass().requires_back_to_front_sort() {
+            -view.transform_point3(centroid).z
+        } else {
+            material_sort_key(&self.material)
+        };
+    }
+}
+
+/// Sort key grouping opaque objects by material. Only 24 bits of the hash
+/// are kept so every key is exactly representable as an `f32`.
+pub fn material_sort_key(material: &Material) -> f32 {
+    let mut hasher = DefaultHasher::new();
+    material.hash(&mut hasher);
+    (hasher.finish() >> 40) as f32
+}
+
+/// Orders instances by descending sort key, farthest first.
+pub(crate) fn sort_back_to_front(instances: &mut [InstanceData]) {
+    instances.sort_by(|a, b| {
+        b.sort_key
+            .partial_cmp(&a.sort_key)
+            .unwrap_or(Ordering::Equal)
+    });
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -55,6 +112,13 @@ pub struct InstanceData {
     pub material_index: u32,
     pub source: InstanceSource,
     pub gpu_index: Option<u32>,
+    pub joint_offset: Option<u32>,
+    pub morph_weight_offset: Option<u32>,
+    /// Copied from [`RenderObject::sort_key`].
+    pub sort_key: f32,
+    /// Copied from [`RenderObject::entity`]; pairs the instance with its
+    /// previous transform for motion vectors.
+    pub entity: Option<hecs::Entity>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -73,6 +137,8 @@ pub struct Batch<'a> {
     pub mesh: Handle<Mesh>,
     pub pass: RenderPass,
     pub depth_state: DepthState,
+    /// Every instance uses a double-sided material, drawn without culling.
+    pub double_sided: bool,
     pub instances: &'a [InstanceData],
     pub materials: &'a [Material],
 }
@@ -83,14 +149,28 @@ struct BatchKey {
     mesh: Handle<Mesh>,
     pass: RenderPass, // Only split if different pipeline needed
     depth_state: DepthState,
+    double_sided: bool, // Selects a pipeline without back-face culling
     source: InstanceSource,
 }
 
 /// Collects objects and batches by pipeline requirements
+///
+/// In retained mode (the default) the batcher remembers which objects went
+/// into which batch last frame. While that composition is unchanged every
+/// object keeps its instance slot, so the renderer only uploads the slots
+/// whose data changed. Adding, removing or rebatching an object falls back
+/// to rewriting every slot for that frame.
 pub struct RenderBatcher {
     batches: HashMap<BatchKey, Vec<InstanceData>>,
     materials: Vec<Material>,
     material_lookup: HashMap<Material, u32>,
+    joint_matrices: Vec<Mat4>,
+    morph_weights: Vec<f32>,
+    culled_objects: u32,
+    manual_objects: u32,
+    retained: bool,
+    composition: DefaultHasher,
+    previous_composition: Option<u64>,
 }
 
 impl RenderBatcher {
@@ -99,27 +179,45 @@ impl RenderBatcher {
             batches: HashMap::new(),
             materials: Vec::new(),
             material_lookup: HashMap::new(),
+            joint_matrices: Vec::new(),
+            morph_weights: Vec::new(),
+            culled_objects: 0,
+            manual_objects: 0,
+            retained: true,
+            composition: DefaultHasher::new(),
+            previous_composition: None,
         }
     }
 
+    /// Enables or disables retained mode. Without it every instance slot is
+    /// rewritten each frame.
+    pub fn set_retained(&mut self, retained: bool) {
+        self.retained = retained;
+    }
+
+    pub fn is_retained(&self) -> bool {
+        self.retained
+    }
+
+    /// Whether the objects added since the last [`clear`](Self::clear) differ
+    /// from the previous frame's in entity, batch or order, which moves
+    /// instance slots. Always `true` outside retained mode.
+    pub fn composition_changed(&self) -> bool {
+        !self.retained || self.previous_composition != Some(self.composition.finish())
+    }
+
     /// Add an object to be rendered
     pub fn add(&mut self, obj: RenderObject) {
-        // Determine which pass this object belongs to
-        let pass = if obj.force_overlay {
-            RenderPass::Overlay
-        } else if obj.material.requires_separate_pass() {
-            RenderPass::Transparent
-        } else {
-            RenderPass::Opaque
-        };
-
         let key = BatchKey {
             mesh: obj.mesh,
-            pass,
+            pass: obj.pass(),
             depth_state: obj.depth_state,
+            double_sided: obj.material.is_double_sided(),
             source: obj.instance_source,
         };
 
+        (&key, obj.entity).hash(&mut self.composition);
+
         let material_index = *self.material_lookup.entry(obj.material).or_insert_with(|| {
             let index = self.materials.len() as u32;
             self.materials.push(obj.material);
@@ -131,16 +229,76 @@ impl RenderBatcher {
             material_index,
             source: obj.instance_source,
             gpu_index: obj.gpu_index,
+            joint_offset: obj.joint_offset,
+            morph_weight_offset: obj.morph_weight_offset,
+            sort_key: obj.sort_key,
+            entity: obj.entity,
         });
     }
 
-    /// Clear all batches
+    /// Sorts the instances of every transparent and overlay batch by
+    /// descending [`RenderObject::sort_key`]. The renderer applies the same
+    /// order when it prepares a frame; call this after filling a batcher to
+    /// read a pass back in draw order through [`iter_pass`](Self::iter_pass)
+    /// or [`get_pass_instances`](Self::get_pass_instances). GPU-driven
+    /// batches keep their instance order.
+    pub fn sort_transparent_back_to_front(&mut self) {
+        for (key, instances) in &mut self.batches {
+            if key.pass.requires_back_to_front_sort() && key.source == InstanceSource::Cpu {
+                sort_back_to_front(instances);
+            }
+        }
+    }
+
+    /// Appends a skin's joint palette for this frame and returns its offset,
+    /// to be stored in `RenderObject::joint_offset`.
+    pub fn add_joint_matrices(&mut self, matrices: &[Mat4]) -> u32 {
+        let offset = self.joint_matrices.len() as u32;
+        self.joint_matrices.extend_from_slice(matrices);
+        offset
+    }
+
+    /// Appends an object's morph target weights for this frame and returns
+    /// their offset, to be stored in `RenderObject::morph_weight_offset`.
+    pub fn add_morph_weights(&mut self, weights: &[f32]) -> u32 {
+        let offset = self.morph_weights.len() as u32;
+        self.morph_weights.extend_from_slice(weights);
+        offset
+    }
+
+    /// Records objects rejected by frustum culling this frame so the
+    /// renderer can report them in [`RendererStats`](crate::renderer::RendererStats).
+    pub fn record_culled(&mut self, count: u32) {
+        self.culled_objects += count;
+    }
+
+    pub fn culled_objects(&self) -> u32 {
+        self.culled_objects
+    }
+
+    /// Records objects added from the renderer's
+    /// [`ManualDrawList`](crate::renderer::ManualDrawList) this frame.
+    pub fn record_manual(&mut self, count: u32) {
+        self.manual_objects += count;
+    }
+
+    pub fn manual_objects(&self) -> u32 {
+        self.manual_objects
+    }
+
+    /// Clear all batches, remembering their composition for retained mode
     pub fn clear(&mut self) {
+        self.previous_composition = Some(self.composition.finish());
+        self.composition = DefaultHasher::new();
         for batch in self.batches.values_mut() {
             batch.clear();
         }
         self.materials.clear();
         self.material_lookup.clear();
+        self.joint_matrices.clear();
+        self.morph_weights.clear();
+        self.culled_objects = 0;
+        self.manual_objects = 0;
     }
 
     pub fn iter(&self) -> impl Iterator<Item = Batch<'_>> {
@@ -148,6 +306,7 @@ impl RenderBatcher {
             mesh: key.mesh,
             pass: key.pass,
             depth_state: key.depth_state,
+            double_sided: key.double_sided,
             instances: instances.as_slice(),
             materials: self.materials.as_slice(),
         })
@@ -160,6 +319,7 @@ impl RenderBatcher {
                     mesh: key.mesh,
                     pass: key.pass,
                     depth_state: key.depth_state,
+                    double_sided: key.double_sided,
                     instances: instances.as_slice(),
                     materials: self.materials.as_slice(),
                 })
@@ -189,6 +349,14 @@ impl RenderBatcher {
     pub fn materials(&self) -> &[Material] {
         &self.materials
     }
+
+    pub fn joint_matrices(&self) -> &[Mat4] {
+        &self.joint_matrices
+    }
+
+    pub fn morph_weights(&self) -> &[f32] {
+        &self.morph_weights
+    }
 }
 
 impl Default for RenderBatcher {
@@ -0,0 +1,57 @@
+// renderer/blit_compute.wgsl - 2:1 downsample of one mip level into the next
+
+@group(0) @binding(0) var src_texture: texture_2d<f32>;
+@group(0) @binding(1) var dst_texture: texture_storage_2d<rgba8unorm, write>;
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+// Averages the 2x2 source texels under a destination texel. Odd source
+// sizes clamp the last row and column instead of reading past the edge.
+fn box_filter(dst: vec2<u32>, srgb: bool) -> vec4<f32> {
+    let src_max = vec2<i32>(textureDimensions(src_texture)) - vec2<i32>(1);
+    let base = vec2<i32>(dst) * 2;
+    var sum = vec4<f32>(0.0);
+    for (var y = 0; y < 2; y++) {
+        for (var x = 0; x < 2; x++) {
+            var texel = textureLoad(src_texture, min(base + vec2<i32>(x, y), src_max), 0);
+            if (srgb) {
+                texel = vec4<f32>(srgb_to_linear(texel.rgb), texel.a);
+            }
+            sum += texel;
+        }
+    }
+    let average = sum * 0.25;
+    if (srgb) {
+        return vec4<f32>(linear_to_srgb(average.rgb), average.a);
+    }
+    return average;
+}
+
+@compute @workgroup_size(8, 8)
+fn downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(dst_texture);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    textureStore(dst_texture, vec2<i32>(id.xy), box_filter(id.xy, false));
+}
+
+// For sRGB-encoded texels stored as rgba8unorm: filters in linear space.
+@compute @workgroup_size(8, 8)
+fn downsample_srgb(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(dst_texture);
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    textureStore(dst_texture, vec2<i32>(id.xy), box_filter(id.xy, true));
+}
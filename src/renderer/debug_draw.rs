@@ -0,0 +1,225 @@
+//! Immediate-mode debug geometry. Lines are queued during a frame, drawn
+//! once by the renderer and discarded.
+
+use std::mem;
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Debug, Default, PartialEq)]
+pub struct DebugLineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl DebugLineVertex {
+    pub const ATTRS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+    pub fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<DebugLineVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRS,
+        }
+    }
+}
+
+/// Line list of gizmos for the current frame. Primitives are depth tested
+/// against the scene by default; call [`Self::set_depth_test`] with `false`
+/// to draw the following primitives on top of everything, in an overlay
+/// pass after post-processing.
+#[derive(Clone, Debug)]
+pub struct DebugDraw {
+    depth_tested: Vec<DebugLineVertex>,
+    on_top: Vec<DebugLineVertex>,
+    depth_test: bool,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            depth_tested: Vec::new(),
+            on_top: Vec::new(),
+            depth_test: true,
+        }
+    }
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether primitives added from now on are hidden behind scene geometry.
+    pub fn set_depth_test(&mut self, depth_test: bool) {
+        self.depth_test = depth_test;
+    }
+
+    pub fn depth_test(&self) -> bool {
+        self.depth_test
+    }
+
+    pub fn draw_line(&mut self, a: Vec3, b: Vec3, color: Vec4) {
+        let color = color.to_array();
+        let target = if self.depth_test {
+            &mut self.depth_tested
+        } else {
+            &mut self.on_top
+        };
+        target.push(DebugLineVertex {
+            position: a.to_array(),
+            color,
+        });
+        target.push(DebugLineVertex {
+            position: b.to_array(),
+            color,
+        });
+    }
+
+    /// Axis-aligned box outline.
+    pub fn draw_aabb(&mut self, min: Vec3, max: Vec3, color: Vec4) {
+        let corners = [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+        ];
+        self.box_edges(&corners, color);
+    }
+
+    /// Three axis-aligned great circles around `center`, each made of
+    /// `segments` lines (at least 3).
+    pub fn draw_sphere_wireframe(
+        &mut self,
+        center: Vec3,
+        radius: f32,
+        segments: usize,
+        color: Vec4,
+    ) {
+        let segments = segments.max(3);
+        let step = std::f32::consts::TAU / segments as f32;
+        let point = |axis: usize, angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            let offset = match axis {
+                0 => Vec3::new(0.0, cos, sin),
+                1 => Vec3::new(cos, 0.0, sin),
+                _ => Vec3::new(cos, sin, 0.0),
+            };
+            center + offset * radius
+        };
+
+        for axis in 0..3 {
+            for i in 0..segments {
+                let a = point(axis, i as f32 * step);
+                let b = point(axis, (i + 1) as f32 * step);
+                self.draw_line(a, b, color);
+            }
+        }
+    }
+
+    /// Outline of the volume a view-projection matrix maps onto clip space,
+    /// such as a camera or shadow frustum.
+    pub fn draw_frustum(&mut self, view_proj: Mat4, color: Vec4) {
+        let inverse = view_proj.inverse();
+        // wgpu clip space: x and y in [-1, 1], depth in [0, 1].
+        let corners = [
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(-1.0, 1.0, 1.0),
+        ]
+        .map(|ndc| inverse.project_point3(ndc));
+        self.box_edges(&corners, color);
+    }
+
+    /// Discards all queued primitives and restores depth testing.
+    pub fn clear(&mut self) {
+        self.depth_tested.clear();
+        self.on_top.clear();
+        self.depth_test = true;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth_tested.is_empty() && self.on_top.is_empty()
+    }
+
+    pub fn depth_tested_vertices(&self) -> &[DebugLineVertex] {
+        &self.depth_tested
+    }
+
+    pub fn on_top_vertices(&self) -> &[DebugLineVertex] {
+        &self.on_top
+    }
+
+    /// Near face `0..4` and far face `4..8`, each wound the same way.
+    fn box_edges(&mut self, corners: &[Vec3; 8], color: Vec4) {
+        for i in 0..4 {
+            let next = (i + 1) % 4;
+            self.draw_line(corners[i], corners[next], color);
+            self.draw_line(corners[i + 4], corners[next + 4], color);
+            self.draw_line(corners[i], corners[i + 4], color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives_emit_line_list_vertices() {
+        let mut debug = DebugDraw::new();
+        debug.draw_line(Vec3::ZERO, Vec3::X, Vec4::ONE);
+        assert_eq!(debug.depth_tested_vertices().len(), 2);
+
+        debug.draw_aabb(Vec3::ZERO, Vec3::ONE, Vec4::ONE);
+        assert_eq!(debug.depth_tested_vertices().len(), 2 + 12 * 2);
+
+        debug.draw_sphere_wireframe(Vec3::ZERO, 1.0, 16, Vec4::ONE);
+        assert_eq!(debug.depth_tested_vertices().len(), 2 + 12 * 2 + 3 * 16 * 2);
+        assert!(debug.on_top_vertices().is_empty());
+    }
+
+    #[test]
+    fn depth_test_flag_routes_lines_and_clear_resets() {
+        let mut debug = DebugDraw::new();
+        debug.set_depth_test(false);
+        debug.draw_line(Vec3::ZERO, Vec3::Y, Vec4::ONE);
+        assert_eq!(debug.on_top_vertices().len(), 2);
+        assert!(debug.depth_tested_vertices().is_empty());
+
+        debug.clear();
+        assert!(debug.is_empty());
+        assert!(debug.depth_test());
+    }
+
+    #[test]
+    fn frustum_corners_round_trip_through_view_proj() {
+        let view_proj = Mat4::orthographic_rh(-2.0, 2.0, -1.0, 1.0, 0.5, 10.0);
+        let mut debug = DebugDraw::new();
+        debug.draw_frustum(view_proj, Vec4::ONE);
+
+        let positions: Vec<Vec3> = debug
+            .depth_tested_vertices()
+            .iter()
+            .map(|v| Vec3::from_array(v.position))
+            .collect();
+        assert_eq!(positions.len(), 24);
+        assert!(positions
+            .iter()
+            .any(|p| p.abs_diff_eq(Vec3::new(-2.0, -1.0, -0.5), 1e-4)));
+        assert!(positions
+            .iter()
+            .any(|p| p.abs_diff_eq(Vec3::new(2.0, 1.0, -10.0), 1e-4)));
+    }
+}
@@ -8,8 +8,12 @@ pub struct Depth {
 }
 
 impl Depth {
+    /// Scene depth with an 8-bit stencil for per-object masks such as
+    /// outlines.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
     pub fn new(device: &wgpu::Device, size: PhysicalSize<u32>, sample_count: u32) -> Self {
-        let format = wgpu::TextureFormat::Depth32Float;
+        let format = Self::FORMAT;
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Depth"),
             size: wgpu::Extent3d {
@@ -42,9 +46,11 @@ impl Depth {
 
 #[cfg(test)]
 mod tests {
+    use super::Depth;
+
     #[test]
-    fn depth_format_is_depth32float() {
-        let fmt = wgpu::TextureFormat::Depth32Float;
-        assert!(matches!(fmt, wgpu::TextureFormat::Depth32Float));
+    fn depth_format_has_stencil() {
+        assert!(Depth::FORMAT.has_depth_aspect());
+        assert!(Depth::FORMAT.has_stencil_aspect());
     }
 }
@@ -0,0 +1,142 @@
+// renderer/draw_list.rs - retained draws that bypass the ECS
+use super::batch::{InstanceSource, RenderObject};
+use super::material::Material;
+use crate::asset::{Handle, Mesh};
+use crate::scene::components::DepthState;
+use crate::scene::transform::Transform;
+use std::collections::BTreeMap;
+
+/// Identifies an object registered with a [`ManualDrawList`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ManualDrawId(u64);
+
+/// A mesh drawn every frame without a backing entity.
+#[derive(Debug, Clone, Copy)]
+pub struct ManualDraw {
+    pub mesh: Handle<Mesh>,
+    pub material: Material,
+    pub transform: Transform,
+    pub depth_state: DepthState,
+}
+
+impl ManualDraw {
+    /// The object to batch; the caller fills in its sort key.
+    pub(crate) fn render_object(&self) -> RenderObject {
+        RenderObject {
+            mesh: self.mesh,
+            material: self.material,
+            transform: self.transform,
+            depth_state: self.depth_state,
+            force_overlay: false,
+            instance_source: InstanceSource::Cpu,
+            gpu_index: None,
+            joint_offset: None,
+            morph_weight_offset: None,
+            entity: None,
+            sort_key: 0.0,
+        }
+    }
+}
+
+/// Objects kept on the renderer and merged into the batches each frame next
+/// to the scene's entities, for procedural geometry such as streamed terrain
+/// chunks that would be wasteful to spawn and despawn as entities. They are
+/// culled against their mesh bounds and drawn in the shadow, depth prepass
+/// and color passes like any other object.
+#[derive(Default)]
+pub struct ManualDrawList {
+    objects: BTreeMap<ManualDrawId, ManualDraw>,
+    next_id: u64,
+}
+
+impl ManualDrawList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draws `mesh` with `material` at `transform` until [`remove`](Self::remove)d.
+    pub fn register_static(
+        &mut self,
+        mesh: Handle<Mesh>,
+        material: Material,
+        transform: Transform,
+    ) -> ManualDrawId {
+        self.register(ManualDraw {
+            mesh,
+            material,
+            transform,
+            depth_state: DepthState::default(),
+        })
+    }
+
+    pub fn register(&mut self, draw: ManualDraw) -> ManualDrawId {
+        let id = ManualDrawId(self.next_id);
+        self.next_id += 1;
+        self.objects.insert(id, draw);
+        id
+    }
+
+    /// Moves a registered object. Returns `false` for unknown ids.
+    pub fn update_transform(&mut self, id: ManualDrawId, transform: Transform) -> bool {
+        match self.objects.get_mut(&id) {
+            Some(draw) => {
+                draw.transform = transform;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops drawing an object and returns it.
+    pub fn remove(&mut self, id: ManualDrawId) -> Option<ManualDraw> {
+        self.objects.remove(&id)
+    }
+
+    pub fn get(&self, id: ManualDrawId) -> Option<&ManualDraw> {
+        self.objects.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: ManualDrawId) -> Option<&mut ManualDraw> {
+        self.objects.get_mut(&id)
+    }
+
+    /// Registered objects in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (ManualDrawId, &ManualDraw)> {
+        self.objects.iter().map(|(id, draw)| (*id, draw))
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{Quat, Vec3};
+
+    #[test]
+    fn removed_ids_are_not_reused() {
+        let mut list = ManualDrawList::new();
+        let mesh = Handle::new(0);
+        let first = list.register_static(mesh, Material::white(), Transform::IDENTITY);
+        assert!(list.remove(first).is_some());
+
+        let second = list.register_static(mesh, Material::white(), Transform::IDENTITY);
+        assert_ne!(first, second);
+        assert!(!list.update_transform(first, Transform::IDENTITY));
+
+        let moved = Transform::from_trs(Vec3::X, Quat::IDENTITY, Vec3::ONE);
+        assert!(list.update_transform(second, moved));
+        assert_eq!(list.get(second).unwrap().transform.translation, Vec3::X);
+        assert_eq!(list.len(), 1);
+    }
+}
@@ -0,0 +1,315 @@
+//! Custom passes scheduled around the renderer's built-in passes. Each custom
+//! pass is anchored before or after a named [`FramePass`]; passes sharing an
+//! anchor run in the order they were added.
+
+use crate::asset::{Assets, Handle, Mesh};
+use crate::renderer::batch::{InstanceData, RenderPass};
+use crate::renderer::internal::batches::{OrderedBatch, PreparedBatches};
+use crate::renderer::material::Material;
+use crate::renderer::Renderer;
+use crate::scene::components::DepthState;
+
+pub type FramePassFn = Box<dyn FnMut(&mut FramePassContext<'_>)>;
+
+/// The passes [`Renderer::render`] records, in frame order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramePass {
+    Shadows,
+    /// Clears the scene depth and stencil.
+    DepthPrepass,
+    /// Clears the scene colour target and draws opaque geometry into it.
+    Main,
+    /// Screen-space effects and tone mapping into the output target.
+    PostProcess,
+    /// Blended geometry drawn over the post-processed image.
+    Transparent,
+    Overlay,
+    /// The egui overlay. Screenshots are taken right before it.
+    Ui,
+}
+
+impl FramePass {
+    pub const ALL: [Self; 7] = [
+        Self::Shadows,
+        Self::DepthPrepass,
+        Self::Main,
+        Self::PostProcess,
+        Self::Transparent,
+        Self::Overlay,
+        Self::Ui,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Shadows => "Shadows",
+            Self::DepthPrepass => "DepthPrepass",
+            Self::Main => "Main",
+            Self::PostProcess => "PostProcess",
+            Self::Transparent => "Transparent",
+            Self::Overlay => "Overlay",
+            Self::Ui => "Ui",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Placement {
+    Before,
+    After,
+}
+
+struct CustomPass {
+    name: String,
+    anchor: FramePass,
+    placement: Placement,
+    run: FramePassFn,
+}
+
+/// Custom passes in registration order.
+#[derive(Default)]
+pub(crate) struct FramePasses {
+    passes: Vec<CustomPass>,
+}
+
+impl FramePasses {
+    pub(crate) fn insert(
+        &mut self,
+        anchor: &str,
+        placement: Placement,
+        name: &str,
+        run: FramePassFn,
+    ) -> Result<(), String> {
+        let Some(anchor) = FramePass::from_name(anchor) else {
+            return Err(format!("Unknown frame pass '{anchor}'"));
+        };
+        if FramePass::from_name(name).is_some() || self.passes.iter().any(|pass| pass.name == name)
+        {
+            return Err(format!("Frame pass '{name}' already exists"));
+        }
+        self.passes.push(CustomPass {
+            name: name.to_owned(),
+            anchor,
+            placement,
+            run,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let count = self.passes.len();
+        self.passes.retain(|pass| pass.name != name);
+        self.passes.len() != count
+    }
+
+    /// Built-in and custom pass names in the order they record.
+    pub(crate) fn order(&self) -> Vec<&str> {
+        let mut order = Vec::with_capacity(FramePass::ALL.len() + self.passes.len());
+        for builtin in FramePass::ALL {
+            order.extend(self.names_at(builtin, Placement::Before));
+            order.push(builtin.name());
+            order.extend(self.names_at(builtin, Placement::After));
+        }
+        order
+    }
+
+    fn names_at(&self, anchor: FramePass, placement: Placement) -> impl Iterator<Item = &str> {
+        self.passes
+            .iter()
+            .filter(move |pass| pass.anchor == anchor && pass.placement == placement)
+            .map(|pass| pass.name.as_str())
+    }
+
+    /// Runs the passes anchored at `placement` of `anchor`.
+    pub(crate) fn run(
+        &mut self,
+        anchor: FramePass,
+        placement: Placement,
+        ctx: &mut FramePassContext<'_>,
+    ) {
+        for pass in &mut self.passes {
+            if pass.anchor == anchor && pass.placement == placement {
+                (pass.run)(ctx);
+            }
+        }
+    }
+}
+
+/// What a custom pass records with.
+pub struct FramePassContext<'a> {
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub renderer: &'a Renderer,
+    pub assets: &'a Assets,
+    pub batches: FrameBatches<'a>,
+    /// Colour target at this point of the frame: the scene target up to
+    /// [`FramePass::PostProcess`], its output afterwards and the surface
+    /// around [`FramePass::Ui`]. Always in the surface format.
+    pub color_view: &'a wgpu::TextureView,
+    /// Single-sampled view `color_view` resolves into while MSAA is on.
+    pub resolve_target: Option<&'a wgpu::TextureView>,
+    /// The shared scene depth and stencil, `None` where its size or sample
+    /// count differs from `color_view`.
+    pub depth_view: Option<&'a wgpu::TextureView>,
+    pub sample_count: u32,
+}
+
+impl FramePassContext<'_> {
+    /// Begins a pass that loads the colour target and, when it matches,
+    /// the scene depth and stencil.
+    pub fn begin_render_pass(&mut self, label: &str) -> wgpu::RenderPass<'_> {
+        self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: self.color_view,
+                depth_slice: None,
+                resolve_target: self.resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: self.depth_view.map(|view| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
+                }
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+}
+
+/// Read-only view of the batches prepared for this frame.
+#[derive(Clone, Copy)]
+pub struct FrameBatches<'a> {
+    prepared: &'a PreparedBatches,
+}
+
+impl<'a> FrameBatches<'a> {
+    pub(crate) fn new(prepared: &'a PreparedBatches) -> Self {
+        Self { prepared }
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = FrameBatch<'a>> {
+        self.prepared.all().iter().map(FrameBatch)
+    }
+
+    pub fn opaque(&self) -> impl Iterator<Item = FrameBatch<'a>> {
+        self.prepared.opaque().iter().map(FrameBatch)
+    }
+
+    pub fn transparent(&self) -> impl Iterator<Item = FrameBatch<'a>> {
+        self.prepared.transparent().iter().map(FrameBatch)
+    }
+
+    pub fn overlay(&self) -> impl Iterator<Item = FrameBatch<'a>> {
+        self.prepared.overlay().iter().map(FrameBatch)
+    }
+
+    /// Materials indexed by [`InstanceData::material_index`].
+    pub fn materials(&self) -> &'a [Material] {
+        self.prepared.materials()
+    }
+}
+
+/// One mesh drawn with one set of pipeline state.
+#[derive(Clone, Copy)]
+pub struct FrameBatch<'a>(&'a OrderedBatch);
+
+impl<'a> FrameBatch<'a> {
+    pub fn mesh(&self) -> Handle<Mesh> {
+        self.0.mesh
+    }
+
+    pub fn pass(&self) -> RenderPass {
+        self.0.pass
+    }
+
+    pub fn depth_state(&self) -> DepthState {
+        self.0.depth_state
+    }
+
+    pub fn double_sided(&self) -> bool {
+        self.0.double_sided
+    }
+
+    pub fn instances(&self) -> &'a [InstanceData] {
+        &self.0.instances
+    }
+
+    /// Index of the first instance in the renderer's objects buffer; the
+    /// others follow it.
+    pub fn first_instance(&self) -> u32 {
+        self.0.first_instance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FramePass, FramePasses, Placement};
+
+    fn noop() -> super::FramePassFn {
+        Box::new(|_| {})
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for pass in FramePass::ALL {
+            assert_eq!(FramePass::from_name(pass.name()), Some(pass));
+        }
+        assert_eq!(FramePass::from_name("main"), None);
+    }
+
+    #[test]
+    fn custom_passes_keep_registration_order_around_their_anchor() {
+        let mut passes = FramePasses::default();
+        passes
+            .insert("Main", Placement::After, "outline", noop())
+            .unwrap();
+        passes
+            .insert("Main", Placement::Before, "sky", noop())
+            .unwrap();
+        passes
+            .insert("Main", Placement::After, "decals", noop())
+            .unwrap();
+
+        let order = passes.order();
+        let main = order.iter().position(|&name| name == "Main").unwrap();
+        assert_eq!(
+            &order[main - 1..main + 3],
+            &["sky", "Main", "outline", "decals"]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_anchors_and_duplicate_names() {
+        let mut passes = FramePasses::default();
+        assert!(passes
+            .insert("Bloom", Placement::Before, "glow", noop())
+            .is_err());
+        passes
+            .insert("Overlay", Placement::Before, "glow", noop())
+            .unwrap();
+        assert!(passes
+            .insert("Ui", Placement::After, "glow", noop())
+            .is_err());
+        assert!(passes
+            .insert("Ui", Placement::After, "Main", noop())
+            .is_err());
+
+        assert!(passes.remove("glow"));
+        assert!(!passes.remove("glow"));
+        assert_eq!(passes.order().len(), FramePass::ALL.len());
+    }
+}
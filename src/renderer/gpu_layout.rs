@@ -0,0 +1,272 @@
+// renderer/gpu_layout.rs
+// Rust-side source of truth for GPU structs shared with WGSL.
+//
+// Each GPU-visible struct lists its fields here together with the matching
+// WGSL type. The list drives both compile-time size checks and the WGSL
+// struct definitions prepended to the main shader, so a field added on the
+// Rust side can no longer silently misalign the shader.
+
+use std::fmt::Write;
+
+use crate::renderer::lights::{
+    DirectionalLightRaw, DirectionalShadowRaw, LightsUniform, PointLightRaw, PointShadowRaw,
+    ShadowsUniform, SpotLightRaw, SpotShadowRaw, MAX_DIRECTIONAL_LIGHTS, MAX_POINT_LIGHTS,
+    MAX_SHADOW_CASCADES, MAX_SPOT_LIGHTS,
+};
+use crate::renderer::objects::{MaterialData, ObjectData};
+use crate::renderer::uniforms::{CameraUniform, EnvironmentUniform};
+
+/// Bumped whenever any struct below changes layout.
+pub const GPU_LAYOUT_VERSION: u32 = 13;
+
+/// Number of cube faces rendered per point light shadow.
+pub const POINT_SHADOW_FACE_COUNT: usize = 6;
+
+#[derive(Debug, Clone, Copy)]
+pub struct WgslMember {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WgslStruct {
+    pub name: &'static str,
+    pub size: usize,
+    pub members: &'static [WgslMember],
+}
+
+impl WgslStruct {
+    pub fn to_wgsl(&self) -> String {
+        let mut out = format!("struct {} {{\n", self.name);
+        for member in self.members {
+            let _ = writeln!(out, "    {}: {},", member.name, member.ty);
+        }
+        out.push_str("};\n");
+        out
+    }
+}
+
+/// Implemented for every Rust struct that is uploaded to a GPU buffer and
+/// read by the main shader.
+pub trait GpuLayout {
+    const WGSL: WgslStruct;
+}
+
+/// Declares the WGSL mirror of a `#[repr(C)]` struct and asserts its size at
+/// compile time. Member offsets come from `offset_of!`, so the WGSL side is
+/// cross-checked against the real Rust layout in the tests below.
+macro_rules! gpu_layout {
+    ($ty:ty => $wgsl_name:literal, size = $size:expr, { $($field:ident : $wgsl_ty:expr),* $(,)? }) => {
+        const _: () = assert!(std::mem::size_of::<$ty>() == $size);
+        const _: () = assert!(std::mem::size_of::<$ty>() % 16 == 0);
+
+        impl GpuLayout for $ty {
+            const WGSL: WgslStruct = WgslStruct {
+                name: $wgsl_name,
+                size: std::mem::size_of::<$ty>(),
+                members: &[$(WgslMember {
+                    name: stringify!($field),
+                    ty: $wgsl_ty,
+                    offset: std::mem::offset_of!($ty, $field),
+                }),*],
+            };
+        }
+    };
+}
+
+gpu_layout!(CameraUniform => "Globals", size = 272, {
+    view_proj: "mat4x4<f32>",
+    inverse_view_proj: "mat4x4<f32>",
+    camera_pos: "vec3<f32>",
+    _padding: "f32",
+    unjittered_view_proj: "mat4x4<f32>",
+    prev_view_proj: "mat4x4<f32>",
+});
+
+gpu_layout!(EnvironmentUniform => "EnvironmentSettings", size = 32, {
+    flags_intensity: "vec4<f32>",
+    ambient_color: "vec4<f32>",
+});
+
+gpu_layout!(ObjectData => "Object", size = 160, {
+    model: "mat4x4<f32>",
+    material_index: "u32",
+    joint_offset: "u32",
+    morph_weight_offset: "u32",
+    morph_target_count: "u32",
+    morph_delta_offset: "u32",
+    morph_vertex_count: "u32",
+    _padding: "array<u32, 2>",
+    prev_model: "mat4x4<f32>",
+});
+
+gpu_layout!(MaterialData => "MaterialData", size = 144, {
+    color: "vec4<f32>",
+    base_color_texture: "u32",
+    metallic_roughness_texture: "u32",
+    normal_texture: "u32",
+    emissive_texture: "u32",
+    occlusion_texture: "u32",
+    material_flags: "u32",
+    metallic_factor: "f32",
+    roughness_factor: "f32",
+    emissive_color: "vec3<f32>",
+    transmission_factor: "f32",
+    transmission_texture: "u32",
+    alpha_cutoff: "f32",
+    clearcoat_factor: "f32",
+    clearcoat_roughness: "f32",
+    clearcoat_texture: "u32",
+    clearcoat_roughness_texture: "u32",
+    clearcoat_normal_texture: "u32",
+    anisotropy_strength: "f32",
+    anisotropy_rotation: "f32",
+    anisotropy_texture: "u32",
+    base_color_uv_rotation: "f32",
+    normal_uv_rotation: "f32",
+    base_color_uv_transform: "vec4<f32>",
+    normal_uv_transform: "vec4<f32>",
+});
+
+gpu_layout!(DirectionalLightRaw => "DirectionalLight", size = 32, {
+    direction: "vec4<f32>",
+    color_intensity: "vec4<f32>",
+});
+
+gpu_layout!(PointLightRaw => "PointLight", size = 48, {
+    position_range: "vec4<f32>",
+    color_intensity: "vec4<f32>",
+    attenuation: "vec4<f32>",
+});
+
+gpu_layout!(SpotLightRaw => "SpotLight", size = 64, {
+    position_range: "vec4<f32>",
+    direction: "vec4<f32>",
+    color_intensity: "vec4<f32>",
+    cone_params: "vec4<f32>",
+});
+
+gpu_layout!(LightsUniform => "Lights", size = 16 + 32 * MAX_DIRECTIONAL_LIGHTS + 48 * MAX_POINT_LIGHTS + 64 * MAX_SPOT_LIGHTS, {
+    counts: "vec4<u32>",
+    directionals: "array<DirectionalLight, MAX_DIRECTIONAL_LIGHTS>",
+    points: "array<PointLight, MAX_POINT_LIGHTS>",
+    spots: "array<SpotLight, MAX_SPOT_LIGHTS>",
+});
+
+gpu_layout!(DirectionalShadowRaw => "DirectionalShadow", size = 64 * MAX_SHADOW_CASCADES + 32, {
+    view_proj: "array<mat4x4<f32>, MAX_SHADOW_CASCADES>",
+    splits: "vec4<f32>",
+    params: "vec4<f32>",
+});
+
+gpu_layout!(PointShadowRaw => "PointShadow", size = 64 * POINT_SHADOW_FACE_COUNT + 16, {
+    view_proj: "array<mat4x4<f32>, POINT_SHADOW_FACE_COUNT>",
+    params: "vec4<f32>",
+});
+
+gpu_layout!(SpotShadowRaw => "SpotShadow", size = 80, {
+    view_proj: "mat4x4<f32>",
+    params: "vec4<f32>",
+});
+
+gpu_layout!(ShadowsUniform => "Shadows", size = 32 + (64 * MAX_SHADOW_CASCADES + 32) * MAX_DIRECTIONAL_LIGHTS + (64 * POINT_SHADOW_FACE_COUNT + 16) * MAX_POINT_LIGHTS + 80 * MAX_SPOT_LIGHTS, {
+    counts: "vec4<u32>",
+    normal_offsets: "vec4<f32>",
+    directionals: "array<DirectionalShadow, MAX_DIRECTIONAL_LIGHTS>",
+    points: "array<PointShadow, MAX_POINT_LIGHTS>",
+    spots: "array<SpotShadow, MAX_SPOT_LIGHTS>",
+});
+
+/// All structs emitted into the generated WGSL, in declaration order.
+pub const GPU_STRUCTS: &[WgslStruct] = &[
+    CameraUniform::WGSL,
+    EnvironmentUniform::WGSL,
+    ObjectData::WGSL,
+    MaterialData::WGSL,
+    DirectionalLightRaw::WGSL,
+    PointLightRaw::WGSL,
+    SpotLightRaw::WGSL,
+    LightsUniform::WGSL,
+    DirectionalShadowRaw::WGSL,
+    PointShadowRaw::WGSL,
+    SpotShadowRaw::WGSL,
+    ShadowsUniform::WGSL,
+];
+
+/// Builds the WGSL constants and struct definitions shared with Rust.
+/// Prepended to the main shader by `RenderPipeline::shader_source`.
+pub fn generated_structs_wgsl() -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated from the Rust GPU struct definitions. Do not edit by hand."
+    );
+    let _ = writeln!(
+        out,
+        "const GPU_LAYOUT_VERSION: u32 = {}u;",
+        GPU_LAYOUT_VERSION
+    );
+    for (name, value) in [
+        ("MAX_DIRECTIONAL_LIGHTS", MAX_DIRECTIONAL_LIGHTS),
+        ("MAX_POINT_LIGHTS", MAX_POINT_LIGHTS),
+        ("MAX_SPOT_LIGHTS", MAX_SPOT_LIGHTS),
+        ("POINT_SHADOW_FACE_COUNT", POINT_SHADOW_FACE_COUNT),
+        ("MAX_SHADOW_CASCADES", MAX_SHADOW_CASCADES),
+    ] {
+        let _ = writeln!(out, "const {}: u32 = {}u;", name, value);
+    }
+    for def in GPU_STRUCTS {
+        out.push('\n');
+        out.push_str(&def.to_wgsl());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_generated() -> naga::Module {
+        naga::front::wgsl::parse_str(&generated_structs_wgsl())
+            .expect("generated WGSL should parse")
+    }
+
+    #[test]
+    fn generated_wgsl_offsets_match_rust_layout() {
+        let module = parse_generated();
+
+        for def in GPU_STRUCTS {
+            let ty = module
+                .types
+                .iter()
+                .find(|(_, ty)| ty.name.as_deref() == Some(def.name))
+                .map(|(_, ty)| ty)
+                .unwrap_or_else(|| panic!("struct {} missing from generated WGSL", def.name));
+
+            let naga::TypeInner::Struct { members, span } = &ty.inner else {
+                panic!("{} is not a struct", def.name);
+            };
+
+            assert_eq!(*span as usize, def.size, "size mismatch for {}", def.name);
+            assert_eq!(members.len(), def.members.len(), "{}", def.name);
+            for (wgsl, rust) in members.iter().zip(def.members) {
+                assert_eq!(wgsl.name.as_deref(), Some(rust.name));
+                assert_eq!(
+                    wgsl.offset as usize, rust.offset,
+                    "offset mismatch for {}.{}",
+                    def.name, rust.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generated_wgsl_carries_layout_version() {
+        let source = generated_structs_wgsl();
+        assert!(source.contains(&format!(
+            "const GPU_LAYOUT_VERSION: u32 = {}u;",
+            GPU_LAYOUT_VERSION
+        )));
+    }
+}
@@ -1,7 +1,9 @@
 use std::{cmp::Ordering, ops::Range};
 
 use crate::asset::{Handle, Mesh};
-use crate::renderer::batch::{InstanceData, InstanceSource, RenderBatcher, RenderPass};
+use crate::renderer::batch::{
+    sort_back_to_front, InstanceData, InstanceSource, RenderBatcher, RenderPass,
+};
 use crate::renderer::material::Material;
 use crate::scene::components::DepthState;
 use glam::Vec3;
@@ -13,6 +15,11 @@ pub(crate) struct OrderedBatch {
     pub depth_state: DepthState,
     pub instances: Vec<InstanceData>,
     pub alpha_blend: bool,
+    /// Some instance uses an alpha-masked material, so depth-only passes
+    /// must sample its base colour and discard cut-out fragments.
+    pub alpha_mask: bool,
+    /// Drawn without back-face culling in every pass.
+    pub double_sided: bool,
     pub first_instance: u32,
 }
 
@@ -22,6 +29,10 @@ pub(crate) struct PreparedBatches {
     pub transparent_range: Range<usize>,
     pub overlay_range: Range<usize>,
     pub materials: Vec<Material>,
+    /// Instances that went through a back-to-front depth sort.
+    pub sorted_instances: u32,
+    /// Number of depth sorts performed while preparing this frame.
+    pub sort_operations: u32,
 }
 
 impl PreparedBatches {
@@ -30,6 +41,7 @@ impl PreparedBatches {
         let mut transparent = Vec::new();
         let mut overlay = Vec::new();
         let materials = batcher.materials();
+        let mut sorts = SortStats::default();
 
         for batch in batcher.iter() {
             if batch.instances.is_empty() {
@@ -39,7 +51,8 @@ impl PreparedBatches {
             let mut instances = batch.instances.to_vec();
 
             if batch.pass.requires_back_to_front_sort() {
-                sort_instances_back_to_front(&mut instances, camera_pos);
+                sort_back_to_front(&mut instances);
+                sorts.record(instances.len());
             }
             optimize_instance_order(batch.pass, &mut instances);
 
@@ -51,6 +64,12 @@ impl PreparedBatches {
                         .unwrap_or(false)
                 });
 
+            let alpha_mask = instances.iter().any(|inst| {
+                materials
+                    .get(inst.material_index as usize)
+                    .is_some_and(Material::is_alpha_masked)
+            });
+
             let mut depth_state = batch.depth_state;
             if alpha_blend {
                 // Keep depth testing but avoid writing so blended geometry layers correctly.
@@ -63,6 +82,8 @@ impl PreparedBatches {
                 depth_state,
                 instances,
                 alpha_blend,
+                alpha_mask,
+                double_sided: batch.double_sided,
                 first_instance: 0,
             };
 
@@ -83,8 +104,11 @@ impl PreparedBatches {
             }
         }
 
-        sort_batches_back_to_front(&mut transparent, camera_pos);
-        sort_batches_back_to_front(&mut overlay, camera_pos);
+        sort_batches_front_to_back(&mut opaque, camera_pos);
+        // Stencil tests run last so they see every silhouette marked this frame.
+        opaque.sort_by_key(|batch| batch.depth_state.stencil_test.is_some());
+        let transparent = interleave_back_to_front(transparent, &mut sorts);
+        let overlay = interleave_back_to_front(overlay, &mut sorts);
 
         let mut batches = Vec::with_capacity(opaque.len() + transparent.len() + overlay.len());
         let opaque_range = append_batches(&mut batches, opaque);
@@ -126,6 +150,8 @@ impl PreparedBatches {
             transparent_range,
             overlay_range,
             materials: materials.to_vec(),
+            sorted_instances: sorts.instances,
+            sort_operations: sorts.operations,
         }
     }
 
@@ -155,28 +181,123 @@ impl PreparedBatches {
     }
 }
 
-fn sort_instances_back_to_front(instances: &mut [InstanceData], camera_pos: Vec3) {
-    instances.sort_by(|a, b| {
-        let da = (a.transform.translation - camera_pos).length_squared();
-        let db = (b.transform.translation - camera_pos).length_squared();
-        db.partial_cmp(&da).unwrap_or(Ordering::Equal)
-    });
+impl OrderedBatch {
+    fn clone_without_instances(&self) -> Self {
+        Self {
+            mesh: self.mesh,
+            pass: self.pass,
+            depth_state: self.depth_state,
+            instances: Vec::new(),
+            alpha_blend: self.alpha_blend,
+            alpha_mask: self.alpha_mask,
+            double_sided: self.double_sided,
+            first_instance: self.first_instance,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SortStats {
+    instances: u32,
+    operations: u32,
 }
 
-fn sort_batches_back_to_front(batches: &mut [OrderedBatch], camera_pos: Vec3) {
+impl SortStats {
+    fn record(&mut self, instances: usize) {
+        self.instances += instances as u32;
+        self.operations += 1;
+    }
+}
+
+/// Orders blended batches back-to-front across meshes, not just within a
+/// batch, by the instances' sort keys. CPU instances are sorted globally and
+/// consecutive instances of the same batch are merged back into one draw;
+/// GPU-instanced batches keep their contiguous instance range and are placed
+/// by their farthest instance.
+fn interleave_back_to_front(
+    batches: Vec<OrderedBatch>,
+    sorts: &mut SortStats,
+) -> Vec<OrderedBatch> {
+    if batches.len() <= 1 {
+        return batches;
+    }
+
+    // (sort key, source batch, instance or `None` for a whole GPU batch)
+    let mut entries: Vec<(f32, usize, Option<InstanceData>)> = Vec::new();
+    for (index, batch) in batches.iter().enumerate() {
+        if batch
+            .instances
+            .iter()
+            .all(|inst| inst.source == InstanceSource::Gpu)
+        {
+            entries.push((farthest_sort_key(batch), index, None));
+        } else {
+            entries.extend(
+                batch
+                    .instances
+                    .iter()
+                    .map(|inst| (inst.sort_key, index, Some(*inst))),
+            );
+        }
+    }
+
+    entries.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    sorts.record(entries.len());
+
+    let mut ordered: Vec<OrderedBatch> = Vec::with_capacity(batches.len());
+    let mut last_source: Option<usize> = None;
+    for (_, index, instance) in entries {
+        let template = &batches[index];
+        match instance {
+            None => {
+                ordered.push(template.clone());
+                last_source = None;
+            }
+            Some(instance) => {
+                if last_source == Some(index) {
+                    if let Some(current) = ordered.last_mut() {
+                        current.instances.push(instance);
+                        continue;
+                    }
+                }
+                ordered.push(OrderedBatch {
+                    instances: vec![instance],
+                    ..template.clone_without_instances()
+                });
+                last_source = Some(index);
+            }
+        }
+    }
+
+    ordered
+}
+
+fn sort_batches_front_to_back(batches: &mut [OrderedBatch], camera_pos: Vec3) {
     batches.sort_by(|a, b| {
-        farthest_distance_sq(b, camera_pos)
-            .partial_cmp(&farthest_distance_sq(a, camera_pos))
+        nearest_distance_sq(a, camera_pos)
+            .partial_cmp(&nearest_distance_sq(b, camera_pos))
             .unwrap_or(Ordering::Equal)
     });
 }
 
-fn farthest_distance_sq(batch: &OrderedBatch, camera_pos: Vec3) -> f32 {
+fn distance_sq(instance: &InstanceData, camera_pos: Vec3) -> f32 {
+    (instance.transform.translation - camera_pos).length_squared()
+}
+
+fn farthest_sort_key(batch: &OrderedBatch) -> f32 {
     batch
         .instances
         .iter()
-        .map(|inst| (inst.transform.translation - camera_pos).length_squared())
-        .fold(0.0, f32::max)
+        .map(|inst| inst.sort_key)
+        .fold(f32::NEG_INFINITY, f32::max)
+}
+
+fn nearest_distance_sq(batch: &OrderedBatch, camera_pos: Vec3) -> f32 {
+    batch
+        .instances
+        .iter()
+        .map(|inst| distance_sq(inst, camera_pos))
+        .fold(f32::INFINITY, f32::min)
 }
 
 fn append_batches(dest: &mut Vec<OrderedBatch>, src: Vec<OrderedBatch>) -> Range<usize> {
@@ -207,11 +328,12 @@ fn optimize_instance_order(pass: RenderPass, instances: &mut [InstanceData]) {
 mod tests {
     use super::*;
     use crate::asset::Handle;
-    use crate::renderer::batch::{InstanceSource, RenderObject};
+    use crate::renderer::batch::{material_sort_key, InstanceSource, RenderObject};
+    use crate::renderer::internal::PipelineKey;
     use crate::renderer::material::Material;
     use crate::scene::components::DepthState;
     use crate::scene::transform::Transform;
-    use glam::Vec3;
+    use glam::{Mat4, Vec3};
 
     #[test]
     fn empty_batches_are_skipped() {
@@ -225,6 +347,10 @@ mod tests {
             force_overlay: false,
             instance_source: InstanceSource::Cpu,
             gpu_index: None,
+            joint_offset: None,
+            morph_weight_offset: None,
+            entity: None,
+            sort_key: 0.0,
         });
 
         batcher.clear();
@@ -236,4 +362,208 @@ mod tests {
             "empty batch entries should not produce draw calls"
         );
     }
+
+    #[test]
+    fn stencil_tested_batches_draw_after_other_opaque_batches() {
+        let mut batcher = RenderBatcher::new();
+        let object_at = |mesh: usize, z: f32, depth_state: DepthState| RenderObject {
+            material: Material::white(),
+            depth_state,
+            ..glass_at(mesh, z)
+        };
+        // The outline shell is nearest, so a plain depth sort would draw it first.
+        batcher.add(object_at(
+            0,
+            -1.0,
+            DepthState::new(true, false).with_stencil_test(1),
+        ));
+        batcher.add(object_at(
+            1,
+            -2.0,
+            DepthState::default().with_stencil_write(1),
+        ));
+        batcher.add(object_at(2, -3.0, DepthState::default()));
+
+        let prepared = PreparedBatches::from_batcher(&batcher, Vec3::ZERO);
+        let order: Vec<usize> = prepared
+            .opaque()
+            .iter()
+            .map(|batch| batch.mesh.index())
+            .collect();
+        assert_eq!(order.last(), Some(&0));
+        assert_eq!(order.len(), 3);
+    }
+
+    /// Blended object at `z`, keyed for a camera at the origin looking down -Z.
+    fn glass_at(mesh: usize, z: f32) -> RenderObject {
+        RenderObject {
+            mesh: Handle::new(mesh),
+            material: Material::white().with_alpha(),
+            transform: Transform::from_trs(Vec3::new(0.0, 0.0, z), Default::default(), Vec3::ONE),
+            depth_state: DepthState::default(),
+            force_overlay: false,
+            instance_source: InstanceSource::Cpu,
+            gpu_index: None,
+            joint_offset: None,
+            morph_weight_offset: None,
+            entity: None,
+            sort_key: -z,
+        }
+    }
+
+    #[test]
+    fn transparent_objects_are_drawn_back_to_front_across_meshes() {
+        let mut batcher = RenderBatcher::new();
+        // Inserted out of order, with the middle object on a different mesh.
+        batcher.add(glass_at(0, -2.0));
+        batcher.add(glass_at(1, -5.0));
+        batcher.add(glass_at(0, -10.0));
+
+        let prepared = PreparedBatches::from_batcher(&batcher, Vec3::ZERO);
+        let order: Vec<(usize, f32)> = prepared
+            .transparent()
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .instances
+                    .iter()
+                    .map(move |inst| (batch.mesh.index(), inst.transform.translation.z))
+            })
+            .collect();
+
+        assert_eq!(order, vec![(0, -10.0), (1, -5.0), (0, -2.0)]);
+        assert_eq!(prepared.transparent().len(), 3);
+        assert!(prepared.sorted_instances >= 3);
+        assert!(prepared.sort_operations >= 1);
+    }
+
+    #[test]
+    fn sort_keys_follow_view_depth_for_blended_objects_only() {
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        let mut near = glass_at(0, 0.0);
+        let mut far = glass_at(0, 0.0);
+        near.update_sort_key(view, Vec3::new(3.0, 0.0, 4.0));
+        far.update_sort_key(view, Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(near.sort_key, 1.0);
+        assert_eq!(far.sort_key, 6.0);
+
+        let mut opaque = RenderObject {
+            material: Material::white(),
+            ..glass_at(0, 0.0)
+        };
+        opaque.update_sort_key(view, Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(opaque.sort_key, material_sort_key(&Material::white()));
+    }
+
+    #[test]
+    fn batcher_sorts_transparent_instances_on_request() {
+        let mut batcher = RenderBatcher::new();
+        for z in [-2.0, -10.0, -5.0] {
+            batcher.add(glass_at(0, z));
+        }
+
+        batcher.sort_transparent_back_to_front();
+        let order: Vec<f32> = batcher
+            .get_pass_instances(RenderPass::Transparent)
+            .iter()
+            .map(|inst| inst.transform.translation.z)
+            .collect();
+
+        assert_eq!(order, vec![-10.0, -5.0, -2.0]);
+    }
+
+    #[test]
+    fn wireframe_objects_get_their_own_batch() {
+        let mut batcher = RenderBatcher::new();
+        batcher.add(RenderObject {
+            material: Material::white(),
+            ..glass_at(0, -2.0)
+        });
+        batcher.add(RenderObject {
+            material: Material::white(),
+            depth_state: DepthState::default().with_wireframe(true),
+            ..glass_at(0, -2.0)
+        });
+
+        let prepared = PreparedBatches::from_batcher(&batcher, Vec3::ZERO);
+        let mut wireframe: Vec<bool> = prepared
+            .opaque()
+            .iter()
+            .map(|batch| batch.depth_state.wireframe)
+            .collect();
+        wireframe.sort();
+
+        assert_eq!(wireframe, vec![false, true]);
+    }
+
+    #[test]
+    fn double_sided_materials_get_their_own_batch_and_pipeline() {
+        let mut batcher = RenderBatcher::new();
+        batcher.add(RenderObject {
+            material: Material::white(),
+            ..glass_at(0, -2.0)
+        });
+        batcher.add(RenderObject {
+            material: Material::white().with_double_sided(true),
+            ..glass_at(0, -2.0)
+        });
+
+        let prepared = PreparedBatches::from_batcher(&batcher, Vec3::ZERO);
+        assert_eq!(prepared.opaque().len(), 2);
+        let key_for = |double_sided: bool| {
+            let batch = prepared
+                .opaque()
+                .iter()
+                .find(|batch| batch.double_sided == double_sided)
+                .unwrap();
+            PipelineKey::for_batch(batch, 1)
+        };
+
+        assert_ne!(key_for(true), key_for(false));
+        assert_eq!(key_for(true), key_for(false).with_double_sided(true));
+    }
+
+    #[test]
+    fn alpha_masked_objects_stay_in_the_opaque_set() {
+        let mut batcher = RenderBatcher::new();
+        batcher.add(RenderObject {
+            material: Material::white().with_alpha_mask(0.5),
+            ..glass_at(0, -2.0)
+        });
+
+        let prepared = PreparedBatches::from_batcher(&batcher, Vec3::ZERO);
+
+        assert!(prepared.transparent().is_empty());
+        let batch = &prepared.opaque()[0];
+        assert!(batch.alpha_mask);
+        assert!(!batch.alpha_blend);
+        assert!(batch.depth_state.depth_write);
+    }
+
+    #[test]
+    fn same_mesh_with_different_materials_shares_one_batch() {
+        let mut batcher = RenderBatcher::new();
+        batcher.add(RenderObject {
+            material: Material::new([255, 0, 0, 255]),
+            ..glass_at(0, -2.0)
+        });
+        batcher.add(RenderObject {
+            material: Material::new([0, 0, 255, 255]),
+            ..glass_at(0, -4.0)
+        });
+
+        let prepared = PreparedBatches::from_batcher(&batcher, Vec3::ZERO);
+
+        assert_eq!(prepared.opaque().len(), 1);
+        let instances = &prepared.opaque()[0].instances;
+        assert_eq!(instances.len(), 2);
+        assert_ne!(instances[0].material_index, instances[1].material_index);
+
+        let mut colors: Vec<[u8; 4]> = instances
+            .iter()
+            .map(|inst| prepared.materials()[inst.material_index as usize].base_color)
+            .collect();
+        colors.sort();
+        assert_eq!(colors, vec![[0, 0, 255, 255], [255, 0, 0, 255]]);
+    }
 }
@@ -1,27 +1,70 @@
+use std::collections::HashMap;
 use std::mem;
 use std::num::NonZeroU64;
+use std::ops::Range;
 
 use bytemuck::Zeroable;
+use glam::Mat4;
 use wgpu::util::DeviceExt;
 
+use crate::asset::Assets;
 use crate::renderer::internal::{
     environment::EnvironmentResources, OrderedBatch, RenderContext, ShadowResources,
+    TransmissionResources,
 };
 use crate::renderer::lights::{LightsData, LightsUniform, ShadowsUniform};
 use crate::renderer::material::Material;
 use crate::renderer::uniforms::CameraUniform;
-use crate::renderer::{batch::InstanceSource, MaterialData, ObjectData};
+use crate::renderer::{batch::InstanceSource, MaterialData, MorphDelta, ObjectData};
+use crate::settings::ShadowSettings;
 
 pub(crate) struct DynamicObjectsBuffer {
     pub(crate) objects: wgpu::Buffer,
     pub(crate) materials: wgpu::Buffer,
+    pub(crate) joints: wgpu::Buffer,
+    pub(crate) morph_deltas: wgpu::Buffer,
+    pub(crate) morph_weights: wgpu::Buffer,
     pub(crate) object_capacity: u32,
     pub(crate) material_capacity: u32,
+    pub(crate) joint_capacity: u32,
+    pub(crate) morph_delta_capacity: u32,
+    pub(crate) morph_delta_len: u32,
+    pub(crate) morph_weight_capacity: u32,
     pub(crate) bind_group: wgpu::BindGroup,
     pub(crate) bind_layout: wgpu::BindGroupLayout,
     pub(crate) object_scratch: Vec<ObjectData>,
     pub(crate) material_scratch: Vec<MaterialData>,
     cpu_segments: Vec<CpuSegment>,
+    /// Object data last written to each slot. `None` marks slots not yet
+    /// written or owned by GPU instances.
+    uploaded: Vec<Option<ObjectData>>,
+    /// Slots whose `uploaded` data has not been written to the GPU yet.
+    dirty: Vec<bool>,
+    /// Contiguous copy of one coalesced range of dirty slots.
+    staging: Vec<ObjectData>,
+    /// Material data last written to the materials buffer.
+    uploaded_materials: Vec<MaterialData>,
+    /// Model matrix each entity was drawn with last frame, the source of
+    /// `ObjectData::prev_model`.
+    previous_models: HashMap<hecs::Entity, Mat4>,
+    /// Model matrices drawn this frame; becomes `previous_models` after the
+    /// upload.
+    current_models: HashMap<hecs::Entity, Mat4>,
+}
+
+/// Clean slots a single object write may span to join two dirty ranges,
+/// trading a few redundant bytes for fewer `write_buffer` calls.
+const MAX_COALESCED_GAP: usize = 16;
+
+/// How many CPU instance slots an upload rewrote or left untouched.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ObjectUploadStats {
+    pub updated: u32,
+    pub reused: u32,
+    pub bytes_written: u64,
+    /// Bytes written to the object, material, joint and morph weight
+    /// buffers, including `bytes_written`.
+    pub bytes_uploaded: u64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -56,6 +99,36 @@ impl DynamicObjectsBuffer {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(mem::size_of::<Mat4>() as u64),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(mem::size_of::<MorphDelta>() as u64),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(mem::size_of::<f32>() as u64),
+                    },
+                    count: None,
+                },
             ],
         });
 
@@ -76,40 +149,76 @@ impl DynamicObjectsBuffer {
             mapped_at_creation: false,
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("ObjectsBindGroup"),
-            layout: &bind_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: objects.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: materials.as_entire_binding(),
-                },
-            ],
+        // Skinned meshes index into this palette; one identity matrix keeps the
+        // binding valid when nothing is skinned.
+        let joints = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("JointMatricesBuffer"),
+            contents: bytemuck::cast_slice(&[Mat4::IDENTITY]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
         });
 
+        // Morph target deltas of every morphable mesh, appended as meshes are
+        // created. The weights are rewritten each frame like the joint palette.
+        let morph_deltas = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("MorphDeltasBuffer"),
+            contents: bytemuck::cast_slice(&[MorphDelta::default()]),
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        let morph_weights = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("MorphWeightsBuffer"),
+            contents: bytemuck::cast_slice(&[0.0f32]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_layout,
+            [&objects, &materials, &joints, &morph_deltas, &morph_weights],
+        );
+
         Self {
             objects,
             materials,
+            joints,
+            morph_deltas,
+            morph_weights,
             object_capacity: capacity,
             material_capacity: capacity,
+            joint_capacity: 1,
+            morph_delta_capacity: 1,
+            morph_delta_len: 0,
+            morph_weight_capacity: 1,
             bind_group,
             bind_layout,
             object_scratch: Vec::with_capacity(capacity as usize),
             material_scratch: Vec::with_capacity(capacity as usize),
             cpu_segments: Vec::new(),
+            uploaded: Vec::new(),
+            dirty: Vec::new(),
+            staging: Vec::new(),
+            uploaded_materials: Vec::new(),
+            previous_models: HashMap::new(),
+            current_models: HashMap::new(),
         }
     }
 
+    /// Uploads this frame's object, material, joint and morph weight data.
+    /// Instance slots and materials whose data matches the last upload are
+    /// skipped; instance slots are rewritten anyway when `rewrite_all` is
+    /// set, which callers use when slots may have moved.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn update(
         &mut self,
         context: &RenderContext,
         batches: &[OrderedBatch],
+        assets: &Assets,
         materials: &[Material],
-    ) -> Result<(), wgpu::SurfaceError> {
+        joint_matrices: &[Mat4],
+        morph_weights: &[f32],
+        rewrite_all: bool,
+    ) -> Result<ObjectUploadStats, wgpu::SurfaceError> {
         self.object_scratch.clear();
         self.cpu_segments.clear();
 
@@ -117,6 +226,10 @@ impl DynamicObjectsBuffer {
         let mut total_instances: u32 = 0;
 
         for batch in batches {
+            let morph_targets = assets
+                .meshes
+                .get(batch.mesh)
+                .and_then(|mesh| mesh.morph_targets());
             for (local_index, inst) in batch.instances.iter().enumerate() {
                 let global_index = if inst.source == InstanceSource::Gpu {
                     inst.gpu_index
@@ -127,13 +240,32 @@ impl DynamicObjectsBuffer {
                 total_instances = total_instances.max(global_index + 1);
 
                 if inst.source == InstanceSource::Gpu {
+                    if let Some(slot) = self.uploaded.get_mut(global_index as usize) {
+                        *slot = None;
+                    }
                     if let Some(segment) = current_segment.take() {
                         self.cpu_segments.push(segment);
                     }
                     continue;
                 }
 
-                let data = ObjectData::new(inst.transform.matrix(), inst.material_index);
+                let model = inst.transform.matrix();
+                // Manual draws and newly drawn entities report no motion.
+                let prev_model = match inst.entity {
+                    Some(entity) => {
+                        self.current_models.insert(entity, model);
+                        self.previous_models.get(&entity).copied().unwrap_or(model)
+                    }
+                    None => model,
+                };
+                let mut data = ObjectData::new(model, inst.material_index)
+                    .with_prev_model(prev_model)
+                    .with_joint_offset(inst.joint_offset.unwrap_or(0));
+                if let (Some(targets), Some(weight_offset)) =
+                    (morph_targets, inst.morph_weight_offset)
+                {
+                    data = data.with_morph_targets(weight_offset, targets);
+                }
                 let scratch_index = self.object_scratch.len();
                 self.object_scratch.push(data);
 
@@ -161,20 +293,18 @@ impl DynamicObjectsBuffer {
         if let Some(segment) = current_segment.take() {
             self.cpu_segments.push(segment);
         }
+        mem::swap(&mut self.previous_models, &mut self.current_models);
+        self.current_models.clear();
 
         if total_instances > self.object_capacity {
             self.grow_objects(context, total_instances);
         }
 
-        for segment in &self.cpu_segments {
-            let start = segment.start_index as usize;
-            let offset = (start * mem::size_of::<ObjectData>()) as u64;
-            let end = segment.scratch_start + segment.length;
-            let slice = &self.object_scratch[segment.scratch_start..end];
-            context
-                .queue
-                .write_buffer(&self.objects, offset, bytemuck::cast_slice(slice));
+        if rewrite_all {
+            self.uploaded.clear();
         }
+        let mut stats = self.write_changed_objects(context);
+        stats.bytes_uploaded = stats.bytes_written;
 
         self.material_scratch.clear();
         self.material_scratch
@@ -185,15 +315,147 @@ impl DynamicObjectsBuffer {
             self.grow_materials(context, required_materials);
         }
 
-        if !self.material_scratch.is_empty() {
+        if let Some(range) = changed_range(&self.uploaded_materials, &self.material_scratch) {
+            let bytes: &[u8] = bytemuck::cast_slice(&self.material_scratch[range.clone()]);
             context.queue.write_buffer(
                 &self.materials,
-                0,
-                bytemuck::cast_slice(&self.material_scratch),
+                (range.start * mem::size_of::<MaterialData>()) as u64,
+                bytes,
             );
+            stats.bytes_uploaded += bytes.len() as u64;
+        }
+        self.uploaded_materials.clear();
+        self.uploaded_materials
+            .extend_from_slice(&self.material_scratch);
+
+        let required_joints = joint_matrices.len() as u32;
+        if required_joints > self.joint_capacity {
+            self.grow_joints(context, required_joints);
+        }
+
+        if !joint_matrices.is_empty() {
+            let bytes: &[u8] = bytemuck::cast_slice(joint_matrices);
+            context.queue.write_buffer(&self.joints, 0, bytes);
+            stats.bytes_uploaded += bytes.len() as u64;
+        }
+
+        let required_weights = morph_weights.len() as u32;
+        if required_weights > self.morph_weight_capacity {
+            self.grow_morph_weights(context, required_weights);
+        }
+
+        if !morph_weights.is_empty() {
+            let bytes: &[u8] = bytemuck::cast_slice(morph_weights);
+            context.queue.write_buffer(&self.morph_weights, 0, bytes);
+            stats.bytes_uploaded += bytes.len() as u64;
+        }
+
+        Ok(stats)
+    }
+
+    /// Marks the CPU slots whose scratch data differs from what was last
+    /// uploaded as dirty, then writes the dirty slots with one `write_buffer`
+    /// per coalesced range. Nothing is written when no slot is dirty.
+    fn write_changed_objects(&mut self, context: &RenderContext) -> ObjectUploadStats {
+        let mut stats = ObjectUploadStats::default();
+        for segment in &self.cpu_segments {
+            let scratch = &self.object_scratch[segment.scratch_start..][..segment.length];
+            let start = segment.start_index as usize;
+            let end = start + scratch.len();
+            if self.uploaded.len() < end {
+                self.uploaded.resize(end, None);
+            }
+            if self.dirty.len() < end {
+                self.dirty.resize(end, false);
+            }
+
+            for ((slot, dirty), data) in self.uploaded[start..end]
+                .iter_mut()
+                .zip(&mut self.dirty[start..end])
+                .zip(scratch)
+            {
+                let unchanged = slot.as_ref().is_some_and(|previous| {
+                    bytemuck::bytes_of(previous) == bytemuck::bytes_of(data)
+                });
+                if !unchanged {
+                    *slot = Some(*data);
+                    *dirty = true;
+                    stats.updated += 1;
+                }
+            }
+        }
+        stats.reused = self.object_scratch.len() as u32 - stats.updated;
+        if stats.updated == 0 {
+            return stats;
         }
 
-        Ok(())
+        for range in dirty_ranges(&self.dirty, &self.uploaded, MAX_COALESCED_GAP) {
+            self.staging.clear();
+            self.staging
+                .extend(self.uploaded[range.clone()].iter().flatten());
+            let offset = (range.start * mem::size_of::<ObjectData>()) as u64;
+            let bytes: &[u8] = bytemuck::cast_slice(&self.staging);
+            context.queue.write_buffer(&self.objects, offset, bytes);
+            stats.bytes_written += bytes.len() as u64;
+        }
+        self.dirty.fill(false);
+        stats
+    }
+
+    /// Appends a mesh's morph target deltas and returns the index of the
+    /// first one. Growing the buffer copies the existing deltas on the GPU.
+    pub(crate) fn append_morph_deltas(
+        &mut self,
+        context: &RenderContext,
+        deltas: &[MorphDelta],
+    ) -> u32 {
+        let offset = self.morph_delta_len;
+        let required = offset + deltas.len() as u32;
+        if required > self.morph_delta_capacity {
+            let new_capacity = required.max(self.morph_delta_capacity * 2);
+            log::info!(
+                "Growing morph deltas buffer: {} -> {}",
+                self.morph_delta_capacity,
+                new_capacity
+            );
+
+            let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("MorphDeltasBuffer"),
+                size: (new_capacity as usize * mem::size_of::<MorphDelta>()) as u64,
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            if offset > 0 {
+                let mut encoder =
+                    context
+                        .device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("MorphDeltasGrow"),
+                        });
+                encoder.copy_buffer_to_buffer(
+                    &self.morph_deltas,
+                    0,
+                    &buffer,
+                    0,
+                    (offset as usize * mem::size_of::<MorphDelta>()) as u64,
+                );
+                context.queue.submit(Some(encoder.finish()));
+            }
+
+            self.morph_deltas = buffer;
+            self.morph_delta_capacity = new_capacity;
+            self.rebuild_bind_group(context);
+        }
+
+        context.queue.write_buffer(
+            &self.morph_deltas,
+            (offset as usize * mem::size_of::<MorphDelta>()) as u64,
+            bytemuck::cast_slice(deltas),
+        );
+        self.morph_delta_len = required;
+        offset
     }
 
     fn grow_objects(&mut self, context: &RenderContext, required: u32) {
@@ -213,6 +475,9 @@ impl DynamicObjectsBuffer {
         });
 
         self.object_capacity = new_capacity;
+        // The new buffer starts out empty.
+        self.uploaded.clear();
+        self.dirty.clear();
         self.rebuild_bind_group(context);
     }
 
@@ -233,26 +498,83 @@ impl DynamicObjectsBuffer {
         });
 
         self.material_capacity = new_capacity;
+        // The new buffer starts out empty.
+        self.uploaded_materials.clear();
+        self.rebuild_bind_group(context);
+    }
+
+    fn grow_joints(&mut self, context: &RenderContext, required: u32) {
+        let new_capacity = required.max(self.joint_capacity * 2);
+        log::info!(
+            "Growing joint matrices buffer: {} -> {}",
+            self.joint_capacity,
+            new_capacity
+        );
+
+        let buffer_size = (new_capacity as usize * mem::size_of::<Mat4>()) as u64;
+        self.joints = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("JointMatricesBuffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        self.joint_capacity = new_capacity;
+        self.rebuild_bind_group(context);
+    }
+
+    fn grow_morph_weights(&mut self, context: &RenderContext, required: u32) {
+        let new_capacity = required.max(self.morph_weight_capacity * 2);
+        log::info!(
+            "Growing morph weights buffer: {} -> {}",
+            self.morph_weight_capacity,
+            new_capacity
+        );
+
+        let buffer_size = (new_capacity as usize * mem::size_of::<f32>()) as u64;
+        self.morph_weights = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("MorphWeightsBuffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        self.morph_weight_capacity = new_capacity;
         self.rebuild_bind_group(context);
     }
 
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        buffers: [&wgpu::Buffer; 5],
+    ) -> wgpu::BindGroup {
+        let entries: Vec<wgpu::BindGroupEntry> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ObjectsBindGroup"),
+            layout,
+            entries: &entries,
+        })
+    }
+
     fn rebuild_bind_group(&mut self, context: &RenderContext) {
-        self.bind_group = context
-            .device
-            .create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("ObjectsBindGroup"),
-                layout: &self.bind_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: self.objects.as_entire_binding(),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: self.materials.as_entire_binding(),
-                    },
-                ],
-            });
+        self.bind_group = Self::create_bind_group(
+            &context.device,
+            &self.bind_layout,
+            [
+                &self.objects,
+                &self.materials,
+                &self.joints,
+                &self.morph_deltas,
+                &self.morph_weights,
+            ],
+        );
     }
 
     pub(crate) fn ensure_capacity(&mut self, context: &RenderContext, required: u32) {
@@ -266,6 +588,42 @@ impl DynamicObjectsBuffer {
     }
 }
 
+/// Ranges covering every dirty slot. A range extends over up to `max_gap`
+/// clean slots to reach the next dirty one, but never over slots without
+/// uploaded data, which may be owned by GPU instances.
+fn dirty_ranges(
+    dirty: &[bool],
+    uploaded: &[Option<ObjectData>],
+    max_gap: usize,
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for index in (0..dirty.len()).filter(|&index| dirty[index]) {
+        match ranges.last_mut() {
+            Some(range)
+                if index - range.end <= max_gap
+                    && uploaded[range.end..index].iter().all(Option::is_some) =>
+            {
+                range.end = index + 1
+            }
+            _ => ranges.push(index..index + 1),
+        }
+    }
+    ranges
+}
+
+/// The span from the first to the last element of `current` that differs
+/// from `uploaded` or lies past its end, or `None` if nothing changed.
+fn changed_range<T: bytemuck::Pod>(uploaded: &[T], current: &[T]) -> Option<Range<usize>> {
+    let changed = |index: &usize| {
+        uploaded.get(*index).is_none_or(|previous| {
+            bytemuck::bytes_of(previous) != bytemuck::bytes_of(&current[*index])
+        })
+    };
+    let start = (0..current.len()).find(changed)?;
+    let end = (start..current.len()).rfind(changed)? + 1;
+    Some(start..end)
+}
+
 pub(crate) struct CameraBuffer {
     pub(crate) buffer: wgpu::Buffer,
     pub(crate) bind_group: wgpu::BindGroup,
@@ -326,6 +684,7 @@ impl LightsBuffer {
         device: &wgpu::Device,
         shadows: &ShadowResources,
         environment: &EnvironmentResources,
+        transmission: &TransmissionResources,
     ) -> Self {
         let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("LightsBindLayout"),
@@ -417,7 +776,7 @@ impl LightsBuffer {
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
                         sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
+                        view_dimension: wgpu::TextureViewDimension::Cube,
                         multisampled: false,
                     },
                     count: None,
@@ -428,6 +787,58 @@ impl LightsBuffer {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 11,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 12,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 13,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 14,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 15,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 16,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
             ],
         });
 
@@ -452,6 +863,7 @@ impl LightsBuffer {
             &shadow_buffer,
             shadows,
             environment,
+            transmission,
         );
 
         Self {
@@ -469,6 +881,7 @@ impl LightsBuffer {
         shadow_buffer: &wgpu::Buffer,
         shadows: &ShadowResources,
         environment: &EnvironmentResources,
+        transmission: &TransmissionResources,
     ) -> wgpu::BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("LightsBindGroup"),
@@ -518,14 +931,44 @@ impl LightsBuffer {
                     binding: 10,
                     resource: wgpu::BindingResource::Sampler(environment.sampler()),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 11,
+                    resource: wgpu::BindingResource::TextureView(transmission.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 12,
+                    resource: wgpu::BindingResource::Sampler(transmission.sampler()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 13,
+                    resource: wgpu::BindingResource::TextureView(shadows.cookie_array_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 14,
+                    resource: wgpu::BindingResource::Sampler(shadows.cookie_sampler()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 15,
+                    resource: wgpu::BindingResource::TextureView(environment.irradiance_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 16,
+                    resource: wgpu::BindingResource::TextureView(environment.brdf_lut_view()),
+                },
             ],
         })
     }
 
-    pub(crate) fn update(&self, queue: &wgpu::Queue, lights: &LightsData) {
+    pub(crate) fn update(
+        &self,
+        queue: &wgpu::Queue,
+        lights: &LightsData,
+        shadow_settings: &ShadowSettings,
+        cascade_count: usize,
+    ) {
         let data = LightsUniform::from_data(lights);
         queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&data));
-        let shadow_data = ShadowsUniform::from_data(lights);
+        let shadow_data = ShadowsUniform::from_data(lights, shadow_settings, cascade_count);
 
         queue.write_buffer(&self.shadow_buffer, 0, bytemuck::bytes_of(&shadow_data));
     }
@@ -535,6 +978,7 @@ impl LightsBuffer {
         device: &wgpu::Device,
         shadows: &ShadowResources,
         environment: &EnvironmentResources,
+        transmission: &TransmissionResources,
     ) {
         self.bind_group = Self::create_bind_group(
             device,
@@ -543,6 +987,40 @@ impl LightsBuffer {
             &self.shadow_buffer,
             shadows,
             environment,
+            transmission,
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_slots_coalesce_across_small_uploaded_gaps() {
+        let object = ObjectData::new(Mat4::IDENTITY, 0);
+        let mut uploaded = vec![Some(object); 12];
+        let mut dirty = vec![false; 12];
+        assert!(dirty_ranges(&dirty, &uploaded, 2).is_empty());
+
+        for index in [1, 2, 5, 11] {
+            dirty[index] = true;
+        }
+        assert_eq!(dirty_ranges(&dirty, &uploaded, 2), vec![1..6, 11..12]);
+        assert_eq!(dirty_ranges(&dirty, &uploaded, 0), vec![1..3, 5..6, 11..12]);
+
+        // A GPU-owned slot in the gap splits the write.
+        uploaded[4] = None;
+        assert_eq!(dirty_ranges(&dirty, &uploaded, 2), vec![1..3, 5..6, 11..12]);
+    }
+
+    #[test]
+    fn changed_range_spans_first_to_last_difference() {
+        let uploaded = [1u32, 2, 3, 4];
+        assert_eq!(changed_range(&uploaded, &uploaded), None);
+        assert_eq!(changed_range(&uploaded, &[1, 9, 3, 8]), Some(1..4));
+        assert_eq!(changed_range(&uploaded, &[1, 2, 3, 4, 5]), Some(4..5));
+        assert_eq!(changed_range(&uploaded, &[1, 2]), None);
+        assert_eq!(changed_range(&[], &[7u32]), Some(0..1));
+    }
+}
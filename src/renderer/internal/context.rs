@@ -12,6 +12,10 @@ use winit::raw_window_handle::{
     WindowHandle as WinitWindowHandle,
 };
 
+use crate::renderer::internal::pipeline::{
+    bindless_texture_capacity, MIN_BINDLESS_TEXTURES, RESERVED_SAMPLED_TEXTURES,
+};
+use crate::renderer::postprocess::scaled_extent;
 use crate::renderer::Depth;
 use crate::settings::RenderSettings;
 
@@ -19,19 +23,32 @@ pub(crate) struct RenderContext {
     // Drop order: bottom to top (fields declared earlier drop last)
     // Keep instance alive for the lifetime of the surface and drop the surface before the window.
     pub(crate) _instance: wgpu::Instance,
+    // Kept to re-validate MSAA sample counts when they change at runtime.
+    adapter: wgpu::Adapter,
     pub(crate) size: PhysicalSize<u32>,
     pub(crate) config: wgpu::SurfaceConfiguration,
     pub(crate) supports_bindless_textures: bool,
+    // Length of the bindless texture array; unused by the classic model.
+    pub(crate) bindless_texture_capacity: u32,
+    // Gates GPU pass timings; see `renderer::timing`.
+    pub(crate) supports_timestamp_queries: bool,
     pub(crate) sample_count: u32,
+    // Scene targets, including `depth`, are this factor of the surface size.
+    pub(crate) resolution_scale: f32,
     // GPU resources (drop before device/queue)
     pub(crate) depth: Depth,
     // Device and queue (drop before surface)
     pub(crate) queue: wgpu::Queue,
     pub(crate) device: wgpu::Device,
+    // Offscreen color target used instead of a swapchain in headless mode
+    pub(crate) offscreen: Option<wgpu::Texture>,
     // Surface dropped last
-    pub(crate) surface: wgpu::Surface<'static>,
+    pub(crate) surface: Option<wgpu::Surface<'static>>,
 }
 
+/// Color format of the offscreen target rendered into by headless contexts.
+pub(crate) const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
 #[cfg(not(target_arch = "wasm32"))]
 type SharedWindow = Arc<Window>;
 #[cfg(target_arch = "wasm32")]
@@ -92,16 +109,7 @@ impl RenderContext {
         size: PhysicalSize<u32>,
         settings: &RenderSettings,
     ) -> Self {
-        let backends = if cfg!(target_arch = "wasm32") {
-            wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL
-        } else {
-            wgpu::Backends::all()
-        };
-
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends,
-            ..Default::default()
-        });
+        let instance = Self::create_instance();
         let surface = instance
             .create_surface(window_handle)
             .expect("Failed to create surface");
@@ -117,6 +125,169 @@ impl RenderContext {
             .await
             .expect("Failed to find adapter");
 
+        let (device, queue, bindless_capacity) = Self::request_device(&adapter).await;
+        let supports_timestamp_queries =
+            device.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+
+        let surface_caps = surface.get_capabilities(&adapter);
+
+        let format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(surface_caps.formats[0]);
+
+        let sample_count = Self::select_sample_count(&adapter, format, settings.sample_count);
+
+        let present_mode = settings.present_mode(&surface_caps.present_modes);
+
+        // Copyable swapchain images let the renderer capture frames for
+        // readback; not every platform offers them.
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC),
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode,
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        let resolution_scale = RenderSettings::sanitize_resolution_scale(settings.resolution_scale);
+        let depth = Depth::new(
+            &device,
+            Self::scaled_size(&device, size, resolution_scale),
+            sample_count,
+        );
+
+        Self {
+            _instance: instance,
+            adapter,
+            surface: Some(surface),
+            offscreen: None,
+            device,
+            queue,
+            config,
+            size,
+            depth,
+            supports_bindless_textures: bindless_capacity.is_some(),
+            bindless_texture_capacity: bindless_capacity.unwrap_or(MIN_BINDLESS_TEXTURES),
+            supports_timestamp_queries,
+            sample_count,
+            resolution_scale,
+        }
+    }
+
+    /// Creates a context without a window. Frames are rendered into an
+    /// internal [`HEADLESS_FORMAT`] texture instead of a swapchain.
+    ///
+    /// Returns `None` when no adapter is available (e.g. on CI machines
+    /// without a GPU or software rasterizer).
+    pub(crate) async fn new_headless(
+        size: PhysicalSize<u32>,
+        settings: &RenderSettings,
+    ) -> Option<Self> {
+        let instance = Self::create_instance();
+
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+        {
+            Ok(adapter) => adapter,
+            Err(err) => {
+                log::warn!("No adapter available for headless rendering: {}", err);
+                return None;
+            }
+        };
+
+        let (device, queue, bindless_capacity) = Self::request_device(&adapter).await;
+        let supports_timestamp_queries =
+            device.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+
+        let format = HEADLESS_FORMAT;
+        let sample_count = Self::select_sample_count(&adapter, format, settings.sample_count);
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        let offscreen = Self::create_offscreen_target(&device, &config);
+        let resolution_scale = RenderSettings::sanitize_resolution_scale(settings.resolution_scale);
+        let depth = Depth::new(
+            &device,
+            Self::scaled_size(&device, size, resolution_scale),
+            sample_count,
+        );
+
+        Some(Self {
+            _instance: instance,
+            adapter,
+            surface: None,
+            offscreen: Some(offscreen),
+            device,
+            queue,
+            config,
+            size,
+            depth,
+            supports_bindless_textures: bindless_capacity.is_some(),
+            bindless_texture_capacity: bindless_capacity.unwrap_or(MIN_BINDLESS_TEXTURES),
+            supports_timestamp_queries,
+            sample_count,
+            resolution_scale,
+        })
+    }
+
+    fn create_offscreen_target(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Color Target"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
+    }
+
+    fn create_instance() -> wgpu::Instance {
+        let backends = if cfg!(target_arch = "wasm32") {
+            wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL
+        } else {
+            wgpu::Backends::all()
+        };
+
+        wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        })
+    }
+
+    /// Also returns the bindless texture array length, `None` when the
+    /// classic per-material binding model is used.
+    async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue, Option<u32>) {
         log::info!("Using adapter: {:?}", adapter.get_info());
         log::info!("Using backend: {:?}", adapter.get_info().backend);
         let adapter_features = adapter.features();
@@ -125,20 +296,33 @@ impl RenderContext {
         let force_traditional = false;
 
         let mut required_features = wgpu::Features::empty();
-        let supports_bindless_textures = if force_traditional {
+        let bindless_capacity = if force_traditional {
             log::warn!("Bindless textures DISABLED (forced for testing)");
-            false
+            None
         } else if adapter_features
             .contains(wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING)
         {
-            required_features |=
-                wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
-                    | wgpu::Features::TEXTURE_BINDING_ARRAY;
-            log::info!("Bindless textures enabled");
-            true
+            let capacity = bindless_texture_capacity(&adapter.limits());
+            match capacity {
+                Some(capacity) => {
+                    required_features |=
+                        wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+                            | wgpu::Features::TEXTURE_BINDING_ARRAY;
+                    // Lets the texture array end at the last used slot.
+                    if adapter_features.contains(wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY) {
+                        required_features |= wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY;
+                    }
+                    log::info!("Bindless textures enabled ({} slots)", capacity);
+                }
+                None => log::warn!(
+                    "Bindless textures disabled: adapter allows fewer than {} textures per shader stage",
+                    MIN_BINDLESS_TEXTURES
+                ),
+            }
+            capacity
         } else {
             log::warn!("Bindless textures not supported");
-            false
+            None
         };
 
         if adapter_features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
@@ -149,14 +333,38 @@ impl RenderContext {
             required_features |= wgpu::Features::FLOAT32_FILTERABLE;
         }
 
-        let mut limits = if supports_bindless_textures {
-            wgpu::Limits {
-                max_binding_array_elements_per_shader_stage: 256,
-                ..wgpu::Limits::default()
+        if adapter_features.contains(wgpu::Features::POLYGON_MODE_LINE) {
+            required_features |= wgpu::Features::POLYGON_MODE_LINE;
+        } else {
+            log::info!("Wireframe rendering not supported");
+        }
+
+        for compression in [
+            wgpu::Features::TEXTURE_COMPRESSION_BC,
+            wgpu::Features::TEXTURE_COMPRESSION_ETC2,
+        ] {
+            if adapter_features.contains(compression) {
+                required_features |= compression;
             }
+        }
+
+        if adapter_features.contains(wgpu::Features::INDIRECT_FIRST_INSTANCE) {
+            required_features |= wgpu::Features::INDIRECT_FIRST_INSTANCE;
+        }
+
+        if adapter_features.contains(wgpu::Features::TIMESTAMP_QUERY) {
+            required_features |= wgpu::Features::TIMESTAMP_QUERY;
         } else {
-            wgpu::Limits::default()
-        };
+            log::info!("Timestamp queries not supported");
+        }
+
+        let mut limits = wgpu::Limits::default();
+        if let Some(capacity) = bindless_capacity {
+            limits.max_binding_array_elements_per_shader_stage = capacity;
+            limits.max_sampled_textures_per_shader_stage = limits
+                .max_sampled_textures_per_shader_stage
+                .max(capacity + RESERVED_SAMPLED_TEXTURES);
+        }
 
         limits.max_bind_groups = limits.max_bind_groups.max(4);
 
@@ -172,18 +380,17 @@ impl RenderContext {
             .await
             .expect("Failed to create device");
 
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.is_srgb())
-            .unwrap_or(surface_caps.formats[0]);
+        (device, queue, bindless_capacity)
+    }
 
+    fn select_sample_count(
+        adapter: &wgpu::Adapter,
+        format: wgpu::TextureFormat,
+        requested: u32,
+    ) -> u32 {
         let format_features = adapter.get_texture_format_features(format);
         let supported_sample_counts = format_features.flags.supported_sample_counts();
-        let requested_samples = settings.sample_count.max(1);
+        let requested_samples = RenderSettings::sanitize_sample_count(requested);
         let mut sample_count =
             Self::choose_supported_sample_count(requested_samples, &supported_sample_counts);
         if sample_count != requested_samples {
@@ -214,33 +421,7 @@ impl RenderContext {
             sample_count = 1;
         }
 
-        let present_mode = settings.present_mode(&surface_caps.present_modes);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width: size.width.max(1),
-            height: size.height.max(1),
-            present_mode,
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-        surface.configure(&device, &config);
-
-        let depth = Depth::new(&device, size, sample_count);
-
-        Self {
-            _instance: instance,
-            surface,
-            device,
-            queue,
-            config,
-            size,
-            depth,
-            supports_bindless_textures,
-            sample_count,
-        }
+        sample_count
     }
 
     fn choose_supported_sample_count(requested: u32, supported: &[u32]) -> u32 {
@@ -253,6 +434,37 @@ impl RenderContext {
             .unwrap_or(1)
     }
 
+    /// Switches to the closest sample count the color format supports and
+    /// recreates the depth buffer. Returns the count actually applied.
+    pub(crate) fn set_sample_count(&mut self, requested: u32) -> u32 {
+        let sample_count = Self::select_sample_count(&self.adapter, self.config.format, requested);
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.depth = Depth::new(&self.device, self.render_size(), sample_count);
+        }
+        sample_count
+    }
+
+    /// Size of the scene targets: the surface size times
+    /// `resolution_scale`, within the device's texture limits.
+    pub(crate) fn render_size(&self) -> PhysicalSize<u32> {
+        Self::scaled_size(&self.device, self.size, self.resolution_scale)
+    }
+
+    fn scaled_size(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        scale: f32,
+    ) -> PhysicalSize<u32> {
+        let extent = scaled_extent(
+            size.width,
+            size.height,
+            scale,
+            device.limits().max_texture_dimension_2d,
+        );
+        PhysicalSize::new(extent.width, extent.height)
+    }
+
     pub(crate) fn resize(&mut self, new_size: PhysicalSize<u32>) {
         if new_size.width == 0 || new_size.height == 0 {
             return;
@@ -260,8 +472,13 @@ impl RenderContext {
         self.size = new_size;
         self.config.width = new_size.width;
         self.config.height = new_size.height;
-        self.surface.configure(&self.device, &self.config);
-        self.depth = Depth::new(&self.device, new_size, self.sample_count);
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+        if self.offscreen.is_some() {
+            self.offscreen = Some(Self::create_offscreen_target(&self.device, &self.config));
+        }
+        self.depth = Depth::new(&self.device, self.render_size(), self.sample_count);
     }
 }
 
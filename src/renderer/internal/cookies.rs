@@ -0,0 +1,220 @@
+//! Spot light cookies: colour textures projected through a spot light's
+//! frustum. Each spot slot owns one layer of a texture array, filled by
+//! blitting the cookie's texture asset whenever the assignment changes.
+
+use crate::asset::Assets;
+use crate::renderer::lights::{LightsData, MAX_SPOT_LIGHTS};
+
+/// Edge length of every cookie layer; cookie textures are resampled to it.
+const COOKIE_SIZE: u32 = 256;
+const COOKIE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+pub(crate) struct SpotCookies {
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    layer_views: Vec<wgpu::TextureView>,
+    sampler: wgpu::Sampler,
+    bind_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    /// Texture asset currently blitted into each layer.
+    layers: [Option<u32>; MAX_SPOT_LIGHTS],
+    /// Texture cache revision the layers were blitted from.
+    texture_revision: u64,
+}
+
+impl SpotCookies {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("SpotCookieArray"),
+            size: wgpu::Extent3d {
+                width: COOKIE_SIZE,
+                height: COOKIE_SIZE,
+                depth_or_array_layers: MAX_SPOT_LIGHTS as u32,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: COOKIE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("SpotCookieArrayView"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let layer_views = (0..MAX_SPOT_LIGHTS as u32)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("SpotCookieLayer"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("SpotCookieSampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("SpotCookieBlitShader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../blit.wgsl").into()),
+        });
+
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("SpotCookieBlitLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("SpotCookieBlitPipelineLayout"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("SpotCookieBlitPipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: COOKIE_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            _texture: texture,
+            view,
+            layer_views,
+            sampler,
+            bind_layout,
+            pipeline,
+            layers: [None; MAX_SPOT_LIGHTS],
+            texture_revision: 0,
+        }
+    }
+
+    /// Blits the cookie texture of every spot whose assignment changed
+    /// into that spot's layer.
+    pub(crate) fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        assets: &Assets,
+        lights: &LightsData,
+    ) {
+        // A removed texture's slot may now hold a different image.
+        if assets.textures.revision() != self.texture_revision {
+            self.texture_revision = assets.textures.revision();
+            self.layers = [None; MAX_SPOT_LIGHTS];
+        }
+
+        for (layer, &cookie) in lights
+            .spot_cookie_textures()
+            .iter()
+            .take(MAX_SPOT_LIGHTS)
+            .enumerate()
+        {
+            if self.layers[layer] == cookie {
+                continue;
+            }
+            self.layers[layer] = cookie;
+
+            let Some(index) = cookie else {
+                continue;
+            };
+            let Some(texture) = assets.textures.get_by_index(index as usize) else {
+                log::warn!("Spot light cookie texture {} does not exist", index);
+                continue;
+            };
+
+            let source = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("SpotCookieBlitBindGroup"),
+                layout: &self.bind_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("SpotCookieBlit"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.layer_views[layer],
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &source, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+
+    pub(crate) fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub(crate) fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+}
@@ -0,0 +1,174 @@
+//! GPU side of [`DebugDraw`]: a growable vertex buffer refilled every frame
+//! and two unlit line pipelines. Depth tested lines draw into the scene
+//! before post-processing; on-top lines draw onto the final image after it.
+
+use std::mem;
+
+use crate::renderer::debug_draw::{DebugDraw, DebugLineVertex};
+use crate::renderer::gpu_layout::generated_structs_wgsl;
+use crate::renderer::internal::{CameraBuffer, RenderContext};
+use crate::renderer::PipelineBuilder;
+
+const INITIAL_VERTEX_CAPACITY: usize = 4096;
+
+pub(crate) struct DebugLineResources {
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    depth_tested_count: u32,
+    on_top_count: u32,
+    depth_tested_pipeline: wgpu::RenderPipeline,
+    on_top_pipeline: wgpu::RenderPipeline,
+}
+
+impl DebugLineResources {
+    pub(crate) fn new(context: &RenderContext, camera: &CameraBuffer, sample_count: u32) -> Self {
+        let depth_tested_pipeline = Self::build_pipeline(context, camera, Some(sample_count));
+        let on_top_pipeline = Self::build_pipeline(context, camera, None);
+        Self {
+            buffer: Self::create_buffer(&context.device, INITIAL_VERTEX_CAPACITY),
+            capacity: INITIAL_VERTEX_CAPACITY,
+            depth_tested_count: 0,
+            on_top_count: 0,
+            depth_tested_pipeline,
+            on_top_pipeline,
+        }
+    }
+
+    /// Recreates the scene pipeline after the MSAA sample count changed.
+    pub(crate) fn rebuild_pipelines(
+        &mut self,
+        context: &RenderContext,
+        camera: &CameraBuffer,
+        sample_count: u32,
+    ) {
+        self.depth_tested_pipeline = Self::build_pipeline(context, camera, Some(sample_count));
+    }
+
+    /// Uploads this frame's lines, growing the vertex buffer when needed.
+    pub(crate) fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, debug: &DebugDraw) {
+        let depth_tested = debug.depth_tested_vertices();
+        let on_top = debug.on_top_vertices();
+        let total = depth_tested.len() + on_top.len();
+
+        if total > self.capacity {
+            self.capacity = total.next_power_of_two();
+            self.buffer = Self::create_buffer(device, self.capacity);
+        }
+
+        if !depth_tested.is_empty() {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(depth_tested));
+        }
+        if !on_top.is_empty() {
+            let offset = mem::size_of_val(depth_tested) as u64;
+            queue.write_buffer(&self.buffer, offset, bytemuck::cast_slice(on_top));
+        }
+
+        self.depth_tested_count = depth_tested.len() as u32;
+        self.on_top_count = on_top.len() as u32;
+    }
+
+    pub(crate) fn has_depth_tested(&self) -> bool {
+        self.depth_tested_count > 0
+    }
+
+    pub(crate) fn has_on_top(&self) -> bool {
+        self.on_top_count > 0
+    }
+
+    /// Draws the depth tested lines into the multisampled scene pass; the
+    /// pass must bind the camera at group 0.
+    pub(crate) fn draw_depth_tested(&self, pass: &mut wgpu::RenderPass<'_>) {
+        if self.depth_tested_count > 0 {
+            pass.set_vertex_buffer(0, self.buffer.slice(..));
+            pass.set_pipeline(&self.depth_tested_pipeline);
+            pass.draw(0..self.depth_tested_count, 0..1);
+        }
+    }
+
+    /// Draws the on-top lines into a single-sampled pass without depth;
+    /// the pass must bind the camera at group 0.
+    pub(crate) fn draw_on_top(&self, pass: &mut wgpu::RenderPass<'_>) {
+        if self.on_top_count > 0 {
+            let start = self.depth_tested_count;
+            pass.set_vertex_buffer(0, self.buffer.slice(..));
+            pass.set_pipeline(&self.on_top_pipeline);
+            pass.draw(start..start + self.on_top_count, 0..1);
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("DebugLineVertices"),
+            size: (capacity * mem::size_of::<DebugLineVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// The scene pipeline with `sample_count` samples and depth testing, or
+    /// the overlay pipeline without depth for `None`.
+    fn build_pipeline(
+        context: &RenderContext,
+        camera: &CameraBuffer,
+        sample_count: Option<u32>,
+    ) -> wgpu::RenderPipeline {
+        let layout = context
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("DebugLinePipelineLayout"),
+                bind_group_layouts: &[&camera.bind_layout],
+                push_constant_ranges: &[],
+            });
+        let shader = context
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("DebugLineShader"),
+                source: wgpu::ShaderSource::Wgsl(shader_source().into()),
+            });
+
+        let builder = PipelineBuilder::new(&context.device, &layout, &shader)
+            .with_vertex_buffer(DebugLineVertex::layout())
+            .with_color_target(
+                context.config.format,
+                Some(wgpu::BlendState::ALPHA_BLENDING),
+            )
+            .with_topology(wgpu::PrimitiveTopology::LineList)
+            .with_no_culling();
+        match sample_count {
+            Some(sample_count) => builder
+                .with_label("DebugLinePipeline")
+                .with_depth_stencil(
+                    context.depth.format,
+                    false,
+                    wgpu::CompareFunction::LessEqual,
+                )
+                .with_multisample(sample_count)
+                .build(),
+            None => builder.with_label("DebugLineOverlayPipeline").build(),
+        }
+    }
+}
+
+fn shader_source() -> String {
+    format!(
+        "{}\n{}",
+        generated_structs_wgsl(),
+        include_str!("../../shader/debug_lines.wgsl")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_line_shader_parses() {
+        let source = shader_source();
+        if let Err(err) = naga::front::wgsl::parse_str(&source) {
+            panic!(
+                "Debug line shader failed to parse:\n{}",
+                err.emit_to_string(&source)
+            );
+        }
+    }
+}
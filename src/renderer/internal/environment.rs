@@ -3,24 +3,41 @@ use std::path::{Path, PathBuf};
 use bytemuck::bytes_of;
 use half::f16;
 
-use crate::environment::Environment;
+use crate::asset::Handle;
+use crate::environment::{Environment, EnvironmentMap};
+use crate::renderer::internal::ibl::{IblBaker, IblMaps};
 use crate::renderer::uniforms::EnvironmentUniform;
 
 pub(crate) struct EnvironmentResources {
     uniform: EnvironmentUniform,
     uniform_buffer: wgpu::Buffer,
     sampler: wgpu::Sampler,
-    fallback_texture: TextureResource,
-    hdr_texture: Option<TextureResource>,
+    baker: IblBaker,
+    fallback: IblMaps,
+    /// Maps baked from the scene environment's HDR background.
+    scene_maps: Option<LoadedMaps>,
     current_path: Option<PathBuf>,
-    current_view_is_hdr: bool,
-    current_max_lod: f32,
+    /// Maps set directly on the renderer; they win over the scene's.
+    override_maps: Option<LoadedMaps>,
+    /// Maps baked by [`Self::load`], indexed by `Handle<EnvironmentMap>`.
+    library: Vec<LoadedMaps>,
+    /// Library entry selected by the scene environment, if it resolves.
+    scene_map: Option<usize>,
+    scene_hdr_active: bool,
+    next_id: u64,
+    /// Id of the maps referenced by the lights bind group, 0 for the
+    /// fallback.
+    bound_id: u64,
+}
+
+struct LoadedMaps {
+    id: u64,
+    maps: IblMaps,
 }
 
 struct TextureResource {
     _texture: wgpu::Texture,
     view: wgpu::TextureView,
-    levels: u32,
 }
 
 impl EnvironmentResources {
@@ -34,9 +51,11 @@ impl EnvironmentResources {
         });
         queue.write_buffer(&uniform_buffer, 0, bytes_of(&uniform));
 
+        // Clamped so the BRDF lookup table does not wrap; cube sampling
+        // ignores the address modes.
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("EnvironmentSampler"),
-            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
             mag_filter: wgpu::FilterMode::Linear,
@@ -47,21 +66,55 @@ impl EnvironmentResources {
             ..Default::default()
         });
 
-        let fallback_texture = create_single_pixel_texture(device, queue, [0.0, 0.0, 0.0, 1.0]);
-        let fallback_max_lod = fallback_texture.levels.saturating_sub(1) as f32;
-
         Self {
             uniform,
             uniform_buffer,
             sampler,
-            fallback_texture,
-            hdr_texture: None,
+            baker: IblBaker::new(device, queue),
+            fallback: IblMaps::constant(device, queue, [0.0, 0.0, 0.0, 1.0]),
+            scene_maps: None,
             current_path: None,
-            current_view_is_hdr: false,
-            current_max_lod: fallback_max_lod,
+            override_maps: None,
+            library: Vec::new(),
+            scene_map: None,
+            scene_hdr_active: false,
+            next_id: 1,
+            bound_id: 0,
         }
     }
 
+    /// Bakes `image` and uses it instead of the scene's HDR background
+    /// until [`Self::clear_override`].
+    pub(crate) fn set_override(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: image::DynamicImage,
+    ) -> Result<(), String> {
+        let maps = self.bake(device, queue, image)?;
+        self.override_maps = Some(maps);
+        Ok(())
+    }
+
+    pub(crate) fn clear_override(&mut self) {
+        self.override_maps = None;
+    }
+
+    /// Bakes `image` and keeps it until the renderer is dropped, for scenes
+    /// to select with `Environment::set_environment_map`.
+    pub(crate) fn load(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: image::DynamicImage,
+    ) -> Result<Handle<EnvironmentMap>, String> {
+        let maps = self.bake(device, queue, image)?;
+        self.library.push(maps);
+        Ok(Handle::new(self.library.len() - 1))
+    }
+
+    /// Returns `true` when the bound cubemaps changed and the lights bind
+    /// group must be rebuilt.
     pub(crate) fn update(
         &mut self,
         device: &wgpu::Device,
@@ -70,58 +123,41 @@ impl EnvironmentResources {
     ) -> bool {
         let active_hdr = environment.active_hdr_background();
         let desired_path = active_hdr.map(|hdr| hdr.path().to_path_buf());
-        let needs_reload = match (&desired_path, &self.current_path, &self.hdr_texture) {
-            (Some(new_path), Some(current_path), Some(_)) if new_path == current_path => false,
-            (Some(_), _, _) => true,
-            (None, _, _) => false,
-        };
-
-        let mut texture_reloaded = false;
-
-        if needs_reload {
-            if let Some(path) = desired_path.as_ref() {
-                match load_hdr_texture(device, queue, path) {
-                    Ok(texture) => {
-                        self.hdr_texture = Some(texture);
-                        self.current_path = Some(path.clone());
-                        texture_reloaded = true;
-                    }
+        let needs_reload = desired_path.is_some() && desired_path != self.current_path;
+
+        if let Some(path) = desired_path.filter(|_| needs_reload) {
+            // Remember failed paths too so a missing file is reported once.
+            self.scene_maps =
+                match load_hdr_image(&path).and_then(|image| self.bake(device, queue, image)) {
+                    Ok(maps) => Some(maps),
                     Err(err) => {
                         log::error!("Failed to load HDR environment {:?}: {}", path, err);
-                        self.hdr_texture = None;
-                        self.current_path = None;
+                        None
                     }
-                }
-            } else {
-                self.hdr_texture = None;
-                self.current_path = None;
-            }
+                };
+            self.current_path = Some(path);
         }
 
-        let has_hdr_texture = self.hdr_texture.is_some();
-        let use_hdr = active_hdr.is_some() && has_hdr_texture;
-
-        let active_levels = if use_hdr {
-            self.hdr_texture
-                .as_ref()
-                .map(|tex| tex.levels)
-                .unwrap_or(self.fallback_texture.levels)
-        } else {
-            self.fallback_texture.levels
-        };
-        self.current_max_lod = active_levels.saturating_sub(1) as f32;
+        self.scene_hdr_active = active_hdr.is_some();
+        // Handles from another renderer fall through to the HDR background.
+        self.scene_map = environment
+            .environment_map()
+            .map(|handle| handle.index())
+            .filter(|&index| index < self.library.len());
 
+        let use_hdr = self.active_maps().is_some();
+        let max_lod = self.maps().specular_levels().saturating_sub(1) as f32;
         let hdr_intensity = active_hdr.map(|hdr| hdr.intensity()).unwrap_or(1.0);
-        let new_uniform = build_uniform(environment, use_hdr, hdr_intensity, self.current_max_lod);
+        let new_uniform = build_uniform(environment, use_hdr, hdr_intensity, max_lod);
         if new_uniform != self.uniform {
             self.uniform = new_uniform;
             queue.write_buffer(&self.uniform_buffer, 0, bytes_of(&self.uniform));
         }
 
-        let texture_changed = self.current_view_is_hdr != use_hdr || (use_hdr && texture_reloaded);
-        self.current_view_is_hdr = use_hdr;
-
-        texture_changed
+        let active_id = self.active_maps().map(|loaded| loaded.id).unwrap_or(0);
+        let changed = active_id != self.bound_id;
+        self.bound_id = active_id;
+        changed
     }
 
     pub(crate) fn uniform_buffer(&self) -> &wgpu::Buffer {
@@ -132,15 +168,44 @@ impl EnvironmentResources {
         &self.sampler
     }
 
+    /// Prefiltered specular cubemap; mip `n` holds roughness
+    /// `n / (levels - 1)`. Also drawn as the skybox.
     pub(crate) fn texture_view(&self) -> &wgpu::TextureView {
-        if self.current_view_is_hdr {
-            self.hdr_texture
-                .as_ref()
-                .map(|tex| &tex.view)
-                .unwrap_or(&self.fallback_texture.view)
-        } else {
-            &self.fallback_texture.view
-        }
+        self.maps().specular_view()
+    }
+
+    pub(crate) fn irradiance_view(&self) -> &wgpu::TextureView {
+        self.maps().irradiance_view()
+    }
+
+    pub(crate) fn brdf_lut_view(&self) -> &wgpu::TextureView {
+        self.baker.brdf_lut_view()
+    }
+
+    fn active_maps(&self) -> Option<&LoadedMaps> {
+        self.override_maps
+            .as_ref()
+            .or(self.scene_map.and_then(|index| self.library.get(index)))
+            .or(self.scene_maps.as_ref().filter(|_| self.scene_hdr_active))
+    }
+
+    fn maps(&self) -> &IblMaps {
+        self.active_maps()
+            .map(|loaded| &loaded.maps)
+            .unwrap_or(&self.fallback)
+    }
+
+    fn bake(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: image::DynamicImage,
+    ) -> Result<LoadedMaps, String> {
+        let equirect = upload_equirect(device, queue, image)?;
+        let maps = self.baker.bake(device, queue, &equirect.view);
+        let id = self.next_id;
+        self.next_id += 1;
+        Ok(LoadedMaps { id, maps })
     }
 }
 
@@ -162,15 +227,22 @@ fn build_uniform(
     }
 }
 
-fn load_hdr_texture(
+pub(crate) fn load_hdr_image(path: &Path) -> Result<image::DynamicImage, String> {
+    image::open(path).map_err(|err| format!("failed to open HDR image {:?}: {}", path, err))
+}
+
+pub(crate) fn decode_hdr_image(bytes: &[u8]) -> Result<image::DynamicImage, String> {
+    image::load_from_memory(bytes).map_err(|err| format!("failed to decode HDR image: {}", err))
+}
+
+/// Uploads an equirectangular panorama with a full mip chain, which the
+/// IBL bake samples to avoid aliasing.
+fn upload_equirect(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
-    path: &Path,
+    image: image::DynamicImage,
 ) -> Result<TextureResource, String> {
-    let image = image::open(path)
-        .map_err(|err| format!("failed to open HDR image {:?}: {}", path, err))?
-        .to_rgba32f();
-
+    let image = image.to_rgba32f();
     let (width, height) = image.dimensions();
     let raw = image.into_raw();
     let mut converted = Vec::with_capacity(raw.len());
@@ -232,7 +304,6 @@ fn load_hdr_texture(
     Ok(TextureResource {
         view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
         _texture: texture,
-        levels: mip_level_count,
     })
 }
 
@@ -399,59 +470,3 @@ fn generate_mipmaps(
 
     queue.submit(Some(encoder.finish()));
 }
-
-fn create_single_pixel_texture(
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
-    color: [f32; 4],
-) -> TextureResource {
-    let values: [u16; 4] = {
-        [
-            f16::from_f32(color[0]).to_bits(),
-            f16::from_f32(color[1]).to_bits(),
-            f16::from_f32(color[2]).to_bits(),
-            f16::from_f32(color[3]).to_bits(),
-        ]
-    };
-
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("EnvironmentFallbackTexture"),
-        size: wgpu::Extent3d {
-            width: 1,
-            height: 1,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba16Float,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        view_formats: &[],
-    });
-
-    queue.write_texture(
-        wgpu::TexelCopyTextureInfo {
-            texture: &texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-            aspect: wgpu::TextureAspect::All,
-        },
-        bytemuck::cast_slice(&values),
-        wgpu::TexelCopyBufferLayout {
-            offset: 0,
-            bytes_per_row: Some(8),
-            rows_per_image: Some(1),
-        },
-        wgpu::Extent3d {
-            width: 1,
-            height: 1,
-            depth_or_array_layers: 1,
-        },
-    );
-
-    TextureResource {
-        view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
-        _texture: texture,
-        levels: 1,
-    }
-}
@@ -0,0 +1,429 @@
+//! Image-based lighting bake. An equirectangular HDR panorama is converted
+//! into a cubemap whose mips hold the GGX-prefiltered radiance for
+//! increasing roughness, plus a small irradiance cubemap for diffuse
+//! lighting. The split-sum BRDF lookup table is baked once per renderer.
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Face size of the prefiltered specular cubemap's first mip.
+const SPECULAR_SIZE: u32 = 512;
+/// Mips of the specular cubemap; roughness goes linearly from 0 to 1.
+const SPECULAR_MIPS: u32 = 6;
+const IRRADIANCE_SIZE: u32 = 32;
+const BRDF_LUT_SIZE: u32 = 256;
+const PREFILTER_SAMPLES: u32 = 256;
+const IRRADIANCE_SAMPLES: u32 = 512;
+const CUBE_FACES: u32 = 6;
+
+const CUBE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+const BRDF_LUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg16Float;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct IblParams {
+    face: u32,
+    roughness: f32,
+    sample_count: u32,
+    face_size: u32,
+}
+
+/// Prefiltered specular and irradiance cubemaps of one environment.
+pub(crate) struct IblMaps {
+    _specular: wgpu::Texture,
+    specular_view: wgpu::TextureView,
+    _irradiance: wgpu::Texture,
+    irradiance_view: wgpu::TextureView,
+    specular_levels: u32,
+}
+
+impl IblMaps {
+    /// 1x1 cubemaps of a constant colour, bound while no HDR is loaded.
+    pub(crate) fn constant(device: &wgpu::Device, queue: &wgpu::Queue, color: [f32; 4]) -> Self {
+        let texel = color.map(|c| half::f16::from_f32(c).to_bits());
+        let faces: Vec<u16> = (0..CUBE_FACES).flat_map(|_| texel).collect();
+
+        let create = |label: &str| {
+            device.create_texture_with_data(
+                queue,
+                &wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: cube_extent(1),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: CUBE_FORMAT,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                },
+                wgpu::util::TextureDataOrder::LayerMajor,
+                bytemuck::cast_slice(&faces),
+            )
+        };
+        let specular = create("EnvironmentFallbackSpecular");
+        let irradiance = create("EnvironmentFallbackIrradiance");
+
+        Self {
+            specular_view: cube_view(&specular),
+            _specular: specular,
+            irradiance_view: cube_view(&irradiance),
+            _irradiance: irradiance,
+            specular_levels: 1,
+        }
+    }
+
+    pub(crate) fn specular_view(&self) -> &wgpu::TextureView {
+        &self.specular_view
+    }
+
+    pub(crate) fn irradiance_view(&self) -> &wgpu::TextureView {
+        &self.irradiance_view
+    }
+
+    pub(crate) fn specular_levels(&self) -> u32 {
+        self.specular_levels
+    }
+}
+
+pub(crate) struct IblBaker {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    equirect_pipeline: wgpu::RenderPipeline,
+    prefilter_pipeline: wgpu::RenderPipeline,
+    irradiance_pipeline: wgpu::RenderPipeline,
+    _brdf_lut: wgpu::Texture,
+    brdf_lut_view: wgpu::TextureView,
+}
+
+impl IblBaker {
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("IblShader"),
+            source: wgpu::ShaderSource::Wgsl(shader_source().into()),
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("IblBindGroupLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bake_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("IblPipelineLayout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let lut_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("IblBrdfLutPipelineLayout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = |layout: &wgpu::PipelineLayout, entry_point: &str, format| {
+            fullscreen_pipeline(device, layout, &shader, entry_point, format)
+        };
+        let equirect_pipeline = pipeline(&bake_layout, "fs_equirect_to_cube", CUBE_FORMAT);
+        let prefilter_pipeline = pipeline(&bake_layout, "fs_prefilter", CUBE_FORMAT);
+        let irradiance_pipeline = pipeline(&bake_layout, "fs_irradiance", CUBE_FORMAT);
+        let lut_pipeline = pipeline(&lut_layout, "fs_brdf_lut", BRDF_LUT_FORMAT);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("IblSourceSampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let brdf_lut = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("BrdfLut"),
+            size: wgpu::Extent3d {
+                width: BRDF_LUT_SIZE,
+                height: BRDF_LUT_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: BRDF_LUT_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let brdf_lut_view = brdf_lut.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("BrdfLutEncoder"),
+        });
+        draw_fullscreen(&mut encoder, &lut_pipeline, None, &brdf_lut_view);
+        queue.submit(Some(encoder.finish()));
+
+        Self {
+            layout,
+            sampler,
+            equirect_pipeline,
+            prefilter_pipeline,
+            irradiance_pipeline,
+            _brdf_lut: brdf_lut,
+            brdf_lut_view,
+        }
+    }
+
+    pub(crate) fn brdf_lut_view(&self) -> &wgpu::TextureView {
+        &self.brdf_lut_view
+    }
+
+    /// Converts a mip-mapped equirectangular panorama into IBL cubemaps.
+    pub(crate) fn bake(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        equirect: &wgpu::TextureView,
+    ) -> IblMaps {
+        let specular = create_cube(device, "EnvironmentSpecular", SPECULAR_SIZE, SPECULAR_MIPS);
+        let irradiance = create_cube(device, "EnvironmentIrradiance", IRRADIANCE_SIZE, 1);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("IblBakeEncoder"),
+        });
+
+        for face in 0..CUBE_FACES {
+            for mip in 0..SPECULAR_MIPS {
+                // Mip 0 is a plain conversion; the rest are GGX convolutions.
+                let pipeline = if mip == 0 {
+                    &self.equirect_pipeline
+                } else {
+                    &self.prefilter_pipeline
+                };
+                let params = IblParams {
+                    face,
+                    roughness: mip as f32 / (SPECULAR_MIPS - 1) as f32,
+                    sample_count: PREFILTER_SAMPLES,
+                    face_size: (SPECULAR_SIZE >> mip).max(1),
+                };
+                let bind_group = self.bind_group(device, equirect, params);
+                let target = face_view(&specular, face, mip);
+                draw_fullscreen(&mut encoder, pipeline, Some(&bind_group), &target);
+            }
+
+            let params = IblParams {
+                face,
+                roughness: 1.0,
+                sample_count: IRRADIANCE_SAMPLES,
+                face_size: IRRADIANCE_SIZE,
+            };
+            let bind_group = self.bind_group(device, equirect, params);
+            let target = face_view(&irradiance, face, 0);
+            draw_fullscreen(
+                &mut encoder,
+                &self.irradiance_pipeline,
+                Some(&bind_group),
+                &target,
+            );
+        }
+
+        queue.submit(Some(encoder.finish()));
+
+        IblMaps {
+            specular_view: cube_view(&specular),
+            _specular: specular,
+            irradiance_view: cube_view(&irradiance),
+            _irradiance: irradiance,
+            specular_levels: SPECULAR_MIPS,
+        }
+    }
+
+    fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        equirect: &wgpu::TextureView,
+        params: IblParams,
+    ) -> wgpu::BindGroup {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("IblParams"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("IblBindGroup"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(equirect),
+                },
+            ],
+        })
+    }
+}
+
+fn shader_source() -> String {
+    format!(
+        "{}\n{}",
+        include_str!("../../shader/constants.wgsl"),
+        include_str!("../../shader/ibl.wgsl")
+    )
+}
+
+fn cube_extent(size: u32) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: size,
+        height: size,
+        depth_or_array_layers: CUBE_FACES,
+    }
+}
+
+fn create_cube(device: &wgpu::Device, label: &str, size: u32, mips: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: cube_extent(size),
+        mip_level_count: mips,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: CUBE_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    })
+}
+
+fn cube_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::Cube),
+        ..Default::default()
+    })
+}
+
+fn face_view(texture: &wgpu::Texture, face: u32, mip: u32) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("IblFaceView"),
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        base_mip_level: mip,
+        mip_level_count: Some(1),
+        base_array_layer: face,
+        array_layer_count: Some(1),
+        ..Default::default()
+    })
+}
+
+fn fullscreen_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    entry_point: &str,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(entry_point),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(entry_point),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+fn draw_fullscreen(
+    encoder: &mut wgpu::CommandEncoder,
+    pipeline: &wgpu::RenderPipeline,
+    bind_group: Option<&wgpu::BindGroup>,
+    target: &wgpu::TextureView,
+) {
+    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("IblBakePass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    pass.set_pipeline(pipeline);
+    if let Some(bind_group) = bind_group {
+        pass.set_bind_group(0, bind_group, &[]);
+    }
+    pass.draw(0..3, 0..1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ibl_shader_parses() {
+        let source = shader_source();
+        if let Err(err) = naga::front::wgsl::parse_str(&source) {
+            panic!(
+                "IBL shader failed to parse:\n{}",
+                err.emit_to_string(&source)
+            );
+        }
+    }
+
+    #[test]
+    fn params_match_wgsl_layout() {
+        assert_eq!(std::mem::size_of::<IblParams>(), 16);
+    }
+}
@@ -0,0 +1,103 @@
+//! Multi-instance batch draws. When the device supports indirect draws with a
+//! non-zero first instance, each call's arguments are staged in a buffer and
+//! issued with `draw_indexed_indirect`; otherwise it falls back to
+//! `draw_indexed`. Single-instance draws always take the direct path.
+
+use std::mem;
+use std::ops::Range;
+
+use wgpu::util::DrawIndexedIndirectArgs;
+
+use crate::renderer::internal::RenderContext;
+
+const INITIAL_DRAW_CAPACITY: usize = 256;
+const ARGS_SIZE: u64 = mem::size_of::<DrawIndexedIndirectArgs>() as u64;
+
+pub(crate) struct InstancedDraws {
+    /// `None` without `INDIRECT_FIRST_INSTANCE`.
+    buffer: Option<wgpu::Buffer>,
+    capacity: usize,
+    args: Vec<DrawIndexedIndirectArgs>,
+    instanced_draw_calls: u32,
+}
+
+impl InstancedDraws {
+    pub(crate) fn new(context: &RenderContext) -> Self {
+        let supported = context
+            .device
+            .features()
+            .contains(wgpu::Features::INDIRECT_FIRST_INSTANCE);
+        if !supported {
+            log::info!("Indirect draws unavailable; instanced batches use draw_indexed");
+        }
+        Self {
+            buffer: supported.then(|| Self::create_buffer(&context.device, INITIAL_DRAW_CAPACITY)),
+            capacity: INITIAL_DRAW_CAPACITY,
+            args: Vec::new(),
+            instanced_draw_calls: 0,
+        }
+    }
+
+    /// Resets the frame and makes room for up to `max_draws` indirect calls.
+    pub(crate) fn begin_frame(&mut self, device: &wgpu::Device, max_draws: usize) {
+        self.args.clear();
+        self.instanced_draw_calls = 0;
+        if self.buffer.is_some() && max_draws > self.capacity {
+            self.capacity = max_draws.next_power_of_two();
+            self.buffer = Some(Self::create_buffer(device, self.capacity));
+        }
+    }
+
+    pub(crate) fn draw(
+        &mut self,
+        pass: &mut wgpu::RenderPass<'_>,
+        index_count: u32,
+        instances: Range<u32>,
+    ) {
+        if instances.len() <= 1 {
+            pass.draw_indexed(0..index_count, 0, instances);
+            return;
+        }
+
+        self.instanced_draw_calls += 1;
+        match &self.buffer {
+            Some(buffer) if self.args.len() < self.capacity => {
+                let offset = self.args.len() as u64 * ARGS_SIZE;
+                self.args.push(DrawIndexedIndirectArgs {
+                    index_count,
+                    instance_count: instances.len() as u32,
+                    first_index: 0,
+                    base_vertex: 0,
+                    first_instance: instances.start,
+                });
+                pass.draw_indexed_indirect(buffer, offset);
+            }
+            _ => pass.draw_indexed(0..index_count, 0, instances),
+        }
+    }
+
+    /// Uploads the staged arguments; call before submitting the frame.
+    /// Returns the number of draws that covered more than one instance.
+    pub(crate) fn finish(&mut self, queue: &wgpu::Queue) -> u32 {
+        if let Some(buffer) = &self.buffer {
+            if !self.args.is_empty() {
+                let bytes: Vec<u8> = self
+                    .args
+                    .iter()
+                    .flat_map(|args| args.as_bytes().iter().copied())
+                    .collect();
+                queue.write_buffer(buffer, 0, &bytes);
+            }
+        }
+        self.instanced_draw_calls
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Indirect Draw Args"),
+            size: capacity as u64 * ARGS_SIZE,
+            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+}
@@ -10,13 +10,28 @@
 pub mod batches;
 pub mod buffers;
 pub mod context;
+pub mod cookies;
+pub mod debug_lines;
 pub mod environment;
+pub mod ibl;
+pub mod indirect;
 pub mod pipeline;
+pub mod readback;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod shader_watcher;
 pub mod shadows;
+pub mod transmission;
 
 pub(crate) use batches::{OrderedBatch, PreparedBatches};
 pub(crate) use buffers::{CameraBuffer, DynamicObjectsBuffer, LightsBuffer};
 pub(crate) use context::RenderContext;
+pub(crate) use debug_lines::DebugLineResources;
 pub(crate) use environment::EnvironmentResources;
-pub(crate) use pipeline::{PipelineKey, RenderPipeline, TextureBindingModel};
+pub(crate) use indirect::InstancedDraws;
+pub(crate) use pipeline::{
+    PipelineKey, RenderPipeline, SceneShader, ShaderSources, StencilMode, TextureBindingModel,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use shader_watcher::ShaderWatcher;
 pub(crate) use shadows::ShadowResources;
+pub(crate) use transmission::TransmissionResources;
@@ -1,16 +1,74 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::num::NonZeroU32;
+use std::time::Duration;
 
 use crate::asset::Assets;
-use crate::renderer::internal::{CameraBuffer, DynamicObjectsBuffer, LightsBuffer, RenderContext};
+use crate::renderer::batch::RenderPass;
+use crate::renderer::gpu_layout::generated_structs_wgsl;
+use crate::renderer::internal::{
+    CameraBuffer, DynamicObjectsBuffer, LightsBuffer, OrderedBatch, RenderContext,
+};
 use crate::renderer::material::MaterialFlags;
-use crate::renderer::{Material, PipelineBuilder, Vertex};
+use crate::renderer::postprocess::{SCENE_EMISSIVE_FORMAT, SCENE_VELOCITY_FORMAT};
+use crate::renderer::texture::SamplerKey;
+use crate::renderer::{Material, PipelineBuilder, RenderMode, SkinVertex, Vertex};
+use crate::time::Instant;
 
-const MAX_TEXTURES: usize = 256;
+/// Bounds on the bindless texture array length. Below the minimum the
+/// classic per-material binding model is used instead.
+pub(crate) const MIN_BINDLESS_TEXTURES: u32 = 256;
+const MAX_BINDLESS_TEXTURES: u32 = 4096;
+/// Sampled textures per stage left for the lighting, shadow and
+/// environment bindings next to the texture array.
+pub(crate) const RESERVED_SAMPLED_TEXTURES: u32 = 32;
+/// Stands in for the texture array length in `bindings_bindless.wgsl`.
+const TEXTURE_CAPACITY_PLACEHOLDER: &str = "{{MAX_TEXTURES}}";
+
+/// Length of the bindless texture array on an adapter with `limits`: the
+/// largest power of two its per-stage limits allow, up to
+/// [`MAX_BINDLESS_TEXTURES`]. `None` when fewer than
+/// [`MIN_BINDLESS_TEXTURES`] fit.
+pub(crate) fn bindless_texture_capacity(limits: &wgpu::Limits) -> Option<u32> {
+    let allowed = limits
+        .max_binding_array_elements_per_shader_stage
+        .min(
+            limits
+                .max_sampled_textures_per_shader_stage
+                .saturating_sub(RESERVED_SAMPLED_TEXTURES),
+        )
+        .min(MAX_BINDLESS_TEXTURES);
+    (allowed >= MIN_BINDLESS_TEXTURES).then(|| 1 << allowed.ilog2())
+}
+
+/// Fills in the texture array length of an assembled shader. Sources
+/// without bindless bindings are returned unchanged.
+pub(crate) fn with_texture_capacity(source: &str, capacity: u32) -> String {
+    source.replace(TEXTURE_CAPACITY_PLACEHOLDER, &capacity.to_string())
+}
+
+/// Main pass variants compiled per frame from the warm-up queue, after the
+/// ones the frame draws with. wgpu has no asynchronous pipeline creation, so
+/// the queue is spread over frames instead of moved off the main thread.
+const QUEUED_COMPILES_PER_FRAME: usize = 2;
 
 pub(crate) struct RenderPipeline {
+    // Main pass variants, compiled the first time a batch needs them or
+    // when the warm-up queue reaches them.
     pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
-    depth_prepass: wgpu::RenderPipeline,
+    queued: VecDeque<PipelineKey>,
+    compiled_this_frame: u32,
+    compile_time: Duration,
+    // Kept to build debug view variants on demand.
+    shader: wgpu::ShaderModule,
+    layout: wgpu::PipelineLayout,
+    sample_count: u32,
+    supports_wireframe: bool,
+    depth_layout: wgpu::PipelineLayout,
+    masked_depth_layout: wgpu::PipelineLayout,
+    background_layout: wgpu::PipelineLayout,
+    // Indexed by whether the batch is double-sided.
+    depth_prepass: [wgpu::RenderPipeline; 2],
+    depth_prepass_masked: [wgpu::RenderPipeline; 2],
     background: wgpu::RenderPipeline,
 }
 
@@ -20,6 +78,68 @@ pub(crate) struct PipelineKey {
     depth_write: bool,
     alpha_blend: bool,
     sample_count: u32,
+    skinned: bool,
+    wireframe: bool,
+    double_sided: bool,
+    stencil: StencilMode,
+    render_mode: RenderMode,
+}
+
+/// How a main pass variant uses the stencil buffer. The reference value is
+/// set per batch, so it does not select a pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum StencilMode {
+    Disabled,
+    /// Replaces the stencil with the reference wherever the batch draws.
+    Write,
+    /// Draws only where the stencil differs from the reference.
+    Test,
+}
+
+impl StencilMode {
+    /// The stencil use of `batch` and its reference value. Overlay batches
+    /// draw without a depth-stencil attachment and never use it.
+    pub(crate) fn of(batch: &OrderedBatch) -> (Self, u32) {
+        if batch.pass == RenderPass::Overlay {
+            return (Self::Disabled, 0);
+        }
+        match (
+            batch.depth_state.stencil_test,
+            batch.depth_state.stencil_write,
+        ) {
+            (Some(value), _) => (Self::Test, value as u32),
+            (None, Some(value)) => (Self::Write, value as u32),
+            (None, None) => (Self::Disabled, 0),
+        }
+    }
+
+    fn state(self) -> wgpu::StencilState {
+        let (compare, pass_op, write_mask) = match self {
+            Self::Disabled => return wgpu::StencilState::default(),
+            Self::Write => (
+                wgpu::CompareFunction::Always,
+                wgpu::StencilOperation::Replace,
+                0xff,
+            ),
+            Self::Test => (
+                wgpu::CompareFunction::NotEqual,
+                wgpu::StencilOperation::Keep,
+                0,
+            ),
+        };
+        let face = wgpu::StencilFaceState {
+            compare,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op,
+        };
+        wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: 0xff,
+            write_mask,
+        }
+    }
 }
 
 impl PipelineKey {
@@ -34,6 +154,219 @@ impl PipelineKey {
             depth_write,
             alpha_blend,
             sample_count,
+            skinned: false,
+            wireframe: false,
+            double_sided: false,
+            stencil: StencilMode::Disabled,
+            render_mode: RenderMode::Lit,
+        }
+    }
+
+    /// The key for drawing `batch` into a `color_sample_count` target.
+    pub(crate) fn for_batch(batch: &OrderedBatch, color_sample_count: u32) -> Self {
+        Self::new(
+            batch.depth_state.depth_test,
+            batch.depth_state.depth_write,
+            batch.alpha_blend,
+            color_sample_count,
+        )
+        .with_wireframe(batch.depth_state.wireframe)
+        .with_double_sided(batch.double_sided)
+        .with_stencil(StencilMode::of(batch).0)
+    }
+
+    /// Selects the variant that reads joint indices and weights from a
+    /// second vertex buffer.
+    pub(crate) fn with_skinning(mut self, skinned: bool) -> Self {
+        self.skinned = skinned;
+        self
+    }
+
+    /// Selects the variant that rasterizes triangle edges only.
+    pub(crate) fn with_wireframe(mut self, wireframe: bool) -> Self {
+        self.wireframe = wireframe;
+        self
+    }
+
+    /// Selects the variant that rasterizes back faces too.
+    pub(crate) fn with_double_sided(mut self, double_sided: bool) -> Self {
+        self.double_sided = double_sided;
+        self
+    }
+
+    /// Selects the variant that writes or tests the stencil buffer.
+    pub(crate) fn with_stencil(mut self, stencil: StencilMode) -> Self {
+        self.stencil = stencil;
+        self
+    }
+
+    /// Selects the fragment entry point for a debug view.
+    pub(crate) fn with_render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+}
+
+/// The emissive attachment every scene pass carries next to the colour
+/// target. Only the lit shader writes it; other entry points leave it alone.
+fn emissive_target(blend: Option<wgpu::BlendState>, written: bool) -> wgpu::ColorTargetState {
+    wgpu::ColorTargetState {
+        format: SCENE_EMISSIVE_FORMAT,
+        blend,
+        write_mask: if written {
+            wgpu::ColorWrites::ALL
+        } else {
+            wgpu::ColorWrites::empty()
+        },
+    }
+}
+
+/// The motion vector attachment after the emissive one. Only opaque lit
+/// surfaces write it; blended ones keep the motion of what lies behind them.
+fn velocity_target(written: bool) -> wgpu::ColorTargetState {
+    wgpu::ColorTargetState {
+        format: SCENE_VELOCITY_FORMAT,
+        blend: None,
+        write_mask: if written {
+            wgpu::ColorWrites::ALL
+        } else {
+            wgpu::ColorWrites::empty()
+        },
+    }
+}
+
+/// Texture declarations and sampling helpers at group 3 for the given model.
+pub(crate) fn texture_bindings_source(bindless: bool) -> &'static str {
+    if bindless {
+        include_str!("../../shader/bindings_bindless.wgsl")
+    } else {
+        include_str!("../../shader/bindings_traditional.wgsl")
+    }
+}
+
+fn texture_bindings_file(bindless: bool) -> &'static str {
+    if bindless {
+        "bindings_bindless.wgsl"
+    } else {
+        "bindings_traditional.wgsl"
+    }
+}
+
+/// The shader modules [`RenderPipeline`] compiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SceneShader {
+    Main,
+    Depth,
+    Background,
+}
+
+impl SceneShader {
+    const ALL: [SceneShader; 3] = [
+        SceneShader::Main,
+        SceneShader::Depth,
+        SceneShader::Background,
+    ];
+
+    /// Files under `src/shader/` this module is assembled from.
+    fn files(self) -> &'static [&'static str] {
+        match self {
+            SceneShader::Main => &[
+                "constants.wgsl",
+                "bindings_bindless.wgsl",
+                "bindings_traditional.wgsl",
+                "pbr_lighting.wgsl",
+                "common.wgsl",
+            ],
+            SceneShader::Depth => &[
+                "bindings_bindless.wgsl",
+                "bindings_traditional.wgsl",
+                "depth_prepass.wgsl",
+            ],
+            SceneShader::Background => &["constants.wgsl", "environment_background.wgsl"],
+        }
+    }
+
+    /// Modules that include `file`, empty for files outside the scene pipeline.
+    pub(crate) fn using(file: &str) -> Vec<SceneShader> {
+        Self::ALL
+            .into_iter()
+            .filter(|shader| shader.files().contains(&file))
+            .collect()
+    }
+}
+
+/// WGSL text of the files the scene shaders are assembled from. Starts out
+/// with the copies compiled into the binary; hot reload replaces files with
+/// their edited contents.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ShaderSources {
+    overrides: HashMap<String, String>,
+}
+
+impl ShaderSources {
+    fn builtin(file: &str) -> Option<&'static str> {
+        Some(match file {
+            "constants.wgsl" => include_str!("../../shader/constants.wgsl"),
+            "bindings_bindless.wgsl" => include_str!("../../shader/bindings_bindless.wgsl"),
+            "bindings_traditional.wgsl" => include_str!("../../shader/bindings_traditional.wgsl"),
+            "pbr_lighting.wgsl" => include_str!("../../shader/pbr_lighting.wgsl"),
+            "common.wgsl" => include_str!("../../shader/common.wgsl"),
+            "depth_prepass.wgsl" => include_str!("../../shader/depth_prepass.wgsl"),
+            "environment_background.wgsl" => {
+                include_str!("../../shader/environment_background.wgsl")
+            }
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn get(&self, file: &str) -> &str {
+        self.overrides
+            .get(file)
+            .map(String::as_str)
+            .or_else(|| Self::builtin(file))
+            .unwrap_or_else(|| panic!("{} is not a scene shader file", file))
+    }
+
+    /// Replaces `file` and returns the previous text so a failed rebuild
+    /// can restore it.
+    pub(crate) fn replace(&mut self, file: &str, source: String) -> Option<String> {
+        self.overrides.insert(file.to_string(), source)
+    }
+
+    pub(crate) fn restore(&mut self, file: &str, previous: Option<String>) {
+        match previous {
+            Some(source) => {
+                self.overrides.insert(file.to_string(), source);
+            }
+            None => {
+                self.overrides.remove(file);
+            }
+        }
+    }
+
+    fn source(&self, shader: SceneShader, bindless: bool) -> String {
+        match shader {
+            // Generated struct layouts first, then the shared PBR lighting
+            // module before common.wgsl
+            SceneShader::Main => format!(
+                "{}\n{}\n{}\n{}\n{}",
+                generated_structs_wgsl(),
+                self.get("constants.wgsl"),
+                self.get(texture_bindings_file(bindless)),
+                self.get("pbr_lighting.wgsl"),
+                self.get("common.wgsl")
+            ),
+            SceneShader::Depth => format!(
+                "{}\n{}",
+                self.get(texture_bindings_file(bindless)),
+                self.get("depth_prepass.wgsl")
+            ),
+            SceneShader::Background => format!(
+                "{}\n{}\n{}",
+                generated_structs_wgsl(),
+                self.get("constants.wgsl"),
+                self.get("environment_background.wgsl")
+            ),
         }
     }
 }
@@ -44,127 +377,17 @@ pub(crate) enum TextureBindingModel {
 }
 
 impl RenderPipeline {
-    pub(crate) fn new(
+    pub(crate) fn build(
         context: &RenderContext,
         camera: &CameraBuffer,
         objects: &DynamicObjectsBuffer,
         lights: &LightsBuffer,
+        texture_bind_layout: &wgpu::BindGroupLayout,
         sample_count: u32,
-    ) -> (Self, TextureBindingModel) {
-        let (texture_bind_layout, texture_binder, shader_source) = if context
-            .supports_bindless_textures
-        {
-            let layout =
-                context
-                    .device
-                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                        label: Some("TextureArrayBindGroupLayout"),
-                        entries: &[
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 0,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Texture {
-                                    sample_type: wgpu::TextureSampleType::Float {
-                                        filterable: true,
-                                    },
-                                    view_dimension: wgpu::TextureViewDimension::D2,
-                                    multisampled: false,
-                                },
-                                count: NonZeroU32::new(MAX_TEXTURES as u32),
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 1,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 2,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Sampler(
-                                    wgpu::SamplerBindingType::NonFiltering,
-                                ),
-                                count: None,
-                            },
-                        ],
-                    });
-
-            let binder =
-                TextureBindingModel::Bindless(BindlessTextureBinder::new(&context.device, &layout));
-            (layout, binder, Self::shader_source(true))
-        } else {
-            let layout =
-                context
-                    .device
-                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                        label: Some("TextureBindGroupLayout"),
-                        entries: &[
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 0,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Texture {
-                                    sample_type: wgpu::TextureSampleType::Float {
-                                        filterable: true,
-                                    },
-                                    view_dimension: wgpu::TextureViewDimension::D2,
-                                    multisampled: false,
-                                },
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 1,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 2,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Sampler(
-                                    wgpu::SamplerBindingType::NonFiltering,
-                                ),
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 3,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Texture {
-                                    sample_type: wgpu::TextureSampleType::Float {
-                                        filterable: true,
-                                    },
-                                    view_dimension: wgpu::TextureViewDimension::D2,
-                                    multisampled: false,
-                                },
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 4,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Texture {
-                                    sample_type: wgpu::TextureSampleType::Float {
-                                        filterable: true,
-                                    },
-                                    view_dimension: wgpu::TextureViewDimension::D2,
-                                    multisampled: false,
-                                },
-                                count: None,
-                            },
-                        ],
-                    });
-
-            let binder = TextureBindingModel::Classic(TraditionalTextureBinder::new(
-                &context.device,
-                &layout,
-            ));
-            (layout, binder, Self::shader_source(false))
-        };
-
-        let shader = context
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("RendererShader"),
-                source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-            });
+        sources: &ShaderSources,
+    ) -> Self {
+        let bindless = context.supports_bindless_textures;
+        let shader = Self::create_shader(context, SceneShader::Main, sources, bindless);
 
         let pipeline_layout =
             context
@@ -175,7 +398,7 @@ impl RenderPipeline {
                         &camera.bind_layout,
                         &objects.bind_layout,
                         &lights.bind_layout,
-                        &texture_bind_layout,
+                        texture_bind_layout,
                     ],
                     push_constant_ranges: &[],
                 });
@@ -189,14 +412,23 @@ impl RenderPipeline {
                     push_constant_ranges: &[],
                 });
 
-        let depth_shader = context
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("DepthShader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../../shader/depth_prepass.wgsl").into(),
-                ),
-            });
+        // Alpha-masked batches also bind the lights and textures so the
+        // prepass can sample base colour alpha at the main pass's group indices.
+        let masked_depth_pipeline_layout =
+            context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("MaskedDepthPipelineLayout"),
+                    bind_group_layouts: &[
+                        &camera.bind_layout,
+                        &objects.bind_layout,
+                        &lights.bind_layout,
+                        texture_bind_layout,
+                    ],
+                    push_constant_ranges: &[],
+                });
+
+        let depth_shader = Self::create_shader(context, SceneShader::Depth, sources, bindless);
 
         let background_layout =
             context
@@ -207,107 +439,217 @@ impl RenderPipeline {
                     push_constant_ranges: &[],
                 });
 
-        let shader_source = format!(
-            "{}\n{}",
-            include_str!("../../shader/constants.wgsl"),
-            include_str!("../../shader/environment_background.wgsl")
+        let background_shader =
+            Self::create_shader(context, SceneShader::Background, sources, bindless);
+        let background_pipeline = Self::create_background_pipeline(
+            context,
+            &background_layout,
+            &background_shader,
+            sample_count,
         );
 
-        let background_shader = context
+        // Wireframe variants are only built when the device can rasterize lines.
+        let supports_wireframe = context
+            .device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+
+        let depth_prepass = [false, true].map(|double_sided| {
+            Self::create_depth_prepass_pipeline(
+                context,
+                &depth_pipeline_layout,
+                &depth_shader,
+                sample_count,
+                double_sided,
+            )
+        });
+        let depth_prepass_masked = [false, true].map(|double_sided| {
+            Self::create_masked_depth_prepass_pipeline(
+                context,
+                &masked_depth_pipeline_layout,
+                &depth_shader,
+                sample_count,
+                double_sided,
+            )
+        });
+
+        let mut render_pipeline = Self {
+            pipelines: HashMap::new(),
+            queued: VecDeque::new(),
+            compiled_this_frame: 0,
+            compile_time: Duration::ZERO,
+            shader,
+            layout: pipeline_layout,
+            sample_count,
+            supports_wireframe,
+            depth_layout: depth_pipeline_layout,
+            masked_depth_layout: masked_depth_pipeline_layout,
+            background_layout,
+            depth_prepass,
+            depth_prepass_masked,
+            background: background_pipeline,
+        };
+        render_pipeline.queue_render_mode(RenderMode::Lit);
+        render_pipeline
+    }
+
+    fn create_shader(
+        context: &RenderContext,
+        shader: SceneShader,
+        sources: &ShaderSources,
+        bindless: bool,
+    ) -> wgpu::ShaderModule {
+        let label = match shader {
+            SceneShader::Main => "RendererShader",
+            SceneShader::Depth => "DepthShader",
+            SceneShader::Background => "EnvironmentBackgroundShader",
+        };
+        context
             .device
             .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("EnvironmentBackgroundShader"),
-                source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-            });
-
-        let background_pipeline =
-            PipelineBuilder::new(&context.device, &background_layout, &background_shader)
-                .with_label("EnvironmentBackgroundPipeline")
-                .with_color_target(context.config.format, Some(wgpu::BlendState::REPLACE))
-                .with_depth_stencil(
-                    context.depth.format,
-                    false, // depth_write
-                    wgpu::CompareFunction::Always,
-                )
-                .with_no_culling()
-                .with_multisample(sample_count)
-                .build();
-
-        let mut pipelines = HashMap::new();
-        for &depth_test in &[false, true] {
-            for &depth_write in &[false, true] {
-                for &alpha_blend in &[false, true] {
-                    let key = PipelineKey {
-                        depth_test,
-                        depth_write,
-                        alpha_blend,
-                        sample_count,
-                    };
-                    let pipeline = Self::create_pipeline(
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(
+                    with_texture_capacity(
+                        &sources.source(shader, bindless),
+                        context.bindless_texture_capacity,
+                    )
+                    .into(),
+                ),
+            })
+    }
+
+    /// Recompiles `shader` from `sources` and rebuilds the pipelines that use
+    /// it. Pipeline layouts stay the same, so existing bind groups remain
+    /// valid. On a compile or validation error the previous pipelines are
+    /// kept and the error is returned.
+    pub(crate) fn reload_shader(
+        &mut self,
+        context: &RenderContext,
+        shader: SceneShader,
+        sources: &ShaderSources,
+    ) -> Result<(), String> {
+        let bindless = context.supports_bindless_textures;
+        context
+            .device
+            .push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = Self::create_shader(context, shader, sources, bindless);
+        match shader {
+            SceneShader::Main => {
+                let pipelines: HashMap<_, _> = self
+                    .pipelines
+                    .keys()
+                    .map(|&key| {
+                        let pipeline = Self::create_pipeline(context, &self.layout, &module, key);
+                        (key, pipeline)
+                    })
+                    .collect();
+                if let Some(err) = pollster::block_on(context.device.pop_error_scope()) {
+                    return Err(err.to_string());
+                }
+                self.pipelines = pipelines;
+                self.shader = module;
+            }
+            SceneShader::Depth => {
+                let depth_prepass = [false, true].map(|double_sided| {
+                    Self::create_depth_prepass_pipeline(
                         context,
-                        &pipeline_layout,
-                        &shader,
-                        depth_test,
-                        depth_write,
-                        alpha_blend,
-                        sample_count,
-                    );
-                    pipelines.insert(key, pipeline);
+                        &self.depth_layout,
+                        &module,
+                        self.sample_count,
+                        double_sided,
+                    )
+                });
+                let depth_prepass_masked = [false, true].map(|double_sided| {
+                    Self::create_masked_depth_prepass_pipeline(
+                        context,
+                        &self.masked_depth_layout,
+                        &module,
+                        self.sample_count,
+                        double_sided,
+                    )
+                });
+                if let Some(err) = pollster::block_on(context.device.pop_error_scope()) {
+                    return Err(err.to_string());
+                }
+                self.depth_prepass = depth_prepass;
+                self.depth_prepass_masked = depth_prepass_masked;
+            }
+            SceneShader::Background => {
+                let background = Self::create_background_pipeline(
+                    context,
+                    &self.background_layout,
+                    &module,
+                    self.sample_count,
+                );
+                if let Some(err) = pollster::block_on(context.device.pop_error_scope()) {
+                    return Err(err.to_string());
                 }
+                self.background = background;
             }
         }
-
-        let depth_prepass = Self::create_depth_prepass_pipeline(
-            context,
-            &depth_pipeline_layout,
-            &depth_shader,
-            sample_count,
-        );
-
-        (
-            Self {
-                pipelines,
-                depth_prepass,
-                background: background_pipeline,
-            },
-            texture_binder,
-        )
+        Ok(())
     }
 
-    fn shader_source(bindless: bool) -> String {
-        let constants = include_str!("../../shader/constants.wgsl");
-        let bindings = if bindless {
-            include_str!("../../shader/bindings_bindless.wgsl")
-        } else {
-            include_str!("../../shader/bindings_traditional.wgsl")
-        };
-
-        // Include shared PBR lighting module before common.wgsl
-        format!(
-            "{}\n{}\n{}\n{}",
-            constants,
-            bindings,
-            include_str!("../../shader/pbr_lighting.wgsl"),
-            include_str!("../../shader/common.wgsl")
-        )
+    fn create_background_pipeline(
+        context: &RenderContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        PipelineBuilder::new(&context.device, pipeline_layout, shader)
+            .with_label("EnvironmentBackgroundPipeline")
+            .with_color_target(context.config.format, Some(wgpu::BlendState::REPLACE))
+            .with_color_target_state(emissive_target(None, false))
+            .with_color_target_state(velocity_target(false))
+            .with_depth_stencil(
+                context.depth.format,
+                false, // depth_write
+                wgpu::CompareFunction::Always,
+            )
+            .with_no_culling()
+            .with_multisample(sample_count)
+            .build()
     }
 
     fn create_pipeline(
         context: &RenderContext,
         pipeline_layout: &wgpu::PipelineLayout,
         shader: &wgpu::ShaderModule,
-        depth_test: bool,
-        depth_write: bool,
-        alpha_blend: bool,
-        sample_count: u32,
+        key: PipelineKey,
     ) -> wgpu::RenderPipeline {
+        let PipelineKey {
+            depth_test,
+            depth_write,
+            alpha_blend,
+            sample_count,
+            skinned,
+            wireframe,
+            double_sided,
+            stencil,
+            render_mode,
+        } = key;
+
+        // Overdraw counts every layer, so it neither tests nor writes depth.
+        let overdraw = render_mode == RenderMode::Overdraw;
+        let depth_test = depth_test && !overdraw;
+        let depth_write = depth_write && !overdraw;
+
         let depth_compare = if depth_test {
             wgpu::CompareFunction::LessEqual
         } else {
             wgpu::CompareFunction::Always
         };
 
-        let blend_state = if alpha_blend {
+        let blend_state = if overdraw {
+            Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::REPLACE,
+            })
+        } else if alpha_blend {
             Some(wgpu::BlendState::ALPHA_BLENDING)
         } else {
             Some(wgpu::BlendState::REPLACE)
@@ -317,17 +659,189 @@ impl RenderPipeline {
             .with_label("MainRenderPipeline")
             .with_vertex_buffer(Vertex::layout())
             .with_color_target(context.config.format, blend_state)
+            .with_color_target_state(emissive_target(blend_state, render_mode == RenderMode::Lit))
+            .with_color_target_state(velocity_target(
+                render_mode == RenderMode::Lit && !alpha_blend,
+            ))
             .with_multisample(sample_count);
 
-        if depth_test || depth_write {
-            builder = builder.with_depth_stencil(context.depth.format, depth_write, depth_compare);
+        if skinned {
+            builder = builder
+                .with_label("SkinnedRenderPipeline")
+                .with_vertex_entry("vs_main_skinned")
+                .with_vertex_buffer(SkinVertex::layout());
+        }
+
+        if depth_test || depth_write || stencil != StencilMode::Disabled {
+            builder = builder
+                .with_depth_stencil(context.depth.format, depth_write, depth_compare)
+                .with_stencil(stencil.state());
+        }
+
+        if double_sided {
+            builder = builder.with_no_culling();
+        }
+
+        if wireframe {
+            builder = builder
+                .with_label("WireframeRenderPipeline")
+                .with_polygon_mode(wgpu::PolygonMode::Line)
+                .with_no_culling();
         }
 
+        builder = match render_mode {
+            RenderMode::Lit => builder,
+            RenderMode::Wireframe => builder
+                .with_label("ShaderWireframeRenderPipeline")
+                .with_fragment_entry("fs_debug_wireframe")
+                .with_no_culling(),
+            RenderMode::Normals => builder
+                .with_label("NormalsDebugPipeline")
+                .with_fragment_entry("fs_debug_normals"),
+            RenderMode::Uvs => builder
+                .with_label("UvDebugPipeline")
+                .with_fragment_entry("fs_debug_uvs"),
+            RenderMode::Overdraw => builder
+                .with_label("OverdrawDebugPipeline")
+                .with_fragment_entry("fs_debug_overdraw"),
+        };
+
         builder.build()
     }
 
+    /// Maps a requested key onto a built variant. Line wireframes use the lit
+    /// shader; without `POLYGON_MODE_LINE` per-batch wireframes draw filled
+    /// and [`RenderMode::Wireframe`] uses the shader wireframe. Debug views
+    /// ignore per-batch wireframes.
+    fn resolve(&self, mut key: PipelineKey) -> PipelineKey {
+        if key.render_mode == RenderMode::Wireframe && self.supports_wireframe {
+            key.render_mode = RenderMode::Lit;
+            key.wireframe = true;
+        }
+        key.wireframe &= self.supports_wireframe && key.render_mode == RenderMode::Lit;
+        key
+    }
+
+    /// Queues every main pass variant `render_mode` can draw with for
+    /// [`Self::compile_queued`]. Variants already built or queued are skipped.
+    pub(crate) fn queue_render_mode(&mut self, render_mode: RenderMode) {
+        let wireframe_modes: &[bool] = if render_mode == RenderMode::Lit {
+            &[false, true]
+        } else {
+            &[false]
+        };
+        for depth_test in [false, true] {
+            for depth_write in [false, true] {
+                for alpha_blend in [false, true] {
+                    for skinned in [false, true] {
+                        for &wireframe in wireframe_modes {
+                            for double_sided in [false, true] {
+                                let key = self.resolve(
+                                    PipelineKey::new(
+                                        depth_test,
+                                        depth_write,
+                                        alpha_blend,
+                                        self.sample_count,
+                                    )
+                                    .with_skinning(skinned)
+                                    .with_wireframe(wireframe)
+                                    .with_double_sided(double_sided)
+                                    .with_render_mode(render_mode),
+                                );
+                                if !self.pipelines.contains_key(&key) && !self.queued.contains(&key)
+                                {
+                                    self.queued.push_back(key);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compiles the variant for `key` unless it already exists. Every key
+    /// passed to [`Self::pipeline`] during a frame must be prepared first.
+    pub(crate) fn prepare(&mut self, context: &RenderContext, key: PipelineKey) {
+        let key = self.resolve(key);
+        if self.pipelines.contains_key(&key) {
+            return;
+        }
+        let started = Instant::now();
+        let pipeline = Self::create_pipeline(context, &self.layout, &self.shader, key);
+        self.compile_time += started.elapsed();
+        self.compiled_this_frame += 1;
+        self.pipelines.insert(key, pipeline);
+        if self.queued.is_empty() {
+            return;
+        }
+        self.queued.retain(|queued| *queued != key);
+        if self.queued.is_empty() {
+            self.log_warm_up_finished();
+        }
+    }
+
+    /// Compiles up to `limit` queued variants; `None` drains the queue.
+    /// Returns how many were compiled.
+    pub(crate) fn compile_queued(&mut self, context: &RenderContext, limit: Option<usize>) -> u32 {
+        let mut compiled = 0;
+        while limit.is_none_or(|limit| (compiled as usize) < limit) {
+            let Some(key) = self.queued.pop_front() else {
+                break;
+            };
+            if self.pipelines.contains_key(&key) {
+                continue;
+            }
+            let started = Instant::now();
+            let pipeline = Self::create_pipeline(context, &self.layout, &self.shader, key);
+            self.compile_time += started.elapsed();
+            self.pipelines.insert(key, pipeline);
+            compiled += 1;
+            if self.queued.is_empty() {
+                self.log_warm_up_finished();
+            }
+        }
+        self.compiled_this_frame += compiled;
+        compiled
+    }
+
+    /// Spends this frame's share of the warm-up queue.
+    pub(crate) fn compile_queued_for_frame(&mut self, context: &RenderContext) {
+        self.compile_queued(context, Some(QUEUED_COMPILES_PER_FRAME));
+    }
+
+    fn log_warm_up_finished(&self) {
+        log::info!(
+            "Compiled {} main pass pipeline variants in {:.1} ms",
+            self.pipelines.len(),
+            self.compile_time.as_secs_f64() * 1000.0
+        );
+    }
+
+    /// Variants compiled since the last call.
+    pub(crate) fn take_compiled_count(&mut self) -> u32 {
+        std::mem::take(&mut self.compiled_this_frame)
+    }
+
+    /// Main pass variants built so far.
+    pub(crate) fn variant_count(&self) -> u32 {
+        self.pipelines.len() as u32
+    }
+
+    /// Variants still waiting in the warm-up queue.
+    pub(crate) fn queued_count(&self) -> u32 {
+        self.queued.len() as u32
+    }
+
+    /// Expects [`prepare`](Self::prepare) to have run for `key` this frame.
     pub(crate) fn pipeline(&self, key: PipelineKey) -> &wgpu::RenderPipeline {
-        self.pipelines.get(&key).expect("missing pipeline variant")
+        self.pipelines
+            .get(&self.resolve(key))
+            .expect("missing pipeline variant")
+    }
+
+    pub(crate) fn supports_wireframe(&self) -> bool {
+        self.supports_wireframe
     }
 
     fn create_depth_prepass_pipeline(
@@ -335,18 +849,50 @@ impl RenderPipeline {
         pipeline_layout: &wgpu::PipelineLayout,
         shader: &wgpu::ShaderModule,
         sample_count: u32,
+        double_sided: bool,
     ) -> wgpu::RenderPipeline {
-        PipelineBuilder::new(&context.device, pipeline_layout, shader)
+        let builder = PipelineBuilder::new(&context.device, pipeline_layout, shader)
             .with_label("DepthPrepassPipeline")
             .depth_only()
             .with_vertex_buffer(Vertex::layout())
             .with_depth_stencil(context.depth.format, true, wgpu::CompareFunction::LessEqual)
-            .with_multisample(sample_count)
-            .build()
+            .with_multisample(sample_count);
+        if double_sided {
+            builder.with_no_culling().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn create_masked_depth_prepass_pipeline(
+        context: &RenderContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        sample_count: u32,
+        double_sided: bool,
+    ) -> wgpu::RenderPipeline {
+        let builder = PipelineBuilder::new(&context.device, pipeline_layout, shader)
+            .with_label("MaskedDepthPrepassPipeline")
+            .with_vertex_entry("vs_masked")
+            .with_fragment_entry("fs_alpha_mask")
+            .with_vertex_buffer(Vertex::layout())
+            .with_depth_stencil(context.depth.format, true, wgpu::CompareFunction::LessEqual)
+            .with_multisample(sample_count);
+        if double_sided {
+            builder.with_no_culling().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    pub(crate) fn depth_prepass(&self, double_sided: bool) -> &wgpu::RenderPipeline {
+        &self.depth_prepass[double_sided as usize]
     }
 
-    pub(crate) fn depth_prepass(&self) -> &wgpu::RenderPipeline {
-        &self.depth_prepass
+    /// Prepass variant that discards alpha-masked fragments. Expects the
+    /// lights at group 2 and the textures at group 3.
+    pub(crate) fn depth_prepass_masked(&self, double_sided: bool) -> &wgpu::RenderPipeline {
+        &self.depth_prepass_masked[double_sided as usize]
     }
 
     pub(crate) fn background(&self) -> &wgpu::RenderPipeline {
@@ -354,38 +900,71 @@ impl RenderPipeline {
     }
 }
 
+/// One sampler per [`SamplerKey`]. Sampler arrays are not portable, so the
+/// material flags pick one of these in the shader instead of each texture
+/// bringing its own.
+#[derive(Clone)]
+struct MaterialSamplers {
+    samplers: [wgpu::Sampler; 4],
+}
+
+impl MaterialSamplers {
+    fn new(device: &wgpu::Device, label: &str) -> Self {
+        let samplers = SamplerKey::ALL.map(|key| {
+            let label = format!(
+                "{}{}{}",
+                label,
+                if key.nearest { "Nearest" } else { "Linear" },
+                if key.clamp_to_edge { "Clamp" } else { "Repeat" }
+            );
+            device.create_sampler(&key.descriptor(Some(&label)))
+        });
+        Self { samplers }
+    }
+
+    /// Bind group entries for the samplers, starting at `first_binding`.
+    fn entries(&self, first_binding: u32) -> impl Iterator<Item = wgpu::BindGroupEntry<'_>> {
+        (first_binding..)
+            .zip(&self.samplers)
+            .map(|(binding, sampler)| wgpu::BindGroupEntry {
+                binding,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            })
+    }
+
+    /// Layout entries matching [`Self::entries`]; nearest samplers do not filter.
+    fn layout_entries(first_binding: u32) -> impl Iterator<Item = wgpu::BindGroupLayoutEntry> {
+        (first_binding..)
+            .zip(SamplerKey::ALL)
+            .map(|(binding, key)| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(if key.nearest {
+                    wgpu::SamplerBindingType::NonFiltering
+                } else {
+                    wgpu::SamplerBindingType::Filtering
+                }),
+                count: None,
+            })
+    }
+}
+
+/// Binds every texture slot in one array of `capacity` entries. With
+/// `PARTIALLY_BOUND_BINDING_ARRAY` the array ends at the cache's last slot,
+/// so rebuilding it costs one entry per slot rather than per array element.
 pub(crate) struct BindlessTextureBinder {
     pub(crate) layout: wgpu::BindGroupLayout,
-    linear_sampler: wgpu::Sampler,
-    nearest_sampler: wgpu::Sampler,
+    samplers: MaterialSamplers,
     _fallback_texture: wgpu::Texture,
     fallback_view: wgpu::TextureView,
     bind_group: wgpu::BindGroup,
+    capacity: usize,
+    partially_bound: bool,
 }
 
 impl BindlessTextureBinder {
-    fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
-        let linear_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("BindlessSamplerLinear"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
-
-        let nearest_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("BindlessSamplerNearest"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+    fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, capacity: u32) -> Self {
+        let samplers = MaterialSamplers::new(device, "BindlessSampler");
 
         let fallback_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("BindlessFallbackTexture"),
@@ -403,74 +982,83 @@ impl BindlessTextureBinder {
         });
         let fallback_view = fallback_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let bind_group = Self::create_bind_group_with_views(
-            device,
-            layout,
-            &linear_sampler,
-            &nearest_sampler,
-            vec![&fallback_view; MAX_TEXTURES],
-        );
+        let capacity = capacity as usize;
+        let partially_bound = device
+            .features()
+            .contains(wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY);
+        let (views, _) = bindless_views(0, [], &fallback_view, capacity, partially_bound);
+        let bind_group = Self::create_bind_group_with_views(device, layout, &samplers, views);
 
         Self {
             layout: layout.clone(),
-            linear_sampler,
-            nearest_sampler,
+            samplers,
             _fallback_texture: fallback_texture,
             fallback_view,
             bind_group,
+            capacity,
+            partially_bound,
         }
     }
 
     fn create_bind_group_with_views(
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
-        linear_sampler: &wgpu::Sampler,
-        nearest_sampler: &wgpu::Sampler,
+        samplers: &MaterialSamplers,
         views: Vec<&wgpu::TextureView>,
     ) -> wgpu::BindGroup {
+        let entries: Vec<wgpu::BindGroupEntry> = std::iter::once(wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureViewArray(&views),
+        })
+        .chain(samplers.entries(1))
+        .collect();
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("BindlessTextureBindGroup"),
             layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureViewArray(&views),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(linear_sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::Sampler(nearest_sampler),
-                },
-            ],
+            entries: &entries,
         })
     }
 
     fn update(&mut self, device: &wgpu::Device, assets: &Assets) {
-        let fallback = &self.fallback_view;
-        let views: Vec<&wgpu::TextureView> = (0..MAX_TEXTURES)
-            .map(|i| {
-                assets
-                    .textures
-                    .get(crate::asset::Handle::new(i))
-                    .map(|t| &t.view)
-                    .unwrap_or(fallback)
-            })
-            .collect();
-
-        self.bind_group = Self::create_bind_group_with_views(
-            device,
-            &self.layout,
-            &self.linear_sampler,
-            &self.nearest_sampler,
-            views,
+        let (views, overflow) = bindless_views(
+            assets.textures.slot_count(),
+            assets
+                .textures
+                .iter()
+                .map(|(handle, texture)| (handle.index(), &texture.view)),
+            &self.fallback_view,
+            self.capacity,
+            self.partially_bound,
         );
 
+        if !overflow.is_empty() {
+            let names: Vec<String> = overflow
+                .iter()
+                .map(|&index| {
+                    assets
+                        .textures
+                        .handle_at(index)
+                        .and_then(|handle| assets.textures.key_of(handle))
+                        .map_or_else(|| format!("slot {}", index), str::to_owned)
+                })
+                .collect();
+            log::error!(
+                "{} textures exceed the device's bindless limit of {} and sample a blank texture: {}",
+                overflow.len(),
+                self.capacity,
+                names.join(", ")
+            );
+        }
+
+        let bound = views.len();
+        self.bind_group =
+            Self::create_bind_group_with_views(device, &self.layout, &self.samplers, views);
+
         log::debug!(
-            "Updated bindless texture array with {} textures",
-            assets.textures.len()
+            "Updated bindless texture array with {} textures ({} of {} slots bound)",
+            assets.textures.len(),
+            bound,
+            self.capacity
         );
     }
 
@@ -479,10 +1067,37 @@ impl BindlessTextureBinder {
     }
 }
 
+/// Array entries for slots `0..slot_count`, placing each `(slot, view)` of
+/// `live` and `fallback` for empty slots. Partially bound arrays end at the
+/// last slot, others are padded to `capacity`. Also returns the occupied
+/// slots that do not fit, which the shader samples as blank.
+fn bindless_views<'a, V>(
+    slot_count: usize,
+    live: impl IntoIterator<Item = (usize, &'a V)>,
+    fallback: &'a V,
+    capacity: usize,
+    partially_bound: bool,
+) -> (Vec<&'a V>, Vec<usize>) {
+    let len = if partially_bound {
+        slot_count.clamp(1, capacity)
+    } else {
+        capacity
+    };
+    let mut views = vec![fallback; len];
+    let mut overflow = Vec::new();
+    for (index, view) in live {
+        match views.get_mut(index) {
+            Some(slot) => *slot = view,
+            None if index >= capacity => overflow.push(index),
+            None => {}
+        }
+    }
+    (views, overflow)
+}
+
 pub(crate) struct TraditionalTextureBinder {
     pub(crate) layout: wgpu::BindGroupLayout,
-    linear_sampler: wgpu::Sampler,
-    nearest_sampler: wgpu::Sampler,
+    samplers: MaterialSamplers,
     _fallback_texture: wgpu::Texture,
     fallback_view: wgpu::TextureView,
     material_bind_groups: HashMap<Material, wgpu::BindGroup>,
@@ -490,27 +1105,7 @@ pub(crate) struct TraditionalTextureBinder {
 
 impl TraditionalTextureBinder {
     fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
-        let linear_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("TraditionalSamplerLinear"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
-
-        let nearest_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("TraditionalSamplerNearest"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let samplers = MaterialSamplers::new(device, "TraditionalSampler");
 
         let fallback_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("TraditionalFallbackTexture"),
@@ -530,8 +1125,7 @@ impl TraditionalTextureBinder {
 
         Self {
             layout: layout.clone(),
-            linear_sampler,
-            nearest_sampler,
+            samplers,
             _fallback_texture: fallback_texture,
             fallback_view,
             material_bind_groups: HashMap::new(),
@@ -541,43 +1135,21 @@ impl TraditionalTextureBinder {
     fn create_bind_group(
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
-        linear_sampler: &wgpu::Sampler,
-        nearest_sampler: &wgpu::Sampler,
+        samplers: &MaterialSamplers,
         views: [&wgpu::TextureView; 5],
     ) -> wgpu::BindGroup {
+        let entries: Vec<wgpu::BindGroupEntry> = (0..)
+            .zip(views)
+            .map(|(binding, view)| wgpu::BindGroupEntry {
+                binding,
+                resource: wgpu::BindingResource::TextureView(view),
+            })
+            .chain(samplers.entries(5))
+            .collect();
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("MaterialTextureBindGroup"),
             layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(views[0]),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(views[1]),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::TextureView(views[2]),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 3,
-                    resource: wgpu::BindingResource::TextureView(views[3]),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 4,
-                    resource: wgpu::BindingResource::TextureView(views[4]),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 5,
-                    resource: wgpu::BindingResource::Sampler(linear_sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 6,
-                    resource: wgpu::BindingResource::Sampler(nearest_sampler),
-                },
-            ],
+            entries: &entries,
         })
     }
 
@@ -588,12 +1160,14 @@ impl TraditionalTextureBinder {
     ) -> &'a wgpu::TextureView {
         assets
             .textures
-            .get(crate::asset::Handle::new(index as usize))
+            .get_by_index(index as usize)
             .map(|t| &t.view)
             .unwrap_or(fallback)
     }
 
     fn update(&mut self, _device: &wgpu::Device, _assets: &Assets) {
+        // Cached groups may hold views of removed textures, or of slots that
+        // were recycled for different ones; rebuild them lazily.
         self.material_bind_groups.clear();
     }
 
@@ -604,8 +1178,7 @@ impl TraditionalTextureBinder {
         material: Material,
     ) -> &wgpu::BindGroup {
         let layout = self.layout.clone();
-        let linear_sampler = self.linear_sampler.clone();
-        let nearest_sampler = self.nearest_sampler.clone();
+        let samplers = self.samplers.clone();
         let fallback_view = self.fallback_view.clone();
 
         self.material_bind_groups
@@ -655,8 +1228,7 @@ impl TraditionalTextureBinder {
                 Self::create_bind_group(
                     device,
                     &layout,
-                    &linear_sampler,
-                    &nearest_sampler,
+                    &samplers,
                     [
                         base_color_view,
                         metallic_roughness_view,
@@ -670,6 +1242,57 @@ impl TraditionalTextureBinder {
 }
 
 impl TextureBindingModel {
+    /// Picks the bindless model when the adapter supports texture binding
+    /// arrays, falling back to one bind group per material.
+    pub(crate) fn new(context: &RenderContext) -> Self {
+        let texture_entry = |binding: u32, count: Option<NonZeroU32>| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count,
+        };
+
+        if context.supports_bindless_textures {
+            let entries: Vec<wgpu::BindGroupLayoutEntry> = std::iter::once(texture_entry(
+                0,
+                NonZeroU32::new(context.bindless_texture_capacity),
+            ))
+            .chain(MaterialSamplers::layout_entries(1))
+            .collect();
+            let layout =
+                context
+                    .device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: Some("TextureArrayBindGroupLayout"),
+                        entries: &entries,
+                    });
+
+            TextureBindingModel::Bindless(BindlessTextureBinder::new(
+                &context.device,
+                &layout,
+                context.bindless_texture_capacity,
+            ))
+        } else {
+            let entries: Vec<wgpu::BindGroupLayoutEntry> = (0..5)
+                .map(|binding| texture_entry(binding, None))
+                .chain(MaterialSamplers::layout_entries(5))
+                .collect();
+            let layout =
+                context
+                    .device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: Some("TextureBindGroupLayout"),
+                        entries: &entries,
+                    });
+
+            TextureBindingModel::Classic(TraditionalTextureBinder::new(&context.device, &layout))
+        }
+    }
+
     pub fn update(&mut self, device: &wgpu::Device, assets: &Assets) {
         match self {
             TextureBindingModel::Bindless(binder) => binder.update(device, assets),
@@ -692,6 +1315,22 @@ impl TextureBindingModel {
         }
     }
 
+    /// Bind group exposing `material`'s textures: the shared group in the
+    /// bindless model, the material's own group otherwise.
+    pub(crate) fn textures_for_material(
+        &mut self,
+        device: &wgpu::Device,
+        assets: &Assets,
+        material: Material,
+    ) -> &wgpu::BindGroup {
+        match self {
+            TextureBindingModel::Bindless(bindless) => bindless.global_bind_group(),
+            TextureBindingModel::Classic(classic) => {
+                classic.bind_group_for_material(device, assets, material)
+            }
+        }
+    }
+
     pub fn bind_group_for_material(
         &mut self,
         device: &wgpu::Device,
@@ -706,3 +1345,133 @@ impl TextureBindingModel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_parses(label: &str, source: &str) {
+        if let Err(err) = naga::front::wgsl::parse_str(source) {
+            panic!(
+                "{} shader failed to parse:\n{}",
+                label,
+                err.emit_to_string(source)
+            );
+        }
+    }
+
+    #[test]
+    fn shader_sources_parse_with_generated_structs() {
+        let sources = ShaderSources::default();
+        for bindless in [false, true] {
+            for shader in SceneShader::ALL {
+                assert_parses(
+                    &format!("{:?} (bindless: {})", shader, bindless),
+                    &with_texture_capacity(&sources.source(shader, bindless), 1024),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bindless_capacity_follows_device_limits() {
+        let limits = |array_elements, sampled_textures| wgpu::Limits {
+            max_binding_array_elements_per_shader_stage: array_elements,
+            max_sampled_textures_per_shader_stage: sampled_textures,
+            ..wgpu::Limits::default()
+        };
+        assert_eq!(
+            bindless_texture_capacity(&limits(500_000, 1_000_000)),
+            Some(4096)
+        );
+        assert_eq!(
+            bindless_texture_capacity(&limits(1000, 1_000_000)),
+            Some(512)
+        );
+        // Reserved bindings leave 268 slots, rounded down to 256.
+        assert_eq!(bindless_texture_capacity(&limits(1000, 300)), Some(256));
+        assert_eq!(bindless_texture_capacity(&limits(1000, 280)), None);
+        assert_eq!(bindless_texture_capacity(&limits(0, 1_000_000)), None);
+
+        let source = with_texture_capacity(texture_bindings_source(true), 512);
+        assert!(source.contains("binding_array<texture_2d<f32>, 512>"));
+        assert!(!source.contains(TEXTURE_CAPACITY_PLACEHOLDER));
+    }
+
+    #[test]
+    fn textures_past_the_bindless_capacity_fall_back() {
+        // 300 texture slots with slot 7 freed.
+        let textures: Vec<Option<u32>> = (0..300).map(|i| (i != 7).then_some(i)).collect();
+        let live = || {
+            textures
+                .iter()
+                .enumerate()
+                .filter_map(|(index, texture)| texture.as_ref().map(|texture| (index, texture)))
+        };
+        let fallback = u32::MAX;
+
+        let (views, overflow) = bindless_views(300, live(), &fallback, 256, false);
+        assert_eq!(views.len(), 256);
+        assert_eq!(*views[7], u32::MAX);
+        assert_eq!(*views[255], 255);
+        assert_eq!(overflow, (256..300).collect::<Vec<_>>());
+
+        let (views, overflow) = bindless_views(300, live(), &fallback, 512, true);
+        assert_eq!(views.len(), 300);
+        assert!(overflow.is_empty());
+
+        let (views, _) = bindless_views(300, live(), &fallback, 512, false);
+        assert_eq!(views.len(), 512);
+        assert!(views[300..].iter().all(|&&view| view == u32::MAX));
+
+        let (views, _) = bindless_views(0, [], &fallback, 512, true);
+        assert_eq!(views, vec![&u32::MAX]);
+    }
+
+    #[test]
+    fn main_shader_exports_debug_view_entry_points() {
+        let source = ShaderSources::default().source(SceneShader::Main, false);
+        let module = naga::front::wgsl::parse_str(&source).unwrap();
+        for entry in [
+            "fs_debug_wireframe",
+            "fs_debug_normals",
+            "fs_debug_uvs",
+            "fs_debug_overdraw",
+        ] {
+            assert!(
+                module.entry_points.iter().any(|ep| ep.name == entry),
+                "missing {}",
+                entry
+            );
+        }
+    }
+
+    #[test]
+    fn changed_files_map_to_the_modules_that_include_them() {
+        assert_eq!(SceneShader::using("common.wgsl"), vec![SceneShader::Main]);
+        assert_eq!(
+            SceneShader::using("constants.wgsl"),
+            vec![SceneShader::Main, SceneShader::Background]
+        );
+        assert_eq!(
+            SceneShader::using("bindings_bindless.wgsl"),
+            vec![SceneShader::Main, SceneShader::Depth]
+        );
+        assert!(SceneShader::using("postprocess.wgsl").is_empty());
+        for shader in SceneShader::ALL {
+            for file in shader.files() {
+                assert!(ShaderSources::builtin(file).is_some(), "{}", file);
+            }
+        }
+    }
+
+    #[test]
+    fn replaced_sources_can_be_restored() {
+        let mut sources = ShaderSources::default();
+        let builtin = sources.get("common.wgsl").to_string();
+        let previous = sources.replace("common.wgsl", "// edited".to_string());
+        assert_eq!(sources.get("common.wgsl"), "// edited");
+        sources.restore("common.wgsl", previous);
+        assert_eq!(sources.get("common.wgsl"), builtin);
+    }
+}
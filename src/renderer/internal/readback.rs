@@ -0,0 +1,404 @@
+//! Copies rendered frames back to the CPU. Headless renderers read their
+//! offscreen target directly; windowed renderers keep a copy of the last
+//! swapchain image because presented surface textures cannot be read.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Default)]
+struct MapState {
+    result: Option<Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// Staging buffer holding a copy of the top-left region of a texture.
+struct StagingCopy {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    swap_red_blue: bool,
+}
+
+impl StagingCopy {
+    /// Records a copy of the top-left `width` x `height` texels of `texture`
+    /// into a new staging buffer. Returns `None` for formats that are not
+    /// four bytes per texel.
+    fn record(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let format = texture.format();
+        if format.block_copy_size(None) != Some(4) {
+            log::warn!("Cannot read back pixels of {:?} frames", format);
+            return None;
+        }
+        let swap_red_blue = matches!(
+            format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+
+        let padded_bytes_per_row = padded_bytes_per_row(width * 4);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pixel Readback Buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Some(Self {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            swap_red_blue,
+        })
+    }
+
+    /// Starts mapping the buffer; the submitted copy must come first.
+    fn map(&self) -> Arc<Mutex<MapState>> {
+        let state = Arc::new(Mutex::new(MapState::default()));
+        let callback_state = Arc::clone(&state);
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let mut state = callback_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+        state
+    }
+
+    fn read_rows(self) -> Vec<u8> {
+        let unpadded_bytes_per_row = (self.width * 4) as usize;
+        let mapped = self.buffer.slice(..).get_mapped_range();
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        for row in mapped.chunks(self.padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+        }
+        drop(mapped);
+        self.buffer.unmap();
+
+        if self.swap_red_blue {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        pixels
+    }
+}
+
+/// Resolves to tightly packed RGBA8 rows once the staging buffer is mapped.
+/// Resolves to an empty `Vec` when the copy or the mapping failed.
+pub(crate) struct PixelReadback {
+    pending: Option<PendingReadback>,
+}
+
+struct PendingReadback {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    device: wgpu::Device,
+    copy: StagingCopy,
+    state: Arc<Mutex<MapState>>,
+}
+
+impl PixelReadback {
+    /// A readback that resolves to no pixels, after a warning was logged.
+    pub(crate) fn failed() -> Self {
+        Self { pending: None }
+    }
+
+    /// Copies the top-left `width` x `height` texels of `texture` into a
+    /// staging buffer and starts mapping it.
+    pub(crate) fn start(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Pixel Readback Encoder"),
+        });
+        let Some(copy) = StagingCopy::record(device, &mut encoder, texture, width, height) else {
+            return Self::failed();
+        };
+        queue.submit(Some(encoder.finish()));
+        let state = copy.map();
+
+        Self {
+            pending: Some(PendingReadback {
+                device: device.clone(),
+                copy,
+                state,
+            }),
+        }
+    }
+}
+
+impl Future for PixelReadback {
+    type Output = Vec<u8>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<u8>> {
+        let Some(pending) = self.pending.as_ref() else {
+            return Poll::Ready(Vec::new());
+        };
+
+        // Native backends only run map callbacks from `Device::poll`; the
+        // browser resolves them on its own event loop.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(err) = pending.device.poll(wgpu::PollType::wait_indefinitely()) {
+            log::warn!("Failed to wait for pixel readback: {:?}", err);
+            return Poll::Ready(Vec::new());
+        }
+
+        let result = {
+            let mut state = pending.state.lock().unwrap();
+            match state.result.take() {
+                Some(result) => result,
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        };
+
+        let pending = self.pending.take().unwrap();
+        if let Err(err) = result {
+            log::warn!("Failed to map pixel readback buffer: {}", err);
+            return Poll::Ready(Vec::new());
+        }
+        Poll::Ready(pending.copy.read_rows())
+    }
+}
+
+/// A captured frame as tightly packed RGBA8 rows, top row first.
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Encodes the screenshot as a PNG file in memory.
+    pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+        let image = image::RgbaImage::from_raw(self.width, self.height, self.pixels.clone())
+            .ok_or_else(|| "Screenshot pixel buffer has the wrong size".to_string())?;
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut bytes, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode screenshot: {}", e))?;
+        Ok(bytes.into_inner())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let bytes = self.encode_png()?;
+        std::fs::write(path, bytes)
+            .map_err(|e| format!("Failed to write screenshot {:?}: {}", path, e))
+    }
+}
+
+pub(crate) type ScreenshotCallback = Box<dyn FnOnce(Screenshot)>;
+
+struct InFlightScreenshot {
+    copy: StagingCopy,
+    state: Option<Arc<Mutex<MapState>>>,
+    callback: ScreenshotCallback,
+}
+
+/// Screenshot requests waiting for the next frame, and copies of earlier
+/// frames waiting for their staging buffer to map. Nothing here blocks:
+/// finished mappings are picked up by [`Self::poll`] on a later frame.
+#[derive(Default)]
+pub(crate) struct ScreenshotQueue {
+    requested: Vec<ScreenshotCallback>,
+    in_flight: Vec<InFlightScreenshot>,
+}
+
+impl ScreenshotQueue {
+    pub(crate) fn request(&mut self, callback: ScreenshotCallback) {
+        self.requested.push(callback);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.requested.is_empty() && self.in_flight.is_empty()
+    }
+
+    /// Records copies of `frame` for every pending request.
+    pub(crate) fn record(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::Texture,
+    ) {
+        if self.requested.is_empty() {
+            return;
+        }
+        if !frame.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            log::warn!(
+                "Frame texture cannot be copied; dropping {} screenshot request(s)",
+                self.requested.len()
+            );
+            self.requested.clear();
+            return;
+        }
+
+        let size = frame.size();
+        for callback in self.requested.drain(..) {
+            if let Some(copy) = StagingCopy::record(device, encoder, frame, size.width, size.height)
+            {
+                self.in_flight.push(InFlightScreenshot {
+                    copy,
+                    state: None,
+                    callback,
+                });
+            }
+        }
+    }
+
+    /// Starts mapping the copies recorded into the frame just submitted.
+    pub(crate) fn after_submit(&mut self) {
+        for screenshot in &mut self.in_flight {
+            if screenshot.state.is_none() {
+                screenshot.state = Some(screenshot.copy.map());
+            }
+        }
+    }
+
+    /// Hands finished screenshots to their callbacks without waiting on the
+    /// GPU.
+    pub(crate) fn poll(&mut self, device: &wgpu::Device) {
+        if self.in_flight.is_empty() {
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(err) = device.poll(wgpu::PollType::Poll) {
+            log::warn!("Failed to poll screenshot readback: {:?}", err);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = device;
+
+        let mut index = 0;
+        while index < self.in_flight.len() {
+            let result = self.in_flight[index]
+                .state
+                .as_ref()
+                .and_then(|state| state.lock().unwrap().result.take());
+            let Some(result) = result else {
+                index += 1;
+                continue;
+            };
+
+            let screenshot = self.in_flight.swap_remove(index);
+            match result {
+                Ok(()) => {
+                    let (width, height) = (screenshot.copy.width, screenshot.copy.height);
+                    let pixels = screenshot.copy.read_rows();
+                    (screenshot.callback)(Screenshot {
+                        width,
+                        height,
+                        pixels,
+                    });
+                }
+                Err(err) => log::warn!("Failed to map screenshot buffer: {}", err),
+            }
+        }
+    }
+}
+
+/// Copy of the most recent swapchain image, kept while frame capture is
+/// enabled so windowed renderers can be read back after presenting.
+pub(crate) struct FrameCapture {
+    texture: Option<wgpu::Texture>,
+}
+
+impl FrameCapture {
+    pub(crate) fn new() -> Self {
+        Self { texture: None }
+    }
+
+    pub(crate) fn texture(&self) -> Option<&wgpu::Texture> {
+        self.texture.as_ref()
+    }
+
+    /// Records a copy of `frame` into the capture texture, recreating it
+    /// when the frame size or format changed.
+    pub(crate) fn record(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::Texture,
+    ) {
+        if !frame.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return;
+        }
+
+        let stale = self.texture.as_ref().is_none_or(|texture| {
+            texture.size() != frame.size() || texture.format() != frame.format()
+        });
+        if stale {
+            self.texture = Some(device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Frame Capture Texture"),
+                size: frame.size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: frame.format(),
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            }));
+        }
+
+        if let Some(texture) = &self.texture {
+            encoder.copy_texture_to_texture(
+                frame.as_image_copy(),
+                texture.as_image_copy(),
+                frame.size(),
+            );
+        }
+    }
+}
+
+pub(crate) fn padded_bytes_per_row(unpadded: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    unpadded.div_ceil(align) * align
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_padded_to_copy_alignment() {
+        assert_eq!(padded_bytes_per_row(4), wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        assert_eq!(padded_bytes_per_row(256), 256);
+        assert_eq!(padded_bytes_per_row(260), 512);
+    }
+}
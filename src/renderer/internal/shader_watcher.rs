@@ -0,0 +1,111 @@
+//! Watches the crate's `src/shader/` directory so edited WGSL files can be
+//! recompiled without restarting the application.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader");
+
+pub(crate) struct ShaderWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl ShaderWatcher {
+    /// Starts watching the shader directory. Returns `None` when it no
+    /// longer exists next to the binary or the watch cannot be set up.
+    pub(crate) fn new() -> Option<Self> {
+        let dir = Path::new(SHADER_DIR);
+        if !dir.is_dir() {
+            log::debug!("Shader hot reload disabled: {} not found", SHADER_DIR);
+            return None;
+        }
+
+        let (sender, events) = mpsc::channel();
+        let handler = move |event: notify::Result<notify::Event>| {
+            sender.send(event).ok();
+        };
+        let mut watcher = match notify::recommended_watcher(handler) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                log::warn!("Failed to create shader watcher: {}", err);
+                return None;
+            }
+        };
+        if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            log::warn!("Failed to watch {}: {}", SHADER_DIR, err);
+            return None;
+        }
+
+        log::info!("Watching {} for shader changes", SHADER_DIR);
+        Some(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// `.wgsl` files created or modified since the last poll, each listed
+    /// once with its current contents. Files that cannot be read are logged
+    /// and skipped.
+    pub(crate) fn poll(&self) -> Vec<(String, String)> {
+        let mut paths = Vec::new();
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    paths.extend(event.paths);
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("Shader watcher error: {}", err),
+            }
+        }
+
+        changed_wgsl_files(&paths)
+            .into_iter()
+            .filter_map(|file| {
+                let path = Path::new(SHADER_DIR).join(&file);
+                match std::fs::read_to_string(&path) {
+                    Ok(source) => Some((file, source)),
+                    Err(err) => {
+                        log::warn!("Failed to read {}: {}", path.display(), err);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// File names of the `.wgsl` paths among `paths`, sorted and deduplicated.
+fn changed_wgsl_files(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wgsl"))
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_wgsl_files_are_reported_once() {
+        let dir = Path::new(SHADER_DIR);
+        let paths = vec![
+            dir.join("common.wgsl"),
+            dir.join("common.wgsl.swp"),
+            dir.join("constants.wgsl"),
+            dir.join("common.wgsl"),
+            dir.join("notes.txt"),
+        ];
+        assert_eq!(
+            changed_wgsl_files(&paths),
+            vec!["common.wgsl".to_string(), "constants.wgsl".to_string()]
+        );
+    }
+}
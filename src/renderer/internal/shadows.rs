@@ -5,13 +5,18 @@ use bytemuck::{Pod, Zeroable};
 use glam::Mat4;
 
 use crate::asset::Assets;
-use crate::renderer::internal::{DynamicObjectsBuffer, OrderedBatch, RenderContext};
+use crate::renderer::internal::cookies::SpotCookies;
+use crate::renderer::internal::pipeline::{texture_bindings_source, with_texture_capacity};
+use crate::renderer::internal::{
+    DynamicObjectsBuffer, OrderedBatch, RenderContext, TextureBindingModel,
+};
 use crate::renderer::lights::{
     LightsData, MAX_DIRECTIONAL_LIGHTS, MAX_POINT_LIGHTS, MAX_SPOT_LIGHTS,
 };
 use crate::renderer::material::Material;
 use crate::renderer::{PipelineBuilder, RenderPass};
-use crate::renderer::Vertex;
+use crate::renderer::{SkinVertex, Vertex};
+use crate::settings::{LightShadowSettings, ShadowSettings};
 
 const POINT_SHADOW_FACE_COUNT: usize = 6;
 const POINT_SHADOW_LAYERS: u32 = (MAX_POINT_LIGHTS * POINT_SHADOW_FACE_COUNT) as u32;
@@ -97,6 +102,163 @@ impl ShadowArray {
     }
 }
 
+fn same_bias(a: &LightShadowSettings, b: &LightShadowSettings) -> bool {
+    a.constant_bias == b.constant_bias && a.slope_bias == b.slope_bias
+}
+
+fn with_bias<'a>(
+    builder: PipelineBuilder<'a>,
+    settings: &LightShadowSettings,
+) -> PipelineBuilder<'a> {
+    builder.with_depth_stencil_biased(
+        wgpu::TextureFormat::Depth32Float,
+        true,
+        wgpu::CompareFunction::LessEqual,
+        settings.constant_bias,
+        settings.slope_bias,
+    )
+}
+
+fn build_caster(
+    builder: PipelineBuilder<'_>,
+    settings: &LightShadowSettings,
+    double_sided: bool,
+) -> wgpu::RenderPipeline {
+    let builder = with_bias(builder, settings);
+    if double_sided {
+        builder.with_no_culling().build()
+    } else {
+        builder.build()
+    }
+}
+
+/// Shadow caster pipelines built with one light type's depth bias. Double
+/// sided materials cast from their back faces too, so each variant also
+/// exists without culling.
+struct ShadowPipelines {
+    culled: ShadowVariants,
+    double_sided: ShadowVariants,
+}
+
+impl ShadowPipelines {
+    fn new(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        layout: &wgpu::PipelineLayout,
+        masked_layout: &wgpu::PipelineLayout,
+        light: &str,
+        settings: &LightShadowSettings,
+    ) -> Self {
+        Self {
+            culled: ShadowVariants::new(
+                device,
+                shader,
+                layout,
+                masked_layout,
+                light,
+                settings,
+                false,
+            ),
+            double_sided: ShadowVariants::new(
+                device,
+                shader,
+                layout,
+                masked_layout,
+                &format!("{light}DoubleSided"),
+                settings,
+                true,
+            ),
+        }
+    }
+
+    fn select(&self, skinned: bool, alpha_mask: bool, double_sided: bool) -> &wgpu::RenderPipeline {
+        let variants = if double_sided {
+            &self.double_sided
+        } else {
+            &self.culled
+        };
+        variants.select(skinned, alpha_mask)
+    }
+}
+
+/// The four shadow caster pipelines for one culling mode.
+struct ShadowVariants {
+    opaque: wgpu::RenderPipeline,
+    skinned: wgpu::RenderPipeline,
+    masked: wgpu::RenderPipeline,
+    masked_skinned: wgpu::RenderPipeline,
+}
+
+impl ShadowVariants {
+    fn new(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        layout: &wgpu::PipelineLayout,
+        masked_layout: &wgpu::PipelineLayout,
+        light: &str,
+        settings: &LightShadowSettings,
+        double_sided: bool,
+    ) -> Self {
+        let opaque = build_caster(
+            PipelineBuilder::new(device, layout, shader)
+                .with_label(&format!("{light}ShadowPipeline"))
+                .with_vertex_entry("vs_main")
+                .depth_only() // No fragment shader for shadow pass
+                .with_vertex_buffer(Vertex::layout()),
+            settings,
+            double_sided,
+        );
+
+        let skinned = build_caster(
+            PipelineBuilder::new(device, layout, shader)
+                .with_label(&format!("{light}SkinnedShadowPipeline"))
+                .with_vertex_entry("vs_main_skinned")
+                .depth_only()
+                .with_vertex_buffer(Vertex::layout())
+                .with_vertex_buffer(SkinVertex::layout()),
+            settings,
+            double_sided,
+        );
+
+        let masked = build_caster(
+            PipelineBuilder::new(device, masked_layout, shader)
+                .with_label(&format!("{light}MaskedShadowPipeline"))
+                .with_vertex_entry("vs_masked")
+                .with_fragment_entry("fs_alpha_mask")
+                .with_vertex_buffer(Vertex::layout()),
+            settings,
+            double_sided,
+        );
+
+        let masked_skinned = build_caster(
+            PipelineBuilder::new(device, masked_layout, shader)
+                .with_label(&format!("{light}MaskedSkinnedShadowPipeline"))
+                .with_vertex_entry("vs_masked_skinned")
+                .with_fragment_entry("fs_alpha_mask")
+                .with_vertex_buffer(Vertex::layout())
+                .with_vertex_buffer(SkinVertex::layout()),
+            settings,
+            double_sided,
+        );
+
+        Self {
+            opaque,
+            skinned,
+            masked,
+            masked_skinned,
+        }
+    }
+
+    fn select(&self, skinned: bool, alpha_mask: bool) -> &wgpu::RenderPipeline {
+        match (skinned, alpha_mask) {
+            (false, false) => &self.opaque,
+            (true, false) => &self.skinned,
+            (false, true) => &self.masked,
+            (true, true) => &self.masked_skinned,
+        }
+    }
+}
+
 pub(crate) struct ShadowResources {
     directional: ShadowArray,
     spot: ShadowArray,
@@ -105,34 +267,35 @@ pub(crate) struct ShadowResources {
     uniform_buffer: wgpu::Buffer,
     uniform_bind_group: wgpu::BindGroup,
     _uniform_layout: wgpu::BindGroupLayout,
-    pipeline: wgpu::RenderPipeline,
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    masked_layout: wgpu::PipelineLayout,
+    directional_pipelines: ShadowPipelines,
+    spot_pipelines: ShadowPipelines,
+    point_pipelines: ShadowPipelines,
+    settings: ShadowSettings,
+    /// Directional shadow map layers per light, one per cascade.
+    cascade_count: usize,
+    /// Fills group 2 so the masked pipelines find textures at group 3, as
+    /// in the main pass.
+    empty_bind_group: wgpu::BindGroup,
     staging_buffer: wgpu::Buffer,
+    cookies: SpotCookies,
 }
 
 impl ShadowResources {
     pub(crate) fn new(
         device: &wgpu::Device,
         objects: &DynamicObjectsBuffer,
-        shadow_map_size: u32,
+        texture_bind_layout: &wgpu::BindGroupLayout,
+        // Bindless texture array length, `None` for the classic model.
+        texture_capacity: Option<u32>,
+        settings: &ShadowSettings,
+        cascade_count: usize,
     ) -> Self {
-        let directional = ShadowArray::new(
-            device,
-            "DirectionalShadowMap",
-            MAX_DIRECTIONAL_LIGHTS as u32,
-            shadow_map_size,
-        );
-        let spot = ShadowArray::new(
-            device,
-            "SpotShadowMap",
-            MAX_SPOT_LIGHTS as u32,
-            shadow_map_size,
-        );
-        let point = ShadowArray::new(
-            device,
-            "PointShadowMap",
-            POINT_SHADOW_LAYERS,
-            shadow_map_size,
-        );
+        let directional = Self::directional_array(device, settings, cascade_count);
+        let spot = Self::spot_array(device, settings);
+        let point = Self::point_array(device, settings);
 
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("ShadowSampler"),
@@ -171,7 +334,7 @@ impl ShadowResources {
             mapped_at_creation: false,
         });
 
-        let max_shadows = (MAX_DIRECTIONAL_LIGHTS
+        let max_shadows = (MAX_DIRECTIONAL_LIGHTS * cascade_count
             + MAX_SPOT_LIGHTS
             + MAX_POINT_LIGHTS * POINT_SHADOW_FACE_COUNT) as u64;
         let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -192,8 +355,8 @@ impl ShadowResources {
             mem::size_of::<ShadowViewUniform>() as u64 * max_shadows
         );
         log::info!(
-            "  Breakdown: {} dir + {} spot + {} point faces",
-            MAX_DIRECTIONAL_LIGHTS,
+            "  Breakdown: {} dir cascades + {} spot + {} point faces",
+            MAX_DIRECTIONAL_LIGHTS * cascade_count,
             MAX_SPOT_LIGHTS,
             MAX_POINT_LIGHTS * POINT_SHADOW_FACE_COUNT
         );
@@ -209,7 +372,7 @@ impl ShadowResources {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("ShadowShader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../../shader/shadow.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(shader_source(texture_capacity).into()),
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -218,19 +381,50 @@ impl ShadowResources {
             push_constant_ranges: &[],
         });
 
-        let pipeline = PipelineBuilder::new(device, &pipeline_layout, &shader)
-            .with_label("ShadowPipeline")
-            .with_vertex_entry("vs_main")
-            .depth_only() // No fragment shader for shadow pass
-            .with_vertex_buffer(Vertex::layout())
-            .with_depth_stencil_biased(
-                wgpu::TextureFormat::Depth32Float,
-                true,
-                wgpu::CompareFunction::LessEqual,
-                2,   // constant bias
-                2.0, // slope bias
-            )
-            .build();
+        let empty_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ShadowEmptyLayout"),
+            entries: &[],
+        });
+        let empty_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ShadowEmptyBindGroup"),
+            layout: &empty_layout,
+            entries: &[],
+        });
+        let masked_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("MaskedShadowPipelineLayout"),
+            bind_group_layouts: &[
+                &uniform_layout,
+                &objects.bind_layout,
+                &empty_layout,
+                texture_bind_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let directional_pipelines = ShadowPipelines::new(
+            device,
+            &shader,
+            &pipeline_layout,
+            &masked_layout,
+            "Directional",
+            &settings.directional,
+        );
+        let spot_pipelines = ShadowPipelines::new(
+            device,
+            &shader,
+            &pipeline_layout,
+            &masked_layout,
+            "Spot",
+            &settings.spot,
+        );
+        let point_pipelines = ShadowPipelines::new(
+            device,
+            &shader,
+            &pipeline_layout,
+            &masked_layout,
+            "Point",
+            &settings.point,
+        );
 
         Self {
             directional,
@@ -240,11 +434,114 @@ impl ShadowResources {
             uniform_buffer,
             uniform_bind_group,
             _uniform_layout: uniform_layout,
-            pipeline,
+            shader,
+            pipeline_layout,
+            masked_layout,
+            directional_pipelines,
+            spot_pipelines,
+            point_pipelines,
+            settings: *settings,
+            cascade_count,
+            empty_bind_group,
             staging_buffer,
+            cookies: SpotCookies::new(device),
         }
     }
 
+    fn directional_array(
+        device: &wgpu::Device,
+        settings: &ShadowSettings,
+        cascade_count: usize,
+    ) -> ShadowArray {
+        ShadowArray::new(
+            device,
+            "DirectionalShadowMap",
+            (MAX_DIRECTIONAL_LIGHTS * cascade_count) as u32,
+            settings.directional.resolution,
+        )
+    }
+
+    fn spot_array(device: &wgpu::Device, settings: &ShadowSettings) -> ShadowArray {
+        ShadowArray::new(
+            device,
+            "SpotShadowMap",
+            MAX_SPOT_LIGHTS as u32,
+            settings.spot.resolution,
+        )
+    }
+
+    fn point_array(device: &wgpu::Device, settings: &ShadowSettings) -> ShadowArray {
+        ShadowArray::new(
+            device,
+            "PointShadowMap",
+            POINT_SHADOW_LAYERS,
+            settings.point.resolution,
+        )
+    }
+
+    pub(crate) fn settings(&self) -> &ShadowSettings {
+        &self.settings
+    }
+
+    pub(crate) fn cascade_count(&self) -> usize {
+        self.cascade_count
+    }
+
+    /// Rebuilds the pipelines whose depth bias changed and the shadow maps
+    /// whose resolution changed. Returns `true` when a shadow map was
+    /// recreated; the caller must then rebuild any bind group that
+    /// references the array views.
+    pub(crate) fn apply_settings(
+        &mut self,
+        device: &wgpu::Device,
+        settings: &ShadowSettings,
+    ) -> bool {
+        let previous = mem::replace(&mut self.settings, *settings);
+        let mut maps_changed = false;
+
+        if previous.directional.resolution != settings.directional.resolution {
+            self.directional = Self::directional_array(device, settings, self.cascade_count);
+            maps_changed = true;
+        }
+        if previous.spot.resolution != settings.spot.resolution {
+            self.spot = Self::spot_array(device, settings);
+            maps_changed = true;
+        }
+        if previous.point.resolution != settings.point.resolution {
+            self.point = Self::point_array(device, settings);
+            maps_changed = true;
+        }
+
+        if !same_bias(&previous.directional, &settings.directional) {
+            self.directional_pipelines =
+                self.build_pipelines(device, "Directional", &settings.directional);
+        }
+        if !same_bias(&previous.spot, &settings.spot) {
+            self.spot_pipelines = self.build_pipelines(device, "Spot", &settings.spot);
+        }
+        if !same_bias(&previous.point, &settings.point) {
+            self.point_pipelines = self.build_pipelines(device, "Point", &settings.point);
+        }
+
+        maps_changed
+    }
+
+    fn build_pipelines(
+        &self,
+        device: &wgpu::Device,
+        light: &str,
+        settings: &LightShadowSettings,
+    ) -> ShadowPipelines {
+        ShadowPipelines::new(
+            device,
+            &self.shader,
+            &self.pipeline_layout,
+            &self.masked_layout,
+            light,
+            settings,
+        )
+    }
+
     pub(crate) fn directional_array_view(&self) -> &wgpu::TextureView {
         self.directional.array_view()
     }
@@ -261,6 +558,14 @@ impl ShadowResources {
         &self.sampler
     }
 
+    pub(crate) fn cookie_array_view(&self) -> &wgpu::TextureView {
+        self.cookies.view()
+    }
+
+    pub(crate) fn cookie_sampler(&self) -> &wgpu::Sampler {
+        self.cookies.sampler()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn render(
         &mut self,
@@ -271,10 +576,15 @@ impl ShadowResources {
         lights: &LightsData,
         objects: &DynamicObjectsBuffer,
         materials: &[Material],
-    ) {
+        textures: &mut TextureBindingModel,
+    ) -> u32 {
+        self.cookies
+            .update(&context.device, encoder, assets, lights);
+
         if batches.is_empty() {
-            return;
+            return 0;
         }
+        let mut views = 0;
 
         let queue = &context.queue;
         let uniform_size = mem::size_of::<ShadowViewUniform>() as u64;
@@ -285,24 +595,27 @@ impl ShadowResources {
             .iter()
             .take(MAX_DIRECTIONAL_LIGHTS)
         {
-            if shadow.params[0] == 0.0 {
-                continue;
+            for view_proj in shadow
+                .view_proj
+                .iter()
+                .take(shadow.cascade_count().min(self.cascade_count))
+            {
+                let matrix = Mat4::from_cols_array_2d(view_proj);
+                let uniform = ShadowViewUniform {
+                    view_proj: matrix.to_cols_array_2d(),
+                };
+                queue.write_buffer(
+                    &self.staging_buffer,
+                    staging_offset,
+                    bytemuck::bytes_of(&uniform),
+                );
+                staging_offset += uniform_size;
             }
-            let matrix = Mat4::from_cols_array_2d(&shadow.view_proj);
-            let uniform = ShadowViewUniform {
-                view_proj: matrix.to_cols_array_2d(),
-            };
-            queue.write_buffer(
-                &self.staging_buffer,
-                staging_offset,
-                bytemuck::bytes_of(&uniform),
-            );
-            staging_offset += uniform_size;
         }
 
         let spot_start_offset = staging_offset;
         for shadow in lights.spot_shadows().iter().take(MAX_SPOT_LIGHTS) {
-            if shadow.params[0] == 0.0 {
+            if !shadow.has_shadow() {
                 continue;
             }
             let matrix = Mat4::from_cols_array_2d(&shadow.view_proj);
@@ -319,7 +632,7 @@ impl ShadowResources {
 
         let point_start_offset = staging_offset;
         for shadow in lights.point_shadows().iter().take(MAX_POINT_LIGHTS) {
-            if shadow.params[0] == 0.0 {
+            if !shadow.has_shadow() {
                 continue;
             }
 
@@ -344,28 +657,32 @@ impl ShadowResources {
             .enumerate()
             .take(MAX_DIRECTIONAL_LIGHTS)
         {
-            if shadow.params[0] == 0.0 {
-                continue;
-            }
+            for cascade in 0..shadow.cascade_count().min(self.cascade_count) {
+                let layer_index = index * self.cascade_count + cascade;
 
-            encoder.copy_buffer_to_buffer(
-                &self.staging_buffer,
-                staging_offset,
-                &self.uniform_buffer,
-                0,
-                uniform_size,
-            );
+                encoder.copy_buffer_to_buffer(
+                    &self.staging_buffer,
+                    staging_offset,
+                    &self.uniform_buffer,
+                    0,
+                    uniform_size,
+                );
 
-            self.render_pass(
-                encoder,
-                self.directional.layer_view(index),
-                assets,
-                batches,
-                objects,
-                materials,
-            );
+                self.render_pass(
+                    &context.device,
+                    encoder,
+                    &self.directional_pipelines,
+                    self.directional.layer_view(layer_index),
+                    assets,
+                    batches,
+                    objects,
+                    materials,
+                    textures,
+                );
+                views += 1;
 
-            staging_offset += uniform_size;
+                staging_offset += uniform_size;
+            }
         }
 
         let mut spot_staging_offset = spot_start_offset;
@@ -375,7 +692,7 @@ impl ShadowResources {
             .enumerate()
             .take(MAX_SPOT_LIGHTS)
         {
-            if shadow.params[0] == 0.0 {
+            if !shadow.has_shadow() {
                 continue;
             }
 
@@ -388,13 +705,17 @@ impl ShadowResources {
             );
 
             self.render_pass(
+                &context.device,
                 encoder,
+                &self.spot_pipelines,
                 self.spot.layer_view(index),
                 assets,
                 batches,
                 objects,
                 materials,
+                textures,
             );
+            views += 1;
 
             spot_staging_offset += uniform_size;
         }
@@ -407,7 +728,7 @@ impl ShadowResources {
             .enumerate()
             .take(MAX_POINT_LIGHTS)
         {
-            if shadow.params[0] == 0.0 {
+            if !shadow.has_shadow() {
                 continue;
             }
 
@@ -423,27 +744,36 @@ impl ShadowResources {
                 );
 
                 self.render_pass(
+                    &context.device,
                     encoder,
+                    &self.point_pipelines,
                     self.point.layer_view(layer_index),
                     assets,
                     batches,
                     objects,
                     materials,
+                    textures,
                 );
+                views += 1;
 
                 point_staging_offset += uniform_size;
             }
         }
+        views
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_pass(
         &self,
+        device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
+        pipelines: &ShadowPipelines,
         view: &wgpu::TextureView,
         assets: &Assets,
         batches: &[OrderedBatch],
         objects: &DynamicObjectsBuffer,
         materials: &[Material],
+        textures: &mut TextureBindingModel,
     ) {
         if batches.is_empty() {
             return;
@@ -463,9 +793,10 @@ impl ShadowResources {
             occlusion_query_set: None,
         });
 
-        pass.set_pipeline(&self.pipeline);
+        pass.set_pipeline(pipelines.select(false, false, false));
         pass.set_bind_group(0, &self.uniform_bind_group, &[]);
         pass.set_bind_group(1, &objects.bind_group, &[]);
+        pass.set_bind_group(2, &self.empty_bind_group, &[]);
 
         for batch in batches {
             if matches!(batch.pass, RenderPass::Transparent | RenderPass::Overlay) {
@@ -476,34 +807,57 @@ impl ShadowResources {
             };
 
             let instance_count = batch.instances.len() as u32;
+            pass.set_pipeline(pipelines.select(
+                mesh.skin_buffer().is_some(),
+                batch.alpha_mask,
+                batch.double_sided,
+            ));
+            if let Some(skin_buffer) = mesh.skin_buffer() {
+                pass.set_vertex_buffer(1, skin_buffer.slice(..));
+            }
             pass.set_vertex_buffer(0, mesh.vertex_buffer().slice(..));
             pass.set_index_buffer(mesh.index_buffer().slice(..), mesh.index_format());
-            let mut current_range_start: Option<u32> = None;
+
+            // Runs of shadow-casting instances. Masked batches also split
+            // runs per material so each run binds its own textures.
+            let mut current_run: Option<(u32, u32)> = None;
 
             for (local_index, instance) in batch.instances.iter().enumerate() {
                 let global_index = batch.first_instance + local_index as u32;
                 let material_index = instance.material_index as usize;
-                let Some(material) = materials.get(material_index) else {
+                let material = materials.get(material_index);
+                if material.is_none() {
                     log::warn!(
                         "Material index {} out of bounds during shadow rendering ({} materials)",
                         material_index,
                         materials.len()
                     );
-                    if let Some(start) = current_range_start.take() {
-                        pass.draw_indexed(0..mesh.index_count(), 0, start..global_index);
-                    }
+                }
+                let casts_shadow = material.is_some_and(|m| !m.is_unlit());
+                let continues_run = matches!(
+                    current_run,
+                    Some((_, run_material))
+                        if casts_shadow
+                            && (!batch.alpha_mask || run_material == instance.material_index)
+                );
+                if continues_run {
+                    continue;
+                }
+
+                if let Some((start, _)) = current_run.take() {
+                    pass.draw_indexed(0..mesh.index_count(), 0, start..global_index);
+                }
+                let Some(material) = material.filter(|_| casts_shadow) else {
                     continue;
                 };
-                if material.is_unlit() {
-                    if let Some(start) = current_range_start.take() {
-                        pass.draw_indexed(0..mesh.index_count(), 0, start..global_index);
-                    }
-                } else if current_range_start.is_none() {
-                    current_range_start = Some(global_index);
+                if batch.alpha_mask {
+                    let bind_group = textures.textures_for_material(device, assets, *material);
+                    pass.set_bind_group(3, bind_group, &[]);
                 }
+                current_run = Some((global_index, instance.material_index));
             }
 
-            if let Some(start) = current_range_start.take() {
+            if let Some((start, _)) = current_run.take() {
                 pass.draw_indexed(
                     0..mesh.index_count(),
                     0,
@@ -513,3 +867,30 @@ impl ShadowResources {
         }
     }
 }
+
+fn shader_source(texture_capacity: Option<u32>) -> String {
+    let bindings = texture_bindings_source(texture_capacity.is_some());
+    format!(
+        "{}\n{}",
+        with_texture_capacity(bindings, texture_capacity.unwrap_or_default()),
+        include_str!("../../shader/shadow.wgsl")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadow_shader_parses_with_both_texture_models() {
+        for texture_capacity in [None, Some(256)] {
+            let source = shader_source(texture_capacity);
+            if let Err(err) = naga::front::wgsl::parse_str(&source) {
+                panic!(
+                    "shadow shader failed to parse:\n{}",
+                    err.emit_to_string(&source)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Mip-chained copy of the opaque scene colour, sampled by transmissive
+//! materials for screen-space refraction. Rougher surfaces read blurrier
+//! mips.
+
+pub(crate) struct TransmissionResources {
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    format: wgpu::TextureFormat,
+    mip_views: Vec<wgpu::TextureView>,
+    downsample_groups: Vec<wgpu::BindGroup>,
+    bind_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl TransmissionResources {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("TransmissionBlitShader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../blit.wgsl").into()),
+        });
+
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("TransmissionBlitLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("TransmissionBlitPipelineLayout"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("TransmissionBlitPipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("TransmissionSampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let (texture, view, mip_views, downsample_groups) =
+            Self::create_targets(device, &bind_layout, &sampler, width, height, format);
+
+        Self {
+            _texture: texture,
+            view,
+            sampler,
+            format,
+            mip_views,
+            downsample_groups,
+            bind_layout,
+            pipeline,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn create_targets(
+        device: &wgpu::Device,
+        bind_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> (
+        wgpu::Texture,
+        wgpu::TextureView,
+        Vec<wgpu::TextureView>,
+        Vec<wgpu::BindGroup>,
+    ) {
+        let width = width.max(1);
+        let height = height.max(1);
+        let mip_level_count = mip_level_count(width, height);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("TransmissionColor"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mip_views: Vec<wgpu::TextureView> = (0..mip_level_count)
+            .map(|mip| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("TransmissionMip"),
+                    base_mip_level: mip,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        // Bind group `i` reads mip `i` to write mip `i + 1`.
+        let downsample_groups = mip_views
+            .iter()
+            .take(mip_views.len().saturating_sub(1))
+            .map(|source| Self::blit_bind_group(device, bind_layout, sampler, source))
+            .collect();
+
+        (texture, view, mip_views, downsample_groups)
+    }
+
+    fn blit_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        source: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("TransmissionBlitBindGroup"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    /// Recreates the mip chain for a new surface size. The caller must
+    /// rebuild any bind group that references [`Self::view`].
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let (texture, view, mip_views, downsample_groups) = Self::create_targets(
+            device,
+            &self.bind_layout,
+            &self.sampler,
+            width,
+            height,
+            self.format,
+        );
+        self._texture = texture;
+        self.view = view;
+        self.mip_views = mip_views;
+        self.downsample_groups = downsample_groups;
+    }
+
+    /// Copies `scene_color` (single-sampled, same size) into mip 0 and
+    /// downsamples the rest of the chain.
+    pub(crate) fn capture(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        scene_color: &wgpu::TextureView,
+    ) {
+        let source = Self::blit_bind_group(device, &self.bind_layout, &self.sampler, scene_color);
+        self.blit(encoder, &source, &self.mip_views[0]);
+        for (group, target) in self.downsample_groups.iter().zip(&self.mip_views[1..]) {
+            self.blit(encoder, group, target);
+        }
+    }
+
+    fn blit(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::BindGroup,
+        target: &wgpu::TextureView,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("TransmissionBlit"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, source, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    pub(crate) fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub(crate) fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+}
+
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_chain_reaches_one_pixel() {
+        assert_eq!(mip_level_count(1, 1), 1);
+        assert_eq!(mip_level_count(1920, 1080), 11);
+        assert_eq!(mip_level_count(64, 64), 7);
+    }
+}
@@ -1,9 +1,15 @@
 use bytemuck::{Pod, Zeroable};
 use glam::{Mat4, Vec3};
 
+use crate::renderer::gpu_layout::POINT_SHADOW_FACE_COUNT;
+use crate::scene::components::AttenuationOverride;
+use crate::settings::ShadowSettings;
+
 pub const MAX_DIRECTIONAL_LIGHTS: usize = 4;
 pub const MAX_POINT_LIGHTS: usize = 4;
 pub const MAX_SPOT_LIGHTS: usize = 4;
+/// Upper bound for [`RenderSettings::cascade_count`](crate::settings::RenderSettings::cascade_count).
+pub const MAX_SHADOW_CASCADES: usize = 4;
 
 #[derive(Clone, Default)]
 pub struct LightsData {
@@ -13,6 +19,7 @@ pub struct LightsData {
     directional_shadows: Vec<DirectionalShadowRaw>,
     point_shadows: Vec<PointShadowRaw>,
     spot_shadows: Vec<SpotShadowRaw>,
+    spot_cookies: Vec<Option<u32>>,
 }
 
 #[derive(Clone, Copy)]
@@ -25,6 +32,7 @@ pub struct SpotLightDescriptor {
     pub inner_angle: f32,
     pub outer_angle: f32,
     pub shadow: Option<SpotShadowData>,
+    pub cookie: Option<SpotCookieData>,
 }
 
 impl LightsData {
@@ -39,6 +47,7 @@ impl LightsData {
         self.directional_shadows.clear();
         self.point_shadows.clear();
         self.spot_shadows.clear();
+        self.spot_cookies.clear();
     }
 
     pub fn add_directional(
@@ -46,7 +55,7 @@ impl LightsData {
         direction: Vec3,
         color: Vec3,
         intensity: f32,
-        shadow: Option<DirectionalShadowData>,
+        shadow: Option<CascadedShadowData>,
     ) {
         self.directional
             .push(DirectionalLightRaw::new(direction, color, intensity));
@@ -61,14 +70,16 @@ impl LightsData {
         intensity: f32,
         range: f32,
         shadow: Option<PointShadowData>,
+        attenuation: Option<AttenuationOverride>,
     ) {
-        self.point
-            .push(PointLightRaw::new(position, color, intensity, range));
+        self.point.push(
+            PointLightRaw::new(position, color, intensity, range).with_attenuation(attenuation),
+        );
         self.point_shadows.push(PointShadowRaw::from_data(shadow));
     }
 
     pub fn add_spot(&mut self, descriptor: SpotLightDescriptor) {
-        self.spot.push(SpotLightRaw::new(
+        let mut raw = SpotLightRaw::new(
             descriptor.position,
             descriptor.direction,
             descriptor.color,
@@ -76,9 +87,18 @@ impl LightsData {
             descriptor.range,
             descriptor.inner_angle,
             descriptor.outer_angle,
-        ));
-        self.spot_shadows
-            .push(SpotShadowRaw::from_data(descriptor.shadow));
+        );
+        let mut shadow = SpotShadowRaw::from_data(descriptor.shadow);
+        if let Some(cookie) = descriptor.cookie {
+            raw.cone_params[2] = 1.0;
+            // The shader projects cookies with the shadow matrix, so a light
+            // without a shadow still needs it filled in.
+            shadow.view_proj = cookie.view_proj.to_cols_array_2d();
+        }
+        self.spot.push(raw);
+        self.spot_shadows.push(shadow);
+        self.spot_cookies
+            .push(descriptor.cookie.map(|cookie| cookie.texture));
     }
 
     pub fn directional_lights(&self) -> &[DirectionalLightRaw] {
@@ -101,9 +121,40 @@ impl LightsData {
         &self.point_shadows
     }
 
+    /// Shadow map layers this frame renders: one per directional cascade
+    /// (up to `cascade_count`), one per shadowed spot light and six per
+    /// shadowed point light.
+    pub fn shadow_view_count(&self, cascade_count: usize) -> u32 {
+        let directional: usize = self
+            .directional_shadows
+            .iter()
+            .take(MAX_DIRECTIONAL_LIGHTS)
+            .map(|shadow| shadow.cascade_count().min(cascade_count))
+            .sum();
+        let spot = self
+            .spot_shadows
+            .iter()
+            .take(MAX_SPOT_LIGHTS)
+            .filter(|shadow| shadow.has_shadow())
+            .count();
+        let point = self
+            .point_shadows
+            .iter()
+            .take(MAX_POINT_LIGHTS)
+            .filter(|shadow| shadow.has_shadow())
+            .count();
+        (directional + spot + point * POINT_SHADOW_FACE_COUNT) as u32
+    }
+
     pub fn spot_shadows(&self) -> &[SpotShadowRaw] {
         &self.spot_shadows
     }
+
+    /// Cookie texture index of each spot light, parallel to
+    /// [`Self::spot_lights`].
+    pub fn spot_cookie_textures(&self) -> &[Option<u32>] {
+        &self.spot_cookies
+    }
 }
 
 // All raw light/shadow structs are uploaded directly to GPU buffers.  WebGPU
@@ -130,39 +181,67 @@ impl DirectionalLightRaw {
     }
 }
 
-#[derive(Clone, Copy)]
-pub struct DirectionalShadowData {
-    pub view_proj: Mat4,
+/// Shadow cascades of one directional light, each fitted to a slice of the
+/// camera frustum. Cascade `i` covers view depths up to `splits[i]`; only the
+/// first `count` cascades are rendered and sampled.
+#[derive(Clone, Copy, Debug)]
+pub struct CascadedShadowData {
+    pub splits: [f32; MAX_SHADOW_CASCADES],
+    pub view_proj: [Mat4; MAX_SHADOW_CASCADES],
+    pub count: usize,
+}
+
+impl CascadedShadowData {
+    /// The used cascades as `(far split, view_proj)` pairs, nearest first.
+    pub fn cascades(&self) -> impl Iterator<Item = (f32, Mat4)> + '_ {
+        self.splits
+            .iter()
+            .copied()
+            .zip(self.view_proj.iter().copied())
+            .take(self.count.min(MAX_SHADOW_CASCADES))
+    }
 }
 
 #[repr(C, align(16))]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct DirectionalShadowRaw {
-    pub view_proj: [[f32; 4]; 4],
+    pub view_proj: [[[f32; 4]; 4]; MAX_SHADOW_CASCADES],
+    /// View-space depth where each cascade ends.
+    pub splits: [f32; 4],
+    /// x: 1 when enabled, y: cascade count.
     pub params: [f32; 4],
-    pub _padding: [f32; 4],
 }
 
 impl DirectionalShadowRaw {
     fn disabled() -> Self {
         Self {
-            view_proj: Mat4::IDENTITY.to_cols_array_2d(),
+            view_proj: [Mat4::IDENTITY.to_cols_array_2d(); MAX_SHADOW_CASCADES],
+            splits: [0.0; 4],
             params: [0.0, 0.0, 0.0, 0.0],
-            _padding: [0.0; 4],
         }
     }
 
-    fn from_data(data: Option<DirectionalShadowData>) -> Self {
+    fn from_data(data: Option<CascadedShadowData>) -> Self {
         if let Some(data) = data {
+            let count = data.count.clamp(1, MAX_SHADOW_CASCADES);
             Self {
-                view_proj: data.view_proj.to_cols_array_2d(),
-                params: [1.0, 0.0, 0.0, 0.0],
-                _padding: [0.0; 4],
+                view_proj: data.view_proj.map(|matrix| matrix.to_cols_array_2d()),
+                splits: data.splits,
+                params: [1.0, count as f32, 0.0, 0.0],
             }
         } else {
             Self::disabled()
         }
     }
+
+    /// Number of cascades to render, `0` when the shadow is disabled.
+    pub fn cascade_count(&self) -> usize {
+        if self.params[0] == 0.0 {
+            0
+        } else {
+            self.params[1] as usize
+        }
+    }
 }
 
 #[repr(C, align(16))]
@@ -170,6 +249,8 @@ impl DirectionalShadowRaw {
 pub struct PointLightRaw {
     pub position_range: [f32; 4],
     pub color_intensity: [f32; 4],
+    /// x: 1 when the override is active, y: linear, z: quadratic.
+    pub attenuation: [f32; 4],
 }
 
 impl PointLightRaw {
@@ -177,8 +258,17 @@ impl PointLightRaw {
         Self {
             position_range: [position.x, position.y, position.z, range],
             color_intensity: [color.x, color.y, color.z, intensity],
+            attenuation: [0.0; 4],
         }
     }
+
+    pub fn with_attenuation(mut self, attenuation: Option<AttenuationOverride>) -> Self {
+        self.attenuation = match attenuation {
+            Some(curve) => [1.0, curve.linear, curve.quadratic, 0.0],
+            None => [0.0; 4],
+        };
+        self
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -196,6 +286,12 @@ pub struct PointShadowRaw {
 }
 
 impl PointShadowRaw {
+    /// Whether the light renders a shadow map this frame. The shader skips
+    /// the shadow lookup otherwise.
+    pub fn has_shadow(&self) -> bool {
+        self.params[0] != 0.0
+    }
+
     fn disabled() -> Self {
         Self {
             view_proj: [Mat4::IDENTITY.to_cols_array_2d(); 6],
@@ -245,6 +341,7 @@ impl SpotLightRaw {
             position_range: [position.x, position.y, position.z, range],
             direction: [direction.x, direction.y, direction.z, 0.0],
             color_intensity: [color.x, color.y, color.z, intensity],
+            // z flags a cookie in the light's layer of the cookie array.
             cone_params: [cos_inner, cos_outer, 0.0, 0.0],
         }
     }
@@ -256,6 +353,14 @@ pub struct SpotShadowData {
     pub far: f32,
 }
 
+/// A texture projected through a spot light's cone.
+#[derive(Clone, Copy)]
+pub struct SpotCookieData {
+    /// Index into the scene's texture assets.
+    pub texture: u32,
+    pub view_proj: Mat4,
+}
+
 #[repr(C, align(16))]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct SpotShadowRaw {
@@ -264,6 +369,12 @@ pub struct SpotShadowRaw {
 }
 
 impl SpotShadowRaw {
+    /// Whether the light renders a shadow map this frame. The shader skips
+    /// the shadow lookup otherwise.
+    pub fn has_shadow(&self) -> bool {
+        self.params[0] != 0.0
+    }
+
     fn disabled() -> Self {
         Self {
             view_proj: Mat4::IDENTITY.to_cols_array_2d(),
@@ -275,10 +386,7 @@ impl SpotShadowRaw {
         if let Some(data) = data {
             Self {
                 view_proj: data.view_proj.to_cols_array_2d(),
-                params: [
-                    1.0, data.far, 0.0, /* receiver offset (world units) */
-                    2.0, /* pcf scale */
-                ],
+                params: [1.0, data.far, 0.0, 2.0 /* pcf scale */],
             }
         } else {
             Self::disabled()
@@ -339,15 +447,27 @@ impl LightsUniform {
 #[repr(C, align(16))]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct ShadowsUniform {
+    /// Shadow counts per light type; w is the number of directional shadow
+    /// map layers reserved per light.
     pub counts: [u32; 4],
+    /// Receiver normal offsets in world units: x directional, y spot,
+    /// z point.
+    pub normal_offsets: [f32; 4],
     pub directionals: [DirectionalShadowRaw; MAX_DIRECTIONAL_LIGHTS],
     pub points: [PointShadowRaw; MAX_POINT_LIGHTS],
     pub spots: [SpotShadowRaw; MAX_SPOT_LIGHTS],
 }
 
 impl ShadowsUniform {
-    pub fn from_data(data: &LightsData) -> Self {
+    pub fn from_data(data: &LightsData, settings: &ShadowSettings, cascade_count: usize) -> Self {
         let mut uniform = Self::zeroed();
+        uniform.counts[3] = cascade_count.clamp(1, MAX_SHADOW_CASCADES) as u32;
+        uniform.normal_offsets = [
+            settings.directional.normal_offset,
+            settings.spot.normal_offset,
+            settings.point.normal_offset,
+            0.0,
+        ];
 
         let dir_count = data.directional_shadows().len().min(MAX_DIRECTIONAL_LIGHTS) as u32;
         uniform.counts[0] = dir_count;
@@ -358,6 +478,8 @@ impl ShadowsUniform {
             .take(dir_count as usize)
         {
             *dst = *src;
+            // Cascades beyond the renderer's layers have no map to sample.
+            dst.params[1] = dst.params[1].min(uniform.counts[3] as f32);
         }
 
         // DEBUG: Log what we're putting in the uniform
@@ -426,6 +548,7 @@ mod tests {
             inner_angle: inner,
             outer_angle: outer,
             shadow: Some(shadow),
+            cookie: None,
         });
 
         let lights = LightsUniform::from_data(&data);
@@ -440,7 +563,8 @@ mod tests {
         );
         assert!(stored_dir.abs_diff_eq(direction, 1e-6));
 
-        let shadows = ShadowsUniform::from_data(&data);
+        let shadows =
+            ShadowsUniform::from_data(&data, &ShadowSettings::default(), MAX_SHADOW_CASCADES);
         assert_eq!(shadows.counts[2], 1);
         assert_eq!(shadows.spots[0].params[0], 1.0);
         assert_eq!(shadows.spots[0].params[1], far);
@@ -448,6 +572,103 @@ mod tests {
         assert!(stored_view.abs_diff_eq(proj * view, 1e-6));
     }
 
+    #[test]
+    fn spot_cookie_without_shadow_keeps_projection() {
+        let mut data = LightsData::new();
+        let view_proj = Mat4::perspective_rh(1.0, 1.0, 0.1, 10.0)
+            * Mat4::look_at_rh(Vec3::Y * 4.0, Vec3::ZERO, Vec3::Z);
+
+        let spot = |cookie| SpotLightDescriptor {
+            position: Vec3::Y * 4.0,
+            direction: Vec3::NEG_Y,
+            color: Vec3::ONE,
+            intensity: 1.0,
+            range: 10.0,
+            inner_angle: 0.3,
+            outer_angle: 0.5,
+            shadow: None,
+            cookie,
+        };
+        data.add_spot(spot(None));
+        data.add_spot(spot(Some(SpotCookieData {
+            texture: 7,
+            view_proj,
+        })));
+
+        assert_eq!(data.spot_cookie_textures(), &[None, Some(7)]);
+
+        let lights = LightsUniform::from_data(&data);
+        assert_eq!(lights.spots[0].cone_params[2], 0.0);
+        assert_eq!(lights.spots[1].cone_params[2], 1.0);
+
+        let shadows =
+            ShadowsUniform::from_data(&data, &ShadowSettings::default(), MAX_SHADOW_CASCADES);
+        assert_eq!(shadows.spots[1].params[0], 0.0);
+        let stored = Mat4::from_cols_array_2d(&shadows.spots[1].view_proj);
+        assert!(stored.abs_diff_eq(view_proj, 1e-6));
+    }
+
+    #[test]
+    fn point_light_packs_attenuation_override() {
+        let mut data = LightsData::new();
+        data.add_point(Vec3::ZERO, Vec3::ONE, 1.0, 10.0, None, None);
+        data.add_point(
+            Vec3::ZERO,
+            Vec3::ONE,
+            1.0,
+            10.0,
+            None,
+            Some(AttenuationOverride::new(0.5, 0.25)),
+        );
+
+        let lights = LightsUniform::from_data(&data);
+        assert_eq!(lights.points[0].attenuation, [0.0; 4]);
+        assert_eq!(lights.points[1].attenuation, [1.0, 0.5, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn directional_cascades_clamp_to_renderer_layers() {
+        let mut data = LightsData::new();
+        let view_proj = [
+            Mat4::from_scale(Vec3::splat(1.0)),
+            Mat4::from_scale(Vec3::splat(2.0)),
+            Mat4::from_scale(Vec3::splat(3.0)),
+            Mat4::from_scale(Vec3::splat(4.0)),
+        ];
+        data.add_directional(
+            Vec3::NEG_Y,
+            Vec3::ONE,
+            1.0,
+            Some(CascadedShadowData {
+                splits: [5.0, 10.0, 20.0, 40.0],
+                view_proj,
+                count: 4,
+            }),
+        );
+        data.add_directional(Vec3::NEG_Y, Vec3::ONE, 1.0, None);
+
+        let shadows = ShadowsUniform::from_data(&data, &ShadowSettings::default(), 2);
+        assert_eq!(shadows.counts[3], 2);
+        assert_eq!(shadows.directionals[0].params, [1.0, 2.0, 0.0, 0.0]);
+        assert_eq!(shadows.directionals[0].splits, [5.0, 10.0, 20.0, 40.0]);
+        let second = Mat4::from_cols_array_2d(&shadows.directionals[0].view_proj[1]);
+        assert!(second.abs_diff_eq(view_proj[1], 1e-6));
+        assert_eq!(data.directional_shadows()[0].cascade_count(), 4);
+        assert_eq!(data.directional_shadows()[1].cascade_count(), 0);
+    }
+
+    #[test]
+    fn shadow_uniform_carries_normal_offsets() {
+        let mut settings = ShadowSettings::default();
+        settings.directional.normal_offset = 0.01;
+        settings.spot.normal_offset = 0.02;
+        settings.point.normal_offset = 0.03;
+
+        let shadows = ShadowsUniform::from_data(&LightsData::new(), &settings, MAX_SHADOW_CASCADES);
+
+        assert_eq!(shadows.normal_offsets, [0.01, 0.02, 0.03, 0.0]);
+    }
+
     #[test]
     fn gpu_structs_are_16_byte_aligned() {
         use std::mem::{align_of, size_of};
@@ -1,8 +1,15 @@
 // renderer/material.rs (PBR version)
 
-use crate::renderer::texture::DEFAULT_CHECKER_TEXTURE_INDEX;
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+use crate::renderer::texture::{SamplerKey, DEFAULT_CHECKER_TEXTURE_INDEX};
+
+/// glTF's default `alphaCutoff`.
+pub const DEFAULT_ALPHA_CUTOFF: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Material {
     pub base_color: [u8; 4],
     pub flags: MaterialFlags,
@@ -15,12 +22,156 @@ pub struct Material {
     pub occlusion_texture: u32,
 
     // PBR parameters (stored as u8, converted to f32 in shader)
-    pub metallic_factor: u8,   // 0-255 -> 0.0-1.0
-    pub roughness_factor: u8,  // 0-255 -> 0.0-1.0
-    pub emissive_strength: u8, // 0-255 -> 0.0-1.0
+    pub metallic_factor: u8,  // 0-255 -> 0.0-1.0
+    pub roughness_factor: u8, // 0-255 -> 0.0-1.0
+    /// Linear emitted radiance, unbounded so HDR emitters can drive bloom.
+    /// Multiplied with the emissive texture when one is set.
+    pub emissive_color: [f32; 3],
+
+    /// Fraction of light transmitted through the surface (`KHR_materials_transmission`).
+    pub transmission_factor: f32,
+    /// Texture whose red channel scales `transmission_factor`.
+    pub transmission_texture: u32,
+
+    /// Strength of the clear-coat layer (`KHR_materials_clearcoat`).
+    #[serde(default)]
+    pub clearcoat_factor: f32,
+    #[serde(default)]
+    pub clearcoat_roughness: f32,
+    /// Texture whose red channel scales `clearcoat_factor`.
+    #[serde(default)]
+    pub clearcoat_texture: u32,
+    /// Texture whose green channel scales `clearcoat_roughness`.
+    #[serde(default)]
+    pub clearcoat_roughness_texture: u32,
+    /// Tangent-space normal map for the coat; the coat follows the
+    /// geometric normal without one.
+    #[serde(default)]
+    pub clearcoat_normal_texture: u32,
+
+    /// Stretch of the specular highlight along the anisotropy direction
+    /// (`KHR_materials_anisotropy`), 0 for isotropic.
+    #[serde(default)]
+    pub anisotropy_strength: f32,
+    /// Counter-clockwise rotation of the anisotropy direction from the
+    /// tangent, in radians.
+    #[serde(default)]
+    pub anisotropy_rotation: f32,
+    /// Red/green hold the tangent-space direction, blue scales the strength.
+    #[serde(default)]
+    pub anisotropy_texture: u32,
+
+    /// UV transform for the base colour texture (`KHR_texture_transform`).
+    #[serde(default)]
+    pub base_color_transform: TextureTransform,
+    /// UV transform for the normal texture. The other slots sample the
+    /// mesh UVs as they are.
+    #[serde(default)]
+    pub normal_transform: TextureTransform,
+
+    /// Alpha below which fragments are discarded when `ALPHA_MASK` is set.
+    pub alpha_cutoff: f32,
+}
+
+// Materials key the batcher's lookup tables, so equality and hashing compare
+// the emissive colour bit for bit.
+impl Material {
+    #[allow(clippy::type_complexity)]
+    fn identity(&self) -> ([u8; 4], u32, [u32; 10], u8, u8, [u32; 9], [u32; 10]) {
+        (
+            self.base_color,
+            self.flags.bits(),
+            [
+                self.base_color_texture,
+                self.metallic_roughness_texture,
+                self.normal_texture,
+                self.emissive_texture,
+                self.occlusion_texture,
+                self.transmission_texture,
+                self.clearcoat_texture,
+                self.clearcoat_roughness_texture,
+                self.clearcoat_normal_texture,
+                self.anisotropy_texture,
+            ],
+            self.metallic_factor,
+            self.roughness_factor,
+            [
+                self.emissive_color[0].to_bits(),
+                self.emissive_color[1].to_bits(),
+                self.emissive_color[2].to_bits(),
+                self.transmission_factor.to_bits(),
+                self.clearcoat_factor.to_bits(),
+                self.clearcoat_roughness.to_bits(),
+                self.anisotropy_strength.to_bits(),
+                self.anisotropy_rotation.to_bits(),
+                self.alpha_cutoff.to_bits(),
+            ],
+            {
+                let [a, b] = [self.base_color_transform, self.normal_transform].map(|t| t.bits());
+                [a[0], a[1], a[2], a[3], a[4], b[0], b[1], b[2], b[3], b[4]]
+            },
+        )
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Texture coordinate transform from `KHR_texture_transform`. UVs are
+/// scaled, rotated counter-clockwise by `rotation` radians, then offset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextureTransform {
+    pub offset: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+}
+
+impl TextureTransform {
+    pub const IDENTITY: Self = Self {
+        offset: [0.0, 0.0],
+        rotation: 0.0,
+        scale: [1.0, 1.0],
+    };
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// `[offset.x, offset.y, scale.x, scale.y]`, as stored in
+    /// [`MaterialData`](crate::renderer::MaterialData).
+    pub fn packed(&self) -> [f32; 4] {
+        [self.offset[0], self.offset[1], self.scale[0], self.scale[1]]
+    }
+
+    fn bits(&self) -> [u32; 5] {
+        [
+            self.offset[0].to_bits(),
+            self.offset[1].to_bits(),
+            self.rotation.to_bits(),
+            self.scale[0].to_bits(),
+            self.scale[1].to_bits(),
+        ]
+    }
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for Material {}
+
+impl Hash for Material {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MaterialFlags(u32);
 
 impl MaterialFlags {
@@ -34,6 +185,19 @@ impl MaterialFlags {
     pub const DOUBLE_SIDED: Self = Self(1 << 6);
     pub const UNLIT: Self = Self(1 << 7);
     pub const USE_NEAREST_FILTERING: Self = Self(1 << 8);
+    pub const USE_TRANSMISSION: Self = Self(1 << 9);
+    pub const USE_TRANSMISSION_TEXTURE: Self = Self(1 << 10);
+    pub const ALPHA_MASK: Self = Self(1 << 11);
+    pub const USE_CLEARCOAT: Self = Self(1 << 12);
+    pub const USE_CLEARCOAT_TEXTURE: Self = Self(1 << 13);
+    pub const USE_CLEARCOAT_ROUGHNESS_TEXTURE: Self = Self(1 << 14);
+    pub const USE_CLEARCOAT_NORMAL_TEXTURE: Self = Self(1 << 15);
+    pub const USE_ANISOTROPY: Self = Self(1 << 16);
+    pub const USE_ANISOTROPY_TEXTURE: Self = Self(1 << 17);
+    pub const DISTANCE_FIELD_ALPHA: Self = Self(1 << 18);
+    pub const USE_CLAMP_TO_EDGE: Self = Self(1 << 19);
+    pub const USE_BASE_COLOR_TRANSFORM: Self = Self(1 << 20);
+    pub const USE_NORMAL_TRANSFORM: Self = Self(1 << 21);
 
     pub const fn bits(&self) -> u32 {
         self.0
@@ -77,7 +241,20 @@ impl Material {
             occlusion_texture: 0,
             metallic_factor: 0,
             roughness_factor: 255, // Default to rough
-            emissive_strength: 0,
+            emissive_color: [0.0; 3],
+            transmission_factor: 0.0,
+            transmission_texture: 0,
+            clearcoat_factor: 0.0,
+            clearcoat_roughness: 0.0,
+            clearcoat_texture: 0,
+            clearcoat_roughness_texture: 0,
+            clearcoat_normal_texture: 0,
+            anisotropy_strength: 0.0,
+            anisotropy_rotation: 0.0,
+            anisotropy_texture: 0,
+            base_color_transform: TextureTransform::IDENTITY,
+            normal_transform: TextureTransform::IDENTITY,
+            alpha_cutoff: DEFAULT_ALPHA_CUTOFF,
         }
     }
 
@@ -97,8 +274,98 @@ impl Material {
         self
     }
 
-    pub fn with_emissive(mut self, strength: f32) -> Self {
-        self.emissive_strength = (strength.clamp(0.0, 1.0) * 255.0) as u8;
+    /// Sets a grey emissive colour of the given strength.
+    pub fn with_emissive(self, strength: f32) -> Self {
+        self.with_emissive_color([strength; 3])
+    }
+
+    /// Sets the linear emissive colour. Values above 1.0 are kept for HDR;
+    /// negative and non-finite channels become 0.
+    pub fn with_emissive_color(mut self, color: [f32; 3]) -> Self {
+        self.emissive_color = color.map(|c| if c.is_finite() { c.max(0.0) } else { 0.0 });
+        self
+    }
+
+    /// Makes the surface transmissive. Transmissive materials refract the
+    /// opaque scene behind them, so they are drawn with the transparent pass.
+    pub fn with_transmission(mut self, factor: f32) -> Self {
+        self.transmission_factor = if factor.is_finite() {
+            factor.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if self.transmission_factor > 0.0 {
+            self.flags.insert(MaterialFlags::USE_TRANSMISSION);
+        } else {
+            self.flags.remove(MaterialFlags::USE_TRANSMISSION);
+        }
+        self
+    }
+
+    pub fn with_transmission_texture(mut self, index: u32) -> Self {
+        self.transmission_texture = index;
+        self.flags |= MaterialFlags::USE_TRANSMISSION_TEXTURE;
+        self
+    }
+
+    /// Adds a clear-coat layer: a thin dielectric specular lobe (IOR 1.5)
+    /// over the base material, like car paint or varnish.
+    pub fn with_clearcoat(mut self, factor: f32, roughness: f32) -> Self {
+        let sanitize = |value: f32| {
+            if value.is_finite() {
+                value.clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+        self.clearcoat_factor = sanitize(factor);
+        self.clearcoat_roughness = sanitize(roughness);
+        if self.clearcoat_factor > 0.0 {
+            self.flags.insert(MaterialFlags::USE_CLEARCOAT);
+        } else {
+            self.flags.remove(MaterialFlags::USE_CLEARCOAT);
+        }
+        self
+    }
+
+    pub fn with_clearcoat_texture(mut self, index: u32) -> Self {
+        self.clearcoat_texture = index;
+        self.flags |= MaterialFlags::USE_CLEARCOAT_TEXTURE;
+        self
+    }
+
+    pub fn with_clearcoat_roughness_texture(mut self, index: u32) -> Self {
+        self.clearcoat_roughness_texture = index;
+        self.flags |= MaterialFlags::USE_CLEARCOAT_ROUGHNESS_TEXTURE;
+        self
+    }
+
+    pub fn with_clearcoat_normal_texture(mut self, index: u32) -> Self {
+        self.clearcoat_normal_texture = index;
+        self.flags |= MaterialFlags::USE_CLEARCOAT_NORMAL_TEXTURE;
+        self
+    }
+
+    /// Stretches the specular highlight along a direction in the tangent
+    /// plane, like brushed metal. Needs meshes with tangents.
+    pub fn with_anisotropy(mut self, strength: f32, rotation: f32) -> Self {
+        self.anisotropy_strength = if strength.is_finite() {
+            strength.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.anisotropy_rotation = if rotation.is_finite() { rotation } else { 0.0 };
+        if self.anisotropy_strength > 0.0 {
+            self.flags.insert(MaterialFlags::USE_ANISOTROPY);
+        } else {
+            self.flags.remove(MaterialFlags::USE_ANISOTROPY);
+        }
+        self
+    }
+
+    pub fn with_anisotropy_texture(mut self, index: u32) -> Self {
+        self.anisotropy_texture = index;
+        self.flags |= MaterialFlags::USE_ANISOTROPY_TEXTURE;
         self
     }
 
@@ -107,6 +374,38 @@ impl Material {
         self
     }
 
+    /// Cutout transparency: fragments with alpha below `cutoff` are
+    /// discarded and the rest are opaque, so masked materials stay in the
+    /// opaque pass and still write depth and cast shadows.
+    pub fn with_alpha_mask(mut self, cutoff: f32) -> Self {
+        self.alpha_cutoff = if cutoff.is_finite() {
+            cutoff.max(0.0)
+        } else {
+            DEFAULT_ALPHA_CUTOFF
+        };
+        self.flags.insert(MaterialFlags::ALPHA_MASK);
+        self
+    }
+
+    /// Treat the base color texture's alpha as a signed distance field with
+    /// the edge at 0.5, as in [`FontAtlas`](crate::renderer::text::FontAtlas),
+    /// and antialias it over one screen pixel.
+    pub fn with_distance_field_alpha(mut self) -> Self {
+        self.flags.insert(MaterialFlags::DISTANCE_FIELD_ALPHA);
+        self
+    }
+
+    /// Draws back faces too, lit with the normal flipped towards the
+    /// viewer, and casts shadows from both sides.
+    pub fn with_double_sided(mut self, double_sided: bool) -> Self {
+        if double_sided {
+            self.flags.insert(MaterialFlags::DOUBLE_SIDED);
+        } else {
+            self.flags.remove(MaterialFlags::DOUBLE_SIDED);
+        }
+        self
+    }
+
     pub fn with_unlit(mut self) -> Self {
         self.flags.insert(MaterialFlags::UNLIT);
         self
@@ -127,12 +426,51 @@ impl Material {
         self
     }
 
+    /// Clamps texture coordinates to the edge texels instead of repeating.
+    pub fn with_clamp_to_edge(mut self) -> Self {
+        self.flags.insert(MaterialFlags::USE_CLAMP_TO_EDGE);
+        self
+    }
+
+    pub fn with_repeat_wrapping(mut self) -> Self {
+        self.flags.remove(MaterialFlags::USE_CLAMP_TO_EDGE);
+        self
+    }
+
+    /// Samples every texture of the material with `key`'s filtering and
+    /// addressing. The shaders pick from a fixed set of samplers, so the
+    /// choice is per material rather than per texture.
+    pub fn with_sampler(self, key: SamplerKey) -> Self {
+        let material = if key.nearest {
+            self.with_nearest_filtering()
+        } else {
+            self.with_linear_filtering()
+        };
+        if key.clamp_to_edge {
+            material.with_clamp_to_edge()
+        } else {
+            material.with_repeat_wrapping()
+        }
+    }
+
     pub fn with_base_color_texture(mut self, index: u32) -> Self {
         self.base_color_texture = index;
         self.flags |= MaterialFlags::USE_BASE_COLOR_TEXTURE;
         self
     }
 
+    /// Transforms the UVs the base colour texture is sampled with. The
+    /// identity transform clears it.
+    pub fn with_base_color_transform(mut self, transform: TextureTransform) -> Self {
+        self.base_color_transform = transform;
+        if transform.is_identity() {
+            self.flags.remove(MaterialFlags::USE_BASE_COLOR_TRANSFORM);
+        } else {
+            self.flags.insert(MaterialFlags::USE_BASE_COLOR_TRANSFORM);
+        }
+        self
+    }
+
     pub fn with_metallic_roughness_texture(mut self, index: u32) -> Self {
         self.metallic_roughness_texture = index;
         self.flags |= MaterialFlags::USE_METALLIC_ROUGHNESS_TEXTURE;
@@ -145,6 +483,17 @@ impl Material {
         self
     }
 
+    /// Transforms the UVs the normal texture is sampled with.
+    pub fn with_normal_transform(mut self, transform: TextureTransform) -> Self {
+        self.normal_transform = transform;
+        if transform.is_identity() {
+            self.flags.remove(MaterialFlags::USE_NORMAL_TRANSFORM);
+        } else {
+            self.flags.insert(MaterialFlags::USE_NORMAL_TRANSFORM);
+        }
+        self
+    }
+
     pub fn with_emissive_texture(mut self, index: u32) -> Self {
         self.emissive_texture = index;
         self.flags |= MaterialFlags::USE_EMISSIVE_TEXTURE;
@@ -157,6 +506,93 @@ impl Material {
         self
     }
 
+    /// Texture slots this material samples, per its `USE_*_TEXTURE` flags.
+    pub fn texture_indices(&self) -> impl Iterator<Item = u32> {
+        let flags = self.flags;
+        [
+            (
+                MaterialFlags::USE_BASE_COLOR_TEXTURE,
+                self.base_color_texture,
+            ),
+            (
+                MaterialFlags::USE_METALLIC_ROUGHNESS_TEXTURE,
+                self.metallic_roughness_texture,
+            ),
+            (MaterialFlags::USE_NORMAL_TEXTURE, self.normal_texture),
+            (MaterialFlags::USE_EMISSIVE_TEXTURE, self.emissive_texture),
+            (MaterialFlags::USE_OCCLUSION_TEXTURE, self.occlusion_texture),
+            (
+                MaterialFlags::USE_TRANSMISSION_TEXTURE,
+                self.transmission_texture,
+            ),
+            (MaterialFlags::USE_CLEARCOAT_TEXTURE, self.clearcoat_texture),
+            (
+                MaterialFlags::USE_CLEARCOAT_ROUGHNESS_TEXTURE,
+                self.clearcoat_roughness_texture,
+            ),
+            (
+                MaterialFlags::USE_CLEARCOAT_NORMAL_TEXTURE,
+                self.clearcoat_normal_texture,
+            ),
+            (
+                MaterialFlags::USE_ANISOTROPY_TEXTURE,
+                self.anisotropy_texture,
+            ),
+        ]
+        .into_iter()
+        .filter(move |(flag, _)| flags.contains(*flag))
+        .map(|(_, index)| index)
+    }
+
+    /// Replaces every slot reported by [`Self::texture_indices`] with
+    /// `remap(slot)`.
+    pub fn remap_textures(&mut self, remap: impl Fn(u32) -> u32) {
+        let flags = self.flags;
+        for (flag, index) in [
+            (
+                MaterialFlags::USE_BASE_COLOR_TEXTURE,
+                &mut self.base_color_texture,
+            ),
+            (
+                MaterialFlags::USE_METALLIC_ROUGHNESS_TEXTURE,
+                &mut self.metallic_roughness_texture,
+            ),
+            (MaterialFlags::USE_NORMAL_TEXTURE, &mut self.normal_texture),
+            (
+                MaterialFlags::USE_EMISSIVE_TEXTURE,
+                &mut self.emissive_texture,
+            ),
+            (
+                MaterialFlags::USE_OCCLUSION_TEXTURE,
+                &mut self.occlusion_texture,
+            ),
+            (
+                MaterialFlags::USE_TRANSMISSION_TEXTURE,
+                &mut self.transmission_texture,
+            ),
+            (
+                MaterialFlags::USE_CLEARCOAT_TEXTURE,
+                &mut self.clearcoat_texture,
+            ),
+            (
+                MaterialFlags::USE_CLEARCOAT_ROUGHNESS_TEXTURE,
+                &mut self.clearcoat_roughness_texture,
+            ),
+            (
+                MaterialFlags::USE_CLEARCOAT_NORMAL_TEXTURE,
+                &mut self.clearcoat_normal_texture,
+            ),
+            (
+                MaterialFlags::USE_ANISOTROPY_TEXTURE,
+                &mut self.anisotropy_texture,
+            ),
+        ] {
+            if flags.contains(flag) {
+                *index = remap(*index);
+            }
+        }
+    }
+
     // Legacy compatibility
     pub fn rgb(r: u8, g: u8, b: u8) -> Self {
         Self::new([r, g, b, 255])
@@ -203,8 +639,36 @@ impl Material {
         self.roughness_factor as f32 / 255.0
     }
 
-    pub fn emissive_f32(&self) -> f32 {
-        self.emissive_strength as f32 / 255.0
+    pub fn emissive_color(&self) -> [f32; 3] {
+        self.emissive_color
+    }
+
+    pub fn transmission_f32(&self) -> f32 {
+        self.transmission_factor
+    }
+
+    pub fn has_clearcoat(&self) -> bool {
+        self.flags.contains(MaterialFlags::USE_CLEARCOAT)
+    }
+
+    pub fn is_anisotropic(&self) -> bool {
+        self.flags.contains(MaterialFlags::USE_ANISOTROPY)
+    }
+
+    pub fn alpha_cutoff(&self) -> f32 {
+        self.alpha_cutoff
+    }
+
+    pub fn is_alpha_masked(&self) -> bool {
+        self.flags.contains(MaterialFlags::ALPHA_MASK)
+    }
+
+    pub fn is_double_sided(&self) -> bool {
+        self.flags.contains(MaterialFlags::DOUBLE_SIDED)
+    }
+
+    pub fn is_transmissive(&self) -> bool {
+        self.flags.contains(MaterialFlags::USE_TRANSMISSION)
     }
 
     pub fn flags_bits(&self) -> u32 {
@@ -216,7 +680,7 @@ impl Material {
     }
 
     pub fn requires_separate_pass(&self) -> bool {
-        self.flags.contains(MaterialFlags::ALPHA_BLEND)
+        self.flags.contains(MaterialFlags::ALPHA_BLEND) || self.is_transmissive()
     }
 }
 
@@ -0,0 +1,184 @@
+// renderer/mipmap.rs - compute shader mip chain generation
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const WORKGROUP_SIZE: u32 = 8;
+/// The only format the downsample shader writes.
+pub const MIPMAP_STORAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+thread_local! {
+    static GENERATORS: RefCell<Vec<(wgpu::Device, Rc<MipmapCompute>)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Fills a texture's mip chain with one 2:1 compute downsample per level.
+/// The pipelines are built once per device; textures need
+/// `STORAGE_BINDING` usage and the [`MIPMAP_STORAGE_FORMAT`] format, but no
+/// render attachment usage.
+pub struct MipmapCompute {
+    bind_group_layout: wgpu::BindGroupLayout,
+    linear: wgpu::ComputePipeline,
+    srgb: wgpu::ComputePipeline,
+}
+
+impl MipmapCompute {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap Compute Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("blit_compute.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Mipmap Compute Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: MIPMAP_STORAGE_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mipmap Compute Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = |label: &str, entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        Self {
+            linear: pipeline("Mipmap Compute Pipeline", "downsample"),
+            srgb: pipeline("Mipmap Compute Pipeline (sRGB)", "downsample_srgb"),
+            bind_group_layout,
+        }
+    }
+
+    /// The shared generator for `device`, created on first use.
+    pub fn for_device(device: &wgpu::Device) -> Rc<Self> {
+        GENERATORS.with(|generators| {
+            let mut generators = generators.borrow_mut();
+            if let Some((_, generator)) = generators.iter().find(|(d, _)| d == device) {
+                return Rc::clone(generator);
+            }
+            let generator = Rc::new(Self::new(device));
+            generators.push((device.clone(), Rc::clone(&generator)));
+            generator
+        })
+    }
+
+    /// Generates every mip level below the first and submits the work.
+    /// `srgb` filters the texels as sRGB-encoded colour.
+    pub fn generate(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        srgb: bool,
+    ) {
+        if texture.mip_level_count() <= 1 {
+            return;
+        }
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Generator"),
+        });
+        self.encode(device, &mut encoder, texture, srgb);
+        queue.submit(Some(encoder.finish()));
+    }
+
+    /// Records the downsample dispatches into `encoder`.
+    pub fn encode(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        srgb: bool,
+    ) {
+        if texture.format() != MIPMAP_STORAGE_FORMAT
+            || !texture
+                .usage()
+                .contains(wgpu::TextureUsages::STORAGE_BINDING)
+        {
+            log::warn!(
+                "Cannot generate mipmaps for a {:?} texture with {:?} usage",
+                texture.format(),
+                texture.usage()
+            );
+            return;
+        }
+
+        let pipeline = if srgb { &self.srgb } else { &self.linear };
+        let mip_view = |level: u32, usage: wgpu::TextureUsages| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mip Level"),
+                format: Some(MIPMAP_STORAGE_FORMAT),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                base_array_layer: 0,
+                array_layer_count: Some(1),
+                usage: Some(usage),
+                ..Default::default()
+            })
+        };
+
+        for target_mip in 1..texture.mip_level_count() {
+            let src_view = mip_view(target_mip - 1, wgpu::TextureUsages::TEXTURE_BINDING);
+            let dst_view = mip_view(target_mip, wgpu::TextureUsages::STORAGE_BINDING);
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap Compute Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&src_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&dst_view),
+                    },
+                ],
+            });
+
+            let size = texture
+                .size()
+                .mip_level_size(target_mip, wgpu::TextureDimension::D2);
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Mipmap Compute Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                size.width.div_ceil(WORKGROUP_SIZE),
+                size.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+    }
+}
@@ -1,30 +1,45 @@
 pub mod batch;
+pub mod debug_draw;
 pub mod depth;
+pub mod draw_list;
+pub mod frame_pass;
+pub mod gpu_layout;
 pub(crate) mod internal;
 pub mod lights;
 pub mod material;
+pub mod mipmap;
 pub mod objects;
 pub mod postprocess;
 pub mod primitives;
 mod renderer_core;
 pub mod render_context;
 pub mod pipeline_builder;
+pub mod text;
 pub mod texture;
+mod texture_ktx2;
+pub mod timing;
 pub mod uniforms;
 pub mod vertex;
 
 pub use batch::{Batch, InstanceData, RenderBatcher, RenderObject, RenderPass};
+pub use debug_draw::{DebugDraw, DebugLineVertex};
 pub use depth::Depth;
+pub use draw_list::{ManualDraw, ManualDrawId, ManualDrawList};
+pub use frame_pass::{FrameBatch, FrameBatches, FramePass, FramePassContext, FramePassFn};
+pub use internal::readback::Screenshot;
 pub use lights::{
-    DirectionalShadowData, LightsData, PointShadowData, SpotLightDescriptor, SpotShadowData,
-    MAX_DIRECTIONAL_LIGHTS, MAX_POINT_LIGHTS, MAX_SPOT_LIGHTS,
+    CascadedShadowData, LightsData, PointShadowData, SpotCookieData, SpotLightDescriptor,
+    SpotShadowData, MAX_DIRECTIONAL_LIGHTS, MAX_POINT_LIGHTS, MAX_SHADOW_CASCADES,
+    MAX_SPOT_LIGHTS,
 };
-pub use material::Material;
+pub use material::{Material, TextureTransform};
 pub use objects::{MaterialData, ObjectData};
 pub use primitives::*;
 pub use render_context::CustomRenderContext;
 pub use pipeline_builder::PipelineBuilder;
-pub use renderer_core::{RenderFrame, Renderer, RendererStats};
-pub use texture::Texture;
+pub use renderer_core::{RenderFrame, RenderMode, Renderer, RendererStats};
+pub use texture::{SamplerKey, Texture};
+pub(crate) use texture_ktx2::is_ktx2;
+pub use timing::{GpuPass, GpuPassTimings};
 pub use uniforms::CameraUniform;
-pub use vertex::Vertex;
+pub use vertex::{MorphDelta, SkinVertex, Vertex};
@@ -2,15 +2,21 @@
 use bytemuck::{Pod, Zeroable};
 use glam::Mat4;
 
+use crate::asset::MorphTargetRange;
 use crate::renderer::Material;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable, Debug)]
 pub struct ObjectData {
-    pub model: [[f32; 4]; 4], // 64 bytes
-    pub material_index: u32,  // 4 bytes
-    pub _padding: [u32; 3],   // 12 bytes to maintain 16-byte alignment
-    pub _padding2: [u32; 4], // 16 bytes so the std430 stride matches WGSL expectations (96 bytes total)
+    pub model: [[f32; 4]; 4],      // 64 bytes
+    pub material_index: u32,       // 4 bytes
+    pub joint_offset: u32,         // 4 bytes, first joint matrix of a skinned mesh
+    pub morph_weight_offset: u32,  // 4 bytes, first morph weight of this instance
+    pub morph_target_count: u32,   // 4 bytes, 0 when the instance is not morphed
+    pub morph_delta_offset: u32,   // 4 bytes, first delta of the mesh's morph targets
+    pub morph_vertex_count: u32,   // 4 bytes, deltas per morph target
+    pub _padding: [u32; 2],        // 8 bytes so `prev_model` starts on a 16-byte boundary
+    pub prev_model: [[f32; 4]; 4], // 64 bytes, last frame's model (160 bytes total)
 }
 
 impl ObjectData {
@@ -18,27 +24,66 @@ impl ObjectData {
         Self {
             model: model.to_cols_array_2d(),
             material_index,
-            _padding: [0; 3],
-            _padding2: [0; 4],
+            joint_offset: 0,
+            morph_weight_offset: 0,
+            morph_target_count: 0,
+            morph_delta_offset: 0,
+            morph_vertex_count: 0,
+            _padding: [0; 2],
+            prev_model: model.to_cols_array_2d(),
         }
     }
+
+    /// Sets the model matrix of the previous frame. Defaults to the current
+    /// one, so the object reports no motion of its own.
+    pub fn with_prev_model(mut self, prev_model: Mat4) -> Self {
+        self.prev_model = prev_model.to_cols_array_2d();
+        self
+    }
+
+    pub fn with_joint_offset(mut self, joint_offset: u32) -> Self {
+        self.joint_offset = joint_offset;
+        self
+    }
+
+    /// Blends `targets` with the weights starting at `weight_offset`.
+    pub fn with_morph_targets(mut self, weight_offset: u32, targets: MorphTargetRange) -> Self {
+        self.morph_weight_offset = weight_offset;
+        self.morph_target_count = targets.target_count;
+        self.morph_delta_offset = targets.delta_offset;
+        self.morph_vertex_count = targets.vertex_count;
+        self
+    }
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable, Debug)]
 pub struct MaterialData {
-    pub color: [f32; 4],                 // 16 bytes
-    pub base_color_texture: u32,         // 4 bytes
-    pub metallic_roughness_texture: u32, // 4 bytes
-    pub normal_texture: u32,             // 4 bytes
-    pub emissive_texture: u32,           // 4 bytes
-    pub occlusion_texture: u32,          // 4 bytes
-    pub material_flags: u32,             // 4 bytes
-    pub metallic_factor: f32,            // 4 bytes
-    pub roughness_factor: f32,           // 4 bytes
-    pub emissive_strength: f32,          // 4 bytes
-    pub _padding: u32,                   // 4 bytes
-    pub _padding2: [u32; 2],             // 8 bytes (ensures 64-byte stride)
+    pub color: [f32; 4],                   // 16 bytes
+    pub base_color_texture: u32,           // 4 bytes
+    pub metallic_roughness_texture: u32,   // 4 bytes
+    pub normal_texture: u32,               // 4 bytes
+    pub emissive_texture: u32,             // 4 bytes
+    pub occlusion_texture: u32,            // 4 bytes
+    pub material_flags: u32,               // 4 bytes
+    pub metallic_factor: f32,              // 4 bytes
+    pub roughness_factor: f32,             // 4 bytes
+    pub emissive_color: [f32; 3],          // 12 bytes (vec3 at a 16-byte offset)
+    pub transmission_factor: f32,          // 4 bytes
+    pub transmission_texture: u32,         // 4 bytes
+    pub alpha_cutoff: f32,                 // 4 bytes
+    pub clearcoat_factor: f32,             // 4 bytes
+    pub clearcoat_roughness: f32,          // 4 bytes
+    pub clearcoat_texture: u32,            // 4 bytes
+    pub clearcoat_roughness_texture: u32,  // 4 bytes
+    pub clearcoat_normal_texture: u32,     // 4 bytes
+    pub anisotropy_strength: f32,          // 4 bytes
+    pub anisotropy_rotation: f32,          // 4 bytes
+    pub anisotropy_texture: u32,           // 4 bytes
+    pub base_color_uv_rotation: f32,       // 4 bytes
+    pub normal_uv_rotation: f32,           // 4 bytes
+    pub base_color_uv_transform: [f32; 4], // 16 bytes, offset.xy and scale.xy
+    pub normal_uv_transform: [f32; 4],     // 16 bytes (144-byte stride)
 }
 
 impl MaterialData {
@@ -53,9 +98,22 @@ impl MaterialData {
             material_flags: material.flags_bits(),
             metallic_factor: material.metallic_f32(),
             roughness_factor: material.roughness_f32(),
-            emissive_strength: material.emissive_f32(),
-            _padding: 0,
-            _padding2: [0, 0],
+            emissive_color: material.emissive_color(),
+            transmission_factor: material.transmission_f32(),
+            transmission_texture: material.transmission_texture,
+            alpha_cutoff: material.alpha_cutoff(),
+            clearcoat_factor: material.clearcoat_factor,
+            clearcoat_roughness: material.clearcoat_roughness,
+            clearcoat_texture: material.clearcoat_texture,
+            clearcoat_roughness_texture: material.clearcoat_roughness_texture,
+            clearcoat_normal_texture: material.clearcoat_normal_texture,
+            anisotropy_strength: material.anisotropy_strength,
+            anisotropy_rotation: material.anisotropy_rotation,
+            anisotropy_texture: material.anisotropy_texture,
+            base_color_uv_rotation: material.base_color_transform.rotation,
+            normal_uv_rotation: material.normal_transform.rotation,
+            base_color_uv_transform: material.base_color_transform.packed(),
+            normal_uv_transform: material.normal_transform.packed(),
         }
     }
 }
@@ -66,7 +124,7 @@ mod tests {
     use crate::renderer::texture::DEFAULT_WHITE_TEXTURE_INDEX;
     #[test]
     fn object_data_size() {
-        assert_eq!(std::mem::size_of::<ObjectData>(), 96);
+        assert_eq!(std::mem::size_of::<ObjectData>(), 160);
     }
 
     #[test]
@@ -113,8 +171,105 @@ mod tests {
         assert!(roughness_values.iter().any(|&r| (r - 1.0).abs() < 0.01));
     }
 
+    #[test]
+    fn material_data_keeps_hdr_emissive_color() {
+        let material = Material::pbr().with_emissive_color([4.0, 2.0, -1.0]);
+        let data = MaterialData::from_material(&material);
+        assert_eq!(data.emissive_color, [4.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn object_data_carries_morph_targets() {
+        let targets = MorphTargetRange {
+            delta_offset: 40,
+            target_count: 2,
+            vertex_count: 8,
+        };
+        let object = ObjectData::new(Mat4::IDENTITY, 0).with_morph_targets(5, targets);
+
+        assert_eq!(object.morph_weight_offset, 5);
+        assert_eq!(object.morph_target_count, 2);
+        assert_eq!(object.morph_delta_offset, 40);
+        assert_eq!(object.morph_vertex_count, 8);
+        assert_eq!(std::mem::size_of::<ObjectData>(), 160);
+    }
+
+    #[test]
+    fn object_data_reports_no_motion_by_default() {
+        use glam::Vec3;
+
+        let model = Mat4::from_translation(Vec3::X);
+        let object = ObjectData::new(model, 0);
+        assert_eq!(object.prev_model, object.model);
+
+        let moved = object.with_prev_model(Mat4::IDENTITY);
+        assert_eq!(moved.prev_model, Mat4::IDENTITY.to_cols_array_2d());
+        assert_eq!(moved.model, model.to_cols_array_2d());
+    }
+
     #[test]
     fn material_data_size() {
-        assert_eq!(std::mem::size_of::<MaterialData>(), 64);
+        assert_eq!(std::mem::size_of::<MaterialData>(), 144);
+    }
+
+    #[test]
+    fn clearcoat_round_trips_into_material_data() {
+        let material = Material::pbr()
+            .with_clearcoat(1.0, 0.2)
+            .with_clearcoat_normal_texture(9);
+        assert!(material.has_clearcoat());
+        assert!(!material.requires_separate_pass());
+        assert_eq!(material.texture_indices().collect::<Vec<_>>(), vec![9]);
+
+        let data = MaterialData::from_material(&material);
+        assert_eq!(data.clearcoat_factor, 1.0);
+        assert_eq!(data.clearcoat_roughness, 0.2);
+        assert_eq!(data.clearcoat_normal_texture, 9);
+        assert!(!Material::pbr().with_clearcoat(0.0, 0.5).has_clearcoat());
+    }
+
+    #[test]
+    fn anisotropy_round_trips_into_material_data() {
+        let material = Material::pbr()
+            .with_anisotropy(1.5, 0.5)
+            .with_anisotropy_texture(4);
+        assert!(material.is_anisotropic());
+
+        let data = MaterialData::from_material(&material);
+        assert_eq!(data.anisotropy_strength, 1.0);
+        assert_eq!(data.anisotropy_rotation, 0.5);
+        assert_eq!(data.anisotropy_texture, 4);
+        assert!(!Material::pbr()
+            .with_anisotropy(f32::NAN, 0.0)
+            .is_anisotropic());
+    }
+
+    #[test]
+    fn transmissive_material_uses_separate_pass() {
+        let material = Material::white()
+            .with_transmission(0.8)
+            .with_transmission_texture(7);
+        assert!(material.requires_separate_pass());
+
+        let data = MaterialData::from_material(&material);
+        assert_eq!(data.transmission_factor, 0.8);
+        assert_eq!(data.transmission_texture, 7);
+        assert!(!Material::white().with_transmission(0.0).is_transmissive());
+    }
+
+    #[test]
+    fn alpha_mask_round_trips_into_material_data() {
+        use crate::renderer::material::MaterialFlags;
+
+        assert_eq!(Material::pbr().alpha_cutoff(), 0.5);
+
+        let material = Material::pbr().with_alpha_mask(0.3);
+        assert!(material.is_alpha_masked());
+        assert!(!material.requires_separate_pass());
+        assert_ne!(material, Material::pbr().with_alpha_mask(0.6));
+
+        let data = MaterialData::from_material(&material);
+        assert_eq!(data.alpha_cutoff, 0.3);
+        assert_ne!(data.material_flags & MaterialFlags::ALPHA_MASK.bits(), 0);
     }
 }
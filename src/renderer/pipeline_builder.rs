@@ -99,6 +99,12 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
+    /// Add a color target with a custom write mask
+    pub fn with_color_target_state(mut self, target: wgpu::ColorTargetState) -> Self {
+        self.color_targets.push(Some(target));
+        self
+    }
+
     /// Configure depth/stencil state
     pub fn with_depth_stencil(
         mut self,
@@ -139,6 +145,15 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
+    /// Replace the stencil state of the depth-stencil set by a
+    /// `with_depth_stencil*` call. Does nothing without one.
+    pub fn with_stencil(mut self, stencil: wgpu::StencilState) -> Self {
+        if let Some(depth_stencil) = &mut self.depth_stencil {
+            depth_stencil.stencil = stencil;
+        }
+        self
+    }
+
     /// Set MSAA sample count
     pub fn with_multisample(mut self, sample_count: u32) -> Self {
         self.multisample.count = sample_count;
@@ -157,6 +172,13 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
+    /// Set how triangles are rasterized. `Line` and `Point` need the matching
+    /// device feature.
+    pub fn with_polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.primitive.polygon_mode = polygon_mode;
+        self
+    }
+
     /// Build the render pipeline
     pub fn build(self) -> wgpu::RenderPipeline {
         self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -0,0 +1,422 @@
+//! Ordering of the post-processing passes. Each node names the textures it
+//! reads and writes; [`RenderGraph`] runs the writers of a texture before its
+//! readers and otherwise keeps the order nodes were inserted in.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Scene depth written by the main pass.
+pub const DEPTH: &str = "depth";
+/// HDR scene colour written by the main pass.
+pub const SCENE_COLOR: &str = "scene_color";
+pub const EMISSIVE: &str = "emissive";
+pub const VELOCITY: &str = "velocity";
+/// Single-sampled copy of [`DEPTH`] the screen-space effects read.
+pub const RESOLVED_DEPTH: &str = "resolved_depth";
+pub const SSAO: &str = "ssao";
+pub const FOG: &str = "fog";
+/// TAA history written this frame.
+pub const TAA_COLOR: &str = "taa_color";
+pub const DOF_COLOR: &str = "dof_color";
+/// Top of the bloom upsample chain.
+pub const BLOOM: &str = "bloom";
+/// The view the composite writes the final image to.
+pub const OUTPUT: &str = "output";
+
+pub type RenderNodeFn = Box<dyn FnMut(&mut RenderNodeContext<'_>)>;
+
+/// What a custom node records with. [`Self::view`] resolves the built-in
+/// resource names above; textures of custom resources belong to the node
+/// that declares them.
+pub struct RenderNodeContext<'a> {
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub device: &'a wgpu::Device,
+    views: &'a [(&'static str, &'a wgpu::TextureView)],
+}
+
+impl<'a> RenderNodeContext<'a> {
+    pub(crate) fn new(
+        encoder: &'a mut wgpu::CommandEncoder,
+        device: &'a wgpu::Device,
+        views: &'a [(&'static str, &'a wgpu::TextureView)],
+    ) -> Self {
+        Self {
+            encoder,
+            device,
+            views,
+        }
+    }
+
+    pub fn view(&self, resource: &str) -> Option<&'a wgpu::TextureView> {
+        self.views
+            .iter()
+            .find(|(name, _)| *name == resource)
+            .map(|(_, view)| *view)
+    }
+}
+
+/// The passes [`PostProcess`](super::PostProcess) records itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BuiltinNode {
+    DepthResolve,
+    Ssao,
+    Fog,
+    Taa,
+    Dof,
+    Bloom,
+    Composite,
+}
+
+impl BuiltinNode {
+    pub(crate) const ALL: [Self; 7] = [
+        Self::DepthResolve,
+        Self::Ssao,
+        Self::Fog,
+        Self::Taa,
+        Self::Dof,
+        Self::Bloom,
+        Self::Composite,
+    ];
+
+    fn node(self) -> RenderNode {
+        let (name, inputs, outputs): (_, &[&str], &[&str]) = match self {
+            Self::DepthResolve => ("depth_resolve", &[DEPTH], &[RESOLVED_DEPTH]),
+            Self::Ssao => ("ssao", &[RESOLVED_DEPTH], &[SSAO]),
+            Self::Fog => ("fog", &[RESOLVED_DEPTH], &[FOG]),
+            Self::Taa => (
+                "taa",
+                &[SCENE_COLOR, VELOCITY, RESOLVED_DEPTH],
+                &[TAA_COLOR],
+            ),
+            Self::Dof => ("dof", &[TAA_COLOR, RESOLVED_DEPTH], &[DOF_COLOR]),
+            Self::Bloom => ("bloom", &[TAA_COLOR, EMISSIVE], &[BLOOM]),
+            Self::Composite => ("composite", &[DOF_COLOR, SSAO, BLOOM, FOG], &[OUTPUT]),
+        };
+        RenderNode {
+            name: name.to_owned(),
+            inputs: inputs.iter().map(|&input| input.to_owned()).collect(),
+            outputs: outputs.iter().map(|&output| output.to_owned()).collect(),
+            enabled: true,
+            kind: NodeKind::Builtin(self),
+        }
+    }
+}
+
+pub(crate) enum NodeKind {
+    Builtin(BuiltinNode),
+    Custom(RenderNodeFn),
+}
+
+/// A named pass and the textures it reads and writes.
+pub struct RenderNode {
+    name: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    enabled: bool,
+    pub(crate) kind: NodeKind,
+}
+
+impl RenderNode {
+    pub fn new(name: &str, run: impl FnMut(&mut RenderNodeContext<'_>) + 'static) -> Self {
+        Self {
+            name: name.to_owned(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            enabled: true,
+            kind: NodeKind::Custom(Box::new(run)),
+        }
+    }
+
+    pub fn with_inputs(mut self, inputs: &[&str]) -> Self {
+        self.inputs = inputs.iter().map(|&input| input.to_owned()).collect();
+        self
+    }
+
+    pub fn with_outputs(mut self, outputs: &[&str]) -> Self {
+        self.outputs = outputs.iter().map(|&output| output.to_owned()).collect();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        matches!(self.kind, NodeKind::Builtin(_))
+    }
+
+    fn writes(&self, resource: &str) -> bool {
+        self.outputs.iter().any(|output| output == resource)
+    }
+}
+
+/// Post-processing nodes, scheduled every frame.
+///
+/// Writers of a resource run before nodes that only read it, and writers of
+/// the same resource run in insertion order. Disabled nodes are skipped but
+/// keep their place, so the nodes reading their outputs stay ordered after
+/// them. Skipped built-in SSAO, fog and bloom leave neutral outputs behind,
+/// and DoF and the composite read past a skipped TAA or DoF; the composite
+/// itself writes the output and stays enabled.
+#[derive(Default)]
+pub struct RenderGraph {
+    pub(crate) nodes: Vec<RenderNode>,
+}
+
+impl RenderGraph {
+    /// The built-in passes: depth resolve, SSAO, fog, TAA, depth of field,
+    /// bloom and composite.
+    pub(crate) fn builtin() -> Self {
+        Self {
+            nodes: BuiltinNode::ALL.map(BuiltinNode::node).into(),
+        }
+    }
+
+    /// Inserts `node` right before `anchor` in insertion order. Fails for an
+    /// unknown anchor, a duplicate name, or dependencies that form a cycle.
+    pub fn insert_node_before(&mut self, anchor: &str, node: RenderNode) -> Result<(), String> {
+        let index = self.anchor_index(anchor)?;
+        self.insert_node(index, node)
+    }
+
+    /// Inserts `node` right after `anchor` in insertion order.
+    pub fn insert_node_after(&mut self, anchor: &str, node: RenderNode) -> Result<(), String> {
+        let index = self.anchor_index(anchor)?;
+        self.insert_node(index + 1, node)
+    }
+
+    /// Removes a custom node. Built-in nodes can only be disabled.
+    pub fn remove_node(&mut self, name: &str) -> Result<RenderNode, String> {
+        let index = self.anchor_index(name)?;
+        if self.nodes[index].is_builtin() {
+            return Err(format!("Built-in render node '{}' cannot be removed", name));
+        }
+        Ok(self.nodes.remove(index))
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let index = self.anchor_index(name)?;
+        if !enabled
+            && matches!(
+                self.nodes[index].kind,
+                NodeKind::Builtin(BuiltinNode::Composite)
+            )
+        {
+            return Err(format!(
+                "Built-in render node '{}' writes the output and cannot be disabled",
+                name
+            ));
+        }
+        self.nodes[index].enabled = enabled;
+        Ok(())
+    }
+
+    pub(crate) fn builtin_enabled(&self, builtin: BuiltinNode) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| node.enabled && matches!(node.kind, NodeKind::Builtin(b) if b == builtin))
+    }
+
+    pub fn node(&self, name: &str) -> Option<&RenderNode> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+
+    /// Nodes in insertion order.
+    pub fn nodes(&self) -> impl Iterator<Item = &RenderNode> {
+        self.nodes.iter()
+    }
+
+    /// Node names in the order they run, disabled ones included.
+    pub fn execution_order(&self) -> Vec<&str> {
+        self.schedule()
+            .unwrap_or_else(|_| (0..self.nodes.len()).collect())
+            .into_iter()
+            .map(|index| self.nodes[index].name.as_str())
+            .collect()
+    }
+
+    fn anchor_index(&self, name: &str) -> Result<usize, String> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .ok_or_else(|| format!("Unknown render node '{}'", name))
+    }
+
+    fn insert_node(&mut self, index: usize, node: RenderNode) -> Result<(), String> {
+        if self.node(&node.name).is_some() {
+            return Err(format!("Render node '{}' already exists", node.name));
+        }
+        self.nodes.insert(index, node);
+        if let Err(err) = self.schedule() {
+            self.nodes.remove(index);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Node indices in execution order. Among nodes that are ready, the one
+    /// inserted first runs first, so the order is deterministic.
+    pub(crate) fn schedule(&self) -> Result<Vec<usize>, String> {
+        let count = self.nodes.len();
+        let mut dependents = vec![Vec::new(); count];
+        let mut pending = vec![0usize; count];
+        for (after, node) in self.nodes.iter().enumerate() {
+            for (before, other) in self.nodes.iter().enumerate() {
+                if before == after {
+                    continue;
+                }
+                let ordered =
+                    node.inputs.iter().any(|input| {
+                        other.writes(input) && (!node.writes(input) || before < after)
+                    }) || node
+                        .outputs
+                        .iter()
+                        .any(|output| other.writes(output) && before < after);
+                if ordered {
+                    dependents[before].push(after);
+                    pending[after] += 1;
+                }
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..count)
+            .filter(|&index| pending[index] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(count);
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+            for &dependent in &dependents[index] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+
+        if order.len() < count {
+            let cycle: Vec<&str> = (0..count)
+                .filter(|&index| pending[index] > 0)
+                .map(|index| self.nodes[index].name.as_str())
+                .collect();
+            return Err(format!(
+                "Render nodes depend on each other: {}",
+                cycle.join(", ")
+            ));
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(name: &str) -> RenderNode {
+        RenderNode::new(name, |_| {})
+    }
+
+    #[test]
+    fn builtin_nodes_run_in_dependency_order() {
+        let graph = RenderGraph::builtin();
+        assert_eq!(
+            graph.execution_order(),
+            [
+                "depth_resolve",
+                "ssao",
+                "fog",
+                "taa",
+                "dof",
+                "bloom",
+                "composite"
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_nodes_are_ordered_by_their_resources() {
+        let mut graph = RenderGraph::builtin();
+        // Declared before SSAO but reads it, so it runs after SSAO.
+        graph
+            .insert_node_before(
+                "depth_resolve",
+                custom("ssao_debug")
+                    .with_inputs(&[SSAO])
+                    .with_outputs(&["ssao_debug"]),
+            )
+            .unwrap();
+        graph
+            .insert_node_after(
+                "composite",
+                custom("overlay")
+                    .with_inputs(&[OUTPUT, "ssao_debug"])
+                    .with_outputs(&[OUTPUT]),
+            )
+            .unwrap();
+        // Writes the composite output too, so it keeps its place before it.
+        graph
+            .insert_node_before("composite", custom("clear").with_outputs(&[OUTPUT]))
+            .unwrap();
+
+        let order = graph.execution_order();
+        let position = |name: &str| order.iter().position(|&node| node == name).unwrap();
+        assert!(position("ssao_debug") > position("ssao"));
+        assert!(position("clear") < position("composite"));
+        assert_eq!(order.last(), Some(&"overlay"));
+    }
+
+    #[test]
+    fn invalid_insertions_are_rejected() {
+        let mut graph = RenderGraph::builtin();
+        assert!(graph.insert_node_after("missing", custom("a")).is_err());
+        assert!(graph.insert_node_after("bloom", custom("bloom")).is_err());
+
+        graph
+            .insert_node_after(
+                "bloom",
+                custom("a").with_inputs(&["b"]).with_outputs(&["a"]),
+            )
+            .unwrap();
+        let cycle = custom("b").with_inputs(&["a"]).with_outputs(&["b"]);
+        assert!(graph.insert_node_after("a", cycle).is_err());
+        assert!(graph.node("b").is_none());
+
+        assert!(graph.remove_node("composite").is_err());
+        assert_eq!(graph.remove_node("a").unwrap().name(), "a");
+    }
+
+    #[test]
+    fn disabled_nodes_keep_their_dependents_ordered() {
+        let mut graph = RenderGraph::builtin();
+        let order = graph.execution_order().join(",");
+        graph.set_enabled("taa", false).unwrap();
+
+        assert_eq!(graph.execution_order().join(","), order);
+        assert!(!graph.node("taa").unwrap().is_enabled());
+        assert!(graph.set_enabled("missing", true).is_err());
+    }
+
+    #[test]
+    fn the_composite_cannot_be_disabled() {
+        let mut graph = RenderGraph::builtin();
+        assert!(graph.set_enabled("composite", false).is_err());
+        assert!(graph.builtin_enabled(BuiltinNode::Composite));
+        graph.set_enabled("composite", true).unwrap();
+
+        graph.set_enabled("dof", false).unwrap();
+        assert!(!graph.builtin_enabled(BuiltinNode::Dof));
+        assert!(graph.builtin_enabled(BuiltinNode::Taa));
+    }
+}
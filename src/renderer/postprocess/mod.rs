@@ -1,82 +1,267 @@
+pub mod graph;
+
+pub use graph::{RenderGraph, RenderNode, RenderNodeContext, RenderNodeFn};
+
+use crate::renderer::timing::{GpuPass, GpuTimer};
 use crate::renderer::PipelineBuilder;
+use crate::scene::camera::ProjectionMatrix;
 use bytemuck::{Pod, Zeroable};
-use glam::Mat4;
+use glam::{Mat4, Vec2, Vec3};
+use graph::{BuiltinNode, NodeKind};
+use serde::{Deserialize, Serialize};
+use wgpu::util::DeviceExt;
 
-const NOISE_TEXTURE_SIZE: u32 = 4;
-const BLOOM_MIP_COUNT: usize = 5;
+/// Upper bound for `BloomSettings::mip_count`.
+pub const MAX_BLOOM_MIP_COUNT: u32 = 8;
 const BLOOM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
-const SSAO_NOISE_DATA: [f32; (NOISE_TEXTURE_SIZE * NOISE_TEXTURE_SIZE * 4) as usize] = [
-    -0.6401949,
-    -0.76821256,
-    0.0,
-    0.0,
-    0.98767775,
-    0.1565012,
-    0.0,
-    0.0,
-    -0.1566164,
-    0.9876595,
-    0.0,
-    0.0,
-    0.1675282,
-    0.98586726,
-    0.0,
-    0.0,
-    -0.08490153,
-    -0.9963893,
-    0.0,
-    0.0,
-    -0.44445047,
-    -0.89580345,
-    0.0,
-    0.0,
-    0.77917,
-    -0.62681264,
-    0.0,
-    0.0,
-    0.85447717,
-    0.519489,
-    0.0,
-    0.0,
-    -0.88205993,
-    0.471_137_3,
-    0.0,
-    0.0,
-    0.98252517,
-    0.18612963,
-    0.0,
-    0.0,
-    0.19578062,
-    0.98064774,
-    0.0,
-    0.0,
-    -0.99943393,
-    -0.03364192,
-    0.0,
-    0.0,
-    0.9861326,
-    0.165959,
-    0.0,
-    0.0,
-    0.3159545,
-    0.94877434,
-    0.0,
-    0.0,
-    -0.5883725,
-    -0.80859,
-    0.0,
-    0.0,
-    -0.96039623,
-    -0.278638,
-    0.0,
-    0.0,
-];
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+
+/// Upper bound for `PostProcessEffects::ssao_kernel_size`; the SSAO shader
+/// loop is unrolled up to this many samples.
+pub const MAX_SSAO_KERNEL_SIZE: u32 = 64;
+
+/// Upper bound for `PostProcessEffects::ssao_blur_radius`, in pixels.
+pub const MAX_SSAO_BLUR_RADIUS: u32 = 8;
+
+/// Bounds for `PostProcessEffects::ssao_noise_size`, the side length of the
+/// tiled SSAO rotation texture.
+pub const MIN_SSAO_NOISE_SIZE: u32 = 4;
+pub const MAX_SSAO_NOISE_SIZE: u32 = 64;
+
+/// Upper bounds for the horizon directions and steps per direction of the
+/// [`SsaoQuality`] presets.
+pub const MAX_SSAO_HORIZON_DIRECTIONS: u32 = 16;
+pub const MAX_SSAO_HORIZON_STEPS: u32 = 16;
+
+/// How the ambient occlusion pass samples the depth buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SsaoQuality {
+    /// The original hemisphere kernel of `ssao_kernel_size` samples. Cheap
+    /// on small kernels but prone to haloing around silhouettes.
+    Classic,
+    /// Horizon-based AO: marches several screen-space directions and keeps
+    /// the highest horizon each one finds within `ssao_radius`.
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl SsaoQuality {
+    pub const ALL: [SsaoQuality; 4] = [
+        SsaoQuality::Classic,
+        SsaoQuality::Low,
+        SsaoQuality::Medium,
+        SsaoQuality::High,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SsaoQuality::Classic => "Classic",
+            SsaoQuality::Low => "Low",
+            SsaoQuality::Medium => "Medium",
+            SsaoQuality::High => "High",
+        }
+    }
+
+    /// Directions and steps per direction of the horizon search, `None` for
+    /// the hemisphere kernel.
+    pub fn horizon_samples(self) -> Option<(u32, u32)> {
+        match self {
+            SsaoQuality::Classic => None,
+            SsaoQuality::Low => Some((4, 4)),
+            SsaoQuality::Medium => Some((8, 6)),
+            SsaoQuality::High => Some((12, 8)),
+        }
+    }
+}
+
+/// Weight of the current frame when blending into the TAA history.
+pub const DEFAULT_TAA_BLEND_FACTOR: f32 = 0.1;
+const TAA_HISTORY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// Length of the Halton(2, 3) jitter sequence before it repeats.
+const TAA_JITTER_SAMPLES: u32 = 8;
+
+const DOF_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// Largest circle of confusion, in pixels, that the bokeh blur spreads over.
+const DOF_MAX_COC_PIXELS: f32 = 12.0;
+
+const FOG_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Format of the second main pass attachment, which receives the
+/// Reinhard-encoded emission of each surface.
+pub const SCENE_EMISSIVE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Format of the third main pass attachment, which receives the screen-space
+/// motion of each surface since the last frame, in UV units.
+pub const SCENE_VELOCITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg16Float;
+
+/// Largest exposure compensation, in EV stops either way.
+pub const MAX_EXPOSURE_COMPENSATION: f32 = 5.0;
+
+/// Curve that maps the HDR scene colour into display range during
+/// composite, after exposure is applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ToneMapping {
+    /// Output is clipped at 1.0.
+    None,
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve.
+    #[default]
+    Aces,
+    /// Minimal AgX with the default contrast look; desaturates highlights
+    /// instead of shifting their hue.
+    AgX,
+    /// Hable's filmic curve from Uncharted 2, normalised to a white point
+    /// of 11.2.
+    Uncharted2,
+}
+
+impl ToneMapping {
+    pub const ALL: [ToneMapping; 5] = [
+        ToneMapping::None,
+        ToneMapping::Reinhard,
+        ToneMapping::Aces,
+        ToneMapping::AgX,
+        ToneMapping::Uncharted2,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToneMapping::None => "None",
+            ToneMapping::Reinhard => "Reinhard",
+            ToneMapping::Aces => "ACES",
+            ToneMapping::AgX => "AgX",
+            ToneMapping::Uncharted2 => "Uncharted 2",
+        }
+    }
+
+    /// Operator index read by `apply_tonemapping` in postprocess.wgsl.
+    fn shader_index(self) -> f32 {
+        match self {
+            ToneMapping::None => 0.0,
+            ToneMapping::Reinhard => 1.0,
+            ToneMapping::Aces => 2.0,
+            ToneMapping::AgX => 3.0,
+            ToneMapping::Uncharted2 => 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BloomSettings {
+    /// Brightness above which pixels start contributing to bloom.
+    pub threshold: f32,
+    /// Width of the soft transition below the threshold, as a fraction of
+    /// the threshold (0 = hard cut-off).
+    pub knee: f32,
+    /// How much each upsampled mip spreads into the next larger one.
+    pub scatter: f32,
+    /// Multiplier on the bloom added during composite.
+    pub intensity: f32,
+    /// Number of half-resolution steps in the blur chain, clamped to
+    /// `1..=MAX_BLOOM_MIP_COUNT`. More mips give a wider glow.
+    pub mip_count: u32,
+    /// How strongly the dirt mask set with `PostProcess::set_bloom_dirt_mask`
+    /// brightens the bloom. Has no effect without a mask.
+    pub dirt_strength: f32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            knee: 0.5,
+            scatter: 0.95,
+            intensity: 1.0,
+            mip_count: 5,
+            dirt_strength: 0.0,
+        }
+    }
+}
+
+impl BloomSettings {
+    fn mip_count(self) -> usize {
+        self.mip_count.clamp(1, MAX_BLOOM_MIP_COUNT) as usize
+    }
+
+    fn params(self) -> BloomParams {
+        BloomParams {
+            threshold: finite_or(self.threshold, 0.8).max(0.0),
+            knee: finite_or(self.knee, 0.5).clamp(0.0, 1.0),
+            scatter: finite_or(self.scatter, 0.95).clamp(0.0, 1.0),
+            _padding: 0.0,
+        }
+    }
+
+    /// Intensity and dirt strength as read by the composite pass.
+    fn composite_params(self) -> [f32; 4] {
+        [
+            finite_or(self.intensity, 1.0).max(0.0),
+            finite_or(self.dirt_strength, 0.0).max(0.0),
+            0.0,
+            0.0,
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct PostProcessEffects {
     pub ssao: bool,
     pub bloom: bool,
+    /// Blooms only the emissive attachment instead of everything above the
+    /// threshold, and adds the glow after tone mapping. Specular highlights
+    /// then stay sharp.
+    pub bloom_emissive_only: bool,
     pub fxaa: bool,
+    /// Temporal anti-aliasing. Jitters the projection every frame and
+    /// accumulates the result into a history buffer.
+    pub taa: bool,
+    /// View-space sampling radius of the SSAO hemisphere, in world units.
+    pub ssao_radius: f32,
+    /// Offset along the surface normal to avoid self-occlusion.
+    pub ssao_bias: f32,
+    /// Blend between no occlusion (0) and full occlusion (1).
+    pub ssao_intensity: f32,
+    /// Sampling pattern and sample count of the occlusion pass.
+    pub ssao_quality: SsaoQuality,
+    /// Number of hemisphere samples for [`SsaoQuality::Classic`], clamped to
+    /// `1..=MAX_SSAO_KERNEL_SIZE`.
+    pub ssao_kernel_size: u32,
+    /// Radius in pixels of the separable blur that denoises the SSAO result,
+    /// clamped to `MAX_SSAO_BLUR_RADIUS`. Taps across depth discontinuities
+    /// are rejected. 0 disables the blur.
+    pub ssao_blur_radius: u32,
+    /// Side length of the SSAO rotation noise texture. Rounded up to a power
+    /// of two within `MIN_SSAO_NOISE_SIZE..=MAX_SSAO_NOISE_SIZE`; larger
+    /// tiles hide the repeating pattern but need a wider blur.
+    pub ssao_noise_size: u32,
+    /// Seed of the SSAO rotation noise; see [`generate_ssao_noise`].
+    pub ssao_noise_seed: u64,
+    /// Threshold, spread and strength of the bloom.
+    pub bloom_settings: BloomSettings,
+    pub tone_mapping: ToneMapping,
+    /// EV stops applied on top of `PostProcess::set_exposure`, clamped to
+    /// `±MAX_EXPOSURE_COMPENSATION`.
+    pub exposure_compensation: f32,
+    /// Depth of field. Blurs surfaces away from `dof_focus_distance` with a
+    /// hexagonal bokeh.
+    pub dof: bool,
+    /// View-space distance that stays in perfect focus, in world units.
+    pub dof_focus_distance: f32,
+    /// Scales the circle of confusion; larger values give a shallower
+    /// depth of field.
+    pub dof_aperture: f32,
+    /// Height fog. Ray-marches the depth buffer and scatters `fog_color`
+    /// over the scene.
+    pub fog: bool,
+    /// Extinction per world unit at height 0.
+    pub fog_density: f32,
+    /// Linear color of the light scattered by the fog.
+    pub fog_color: [f32; 3],
+    /// How quickly the density falls off with world height; 0 gives uniform
+    /// fog.
+    pub fog_height_falloff: f32,
 }
 
 impl Default for PostProcessEffects {
@@ -84,7 +269,27 @@ impl Default for PostProcessEffects {
         Self {
             ssao: true,
             bloom: true,
+            bloom_emissive_only: false,
             fxaa: true,
+            taa: false,
+            ssao_radius: 0.2,
+            ssao_bias: 0.05,
+            ssao_intensity: 0.75,
+            ssao_quality: SsaoQuality::default(),
+            ssao_kernel_size: 32,
+            ssao_blur_radius: 2,
+            ssao_noise_size: MIN_SSAO_NOISE_SIZE,
+            ssao_noise_seed: 0x5EED,
+            bloom_settings: BloomSettings::default(),
+            tone_mapping: ToneMapping::default(),
+            exposure_compensation: 0.0,
+            dof: false,
+            dof_focus_distance: 5.0,
+            dof_aperture: 0.5,
+            fog: false,
+            fog_density: 0.05,
+            fog_color: [0.6, 0.65, 0.7],
+            fog_height_falloff: 0.2,
         }
     }
 }
@@ -98,25 +303,203 @@ impl PostProcessEffects {
             0.0,
         ]
     }
+
+    fn ssao_radius_bias(self) -> [f32; 2] {
+        [
+            finite_or(self.ssao_radius, 0.2).max(1.0e-3),
+            finite_or(self.ssao_bias, 0.05).max(0.0),
+        ]
+    }
+
+    fn ssao_intensity(self) -> f32 {
+        finite_or(self.ssao_intensity, 0.75).clamp(0.0, 1.0)
+    }
+
+    fn ssao_kernel_size(self) -> u32 {
+        self.ssao_kernel_size.clamp(1, MAX_SSAO_KERNEL_SIZE)
+    }
+
+    /// Horizon directions and steps as read by `fs_ssao`; zero directions
+    /// select the hemisphere kernel.
+    fn ssao_horizon_params(self) -> [f32; 2] {
+        match self.ssao_quality.horizon_samples() {
+            Some((directions, steps)) => [
+                directions.clamp(1, MAX_SSAO_HORIZON_DIRECTIONS) as f32,
+                steps.clamp(1, MAX_SSAO_HORIZON_STEPS) as f32,
+            ],
+            None => [0.0, 0.0],
+        }
+    }
+
+    fn ssao_blur_radius(self) -> u32 {
+        self.ssao_blur_radius.min(MAX_SSAO_BLUR_RADIUS)
+    }
+
+    fn ssao_noise_size(self) -> u32 {
+        self.ssao_noise_size
+            .clamp(MIN_SSAO_NOISE_SIZE, MAX_SSAO_NOISE_SIZE)
+            .next_power_of_two()
+    }
+
+    /// Linear factor for `exposure_compensation`.
+    fn exposure_scale(self) -> f32 {
+        let stops = finite_or(self.exposure_compensation, 0.0)
+            .clamp(-MAX_EXPOSURE_COMPENSATION, MAX_EXPOSURE_COMPENSATION);
+        stops.exp2()
+    }
+
+    fn dof_params(self) -> [f32; 4] {
+        [
+            finite_or(self.dof_focus_distance, 5.0).max(1.0e-3),
+            finite_or(self.dof_aperture, 0.5).max(0.0),
+            DOF_MAX_COC_PIXELS,
+            0.0,
+        ]
+    }
+
+    /// Fog color and density, then height falloff and the enable flag.
+    fn fog_params(self) -> ([f32; 4], [f32; 4]) {
+        let [r, g, b] = self
+            .fog_color
+            .map(|channel| finite_or(channel, 0.0).max(0.0));
+        (
+            [r, g, b, finite_or(self.fog_density, 0.05).max(0.0)],
+            [
+                finite_or(self.fog_height_falloff, 0.2).max(0.0),
+                if self.fog { 1.0 } else { 0.0 },
+                0.0,
+                0.0,
+            ],
+        )
+    }
+}
+
+/// Builds a `size` x `size` RGBA32F texture of random unit-length 2D
+/// rotation vectors (xy, with zw zero) for the SSAO kernel. Angles are
+/// uniformly distributed and the same seed always yields the same texture.
+pub fn generate_ssao_noise(size: u32, seed: u64) -> Vec<f32> {
+    // splitmix64 scrambles the seed so nearby seeds give unrelated
+    // sequences and xorshift never starts from the all-zero state.
+    let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    state ^= state >> 31;
+    if state == 0 {
+        state = 0x9E37_79B9_7F4A_7C15;
+    }
+
+    let texels = (size * size) as usize;
+    let mut data = Vec::with_capacity(texels * 4);
+    for _ in 0..texels {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        // The top 24 bits give a uniform value in [0, 1).
+        let unit = (state >> 40) as f32 / (1u64 << 24) as f32;
+        let (sin, cos) = (unit * std::f32::consts::TAU).sin_cos();
+        data.extend_from_slice(&[cos, sin, 0.0, 0.0]);
+    }
+    data
+}
+
+fn finite_or(value: f32, fallback: f32) -> f32 {
+    if value.is_finite() {
+        value
+    } else {
+        fallback
+    }
+}
+
+/// Element `index` of the Halton low-discrepancy sequence in `base`.
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Sizes of the bloom chain textures: half of `size`, then halving per
+/// level down to 1x1.
+fn bloom_mip_extents(size: &wgpu::Extent3d, mip_count: usize) -> Vec<wgpu::Extent3d> {
+    let mut width = (size.width.max(2) / 2).max(1);
+    let mut height = (size.height.max(2) / 2).max(1);
+    (0..mip_count)
+        .map(|_| {
+            let extent = wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            };
+            width = (width / 2).max(1);
+            height = (height / 2).max(1);
+            extent
+        })
+        .collect()
+}
+
+/// Size of the scene targets for a `width` x `height` surface at `scale`,
+/// at least 1x1. The scale is lowered when the larger side would exceed
+/// `max_dimension`, keeping the aspect ratio.
+pub(crate) fn scaled_extent(
+    width: u32,
+    height: u32,
+    scale: f32,
+    max_dimension: u32,
+) -> wgpu::Extent3d {
+    let longest = width.max(height).max(1) as f32;
+    let scale = scale.min(max_dimension.max(1) as f32 / longest);
+    let scaled = |side: u32| ((side as f32 * scale).round() as u32).clamp(1, max_dimension.max(1));
+    wgpu::Extent3d {
+        width: scaled(width),
+        height: scaled(height),
+        depth_or_array_layers: 1,
+    }
+}
+
+/// Sub-pixel jitter for `frame` in NDC units, within ±half a pixel.
+fn taa_jitter(frame: u32, size: wgpu::Extent3d) -> Vec2 {
+    // Halton index 0 is (0, 0); start at 1 so every sample is off-centre.
+    let index = frame % TAA_JITTER_SAMPLES + 1;
+    let offset = Vec2::new(halton(index, 2) - 0.5, halton(index, 3) - 0.5);
+    offset * 2.0 / Vec2::new(size.width.max(1) as f32, size.height.max(1) as f32)
 }
 
 pub struct PostProcess {
     scene: TextureBundle,
     scene_msaa: Option<MsaaTarget>,
+    emissive: TextureBundle,
+    emissive_msaa: Option<MsaaTarget>,
+    velocity: TextureBundle,
+    velocity_msaa: Option<MsaaTarget>,
     ssao: TextureBundle,
+    // Intermediate of the separable blur. The vertical pass writes the
+    // final result back into `ssao`, which the composite reads.
+    ssao_blur: TextureBundle,
     bloom_down_chain: Vec<BloomMip>,
     bloom_up_chain: Vec<BloomMip>,
+    // Lens dirt multiplied into the bloom; 1x1 black until a mask is set.
+    bloom_dirt_view: wgpu::TextureView,
     sampler_linear: wgpu::Sampler,
     sampler_noise: wgpu::Sampler,
     _noise_texture: wgpu::Texture,
     noise_view: wgpu::TextureView,
+    uniform_layout: wgpu::BindGroupLayout,
     uniform_buffer: wgpu::Buffer,
     uniform_bind_group: wgpu::BindGroup,
+    bloom_params_buffer: wgpu::Buffer,
+    bloom_params_bind_group: wgpu::BindGroup,
     depth_resolve_layout: Option<wgpu::BindGroupLayout>,
     depth_resolve_pipeline: Option<wgpu::RenderPipeline>,
     depth_resolve_bind_group: Option<wgpu::BindGroup>,
     ssao_layout: wgpu::BindGroupLayout,
     ssao_pipeline: wgpu::RenderPipeline,
+    ssao_blur_layout: wgpu::BindGroupLayout,
+    ssao_blur_horizontal_pipeline: wgpu::RenderPipeline,
+    ssao_blur_vertical_pipeline: wgpu::RenderPipeline,
     bloom_prefilter_layout: wgpu::BindGroupLayout,
     bloom_prefilter_pipeline: wgpu::RenderPipeline,
     bloom_downsample_layout: wgpu::BindGroupLayout,
@@ -128,17 +511,30 @@ pub struct PostProcess {
     size: wgpu::Extent3d,
     effects: PostProcessEffects,
     ssao_bind_group: Option<wgpu::BindGroup>,
+    // [0] reads `ssao` (horizontal pass), [1] reads `ssao_blur` (vertical).
+    ssao_blur_bind_groups: Vec<wgpu::BindGroup>,
     bloom_prefilter_bind_group: Option<wgpu::BindGroup>,
+    emissive_bloom_prefilter_bind_group: Option<wgpu::BindGroup>,
     bloom_downsample_passes: Vec<BloomDownsamplePass>,
     bloom_upsample_passes: Vec<BloomUpsamplePass>,
     composite_bind_group: Option<wgpu::BindGroup>,
+    taa: TaaPass,
+    // Indexed by `TaaPass::write_index`; read the TAA output instead of the scene.
+    taa_bloom_prefilter_bind_groups: Vec<wgpu::BindGroup>,
+    taa_composite_bind_groups: Vec<wgpu::BindGroup>,
+    dof: DofPass,
+    dof_composite_bind_group: Option<wgpu::BindGroup>,
+    fog: FogPass,
+    upscale: UpscalePass,
+    resolution_scale: f32,
     resolved_depth: Option<TextureBundle>,
     cached_depth_view: Option<wgpu::TextureView>,
     bind_groups_dirty: bool,
-    last_proj: Mat4,
-    last_near: f32,
-    last_far: f32,
+    last_projection: ProjectionMatrix,
+    last_view: Mat4,
+    exposure: f32,
     sample_count: u32,
+    graph: RenderGraph,
 }
 
 impl PostProcess {
@@ -147,12 +543,14 @@ impl PostProcess {
         queue: &wgpu::Queue,
         config: &wgpu::SurfaceConfiguration,
         sample_count: u32,
+        resolution_scale: f32,
     ) -> Self {
-        let size = wgpu::Extent3d {
-            width: config.width.max(1),
-            height: config.height.max(1),
-            depth_or_array_layers: 1,
-        };
+        let size = scaled_extent(
+            config.width,
+            config.height,
+            resolution_scale,
+            device.limits().max_texture_dimension_2d,
+        );
 
         let sampler_linear = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("PostProcessLinearSampler"),
@@ -177,9 +575,28 @@ impl PostProcess {
         });
 
         let (scene, scene_msaa) =
-            Self::create_scene_targets(device, &size, config.format, sample_count);
-        let ssao = TextureBundle::ssao(device, &size);
-        let (bloom_down_chain, bloom_up_chain) = Self::create_bloom_chain(device, &size);
+            Self::create_scene_targets(device, &size, config.format, sample_count, "SceneColor");
+        let (emissive, emissive_msaa) = Self::create_scene_targets(
+            device,
+            &size,
+            SCENE_EMISSIVE_FORMAT,
+            sample_count,
+            "SceneEmissive",
+        );
+        let (velocity, velocity_msaa) = Self::create_scene_targets(
+            device,
+            &size,
+            SCENE_VELOCITY_FORMAT,
+            sample_count,
+            "SceneVelocity",
+        );
+        let ssao = TextureBundle::ssao(device, &size, "SsaoTexture");
+        let ssao_blur = TextureBundle::ssao(device, &size, "SsaoBlurTexture");
+        let effects = PostProcessEffects::default();
+        let (bloom_down_chain, bloom_up_chain) =
+            Self::create_bloom_chain(device, &size, effects.bloom_settings.mip_count());
+        let bloom_dirt_view = Self::create_black_texture(device, queue, "BloomDirtFallback")
+            .create_view(&wgpu::TextureViewDescriptor::default());
 
         let resolved_depth = if sample_count > 1 {
             Some(TextureBundle::depth(device, &size, "ResolvedDepth"))
@@ -220,7 +637,40 @@ impl PostProcess {
             }],
         });
 
-        let noise_texture = Self::create_noise_texture(device, queue);
+        let bloom_params_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("BloomParamsLayout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<BloomParams>() as u64
+                        ),
+                    },
+                    count: None,
+                }],
+            });
+
+        let bloom_params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("BloomParamsBuffer"),
+            size: std::mem::size_of::<BloomParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bloom_params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("BloomParamsBindGroup"),
+            layout: &bloom_params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: bloom_params_buffer.as_entire_binding(),
+            }],
+        });
+
+        let noise_texture = Self::create_noise_texture(device, queue, effects);
         let noise_view = noise_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         let postprocess_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -235,47 +685,8 @@ impl PostProcess {
             compilation_options: Default::default(),
         };
 
-        let (depth_resolve_layout, depth_resolve_pipeline) = if sample_count > 1 {
-            let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("DepthResolveLayout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Depth,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: true,
-                    },
-                    count: None,
-                }],
-            });
-            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("DepthResolveShader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../../shader/depth_resolve.wgsl").into(),
-                ),
-            });
-            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("DepthResolvePipelineLayout"),
-                bind_group_layouts: &[&uniform_layout, &layout],
-                push_constant_ranges: &[],
-            });
-            let pipeline = PipelineBuilder::new(device, &pipeline_layout, &shader)
-                .with_label("DepthResolvePipeline")
-                .with_vertex_entry("vs_fullscreen")
-                .with_fragment_entry("fs_resolve_depth")
-                .with_depth_stencil(
-                    wgpu::TextureFormat::Depth32Float,
-                    true,
-                    wgpu::CompareFunction::Always,
-                )
-                .with_vertex_state(fullscreen_vertex.clone())
-                .with_no_culling()
-                .build();
-            (Some(layout), Some(pipeline))
-        } else {
-            (None, None)
-        };
+        let (depth_resolve_layout, depth_resolve_pipeline) =
+            Self::create_depth_resolve(device, &uniform_layout, sample_count);
 
         // SSAO pipeline setup
         let ssao_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -326,6 +737,62 @@ impl PostProcess {
                 .with_no_culling()
                 .build();
 
+        let ssao_blur_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("SsaoBlurLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                // Depth of the occlusion pass, to keep the blur from
+                // crossing silhouettes.
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let ssao_blur_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("SsaoBlurPipelineLayout"),
+                bind_group_layouts: &[&uniform_layout, &ssao_blur_layout],
+                push_constant_ranges: &[],
+            });
+
+        let ssao_blur_pipeline = |label: &str, entry: &str| {
+            PipelineBuilder::new(device, &ssao_blur_pipeline_layout, &postprocess_shader)
+                .with_label(label)
+                .with_vertex_entry("vs_fullscreen")
+                .with_fragment_entry(entry)
+                .with_color_target(wgpu::TextureFormat::R8Unorm, None)
+                .with_vertex_state(fullscreen_vertex.clone())
+                .with_no_culling()
+                .build()
+        };
+        let ssao_blur_horizontal_pipeline =
+            ssao_blur_pipeline("SsaoBlurHorizontalPipeline", "fs_ssao_blur_horizontal");
+        let ssao_blur_vertical_pipeline =
+            ssao_blur_pipeline("SsaoBlurVerticalPipeline", "fs_ssao_blur_vertical");
+
         // Bloom prefilter pipeline
         let bloom_prefilter_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -353,7 +820,7 @@ impl PostProcess {
         let bloom_prefilter_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("BloomPrefilterPipelineLayout"),
-                bind_group_layouts: &[&bloom_prefilter_layout],
+                bind_group_layouts: &[&bloom_prefilter_layout, &bloom_params_layout],
                 push_constant_ranges: &[],
             });
 
@@ -449,7 +916,7 @@ impl PostProcess {
         let bloom_upsample_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("BloomUpsamplePipelineLayout"),
-                bind_group_layouts: &[&bloom_upsample_layout],
+                bind_group_layouts: &[&bloom_upsample_layout, &bloom_params_layout],
                 push_constant_ranges: &[],
             });
 
@@ -503,6 +970,26 @@ impl PostProcess {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
             ],
         });
 
@@ -523,23 +1010,64 @@ impl PostProcess {
                 .with_no_culling()
                 .build();
 
+        let taa = TaaPass::new(
+            device,
+            &size,
+            &postprocess_shader,
+            fullscreen_vertex.clone(),
+        );
+        let dof = DofPass::new(
+            device,
+            &size,
+            &postprocess_shader,
+            fullscreen_vertex.clone(),
+            &uniform_layout,
+        );
+        let fog = FogPass::new(
+            device,
+            &size,
+            &postprocess_shader,
+            fullscreen_vertex,
+            &uniform_layout,
+        );
+        let mut upscale = UpscalePass::new(device, config.format);
+        upscale.resize(
+            device,
+            &sampler_linear,
+            &size,
+            config.width.max(1),
+            config.height.max(1),
+        );
+
         let post = Self {
             scene,
             scene_msaa,
+            emissive,
+            emissive_msaa,
+            velocity,
+            velocity_msaa,
             ssao,
+            ssao_blur,
             bloom_down_chain,
             bloom_up_chain,
+            bloom_dirt_view,
             sampler_linear,
             sampler_noise,
             _noise_texture: noise_texture,
             noise_view,
+            uniform_layout,
             uniform_buffer,
             uniform_bind_group,
+            bloom_params_buffer,
+            bloom_params_bind_group,
             depth_resolve_layout,
             depth_resolve_pipeline,
             depth_resolve_bind_group: None,
             ssao_layout,
             ssao_pipeline,
+            ssao_blur_layout,
+            ssao_blur_horizontal_pipeline,
+            ssao_blur_vertical_pipeline,
             bloom_prefilter_layout,
             bloom_prefilter_pipeline,
             bloom_downsample_layout,
@@ -549,28 +1077,42 @@ impl PostProcess {
             composite_layout,
             composite_pipeline,
             size,
-            effects: PostProcessEffects::default(),
+            effects,
             ssao_bind_group: None,
+            ssao_blur_bind_groups: Vec::new(),
             bloom_prefilter_bind_group: None,
+            emissive_bloom_prefilter_bind_group: None,
             bloom_downsample_passes: Vec::new(),
             bloom_upsample_passes: Vec::new(),
             composite_bind_group: None,
+            taa,
+            taa_bloom_prefilter_bind_groups: Vec::new(),
+            taa_composite_bind_groups: Vec::new(),
+            dof,
+            dof_composite_bind_group: None,
+            fog,
+            upscale,
+            resolution_scale,
             resolved_depth,
             cached_depth_view: None,
             bind_groups_dirty: true,
-            last_proj: Mat4::IDENTITY,
-            last_near: 0.01,
-            last_far: 100.0,
+            last_projection: ProjectionMatrix {
+                proj: Mat4::IDENTITY,
+                near: 0.01,
+                far: 100.0,
+                orthographic: false,
+            },
+            last_view: Mat4::IDENTITY,
+            exposure: 1.0,
             sample_count,
+            graph: RenderGraph::builtin(),
         };
 
         let initial_uniform = PostProcessUniform::new(
-            post.last_proj,
-            post.last_proj.inverse(),
+            post.last_projection,
             post.size.width as f32,
             post.size.height as f32,
-            post.last_near,
-            post.last_far,
+            post.exposure,
             post.effects,
             post.sample_count,
         );
@@ -579,6 +1121,12 @@ impl PostProcess {
             0,
             bytemuck::bytes_of(&initial_uniform),
         );
+        queue.write_buffer(
+            &post.bloom_params_buffer,
+            0,
+            bytemuck::bytes_of(&post.effects.bloom_settings.params()),
+        );
+        post.taa.upload_uniform(queue, post.size);
 
         post
     }
@@ -594,222 +1142,672 @@ impl PostProcess {
         if width == 0 || height == 0 {
             return;
         }
-        self.size = wgpu::Extent3d {
+        self.size = scaled_extent(
             width,
             height,
-            depth_or_array_layers: 1,
-        };
+            self.resolution_scale,
+            device.limits().max_texture_dimension_2d,
+        );
         let (scene, scene_msaa) =
-            Self::create_scene_targets(device, &self.size, format, self.sample_count);
+            Self::create_scene_targets(device, &self.size, format, self.sample_count, "SceneColor");
         self.scene = scene;
         self.scene_msaa = scene_msaa;
-        self.ssao = TextureBundle::ssao(device, &self.size);
+        (self.emissive, self.emissive_msaa) = Self::create_scene_targets(
+            device,
+            &self.size,
+            SCENE_EMISSIVE_FORMAT,
+            self.sample_count,
+            "SceneEmissive",
+        );
+        (self.velocity, self.velocity_msaa) = Self::create_scene_targets(
+            device,
+            &self.size,
+            SCENE_VELOCITY_FORMAT,
+            self.sample_count,
+            "SceneVelocity",
+        );
+        self.ssao = TextureBundle::ssao(device, &self.size, "SsaoTexture");
+        self.ssao_blur = TextureBundle::ssao(device, &self.size, "SsaoBlurTexture");
         self.resolved_depth = if self.sample_count > 1 {
             Some(TextureBundle::depth(device, &self.size, "ResolvedDepth"))
         } else {
             None
         };
-        let (down_chain, up_chain) = Self::create_bloom_chain(device, &self.size);
-        self.bloom_down_chain = down_chain;
-        self.bloom_up_chain = up_chain;
+        self.rebuild_bloom_chain(device);
+        // Old history no longer matches the new resolution.
+        self.taa.resize(device, &self.size);
+        self.dof.resize(device, &self.size);
+        self.fog.resize(device, &self.size);
+        self.upscale
+            .resize(device, &self.sampler_linear, &self.size, width, height);
         self.mark_bind_groups_dirty();
         self.upload_uniform(queue);
+        self.taa.upload_uniform(queue, self.size);
     }
 
-    pub fn update_camera(&mut self, queue: &wgpu::Queue, proj: Mat4, near: f32, far: f32) {
-        self.last_proj = proj;
-        self.last_near = near;
-        self.last_far = far;
-        self.upload_uniform(queue);
-    }
-
-    pub fn scene_color_views(&self) -> (&wgpu::TextureView, Option<&wgpu::TextureView>) {
-        match self.scene_msaa.as_ref() {
-            Some(msaa) => (&msaa.view, Some(&self.scene.view)),
-            None => (&self.scene.view, None),
-        }
-    }
-
-    pub fn scene_view(&self) -> &wgpu::TextureView {
-        &self.scene.view
-    }
-
-    pub fn ssao_texture(&self) -> &wgpu::TextureView {
-        &self.ssao.view
+    /// Sets the scene target size relative to the surface. Takes effect on
+    /// the next [`resize`](Self::resize).
+    pub fn set_resolution_scale(&mut self, scale: f32) {
+        self.resolution_scale = scale;
     }
 
-    pub fn bloom_texture(&self) -> &wgpu::TextureView {
-        &self.bloom_up_chain[0].view
+    pub fn resolution_scale(&self) -> f32 {
+        self.resolution_scale
     }
 
-    pub fn set_depth_view(&mut self, depth_view: &wgpu::TextureView) {
-        self.cached_depth_view = Some(depth_view.clone());
-        self.mark_bind_groups_dirty();
+    /// Size of the scene, depth and effect targets.
+    pub fn render_size(&self) -> wgpu::Extent3d {
+        self.size
     }
 
-    pub fn set_effects(&mut self, queue: &wgpu::Queue, effects: PostProcessEffects) {
-        if self.effects != effects {
-            self.effects = effects;
-            self.upload_uniform(queue);
-        }
+    /// Target that [`execute`](Self::execute) and the passes drawn after it
+    /// should render into while the scene resolution differs from the
+    /// surface; `None` to render straight to the surface.
+    /// [`upscale`](Self::upscale) then copies it to the surface.
+    pub fn output_view(&self) -> Option<&wgpu::TextureView> {
+        self.upscale.target.as_ref().map(|target| &target.view)
     }
 
-    pub fn effects(&self) -> PostProcessEffects {
-        self.effects
+    /// Bilinearly stretches the [`output_view`](Self::output_view) over
+    /// `target`. Does nothing at the surface resolution.
+    pub fn upscale(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        self.upscale.record(encoder, target);
     }
 
-    pub fn execute(
+    /// Recreates the multisampled scene target and depth resolve resources
+    /// for a new MSAA level. The caller must re-register the depth view.
+    pub fn set_sample_count(
         &mut self,
-        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) {
+        let sample_count = sample_count.max(1);
+        if sample_count == self.sample_count {
+            return;
+        }
+        self.sample_count = sample_count;
+        let (scene, scene_msaa) =
+            Self::create_scene_targets(device, &self.size, format, sample_count, "SceneColor");
+        self.scene = scene;
+        self.scene_msaa = scene_msaa;
+        (self.emissive, self.emissive_msaa) = Self::create_scene_targets(
+            device,
+            &self.size,
+            SCENE_EMISSIVE_FORMAT,
+            sample_count,
+            "SceneEmissive",
+        );
+        (self.velocity, self.velocity_msaa) = Self::create_scene_targets(
+            device,
+            &self.size,
+            SCENE_VELOCITY_FORMAT,
+            sample_count,
+            "SceneVelocity",
+        );
+        self.resolved_depth = if sample_count > 1 {
+            Some(TextureBundle::depth(device, &self.size, "ResolvedDepth"))
+        } else {
+            None
+        };
+        let (layout, pipeline) =
+            Self::create_depth_resolve(device, &self.uniform_layout, sample_count);
+        self.depth_resolve_layout = layout;
+        self.depth_resolve_pipeline = pipeline;
+        self.cached_depth_view = None;
+        self.taa.invalidate();
+        self.mark_bind_groups_dirty();
+        self.upload_uniform(queue);
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Updates the projection used for depth reconstruction. The clip planes
+    /// travel with the matrix so they cannot drift apart. `view` places the
+    /// fog's height falloff in world space.
+    pub fn update_camera(&mut self, queue: &wgpu::Queue, projection: ProjectionMatrix, view: Mat4) {
+        self.last_projection = projection;
+        self.last_view = view;
+        self.upload_uniform(queue);
+    }
+
+    /// Records this frame's unjittered view-projection for TAA reprojection.
+    /// Call once per frame, after `update_camera`.
+    pub fn update_view_proj(&mut self, queue: &wgpu::Queue, view_proj: Mat4) {
+        self.taa.prev_view_proj = if self.taa.history_valid {
+            self.taa.view_proj
+        } else {
+            view_proj
+        };
+        self.taa.view_proj = view_proj;
+        self.taa.upload_uniform(queue, self.size);
+    }
+
+    /// This and last frame's unjittered view-projection, as recorded by
+    /// [`update_view_proj`](Self::update_view_proj). Equal while the TAA
+    /// history is invalid, so a reset frame reports no camera motion.
+    pub fn motion_view_proj(&self) -> (Mat4, Mat4) {
+        (self.taa.view_proj, self.taa.prev_view_proj)
+    }
+
+    /// Applies this frame's sub-pixel TAA jitter to `proj`. Returns `proj`
+    /// unchanged when TAA or its graph node is disabled.
+    pub fn jitter_projection(&self, proj: Mat4) -> Mat4 {
+        if !self.effects.taa || !self.graph.builtin_enabled(BuiltinNode::Taa) {
+            return proj;
+        }
+        let jitter = self.taa.jitter(self.size);
+        Mat4::from_translation(Vec3::new(jitter.x, jitter.y, 0.0)) * proj
+    }
+
+    /// Sets how much of the current frame is blended into the TAA history.
+    /// Lower values are smoother but ghost more; clamped to `0.01..=1.0`.
+    pub fn set_taa_blend_factor(&mut self, queue: &wgpu::Queue, factor: f32) {
+        let factor = finite_or(factor, DEFAULT_TAA_BLEND_FACTOR).clamp(0.01, 1.0);
+        if self.taa.blend_factor != factor {
+            self.taa.blend_factor = factor;
+            self.taa.upload_uniform(queue, self.size);
+        }
+    }
+
+    pub fn taa_blend_factor(&self) -> f32 {
+        self.taa.blend_factor
+    }
+
+    pub fn scene_color_views(&self) -> (&wgpu::TextureView, Option<&wgpu::TextureView>) {
+        match self.scene_msaa.as_ref() {
+            Some(msaa) => (&msaa.view, Some(&self.scene.view)),
+            None => (&self.scene.view, None),
+        }
+    }
+
+    pub fn scene_view(&self) -> &wgpu::TextureView {
+        &self.scene.view
+    }
+
+    /// Render and resolve views of the emissive attachment, laid out like
+    /// [`scene_color_views`](Self::scene_color_views).
+    pub fn emissive_views(&self) -> (&wgpu::TextureView, Option<&wgpu::TextureView>) {
+        match self.emissive_msaa.as_ref() {
+            Some(msaa) => (&msaa.view, Some(&self.emissive.view)),
+            None => (&self.emissive.view, None),
+        }
+    }
+
+    /// The single-sampled emissive texture.
+    pub fn emissive_view(&self) -> &wgpu::TextureView {
+        &self.emissive.view
+    }
+
+    /// Render and resolve views of the velocity attachment, laid out like
+    /// [`scene_color_views`](Self::scene_color_views).
+    pub fn velocity_views(&self) -> (&wgpu::TextureView, Option<&wgpu::TextureView>) {
+        match self.velocity_msaa.as_ref() {
+            Some(msaa) => (&msaa.view, Some(&self.velocity.view)),
+            None => (&self.velocity.view, None),
+        }
+    }
+
+    /// The single-sampled velocity texture.
+    pub fn velocity_view(&self) -> &wgpu::TextureView {
+        &self.velocity.view
+    }
+
+    pub fn ssao_texture(&self) -> &wgpu::TextureView {
+        &self.ssao.view
+    }
+
+    pub fn bloom_texture(&self) -> &wgpu::TextureView {
+        &self.bloom_up_chain[0].view
+    }
+
+    pub fn set_depth_view(&mut self, depth_view: &wgpu::TextureView) {
+        self.cached_depth_view = Some(depth_view.clone());
+        self.mark_bind_groups_dirty();
+    }
+
+    pub fn set_effects(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        effects: PostProcessEffects,
+    ) {
+        if self.effects != effects {
+            if self.effects.ssao_noise_size() != effects.ssao_noise_size()
+                || self.effects.ssao_noise_seed != effects.ssao_noise_seed
+            {
+                let noise_texture = Self::create_noise_texture(device, queue, effects);
+                self.noise_view =
+                    noise_texture.create_view(&wgpu::TextureViewDescriptor::default());
+                self._noise_texture = noise_texture;
+                self.mark_bind_groups_dirty();
+            }
+            if self.effects.taa != effects.taa {
+                self.taa.invalidate();
+                self.taa.upload_uniform(queue, self.size);
+            }
+            if self.effects.dof != effects.dof || self.effects.dof_params() != effects.dof_params()
+            {
+                self.mark_bind_groups_dirty();
+            }
+            let mip_count_changed =
+                self.effects.bloom_settings.mip_count() != effects.bloom_settings.mip_count();
+            self.effects = effects;
+            if mip_count_changed {
+                self.rebuild_bloom_chain(device);
+            }
+            self.upload_uniform(queue);
+        }
+    }
+
+    /// Sets the lens dirt texture that modulates the bloom, scaled by
+    /// `BloomSettings::dirt_strength`. `None` removes it.
+    pub fn set_bloom_dirt_mask(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view: Option<&wgpu::TextureView>,
+    ) {
+        self.bloom_dirt_view = match view {
+            Some(view) => view.clone(),
+            None => Self::create_black_texture(device, queue, "BloomDirtFallback")
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+        };
+        self.mark_bind_groups_dirty();
+    }
+
+    /// Number of textures in each half of the bloom chain.
+    pub fn bloom_mip_count(&self) -> usize {
+        self.bloom_down_chain.len()
+    }
+
+    pub fn effects(&self) -> PostProcessEffects {
+        self.effects
+    }
+
+    /// Sets the linear exposure multiplier applied during composite.
+    pub fn set_exposure(&mut self, queue: &wgpu::Queue, exposure: f32) {
+        let exposure = if exposure.is_finite() {
+            exposure.max(0.0)
+        } else {
+            1.0
+        };
+        if self.exposure != exposure {
+            self.exposure = exposure;
+            self.upload_uniform(queue);
+        }
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// The post-processing graph. Custom nodes inserted here run between
+    /// the built-in passes, ordered by the textures they read and write.
+    pub fn graph(&self) -> &RenderGraph {
+        &self.graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut RenderGraph {
+        &mut self.graph
+    }
+
+    pub fn execute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
         device: &wgpu::Device,
         target: &wgpu::TextureView,
+        timer: &mut GpuTimer,
     ) {
         self.ensure_cached_bind_groups(device);
 
-        if self.effects.ssao {
-            if let (Some(pipeline), Some(bind_group), Some(resolved)) = (
-                self.depth_resolve_pipeline.as_ref(),
-                self.depth_resolve_bind_group.as_ref(),
-                self.resolved_depth.as_ref(),
-            ) {
-                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("DepthResolvePass"),
-                    color_attachments: &[],
-                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                        view: &resolved.view,
-                        depth_ops: Some(wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(1.0),
-                            store: wgpu::StoreOp::Store,
-                        }),
-                        stencil_ops: None,
-                    }),
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-                pass.set_pipeline(pipeline);
-                pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-                pass.set_bind_group(1, bind_group, &[]);
-                pass.draw(0..3, 0..1);
+        let mut graph = std::mem::take(&mut self.graph);
+        // Insertion rejects cyclic nodes, so the fallback is never taken.
+        let order = graph
+            .schedule()
+            .unwrap_or_else(|_| (0..graph.nodes.len()).collect());
+        let active = self.active_builtins(&graph);
+        let views = self.graph_views(target);
+        for index in order {
+            let node = &mut graph.nodes[index];
+            let enabled = node.is_enabled();
+            match &mut node.kind {
+                NodeKind::Builtin(builtin) => {
+                    if active.contains(*builtin) {
+                        self.record_builtin(*builtin, active, encoder, target, timer);
+                    } else {
+                        self.clear_builtin(*builtin, encoder);
+                    }
+                }
+                NodeKind::Custom(run) if enabled => {
+                    run(&mut RenderNodeContext::new(encoder, device, &views));
+                }
+                NodeKind::Custom(_) => {}
             }
+        }
+        self.graph = graph;
 
-            let ssao_bind_group = self
-                .ssao_bind_group
-                .as_ref()
-                .expect("SSAO bind group not initialized");
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("SsaoPass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.ssao.view,
-                    resolve_target: None,
-                    depth_slice: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            pass.set_pipeline(&self.ssao_pipeline);
-            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-            pass.set_bind_group(1, ssao_bind_group, &[]);
-            pass.draw(0..3, 0..1);
+        if active.contains(BuiltinNode::Taa) {
+            self.taa.advance();
         } else {
-            let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("SsaoPass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.ssao.view,
-                    resolve_target: None,
-                    depth_slice: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+            // The history would be stale once the node runs again.
+            self.taa.invalidate();
         }
+    }
+}
 
-        if self.effects.bloom {
-            let bloom_prefilter = self
-                .bloom_prefilter_bind_group
-                .as_ref()
-                .expect("Bloom prefilter bind group not initialized");
+impl PostProcess {
+    /// Views of the built-in graph resources for custom nodes.
+    fn graph_views<'a>(
+        &'a self,
+        target: &'a wgpu::TextureView,
+    ) -> Vec<(&'static str, &'a wgpu::TextureView)> {
+        let mut views = vec![
+            (graph::SCENE_COLOR, &self.scene.view),
+            (graph::EMISSIVE, &self.emissive.view),
+            (graph::VELOCITY, &self.velocity.view),
+            (graph::SSAO, &self.ssao.view),
+            (graph::FOG, &self.fog.output.view),
+            (
+                graph::TAA_COLOR,
+                &self.taa.history[self.taa.write_index].view,
+            ),
+            (graph::DOF_COLOR, &self.dof.output.view),
+            (graph::BLOOM, &self.bloom_up_chain[0].view),
+            (graph::OUTPUT, target),
+        ];
+        if let Some(depth) = &self.cached_depth_view {
+            views.push((graph::DEPTH, depth));
+        }
+        if let Some(resolved) = &self.resolved_depth {
+            views.push((graph::RESOLVED_DEPTH, &resolved.view));
+        }
+        views
+    }
 
-            {
-                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("BloomPrefilter"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &self.bloom_down_chain[0].view,
-                        resolve_target: None,
-                        depth_slice: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                            store: wgpu::StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-                pass.set_pipeline(&self.bloom_prefilter_pipeline);
-                pass.set_bind_group(0, bloom_prefilter, &[]);
-                pass.draw(0..3, 0..1);
+    fn builtin_active(&self, builtin: BuiltinNode) -> bool {
+        let effects = &self.effects;
+        match builtin {
+            BuiltinNode::DepthResolve => effects.ssao || effects.taa || effects.dof || effects.fog,
+            BuiltinNode::Ssao => effects.ssao,
+            BuiltinNode::Fog => effects.fog,
+            BuiltinNode::Taa => effects.taa,
+            BuiltinNode::Dof => effects.dof,
+            BuiltinNode::Bloom => effects.bloom,
+            BuiltinNode::Composite => true,
+        }
+    }
+
+    /// Built-in nodes that record this frame: enabled in `graph` with their
+    /// effect on. Under MSAA the depth readers sample the resolved depth, so
+    /// they are skipped along with its resolve.
+    fn active_builtins(&self, graph: &RenderGraph) -> ActiveBuiltins {
+        let mut active = BuiltinNode::ALL
+            .map(|builtin| graph.builtin_enabled(builtin) && self.builtin_active(builtin));
+        if self.resolved_depth.is_some() && !active[BuiltinNode::DepthResolve as usize] {
+            for builtin in [
+                BuiltinNode::Ssao,
+                BuiltinNode::Fog,
+                BuiltinNode::Taa,
+                BuiltinNode::Dof,
+            ] {
+                active[builtin as usize] = false;
             }
+        }
+        ActiveBuiltins(active)
+    }
 
-            for pass_info in &self.bloom_downsample_passes {
-                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("BloomDownsample"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &self.bloom_down_chain[pass_info.target_index].view,
-                        resolve_target: None,
-                        depth_slice: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                            store: wgpu::StoreOp::Store,
+    fn record_builtin(
+        &self,
+        builtin: BuiltinNode,
+        active: ActiveBuiltins,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        timer: &mut GpuTimer,
+    ) {
+        match builtin {
+            BuiltinNode::DepthResolve => {
+                if let (Some(pipeline), Some(bind_group), Some(resolved)) = (
+                    self.depth_resolve_pipeline.as_ref(),
+                    self.depth_resolve_bind_group.as_ref(),
+                    self.resolved_depth.as_ref(),
+                ) {
+                    timer.begin(encoder, GpuPass::DepthResolve);
+                    {
+                        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                            label: Some("DepthResolvePass"),
+                            color_attachments: &[],
+                            depth_stencil_attachment: Some(
+                                wgpu::RenderPassDepthStencilAttachment {
+                                    view: &resolved.view,
+                                    depth_ops: Some(wgpu::Operations {
+                                        load: wgpu::LoadOp::Clear(1.0),
+                                        store: wgpu::StoreOp::Store,
+                                    }),
+                                    stencil_ops: None,
+                                },
+                            ),
+                            timestamp_writes: None,
+                            occlusion_query_set: None,
+                        });
+                        pass.set_pipeline(pipeline);
+                        pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                        pass.set_bind_group(1, bind_group, &[]);
+                        pass.draw(0..3, 0..1);
+                    }
+                    timer.end(encoder, GpuPass::DepthResolve);
+                }
+            }
+            BuiltinNode::Ssao => {
+                let ssao_bind_group = self
+                    .ssao_bind_group
+                    .as_ref()
+                    .expect("SSAO bind group not initialized");
+                timer.begin(encoder, GpuPass::Ssao);
+                {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("SsaoPass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &self.ssao.view,
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    pass.set_pipeline(&self.ssao_pipeline);
+                    pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                    pass.set_bind_group(1, ssao_bind_group, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+                if self.effects.ssao_blur_radius() > 0 {
+                    self.record_ssao_blur(encoder);
+                }
+                timer.end(encoder, GpuPass::Ssao);
+            }
+            BuiltinNode::Fog => {
+                timer.begin(encoder, GpuPass::Fog);
+                self.fog.record(encoder, &self.uniform_bind_group);
+                timer.end(encoder, GpuPass::Fog);
+            }
+            BuiltinNode::Taa => {
+                timer.begin(encoder, GpuPass::Taa);
+                self.taa.record(encoder);
+                timer.end(encoder, GpuPass::Taa);
+            }
+            BuiltinNode::Dof => {
+                // Blur whichever color the composite would otherwise read.
+                let input_index = if active.contains(BuiltinNode::Taa) {
+                    1 + self.taa.write_index
+                } else {
+                    0
+                };
+                timer.begin(encoder, GpuPass::Dof);
+                self.dof
+                    .record(encoder, input_index, &self.uniform_bind_group);
+                timer.end(encoder, GpuPass::Dof);
+            }
+            BuiltinNode::Bloom => {
+                let bloom_prefilter = if self.effects.bloom_emissive_only {
+                    self.emissive_bloom_prefilter_bind_group
+                        .as_ref()
+                        .expect("Emissive bloom prefilter bind group not initialized")
+                } else if active.contains(BuiltinNode::Taa) {
+                    &self.taa_bloom_prefilter_bind_groups[self.taa.write_index]
+                } else {
+                    self.bloom_prefilter_bind_group
+                        .as_ref()
+                        .expect("Bloom prefilter bind group not initialized")
+                };
+
+                timer.begin(encoder, GpuPass::Bloom);
+                {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("BloomPrefilter"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &self.bloom_down_chain[0].view,
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    pass.set_pipeline(&self.bloom_prefilter_pipeline);
+                    pass.set_bind_group(0, bloom_prefilter, &[]);
+                    pass.set_bind_group(1, &self.bloom_params_bind_group, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+
+                for pass_info in &self.bloom_downsample_passes {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("BloomDownsample"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &self.bloom_down_chain[pass_info.target_index].view,
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    pass.set_pipeline(&self.bloom_downsample_pipeline);
+                    pass.set_bind_group(0, &pass_info.bind_group, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+
+                if let (Some(last_down), Some(last_up)) =
+                    (self.bloom_down_chain.last(), self.bloom_up_chain.last())
+                {
+                    encoder.copy_texture_to_texture(
+                        wgpu::TexelCopyTextureInfo {
+                            texture: last_down.texture(),
+                            mip_level: 0,
+                            origin: wgpu::Origin3d::ZERO,
+                            aspect: wgpu::TextureAspect::All,
                         },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-                pass.set_pipeline(&self.bloom_downsample_pipeline);
-                pass.set_bind_group(0, &pass_info.bind_group, &[]);
-                pass.draw(0..3, 0..1);
+                        wgpu::TexelCopyTextureInfo {
+                            texture: last_up.texture(),
+                            mip_level: 0,
+                            origin: wgpu::Origin3d::ZERO,
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        last_down.extent(),
+                    );
+                }
+
+                for pass_info in &self.bloom_upsample_passes {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("BloomUpsample"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &self.bloom_up_chain[pass_info.target_index].view,
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    pass.set_pipeline(&self.bloom_upsample_pipeline);
+                    pass.set_bind_group(0, &pass_info.bind_group, &[]);
+                    pass.set_bind_group(1, &self.bloom_params_bind_group, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+                timer.end(encoder, GpuPass::Bloom);
             }
-
-            if let (Some(last_down), Some(last_up)) =
-                (self.bloom_down_chain.last(), self.bloom_up_chain.last())
-            {
-                encoder.copy_texture_to_texture(
-                    wgpu::TexelCopyTextureInfo {
-                        texture: last_down.texture(),
-                        mip_level: 0,
-                        origin: wgpu::Origin3d::ZERO,
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    wgpu::TexelCopyTextureInfo {
-                        texture: last_up.texture(),
-                        mip_level: 0,
-                        origin: wgpu::Origin3d::ZERO,
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    last_down.extent(),
-                );
+            BuiltinNode::Composite => {
+                let composite_bind_group = if active.contains(BuiltinNode::Dof) {
+                    self.dof_composite_bind_group
+                        .as_ref()
+                        .expect("DoF composite bind group not initialized")
+                } else if active.contains(BuiltinNode::Taa) {
+                    &self.taa_composite_bind_groups[self.taa.write_index]
+                } else {
+                    self.composite_bind_group
+                        .as_ref()
+                        .expect("Composite bind group not initialized")
+                };
+
+                timer.begin(encoder, GpuPass::Composite);
+                {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("CompositePass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: target,
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    pass.set_pipeline(&self.composite_pipeline);
+                    pass.set_bind_group(0, composite_bind_group, &[]);
+                    pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+                    pass.draw(0..3, 0..1);
+                }
+                timer.end(encoder, GpuPass::Composite);
             }
+        }
+    }
 
-            for pass_info in &self.bloom_upsample_passes {
-                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("BloomUpsample"),
+    /// Leaves the outputs of a skipped node in the state the composite
+    /// expects when the effect is off: no occlusion, no fog and no bloom.
+    fn clear_builtin(&self, builtin: BuiltinNode, encoder: &mut wgpu::CommandEncoder) {
+        match builtin {
+            BuiltinNode::Ssao => {
+                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("SsaoPass"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &self.bloom_up_chain[pass_info.target_index].view,
+                        view: &self.ssao.view,
                         resolve_target: None,
                         depth_slice: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
                             store: wgpu::StoreOp::Store,
                         },
                     })],
@@ -817,20 +1815,21 @@ impl PostProcess {
                     timestamp_writes: None,
                     occlusion_query_set: None,
                 });
-                pass.set_pipeline(&self.bloom_upsample_pipeline);
-                pass.set_bind_group(0, &pass_info.bind_group, &[]);
-                pass.draw(0..3, 0..1);
             }
-        } else {
-            for mip in &self.bloom_up_chain {
+            BuiltinNode::Fog => {
                 let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("BloomDisabledClear"),
+                    label: Some("FogPass"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &mip.view,
+                        view: &self.fog.output.view,
                         resolve_target: None,
                         depth_slice: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            load: wgpu::LoadOp::Clear(wgpu::Color {
+                                r: 0.0,
+                                g: 0.0,
+                                b: 0.0,
+                                a: 1.0,
+                            }),
                             store: wgpu::StoreOp::Store,
                         },
                     })],
@@ -839,22 +1838,56 @@ impl PostProcess {
                     occlusion_query_set: None,
                 });
             }
+            BuiltinNode::Bloom => {
+                for mip in &self.bloom_up_chain {
+                    let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("BloomDisabledClear"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &mip.view,
+                            resolve_target: None,
+                            depth_slice: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                }
+            }
+            _ => {}
         }
+    }
+}
 
-        let composite_bind_group = self
-            .composite_bind_group
-            .as_ref()
-            .expect("Composite bind group not initialized");
-
+impl PostProcess {
+    /// Blurs `ssao` horizontally into `ssao_blur`, then vertically back.
+    fn record_ssao_blur(&self, encoder: &mut wgpu::CommandEncoder) {
+        let passes = [
+            (
+                &self.ssao_blur_horizontal_pipeline,
+                &self.ssao_blur.view,
+                "SsaoBlurHorizontalPass",
+            ),
+            (
+                &self.ssao_blur_vertical_pipeline,
+                &self.ssao.view,
+                "SsaoBlurVerticalPass",
+            ),
+        ];
+        for ((pipeline, target, label), bind_group) in
+            passes.into_iter().zip(&self.ssao_blur_bind_groups)
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("CompositePass"),
+                label: Some(label),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: target,
                     resolve_target: None,
                     depth_slice: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -862,65 +1895,70 @@ impl PostProcess {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-            pass.set_pipeline(&self.composite_pipeline);
-            pass.set_bind_group(0, composite_bind_group, &[]);
-            pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            pass.set_bind_group(1, bind_group, &[]);
             pass.draw(0..3, 0..1);
         }
     }
-}
 
-impl PostProcess {
     fn upload_uniform(&self, queue: &wgpu::Queue) {
-        let proj_inv = self.last_proj.inverse();
         let uniform = PostProcessUniform::new(
-            self.last_proj,
-            proj_inv,
+            self.last_projection,
             self.size.width as f32,
             self.size.height as f32,
-            self.last_near,
-            self.last_far,
+            self.exposure,
             self.effects,
             self.sample_count,
-        );
+        )
+        .with_view(self.last_view);
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+        queue.write_buffer(
+            &self.bloom_params_buffer,
+            0,
+            bytemuck::bytes_of(&self.effects.bloom_settings.params()),
+        );
     }
 
     fn create_bloom_chain(
         device: &wgpu::Device,
         size: &wgpu::Extent3d,
+        mip_count: usize,
     ) -> (Vec<BloomMip>, Vec<BloomMip>) {
-        let mut down_chain = Vec::with_capacity(BLOOM_MIP_COUNT);
-        let mut up_chain = Vec::with_capacity(BLOOM_MIP_COUNT);
-        let mut width = (size.width.max(2) / 2).max(1);
-        let mut height = (size.height.max(2) / 2).max(1);
-
-        for level in 0..BLOOM_MIP_COUNT {
-            let mip_size = wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            };
-            down_chain.push(BloomMip::new(
-                device,
-                mip_size,
-                &format!("BloomDown{level}"),
-            ));
-            up_chain.push(BloomMip::new(device, mip_size, &format!("BloomUp{level}")));
-            width = (width / 2).max(1);
-            height = (height / 2).max(1);
-        }
+        bloom_mip_extents(size, mip_count)
+            .into_iter()
+            .enumerate()
+            .map(|(level, mip_size)| {
+                (
+                    BloomMip::new(device, mip_size, &format!("BloomDown{level}")),
+                    BloomMip::new(device, mip_size, &format!("BloomUp{level}")),
+                )
+            })
+            .unzip()
+    }
 
-        (down_chain, up_chain)
+    /// Recreates the bloom textures for the current size and mip count. The
+    /// passes between them are rebuilt with the other bind groups.
+    fn rebuild_bloom_chain(&mut self, device: &wgpu::Device) {
+        let (down_chain, up_chain) =
+            Self::create_bloom_chain(device, &self.size, self.effects.bloom_settings.mip_count());
+        self.bloom_down_chain = down_chain;
+        self.bloom_up_chain = up_chain;
+        self.mark_bind_groups_dirty();
     }
 
     fn mark_bind_groups_dirty(&mut self) {
         self.depth_resolve_bind_group = None;
         self.ssao_bind_group = None;
+        self.ssao_blur_bind_groups.clear();
         self.bloom_prefilter_bind_group = None;
+        self.emissive_bloom_prefilter_bind_group = None;
         self.bloom_downsample_passes.clear();
         self.bloom_upsample_passes.clear();
         self.composite_bind_group = None;
+        self.taa.input_bind_groups.clear();
+        self.taa_bloom_prefilter_bind_groups.clear();
+        self.taa_composite_bind_groups.clear();
         self.bind_groups_dirty = true;
     }
 
@@ -987,21 +2025,48 @@ impl PostProcess {
             }));
         }
 
-        self.bloom_prefilter_bind_group =
-            Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("BloomPrefilterBindGroup"),
-                layout: &self.bloom_prefilter_layout,
+        let ssao_depth_view = self
+            .resolved_depth
+            .as_ref()
+            .map(|resolved| &resolved.view)
+            .unwrap_or(depth_view);
+        self.ssao_blur_bind_groups = [
+            (&self.ssao.view, "SsaoBlurHorizontalBindGroup"),
+            (&self.ssao_blur.view, "SsaoBlurVerticalBindGroup"),
+        ]
+        .into_iter()
+        .map(|(source, label)| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout: &self.ssao_blur_layout,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&self.scene.view),
+                        resource: wgpu::BindingResource::TextureView(source),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
                         resource: wgpu::BindingResource::Sampler(&self.sampler_linear),
                     },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(ssao_depth_view),
+                    },
                 ],
-            }));
+            })
+        })
+        .collect();
+
+        self.bloom_prefilter_bind_group = Some(self.create_bloom_prefilter_bind_group(
+            device,
+            &self.scene.view,
+            "BloomPrefilterBindGroup",
+        ));
+        self.emissive_bloom_prefilter_bind_group = Some(self.create_bloom_prefilter_bind_group(
+            device,
+            &self.emissive.view,
+            "EmissiveBloomPrefilterBindGroup",
+        ));
 
         self.bloom_downsample_passes = self
             .bloom_down_chain
@@ -1059,13 +2124,103 @@ impl PostProcess {
             });
         }
 
-        self.composite_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("CompositeBindGroup"),
-            layout: &self.composite_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&self.scene.view),
+        self.composite_bind_group =
+            Some(self.create_composite_bind_group(device, &self.scene.view, "CompositeBindGroup"));
+
+        let taa_depth_view = self
+            .resolved_depth
+            .as_ref()
+            .map(|resolved| &resolved.view)
+            .unwrap_or(depth_view);
+        self.taa.input_bind_groups = self.taa.create_input_bind_groups(
+            device,
+            &self.scene.view,
+            taa_depth_view,
+            &self.velocity.view,
+            &self.sampler_linear,
+        );
+        self.taa_bloom_prefilter_bind_groups = self
+            .taa
+            .history
+            .iter()
+            .map(|history| {
+                self.create_bloom_prefilter_bind_group(
+                    device,
+                    &history.view,
+                    "TaaBloomPrefilterBindGroup",
+                )
+            })
+            .collect();
+        self.taa_composite_bind_groups = self
+            .taa
+            .history
+            .iter()
+            .map(|history| {
+                self.create_composite_bind_group(device, &history.view, "TaaCompositeBindGroup")
+            })
+            .collect();
+
+        // Input 0 is the scene, 1 and 2 the TAA history written this frame.
+        let dof_sources = [
+            &self.scene.view,
+            &self.taa.history[0].view,
+            &self.taa.history[1].view,
+        ];
+        self.dof.input_bind_groups = self.dof.create_input_bind_groups(
+            device,
+            &dof_sources,
+            taa_depth_view,
+            &self.sampler_linear,
+        );
+        self.dof.combine_bind_group = Some(
+            self.dof
+                .create_combine_bind_group(device, &self.sampler_linear),
+        );
+        self.dof_composite_bind_group = Some(self.create_composite_bind_group(
+            device,
+            &self.dof.output.view,
+            "DofCompositeBindGroup",
+        ));
+        self.fog.input_bind_group = Some(self.fog.create_input_bind_group(device, taa_depth_view));
+
+        self.bind_groups_dirty = false;
+    }
+
+    fn create_bloom_prefilter_bind_group(
+        &self,
+        device: &wgpu::Device,
+        scene_view: &wgpu::TextureView,
+        label: &str,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout: &self.bloom_prefilter_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(scene_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler_linear),
+                },
+            ],
+        })
+    }
+
+    fn create_composite_bind_group(
+        &self,
+        device: &wgpu::Device,
+        scene_view: &wgpu::TextureView,
+        label: &str,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout: &self.composite_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(scene_view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
@@ -1079,22 +2234,34 @@ impl PostProcess {
                     binding: 3,
                     resource: wgpu::BindingResource::Sampler(&self.sampler_linear),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&self.fog.output.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&self.bloom_dirt_view),
+                },
             ],
-        }));
-
-        self.bind_groups_dirty = false;
+        })
     }
 
-    fn create_noise_texture(device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
-        let data_bytes = bytemuck::cast_slice(&SSAO_NOISE_DATA);
+    fn create_noise_texture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        effects: PostProcessEffects,
+    ) -> wgpu::Texture {
+        let size = effects.ssao_noise_size();
+        let data = generate_ssao_noise(size, effects.ssao_noise_seed);
+        let extent = wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        };
 
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("SsaoNoiseTexture"),
-            size: wgpu::Extent3d {
-                width: NOISE_TEXTURE_SIZE,
-                height: NOISE_TEXTURE_SIZE,
-                depth_or_array_layers: 1,
-            },
+            size: extent,
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
@@ -1110,36 +2277,108 @@ impl PostProcess {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            data_bytes,
+            bytemuck::cast_slice(&data),
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
-                bytes_per_row: Some((4 * std::mem::size_of::<f32>()) as u32 * NOISE_TEXTURE_SIZE),
-                rows_per_image: Some(NOISE_TEXTURE_SIZE),
-            },
-            wgpu::Extent3d {
-                width: NOISE_TEXTURE_SIZE,
-                height: NOISE_TEXTURE_SIZE,
-                depth_or_array_layers: 1,
+                bytes_per_row: Some((4 * std::mem::size_of::<f32>()) as u32 * size),
+                rows_per_image: Some(size),
             },
+            extent,
         );
 
         texture
     }
 
+    fn create_black_texture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+    ) -> wgpu::Texture {
+        device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &[0, 0, 0, 255],
+        )
+    }
+
+    /// Pipeline that resolves the multisampled depth buffer for the depth
+    /// based effects. Not needed (and `None`) without MSAA.
+    fn create_depth_resolve(
+        device: &wgpu::Device,
+        uniform_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+    ) -> (Option<wgpu::BindGroupLayout>, Option<wgpu::RenderPipeline>) {
+        if sample_count <= 1 {
+            return (None, None);
+        }
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("DepthResolveLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: true,
+                },
+                count: None,
+            }],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("DepthResolveShader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../../shader/depth_resolve.wgsl").into(),
+            ),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("DepthResolvePipelineLayout"),
+            bind_group_layouts: &[uniform_layout, &layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = PipelineBuilder::new(device, &pipeline_layout, &shader)
+            .with_label("DepthResolvePipeline")
+            .with_vertex_entry("vs_fullscreen")
+            .with_fragment_entry("fs_resolve_depth")
+            .with_depth_stencil(
+                wgpu::TextureFormat::Depth32Float,
+                true,
+                wgpu::CompareFunction::Always,
+            )
+            .with_no_culling()
+            .build();
+        (Some(layout), Some(pipeline))
+    }
+
     fn create_scene_targets(
         device: &wgpu::Device,
         size: &wgpu::Extent3d,
         format: wgpu::TextureFormat,
         sample_count: u32,
+        label: &str,
     ) -> (TextureBundle, Option<MsaaTarget>) {
-        let resolved = TextureBundle::color(device, size, format, "SceneColor");
+        let resolved = TextureBundle::color(device, size, format, label);
         let msaa = if sample_count > 1 {
             Some(MsaaTarget::new(
                 device,
                 size,
                 format,
                 sample_count,
-                "SceneColorMsaa",
+                &format!("{label}Msaa"),
             ))
         } else {
             None
@@ -1160,46 +2399,125 @@ struct PostProcessUniform {
     intensity_power: [f32; 2],
     noise_scale: [f32; 2],
     near_far: [f32; 2],
-    // Ensure `effects` starts on a 16-byte boundary to match WGSL uniform layout.
-    _effects_padding: [f32; 2],
+    exposure: f32,
+    // 1.0 when depth is linear (orthographic projection). Also keeps
+    // `effects` on a 16-byte boundary to match the WGSL uniform layout.
+    orthographic: f32,
     effects: [f32; 4],
+    // x = kernel size, y = blur radius, z = horizon directions (0 selects
+    // the hemisphere kernel), w = horizon steps per direction.
+    ssao_params: [f32; 4],
+    // x = focus distance, y = aperture, z = max CoC in pixels, w reserved.
+    dof_params: [f32; 4],
+    // rgb = fog color, a = density.
+    fog_color_density: [f32; 4],
+    // x = height falloff, y = fog enabled, zw reserved.
+    fog_params: [f32; 4],
+    // xyz = world up in view space, w = camera height.
+    fog_frame: [f32; 4],
+    // x = bloom intensity, y = dirt mask strength, z = emissive only,
+    // w reserved.
+    bloom_params: [f32; 4],
+    // x = tone mapping operator, yzw reserved.
+    tonemap_params: [f32; 4],
+}
+
+// Mirrors `PostUniform` in postprocess.wgsl and depth_resolve.wgsl.
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, near_far) == 160);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, exposure) == 168);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, orthographic) == 172);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, effects) == 176);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, ssao_params) == 192);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, dof_params) == 208);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, fog_color_density) == 224);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, fog_params) == 240);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, fog_frame) == 256);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, bloom_params) == 272);
+const _: () = assert!(std::mem::offset_of!(PostProcessUniform, tonemap_params) == 288);
+const _: () = assert!(std::mem::size_of::<PostProcessUniform>() == 304);
+
+/// Mirrors `BloomParams` in postprocess.wgsl.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    scatter: f32,
+    _padding: f32,
 }
 
+const _: () = assert!(std::mem::size_of::<BloomParams>() == 16);
+
 impl PostProcessUniform {
-    #[allow(clippy::too_many_arguments)]
     fn new(
-        proj: Mat4,
-        proj_inv: Mat4,
+        projection: ProjectionMatrix,
         width: f32,
         height: f32,
-        near: f32,
-        far: f32,
+        exposure: f32,
         effects: PostProcessEffects,
         sample_count: u32,
     ) -> Self {
-        let radius = 0.2f32;
-        let bias = 0.05f32;
-        let intensity = 0.75f32;
+        let [radius, bias] = effects.ssao_radius_bias();
+        let intensity = effects.ssao_intensity();
         let power = 1.25f32;
-        let noise_scale = [
-            width / NOISE_TEXTURE_SIZE as f32,
-            height / NOISE_TEXTURE_SIZE as f32,
-        ];
+        let noise_size = effects.ssao_noise_size() as f32;
+        let noise_scale = [width / noise_size, height / noise_size];
         let mut effects_arr = effects.uniform_components();
         // Store sample_count in w component so the depth resolve pass can iterate samples.
         effects_arr[3] = sample_count as f32;
+        let (fog_color_density, fog_params) = effects.fog_params();
+        let [directions, steps] = effects.ssao_horizon_params();
         Self {
-            proj: proj.to_cols_array_2d(),
-            proj_inv: proj_inv.to_cols_array_2d(),
+            proj: projection.proj.to_cols_array_2d(),
+            proj_inv: projection.proj.inverse().to_cols_array_2d(),
             resolution: [width, height],
             radius_bias: [radius, bias],
             intensity_power: [intensity, power],
             noise_scale,
-            near_far: [near, far],
-            _effects_padding: [0.0, 0.0],
+            near_far: [projection.near, projection.far],
+            exposure: exposure * effects.exposure_scale(),
+            orthographic: if projection.orthographic { 1.0 } else { 0.0 },
             effects: effects_arr,
+            ssao_params: [
+                effects.ssao_kernel_size() as f32,
+                effects.ssao_blur_radius() as f32,
+                directions,
+                steps,
+            ],
+            dof_params: effects.dof_params(),
+            fog_color_density,
+            fog_params,
+            fog_frame: [0.0, 1.0, 0.0, 0.0],
+            bloom_params: {
+                let mut params = effects.bloom_settings.composite_params();
+                params[2] = if effects.bloom_emissive_only {
+                    1.0
+                } else {
+                    0.0
+                };
+                params
+            },
+            tonemap_params: [effects.tone_mapping.shader_index(), 0.0, 0.0, 0.0],
         }
     }
+
+    /// Fills in the world up axis and camera height the fog march needs.
+    fn with_view(mut self, view: Mat4) -> Self {
+        let up = view.transform_vector3(Vec3::Y).normalize_or(Vec3::Y);
+        let camera_height = view.inverse().w_axis.y;
+        self.fog_frame = [up.x, up.y, up.z, camera_height];
+        self
+    }
+}
+
+/// Built-in nodes recording this frame, indexed by [`BuiltinNode`].
+#[derive(Clone, Copy)]
+struct ActiveBuiltins([bool; BuiltinNode::ALL.len()]);
+
+impl ActiveBuiltins {
+    fn contains(self, builtin: BuiltinNode) -> bool {
+        self.0[builtin as usize]
+    }
 }
 
 struct MsaaTarget {
@@ -1281,10 +2599,10 @@ impl TextureBundle {
         }
     }
 
-    fn ssao(device: &wgpu::Device, size: &wgpu::Extent3d) -> Self {
+    fn ssao(device: &wgpu::Device, size: &wgpu::Extent3d, label: &str) -> Self {
         let format = wgpu::TextureFormat::R8Unorm;
         let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("SsaoTexture"),
+            label: Some(label),
             size: *size,
             mip_level_count: 1,
             sample_count: 1,
@@ -1348,3 +2666,1248 @@ struct BloomUpsamplePass {
     target_index: usize,
     bind_group: wgpu::BindGroup,
 }
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct TaaUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    prev_view_proj: [[f32; 4]; 4],
+    jitter: [f32; 2],
+    blend_factor: f32,
+    history_valid: f32,
+}
+
+// Mirrors `TaaUniform` in postprocess.wgsl.
+const _: () = assert!(std::mem::size_of::<TaaUniform>() == 144);
+
+/// Temporal anti-aliasing resolve. Ping-pongs between two history targets:
+/// each frame reads the previous result and writes the new one, which then
+/// stands in for the scene color in bloom and composite.
+struct TaaPass {
+    history: [TextureBundle; 2],
+    write_index: usize,
+    history_valid: bool,
+    frame_index: u32,
+    blend_factor: f32,
+    view_proj: Mat4,
+    prev_view_proj: Mat4,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    input_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    input_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl TaaPass {
+    fn new(
+        device: &wgpu::Device,
+        size: &wgpu::Extent3d,
+        shader: &wgpu::ShaderModule,
+        fullscreen_vertex: wgpu::VertexState,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let input_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("TaaInputLayout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture_entry(4),
+            ],
+        });
+
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("TaaUniformLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<TaaUniform>() as u64
+                    ),
+                },
+                count: None,
+            }],
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("TaaUniformBuffer"),
+            size: std::mem::size_of::<TaaUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("TaaUniformBindGroup"),
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("TaaPipelineLayout"),
+            bind_group_layouts: &[&input_layout, &uniform_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = PipelineBuilder::new(device, &pipeline_layout, shader)
+            .with_label("TaaPipeline")
+            .with_vertex_entry("vs_fullscreen")
+            .with_fragment_entry("fs_taa")
+            .with_color_target(TAA_HISTORY_FORMAT, Some(wgpu::BlendState::REPLACE))
+            .with_vertex_state(fullscreen_vertex)
+            .with_no_culling()
+            .build();
+
+        Self {
+            history: Self::create_history(device, size),
+            write_index: 0,
+            history_valid: false,
+            frame_index: 0,
+            blend_factor: DEFAULT_TAA_BLEND_FACTOR,
+            view_proj: Mat4::IDENTITY,
+            prev_view_proj: Mat4::IDENTITY,
+            uniform_buffer,
+            uniform_bind_group,
+            input_layout,
+            pipeline,
+            input_bind_groups: Vec::new(),
+        }
+    }
+
+    fn create_history(device: &wgpu::Device, size: &wgpu::Extent3d) -> [TextureBundle; 2] {
+        [
+            TextureBundle::color(device, size, TAA_HISTORY_FORMAT, "TaaHistory0"),
+            TextureBundle::color(device, size, TAA_HISTORY_FORMAT, "TaaHistory1"),
+        ]
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, size: &wgpu::Extent3d) {
+        self.history = Self::create_history(device, size);
+        self.invalidate();
+    }
+
+    /// Drops the accumulated history; the next frame is output unblended.
+    fn invalidate(&mut self) {
+        self.history_valid = false;
+    }
+
+    fn jitter(&self, size: wgpu::Extent3d) -> Vec2 {
+        taa_jitter(self.frame_index, size)
+    }
+
+    /// One bind group per write index, each reading the other history target.
+    fn create_input_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        scene_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        velocity_view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> Vec<wgpu::BindGroup> {
+        (0..self.history.len())
+            .map(|write_index| {
+                let read_index = 1 - write_index;
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&format!("TaaInputBindGroup{write_index}")),
+                    layout: &self.input_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(scene_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(
+                                &self.history[read_index].view,
+                            ),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(depth_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 4,
+                            resource: wgpu::BindingResource::TextureView(velocity_view),
+                        },
+                    ],
+                })
+            })
+            .collect()
+    }
+
+    fn uniform(&self, size: wgpu::Extent3d) -> TaaUniform {
+        let jitter = self.jitter(size);
+        TaaUniform {
+            inv_view_proj: self.view_proj.inverse().to_cols_array_2d(),
+            prev_view_proj: self.prev_view_proj.to_cols_array_2d(),
+            jitter: jitter.to_array(),
+            blend_factor: self.blend_factor,
+            history_valid: if self.history_valid { 1.0 } else { 0.0 },
+        }
+    }
+
+    fn upload_uniform(&self, queue: &wgpu::Queue, size: wgpu::Extent3d) {
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&self.uniform(size)),
+        );
+    }
+
+    fn record(&self, encoder: &mut wgpu::CommandEncoder) {
+        let bind_group = &self.input_bind_groups[self.write_index];
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("TaaPass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.history[self.write_index].view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    /// Makes this frame's output the history for the next frame.
+    fn advance(&mut self) {
+        self.write_index = 1 - self.write_index;
+        self.frame_index = self.frame_index.wrapping_add(1);
+        self.history_valid = true;
+    }
+}
+
+/// Depth of field with a separable hexagonal bokeh. The first pass computes
+/// the circle of confusion and blurs along two hexagon edges into two
+/// targets; the second blurs those along the remaining edges and averages
+/// the resulting rhombi into a hexagon.
+struct DofPass {
+    blur: [TextureBundle; 2],
+    output: TextureBundle,
+    input_layout: wgpu::BindGroupLayout,
+    combine_layout: wgpu::BindGroupLayout,
+    blur_pipeline: wgpu::RenderPipeline,
+    combine_pipeline: wgpu::RenderPipeline,
+    input_bind_groups: Vec<wgpu::BindGroup>,
+    combine_bind_group: Option<wgpu::BindGroup>,
+}
+
+impl DofPass {
+    fn new(
+        device: &wgpu::Device,
+        size: &wgpu::Extent3d,
+        shader: &wgpu::ShaderModule,
+        fullscreen_vertex: wgpu::VertexState,
+        uniform_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let sampler_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+
+        let input_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("DofInputLayout"),
+            entries: &[
+                texture_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                sampler_entry(2),
+            ],
+        });
+
+        let combine_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("DofCombineLayout"),
+            entries: &[texture_entry(0), texture_entry(1), sampler_entry(2)],
+        });
+
+        let blur_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("DofBlurPipelineLayout"),
+            bind_group_layouts: &[&input_layout, uniform_layout],
+            push_constant_ranges: &[],
+        });
+        let blur_pipeline = PipelineBuilder::new(device, &blur_layout, shader)
+            .with_label("DofBlurPipeline")
+            .with_vertex_entry("vs_fullscreen")
+            .with_fragment_entry("fs_dof")
+            .with_color_target(DOF_FORMAT, Some(wgpu::BlendState::REPLACE))
+            .with_color_target(DOF_FORMAT, Some(wgpu::BlendState::REPLACE))
+            .with_vertex_state(fullscreen_vertex.clone())
+            .with_no_culling()
+            .build();
+
+        let combine_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("DofCombinePipelineLayout"),
+                bind_group_layouts: &[&combine_layout, uniform_layout],
+                push_constant_ranges: &[],
+            });
+        let combine_pipeline = PipelineBuilder::new(device, &combine_pipeline_layout, shader)
+            .with_label("DofCombinePipeline")
+            .with_vertex_entry("vs_fullscreen")
+            .with_fragment_entry("fs_dof_combine")
+            .with_color_target(DOF_FORMAT, Some(wgpu::BlendState::REPLACE))
+            .with_vertex_state(fullscreen_vertex)
+            .with_no_culling()
+            .build();
+
+        let (blur, output) = Self::create_targets(device, size);
+        Self {
+            blur,
+            output,
+            input_layout,
+            combine_layout,
+            blur_pipeline,
+            combine_pipeline,
+            input_bind_groups: Vec::new(),
+            combine_bind_group: None,
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        size: &wgpu::Extent3d,
+    ) -> ([TextureBundle; 2], TextureBundle) {
+        (
+            [
+                TextureBundle::color(device, size, DOF_FORMAT, "DofVerticalBlur"),
+                TextureBundle::color(device, size, DOF_FORMAT, "DofDiagonalBlur"),
+            ],
+            TextureBundle::color(device, size, DOF_FORMAT, "DofOutput"),
+        )
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, size: &wgpu::Extent3d) {
+        let (blur, output) = Self::create_targets(device, size);
+        self.blur = blur;
+        self.output = output;
+    }
+
+    fn create_input_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        color_views: &[&wgpu::TextureView],
+        depth_view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> Vec<wgpu::BindGroup> {
+        color_views
+            .iter()
+            .enumerate()
+            .map(|(index, color_view)| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&format!("DofInputBindGroup{index}")),
+                    layout: &self.input_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(color_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(depth_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                    ],
+                })
+            })
+            .collect()
+    }
+
+    fn create_combine_bind_group(
+        &self,
+        device: &wgpu::Device,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("DofCombineBindGroup"),
+            layout: &self.combine_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.blur[0].view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.blur[1].view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn record(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input_index: usize,
+        uniform_bind_group: &wgpu::BindGroup,
+    ) {
+        let color_attachment = |view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("DofBlurPass"),
+                color_attachments: &[
+                    color_attachment(&self.blur[0].view),
+                    color_attachment(&self.blur[1].view),
+                ],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.blur_pipeline);
+            pass.set_bind_group(0, &self.input_bind_groups[input_index], &[]);
+            pass.set_bind_group(1, uniform_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("DofCombinePass"),
+            color_attachments: &[color_attachment(&self.output.view)],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.combine_pipeline);
+        pass.set_bind_group(
+            0,
+            self.combine_bind_group
+                .as_ref()
+                .expect("DoF combine bind group not initialized"),
+            &[],
+        );
+        pass.set_bind_group(1, uniform_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+struct FogPass {
+    output: TextureBundle,
+    input_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    input_bind_group: Option<wgpu::BindGroup>,
+}
+
+impl FogPass {
+    fn new(
+        device: &wgpu::Device,
+        size: &wgpu::Extent3d,
+        shader: &wgpu::ShaderModule,
+        fullscreen_vertex: wgpu::VertexState,
+        uniform_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let input_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("FogInputLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("FogPipelineLayout"),
+            bind_group_layouts: &[&input_layout, uniform_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = PipelineBuilder::new(device, &pipeline_layout, shader)
+            .with_label("FogPipeline")
+            .with_vertex_entry("vs_fullscreen")
+            .with_fragment_entry("fs_volumetric_fog")
+            .with_color_target(FOG_FORMAT, Some(wgpu::BlendState::REPLACE))
+            .with_vertex_state(fullscreen_vertex)
+            .with_no_culling()
+            .build();
+
+        Self {
+            output: TextureBundle::color(device, size, FOG_FORMAT, "FogOutput"),
+            input_layout,
+            pipeline,
+            input_bind_group: None,
+        }
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, size: &wgpu::Extent3d) {
+        self.output = TextureBundle::color(device, size, FOG_FORMAT, "FogOutput");
+    }
+
+    fn create_input_bind_group(
+        &self,
+        device: &wgpu::Device,
+        depth_view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("FogInputBindGroup"),
+            layout: &self.input_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(depth_view),
+            }],
+        })
+    }
+
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, uniform_bind_group: &wgpu::BindGroup) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("FogPass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.output.view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    // No in-scattering and full transmittance.
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 0.0,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(
+            0,
+            self.input_bind_group
+                .as_ref()
+                .expect("Fog input bind group not initialized"),
+            &[],
+        );
+        pass.set_bind_group(1, uniform_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// Stretches the composited image over the surface when the scene renders
+/// at a different resolution.
+struct UpscalePass {
+    // Surface-format target the composite and the late scene passes draw
+    // into; `None` while the scene matches the surface size.
+    target: Option<TextureBundle>,
+    format: wgpu::TextureFormat,
+    input_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    input_bind_group: Option<wgpu::BindGroup>,
+}
+
+impl UpscalePass {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("UpscaleShader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../blit.wgsl").into()),
+        });
+
+        let input_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("UpscaleInputLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("UpscalePipelineLayout"),
+            bind_group_layouts: &[&input_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = PipelineBuilder::new(device, &pipeline_layout, &shader)
+            .with_label("UpscalePipeline")
+            .with_vertex_entry("vs_main")
+            .with_fragment_entry("fs_main")
+            .with_color_target(format, Some(wgpu::BlendState::REPLACE))
+            .with_no_culling()
+            .build();
+
+        Self {
+            target: None,
+            format,
+            input_layout,
+            pipeline,
+            input_bind_group: None,
+        }
+    }
+
+    /// Creates the intermediate target when `size` differs from the
+    /// `width` x `height` surface and drops it otherwise.
+    fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        sampler: &wgpu::Sampler,
+        size: &wgpu::Extent3d,
+        width: u32,
+        height: u32,
+    ) {
+        if size.width == width && size.height == height {
+            self.target = None;
+            self.input_bind_group = None;
+            return;
+        }
+        let target = TextureBundle::color(device, size, self.format, "UpscaleSource");
+        self.input_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("UpscaleInputBindGroup"),
+            layout: &self.input_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&target.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        }));
+        self.target = Some(target);
+    }
+
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let Some(bind_group) = self.input_bind_group.as_ref() else {
+            return;
+        };
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("UpscalePass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Camera;
+
+    #[test]
+    fn uniform_clip_planes_come_from_camera_projection() {
+        let mut camera = Camera::default();
+        camera.set_clip_planes(0.5, 250.0).unwrap();
+        let projection = camera.projection_matrix(16.0 / 9.0);
+
+        let uniform = PostProcessUniform::new(
+            projection,
+            1280.0,
+            720.0,
+            1.0,
+            PostProcessEffects::default(),
+            1,
+        );
+
+        assert_eq!(uniform.near_far, [0.5, 250.0]);
+        assert_eq!(uniform.orthographic, 0.0);
+        assert_eq!(uniform.proj, camera.proj(16.0 / 9.0).to_cols_array_2d());
+    }
+
+    #[test]
+    fn orthographic_camera_sets_linear_depth_flag() {
+        let camera = Camera::orthographic(8.0, 8.0, 1.0, 30.0);
+        let uniform = PostProcessUniform::new(
+            camera.projection_matrix(1.0),
+            640.0,
+            640.0,
+            1.0,
+            PostProcessEffects::default(),
+            1,
+        );
+
+        assert_eq!(uniform.near_far, [1.0, 30.0]);
+        assert_eq!(uniform.orthographic, 1.0);
+    }
+
+    fn uniform_bytes(effects: PostProcessEffects) -> Vec<u8> {
+        let uniform = PostProcessUniform::new(
+            Camera::default().projection_matrix(1.0),
+            800.0,
+            600.0,
+            1.0,
+            effects,
+            1,
+        );
+        bytemuck::bytes_of(&uniform).to_vec()
+    }
+
+    #[test]
+    fn ssao_parameters_change_uniform_bytes() {
+        let base = PostProcessEffects::default();
+        let baseline = uniform_bytes(base);
+
+        let variants = [
+            PostProcessEffects {
+                ssao_radius: 1.5,
+                ..base
+            },
+            PostProcessEffects {
+                ssao_bias: 0.2,
+                ..base
+            },
+            PostProcessEffects {
+                ssao_intensity: 0.3,
+                ..base
+            },
+            PostProcessEffects {
+                ssao_kernel_size: 16,
+                ..base
+            },
+            PostProcessEffects {
+                ssao_blur_radius: 0,
+                ..base
+            },
+            PostProcessEffects {
+                ssao_quality: SsaoQuality::High,
+                ..base
+            },
+        ];
+        for variant in variants {
+            assert_ne!(uniform_bytes(variant), baseline, "{:?}", variant);
+        }
+    }
+
+    #[test]
+    fn emissive_only_bloom_sets_its_uniform_flag() {
+        let base = PostProcessEffects::default();
+        let emissive = PostProcessEffects {
+            bloom_emissive_only: true,
+            ..base
+        };
+        let uniform = PostProcessUniform::new(
+            Camera::default().projection_matrix(1.0),
+            64.0,
+            64.0,
+            1.0,
+            emissive,
+            4,
+        );
+        assert_eq!(uniform.bloom_params[2], 1.0);
+        // The depth resolve pass still finds the sample count in effects.w.
+        assert_eq!(uniform.effects[3], 4.0);
+        assert_ne!(uniform_bytes(emissive), uniform_bytes(base));
+    }
+
+    #[test]
+    fn ssao_quality_presets_raise_the_horizon_sample_count() {
+        let params = |ssao_quality| {
+            PostProcessEffects {
+                ssao_quality,
+                ..PostProcessEffects::default()
+            }
+            .ssao_horizon_params()
+        };
+        // Zero directions keep the shader on the hemisphere kernel.
+        assert_eq!(params(SsaoQuality::Classic), [0.0, 0.0]);
+
+        let samples = |quality| {
+            let [directions, steps] = params(quality);
+            directions * steps
+        };
+        assert!(samples(SsaoQuality::Low) >= 1.0);
+        assert!(samples(SsaoQuality::Low) < samples(SsaoQuality::Medium));
+        assert!(samples(SsaoQuality::Medium) < samples(SsaoQuality::High));
+        for quality in SsaoQuality::ALL {
+            let [directions, steps] = params(quality);
+            assert!(directions <= MAX_SSAO_HORIZON_DIRECTIONS as f32);
+            assert!(steps <= MAX_SSAO_HORIZON_STEPS as f32);
+        }
+    }
+
+    #[test]
+    fn ssao_noise_is_unit_length_and_seeded() {
+        let noise = generate_ssao_noise(8, 42);
+        assert_eq!(noise.len(), 8 * 8 * 4);
+        for texel in noise.chunks_exact(4) {
+            let length = (texel[0] * texel[0] + texel[1] * texel[1]).sqrt();
+            assert!((length - 1.0).abs() < 1e-5, "{:?}", texel);
+            assert_eq!(&texel[2..], &[0.0, 0.0]);
+        }
+
+        assert_eq!(noise, generate_ssao_noise(8, 42));
+        assert_ne!(noise, generate_ssao_noise(8, 43));
+        assert!(!generate_ssao_noise(4, 0).iter().any(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn ssao_noise_angles_cover_the_circle() {
+        let noise = generate_ssao_noise(32, 7);
+        let mut quadrants = [0usize; 4];
+        for texel in noise.chunks_exact(4) {
+            let quadrant = match (texel[0] >= 0.0, texel[1] >= 0.0) {
+                (true, true) => 0,
+                (false, true) => 1,
+                (false, false) => 2,
+                (true, false) => 3,
+            };
+            quadrants[quadrant] += 1;
+        }
+        // 1024 samples; each quadrant expects 256.
+        for count in quadrants {
+            assert!((200..=312).contains(&count), "{:?}", quadrants);
+        }
+    }
+
+    #[test]
+    fn ssao_noise_size_is_a_clamped_power_of_two() {
+        let size = |ssao_noise_size| {
+            PostProcessEffects {
+                ssao_noise_size,
+                ..PostProcessEffects::default()
+            }
+            .ssao_noise_size()
+        };
+        assert_eq!(size(0), MIN_SSAO_NOISE_SIZE);
+        assert_eq!(size(5), 8);
+        assert_eq!(size(16), 16);
+        assert_eq!(size(1000), MAX_SSAO_NOISE_SIZE);
+    }
+
+    #[test]
+    fn ssao_parameters_are_sanitized() {
+        let effects = PostProcessEffects {
+            ssao_radius: f32::NAN,
+            ssao_bias: -1.0,
+            ssao_intensity: 4.0,
+            ssao_kernel_size: 1000,
+            ssao_blur_radius: 100,
+            ..PostProcessEffects::default()
+        };
+        let uniform = PostProcessUniform::new(
+            Camera::default().projection_matrix(1.0),
+            800.0,
+            600.0,
+            1.0,
+            effects,
+            1,
+        );
+
+        assert_eq!(uniform.radius_bias, [0.2, 0.0]);
+        assert_eq!(uniform.intensity_power[0], 1.0);
+        assert_eq!(uniform.ssao_params[0], MAX_SSAO_KERNEL_SIZE as f32);
+        assert_eq!(uniform.ssao_params[1], MAX_SSAO_BLUR_RADIUS as f32);
+    }
+
+    #[test]
+    fn dof_parameters_reach_uniform_and_are_sanitized() {
+        let effects = PostProcessEffects {
+            dof: true,
+            dof_focus_distance: -3.0,
+            dof_aperture: f32::INFINITY,
+            ..PostProcessEffects::default()
+        };
+        let uniform = PostProcessUniform::new(
+            Camera::default().projection_matrix(1.0),
+            800.0,
+            600.0,
+            1.0,
+            effects,
+            1,
+        );
+
+        assert_eq!(uniform.dof_params, [1.0e-3, 0.5, DOF_MAX_COC_PIXELS, 0.0]);
+    }
+
+    #[test]
+    fn fog_parameters_reach_uniform_and_are_sanitized() {
+        let effects = PostProcessEffects {
+            fog: true,
+            fog_density: f32::NAN,
+            fog_color: [0.5, -1.0, 2.0],
+            fog_height_falloff: -4.0,
+            ..PostProcessEffects::default()
+        };
+        let uniform = PostProcessUniform::new(
+            Camera::default().projection_matrix(1.0),
+            800.0,
+            600.0,
+            1.0,
+            effects,
+            1,
+        );
+
+        assert_eq!(uniform.fog_color_density, [0.5, 0.0, 2.0, 0.05]);
+        assert_eq!(uniform.fog_params, [0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn fog_frame_holds_world_up_and_camera_height() {
+        let camera = Camera {
+            eye: Vec3::new(3.0, 4.0, 5.0),
+            target: Vec3::new(0.0, 4.0, 0.0),
+            ..Camera::default()
+        };
+        let uniform = PostProcessUniform::new(
+            camera.projection_matrix(1.0),
+            800.0,
+            600.0,
+            1.0,
+            PostProcessEffects::default(),
+            1,
+        )
+        .with_view(camera.view());
+
+        let [x, y, z, height] = uniform.fog_frame;
+        assert!(Vec3::new(x, y, z).abs_diff_eq(Vec3::Y, 1e-5));
+        assert!((height - 4.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bloom_parameters_are_sanitized() {
+        let settings = BloomSettings {
+            threshold: -2.0,
+            knee: f32::NAN,
+            scatter: 3.0,
+            intensity: f32::INFINITY,
+            mip_count: 0,
+            dirt_strength: -1.0,
+        };
+        let params = settings.params();
+
+        assert_eq!(params.threshold, 0.0);
+        assert_eq!(params.knee, 0.5);
+        assert_eq!(params.scatter, 1.0);
+        assert_eq!(settings.composite_params(), [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(settings.mip_count(), 1);
+        let settings = BloomSettings {
+            mip_count: 100,
+            ..settings
+        };
+        assert_eq!(settings.mip_count(), MAX_BLOOM_MIP_COUNT as usize);
+    }
+
+    #[test]
+    fn bloom_settings_reach_uniform() {
+        let effects = PostProcessEffects {
+            bloom_settings: BloomSettings {
+                intensity: 2.5,
+                dirt_strength: 0.75,
+                ..BloomSettings::default()
+            },
+            ..PostProcessEffects::default()
+        };
+        let uniform = PostProcessUniform::new(
+            Camera::default().projection_matrix(1.0),
+            800.0,
+            600.0,
+            1.0,
+            effects,
+            1,
+        );
+        assert_eq!(uniform.bloom_params, [2.5, 0.75, 0.0, 0.0]);
+
+        let offset = std::mem::offset_of!(PostProcessUniform, bloom_params);
+        let bytes = bytemuck::bytes_of(&uniform);
+        assert_eq!(bytes.len(), std::mem::size_of::<PostProcessUniform>());
+        assert_eq!(&bytes[offset..offset + 4], &2.5f32.to_ne_bytes());
+    }
+
+    #[test]
+    fn tone_mapping_and_exposure_reach_uniform() {
+        let uniform_for = |effects: PostProcessEffects| {
+            PostProcessUniform::new(
+                Camera::default().projection_matrix(1.0),
+                800.0,
+                600.0,
+                0.5,
+                effects,
+                1,
+            )
+        };
+
+        let defaults = uniform_for(PostProcessEffects::default());
+        assert_eq!(defaults.tonemap_params[0], 2.0, "ACES by default");
+        assert_eq!(defaults.exposure, 0.5);
+
+        let indices: Vec<f32> = ToneMapping::ALL
+            .iter()
+            .map(|&tone_mapping| {
+                uniform_for(PostProcessEffects {
+                    tone_mapping,
+                    ..PostProcessEffects::default()
+                })
+                .tonemap_params[0]
+            })
+            .collect();
+        assert_eq!(indices, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        let brighter = uniform_for(PostProcessEffects {
+            exposure_compensation: 2.0,
+            ..PostProcessEffects::default()
+        });
+        assert_eq!(brighter.exposure, 2.0);
+        let clamped = uniform_for(PostProcessEffects {
+            exposure_compensation: -100.0,
+            ..PostProcessEffects::default()
+        });
+        assert_eq!(clamped.exposure, 0.5 / 32.0);
+    }
+
+    #[test]
+    fn scaled_extent_rounds_and_stays_within_limits() {
+        let dims = |e: wgpu::Extent3d| (e.width, e.height);
+        assert_eq!(dims(scaled_extent(1280, 720, 0.5, 8192)), (640, 360));
+        assert_eq!(dims(scaled_extent(1280, 720, 2.0, 8192)), (2560, 1440));
+        assert_eq!(dims(scaled_extent(3, 2, 0.01, 8192)), (1, 1));
+        // 4x on 4K would be 15360 wide; the scale drops to fit the limit.
+        assert_eq!(dims(scaled_extent(3840, 2160, 4.0, 8192)), (8192, 4608));
+    }
+
+    #[test]
+    fn bloom_mip_extents_halve_per_level() {
+        let size = wgpu::Extent3d {
+            width: 1280,
+            height: 720,
+            depth_or_array_layers: 1,
+        };
+        let extents = bloom_mip_extents(&size, 3);
+        let dims: Vec<_> = extents.iter().map(|e| (e.width, e.height)).collect();
+        assert_eq!(dims, vec![(640, 360), (320, 180), (160, 90)]);
+
+        // A longer chain on a tiny target bottoms out at 1x1.
+        let tiny = wgpu::Extent3d {
+            width: 6,
+            height: 3,
+            depth_or_array_layers: 1,
+        };
+        let extents = bloom_mip_extents(&tiny, MAX_BLOOM_MIP_COUNT as usize);
+        assert_eq!(extents.len(), MAX_BLOOM_MIP_COUNT as usize);
+        assert_eq!((extents[0].width, extents[0].height), (3, 1));
+        assert!(extents
+            .iter()
+            .skip(2)
+            .all(|e| e.width == 1 && e.height == 1));
+    }
+
+    #[test]
+    fn wgsl_post_uniform_matches_rust_layout() {
+        let sources = [
+            include_str!("../../shader/postprocess.wgsl"),
+            include_str!("../../shader/depth_resolve.wgsl"),
+        ];
+        for source in sources {
+            let module = naga::front::wgsl::parse_str(source).expect("shader should parse");
+            let (_, ty) = module
+                .types
+                .iter()
+                .find(|(_, ty)| ty.name.as_deref() == Some("PostUniform"))
+                .expect("PostUniform declared");
+            let naga::TypeInner::Struct { members, span } = &ty.inner else {
+                panic!("PostUniform is not a struct");
+            };
+            let offset = |name: &str| {
+                members
+                    .iter()
+                    .find(|member| member.name.as_deref() == Some(name))
+                    .map(|member| member.offset as usize)
+                    .unwrap_or_else(|| panic!("PostUniform.{} missing", name))
+            };
+
+            assert_eq!(*span as usize, std::mem::size_of::<PostProcessUniform>());
+            assert_eq!(
+                offset("near_far"),
+                std::mem::offset_of!(PostProcessUniform, near_far)
+            );
+            assert_eq!(
+                offset("exposure"),
+                std::mem::offset_of!(PostProcessUniform, exposure)
+            );
+            assert_eq!(
+                offset("orthographic"),
+                std::mem::offset_of!(PostProcessUniform, orthographic)
+            );
+            assert_eq!(
+                offset("effects"),
+                std::mem::offset_of!(PostProcessUniform, effects)
+            );
+            assert_eq!(
+                offset("ssao_params"),
+                std::mem::offset_of!(PostProcessUniform, ssao_params)
+            );
+            assert_eq!(
+                offset("dof_params"),
+                std::mem::offset_of!(PostProcessUniform, dof_params)
+            );
+            assert_eq!(
+                offset("fog_frame"),
+                std::mem::offset_of!(PostProcessUniform, fog_frame)
+            );
+            assert_eq!(
+                offset("bloom_params"),
+                std::mem::offset_of!(PostProcessUniform, bloom_params)
+            );
+            assert_eq!(
+                offset("tonemap_params"),
+                std::mem::offset_of!(PostProcessUniform, tonemap_params)
+            );
+        }
+    }
+
+    #[test]
+    fn halton_matches_reference_values() {
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(2, 2), 0.25);
+        assert_eq!(halton(3, 2), 0.75);
+        assert!((halton(1, 3) - 1.0 / 3.0).abs() < 1e-6);
+        assert!((halton(2, 3) - 2.0 / 3.0).abs() < 1e-6);
+        assert!((halton(3, 3) - 1.0 / 9.0).abs() < 1e-6);
+        assert!((halton(4, 3) - 4.0 / 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn taa_jitter_stays_within_half_pixel_and_cycles() {
+        let size = wgpu::Extent3d {
+            width: 1920,
+            height: 1080,
+            depth_or_array_layers: 1,
+        };
+        let half_pixel = Vec2::new(1.0 / 1920.0, 1.0 / 1080.0);
+        for frame in 0..TAA_JITTER_SAMPLES {
+            let jitter = taa_jitter(frame, size);
+            assert!(jitter.x.abs() <= half_pixel.x && jitter.y.abs() <= half_pixel.y);
+            assert_ne!(jitter, Vec2::ZERO);
+            assert_eq!(jitter, taa_jitter(frame + TAA_JITTER_SAMPLES, size));
+        }
+        assert_ne!(taa_jitter(0, size), taa_jitter(1, size));
+    }
+
+    #[test]
+    fn wgsl_taa_uniform_matches_rust_layout() {
+        let module = naga::front::wgsl::parse_str(include_str!("../../shader/postprocess.wgsl"))
+            .expect("shader should parse");
+        let (_, ty) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("TaaUniform"))
+            .expect("TaaUniform declared");
+        let naga::TypeInner::Struct { members, span } = &ty.inner else {
+            panic!("TaaUniform is not a struct");
+        };
+
+        assert_eq!(*span as usize, std::mem::size_of::<TaaUniform>());
+        let offsets: Vec<_> = members
+            .iter()
+            .map(|member| member.offset as usize)
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                std::mem::offset_of!(TaaUniform, inv_view_proj),
+                std::mem::offset_of!(TaaUniform, prev_view_proj),
+                std::mem::offset_of!(TaaUniform, jitter),
+                std::mem::offset_of!(TaaUniform, blend_factor),
+                std::mem::offset_of!(TaaUniform, history_valid),
+            ]
+        );
+    }
+
+    #[test]
+    fn uniform_effects_start_on_16_byte_boundary() {
+        assert_eq!(std::mem::offset_of!(PostProcessUniform, effects) % 16, 0);
+        assert_eq!(std::mem::size_of::<PostProcessUniform>() % 16, 0);
+    }
+}
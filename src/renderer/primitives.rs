@@ -1,7 +1,12 @@
 use super::vertex::{v, Vertex};
+use crate::asset::Mesh;
 use std::f32::consts::PI;
 
+/// Unit UV sphere with `segments` columns around the Y axis and `rings`
+/// rows from pole to pole.
 pub fn sphere_mesh(segments: u32, rings: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
 
@@ -10,6 +15,7 @@ pub fn sphere_mesh(segments: u32, rings: u32) -> (Vec<Vertex>, Vec<u32>) {
         let phi = PI * ring as f32 / rings as f32;
         let y = phi.cos();
         let ring_radius = phi.sin();
+        let is_pole = ring == 0 || ring == rings;
 
         for segment in 0..=segments {
             let theta = 2.0 * PI * segment as f32 / segments as f32;
@@ -19,8 +25,13 @@ pub fn sphere_mesh(segments: u32, rings: u32) -> (Vec<Vertex>, Vec<u32>) {
             let pos = [x, y, z];
             let normal = [x, y, z]; // For unit sphere, position = normal
 
-            // UV coordinates
-            let u = segment as f32 / segments as f32;
+            // UV coordinates. Each pole vertex only serves the triangle of
+            // its own column, so it sits in the middle of that column.
+            let u = if is_pole {
+                (segment as f32 + 0.5) / segments as f32
+            } else {
+                segment as f32 / segments as f32
+            };
             let tex_v = ring as f32 / rings as f32;
 
             // Tangent points in the direction of increasing theta (around the sphere)
@@ -36,20 +47,214 @@ pub fn sphere_mesh(segments: u32, rings: u32) -> (Vec<Vertex>, Vec<u32>) {
             let current = ring * (segments + 1) + segment;
             let next = current + segments + 1;
 
-            // Two triangles per quad — reversed winding (swap the last two of each tri)
-            indices.push(current);
-            indices.push(current + 1);
-            indices.push(next);
+            // Two triangles per quad — reversed winding (swap the last two of each tri).
+            // The quads touching a pole collapse to one triangle.
+            if ring != 0 {
+                indices.push(current);
+                indices.push(current + 1);
+                indices.push(next);
+            }
 
-            indices.push(current + 1);
-            indices.push(next + 1);
-            indices.push(next);
+            if ring != rings - 1 {
+                indices.push(current + 1);
+                indices.push(next + 1);
+                indices.push(next);
+            }
         }
     }
 
     (vertices, indices)
 }
 
+/// Uploads a UV sphere of the given radius, `rings` rows from pole to pole
+/// and `sectors` columns around the Y axis.
+pub fn create_sphere(device: &wgpu::Device, radius: f32, rings: u32, sectors: u32) -> Mesh {
+    let (mut vertices, indices) = sphere_mesh(sectors, rings);
+    for vertex in &mut vertices {
+        vertex.pos = vertex.pos.map(|c| c * radius);
+    }
+    Mesh::from_vertices(device, &vertices, &indices)
+}
+
+/// Cylinder along the Y axis, centred on the origin, with `segments` sides.
+/// A zero `radius_top` or `radius_bottom` gives a cone without that cap.
+/// The sides have smooth normals and wrap the texture once around; the caps
+/// have flat normals and a planar projection.
+pub fn cylinder_mesh(
+    radius_top: f32,
+    radius_bottom: f32,
+    height: f32,
+    segments: u32,
+) -> (Vec<Vertex>, Vec<u32>) {
+    let segments = segments.max(3);
+    let half_height = height * 0.5;
+    // Outward normals tilt up by the slope of the side.
+    let slope = if height != 0.0 {
+        (radius_bottom - radius_top) / height
+    } else {
+        0.0
+    };
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    // Sides: a top and a bottom vertex per column.
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let theta = 2.0 * PI * u;
+        let (sin, cos) = theta.sin_cos();
+        let normal = glam::Vec3::new(cos, slope, sin).normalize().to_array();
+        let tangent = [-sin, 0.0, cos, 1.0];
+
+        vertices.push(v(
+            [radius_top * cos, half_height, radius_top * sin],
+            normal,
+            [u, 0.0],
+            tangent,
+        ));
+        vertices.push(v(
+            [radius_bottom * cos, -half_height, radius_bottom * sin],
+            normal,
+            [u, 1.0],
+            tangent,
+        ));
+    }
+    for segment in 0..segments {
+        let top = segment * 2;
+        let bottom = top + 1;
+        let next_top = top + 2;
+        let next_bottom = top + 3;
+        // A side that narrows to a point collapses to one triangle.
+        if radius_top > 0.0 {
+            indices.extend_from_slice(&[top, next_top, bottom]);
+        }
+        if radius_bottom > 0.0 {
+            indices.extend_from_slice(&[next_top, next_bottom, bottom]);
+        }
+    }
+
+    // Caps: a centre vertex and their own ring, for the hard edge.
+    for (radius, y, normal_y) in [
+        (radius_top, half_height, 1.0),
+        (radius_bottom, -half_height, -1.0),
+    ] {
+        if radius <= 0.0 {
+            continue;
+        }
+        let centre = vertices.len() as u32;
+        let normal = [0.0, normal_y, 0.0];
+        let tangent = [1.0, 0.0, 0.0, 1.0];
+        vertices.push(v([0.0, y, 0.0], normal, [0.5, 0.5], tangent));
+        for segment in 0..=segments {
+            let theta = 2.0 * PI * segment as f32 / segments as f32;
+            let (sin, cos) = theta.sin_cos();
+            let uv = [0.5 + cos * 0.5, 0.5 + sin * 0.5 * normal_y];
+            vertices.push(v([radius * cos, y, radius * sin], normal, uv, tangent));
+        }
+        for segment in 0..segments {
+            let current = centre + 1 + segment;
+            if normal_y > 0.0 {
+                indices.extend_from_slice(&[centre, current + 1, current]);
+            } else {
+                indices.extend_from_slice(&[centre, current, current + 1]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Uploads a [`cylinder_mesh`]; a zero `radius_top` makes it a cone.
+pub fn create_cylinder(
+    device: &wgpu::Device,
+    radius_top: f32,
+    radius_bottom: f32,
+    height: f32,
+    segments: u32,
+) -> Mesh {
+    let (vertices, indices) = cylinder_mesh(radius_top, radius_bottom, height, segments);
+    Mesh::from_vertices(device, &vertices, &indices)
+}
+
+/// Ground plane in the XZ plane, centred on the origin and facing +Y, split
+/// into `subdivisions_x` by `subdivisions_z` cells. UVs run from 0 to 1
+/// across the whole plane.
+pub fn plane_mesh(
+    width: f32,
+    depth: f32,
+    subdivisions_x: u32,
+    subdivisions_z: u32,
+) -> (Vec<Vertex>, Vec<u32>) {
+    let cells_x = subdivisions_x.max(1);
+    let cells_z = subdivisions_z.max(1);
+    let mut vertices = Vec::with_capacity(((cells_x + 1) * (cells_z + 1)) as usize);
+    let mut indices = Vec::with_capacity((cells_x * cells_z * 6) as usize);
+
+    for row in 0..=cells_z {
+        let tex_v = row as f32 / cells_z as f32;
+        for column in 0..=cells_x {
+            let u = column as f32 / cells_x as f32;
+            vertices.push(v(
+                [(u - 0.5) * width, 0.0, (tex_v - 0.5) * depth],
+                [0.0, 1.0, 0.0],
+                [u, tex_v],
+                [1.0, 0.0, 0.0, 1.0],
+            ));
+        }
+    }
+
+    for row in 0..cells_z {
+        for column in 0..cells_x {
+            let current = row * (cells_x + 1) + column;
+            let next = current + cells_x + 1;
+            indices.extend_from_slice(&[current, next, current + 1]);
+            indices.extend_from_slice(&[current + 1, next, next + 1]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Uploads a [`plane_mesh`].
+pub fn create_plane(
+    device: &wgpu::Device,
+    width: f32,
+    depth: f32,
+    subdivisions_x: u32,
+    subdivisions_z: u32,
+) -> Mesh {
+    let (vertices, indices) = plane_mesh(width, depth, subdivisions_x, subdivisions_z);
+    Mesh::from_vertices(device, &vertices, &indices)
+}
+
+/// Editor-style grid in the XZ plane: `count` cells of a `size` square, so
+/// `count + 1` lines along each axis. The indices form a line list, not
+/// triangles.
+pub fn grid_lines_mesh(size: f32, count: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let count = count.max(1);
+    let half = size * 0.5;
+    let mut vertices = Vec::with_capacity((count as usize + 1) * 4);
+    for line in 0..=count {
+        let offset = line as f32 / count as f32 * size - half;
+        for (start, end) in [
+            ([offset, 0.0, -half], [offset, 0.0, half]),
+            ([-half, 0.0, offset], [half, 0.0, offset]),
+        ] {
+            vertices.push(v(start, [0.0, 1.0, 0.0], [0.0, 0.0], [1.0, 0.0, 0.0, 1.0]));
+            vertices.push(v(end, [0.0, 1.0, 0.0], [1.0, 1.0], [1.0, 0.0, 0.0, 1.0]));
+        }
+    }
+    let indices = (0..vertices.len() as u32).collect();
+    (vertices, indices)
+}
+
+/// Uploads a [`grid_lines_mesh`]. The scene pipelines draw triangle lists,
+/// so draw it from a custom pipeline with
+/// [`PrimitiveTopology::LineList`](wgpu::PrimitiveTopology::LineList).
+pub fn create_grid_lines(device: &wgpu::Device, size: f32, count: u32) -> Mesh {
+    let (vertices, indices) = grid_lines_mesh(size, count);
+    Mesh::from_vertices(device, &vertices, &indices)
+}
+
 pub fn quad_mesh() -> (Vec<Vertex>, Vec<u32>) {
     let verts = vec![
         v(
@@ -262,4 +467,75 @@ mod tests {
         assert_eq!(v.len(), 24);
         assert_eq!(i.len(), 36);
     }
+
+    #[test]
+    fn sphere_has_no_degenerate_pole_triangles() {
+        let (segments, rings) = (16, 8);
+        let (v, i) = sphere_mesh(segments, rings);
+        assert_eq!(v.len() as u32, (segments + 1) * (rings + 1));
+        // One triangle per pole quad, two for every other quad.
+        assert_eq!(i.len() as u32, 3 * segments * (2 * rings - 2));
+
+        for tri in i.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| glam::Vec3::from(v[tri[k] as usize].pos));
+            assert!((b - a).cross(c - a).length() > 1e-6, "degenerate {:?}", tri);
+        }
+    }
+
+    fn triangle_normals(v: &[Vertex], i: &[u32]) -> Vec<glam::Vec3> {
+        i.chunks_exact(3)
+            .map(|tri| {
+                let [a, b, c] = [0, 1, 2].map(|k| glam::Vec3::from(v[tri[k] as usize].pos));
+                (b - a).cross(c - a)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cylinder_triangle_counts() {
+        let segments = 12;
+        // Two triangles per side, one per cap slice.
+        let (v, i) = cylinder_mesh(1.0, 1.0, 2.0, segments);
+        assert_eq!(i.len() as u32 / 3, 4 * segments);
+        assert_eq!(v.len() as u32, 2 * (segments + 1) + 2 * (segments + 2));
+
+        // A cone loses its top cap and half of every side quad.
+        let (v, i) = cylinder_mesh(0.0, 1.0, 2.0, segments);
+        assert_eq!(i.len() as u32 / 3, 2 * segments);
+        for (normal, tri) in triangle_normals(&v, &i).iter().zip(i.chunks_exact(3)) {
+            assert!(normal.length() > 1e-6, "degenerate {:?}", tri);
+        }
+    }
+
+    #[test]
+    fn cylinder_faces_point_outwards() {
+        let (v, i) = cylinder_mesh(0.5, 1.0, 2.0, 16);
+        for (normal, tri) in triangle_normals(&v, &i).iter().zip(i.chunks_exact(3)) {
+            let vertex_normal = glam::Vec3::from(v[tri[0] as usize].normal);
+            assert!(normal.dot(vertex_normal) > 0.0, "inward {:?}", tri);
+        }
+    }
+
+    #[test]
+    fn plane_is_a_regular_upward_grid() {
+        let (v, i) = plane_mesh(4.0, 2.0, 4, 2);
+        assert_eq!(v.len(), 5 * 3);
+        assert_eq!(i.len(), 4 * 2 * 6);
+        assert_eq!(v[0].pos, [-2.0, 0.0, -1.0]);
+        assert_eq!(v[14].pos, [2.0, 0.0, 1.0]);
+        assert_eq!((v[0].uv, v[14].uv), ([0.0, 0.0], [1.0, 1.0]));
+        for normal in triangle_normals(&v, &i) {
+            assert!(normal.normalize().abs_diff_eq(glam::Vec3::Y, 1e-6));
+        }
+    }
+
+    #[test]
+    fn grid_lines_cover_both_axes() {
+        let (v, i) = grid_lines_mesh(10.0, 5);
+        // Six lines along each axis, two indices per line.
+        assert_eq!(i.len(), 2 * 6 * 2);
+        assert!(v
+            .iter()
+            .all(|vertex| vertex.pos[0].abs() <= 5.0 && vertex.pos[2].abs() <= 5.0));
+    }
 }
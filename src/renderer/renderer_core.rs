@@ -1,35 +1,114 @@
 // renderer/renderer.rs
-use crate::asset::{Assets, Mesh};
-use crate::environment::Environment;
+use crate::asset::{Assets, Handle, Mesh, MorphTargetRange};
+use crate::environment::{Environment, EnvironmentMap};
 use crate::renderer::batch::InstanceData;
+use crate::renderer::internal::environment::{decode_hdr_image, load_hdr_image};
+use crate::renderer::internal::readback::{
+    FrameCapture, PixelReadback, Screenshot, ScreenshotQueue,
+};
 use crate::renderer::internal::{
-    CameraBuffer, DynamicObjectsBuffer, EnvironmentResources, LightsBuffer, OrderedBatch,
-    PipelineKey, PreparedBatches, RenderContext, RenderPipeline, ShadowResources,
-    TextureBindingModel,
+    CameraBuffer, DebugLineResources, DynamicObjectsBuffer, EnvironmentResources, InstancedDraws,
+    LightsBuffer, OrderedBatch, PipelineKey, PreparedBatches, RenderContext, RenderPipeline,
+    ShaderSources, ShadowResources, StencilMode, TextureBindingModel, TransmissionResources,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::renderer::internal::{SceneShader, ShaderWatcher};
 use crate::renderer::{
-    lights::{MAX_DIRECTIONAL_LIGHTS, MAX_POINT_LIGHTS, MAX_SPOT_LIGHTS},
-    postprocess::{PostProcess, PostProcessEffects},
-    CameraUniform, LightsData, Material, RenderBatcher, RenderPass, Vertex,
+    frame_pass::{FrameBatches, FramePass, FramePassContext, FramePasses, Placement},
+    lights::MAX_SHADOW_CASCADES,
+    mipmap::MipmapCompute,
+    postprocess::{BloomSettings, PostProcess, PostProcessEffects, RenderGraph, ToneMapping},
+    timing::{GpuPass, GpuPassTimings, GpuTimer},
+    CameraUniform, DebugDraw, LightsData, ManualDrawList, Material, MorphDelta, RenderBatcher,
+    RenderPass, SkinVertex, Texture, Vertex,
 };
 use crate::scene::Camera;
-use crate::settings::RenderSettings;
+use crate::settings::{RenderSettings, ShadowSettings};
 
 use glam::Vec3;
-#[cfg(target_arch = "wasm32")]
+use std::future::Future;
+use std::path::Path;
 use std::rc::Rc;
 #[cfg(not(target_arch = "wasm32"))]
 use std::sync::Arc;
 use winit::{dpi::PhysicalSize, window::Window};
 
 const INITIAL_OBJECTS_CAPACITY: u32 = 1024 * 100;
-const POINT_SHADOW_FACE_COUNT: u32 = 6;
 
 #[cfg(feature = "egui")]
 type UiHook =
     Box<dyn FnOnce(&wgpu::Device, &wgpu::Queue, &mut wgpu::CommandEncoder, &wgpu::TextureView)>;
 pub struct RenderFrame {
-    pub frame: wgpu::SurfaceTexture,
+    target: FrameTarget,
+}
+
+/// Attachments the custom passes at one point of the frame draw into.
+struct PassTarget<'a> {
+    color: &'a wgpu::TextureView,
+    resolve: Option<&'a wgpu::TextureView>,
+    depth: Option<&'a wgpu::TextureView>,
+    sample_count: u32,
+}
+
+enum FrameTarget {
+    Surface(wgpu::SurfaceTexture),
+    Offscreen(wgpu::Texture),
+}
+
+impl RenderFrame {
+    /// Texture the frame was rendered into: the swapchain image, or the
+    /// internal color target of a headless renderer.
+    pub fn texture(&self) -> &wgpu::Texture {
+        match &self.target {
+            FrameTarget::Surface(frame) => &frame.texture,
+            FrameTarget::Offscreen(texture) => texture,
+        }
+    }
+
+    /// Presents the frame to the window. Headless frames have nothing to
+    /// present; use [`Renderer::read_back_frame`] to inspect them instead.
+    pub fn present(self) {
+        if let FrameTarget::Surface(frame) = self.target {
+            frame.present();
+        }
+    }
+}
+
+/// How the main pass shades scene geometry. Every mode except `Lit` is a
+/// debug view that skips lighting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    #[default]
+    Lit,
+    /// Draws triangle edges only. Uses `wgpu::Features::POLYGON_MODE_LINE`
+    /// when available and a barycentric shader wireframe otherwise.
+    Wireframe,
+    /// Interpolated world-space normals mapped to RGB.
+    Normals,
+    /// Texture coordinates as red and green, wrapped to `0..1`.
+    Uvs,
+    /// Additive heat map of how many fragments each pixel shades.
+    Overdraw,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 5] = [
+        RenderMode::Lit,
+        RenderMode::Wireframe,
+        RenderMode::Normals,
+        RenderMode::Uvs,
+        RenderMode::Overdraw,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RenderMode::Lit => "Lit",
+            RenderMode::Wireframe => "Wireframe",
+            RenderMode::Normals => "Normals",
+            RenderMode::Uvs => "UVs",
+            RenderMode::Overdraw => "Overdraw",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -41,6 +120,39 @@ pub struct RendererStats {
     pub transparent_draw_calls: u32,
     pub overlay_draw_calls: u32,
     pub shadow_draw_calls: u32,
+    /// Shadow map layers rendered: directional cascades, spot lights and six
+    /// faces per point light.
+    pub shadow_views: u32,
+    /// Depth prepass and color pass draws that covered more than one
+    /// instance, issued indirectly when the device allows it.
+    pub instance_draw_calls: u32,
+    /// Entities and manual draws skipped because their bounds were outside
+    /// the view frustum.
+    pub culled_objects: u32,
+    /// Instances drawn from the renderer's [`ManualDrawList`].
+    pub manual_objects: u32,
+    /// Transparent and overlay instances depth-sorted this frame.
+    pub sorted_instances: u32,
+    /// Depth sorts performed while preparing batches.
+    pub sort_operations: u32,
+    /// CPU instances whose object data was uploaded this frame.
+    pub updated_instances: u32,
+    /// CPU instances whose slot already held this frame's data.
+    pub reused_instances: u32,
+    /// Bytes of instance data written to the objects buffer this frame.
+    pub instance_bytes_written: u64,
+    /// Bytes written to the object, material, joint and morph weight
+    /// buffers this frame. Unchanged instances and materials are skipped.
+    pub bytes_uploaded: u64,
+    /// Main pass pipeline variants compiled since the previous frame: for
+    /// this frame's batches, from the warm-up queue or by
+    /// [`Renderer::warm_up`].
+    pub pipelines_compiled: u32,
+    /// Main pass pipeline variants built so far.
+    pub pipeline_variants: u32,
+    /// Per-pass GPU time of a recent frame. `None` when the device lacks
+    /// timestamp queries or no results have been read back yet.
+    pub gpu_timings: Option<GpuPassTimings>,
 }
 
 impl RendererStats {
@@ -55,20 +167,35 @@ impl RendererStats {
 
 pub struct Renderer {
     texture_binder: TextureBindingModel,
+    /// Texture cache revision the binder last mirrored.
+    texture_revision: Option<u64>,
     objects_buffer: DynamicObjectsBuffer,
     camera_buffer: CameraBuffer,
     lights_buffer: LightsBuffer,
     environment: EnvironmentResources,
     shadows: ShadowResources,
+    transmission: TransmissionResources,
+    debug_lines: DebugLineResources,
+    instanced_draws: InstancedDraws,
+    mipmaps: Rc<MipmapCompute>,
+    manual_draws: ManualDrawList,
+    frame_capture: Option<FrameCapture>,
+    screenshots: ScreenshotQueue,
     postprocess: PostProcess,
+    gpu_timer: GpuTimer,
     camera_position: Vec3,
     camera_target: Vec3,
     camera_up: Vec3,
     settings: RenderSettings,
+    render_mode: RenderMode,
     #[cfg(feature = "egui")]
     ui_hook: Option<UiHook>,
+    frame_passes: FramePasses,
     stats: RendererStats,
     pipeline: RenderPipeline,
+    shader_sources: ShaderSources,
+    #[cfg(not(target_arch = "wasm32"))]
+    shader_watcher: Option<ShaderWatcher>,
     context: RenderContext,
 }
 
@@ -77,7 +204,11 @@ impl Renderer {
     pub async fn new(window: Arc<Window>, settings: RenderSettings) -> Self {
         let size = window.inner_size();
         let context = RenderContext::new(window, size, &settings).await;
-        Self::from_context(context, settings)
+        let mut renderer = Self::from_context(context, settings);
+        if cfg!(debug_assertions) {
+            renderer.shader_watcher = ShaderWatcher::new();
+        }
+        renderer
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -87,46 +218,106 @@ impl Renderer {
         Self::from_context(context, settings)
     }
 
+    /// Creates a renderer without a window that draws into an internal
+    /// `Rgba8UnormSrgb` target of the given size. Returns `None` when no
+    /// adapter is available.
+    pub async fn new_headless(width: u32, height: u32, settings: RenderSettings) -> Option<Self> {
+        let size = PhysicalSize::new(width.max(1), height.max(1));
+        let context = RenderContext::new_headless(size, &settings).await?;
+        Some(Self::from_context(context, settings))
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.context.surface.is_none()
+    }
+
     fn from_context(context: RenderContext, mut settings: RenderSettings) -> Self {
         let sample_count = context.sample_count;
+        let shader_sources = ShaderSources::default();
         settings.sample_count = sample_count;
+        settings.resolution_scale = context.resolution_scale;
+        settings.shadows = settings.shadows.validate();
+        settings.cascade_count = settings.cascade_count.clamp(1, MAX_SHADOW_CASCADES);
         let camera_buffer = CameraBuffer::new(&context.device);
         let environment = EnvironmentResources::new(&context.device, &context.queue);
         let objects_buffer = DynamicObjectsBuffer::new(&context.device, INITIAL_OBJECTS_CAPACITY);
-        let shadows =
-            ShadowResources::new(&context.device, &objects_buffer, settings.shadow_map_size);
-        let lights_buffer = LightsBuffer::new(&context.device, &shadows, &environment);
-        let (pipeline, texture_binder) = RenderPipeline::new(
+        let texture_binder = TextureBindingModel::new(&context);
+        let shadows = ShadowResources::new(
+            &context.device,
+            &objects_buffer,
+            texture_binder.bind_layout(),
+            context
+                .supports_bindless_textures
+                .then_some(context.bindless_texture_capacity),
+            &settings.shadows,
+            settings.cascade_count,
+        );
+        let render_size = context.render_size();
+        let transmission = TransmissionResources::new(
+            &context.device,
+            render_size.width,
+            render_size.height,
+            context.config.format,
+        );
+        let lights_buffer =
+            LightsBuffer::new(&context.device, &shadows, &environment, &transmission);
+        let pipeline = RenderPipeline::build(
             &context,
             &camera_buffer,
             &objects_buffer,
             &lights_buffer,
+            texture_binder.bind_layout(),
             sample_count,
+            &shader_sources,
         );
+        let debug_lines = DebugLineResources::new(&context, &camera_buffer, sample_count);
         let mut postprocess = PostProcess::new(
             &context.device,
             &context.queue,
             &context.config,
             sample_count,
+            context.resolution_scale,
         );
         postprocess.set_depth_view(&context.depth.sampled_view);
+        let effects = PostProcessEffects {
+            ssao_quality: settings.ssao_quality,
+            ..postprocess.effects()
+        };
+        postprocess.set_effects(&context.device, &context.queue, effects);
+        let gpu_timer = GpuTimer::new(&context);
+        let instanced_draws = InstancedDraws::new(&context);
+        let mipmaps = MipmapCompute::for_device(&context.device);
 
         Self {
             context,
             pipeline,
+            shader_sources,
+            #[cfg(not(target_arch = "wasm32"))]
+            shader_watcher: None,
             texture_binder,
+            texture_revision: None,
             objects_buffer,
             camera_buffer,
             lights_buffer,
             environment,
             shadows,
+            transmission,
+            debug_lines,
+            instanced_draws,
+            mipmaps,
+            manual_draws: ManualDrawList::new(),
+            frame_capture: None,
+            screenshots: ScreenshotQueue::default(),
             postprocess,
+            gpu_timer,
             camera_position: Vec3::ZERO,
             camera_target: Vec3::ZERO,
             camera_up: Vec3::Y,
             settings,
+            render_mode: RenderMode::Lit,
             #[cfg(feature = "egui")]
             ui_hook: None,
+            frame_passes: FramePasses::default(),
             stats: RendererStats::default(),
         }
     }
@@ -145,6 +336,20 @@ impl Renderer {
         &self.context.queue
     }
 
+    /// The compute pipeline that fills texture mip chains on this device.
+    pub fn mipmaps(&self) -> &MipmapCompute {
+        &self.mipmaps
+    }
+
+    /// Objects drawn every frame alongside the scene's entities.
+    pub fn manual_draws(&self) -> &ManualDrawList {
+        &self.manual_draws
+    }
+
+    pub fn manual_draws_mut(&mut self) -> &mut ManualDrawList {
+        &mut self.manual_draws
+    }
+
     pub fn reserve_object_capacity(&mut self, count: u32) {
         self.objects_buffer.ensure_capacity(&self.context, count);
     }
@@ -169,6 +374,14 @@ impl Renderer {
         &self.lights_buffer.bind_group
     }
 
+    /// Length of the bindless texture array, `None` when textures are bound
+    /// per material.
+    pub(crate) fn bindless_texture_capacity(&self) -> Option<u32> {
+        self.context
+            .supports_bindless_textures
+            .then_some(self.context.bindless_texture_capacity)
+    }
+
     pub fn textures_bind_layout(&self) -> &wgpu::BindGroupLayout {
         self.texture_binder.bind_layout()
     }
@@ -183,6 +396,10 @@ impl Renderer {
         &self.context.depth.view
     }
 
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        self.context.depth.format
+    }
+
     pub fn settings(&self) -> &RenderSettings {
         &self.settings
     }
@@ -198,6 +415,35 @@ impl Renderer {
         );
         self.postprocess
             .set_depth_view(&self.context.depth.sampled_view);
+        let render_size = self.context.render_size();
+        self.transmission
+            .resize(&self.context.device, render_size.width, render_size.height);
+        self.lights_buffer.rebuild_bind_group(
+            &self.context.device,
+            &self.shadows,
+            &self.environment,
+            &self.transmission,
+        );
+    }
+
+    /// Renders the scene at `scale` times the surface size, upscaling or
+    /// supersampling into the surface; see
+    /// [`RenderSettings::resolution_scale`].
+    pub fn set_resolution_scale(&mut self, scale: f32) {
+        let scale = RenderSettings::sanitize_resolution_scale(scale);
+        if scale == self.context.resolution_scale {
+            return;
+        }
+        self.settings.resolution_scale = scale;
+        self.context.resolution_scale = scale;
+        self.postprocess.set_resolution_scale(scale);
+        // Recreates the depth buffer and every scene target at the new size.
+        self.resize(self.context.size);
+    }
+
+    /// Size of the scene color and depth targets.
+    pub fn render_size(&self) -> PhysicalSize<u32> {
+        self.context.render_size()
     }
 
     pub fn aspect_ratio(&self) -> f32 {
@@ -208,15 +454,22 @@ impl Renderer {
         self.camera_position = camera.position(); // Store it
         self.camera_target = camera.target;
         self.camera_up = camera.up;
-        let vp = camera.view_proj(aspect);
+        let projection = camera.projection_matrix(aspect);
+        let view = camera.view();
+        // Geometry is rasterized with the TAA jitter; post-process reprojection
+        // needs the stable matrix.
+        let vp = self.postprocess.jitter_projection(projection.proj) * view;
         let inv_vp = vp.inverse();
-        let uni = CameraUniform::from_matrices(vp, inv_vp, camera.position());
+        self.postprocess
+            .update_camera(&self.context.queue, projection, view);
+        self.postprocess
+            .update_view_proj(&self.context.queue, projection.proj * view);
+        let (unjittered_vp, prev_vp) = self.postprocess.motion_view_proj();
+        let uni = CameraUniform::from_matrices(vp, inv_vp, camera.position())
+            .with_motion(unjittered_vp, prev_vp);
         self.context
             .queue
             .write_buffer(&self.camera_buffer.buffer, 0, bytemuck::bytes_of(&uni));
-        let proj = camera.proj(aspect);
-        self.postprocess
-            .update_camera(&self.context.queue, proj, camera.near, camera.far);
     }
 
     pub fn camera_position(&self) -> Vec3 {
@@ -232,15 +485,81 @@ impl Renderer {
     }
 
     pub fn set_lights(&mut self, lights: &LightsData) {
-        self.lights_buffer.update(&self.context.queue, lights);
+        self.lights_buffer.update(
+            &self.context.queue,
+            lights,
+            self.shadows.settings(),
+            self.shadows.cascade_count(),
+        );
+    }
+
+    /// Uploads the debug lines drawn by the next [`Self::render`].
+    pub fn set_debug_draw(&mut self, debug: &DebugDraw) {
+        self.debug_lines
+            .upload(&self.context.device, &self.context.queue, debug);
     }
 
     pub fn create_mesh(&self, vertices: &[Vertex], indices: &[u32]) -> crate::asset::Mesh {
         crate::asset::Mesh::from_vertices(&self.context.device, vertices, indices)
     }
 
+    pub fn create_skinned_mesh(
+        &self,
+        vertices: &[Vertex],
+        skin: &[SkinVertex],
+        indices: &[u32],
+    ) -> crate::asset::Mesh {
+        crate::asset::Mesh::from_skinned_vertices(&self.context.device, vertices, skin, indices)
+    }
+
+    /// Creates a mesh whose vertices are displaced by morph targets. Each
+    /// entry of `targets` holds one delta per vertex; the deltas are appended
+    /// to the renderer's shared morph buffer and blended in the vertex shader
+    /// with the entity's [`MorphWeights`](crate::scene::components::MorphWeights).
+    pub fn create_morphed_mesh(
+        &mut self,
+        vertices: &[Vertex],
+        skin: Option<&[SkinVertex]>,
+        indices: &[u32],
+        targets: &[Vec<MorphDelta>],
+    ) -> crate::asset::Mesh {
+        let mesh = match skin {
+            Some(skin) => self.create_skinned_mesh(vertices, skin, indices),
+            None => self.create_mesh(vertices, indices),
+        };
+        if targets.is_empty() {
+            return mesh;
+        }
+
+        let vertex_count = vertices.len();
+        let deltas: Vec<MorphDelta> = targets
+            .iter()
+            .flat_map(|target| {
+                debug_assert_eq!(target.len(), vertex_count);
+                target
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(MorphDelta::default()))
+                    .take(vertex_count)
+            })
+            .collect();
+        let delta_offset = self
+            .objects_buffer
+            .append_morph_deltas(&self.context, &deltas);
+
+        mesh.with_morph_targets(MorphTargetRange {
+            delta_offset,
+            target_count: targets.len() as u32,
+            vertex_count: vertex_count as u32,
+        })
+    }
+
+    /// Mirrors `assets.textures` into the texture bind groups. Removed
+    /// textures fall back to a blank view. [`Self::render`] calls this
+    /// whenever the texture cache changed since the last update.
     pub fn update_texture_bind_group(&mut self, assets: &Assets) {
         self.texture_binder.update(&self.context.device, assets);
+        self.texture_revision = Some(assets.textures.revision());
     }
 
     pub fn render(
@@ -250,9 +569,14 @@ impl Renderer {
         lights: &LightsData,
         environment: &Environment,
     ) -> Result<RenderFrame, wgpu::SurfaceError> {
-        let frame = self.context.surface.get_current_texture()?;
+        if self.texture_revision != Some(assets.textures.revision()) {
+            self.update_texture_bind_group(assets);
+        }
+        self.screenshots.poll(&self.context.device);
+
+        let frame = self.acquire_frame()?;
         let view = frame
-            .texture
+            .texture()
             .create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder =
@@ -262,18 +586,33 @@ impl Renderer {
                     label: Some("Encoder"),
                 });
 
+        self.gpu_timer.begin_frame(&self.context.device);
         let mut prepared_batches = PreparedBatches::from_batcher(batcher, self.camera_position);
 
         let batch_count = prepared_batches.all().len() as u32;
+        // Each batch draws at most once in the prepass and once in its color
+        // pass without bindless textures splitting it into material runs.
+        self.instanced_draws
+            .begin_frame(&self.context.device, batch_count as usize * 2);
         let instance_count = prepared_batches
             .all()
             .iter()
             .map(|batch| batch.instances.len() as u32)
             .sum();
 
+        self.prepare_batch_pipelines(assets, &prepared_batches);
+        self.pipeline.compile_queued_for_frame(&self.context);
+
         let mut frame_stats = RendererStats {
             batch_count,
             instance_count,
+            culled_objects: batcher.culled_objects(),
+            manual_objects: batcher.manual_objects(),
+            sorted_instances: prepared_batches.sorted_instances,
+            sort_operations: prepared_batches.sort_operations,
+            gpu_timings: self.gpu_timer.latest(),
+            pipelines_compiled: self.pipeline.take_compiled_count(),
+            pipeline_variants: self.pipeline.variant_count(),
             ..RendererStats::default()
         };
 
@@ -286,17 +625,91 @@ impl Renderer {
                 &self.context.device,
                 &self.shadows,
                 &self.environment,
+                &self.transmission,
             );
         }
 
-        self.objects_buffer.update(
+        let uploads = self.objects_buffer.update(
             &self.context,
             prepared_batches.all(),
+            assets,
             prepared_batches.materials(),
+            batcher.joint_matrices(),
+            batcher.morph_weights(),
+            batcher.composition_changed(),
         )?;
-        self.lights_buffer.update(&self.context.queue, lights);
+        frame_stats.updated_instances = uploads.updated;
+        frame_stats.reused_instances = uploads.reused;
+        frame_stats.instance_bytes_written = uploads.bytes_written;
+        frame_stats.bytes_uploaded = uploads.bytes_uploaded;
+        self.lights_buffer.update(
+            &self.context.queue,
+            lights,
+            self.shadows.settings(),
+            self.shadows.cascade_count(),
+        );
 
-        self.shadows.render(
+        // Below or above native resolution the composite and the passes
+        // sharing the scene depth draw at render size, then get stretched
+        // over the surface.
+        let output_view = self
+            .postprocess
+            .output_view()
+            .cloned()
+            .unwrap_or_else(|| view.clone());
+        let (scene_view, resolve_target) = {
+            let (view, resolve) = self.postprocess.scene_color_views();
+            (view.clone(), resolve.cloned())
+        };
+        let (emissive_view, emissive_resolve) = {
+            let (view, resolve) = self.postprocess.emissive_views();
+            (view.clone(), resolve.cloned())
+        };
+        let (velocity_view, velocity_resolve) = {
+            let (view, resolve) = self.postprocess.velocity_views();
+            (view.clone(), resolve.cloned())
+        };
+        // Scene pipelines also write emission and motion, so the passes after
+        // post-processing bind the single-sampled targets and discard them.
+        let late_emissive_view = self.postprocess.emissive_view().clone();
+        let late_velocity_view = self.postprocess.velocity_view().clone();
+        let depth_view = self.context.depth.view.clone();
+
+        // Custom passes draw into whichever colour target is current; the
+        // scene depth only matches the single-sampled targets without MSAA,
+        // and the surface at native resolution.
+        let single_sampled_depth = (self.context.sample_count == 1).then_some(&depth_view);
+        let scene_target = PassTarget {
+            color: &scene_view,
+            resolve: resolve_target.as_ref(),
+            depth: Some(&depth_view),
+            sample_count: self.context.sample_count,
+        };
+        let late_target = PassTarget {
+            color: &output_view,
+            resolve: None,
+            depth: single_sampled_depth,
+            sample_count: 1,
+        };
+        let surface_target = PassTarget {
+            color: &view,
+            resolve: None,
+            depth: single_sampled_depth.filter(|_| self.postprocess.output_view().is_none()),
+            sample_count: 1,
+        };
+        // Taken for the frame so the passes can borrow the renderer.
+        let mut frame_passes = std::mem::take(&mut self.frame_passes);
+
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Shadows, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
+        );
+        self.gpu_timer.begin(&mut encoder, GpuPass::Shadows);
+        frame_stats.shadow_views = self.shadows.render(
             &self.context,
             &mut encoder,
             assets,
@@ -304,16 +717,30 @@ impl Renderer {
             lights,
             &self.objects_buffer,
             prepared_batches.materials(),
+            &mut self.texture_binder,
+        );
+        self.gpu_timer.end(&mut encoder, GpuPass::Shadows);
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Shadows, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
         );
-
-        let (scene_view, resolve_target) = {
-            let (view, resolve) = self.postprocess.scene_color_views();
-            (view.clone(), resolve.cloned())
-        };
-        let depth_view = self.context.depth.view.clone();
 
         // Depth-only prepass
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::DepthPrepass, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
+        );
+        self.gpu_timer.begin(&mut encoder, GpuPass::DepthPrepass);
         {
+            let materials = prepared_batches.materials.clone();
             let opaque_batches = prepared_batches.opaque_mut();
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("DepthPrepass"),
@@ -324,52 +751,111 @@ impl Renderer {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: wgpu::StoreOp::Store,
                     }),
-                    stencil_ops: None,
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: wgpu::StoreOp::Store,
+                    }),
                 }),
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
 
-            pass.set_pipeline(self.pipeline.depth_prepass());
             pass.set_bind_group(0, &self.camera_buffer.bind_group, &[]);
             pass.set_bind_group(1, &self.objects_buffer.bind_group, &[]);
 
             for batch in opaque_batches {
+                // Stencil tested batches draw around what earlier batches
+                // marked, so they must not occlude it in the prepass.
                 if batch.alpha_blend
                     || !batch.depth_state.depth_write
                     || !batch.depth_state.depth_test
+                    || batch.depth_state.stencil_test.is_some()
                 {
                     continue;
                 }
                 let Some(mesh) = mesh_for_batch(assets, batch) else {
                     continue;
                 };
-                self.draw_full_batch(&mut pass, mesh, batch);
-                frame_stats.depth_prepass_draw_calls += 1;
+                // The prepass pipeline has no skinning or morphing path;
+                // deformed meshes keep writing depth in the main pass instead.
+                if mesh.is_skinned() || mesh.morph_targets().is_some() {
+                    continue;
+                }
+                if batch.alpha_mask {
+                    pass.set_pipeline(self.pipeline.depth_prepass_masked(batch.double_sided));
+                    pass.set_bind_group(2, &self.lights_buffer.bind_group, &[]);
+                    frame_stats.depth_prepass_draw_calls +=
+                        self.draw_textured_batch(&mut pass, assets, mesh, batch, &materials);
+                } else {
+                    pass.set_pipeline(self.pipeline.depth_prepass(batch.double_sided));
+                    self.draw_full_batch(&mut pass, mesh, batch);
+                    frame_stats.depth_prepass_draw_calls += 1;
+                }
                 batch.depth_state.depth_write = false;
             }
         }
+        self.gpu_timer.end(&mut encoder, GpuPass::DepthPrepass);
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::DepthPrepass, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
+        );
 
         // Main color pass (to postprocess scene target)
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Main, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
+        );
+        self.gpu_timer.begin(&mut encoder, GpuPass::Main);
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("MainPass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &scene_view,
-                    depth_slice: None,
-                    resolve_target: resolve_target.as_ref(),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(environment.clear_color()),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
+                color_attachments: &[
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &scene_view,
+                        depth_slice: None,
+                        resolve_target: resolve_target.as_ref(),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(environment.clear_color()),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &emissive_view,
+                        depth_slice: None,
+                        resolve_target: emissive_resolve.as_ref(),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &velocity_view,
+                        depth_slice: None,
+                        resolve_target: velocity_resolve.as_ref(),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                ],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &depth_view,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Load,
                         store: wgpu::StoreOp::Store,
                     }),
-                    stencil_ops: None,
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
                 }),
                 timestamp_writes: None,
                 occlusion_query_set: None,
@@ -387,19 +873,40 @@ impl Renderer {
                 self.context.sample_count,
             );
         }
+        self.gpu_timer.end(&mut encoder, GpuPass::Main);
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Main, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
+        );
 
-        // Resolve scene → swapchain
-        self.postprocess
-            .execute(&mut encoder, &self.context.device, &view);
+        // Transmissive materials refract a blurred copy of the opaque scene.
+        let materials = prepared_batches.materials();
+        let needs_transmission = prepared_batches.transparent().iter().any(|batch| {
+            batch.instances.iter().any(|inst| {
+                materials
+                    .get(inst.material_index as usize)
+                    .is_some_and(Material::is_transmissive)
+            })
+        });
+        if needs_transmission {
+            let resolved_scene = resolve_target.as_ref().unwrap_or(&scene_view);
+            self.transmission
+                .capture(&self.context.device, &mut encoder, resolved_scene);
+        }
 
-        // Transparent pass (drawn after post-process so SSAO/Fxaa apply only to opaque surfaces).
-        if !prepared_batches.transparent().is_empty() {
+        // Depth tested debug lines share the scene depth and run through
+        // post-processing with it.
+        if self.debug_lines.has_depth_tested() {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("TransparentPass"),
+                label: Some("DebugLinePass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: &scene_view,
                     depth_slice: None,
-                    resolve_target: None,
+                    resolve_target: resolve_target.as_ref(),
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Load,
                         store: wgpu::StoreOp::Store,
@@ -416,6 +923,89 @@ impl Renderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            rpass.set_bind_group(0, &self.camera_buffer.bind_group, &[]);
+            self.debug_lines.draw_depth_tested(&mut rpass);
+        }
+
+        // Resolve scene → swapchain
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::PostProcess, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &scene_target,
+        );
+        self.postprocess.execute(
+            &mut encoder,
+            &self.context.device,
+            &output_view,
+            &mut self.gpu_timer,
+        );
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::PostProcess, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &late_target,
+        );
+
+        // Transparent pass (drawn after post-process so SSAO/Fxaa apply only to opaque surfaces).
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Transparent, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &late_target,
+        );
+        if !prepared_batches.transparent().is_empty() {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("TransparentPass"),
+                color_attachments: &[
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &output_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &late_emissive_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &late_velocity_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    }),
+                ],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
 
             frame_stats.transparent_draw_calls += self.record_batches(
                 &mut rpass,
@@ -426,11 +1016,86 @@ impl Renderer {
             );
         }
 
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Transparent, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &late_target,
+        );
+
         // Overlay pass (your overlays draw after UI if you keep it here;
         // if you want UI on top of overlays, move this block above ui_hook).
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Overlay, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &late_target,
+        );
         if !prepared_batches.overlay().is_empty() {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("OverlayPass"),
+                color_attachments: &[
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &output_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &late_emissive_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &late_velocity_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    }),
+                ],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            frame_stats.overlay_draw_calls += self.record_batches(
+                &mut rpass,
+                assets,
+                prepared_batches.overlay(),
+                prepared_batches.materials(),
+                1,
+            );
+        }
+
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Overlay, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &late_target,
+        );
+
+        self.postprocess.upscale(&mut encoder, &view);
+
+        // Lines drawn on top skip post-processing so they stay crisp.
+        if self.debug_lines.has_on_top() {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("DebugLineOverlayPass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
                     depth_slice: None,
@@ -444,16 +1109,23 @@ impl Renderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-
-            frame_stats.overlay_draw_calls += self.record_batches(
-                &mut rpass,
-                assets,
-                prepared_batches.overlay(),
-                prepared_batches.materials(),
-                1,
-            );
+            rpass.set_bind_group(0, &self.camera_buffer.bind_group, &[]);
+            self.debug_lines.draw_on_top(&mut rpass);
         }
 
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Ui, Placement::Before),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &surface_target,
+        );
+
+        // Screenshots show the composited scene without the UI overlay.
+        self.screenshots
+            .record(&self.context.device, &mut encoder, frame.texture());
+
         // --- EGUI (optional) ---
         #[cfg(feature = "egui")]
         if let Some(hook) = self.ui_hook.take() {
@@ -467,16 +1139,162 @@ impl Renderer {
             );
         }
 
+        self.run_frame_passes(
+            &mut frame_passes,
+            (FramePass::Ui, Placement::After),
+            &mut encoder,
+            assets,
+            &prepared_batches,
+            &surface_target,
+        );
+        self.frame_passes = frame_passes;
+
         frame_stats.shadow_draw_calls = estimate_shadow_draw_calls(
             prepared_batches.all(),
             prepared_batches.materials(),
             lights,
+            self.shadows.cascade_count(),
         );
 
+        frame_stats.instance_draw_calls = self.instanced_draws.finish(&self.context.queue);
         self.stats = frame_stats;
 
+        if let Some(capture) = self.frame_capture.as_mut() {
+            capture.record(&self.context.device, &mut encoder, frame.texture());
+        }
+
+        self.gpu_timer.resolve(&mut encoder);
         self.context.queue.submit(Some(encoder.finish()));
-        Ok(RenderFrame { frame })
+        self.gpu_timer.after_submit();
+        self.screenshots.after_submit();
+        Ok(frame)
+    }
+
+    fn run_frame_passes(
+        &self,
+        passes: &mut FramePasses,
+        (stage, placement): (FramePass, Placement),
+        encoder: &mut wgpu::CommandEncoder,
+        assets: &Assets,
+        batches: &PreparedBatches,
+        target: &PassTarget<'_>,
+    ) {
+        let mut ctx = FramePassContext {
+            encoder,
+            renderer: self,
+            assets,
+            batches: FrameBatches::new(batches),
+            color_view: target.color,
+            resolve_target: target.resolve,
+            depth_view: target.depth,
+            sample_count: target.sample_count,
+        };
+        passes.run(stage, placement, &mut ctx);
+    }
+
+    fn acquire_frame(&self) -> Result<RenderFrame, wgpu::SurfaceError> {
+        let target = match (&self.context.surface, &self.context.offscreen) {
+            (Some(surface), _) => FrameTarget::Surface(surface.get_current_texture()?),
+            (None, Some(texture)) => FrameTarget::Offscreen(texture.clone()),
+            (None, None) => return Err(wgpu::SurfaceError::Lost),
+        };
+        Ok(RenderFrame { target })
+    }
+
+    /// Copies the last rendered frame of a headless renderer into tightly
+    /// packed RGBA8 rows (`width * height * 4` bytes). Returns `None` for
+    /// windowed renderers; use [`Self::read_pixels`] with frame capture
+    /// enabled to read those.
+    pub fn read_back_frame(&self) -> Option<Vec<u8>> {
+        self.context.offscreen.as_ref()?;
+        let pixels = pollster::block_on(self.read_pixels(None));
+        (!pixels.is_empty()).then_some(pixels)
+    }
+
+    /// Reads the top-left `rect` region (the whole frame when `None`) of the
+    /// most recently rendered frame as tightly packed RGBA8 rows.
+    ///
+    /// Headless renderers read their offscreen target. Windowed renderers
+    /// read the copy kept by [`Self::set_frame_capture`], and resolve to an
+    /// empty `Vec` when capture is disabled or the surface cannot be copied.
+    pub fn read_pixels(&self, rect: Option<wgpu::Extent3d>) -> impl Future<Output = Vec<u8>> {
+        let texture = match (&self.context.offscreen, &self.frame_capture) {
+            (Some(texture), _) => Some(texture),
+            (None, Some(capture)) => capture.texture(),
+            (None, None) => None,
+        };
+        let Some(texture) = texture else {
+            log::warn!("No frame to read back; enable frame capture on windowed renderers");
+            return PixelReadback::failed();
+        };
+
+        let size = texture.size();
+        let rect = rect.unwrap_or(size);
+        let width = rect.width.min(size.width);
+        let height = rect.height.min(size.height);
+        if width == 0 || height == 0 {
+            log::warn!("Ignoring empty pixel readback region {:?}", rect);
+            return PixelReadback::failed();
+        }
+
+        PixelReadback::start(
+            &self.context.device,
+            &self.context.queue,
+            texture,
+            width,
+            height,
+        )
+    }
+
+    /// Keeps a copy of every windowed frame so [`Self::read_pixels`] can
+    /// read it after presenting. Costs one full-frame copy per frame while
+    /// enabled; headless renderers never need it.
+    pub fn set_frame_capture(&mut self, enabled: bool) {
+        if !enabled {
+            self.frame_capture = None;
+        } else if self.frame_capture.is_none() && !self.is_headless() {
+            if !self
+                .context
+                .config
+                .usage
+                .contains(wgpu::TextureUsages::COPY_SRC)
+            {
+                log::warn!("Surface does not support copies; frames cannot be captured");
+            }
+            self.frame_capture = Some(FrameCapture::new());
+        }
+    }
+
+    pub fn frame_capture(&self) -> bool {
+        self.frame_capture.is_some()
+    }
+
+    /// Captures the next rendered frame, after post-processing but before
+    /// the UI overlay, and hands it to `callback`. The copy is mapped in the
+    /// background and the callback runs during a later [`Self::render`], so
+    /// the frame loop never waits on the GPU.
+    pub fn request_screenshot(&mut self, callback: impl FnOnce(Screenshot) + 'static) {
+        self.screenshots.request(Box::new(callback));
+    }
+
+    /// Captures the next rendered frame into a PNG file at `path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn request_screenshot_to_file(&mut self, path: impl Into<std::path::PathBuf>) {
+        let path = path.into();
+        self.request_screenshot(move |screenshot| match screenshot.save_png(&path) {
+            Ok(()) => log::info!(
+                "Saved {}x{} screenshot to {:?}",
+                screenshot.width,
+                screenshot.height,
+                path
+            ),
+            Err(err) => log::warn!("{}", err),
+        });
+    }
+
+    /// Whether screenshots are still waiting to be captured or mapped.
+    pub fn has_pending_screenshots(&self) -> bool {
+        !self.screenshots.is_empty()
     }
 
     // Add helper method to get surface format
@@ -492,18 +1310,347 @@ impl Renderer {
         self.context.sample_count
     }
 
+    /// Whether the device can rasterize [`RenderMode::Wireframe`] as lines.
+    /// Without it the wireframe is drawn by a fragment shader.
+    pub fn supports_wireframe(&self) -> bool {
+        self.pipeline.supports_wireframe()
+    }
+
+    /// Switches the main pass between lit shading and a debug view. Pipeline
+    /// variants for a debug view are compiled as batches need them, with the
+    /// rest queued for warm-up. Returns the applied mode.
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> RenderMode {
+        if mode == RenderMode::Wireframe && !self.supports_wireframe() {
+            log::info!("POLYGON_MODE_LINE unavailable; using the shader wireframe");
+        }
+        self.pipeline.queue_render_mode(mode);
+        self.render_mode = mode;
+        mode
+    }
+
+    /// Compiles every main pass pipeline variant still waiting to be built,
+    /// so later frames never stall on a new permutation. Call it while a
+    /// loading screen is up; otherwise variants are compiled when a batch
+    /// first needs them and a few queued ones each frame. Returns how many
+    /// were compiled.
+    pub fn warm_up(&mut self) -> u32 {
+        self.pipeline.compile_queued(&self.context, None)
+    }
+
+    /// Main pass pipeline variants not compiled yet.
+    pub fn pending_pipelines(&self) -> u32 {
+        self.pipeline.queued_count()
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    /// Recompiles scene shaders whose WGSL files changed on disk since the
+    /// last call. Only native debug builds with a window watch the shader
+    /// directory; elsewhere this does nothing. A shader that fails to compile
+    /// is logged and the previous pipelines stay in use.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_changed_shaders(&mut self) {
+        let Some(watcher) = &self.shader_watcher else {
+            return;
+        };
+        for (file, source) in watcher.poll() {
+            let shaders = SceneShader::using(&file);
+            if shaders.is_empty() {
+                log::info!(
+                    "{} changed but is not hot-reloadable; restart to apply",
+                    file
+                );
+                continue;
+            }
+            if self.shader_sources.get(&file) == source {
+                continue;
+            }
+
+            let previous = self.shader_sources.replace(&file, source);
+            let mut failed = false;
+            for shader in shaders {
+                if let Err(err) =
+                    self.pipeline
+                        .reload_shader(&self.context, shader, &self.shader_sources)
+                {
+                    log::warn!(
+                        "Failed to reload {:?} shader after editing {}:\n{}",
+                        shader,
+                        file,
+                        err
+                    );
+                    failed = true;
+                }
+            }
+            if failed {
+                self.shader_sources.restore(&file, previous);
+            } else {
+                log::info!("Reloaded {}", file);
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn reload_changed_shaders(&mut self) {}
+
+    /// Changes the MSAA level at runtime, rebuilding the multisampled targets
+    /// and pipelines without recreating the device. The request is validated
+    /// like [`RenderSettings::sample_count`]; the count actually applied is
+    /// returned. Pipelines created outside the renderer against
+    /// [`sample_count`](Self::sample_count) must be rebuilt by their owner.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> u32 {
+        let previous = self.context.sample_count;
+        let sample_count = self.context.set_sample_count(sample_count);
+        self.settings.sample_count = sample_count;
+        if sample_count == previous {
+            return sample_count;
+        }
+
+        self.pipeline = RenderPipeline::build(
+            &self.context,
+            &self.camera_buffer,
+            &self.objects_buffer,
+            &self.lights_buffer,
+            self.texture_binder.bind_layout(),
+            sample_count,
+            &self.shader_sources,
+        );
+        self.pipeline.queue_render_mode(self.render_mode);
+        self.debug_lines
+            .rebuild_pipelines(&self.context, &self.camera_buffer, sample_count);
+        self.postprocess.set_sample_count(
+            &self.context.device,
+            &self.context.queue,
+            self.context.config.format,
+            sample_count,
+        );
+        self.postprocess
+            .set_depth_view(&self.context.depth.sampled_view);
+        log::info!("MSAA sample count changed to {}", sample_count);
+        sample_count
+    }
+
+    /// Applies new shadow map resolutions and biases. Pipelines are rebuilt
+    /// for changed biases and shadow maps are recreated for changed
+    /// resolutions; normal offsets take effect with the next light update.
+    pub fn set_shadow_settings(&mut self, settings: ShadowSettings) {
+        let settings = settings.validate();
+        if settings == self.settings.shadows {
+            return;
+        }
+        self.settings.shadows = settings;
+
+        if self.shadows.apply_settings(&self.context.device, &settings) {
+            self.lights_buffer.rebuild_bind_group(
+                &self.context.device,
+                &self.shadows,
+                &self.environment,
+                &self.transmission,
+            );
+        }
+    }
+
+    pub fn shadow_settings(&self) -> ShadowSettings {
+        self.settings.shadows
+    }
+
+    /// Loads an equirectangular `.hdr`/`.exr` panorama, bakes its
+    /// image-based lighting cubemaps and uses it for lighting and the skybox
+    /// in place of the scene environment's HDR background. The bind groups
+    /// switch over on the next frame; on error the current environment is
+    /// kept.
+    pub fn set_environment_hdr(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let image = load_hdr_image(path.as_ref())?;
+        self.environment
+            .set_override(&self.context.device, &self.context.queue, image)
+    }
+
+    /// Like [`Self::set_environment_hdr`] for an encoded image in memory.
+    pub fn set_environment_hdr_bytes(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let image = decode_hdr_image(bytes)?;
+        self.environment
+            .set_override(&self.context.device, &self.context.queue, image)
+    }
+
+    /// Loads an equirectangular `.hdr`/`.exr` panorama and bakes its
+    /// image-based lighting cubemaps for scenes to select with
+    /// `Scene::set_environment_map`. Each call bakes a new map.
+    pub fn load_environment_map(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<Handle<EnvironmentMap>, String> {
+        let image = load_hdr_image(path.as_ref())?;
+        self.environment
+            .load(&self.context.device, &self.context.queue, image)
+    }
+
+    /// Like [`Self::load_environment_map`] for an encoded image in memory.
+    pub fn load_environment_map_bytes(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<Handle<EnvironmentMap>, String> {
+        let image = decode_hdr_image(bytes)?;
+        self.environment
+            .load(&self.context.device, &self.context.queue, image)
+    }
+
+    /// Drops the environment set by [`Self::set_environment_hdr`], falling
+    /// back to the scene environment.
+    pub fn clear_environment_hdr(&mut self) {
+        self.environment.clear_override();
+    }
+
     pub fn set_postprocess_effects(&mut self, effects: PostProcessEffects) {
-        self.postprocess.set_effects(&self.context.queue, effects);
+        self.settings.ssao_quality = effects.ssao_quality;
+        self.postprocess
+            .set_effects(&self.context.device, &self.context.queue, effects);
     }
 
     pub fn postprocess_effects(&self) -> PostProcessEffects {
         self.postprocess.effects()
     }
 
+    /// The post-processing passes and their order. Insert custom nodes with
+    /// [`RenderGraph::insert_node_before`] or [`RenderGraph::insert_node_after`].
+    pub fn postprocess_graph(&self) -> &RenderGraph {
+        self.postprocess.graph()
+    }
+
+    pub fn postprocess_graph_mut(&mut self) -> &mut RenderGraph {
+        self.postprocess.graph_mut()
+    }
+
+    /// Records `pass` every frame right before the built-in pass named
+    /// `anchor` (see [`FramePass::name`]), after passes added there earlier.
+    /// Fails on an unknown anchor or a name already in use.
+    pub fn add_pass_before(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        pass: impl FnMut(&mut FramePassContext<'_>) + 'static,
+    ) -> Result<(), String> {
+        self.frame_passes
+            .insert(anchor, Placement::Before, name, Box::new(pass))
+    }
+
+    /// Records `pass` every frame right after the built-in pass named
+    /// `anchor`, after passes added there earlier.
+    pub fn add_pass_after(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        pass: impl FnMut(&mut FramePassContext<'_>) + 'static,
+    ) -> Result<(), String> {
+        self.frame_passes
+            .insert(anchor, Placement::After, name, Box::new(pass))
+    }
+
+    /// Removes a pass added with [`Self::add_pass_before`] or
+    /// [`Self::add_pass_after`]. Returns whether it existed.
+    pub fn remove_pass(&mut self, name: &str) -> bool {
+        self.frame_passes.remove(name)
+    }
+
+    /// Built-in and custom pass names in recording order.
+    pub fn frame_pass_order(&self) -> Vec<&str> {
+        self.frame_passes.order()
+    }
+
+    /// Replaces the bloom settings, keeping the other effects. A new mip
+    /// count rebuilds the bloom chain.
+    pub fn set_bloom_settings(&mut self, settings: BloomSettings) {
+        let effects = PostProcessEffects {
+            bloom_settings: settings,
+            ..self.postprocess.effects()
+        };
+        self.set_postprocess_effects(effects);
+    }
+
+    pub fn bloom_settings(&self) -> BloomSettings {
+        self.postprocess.effects().bloom_settings
+    }
+
+    /// Uses `texture` as the lens dirt mask scaled by
+    /// [`BloomSettings::dirt_strength`]; `None` removes it.
+    pub fn set_bloom_dirt_mask(&mut self, texture: Option<&Texture>) {
+        self.postprocess.set_bloom_dirt_mask(
+            &self.context.device,
+            &self.context.queue,
+            texture.map(|texture| &texture.view),
+        );
+    }
+
+    /// Selects the composite tone mapping curve and the linear exposure
+    /// applied before it.
+    pub fn set_tonemapping(&mut self, tone_mapping: ToneMapping, exposure: f32) {
+        let effects = PostProcessEffects {
+            tone_mapping,
+            ..self.postprocess.effects()
+        };
+        self.set_postprocess_effects(effects);
+        self.set_exposure(exposure);
+    }
+
+    pub fn tonemapping(&self) -> ToneMapping {
+        self.postprocess.effects().tone_mapping
+    }
+
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.postprocess.set_exposure(&self.context.queue, exposure);
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.postprocess.exposure()
+    }
+
+    pub fn set_taa_blend_factor(&mut self, factor: f32) {
+        self.postprocess
+            .set_taa_blend_factor(&self.context.queue, factor);
+    }
+
+    pub fn taa_blend_factor(&self) -> f32 {
+        self.postprocess.taa_blend_factor()
+    }
+
     pub fn last_frame_stats(&self) -> RendererStats {
         self.stats
     }
 
+    /// Turns per-pass GPU timestamp queries on or off. They start enabled
+    /// when the device supports `TIMESTAMP_QUERY`. Results reach
+    /// [`RendererStats::gpu_timings`] a frame or more later. Returns whether
+    /// timestamps are recorded after the call.
+    pub fn enable_gpu_timestamps(&mut self, enabled: bool) -> bool {
+        self.gpu_timer.set_enabled(&self.context, enabled)
+    }
+
+    pub fn gpu_timestamps_enabled(&self) -> bool {
+        self.gpu_timer.is_enabled()
+    }
+
+    /// Compiles the main pass variants this frame's batches draw with, at
+    /// the sample counts [`Self::record_batches`] is called with.
+    fn prepare_batch_pipelines(&mut self, assets: &Assets, batches: &PreparedBatches) {
+        let passes = [
+            (batches.opaque(), self.context.sample_count),
+            (batches.transparent(), 1),
+            (batches.overlay(), 1),
+        ];
+        for (pass_batches, color_sample_count) in passes {
+            for batch in pass_batches {
+                let Some(mesh) = assets.meshes.get(batch.mesh) else {
+                    continue;
+                };
+                let key = PipelineKey::for_batch(batch, color_sample_count)
+                    .with_skinning(mesh.is_skinned())
+                    .with_render_mode(self.render_mode);
+                self.pipeline.prepare(&self.context, key);
+            }
+        }
+    }
+
     fn record_batches(
         &mut self,
         rpass: &mut wgpu::RenderPass<'_>,
@@ -517,27 +1664,33 @@ impl Renderer {
         }
 
         let mut draw_calls = 0u32;
+        for batch in batches {
+            let Some(mesh) = self.setup_batch_state(rpass, assets, batch, color_sample_count)
+            else {
+                continue;
+            };
+            draw_calls += self.draw_textured_batch(rpass, assets, mesh, batch, materials);
+        }
+        draw_calls
+    }
 
+    /// Draws `batch` with its textures bound at group 3: in one call with the
+    /// bindless group, or one call per material run otherwise.
+    fn draw_textured_batch(
+        &mut self,
+        pass: &mut wgpu::RenderPass<'_>,
+        assets: &Assets,
+        mesh: &Mesh,
+        batch: &OrderedBatch,
+        materials: &[Material],
+    ) -> u32 {
         if let Some(bindless_group) = self.texture_binder.global_bind_group() {
-            for batch in batches {
-                let Some(mesh) = self.setup_batch_state(rpass, assets, batch, color_sample_count)
-                else {
-                    continue;
-                };
-                rpass.set_bind_group(3, bindless_group, &[]);
-                self.draw_full_batch(rpass, mesh, batch);
-                draw_calls += 1;
-            }
+            pass.set_bind_group(3, bindless_group, &[]);
+            self.draw_full_batch(pass, mesh, batch);
+            1
         } else {
-            for batch in batches {
-                let Some(mesh) = self.setup_batch_state(rpass, assets, batch, color_sample_count)
-                else {
-                    continue;
-                };
-                draw_calls += self.draw_classic_batch(rpass, assets, mesh, batch, materials) as u32;
-            }
+            self.draw_classic_batch(pass, assets, mesh, batch, materials) as u32
         }
-        draw_calls
     }
 
     fn setup_batch_state<'a>(
@@ -548,26 +1701,32 @@ impl Renderer {
         color_sample_count: u32,
     ) -> Option<&'a Mesh> {
         let mesh = mesh_for_batch(assets, batch)?;
-        let pipeline_key = PipelineKey::new(
-            batch.depth_state.depth_test,
-            batch.depth_state.depth_write,
-            batch.alpha_blend,
-            color_sample_count,
-        );
+        let pipeline_key = PipelineKey::for_batch(batch, color_sample_count)
+            .with_skinning(mesh.is_skinned())
+            .with_render_mode(self.render_mode);
         let pipeline = self.pipeline.pipeline(pipeline_key);
         rpass.set_pipeline(pipeline);
+        let (stencil, reference) = StencilMode::of(batch);
+        if stencil != StencilMode::Disabled {
+            rpass.set_stencil_reference(reference);
+        }
         rpass.set_bind_group(0, &self.camera_buffer.bind_group, &[]);
         rpass.set_bind_group(1, &self.objects_buffer.bind_group, &[]);
         rpass.set_bind_group(2, &self.lights_buffer.bind_group, &[]);
         Some(mesh)
     }
 
-    fn draw_full_batch(&self, pass: &mut wgpu::RenderPass<'_>, mesh: &Mesh, batch: &OrderedBatch) {
+    fn draw_full_batch(
+        &mut self,
+        pass: &mut wgpu::RenderPass<'_>,
+        mesh: &Mesh,
+        batch: &OrderedBatch,
+    ) {
         self.set_geometry_buffers(pass, mesh);
         let instance_count = batch.instances.len() as u32;
-        pass.draw_indexed(
-            0..mesh.index_count(),
-            0,
+        self.instanced_draws.draw(
+            pass,
+            mesh.index_count(),
             batch.first_instance..(batch.first_instance + instance_count),
         );
     }
@@ -611,7 +1770,8 @@ impl Renderer {
             let end_instance = start_instance + run_length as u32;
 
             pass.set_bind_group(3, bind_group, &[]);
-            pass.draw_indexed(0..mesh.index_count(), 0, start_instance..end_instance);
+            self.instanced_draws
+                .draw(pass, mesh.index_count(), start_instance..end_instance);
 
             local_offset += run_length;
             draw_calls += 1;
@@ -621,6 +1781,9 @@ impl Renderer {
 
     fn set_geometry_buffers(&self, pass: &mut wgpu::RenderPass<'_>, mesh: &Mesh) {
         pass.set_vertex_buffer(0, mesh.vertex_buffer().slice(..));
+        if let Some(skin_buffer) = mesh.skin_buffer() {
+            pass.set_vertex_buffer(1, skin_buffer.slice(..));
+        }
         pass.set_index_buffer(mesh.index_buffer().slice(..), mesh.index_format());
     }
 
@@ -645,6 +1808,7 @@ fn estimate_shadow_draw_calls(
     batches: &[OrderedBatch],
     materials: &[Material],
     lights: &LightsData,
+    cascade_count: usize,
 ) -> u32 {
     if batches.is_empty() {
         return 0;
@@ -659,30 +1823,7 @@ fn estimate_shadow_draw_calls(
         return 0;
     }
 
-    let directional_passes = lights
-        .directional_shadows()
-        .iter()
-        .take(MAX_DIRECTIONAL_LIGHTS)
-        .filter(|shadow| shadow.params[0] != 0.0)
-        .count() as u32;
-
-    let spot_passes = lights
-        .spot_shadows()
-        .iter()
-        .take(MAX_SPOT_LIGHTS)
-        .filter(|shadow| shadow.params[0] != 0.0)
-        .count() as u32;
-
-    let point_passes = lights
-        .point_shadows()
-        .iter()
-        .take(MAX_POINT_LIGHTS)
-        .filter(|shadow| shadow.params[0] != 0.0)
-        .count() as u32
-        * POINT_SHADOW_FACE_COUNT;
-
-    let total_passes = directional_passes + spot_passes + point_passes;
-    per_pass_draws * total_passes
+    per_pass_draws * lights.shadow_view_count(cascade_count)
 }
 
 fn count_shadow_draws_for_batch(batch: &OrderedBatch, materials: &[Material]) -> u32 {
@@ -0,0 +1,431 @@
+//! Signed distance field glyph atlas and text quad generation.
+//!
+//! [`FontAtlas::build`] rasterizes printable ASCII from a TrueType font and
+//! stores, per texel, the distance to the nearest glyph edge. Materials with
+//! [`Material::with_distance_field_alpha`](crate::renderer::Material::with_distance_field_alpha)
+//! threshold that distance in the fragment shader, so labels stay sharp at
+//! any size.
+
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use glam::Vec2;
+
+use crate::renderer::{Texture, Vertex};
+
+/// DejaVu Sans Mono Bold, used when no other font is given.
+pub const DEFAULT_FONT: &[u8] = include_bytes!("fonts/DejaVuSansMono-Bold.ttf");
+
+/// Pixel height glyphs are rasterized at before the distance transform.
+pub const DEFAULT_GLYPH_PX: f32 = 48.0;
+/// Distance in atlas texels that maps to the full 0..=255 range.
+pub const DEFAULT_SPREAD: u32 = 6;
+const ATLAS_WIDTH: u32 = 512;
+/// Drawn in place of characters the atlas does not contain.
+const FALLBACK_CHAR: char = '?';
+
+/// Where a label's origin sits on its text block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl TextAnchor {
+    /// Anchor position as a fraction of the block, from the bottom-left
+    /// (0, 0) to the top-right (1, 1).
+    pub fn fraction(self) -> Vec2 {
+        match self {
+            TextAnchor::TopLeft => Vec2::new(0.0, 1.0),
+            TextAnchor::Top => Vec2::new(0.5, 1.0),
+            TextAnchor::TopRight => Vec2::new(1.0, 1.0),
+            TextAnchor::Left => Vec2::new(0.0, 0.5),
+            TextAnchor::Center => Vec2::new(0.5, 0.5),
+            TextAnchor::Right => Vec2::new(1.0, 0.5),
+            TextAnchor::BottomLeft => Vec2::new(0.0, 0.0),
+            TextAnchor::Bottom => Vec2::new(0.5, 0.0),
+            TextAnchor::BottomRight => Vec2::new(1.0, 0.0),
+        }
+    }
+}
+
+/// Placement of one glyph. Lengths are in line heights, so a label's font
+/// size scales them directly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphInfo {
+    /// Top-left and bottom-right corners in the atlas.
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    /// Bottom-left corner of the quad relative to the pen on the baseline,
+    /// y up. Includes the distance spread around the outline.
+    pub offset: Vec2,
+    /// Quad size; zero for glyphs without an outline such as space.
+    pub size: Vec2,
+    pub advance: f32,
+}
+
+/// A single-channel distance field atlas for one font. Texels above 128 lie
+/// inside a glyph.
+#[derive(Debug, Clone)]
+pub struct FontAtlas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    glyphs: HashMap<char, GlyphInfo>,
+    ascent: f32,
+    line_height: f32,
+}
+
+/// Vertices and indices for a label, in the XY plane facing +Z so the
+/// billboard transform turns it towards the camera.
+#[derive(Debug, Clone, Default)]
+pub struct TextMesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    /// Width and height of the text block.
+    pub size: Vec2,
+}
+
+impl FontAtlas {
+    /// Builds an atlas of printable ASCII from the bundled font.
+    pub fn default_font() -> Result<Self, String> {
+        Self::build(
+            DEFAULT_FONT,
+            (' '..='~').collect::<Vec<_>>().as_slice(),
+            DEFAULT_GLYPH_PX,
+            DEFAULT_SPREAD,
+        )
+    }
+
+    /// Rasterizes `chars` from the TrueType or OpenType `font_data` at
+    /// `glyph_px` pixels per line and converts them to distance fields
+    /// spreading `spread` texels around each outline.
+    pub fn build(
+        font_data: &[u8],
+        chars: &[char],
+        glyph_px: f32,
+        spread: u32,
+    ) -> Result<Self, String> {
+        let font = FontRef::try_from_slice(font_data)
+            .map_err(|err| format!("Failed to parse font: {}", err))?;
+        if !(glyph_px.is_finite() && glyph_px > 0.0) {
+            return Err(format!("Invalid glyph size {}", glyph_px));
+        }
+        let scaled = font.as_scaled(PxScale::from(glyph_px));
+        let line_px = scaled.height() + scaled.line_gap();
+
+        let mut fields = Vec::with_capacity(chars.len());
+        for &ch in chars {
+            let glyph = scaled.scaled_glyph(ch);
+            let advance = scaled.h_advance(glyph.id) / glyph_px;
+            let field = font
+                .outline_glyph(glyph)
+                .filter(|outline| {
+                    let bounds = outline.px_bounds();
+                    bounds.width() >= 1.0 && bounds.height() >= 1.0
+                })
+                .map(|outline| {
+                    let bounds = outline.px_bounds();
+                    let width = bounds.width() as u32;
+                    let height = bounds.height() as u32;
+                    let mut coverage = vec![0.0f32; (width * height) as usize];
+                    outline.draw(|x, y, value| {
+                        if x < width && y < height {
+                            coverage[(y * width + x) as usize] = value;
+                        }
+                    });
+                    let pixels = distance_field(&coverage, width, height, spread);
+                    let origin = Vec2::new(
+                        bounds.min.x - spread as f32,
+                        -(bounds.max.y + spread as f32),
+                    );
+                    (pixels, width + spread * 2, height + spread * 2, origin)
+                });
+            fields.push((ch, advance, field));
+        }
+
+        let sizes: Vec<[u32; 2]> = fields
+            .iter()
+            .map(|(_, _, field)| field.as_ref().map_or([0, 0], |f| [f.1, f.2]))
+            .collect();
+        let (positions, height) = pack_shelves(&sizes, ATLAS_WIDTH).ok_or_else(|| {
+            format!(
+                "Glyphs at {}px do not fit a {} texel atlas",
+                glyph_px, ATLAS_WIDTH
+            )
+        })?;
+
+        let width = ATLAS_WIDTH;
+        let mut pixels = vec![0u8; (width * height) as usize];
+        let mut glyphs = HashMap::with_capacity(fields.len());
+        for ((ch, advance, field), [x, y]) in fields.into_iter().zip(positions) {
+            let mut info = GlyphInfo {
+                uv_min: Vec2::ZERO,
+                uv_max: Vec2::ZERO,
+                offset: Vec2::ZERO,
+                size: Vec2::ZERO,
+                advance,
+            };
+            if let Some((field, field_width, field_height, origin)) = field {
+                for row in 0..field_height {
+                    let src = (row * field_width) as usize;
+                    let dst = ((y + row) * width + x) as usize;
+                    pixels[dst..dst + field_width as usize]
+                        .copy_from_slice(&field[src..src + field_width as usize]);
+                }
+                let atlas_size = Vec2::new(width as f32, height as f32);
+                info.uv_min = Vec2::new(x as f32, y as f32) / atlas_size;
+                info.uv_max =
+                    Vec2::new((x + field_width) as f32, (y + field_height) as f32) / atlas_size;
+                info.offset = origin / glyph_px;
+                info.size = Vec2::new(field_width as f32, field_height as f32) / glyph_px;
+            }
+            glyphs.insert(ch, info);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            glyphs,
+            ascent: scaled.ascent() / glyph_px,
+            line_height: line_px / glyph_px,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// One distance byte per texel, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn glyph(&self, ch: char) -> Option<&GlyphInfo> {
+        self.glyphs.get(&ch)
+    }
+
+    /// Uploads the atlas as a linear RGBA texture: white, with the distance
+    /// in alpha.
+    pub fn create_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Texture {
+        let rgba: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|&distance| [255, 255, 255, distance])
+            .collect();
+        Texture::from_rgba(
+            device,
+            queue,
+            &rgba,
+            self.width,
+            self.height,
+            false,
+            Some("Font Atlas"),
+        )
+    }
+
+    /// Lays `text` out in lines of `font_size` height and builds one quad
+    /// per visible glyph, with `anchor` at the origin. `\n` starts a new
+    /// line; characters missing from the atlas draw as `?`.
+    pub fn layout(&self, text: &str, font_size: f32, anchor: TextAnchor) -> TextMesh {
+        let mut mesh = TextMesh::default();
+        let mut width = 0.0f32;
+        let mut line_count = 0;
+
+        for (line_index, line) in text.split('\n').enumerate() {
+            line_count += 1;
+            let baseline = -(self.ascent + line_index as f32 * self.line_height);
+            let mut pen = 0.0;
+            for ch in line.chars() {
+                let Some(glyph) = self
+                    .glyphs
+                    .get(&ch)
+                    .or_else(|| self.glyphs.get(&FALLBACK_CHAR))
+                else {
+                    continue;
+                };
+                if glyph.size.x > 0.0 && glyph.size.y > 0.0 {
+                    push_quad(&mut mesh, Vec2::new(pen, baseline) + glyph.offset, glyph);
+                }
+                pen += glyph.advance;
+            }
+            width = width.max(pen);
+        }
+
+        let height = line_count as f32 * self.line_height;
+        let fraction = anchor.fraction();
+        let shift = Vec2::new(-width * fraction.x, height * (1.0 - fraction.y));
+        for vertex in &mut mesh.vertices {
+            vertex.pos[0] = (vertex.pos[0] + shift.x) * font_size;
+            vertex.pos[1] = (vertex.pos[1] + shift.y) * font_size;
+        }
+        mesh.size = Vec2::new(width, height) * font_size;
+        mesh
+    }
+}
+
+fn push_quad(mesh: &mut TextMesh, min: Vec2, glyph: &GlyphInfo) {
+    let max = min + glyph.size;
+    let base = mesh.vertices.len() as u32;
+    let corners = [
+        ([min.x, min.y], [glyph.uv_min.x, glyph.uv_max.y]),
+        ([max.x, min.y], [glyph.uv_max.x, glyph.uv_max.y]),
+        ([max.x, max.y], [glyph.uv_max.x, glyph.uv_min.y]),
+        ([min.x, max.y], [glyph.uv_min.x, glyph.uv_min.y]),
+    ];
+    for ([x, y], uv) in corners {
+        mesh.vertices.push(Vertex {
+            pos: [x, y, 0.0],
+            normal: [0.0, 0.0, 1.0],
+            uv,
+            tangent: [1.0, 0.0, 0.0, 1.0],
+        });
+    }
+    mesh.indices
+        .extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+}
+
+/// Converts `coverage` to a distance field padded by `spread` texels on each
+/// side. 128 marks the outline; each texel of distance moves the value by
+/// `128 / spread`.
+fn distance_field(coverage: &[f32], width: u32, height: u32, spread: u32) -> Vec<u8> {
+    let spread_i = spread as i32;
+    let out_width = width as i32 + spread_i * 2;
+    let out_height = height as i32 + spread_i * 2;
+    let inside = |x: i32, y: i32| {
+        x >= 0
+            && y >= 0
+            && x < width as i32
+            && y < height as i32
+            && coverage[(y * width as i32 + x) as usize] >= 0.5
+    };
+
+    let mut pixels = Vec::with_capacity((out_width * out_height) as usize);
+    for out_y in 0..out_height {
+        for out_x in 0..out_width {
+            let (x, y) = (out_x - spread_i, out_y - spread_i);
+            let here = inside(x, y);
+            // Nearest texel on the other side of the outline.
+            let mut nearest = (spread_i * spread_i + 1) as f32;
+            for dy in -spread_i..=spread_i {
+                for dx in -spread_i..=spread_i {
+                    if inside(x + dx, y + dy) != here {
+                        nearest = nearest.min((dx * dx + dy * dy) as f32);
+                    }
+                }
+            }
+            let distance = (nearest.sqrt() - 0.5).min(spread as f32);
+            let signed = if here { distance } else { -distance };
+            let value = 0.5 + signed / (2.0 * spread as f32);
+            pixels.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    pixels
+}
+
+/// Places rectangles of `sizes` in rows of `width`, tallest first. Returns
+/// the top-left corner of each rectangle in input order and the atlas
+/// height, rounded up to a power of two. `None` when a rectangle is wider
+/// than the atlas.
+fn pack_shelves(sizes: &[[u32; 2]], width: u32) -> Option<(Vec<[u32; 2]>, u32)> {
+    const PADDING: u32 = 1;
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(sizes[index][1]));
+
+    let mut positions = vec![[0, 0]; sizes.len()];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for index in order {
+        let [w, h] = sizes[index];
+        if w == 0 || h == 0 {
+            continue;
+        }
+        if w > width {
+            return None;
+        }
+        if x + w > width {
+            x = 0;
+            y += shelf_height + PADDING;
+            shelf_height = 0;
+        }
+        positions[index] = [x, y];
+        x += w + PADDING;
+        shelf_height = shelf_height.max(h);
+    }
+    Some((positions, (y + shelf_height).max(1).next_power_of_two()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shelves_do_not_overlap() {
+        let sizes = [[30, 40], [0, 0], [200, 10], [300, 40], [100, 20]];
+        let (positions, height) = pack_shelves(&sizes, 512).unwrap();
+        assert_eq!(height, 64);
+
+        let rects: Vec<_> = sizes
+            .iter()
+            .zip(&positions)
+            .filter(|(size, _)| size[0] > 0)
+            .map(|(&[w, h], &[x, y])| (x, y, x + w, y + h))
+            .collect();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.2 <= 512 && a.3 <= height);
+            for b in &rects[i + 1..] {
+                let overlap = a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3;
+                assert!(!overlap, "{a:?} overlaps {b:?}");
+            }
+        }
+        assert!(pack_shelves(&[[513, 4]], 512).is_none());
+    }
+
+    #[test]
+    fn default_atlas_packs_printable_ascii() {
+        let atlas = FontAtlas::default_font().unwrap();
+        let (width, height) = atlas.size();
+        assert_eq!(atlas.pixels().len(), (width * height) as usize);
+
+        let a = atlas.glyph('A').unwrap();
+        assert!(a.uv_min.cmplt(a.uv_max).all());
+        assert!(a.uv_min.cmpge(Vec2::ZERO).all() && a.uv_max.cmple(Vec2::ONE).all());
+        let space = atlas.glyph(' ').unwrap();
+        assert_eq!(space.size, Vec2::ZERO);
+        assert!(space.advance > 0.0);
+    }
+
+    #[test]
+    fn layout_builds_one_quad_per_visible_glyph() {
+        let atlas = FontAtlas::default_font().unwrap();
+        let mesh = atlas.layout("Hi x\nOK", 2.0, TextAnchor::BottomLeft);
+        assert_eq!(mesh.vertices.len(), 5 * 4);
+        assert_eq!(mesh.indices.len(), 5 * 6);
+
+        // Monospace: the first line is four advances wide, two lines tall.
+        let advance = atlas.glyph('H').unwrap().advance;
+        assert!((mesh.size.x - 4.0 * advance * 2.0).abs() < 1e-4);
+        assert!((mesh.size.y - 2.0 * atlas.line_height * 2.0).abs() < 1e-4);
+
+        // The 'H' quad starts at the top line and maps the glyph's UVs.
+        let h = atlas.glyph('H').unwrap();
+        let first = &mesh.vertices[..4];
+        assert_eq!(first[0].uv, [h.uv_min.x, h.uv_max.y]);
+        assert_eq!(first[2].uv, [h.uv_max.x, h.uv_min.y]);
+        assert!(first[0].pos[1] > mesh.size.y * 0.4);
+
+        let centered = atlas.layout("Hi x\nOK", 2.0, TextAnchor::Center);
+        let shift = Vec2::from_slice(&centered.vertices[0].pos[..2])
+            - Vec2::from_slice(&mesh.vertices[0].pos[..2]);
+        assert!((shift - Vec2::new(-mesh.size.x, -mesh.size.y) * 0.5).length() < 1e-4);
+    }
+}
@@ -6,9 +6,13 @@ pub const DEFAULT_WHITE_TEXTURE_INDEX: u32 = 0;
 pub const DEFAULT_NORMAL_TEXTURE_INDEX: u32 = 1;
 pub const DEFAULT_METALLIC_ROUGHNESS_TEXTURE_INDEX: u32 = 2;
 pub const DEFAULT_CHECKER_TEXTURE_INDEX: u32 = 3;
+/// Slots below this hold the default textures and are never released.
+pub const DEFAULT_TEXTURE_COUNT: u32 = 4;
 
 use std::path::Path;
 
+use super::mipmap::MipmapCompute;
+
 #[cfg(target_arch = "wasm32")]
 use crate::io;
 
@@ -21,11 +25,64 @@ struct RgbaTextureSource<'a> {
     label: Option<&'a str>,
 }
 
+/// How a texture is addressed and filtered. Material shaders choose from
+/// one sampler per combination, so this is all that is kept of a glTF
+/// sampler; mirrored wrapping and mixed min/mag filters are not represented.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SamplerKey {
+    /// Clamp coordinates to the edge texels instead of repeating.
+    pub clamp_to_edge: bool,
+    /// Nearest filtering, for pixel art.
+    pub nearest: bool,
+}
+
+impl SamplerKey {
+    /// Every key, in the order the material bind groups expose the samplers.
+    pub const ALL: [Self; 4] = [
+        Self::new(false, false),
+        Self::new(false, true),
+        Self::new(true, false),
+        Self::new(true, true),
+    ];
+
+    pub const fn new(clamp_to_edge: bool, nearest: bool) -> Self {
+        Self {
+            clamp_to_edge,
+            nearest,
+        }
+    }
+
+    pub fn descriptor<'a>(&self, label: Option<&'a str>) -> wgpu::SamplerDescriptor<'a> {
+        let address_mode = if self.clamp_to_edge {
+            wgpu::AddressMode::ClampToEdge
+        } else {
+            wgpu::AddressMode::Repeat
+        };
+        let filter = if self.nearest {
+            wgpu::FilterMode::Nearest
+        } else {
+            wgpu::FilterMode::Linear
+        };
+        wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Texture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
+    /// The settings `sampler` was created with.
+    pub sampler_key: SamplerKey,
 }
 
 impl Texture {
@@ -77,18 +134,31 @@ impl Texture {
         let rgba = img.to_rgba8();
         let (width, height) = rgba.dimensions();
 
-        let (texture_format, view_format) = Self::formats_for_color_space(is_srgb);
-
-        let source = Self::rgba_source(
+        Ok(Self::from_rgba(
+            device,
+            queue,
             &rgba,
             width,
             height,
-            texture_format,
-            view_format,
+            is_srgb,
             path.to_str(),
-        );
+        ))
+    }
 
-        Ok(Self::from_rgba8(device, queue, source))
+    /// Create texture from already decoded rgba8 pixels with mipmaps
+    pub fn from_rgba(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        is_srgb: bool,
+        label: Option<&str>,
+    ) -> Self {
+        let (texture_format, view_format) = Self::formats_for_color_space(is_srgb);
+        let source = Self::rgba_source(pixels, width, height, texture_format, view_format, label);
+
+        Self::from_rgba8(device, queue, source)
     }
 
     /// Create texture from rgba8 data with mipmaps
@@ -119,7 +189,7 @@ impl Texture {
             format: source.texture_format,
             usage: wgpu::TextureUsages::TEXTURE_BINDING
                 | wgpu::TextureUsages::COPY_DST
-                | wgpu::TextureUsages::RENDER_ATTACHMENT, // Needed for mipmap generation
+                | wgpu::TextureUsages::STORAGE_BINDING, // Needed for mipmap generation
             view_formats: &view_formats,
         });
 
@@ -141,36 +211,40 @@ impl Texture {
         );
 
         // Generate mipmaps
-        Self::generate_mipmaps(
+        Self::generate_mipmaps_compute(
             device,
             queue,
             &texture,
-            mip_level_count,
-            source.texture_format,
+            source.view_format == Some(wgpu::TextureFormat::Rgba8UnormSrgb),
         );
 
+        // sRGB views cannot carry the storage usage used by mip generation.
         let view = texture.create_view(&wgpu::TextureViewDescriptor {
             format: source.view_format.or(Some(source.texture_format)),
+            usage: Some(wgpu::TextureUsages::TEXTURE_BINDING),
             ..Default::default()
         });
 
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear, // Enable trilinear filtering
-            ..Default::default()
-        });
+        let sampler_key = SamplerKey::default();
+        let sampler = device.create_sampler(&sampler_key.descriptor(None));
 
         Self {
             texture,
             view,
             sampler,
+            sampler_key,
         }
     }
 
+    /// Replaces the texture's sampler with one created from `key`.
+    pub fn with_sampler_key(mut self, device: &wgpu::Device, key: SamplerKey) -> Self {
+        if key != self.sampler_key {
+            self.sampler = device.create_sampler(&key.descriptor(None));
+            self.sampler_key = key;
+        }
+        self
+    }
+
     pub fn storage_rgba8(
         device: &wgpu::Device,
         width: u32,
@@ -215,163 +289,20 @@ impl Texture {
             texture,
             view,
             sampler,
+            sampler_key: SamplerKey::new(true, true),
         }
     }
 
-    /// Generate mipmaps using GPU rendering
-    fn generate_mipmaps(
+    /// Fills every mip level below the first by downsampling with the
+    /// device's shared [`MipmapCompute`] pipeline. `srgb` averages texels in
+    /// linear space for colour textures.
+    pub fn generate_mipmaps_compute(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         texture: &wgpu::Texture,
-        mip_level_count: u32,
-        format: wgpu::TextureFormat,
+        srgb: bool,
     ) {
-        if mip_level_count <= 1 {
-            return;
-        }
-
-        // Create a simple shader for downsampling
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Blit Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("blit.wgsl").into()),
-        });
-
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Blit Bind Group Layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Blit Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
-        });
-
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Blit Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: None,
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                ..Default::default()
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
-
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Mip Sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
-
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Mipmap Generator"),
-        });
-
-        for target_mip in 1..mip_level_count {
-            let src_mip = target_mip - 1;
-
-            let src_view = texture.create_view(&wgpu::TextureViewDescriptor {
-                label: Some("Mip Source"),
-                format: Some(format),
-                dimension: Some(wgpu::TextureViewDimension::D2),
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: src_mip,
-                mip_level_count: Some(1),
-                base_array_layer: 0,
-                array_layer_count: Some(1),
-                usage: Some(wgpu::TextureUsages::TEXTURE_BINDING), // Add this line
-            });
-
-            let dst_view = texture.create_view(&wgpu::TextureViewDescriptor {
-                label: Some("Mip Destination"),
-                format: Some(format),
-                dimension: Some(wgpu::TextureViewDimension::D2),
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: target_mip,
-                mip_level_count: Some(1),
-                base_array_layer: 0,
-                array_layer_count: Some(1),
-                usage: Some(wgpu::TextureUsages::RENDER_ATTACHMENT), // Add this line
-            });
-
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Mip Bind Group"),
-                layout: &bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&src_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&sampler),
-                    },
-                ],
-            });
-
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Mipmap Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &dst_view,
-                    resolve_target: None,
-                    depth_slice: None, // Add this line
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            rpass.set_pipeline(&pipeline);
-            rpass.set_bind_group(0, &bind_group, &[]);
-            rpass.draw(0..3, 0..1); // Fullscreen triangle
-        }
-
-        queue.submit(Some(encoder.finish()));
+        MipmapCompute::for_device(device).generate(device, queue, texture, srgb);
     }
 
     /// Create a solid color 1x1 texture (no mipmaps needed)
@@ -707,4 +638,48 @@ mod tests {
             assert_eq!(tex_256x256.texture.mip_level_count(), 9); // 256, 128, 64, 32, 16, 8, 4, 2, 1
         });
     }
+
+    #[test]
+    #[ignore]
+    fn test_npot_texture_shares_mipmap_pipeline() {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+                backends: wgpu::Backends::all(),
+                ..Default::default()
+            });
+
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions::default())
+                .await
+                .unwrap();
+
+            let (device, queue) = adapter
+                .request_device(&wgpu::DeviceDescriptor::default())
+                .await
+                .unwrap();
+
+            let data = vec![128u8; 5 * 3 * 4];
+            let texture = Texture::from_rgba8(
+                &device,
+                &queue,
+                Texture::rgba_source(
+                    &data,
+                    5,
+                    3,
+                    wgpu::TextureFormat::Rgba8Unorm,
+                    Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+                    Some("5x3"),
+                ),
+            );
+            assert_eq!(texture.texture.mip_level_count(), 3); // 5x3, 2x1, 1x1
+            assert!(!texture
+                .texture
+                .usage()
+                .contains(wgpu::TextureUsages::RENDER_ATTACHMENT));
+
+            let first = MipmapCompute::for_device(&device);
+            let second = MipmapCompute::for_device(&device);
+            assert!(std::rc::Rc::ptr_eq(&first, &second));
+        });
+    }
 }
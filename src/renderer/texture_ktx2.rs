@@ -0,0 +1,243 @@
+//! KTX2 container support for [`Texture`].
+//!
+//! Block-compressed payloads (BC7, ETC2) are uploaded with their mip chain
+//! as stored. Devices without the matching compression feature get the
+//! levels decoded to RGBA8 on the CPU instead. Basis Universal payloads
+//! (BasisLZ supercompression or UASTC) need a transcoder and are rejected.
+
+use std::io::Read;
+
+use ktx2::{Format, SupercompressionScheme};
+use wgpu::util::DeviceExt;
+
+use crate::renderer::texture::SamplerKey;
+use crate::renderer::Texture;
+
+/// The first 12 bytes of every KTX2 file.
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+/// Whether `bytes` start with the KTX2 file identifier.
+pub(crate) fn is_ktx2(bytes: &[u8]) -> bool {
+    bytes.starts_with(&KTX2_IDENTIFIER)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Payload {
+    Bc7,
+    Etc2,
+    Rgba8,
+}
+
+impl Payload {
+    fn from_format(format: Format) -> Option<(Self, bool)> {
+        match format {
+            Format::BC7_UNORM_BLOCK => Some((Payload::Bc7, false)),
+            Format::BC7_SRGB_BLOCK => Some((Payload::Bc7, true)),
+            Format::ETC2_R8G8B8A8_UNORM_BLOCK => Some((Payload::Etc2, false)),
+            Format::ETC2_R8G8B8A8_SRGB_BLOCK => Some((Payload::Etc2, true)),
+            Format::R8G8B8A8_UNORM => Some((Payload::Rgba8, false)),
+            Format::R8G8B8A8_SRGB => Some((Payload::Rgba8, true)),
+            _ => None,
+        }
+    }
+
+    /// GPU format for this payload, or `None` when the device cannot sample
+    /// it and the levels must be decoded to RGBA8.
+    fn texture_format(self, srgb: bool, features: wgpu::Features) -> Option<wgpu::TextureFormat> {
+        use wgpu::TextureFormat as F;
+        match (self, srgb) {
+            (Payload::Bc7, false) if features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC) => {
+                Some(F::Bc7RgbaUnorm)
+            }
+            (Payload::Bc7, true) if features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC) => {
+                Some(F::Bc7RgbaUnormSrgb)
+            }
+            (Payload::Etc2, false)
+                if features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2) =>
+            {
+                Some(F::Etc2Rgba8Unorm)
+            }
+            (Payload::Etc2, true)
+                if features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2) =>
+            {
+                Some(F::Etc2Rgba8UnormSrgb)
+            }
+            (Payload::Rgba8, false) => Some(F::Rgba8Unorm),
+            (Payload::Rgba8, true) => Some(F::Rgba8UnormSrgb),
+            _ => None,
+        }
+    }
+
+    /// Expands one mip level to tightly packed RGBA8.
+    fn decode_rgba8(self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+        let (width, height) = (width as usize, height as usize);
+        let mut texels = vec![0u32; width * height];
+        let result = match self {
+            Payload::Bc7 => texture2ddecoder::decode_bc7(data, width, height, &mut texels),
+            Payload::Etc2 => texture2ddecoder::decode_etc2_rgba8(data, width, height, &mut texels),
+            Payload::Rgba8 => return Ok(data.to_vec()),
+        };
+        result.map_err(|err| format!("Failed to decode {:?} level: {}", self, err))?;
+
+        // The decoder packs texels as little-endian BGRA.
+        Ok(texels
+            .into_iter()
+            .flat_map(|texel| {
+                let [b, g, r, a] = texel.to_le_bytes();
+                [r, g, b, a]
+            })
+            .collect())
+    }
+}
+
+impl Texture {
+    /// Create a 2D texture from a KTX2 file, keeping its mip chain. BC7 and
+    /// ETC2 payloads stay compressed when the device supports them and are
+    /// decoded to RGBA8 otherwise. Zstandard supercompression is handled;
+    /// Basis Universal payloads return an error.
+    pub fn from_ktx2(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        label: Option<&str>,
+    ) -> Result<Self, String> {
+        let name = label.unwrap_or("KTX2 texture");
+        let reader = ktx2::Reader::new(bytes)
+            .map_err(|err| format!("Failed to parse KTX2 {}: {:?}", name, err))?;
+        let header = reader.header();
+
+        if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count != 1 {
+            return Err(format!(
+                "KTX2 {} is not a single 2D image (depth {}, layers {}, faces {})",
+                name, header.pixel_depth, header.layer_count, header.face_count
+            ));
+        }
+        let scheme = header.supercompression_scheme;
+        if scheme == Some(SupercompressionScheme::BasisLZ) || header.format.is_none() {
+            return Err(format!(
+                "KTX2 {} holds a Basis Universal payload, which needs a transcoder",
+                name
+            ));
+        }
+        let format = header.format.expect("checked above");
+        let (payload, srgb) = Payload::from_format(format)
+            .ok_or_else(|| format!("KTX2 {} uses unsupported format {:?}", name, format))?;
+
+        let width = header.pixel_width;
+        let height = header.pixel_height.max(1);
+        let level_count = header.level_count.max(1);
+
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for level in reader.levels() {
+            let data = match scheme {
+                None => level.data.to_vec(),
+                Some(SupercompressionScheme::Zstandard) => {
+                    let mut decoded = Vec::with_capacity(level.uncompressed_byte_length as usize);
+                    ruzstd::decoding::StreamingDecoder::new(level.data)
+                        .map_err(|err| format!("Invalid Zstandard level in {}: {}", name, err))?
+                        .read_to_end(&mut decoded)
+                        .map_err(|err| format!("Failed to inflate level in {}: {}", name, err))?;
+                    decoded
+                }
+                Some(other) => {
+                    return Err(format!(
+                        "KTX2 {} uses unsupported supercompression {:?}",
+                        name, other
+                    ))
+                }
+            };
+            levels.push(data);
+        }
+
+        // Compressed textures need block-aligned base dimensions.
+        let block_aligned = payload == Payload::Rgba8 || (width % 4 == 0 && height % 4 == 0);
+        let texture_format = payload
+            .texture_format(srgb, device.features())
+            .filter(|_| block_aligned);
+
+        let (texture_format, data) = match texture_format {
+            Some(texture_format) => (texture_format, levels.concat()),
+            None => {
+                log::info!(
+                    "{:?} unavailable for {}; decoding {} levels to RGBA8",
+                    payload,
+                    name,
+                    levels.len()
+                );
+                let mut data = Vec::new();
+                for (level, bytes) in levels.iter().enumerate() {
+                    let level_width = (width >> level).max(1);
+                    let level_height = (height >> level).max(1);
+                    data.extend(payload.decode_rgba8(bytes, level_width, level_height)?);
+                }
+                let texture_format = if srgb {
+                    wgpu::TextureFormat::Rgba8UnormSrgb
+                } else {
+                    wgpu::TextureFormat::Rgba8Unorm
+                };
+                (texture_format, data)
+            }
+        };
+
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label,
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: levels.len() as u32,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: texture_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::MipMajor,
+            &data,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler_key = SamplerKey::default();
+        let sampler = device.create_sampler(&sampler_key.descriptor(None));
+
+        Ok(Self {
+            texture,
+            view,
+            sampler,
+            sampler_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_detects_ktx2_files() {
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        bytes.extend([0u8; 4]);
+        assert!(is_ktx2(&bytes));
+        assert!(!is_ktx2(b"\x89PNG\r\n\x1a\n"));
+        assert!(!is_ktx2(&KTX2_IDENTIFIER[..8]));
+    }
+
+    #[test]
+    fn compressed_payloads_fall_back_without_device_support() {
+        let none = wgpu::Features::empty();
+        assert_eq!(Payload::Bc7.texture_format(false, none), None);
+        assert_eq!(Payload::Etc2.texture_format(true, none), None);
+        assert_eq!(
+            Payload::Bc7.texture_format(true, wgpu::Features::TEXTURE_COMPRESSION_BC),
+            Some(wgpu::TextureFormat::Bc7RgbaUnormSrgb)
+        );
+        assert_eq!(
+            Payload::Rgba8.texture_format(false, none),
+            Some(wgpu::TextureFormat::Rgba8Unorm)
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! GPU pass timings from wgpu timestamp queries.
+//!
+//! Each timed pass is bracketed by two empty compute passes that write a
+//! timestamp, so chains of render passes (shadow maps, the bloom mips) are
+//! measured as a single span. Results are read back asynchronously and lag
+//! the rendered frame by one or more frames.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::renderer::internal::RenderContext;
+
+/// Render passes with their own GPU timing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GpuPass {
+    Shadows,
+    DepthPrepass,
+    Main,
+    DepthResolve,
+    Ssao,
+    Fog,
+    Taa,
+    Dof,
+    Bloom,
+    Composite,
+}
+
+impl GpuPass {
+    pub const ALL: [GpuPass; 10] = [
+        GpuPass::Shadows,
+        GpuPass::DepthPrepass,
+        GpuPass::Main,
+        GpuPass::DepthResolve,
+        GpuPass::Ssao,
+        GpuPass::Fog,
+        GpuPass::Taa,
+        GpuPass::Dof,
+        GpuPass::Bloom,
+        GpuPass::Composite,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GpuPass::Shadows => "Shadows",
+            GpuPass::DepthPrepass => "Depth prepass",
+            GpuPass::Main => "Main",
+            GpuPass::DepthResolve => "Depth resolve",
+            GpuPass::Ssao => "SSAO",
+            GpuPass::Fog => "Fog",
+            GpuPass::Taa => "TAA",
+            GpuPass::Dof => "Depth of field",
+            GpuPass::Bloom => "Bloom",
+            GpuPass::Composite => "Composite",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn bit(self) -> u32 {
+        1 << self.index()
+    }
+}
+
+const QUERY_COUNT: u32 = GpuPass::ALL.len() as u32 * 2;
+const RESOLVE_SIZE: u64 = QUERY_COUNT as u64 * wgpu::QUERY_SIZE as u64;
+
+/// GPU time per pass, stored in nanoseconds. A pass is `None` when it did
+/// not run in the measured frame (e.g. SSAO disabled).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GpuPassTimings {
+    durations_ns: [Option<u64>; GpuPass::ALL.len()],
+}
+
+impl GpuPassTimings {
+    /// Duration of `pass` in nanoseconds.
+    pub fn get_ns(&self, pass: GpuPass) -> Option<u64> {
+        self.durations_ns[pass.index()]
+    }
+
+    /// Duration of `pass` in milliseconds.
+    pub fn get(&self, pass: GpuPass) -> Option<f32> {
+        self.get_ns(pass).map(|ns| (ns as f64 / 1_000_000.0) as f32)
+    }
+
+    /// Timed passes in frame order, in milliseconds.
+    pub fn iter(&self) -> impl Iterator<Item = (GpuPass, f32)> + '_ {
+        GpuPass::ALL
+            .iter()
+            .filter_map(|&pass| self.get(pass).map(|ms| (pass, ms)))
+    }
+
+    /// Sum of all timed passes in nanoseconds.
+    pub fn total_ns(&self) -> u64 {
+        self.durations_ns.iter().flatten().sum()
+    }
+
+    /// Sum of all timed passes in milliseconds.
+    pub fn total_ms(&self) -> f32 {
+        (self.total_ns() as f64 / 1_000_000.0) as f32
+    }
+
+    /// Converts resolved `[begin, end]` tick pairs into nanoseconds. Only
+    /// passes in `written` are reported; out-of-order pairs are dropped.
+    fn from_ticks(ticks: &[u64], written: u32, period_ns: f32) -> Self {
+        let mut timings = Self::default();
+        for pass in GpuPass::ALL {
+            if written & pass.bit() == 0 {
+                continue;
+            }
+            let begin = ticks.get(pass.index() * 2).copied();
+            let end = ticks.get(pass.index() * 2 + 1).copied();
+            if let (Some(begin), Some(end)) = (begin, end) {
+                if end >= begin {
+                    let ns = (end - begin) as f64 * period_ns as f64;
+                    timings.durations_ns[pass.index()] = Some(ns.round() as u64);
+                }
+            }
+        }
+        timings
+    }
+}
+
+struct TimerQueries {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    period_ns: f32,
+    mapped: Arc<AtomicBool>,
+    /// Passes resolved into the readback buffer that is being mapped.
+    in_flight: Option<u32>,
+}
+
+impl TimerQueries {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("GpuTimerQueries"),
+                ty: wgpu::QueryType::Timestamp,
+                count: QUERY_COUNT,
+            }),
+            resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("GpuTimerResolve"),
+                size: RESOLVE_SIZE,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("GpuTimerReadback"),
+                size: RESOLVE_SIZE,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            period_ns: queue.get_timestamp_period(),
+            mapped: Arc::new(AtomicBool::new(false)),
+            in_flight: None,
+        }
+    }
+}
+
+/// Records timestamps around [`GpuPass`]es. Enabled by default when the
+/// device supports [`wgpu::Features::TIMESTAMP_QUERY`]; otherwise every call
+/// does nothing.
+pub struct GpuTimer {
+    supported: bool,
+    queries: Option<TimerQueries>,
+    /// Passes that wrote their begin timestamp this frame.
+    begun: u32,
+    /// Passes that wrote both timestamps this frame.
+    written: u32,
+    latest: Option<GpuPassTimings>,
+}
+
+impl GpuTimer {
+    pub(crate) fn new(context: &RenderContext) -> Self {
+        let supported = context.supports_timestamp_queries;
+        Self {
+            supported,
+            queries: supported.then(|| TimerQueries::new(&context.device, &context.queue)),
+            begun: 0,
+            written: 0,
+            latest: None,
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.supported
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.queries.is_some()
+    }
+
+    /// Creates or drops the timestamp query set. Returns whether timestamps
+    /// are recorded afterwards, which is always `false` without device
+    /// support. Disabling clears the latest timings.
+    pub(crate) fn set_enabled(&mut self, context: &RenderContext, enabled: bool) -> bool {
+        if !enabled {
+            self.queries = None;
+            self.latest = None;
+        } else if self.supported && self.queries.is_none() {
+            self.queries = Some(TimerQueries::new(&context.device, &context.queue));
+        }
+        self.is_enabled()
+    }
+
+    /// Most recent timings read back from the GPU. `None` when timestamp
+    /// queries are unsupported or no frame has been read back yet.
+    pub fn latest(&self) -> Option<GpuPassTimings> {
+        self.latest
+    }
+
+    /// Collects a finished readback, if any, and starts a new frame.
+    pub(crate) fn begin_frame(&mut self, device: &wgpu::Device) {
+        self.begun = 0;
+        self.written = 0;
+
+        let Some(queries) = self.queries.as_mut() else {
+            return;
+        };
+        let Some(written) = queries.in_flight else {
+            return;
+        };
+
+        let _ = device.poll(wgpu::PollType::Poll);
+        if !queries.mapped.swap(false, Ordering::AcqRel) {
+            return;
+        }
+
+        let slice = queries.readback_buffer.slice(..);
+        let ticks: Vec<u64> = slice
+            .get_mapped_range()
+            .chunks_exact(wgpu::QUERY_SIZE as usize)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+            .collect();
+        queries.readback_buffer.unmap();
+        queries.in_flight = None;
+        self.latest = Some(GpuPassTimings::from_ticks(
+            &ticks,
+            written,
+            queries.period_ns,
+        ));
+    }
+
+    /// Writes the begin timestamp of `pass`.
+    pub(crate) fn begin(&mut self, encoder: &mut wgpu::CommandEncoder, pass: GpuPass) {
+        if self.write_timestamp(encoder, pass.index() as u32 * 2) {
+            self.begun |= pass.bit();
+        }
+    }
+
+    /// Writes the end timestamp of `pass`. Ignored unless `begin` ran.
+    pub(crate) fn end(&mut self, encoder: &mut wgpu::CommandEncoder, pass: GpuPass) {
+        if self.begun & pass.bit() != 0
+            && self.write_timestamp(encoder, pass.index() as u32 * 2 + 1)
+        {
+            self.written |= pass.bit();
+        }
+    }
+
+    fn write_timestamp(&self, encoder: &mut wgpu::CommandEncoder, index: u32) -> bool {
+        // Queries are skipped while the previous results are still mapped.
+        let Some(queries) = self.queries.as_ref().filter(|q| q.in_flight.is_none()) else {
+            return false;
+        };
+        encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("GpuTimestamp"),
+            timestamp_writes: Some(wgpu::ComputePassTimestampWrites {
+                query_set: &queries.query_set,
+                beginning_of_pass_write_index: Some(index),
+                end_of_pass_write_index: None,
+            }),
+        });
+        true
+    }
+
+    /// Resolves this frame's queries into the readback buffer. Must be
+    /// followed by [`Self::after_submit`] once `encoder` is submitted.
+    pub(crate) fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(queries) = self.queries.as_ref() else {
+            return;
+        };
+        if self.written == 0 || queries.in_flight.is_some() {
+            return;
+        }
+        encoder.resolve_query_set(
+            &queries.query_set,
+            0..QUERY_COUNT,
+            &queries.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &queries.resolve_buffer,
+            0,
+            &queries.readback_buffer,
+            0,
+            RESOLVE_SIZE,
+        );
+    }
+
+    /// Starts mapping the resolved timestamps without blocking.
+    pub(crate) fn after_submit(&mut self) {
+        let written = self.written;
+        let Some(queries) = self.queries.as_mut() else {
+            return;
+        };
+        if written == 0 || queries.in_flight.is_some() {
+            return;
+        }
+        queries.in_flight = Some(written);
+        let mapped = Arc::clone(&queries.mapped);
+        queries.readback_buffer.slice(..).map_async(
+            wgpu::MapMode::Read,
+            move |result| match result {
+                Ok(()) => mapped.store(true, Ordering::Release),
+                Err(err) => log::warn!("Failed to map GPU timestamp readback: {}", err),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_convert_to_milliseconds() {
+        let mut ticks = vec![0u64; QUERY_COUNT as usize];
+        ticks[GpuPass::Ssao.index() * 2] = 1_000;
+        ticks[GpuPass::Ssao.index() * 2 + 1] = 3_001_000;
+        ticks[GpuPass::Main.index() * 2] = 10;
+        ticks[GpuPass::Main.index() * 2 + 1] = 500_010;
+
+        let written = GpuPass::Ssao.bit() | GpuPass::Main.bit();
+        let timings = GpuPassTimings::from_ticks(&ticks, written, 2.0);
+
+        assert_eq!(timings.get_ns(GpuPass::Ssao), Some(6_000_000));
+        assert_eq!(timings.get_ns(GpuPass::Main), Some(1_000_000));
+        assert_eq!(timings.total_ns(), 7_000_000);
+        assert!((timings.get(GpuPass::Ssao).unwrap() - 6.0).abs() < 1e-4);
+        assert!((timings.total_ms() - 7.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn unwritten_and_reversed_passes_are_none() {
+        let mut ticks = vec![0u64; QUERY_COUNT as usize];
+        ticks[GpuPass::Bloom.index() * 2] = 100;
+        ticks[GpuPass::Bloom.index() * 2 + 1] = 50;
+
+        let timings = GpuPassTimings::from_ticks(&ticks, GpuPass::Bloom.bit(), 1.0);
+
+        assert_eq!(timings.get(GpuPass::Bloom), None);
+        assert_eq!(timings.get(GpuPass::Shadows), None);
+        assert_eq!(timings.iter().count(), 0);
+    }
+}
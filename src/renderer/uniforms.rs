@@ -9,6 +9,11 @@ pub struct CameraUniform {
     pub inverse_view_proj: [[f32; 4]; 4],
     pub camera_pos: [f32; 3],
     pub _padding: f32,
+    /// `view_proj` without the TAA jitter; velocity is measured with it so
+    /// the jitter does not read as motion.
+    pub unjittered_view_proj: [[f32; 4]; 4],
+    /// Last frame's unjittered view-projection.
+    pub prev_view_proj: [[f32; 4]; 4],
 }
 
 impl CameraUniform {
@@ -18,6 +23,8 @@ impl CameraUniform {
             inverse_view_proj: Mat4::IDENTITY.to_cols_array_2d(),
             camera_pos: [0.0, 0.0, 0.0],
             _padding: 0.0,
+            unjittered_view_proj: Mat4::IDENTITY.to_cols_array_2d(),
+            prev_view_proj: Mat4::IDENTITY.to_cols_array_2d(),
         }
     }
 
@@ -32,8 +39,18 @@ impl CameraUniform {
             inverse_view_proj: inverse_view_proj.to_cols_array_2d(),
             camera_pos: camera_pos.to_array(),
             _padding: 0.0,
+            unjittered_view_proj: view_proj.to_cols_array_2d(),
+            prev_view_proj: view_proj.to_cols_array_2d(),
         }
     }
+
+    /// Sets the unjittered matrices of this and the previous frame. Without
+    /// them the camera reports no motion.
+    pub fn with_motion(mut self, unjittered_view_proj: Mat4, prev_view_proj: Mat4) -> Self {
+        self.unjittered_view_proj = unjittered_view_proj.to_cols_array_2d();
+        self.prev_view_proj = prev_view_proj.to_cols_array_2d();
+        self
+    }
 }
 
 impl Default for CameraUniform {
@@ -68,8 +85,8 @@ impl Default for EnvironmentUniform {
 mod tests {
     use super::*;
     #[test]
-    fn camera_uniform_is_144_bytes() {
-        // 2 * mat4x4<f32> = 128 bytes, vec3<f32> = 12 bytes, padding = 4 bytes = 144 bytes
-        assert_eq!(std::mem::size_of::<CameraUniform>(), 144);
+    fn camera_uniform_is_272_bytes() {
+        // 4 * mat4x4<f32> = 256 bytes, vec3<f32> = 12 bytes, padding = 4 bytes = 272 bytes
+        assert_eq!(std::mem::size_of::<CameraUniform>(), 272);
     }
 }
@@ -27,6 +27,45 @@ impl Vertex {
     }
 }
 
+/// Per-vertex skinning data, stored in a second vertex buffer so rigid
+/// meshes keep the compact `Vertex` layout.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Debug, Default, PartialEq)]
+pub struct SkinVertex {
+    /// Indices into the owning skin's joint list.
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+impl SkinVertex {
+    pub const ATTRS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+        4 => Uint32x4,  // joints
+        5 => Float32x4  // weights
+    ];
+
+    pub fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<SkinVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRS,
+        }
+    }
+
+    /// Rescales the weights to sum to one. Vertices without any weight are
+    /// bound fully to the first joint.
+    pub fn normalized(mut self) -> Self {
+        let sum: f32 = self.weights.iter().sum();
+        if sum > f32::EPSILON && sum.is_finite() {
+            for weight in &mut self.weights {
+                *weight /= sum;
+            }
+        } else {
+            self.weights = [1.0, 0.0, 0.0, 0.0];
+        }
+        self
+    }
+}
+
 #[inline]
 pub fn v(pos: [f32; 3], normal: [f32; 3], uv: [f32; 2], tangent: [f32; 4]) -> Vertex {
     Vertex {
@@ -37,6 +76,24 @@ pub fn v(pos: [f32; 3], normal: [f32; 3], uv: [f32; 2], tangent: [f32; 4]) -> Ve
     }
 }
 
+/// One vertex's displacement for one morph target. Stored in a storage
+/// buffer and read by the vertex shader; `w` components are unused padding.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, Debug, Default, PartialEq)]
+pub struct MorphDelta {
+    pub position: [f32; 4],
+    pub normal: [f32; 4],
+}
+
+impl MorphDelta {
+    pub fn new(position: [f32; 3], normal: [f32; 3]) -> Self {
+        Self {
+            position: [position[0], position[1], position[2], 0.0],
+            normal: [normal[0], normal[1], normal[2], 0.0],
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -48,6 +105,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn skin_vertex_stride_matches_struct_size() {
+        assert_eq!(
+            SkinVertex::layout().array_stride,
+            std::mem::size_of::<SkinVertex>() as wgpu::BufferAddress
+        );
+        assert_eq!(std::mem::size_of::<SkinVertex>(), 32);
+    }
+
+    #[test]
+    fn skin_weights_are_normalized() {
+        let skin = SkinVertex {
+            joints: [0, 1, 0, 0],
+            weights: [2.0, 2.0, 0.0, 0.0],
+        }
+        .normalized();
+        assert_eq!(skin.weights, [0.5, 0.5, 0.0, 0.0]);
+
+        let unweighted = SkinVertex::default().normalized();
+        assert_eq!(unweighted.weights, [1.0, 0.0, 0.0, 0.0]);
+    }
+
     #[test]
     fn vertex_size_is_48_bytes() {
         // 3 floats (pos) + 3 floats (normal) + 2 floats (uv) + 4 floats (tangent) = 12 floats = 48 bytes
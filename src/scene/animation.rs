@@ -1,21 +1,25 @@
 use glam::{Quat, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AnimationInterpolation {
     Step,
     Linear,
     CubicSpline,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AnimationOutput {
     Vec3(Vec<Vec3>),
     Quat(Vec<Quat>),
     Vec4(Vec<Vec4>),
+    /// Morph target weights, flattened keyframe by keyframe. Each keyframe
+    /// holds one weight per target (three groups of them for cubic splines).
+    Float(Vec<f32>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationSampler {
     pub times: Vec<f32>,
     pub output: AnimationOutput,
@@ -299,14 +303,73 @@ impl AnimationSampler {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl AnimationSampler {
+    /// Samples a [`AnimationOutput::Float`] track, returning one weight per
+    /// morph target.
+    pub fn sample_weights(&self, time: f32) -> Option<Vec<f32>> {
+        let values = match &self.output {
+            AnimationOutput::Float(values) => values,
+            _ => return None,
+        };
+
+        let keyframe_values = match self.interpolation {
+            AnimationInterpolation::CubicSpline => self.times.len() * 3,
+            _ => self.times.len(),
+        };
+        if keyframe_values == 0 || values.len() % keyframe_values != 0 {
+            return None;
+        }
+        let count = values.len() / keyframe_values;
+        let (lower, upper, factor) = self.sample_indices(time)?;
+
+        let weights = match self.interpolation {
+            AnimationInterpolation::Step => values[lower * count..(lower + 1) * count].to_vec(),
+            AnimationInterpolation::Linear => {
+                let a = &values[lower * count..(lower + 1) * count];
+                let b = &values[upper * count..(upper + 1) * count];
+                a.iter().zip(b).map(|(a, b)| a + (b - a) * factor).collect()
+            }
+            AnimationInterpolation::CubicSpline => {
+                // Per keyframe: [in_tangents, values, out_tangents].
+                let group = |key: usize, part: usize| {
+                    let start = (key * 3 + part) * count;
+                    &values[start..start + count]
+                };
+                if lower == upper {
+                    group(lower, 1).to_vec()
+                } else {
+                    let dt = self.times[upper] - self.times[lower];
+                    let t = factor;
+                    let t2 = t * t;
+                    let t3 = t2 * t;
+                    let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                    let h10 = t3 - 2.0 * t2 + t;
+                    let h01 = -2.0 * t3 + 3.0 * t2;
+                    let h11 = t3 - t2;
+                    (0..count)
+                        .map(|i| {
+                            group(lower, 1)[i] * h00
+                                + group(lower, 2)[i] * h10 * dt
+                                + group(upper, 1)[i] * h01
+                                + group(upper, 0)[i] * h11 * dt
+                        })
+                        .collect()
+                }
+            }
+        };
+
+        Some(weights)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransformProperty {
     Translation,
     Rotation,
     Scale,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MaterialProperty {
     BaseColorFactor,
 }
@@ -321,6 +384,19 @@ pub enum AnimationTarget {
         material_index: usize,
         property: MaterialProperty,
     },
+    /// Writes the entity's [`MorphWeights`](crate::scene::components::MorphWeights).
+    MorphWeights { entity: hecs::Entity },
+}
+
+impl AnimationTarget {
+    /// The animated entity; `None` for material targets.
+    pub fn entity(&self) -> Option<hecs::Entity> {
+        match *self {
+            AnimationTarget::Transform { entity, .. }
+            | AnimationTarget::MorphWeights { entity } => Some(entity),
+            AnimationTarget::Material { .. } => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -355,6 +431,7 @@ impl AnimationClip {
         time: f32,
         transform_updates: &mut HashMap<hecs::Entity, TransformUpdate>,
         material_updates: &mut HashMap<usize, MaterialUpdate>,
+        morph_updates: &mut HashMap<hecs::Entity, Vec<f32>>,
     ) {
         for channel in &self.channels {
             match channel.target {
@@ -391,18 +468,41 @@ impl AnimationClip {
                         }
                     }
                 }
+                AnimationTarget::MorphWeights { entity } => {
+                    if let Some(weights) = channel.sampler.sample_weights(time) {
+                        morph_updates.insert(entity, weights);
+                    }
+                }
             }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Playback milestones reported by `Scene::drain_animation_events`, tagged
+/// with the index of the [`AnimationState`] they happened to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationEvent {
+    /// A non-looping state reached the end of its clip (or the start, when
+    /// playing in reverse) and stopped.
+    ClipFinished(usize),
+    /// A looping state wrapped around its clip.
+    ClipLooped(usize),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationState {
     pub clip_index: usize,
     pub time: f32,
+    /// Playback rate; negative values play the clip in reverse.
     pub speed: f32,
     pub looping: bool,
     pub playing: bool,
+    /// Set when a non-looping state runs off either end of its clip.
+    /// Cleared by [`Self::seek`] and [`Self::resume`].
+    pub finished: bool,
+    /// Disabled states are neither advanced nor sampled. A finished
+    /// crossfade disables the state it faded out of.
+    pub enabled: bool,
 }
 
 impl AnimationState {
@@ -413,33 +513,143 @@ impl AnimationState {
             speed: 1.0,
             looping: true,
             playing: true,
+            finished: false,
+            enabled: true,
         }
     }
 
+    /// Seeks to `time` seconds. The time is wrapped (looping) or clamped
+    /// into the clip on the next update without reporting any events.
+    /// Playing animations continue from there; paused ones keep sampling
+    /// this time on every update.
+    pub fn seek(&mut self, time: f32) {
+        if !time.is_finite() {
+            log::warn!("Ignoring non-finite animation time {}", time);
+            return;
+        }
+        self.time = time;
+        self.finished = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Continues playback. A finished state replays from where it stopped,
+    /// so seek first to restart it.
+    pub fn resume(&mut self) {
+        self.playing = true;
+        self.finished = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
     pub fn advance(&mut self, dt: f32, duration: f32) -> f32 {
-        if !self.playing {
+        self.step(0, dt, duration, &mut Vec::new())
+    }
+
+    /// Advances like [`Self::advance`], pushing an [`AnimationEvent`] tagged
+    /// with `index` for every loop and for finishing.
+    pub(crate) fn step(
+        &mut self,
+        index: usize,
+        dt: f32,
+        duration: f32,
+        events: &mut Vec<AnimationEvent>,
+    ) -> f32 {
+        let duration = duration.max(0.0);
+        // Seeks land anywhere; bring them into the clip silently first.
+        self.time = self.wrap_or_clamp(self.time, duration);
+
+        if !self.playing || duration <= 0.0 {
             return self.time;
         }
 
-        let mut time = self.time + dt * self.speed;
-        let duration = duration.max(0.0);
+        let time = self.time + dt * self.speed;
+        if self.looping {
+            let loops = (time / duration).floor().abs() as usize;
+            events.extend(std::iter::repeat_n(
+                AnimationEvent::ClipLooped(index),
+                loops,
+            ));
+        } else if (self.speed > 0.0 && time >= duration) || (self.speed < 0.0 && time <= 0.0) {
+            self.playing = false;
+            self.finished = true;
+            events.push(AnimationEvent::ClipFinished(index));
+        }
 
-        if duration > 0.0 {
-            if self.looping {
-                time = time.rem_euclid(duration);
-                if time < 0.0 {
-                    time += duration;
-                }
-            } else if time >= duration {
-                time = duration;
-                self.playing = false;
-            } else if time < 0.0 {
-                time = 0.0;
+        self.time = self.wrap_or_clamp(time, duration);
+        self.time
+    }
+
+    fn wrap_or_clamp(&self, time: f32, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            0.0
+        } else if self.looping {
+            time.rem_euclid(duration)
+        } else {
+            time.clamp(0.0, duration)
+        }
+    }
+}
+
+/// How the animation system combines [`AnimationState`]s (by index into
+/// `Scene::animation_states`) in a frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationPlayback {
+    /// A state sampled on its own.
+    Single(usize),
+    /// Crossfade between two states. `blend_t` runs from 0 (only `from`)
+    /// to 1 (only `to`) over `duration` seconds.
+    AnimationBlend {
+        from: usize,
+        to: usize,
+        blend_t: f32,
+        duration: f32,
+    },
+}
+
+impl AnimationPlayback {
+    /// A crossfade starting at `from`'s pose. Non-positive durations finish
+    /// on the next update.
+    pub fn blend(from: usize, to: usize, duration: f32) -> Self {
+        AnimationPlayback::AnimationBlend {
+            from,
+            to,
+            blend_t: 0.0,
+            duration: duration.max(0.0),
+        }
+    }
+
+    /// Moves a crossfade `dt` seconds along. Returns `true` once it has
+    /// fully reached its `to` state.
+    pub fn advance(&mut self, dt: f32) -> bool {
+        match self {
+            AnimationPlayback::Single(_) => false,
+            AnimationPlayback::AnimationBlend {
+                blend_t, duration, ..
+            } => {
+                *blend_t = if *duration > 0.0 {
+                    (*blend_t + dt / *duration).min(1.0)
+                } else {
+                    1.0
+                };
+                *blend_t >= 1.0
             }
         }
+    }
 
-        self.time = time;
-        time
+    pub fn involves(&self, state: usize) -> bool {
+        match *self {
+            AnimationPlayback::Single(index) => index == state,
+            AnimationPlayback::AnimationBlend { from, to, .. } => from == state || to == state,
+        }
     }
 }
 
@@ -450,14 +660,73 @@ pub struct TransformUpdate {
     pub scale: Option<Vec3>,
 }
 
+impl TransformUpdate {
+    /// Interpolates towards `other` by `t`. Properties only one side
+    /// animates are taken from that side.
+    pub fn blend(&self, other: &TransformUpdate, t: f32) -> TransformUpdate {
+        TransformUpdate {
+            translation: blend_option(self.translation, other.translation, |a, b| a.lerp(b, t)),
+            rotation: blend_option(self.rotation, other.rotation, |a, b| a.slerp(b, t)),
+            scale: blend_option(self.scale, other.scale, |a, b| a.lerp(b, t)),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct MaterialUpdate {
     pub base_color: Option<Vec4>,
 }
 
+impl MaterialUpdate {
+    pub fn blend(&self, other: &MaterialUpdate, t: f32) -> MaterialUpdate {
+        MaterialUpdate {
+            base_color: blend_option(self.base_color, other.base_color, |a, b| a.lerp(b, t)),
+        }
+    }
+}
+
+fn blend_option<T: Copy>(a: Option<T>, b: Option<T>, mix: impl FnOnce(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(mix(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Blends two keyed sets of updates, e.g. the samples of a crossfade's
+/// `from` and `to` clips.
+pub(crate) fn blend_updates<K, V>(
+    from: HashMap<K, V>,
+    mut to: HashMap<K, V>,
+    t: f32,
+    blend: impl Fn(&V, &V, f32) -> V,
+) -> HashMap<K, V>
+where
+    K: std::hash::Hash + Eq,
+{
+    let mut blended = HashMap::with_capacity(from.len().max(to.len()));
+    for (key, a) in from {
+        let value = match to.remove(&key) {
+            Some(b) => blend(&a, &b, t),
+            None => a,
+        };
+        blended.insert(key, value);
+    }
+    blended.extend(to);
+    blended
+}
+
+/// Morph weights blend per target; mismatched target counts switch halfway.
+pub(crate) fn blend_weights(a: &[f32], b: &[f32], t: f32) -> Vec<f32> {
+    if a.len() != b.len() {
+        return if t < 0.5 { a.to_vec() } else { b.to_vec() };
+    }
+    a.iter().zip(b).map(|(a, b)| a + (b - a) * t).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scene::{Scene, Transform, TransformComponent};
     use glam::{vec3, vec4};
     use hecs::World;
 
@@ -519,6 +788,20 @@ mod tests {
         assert_eq!(sampler.sample_vec4(2.0).unwrap(), vec4(0.0, 0.0, 1.0, 1.0));
     }
 
+    #[test]
+    fn sampler_interpolates_morph_weights_per_target() {
+        let sampler = AnimationSampler {
+            times: vec![0.0, 1.0],
+            output: AnimationOutput::Float(vec![0.0, 1.0, 1.0, 0.0]),
+            interpolation: AnimationInterpolation::Linear,
+        };
+
+        assert_eq!(sampler.sample_weights(0.0).unwrap(), vec![0.0, 1.0]);
+        assert_eq!(sampler.sample_weights(0.25).unwrap(), vec![0.25, 0.75]);
+        assert_eq!(sampler.sample_weights(1.0).unwrap(), vec![1.0, 0.0]);
+        assert!(sampler.sample_vec3(0.5).is_none());
+    }
+
     #[test]
     fn animation_clip_writes_transform_and_material_updates() {
         let mut world = World::new();
@@ -553,7 +836,12 @@ mod tests {
 
         let mut transform_updates = HashMap::new();
         let mut material_updates = HashMap::new();
-        clip.sample(0.5, &mut transform_updates, &mut material_updates);
+        clip.sample(
+            0.5,
+            &mut transform_updates,
+            &mut material_updates,
+            &mut HashMap::new(),
+        );
 
         let transform = transform_updates.get(&entity).expect("missing transform");
         assert!(transform.rotation.is_none());
@@ -585,6 +873,179 @@ mod tests {
         assert!((advanced - 2.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn reverse_playback_stops_at_the_start_of_the_clip() {
+        let mut state = AnimationState::new(0);
+        state.looping = false;
+        state.speed = -2.0;
+        state.seek(1.5);
+
+        let mut events = Vec::new();
+        assert!((state.step(3, 0.5, 2.0, &mut events) - 0.5).abs() < 1e-6);
+        assert!(events.is_empty());
+
+        assert_eq!(state.step(3, 0.5, 2.0, &mut events), 0.0);
+        assert!(state.is_finished());
+        assert!(!state.is_playing());
+        assert_eq!(events, vec![AnimationEvent::ClipFinished(3)]);
+
+        let mut looping = AnimationState::new(0);
+        looping.speed = -1.0;
+        looping.seek(0.25);
+        let time = looping.step(1, 0.5, 2.0, &mut events);
+        assert!((time - 1.75).abs() < 1e-6);
+        assert_eq!(events.last(), Some(&AnimationEvent::ClipLooped(1)));
+    }
+
+    #[test]
+    fn non_looping_state_reports_finished_once() {
+        let mut scene = Scene::new();
+        let entity = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+
+        let mut clip = AnimationClip::new("hop");
+        clip.add_channel(AnimationChannel {
+            sampler: AnimationSampler {
+                times: vec![0.0, 1.0],
+                output: AnimationOutput::Vec3(vec![Vec3::ZERO, Vec3::Y]),
+                interpolation: AnimationInterpolation::Linear,
+            },
+            target: AnimationTarget::Transform {
+                entity,
+                property: TransformProperty::Translation,
+            },
+        });
+        let clip_index = scene.add_animation_clip(clip);
+        let looping = scene.play_animation(clip_index, true).unwrap();
+        let once = scene.play_animation(clip_index, false).unwrap();
+
+        scene.update(0.75);
+        assert!(scene.drain_animation_events().is_empty());
+
+        scene.update(0.5);
+        scene.update(0.5);
+        assert_eq!(
+            scene.drain_animation_events(),
+            vec![
+                AnimationEvent::ClipLooped(looping),
+                AnimationEvent::ClipFinished(once),
+            ]
+        );
+        assert!(scene.animation_states()[once].is_finished());
+        assert_eq!(scene.animation_states()[once].time, 1.0);
+
+        assert!(scene.stop_animation(looping));
+        scene.update(0.5);
+        assert!(scene.drain_animation_events().is_empty());
+        assert!(!scene.stop_animation(once + 1));
+    }
+
+    #[test]
+    fn paused_animation_samples_the_same_pose_every_update() {
+        let mut scene = Scene::new();
+        let entity = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+
+        let mut clip = AnimationClip::new("slide");
+        clip.add_channel(AnimationChannel {
+            sampler: AnimationSampler {
+                times: vec![0.0, 2.0],
+                output: AnimationOutput::Vec3(vec![Vec3::ZERO, vec3(4.0, 0.0, 0.0)]),
+                interpolation: AnimationInterpolation::Linear,
+            },
+            target: AnimationTarget::Transform {
+                entity,
+                property: TransformProperty::Translation,
+            },
+        });
+        let clip_index = scene.add_animation_clip(clip);
+        let state_index = scene.play_animation(clip_index, true).unwrap();
+
+        let state = scene.animation_state_mut(state_index).unwrap();
+        state.seek(0.5);
+        state.pause();
+        assert!(!state.is_playing());
+
+        let translation = |scene: &Scene| {
+            scene
+                .world
+                .get::<&TransformComponent>(entity)
+                .unwrap()
+                .0
+                .translation
+        };
+        let mut poses = Vec::new();
+        for _ in 0..3 {
+            scene.update(0.25);
+            poses.push(translation(&scene));
+        }
+        assert!(poses.iter().all(|pose| *pose == vec3(1.0, 0.0, 0.0)));
+        assert_eq!(scene.animation_states()[state_index].time, 0.5);
+
+        let state = scene.animation_state_mut(state_index).unwrap();
+        state.resume();
+        scene.update(0.25);
+        assert!((translation(&scene).x - 1.5).abs() < 1e-5);
+        assert!(scene.animation_state_mut(state_index + 1).is_none());
+    }
+
+    #[test]
+    fn crossfade_blends_translation_between_clips() {
+        let mut scene = Scene::new();
+        let entity = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+
+        let hold = |value: Vec3| {
+            let mut clip = AnimationClip::new("hold");
+            clip.add_channel(AnimationChannel {
+                sampler: AnimationSampler {
+                    times: vec![0.0, 1.0],
+                    output: AnimationOutput::Vec3(vec![value, value]),
+                    interpolation: AnimationInterpolation::Linear,
+                },
+                target: AnimationTarget::Transform {
+                    entity,
+                    property: TransformProperty::Translation,
+                },
+            });
+            clip
+        };
+        let idle = scene.add_animation_clip(hold(Vec3::ZERO));
+        let walk = scene.add_animation_clip(hold(vec3(4.0, 2.0, 0.0)));
+        let from = scene.play_animation(idle, true).unwrap();
+        let to = scene.play_animation(walk, true).unwrap();
+
+        assert!(scene.crossfade(from, to, 1.0).is_ok());
+        assert!(scene.crossfade(from, from, 1.0).is_err());
+        assert!(scene.crossfade(from, 5, 1.0).is_err());
+
+        let translation = |scene: &Scene| {
+            scene
+                .world
+                .get::<&TransformComponent>(entity)
+                .unwrap()
+                .0
+                .translation
+        };
+
+        scene.update(0.5);
+        let mid = translation(&scene);
+        assert!(mid.x > 0.0 && mid.x < 4.0);
+        assert!(mid.abs_diff_eq(vec3(2.0, 1.0, 0.0), 1e-5));
+        assert_eq!(scene.animation_blends().len(), 1);
+
+        scene.update(0.6);
+        assert!(translation(&scene).abs_diff_eq(vec3(4.0, 2.0, 0.0), 1e-5));
+        assert!(scene.animation_blends().is_empty());
+        assert!(!scene.animation_states()[from].enabled);
+
+        scene.update(0.1);
+        assert!(translation(&scene).abs_diff_eq(vec3(4.0, 2.0, 0.0), 1e-5));
+    }
+
     #[test]
     fn cubic_spline_vec3_interpolation() {
         // Data format: [in_tangent_0, value_0, out_tangent_0, in_tangent_1, value_1, out_tangent_1]
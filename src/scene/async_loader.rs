@@ -0,0 +1,307 @@
+//! Background glTF loading. File IO and image decoding run off the render
+//! thread (a worker thread on native, `spawn_local` + `fetch` on wasm);
+//! GPU uploads and entity spawning are spread over frames by
+//! [`GltfLoadHandle::poll`].
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::renderer::Renderer;
+use crate::scene::loader::{GltfFinalizer, PreparedGltf, SceneLoader};
+use crate::scene::Scene;
+
+/// Shared progress of a background load, for UI overlays.
+pub type GltfLoadProgressHandle = Arc<Mutex<GltfLoadProgress>>;
+
+type PreparedSlot = Arc<Mutex<Option<Result<PreparedGltf, String>>>>;
+type OnComplete = Box<dyn FnOnce(&mut Scene)>;
+
+/// Textures plus meshes uploaded per [`GltfLoadHandle::poll`] by default.
+const DEFAULT_UPLOADS_PER_FRAME: usize = 16;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GltfLoadStage {
+    /// Reading the glTF file and its buffers.
+    #[default]
+    Reading,
+    /// Decoding textures to RGBA8.
+    Decoding,
+    /// Uploading textures and meshes to the GPU.
+    Uploading,
+    Complete,
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GltfLoadProgress {
+    pub stage: GltfLoadStage,
+    /// Bytes of the glTF file and its external resources read so far.
+    pub bytes_loaded: u64,
+    pub textures_decoded: usize,
+    pub textures_total: usize,
+    pub meshes_uploaded: usize,
+    pub meshes_total: usize,
+}
+
+impl GltfLoadProgress {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.stage, GltfLoadStage::Complete | GltfLoadStage::Failed)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GltfLoadStatus {
+    Pending,
+    Complete,
+    Failed(String),
+}
+
+/// A glTF file loading in the background, created by
+/// [`SceneLoader::load_gltf_async`]. Call [`Self::poll`] once per frame
+/// (e.g. from a GPU system) until it stops returning
+/// [`GltfLoadStatus::Pending`].
+pub struct GltfLoadHandle {
+    path: PathBuf,
+    scale: f32,
+    uploads_per_frame: usize,
+    progress: GltfLoadProgressHandle,
+    prepared: PreparedSlot,
+    finalizer: Option<GltfFinalizer>,
+    on_complete: Option<OnComplete>,
+    status: GltfLoadStatus,
+}
+
+impl GltfLoadHandle {
+    pub(crate) fn start(path: &Path, scale: f32) -> Self {
+        let progress: GltfLoadProgressHandle = Arc::default();
+        let prepared: PreparedSlot = Arc::default();
+
+        spawn_prepare(path.to_path_buf(), progress.clone(), prepared.clone());
+
+        Self {
+            path: path.to_path_buf(),
+            scale,
+            uploads_per_frame: DEFAULT_UPLOADS_PER_FRAME,
+            progress,
+            prepared,
+            finalizer: None,
+            on_complete: None,
+            status: GltfLoadStatus::Pending,
+        }
+    }
+
+    /// Runs `callback` right after the scene has been spawned.
+    pub fn with_on_complete(mut self, callback: impl FnOnce(&mut Scene) + 'static) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Limits how many textures and meshes are uploaded per [`Self::poll`].
+    pub fn with_uploads_per_frame(mut self, uploads: usize) -> Self {
+        self.uploads_per_frame = uploads.max(1);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn progress(&self) -> GltfLoadProgress {
+        lock(&self.progress)
+    }
+
+    pub fn progress_handle(&self) -> GltfLoadProgressHandle {
+        Arc::clone(&self.progress)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.status != GltfLoadStatus::Pending
+    }
+
+    /// Uploads the next batch of decoded data once the background work is
+    /// done, and spawns the scene when everything is on the GPU.
+    pub fn poll(&mut self, scene: &mut Scene, renderer: &mut Renderer) -> GltfLoadStatus {
+        if self.status != GltfLoadStatus::Pending {
+            return self.status.clone();
+        }
+
+        if self.finalizer.is_none() {
+            let prepared = self
+                .prepared
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .take();
+            match prepared {
+                None => return GltfLoadStatus::Pending,
+                Some(Ok(prepared)) => {
+                    let finalizer = GltfFinalizer::new(prepared, self.scale);
+                    update(&self.progress, |progress| {
+                        progress.stage = GltfLoadStage::Uploading;
+                        progress.meshes_total = finalizer.mesh_count();
+                    });
+                    self.finalizer = Some(finalizer);
+                }
+                Some(Err(err)) => return self.fail(err),
+            }
+        }
+
+        let Some(finalizer) = self.finalizer.as_mut() else {
+            return GltfLoadStatus::Pending;
+        };
+
+        match finalizer.step(scene, renderer, self.uploads_per_frame) {
+            Ok(spawned) => {
+                let meshes_uploaded = finalizer.meshes_uploaded();
+                update(&self.progress, |progress| {
+                    progress.meshes_uploaded = meshes_uploaded;
+                });
+                if !spawned {
+                    return GltfLoadStatus::Pending;
+                }
+            }
+            Err(err) => return self.fail(err),
+        }
+
+        if finalizer.texture_count() > 0 {
+            renderer.update_texture_bind_group(&scene.assets);
+        }
+        self.finalizer = None;
+        update(&self.progress, |progress| {
+            progress.stage = GltfLoadStage::Complete
+        });
+        if let Some(callback) = self.on_complete.take() {
+            callback(scene);
+        }
+
+        self.status = GltfLoadStatus::Complete;
+        self.status.clone()
+    }
+
+    fn fail(&mut self, err: String) -> GltfLoadStatus {
+        log::error!("Failed to load glTF {:?}: {}", self.path, err);
+        self.finalizer = None;
+        update(&self.progress, |progress| {
+            progress.stage = GltfLoadStage::Failed
+        });
+        self.status = GltfLoadStatus::Failed(err);
+        self.status.clone()
+    }
+}
+
+fn lock(progress: &GltfLoadProgressHandle) -> GltfLoadProgress {
+    progress
+        .lock()
+        .map(|guard| *guard)
+        .unwrap_or_else(|poisoned| *poisoned.into_inner())
+}
+
+fn update(progress: &GltfLoadProgressHandle, apply: impl FnOnce(&mut GltfLoadProgress)) {
+    let mut guard = progress
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    apply(&mut guard);
+}
+
+fn finish_prepare(slot: &PreparedSlot, result: Result<PreparedGltf, String>) {
+    *slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(result);
+}
+
+/// Records the imported file and moves on to texture decoding.
+fn begin_decoding(progress: &GltfLoadProgressHandle, document: &gltf::Document) {
+    update(progress, |progress| {
+        progress.stage = GltfLoadStage::Decoding;
+        progress.textures_total = document.textures().len();
+        progress.meshes_total = document.meshes().len();
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_prepare(path: PathBuf, progress: GltfLoadProgressHandle, slot: PreparedSlot) {
+    let worker_progress = progress.clone();
+    let worker_slot = slot.clone();
+    let spawned = std::thread::Builder::new()
+        .name("gltf-loader".into())
+        .spawn(move || {
+            let result = prepare_native(&path, &worker_progress);
+            finish_prepare(&worker_slot, result);
+        });
+
+    if let Err(err) = spawned {
+        finish_prepare(
+            &slot,
+            Err(format!("Failed to spawn glTF loader thread: {}", err)),
+        );
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn prepare_native(path: &Path, progress: &GltfLoadProgressHandle) -> Result<PreparedGltf, String> {
+    let import = SceneLoader::import_gltf(path)?;
+
+    let (document, buffers, _) = &import;
+    let file_bytes = std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    let external_bytes: u64 = document
+        .buffers()
+        .filter(|buffer| matches!(buffer.source(), gltf::buffer::Source::Uri(_)))
+        .filter_map(|buffer| buffers.get(buffer.index()))
+        .map(|data| data.len() as u64)
+        .sum();
+    update(progress, |progress| {
+        progress.bytes_loaded = file_bytes + external_bytes
+    });
+    begin_decoding(progress, document);
+
+    SceneLoader::prepare_gltf(path, import, &mut || {
+        update(progress, |progress| progress.textures_decoded += 1)
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn_prepare(path: PathBuf, progress: GltfLoadProgressHandle, slot: PreparedSlot) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let import = SceneLoader::import_gltf_web_async(&path, &mut |bytes| {
+            update(&progress, |progress| progress.bytes_loaded += bytes as u64)
+        })
+        .await;
+
+        let result = import.and_then(|import| {
+            begin_decoding(&progress, &import.0);
+            SceneLoader::prepare_gltf(&path, import, &mut || {
+                update(&progress, |progress| progress.textures_decoded += 1)
+            })
+        });
+        finish_prepare(&slot, result);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_stages() {
+        let mut progress = GltfLoadProgress::default();
+        assert!(!progress.is_finished());
+        progress.stage = GltfLoadStage::Uploading;
+        assert!(!progress.is_finished());
+        progress.stage = GltfLoadStage::Complete;
+        assert!(progress.is_finished());
+        progress.stage = GltfLoadStage::Failed;
+        assert!(progress.is_finished());
+    }
+
+    #[test]
+    fn missing_file_fails_without_renderer_work() {
+        let handle = SceneLoader::load_gltf_async("does/not/exist.gltf", 1.0);
+        let progress = handle.progress_handle();
+        let slot = handle.prepared.clone();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while slot.lock().unwrap().is_none() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert!(matches!(slot.lock().unwrap().as_ref(), Some(Err(_))));
+        assert_eq!(lock(&progress).textures_decoded, 0);
+    }
+}
@@ -79,6 +79,12 @@ impl<'w> EntityBuilder<'w> {
         self
     }
 
+    /// Add a pulse (sine wave) property animation component
+    pub fn with_pulse_animation(mut self, pulse: PulseAnimation) -> Self {
+        self.builder.add(pulse);
+        self
+    }
+
     /// Spawn the entity into the world
     pub fn spawn(&mut self) -> hecs::Entity {
         self.world.spawn(self.builder.build())
@@ -1,21 +1,121 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug)]
+/// Smallest and largest vertical field of view accepted by [`Camera::set_fov_y`].
+pub const MIN_FOV_Y_RADIANS: f32 = 1.0e-3;
+pub const MAX_FOV_Y_RADIANS: f32 = std::f32::consts::PI - 1.0e-3;
+
+/// Far/near ratios above this lose most depth precision with a standard
+/// (non reverse-Z) depth buffer.
+pub const DEPTH_PRECISION_WARNING_RATIO: f32 = 100_000.0;
+
+/// Largest pitch [`Camera::orbit_around`] accepts. Orbiting exactly over a
+/// pole would point the view along `up` and collapse the look-at basis.
+pub const MAX_ORBIT_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 1.0e-3;
+const MIN_ORBIT_DISTANCE: f32 = 1.0e-3;
+
+const FALLBACK_NEAR: f32 = 0.1;
+const FALLBACK_FAR: f32 = 100.0;
+
+/// How a [`Camera`] projects view space onto the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CameraProjection {
+    /// `fov` is the vertical field of view in radians.
+    Perspective { fov: f32, near: f32, far: f32 },
+    /// Parallel projection of a `width` x `height` view-space rectangle.
+    /// The rectangle is widened or heightened to the viewport aspect so the
+    /// requested area always stays fully visible.
+    Orthographic {
+        width: f32,
+        height: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl CameraProjection {
+    pub fn clip_planes(&self) -> (f32, f32) {
+        match *self {
+            CameraProjection::Perspective { near, far, .. }
+            | CameraProjection::Orthographic { near, far, .. } => (near, far),
+        }
+    }
+
+    pub fn is_orthographic(&self) -> bool {
+        matches!(self, CameraProjection::Orthographic { .. })
+    }
+}
+
+impl Default for CameraProjection {
+    fn default() -> Self {
+        CameraProjection::Perspective {
+            fov: 60f32.to_radians(),
+            near: FALLBACK_NEAR,
+            far: FALLBACK_FAR,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Camera {
     pub eye: Vec3,
     pub target: Vec3,
     pub up: Vec3,
-    pub fov_y_radians: f32,
+    pub projection: CameraProjection,
+}
+
+/// Projection matrix together with the clip planes it was built from.
+///
+/// Consumers that need both (the post-process depth linearisation) take this
+/// instead of separate values so the planes can never drift from the matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProjectionMatrix {
+    pub proj: Mat4,
     pub near: f32,
     pub far: f32,
+    /// Depth is linear in `[near, far]` rather than hyperbolic.
+    pub orthographic: bool,
 }
 
 impl Camera {
+    /// An orthographic camera at the default position looking at the origin.
+    pub fn orthographic(width: f32, height: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: CameraProjection::Orthographic {
+                width,
+                height,
+                near,
+                far,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// A camera placed by a node's world matrix. Like glTF cameras it looks
+    /// down the local -Z axis with +Y up; scale in `world` is ignored.
+    pub fn from_world_matrix(world: Mat4, projection: CameraProjection) -> Self {
+        let eye = world.transform_point3(Vec3::ZERO);
+        let forward = world
+            .transform_vector3(Vec3::NEG_Z)
+            .try_normalize()
+            .unwrap_or(Vec3::NEG_Z);
+        let up = world
+            .transform_vector3(Vec3::Y)
+            .try_normalize()
+            .unwrap_or(Vec3::Y);
+        Self {
+            eye,
+            target: eye + forward,
+            up,
+            projection,
+        }
+    }
+
     pub fn view(&self) -> Mat4 {
         Mat4::look_at_rh(self.eye, self.target, self.up)
     }
     pub fn proj(&self, aspect: f32) -> Mat4 {
-        Mat4::perspective_rh(self.fov_y_radians, aspect, self.near, self.far)
+        self.projection_matrix(aspect).proj
     }
     pub fn view_proj(&self, aspect: f32) -> Mat4 {
         self.proj(aspect) * self.view()
@@ -23,6 +123,231 @@ impl Camera {
     pub fn position(&self) -> Vec3 {
         self.eye
     }
+
+    /// Builds the projection from sanitized parameters. Invalid values that
+    /// were written directly to [`Self::projection`] fall back to defaults
+    /// instead of producing NaNs downstream.
+    pub fn projection_matrix(&self, aspect: f32) -> ProjectionMatrix {
+        let (near, far) = self.clip_planes();
+        let (near, far) = if validate_clip_planes(near, far).is_ok() {
+            (near, far)
+        } else {
+            (FALLBACK_NEAR, FALLBACK_FAR)
+        };
+        let aspect = if aspect.is_finite() && aspect > 0.0 {
+            aspect
+        } else {
+            1.0
+        };
+
+        let proj = match self.projection {
+            CameraProjection::Perspective { fov, .. } => {
+                let fov = if fov.is_finite() {
+                    fov.clamp(MIN_FOV_Y_RADIANS, MAX_FOV_Y_RADIANS)
+                } else {
+                    60f32.to_radians()
+                };
+                Mat4::perspective_rh(fov, aspect, near, far)
+            }
+            CameraProjection::Orthographic { width, height, .. } => {
+                let (width, height) = fit_orthographic_extent(width, height, aspect);
+                Mat4::orthographic_rh(
+                    -width * 0.5,
+                    width * 0.5,
+                    -height * 0.5,
+                    height * 0.5,
+                    near,
+                    far,
+                )
+            }
+        };
+
+        ProjectionMatrix {
+            proj,
+            near,
+            far,
+            orthographic: self.projection.is_orthographic(),
+        }
+    }
+
+    /// Vertical field of view in radians, or `None` for orthographic cameras.
+    pub fn fov_y(&self) -> Option<f32> {
+        match self.projection {
+            CameraProjection::Perspective { fov, .. } => Some(fov),
+            CameraProjection::Orthographic { .. } => None,
+        }
+    }
+
+    /// Sets the vertical field of view, clamped to (0, π). Fails for
+    /// orthographic cameras.
+    pub fn set_fov_y(&mut self, fov_y_radians: f32) -> Result<(), String> {
+        if !fov_y_radians.is_finite() {
+            return Err(format!(
+                "Field of view must be finite, got {}",
+                fov_y_radians
+            ));
+        }
+        let CameraProjection::Perspective { fov, .. } = &mut self.projection else {
+            return Err("Orthographic cameras have no field of view".to_string());
+        };
+        let clamped = fov_y_radians.clamp(MIN_FOV_Y_RADIANS, MAX_FOV_Y_RADIANS);
+        if clamped != fov_y_radians {
+            log::warn!(
+                "Field of view {} rad is outside (0, π); clamping to {}",
+                fov_y_radians,
+                clamped
+            );
+        }
+        *fov = clamped;
+        Ok(())
+    }
+
+    /// Sets the clip planes. Requires `0 < near < far`.
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) -> Result<(), String> {
+        validate_clip_planes(near, far)?;
+        let ratio = far / near;
+        if ratio > DEPTH_PRECISION_WARNING_RATIO && !self.projection.is_orthographic() {
+            log::warn!(
+                "Clip plane ratio far/near = {:.0} exceeds {:.0}; expect depth precision \
+                 artifacts (raise near or consider a reverse-Z depth buffer)",
+                ratio,
+                DEPTH_PRECISION_WARNING_RATIO
+            );
+        }
+        match &mut self.projection {
+            CameraProjection::Perspective {
+                near: n, far: f, ..
+            }
+            | CameraProjection::Orthographic {
+                near: n, far: f, ..
+            } => {
+                *n = near;
+                *f = far;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clip_planes(&self) -> (f32, f32) {
+        self.projection.clip_planes()
+    }
+
+    /// A copy of this camera on an orbit around `target`. `yaw` turns
+    /// around world Y with 0 on the +X side, `pitch` lifts the eye towards
+    /// +Y and is clamped to [`MAX_ORBIT_PITCH`]. The projection is kept and
+    /// `up` becomes +Y. Non-finite arguments return the camera unchanged.
+    pub fn orbit_around(&self, target: Vec3, yaw: f32, pitch: f32, distance: f32) -> Camera {
+        if !target.is_finite() || !yaw.is_finite() || !pitch.is_finite() || !distance.is_finite() {
+            log::warn!("Ignoring non-finite orbit parameters");
+            return *self;
+        }
+        let pitch = pitch.clamp(-MAX_ORBIT_PITCH, MAX_ORBIT_PITCH);
+        let distance = distance.max(MIN_ORBIT_DISTANCE);
+        let (sin_pitch, cos_pitch) = pitch.sin_cos();
+        let (sin_yaw, cos_yaw) = yaw.sin_cos();
+        Camera {
+            eye: target + Vec3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw) * distance,
+            target,
+            up: Vec3::Y,
+            projection: self.projection,
+        }
+    }
+
+    /// Yaw, pitch and distance of the eye around the target, in the
+    /// convention of [`Self::orbit_around`].
+    pub fn orbit_angles(&self) -> (f32, f32, f32) {
+        let offset = self.eye - self.target;
+        let distance = offset.length().max(MIN_ORBIT_DISTANCE);
+        let yaw = offset.z.atan2(offset.x);
+        let pitch = (offset.y / distance).clamp(-1.0, 1.0).asin();
+        (yaw, pitch, distance)
+    }
+
+    /// Orbits around the target by a mouse drag of `delta` pixels:
+    /// horizontal movement turns, dragging down lifts the eye.
+    pub fn handle_mouse_drag(&self, delta: Vec2, sensitivity: f32) -> Camera {
+        let (yaw, pitch, distance) = self.orbit_angles();
+        self.orbit_around(
+            self.target,
+            yaw + delta.x * sensitivity,
+            pitch + delta.y * sensitivity,
+            distance,
+        )
+    }
+
+    /// Moves towards the target by `zoom_speed` of the distance per scroll
+    /// notch; negative `delta` moves away.
+    pub fn handle_scroll(&self, delta: f32, zoom_speed: f32) -> Camera {
+        let (yaw, pitch, distance) = self.orbit_angles();
+        let factor = (1.0 - zoom_speed).clamp(0.01, 0.99);
+        self.orbit_around(self.target, yaw, pitch, distance * factor.powf(delta))
+    }
+
+    /// Points the camera at the bounding box `min..max`, keeping the current
+    /// view direction, and derives clip planes that tightly enclose it.
+    /// Orthographic cameras also resize their view rectangle to the box.
+    pub fn frame_bounds(&mut self, min: Vec3, max: Vec3) {
+        let center = (min + max) * 0.5;
+        let radius = ((max - min).length() * 0.5).max(1.0e-3);
+        let direction = (self.eye - self.target).try_normalize().unwrap_or(Vec3::Z);
+        let distance = match &mut self.projection {
+            CameraProjection::Perspective { fov, .. } => {
+                let half_fov = fov.clamp(MIN_FOV_Y_RADIANS, MAX_FOV_Y_RADIANS) * 0.5;
+                radius / half_fov.sin()
+            }
+            CameraProjection::Orthographic { width, height, .. } => {
+                *width = radius * 2.0;
+                *height = radius * 2.0;
+                radius * 2.0
+            }
+        };
+
+        self.target = center;
+        self.eye = center + direction * distance;
+
+        let near = (distance - radius).max(radius * 0.01);
+        let far = distance + radius * 2.0;
+        // Both planes derive from a positive radius, so this cannot fail.
+        let _ = self.set_clip_planes(near, far);
+    }
+}
+
+/// Grows `width` or `height` so their ratio matches `aspect`. Non-positive
+/// or non-finite extents fall back to a 2 x 2 rectangle.
+fn fit_orthographic_extent(width: f32, height: f32, aspect: f32) -> (f32, f32) {
+    let valid = |extent: f32| extent.is_finite() && extent > 0.0;
+    let (width, height) = if valid(width) && valid(height) {
+        (width, height)
+    } else {
+        (2.0, 2.0)
+    };
+    if width / height < aspect {
+        (height * aspect, height)
+    } else {
+        (width, width / aspect)
+    }
+}
+
+fn validate_clip_planes(near: f32, far: f32) -> Result<(), String> {
+    if !near.is_finite() || !far.is_finite() {
+        return Err(format!(
+            "Clip planes must be finite, got near={} far={}",
+            near, far
+        ));
+    }
+    if near <= 0.0 {
+        return Err(format!(
+            "Near plane must be greater than zero, got {}",
+            near
+        ));
+    }
+    if far <= near {
+        return Err(format!(
+            "Far plane ({}) must be greater than near plane ({})",
+            far, near
+        ));
+    }
+    Ok(())
 }
 
 impl Default for Camera {
@@ -31,9 +356,7 @@ impl Default for Camera {
             eye: Vec3::new(0.0, 0.0, 3.0),
             target: Vec3::ZERO,
             up: Vec3::Y,
-            fov_y_radians: 60f32.to_radians(),
-            near: 0.1,
-            far: 100.0,
+            projection: CameraProjection::default(),
         }
     }
 }
@@ -51,4 +374,203 @@ mod tests {
         let eps = 1e-4;
         assert!(id.abs_diff_eq(Mat4::IDENTITY, eps));
     }
+
+    #[test]
+    fn set_fov_rejects_non_finite() {
+        let mut cam = Camera::default();
+        assert!(cam.set_fov_y(f32::NAN).is_err());
+        assert!(cam.set_fov_y(f32::INFINITY).is_err());
+        assert_eq!(cam.fov_y(), Some(60f32.to_radians()));
+    }
+
+    #[test]
+    fn set_fov_clamps_into_open_range() {
+        let mut cam = Camera::default();
+        cam.set_fov_y(0.0).unwrap();
+        assert_eq!(cam.fov_y(), Some(MIN_FOV_Y_RADIANS));
+        cam.set_fov_y(4.0).unwrap();
+        assert_eq!(cam.fov_y(), Some(MAX_FOV_Y_RADIANS));
+    }
+
+    #[test]
+    fn set_clip_planes_rejects_invalid_values() {
+        let mut cam = Camera::default();
+        assert!(cam.set_clip_planes(0.0, 10.0).is_err());
+        assert!(cam.set_clip_planes(-1.0, 10.0).is_err());
+        assert!(cam.set_clip_planes(1.0, 1.0).is_err());
+        assert!(cam.set_clip_planes(2.0, 1.0).is_err());
+        assert!(cam.set_clip_planes(f32::NAN, 10.0).is_err());
+        assert!(cam.set_clip_planes(0.1, f32::INFINITY).is_err());
+        assert_eq!(cam.clip_planes(), (0.1, 100.0));
+    }
+
+    #[test]
+    fn set_clip_planes_accepts_valid_values() {
+        let mut cam = Camera::default();
+        cam.set_clip_planes(0.5, 500.0).unwrap();
+        assert_eq!(cam.clip_planes(), (0.5, 500.0));
+    }
+
+    #[test]
+    fn projection_carries_planes_used_by_matrix() {
+        let mut cam = Camera::default();
+        cam.set_clip_planes(0.25, 40.0).unwrap();
+        let projection = cam.projection_matrix(1.5);
+
+        assert_eq!((projection.near, projection.far), (0.25, 40.0));
+        assert!(!projection.orthographic);
+        let expected = Mat4::perspective_rh(cam.fov_y().unwrap(), 1.5, 0.25, 40.0);
+        assert!(projection.proj.abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn projection_falls_back_when_fields_are_invalid() {
+        let cam = Camera {
+            projection: CameraProjection::Perspective {
+                fov: f32::NAN,
+                near: 0.0,
+                far: -1.0,
+            },
+            ..Camera::default()
+        };
+        let projection = cam.projection_matrix(1.0);
+
+        assert_eq!(
+            (projection.near, projection.far),
+            (FALLBACK_NEAR, FALLBACK_FAR)
+        );
+        assert!(projection.proj.is_finite());
+    }
+
+    #[test]
+    fn orbit_around_places_eye_and_round_trips() {
+        let base = Camera::orthographic(4.0, 4.0, 0.5, 50.0);
+        let target = Vec3::new(1.0, 2.0, 3.0);
+        let cam = base.orbit_around(target, 0.0, 0.0, 5.0);
+        assert!(cam.eye.abs_diff_eq(target + Vec3::X * 5.0, 1e-5));
+        assert_eq!(cam.target, target);
+        assert_eq!(cam.projection, base.projection);
+
+        let cam = base.orbit_around(target, 1.2, -0.4, 7.0);
+        let (yaw, pitch, distance) = cam.orbit_angles();
+        assert!((yaw - 1.2).abs() < 1e-5);
+        assert!((pitch + 0.4).abs() < 1e-5);
+        assert!((distance - 7.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn orbit_stays_clear_of_the_poles() {
+        let cam = Camera::default();
+        for pitch in [
+            std::f32::consts::FRAC_PI_2,
+            10.0,
+            -std::f32::consts::FRAC_PI_2,
+        ] {
+            let orbit = cam.orbit_around(Vec3::ZERO, 0.7, pitch, 3.0);
+            let (_, clamped, _) = orbit.orbit_angles();
+            assert!((clamped.abs() - MAX_ORBIT_PITCH).abs() < 1e-3);
+            assert!(orbit.view().is_finite());
+        }
+
+        // Dragging past the pole clamps instead of flipping over it.
+        let top = cam
+            .orbit_around(Vec3::ZERO, 0.7, MAX_ORBIT_PITCH, 3.0)
+            .handle_mouse_drag(Vec2::new(0.0, 500.0), 0.01);
+        let (yaw, pitch, _) = top.orbit_angles();
+        assert!((pitch - MAX_ORBIT_PITCH).abs() < 1e-3);
+        assert!((yaw - 0.7).abs() < 1e-3);
+        assert!(top.view().is_finite());
+    }
+
+    #[test]
+    fn scroll_zooms_and_rejects_degenerate_input() {
+        let cam = Camera::default().orbit_around(Vec3::ZERO, 0.0, 0.0, 10.0);
+        let closer = cam.handle_scroll(1.0, 0.1);
+        assert!((closer.eye.distance(closer.target) - 9.0).abs() < 1e-4);
+        let farther = cam.handle_scroll(-1.0, 0.1);
+        assert!(farther.eye.distance(farther.target) > 10.0);
+
+        let collapsed = cam.handle_scroll(1.0e6, 0.5);
+        assert!(collapsed.eye.distance(collapsed.target) >= MIN_ORBIT_DISTANCE * 0.999);
+        assert!(collapsed.view().is_finite());
+
+        let unchanged = cam.orbit_around(Vec3::ZERO, f32::NAN, 0.0, 1.0);
+        assert_eq!(unchanged.eye, cam.eye);
+    }
+
+    #[test]
+    fn frame_bounds_encloses_box() {
+        let mut cam = Camera::default();
+        cam.frame_bounds(Vec3::splat(-2.0), Vec3::splat(2.0));
+
+        let distance = cam.eye.distance(cam.target);
+        let radius = Vec3::splat(2.0).length();
+        assert_eq!(cam.target, Vec3::ZERO);
+        let (near, far) = cam.clip_planes();
+        assert!(near > 0.0 && near <= distance - radius + 1e-4);
+        assert!(far >= distance + radius);
+    }
+
+    #[test]
+    fn orthographic_projection_is_linear_and_fits_aspect() {
+        let cam = Camera::orthographic(4.0, 2.0, 0.5, 50.0);
+        let projection = cam.projection_matrix(1.0);
+
+        assert!(projection.orthographic);
+        assert_eq!((projection.near, projection.far), (0.5, 50.0));
+        // A square viewport keeps the full 4-unit width visible.
+        let expected = Mat4::orthographic_rh(-2.0, 2.0, -2.0, 2.0, 0.5, 50.0);
+        assert!(projection.proj.abs_diff_eq(expected, 1e-6));
+
+        let wide = cam.projection_matrix(4.0);
+        let expected = Mat4::orthographic_rh(-4.0, 4.0, -1.0, 1.0, 0.5, 50.0);
+        assert!(wide.proj.abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn perspective_maps_points_to_expected_ndc() {
+        let mut cam = Camera {
+            eye: Vec3::ZERO,
+            target: Vec3::NEG_Z,
+            ..Default::default()
+        };
+        cam.set_fov_y(90f32.to_radians()).unwrap();
+        cam.set_clip_planes(1.0, 10.0).unwrap();
+        let view_proj = cam.view_proj(2.0);
+
+        // With a 90° field of view the frustum edge sits at |y| = depth.
+        let top_far = view_proj.project_point3(Vec3::new(0.0, 10.0, -10.0));
+        assert!(top_far.abs_diff_eq(Vec3::new(0.0, 1.0, 1.0), 1e-5));
+        let right_near = view_proj.project_point3(Vec3::new(2.0, 0.0, -1.0));
+        assert!(right_near.abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-5));
+        // Depth is hyperbolic: halfway in view space lands past 0.5.
+        let middle = view_proj.project_point3(Vec3::new(0.0, 0.0, -5.5));
+        assert!(middle.z > 0.5);
+    }
+
+    #[test]
+    fn orthographic_maps_points_to_expected_ndc() {
+        let mut cam = Camera::orthographic(4.0, 2.0, 1.0, 11.0);
+        cam.eye = Vec3::ZERO;
+        cam.target = Vec3::NEG_Z;
+        let view_proj = cam.view_proj(2.0);
+
+        let corner_near = view_proj.project_point3(Vec3::new(2.0, 1.0, -1.0));
+        assert!(corner_near.abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-5));
+        let corner_far = view_proj.project_point3(Vec3::new(-2.0, -1.0, -11.0));
+        assert!(corner_far.abs_diff_eq(Vec3::new(-1.0, -1.0, 1.0), 1e-5));
+        // Depth is linear and independent of the lateral offset.
+        let middle = view_proj.project_point3(Vec3::new(1.5, -0.5, -6.0));
+        assert!((middle.z - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orthographic_camera_has_no_field_of_view() {
+        let mut cam = Camera::orthographic(10.0, 10.0, 0.1, 100.0);
+        assert_eq!(cam.fov_y(), None);
+        assert!(cam.set_fov_y(1.0).is_err());
+
+        cam.set_clip_planes(1.0, 20.0).unwrap();
+        assert_eq!(cam.clip_planes(), (1.0, 20.0));
+    }
 }
@@ -0,0 +1,403 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glam::Vec3;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::app::{AppBuilder, Plugin};
+use crate::scene::Camera;
+
+/// Scroll distance reported by touchpads is in pixels; this many pixels count
+/// as one wheel notch.
+const PIXELS_PER_SCROLL_LINE: f32 = 50.0;
+/// Distances closer than this make the orbit basis degenerate.
+const MIN_ORBIT_DISTANCE: f32 = 1.0e-3;
+
+/// Tuning parameters for [`OrbitCameraController`].
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitCameraSettings {
+    /// Radians of yaw/pitch per pixel of mouse movement.
+    pub rotate_speed: f32,
+    /// Radians per second applied while an arrow key is held.
+    pub key_rotate_speed: f32,
+    /// Fraction of the current distance removed per scroll notch.
+    pub zoom_speed: f32,
+    /// Fraction of the current distance moved per pixel of middle-mouse drag.
+    pub pan_speed: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Smoothing time constant in seconds. `0.0` applies input immediately.
+    pub damping: f32,
+    /// Pan the orbit target while the middle mouse button is held.
+    pub enable_pan: bool,
+}
+
+impl Default for OrbitCameraSettings {
+    fn default() -> Self {
+        let limit = 89f32.to_radians();
+        Self {
+            rotate_speed: 0.005,
+            key_rotate_speed: 1.5,
+            zoom_speed: 0.1,
+            pan_speed: 0.0015,
+            min_pitch: -limit,
+            max_pitch: limit,
+            min_distance: 0.1,
+            max_distance: 500.0,
+            damping: 0.08,
+            enable_pan: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OrbitState {
+    target: Vec3,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl OrbitState {
+    fn from_camera(camera: &Camera) -> Self {
+        let offset = camera.eye - camera.target;
+        let distance = offset.length().max(MIN_ORBIT_DISTANCE);
+        Self {
+            target: camera.target,
+            yaw: offset.z.atan2(offset.x),
+            pitch: (offset.y / distance).clamp(-1.0, 1.0).asin(),
+            distance,
+        }
+    }
+
+    fn eye(&self) -> Vec3 {
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        self.target + Vec3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw) * self.distance
+    }
+
+    fn lerp(&self, goal: &Self, t: f32) -> Self {
+        Self {
+            target: self.target.lerp(goal.target, t),
+            yaw: self.yaw + (goal.yaw - self.yaw) * t,
+            pitch: self.pitch + (goal.pitch - self.pitch) * t,
+            distance: self.distance + (goal.distance - self.distance) * t,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct HeldKeys {
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+}
+
+/// Orbit/arcball camera driven by winit input.
+///
+/// Left mouse drag rotates around the target, the wheel zooms, middle mouse
+/// drag pans and the arrow keys rotate. The initial orbit is taken from the
+/// scene camera the first time [`update`](Self::update) runs.
+#[derive(Debug)]
+pub struct OrbitCameraController {
+    settings: OrbitCameraSettings,
+    goal: Option<OrbitState>,
+    current: Option<OrbitState>,
+    cursor: Option<(f64, f64)>,
+    rotating: bool,
+    panning: bool,
+    keys: HeldKeys,
+}
+
+impl OrbitCameraController {
+    pub fn new(settings: OrbitCameraSettings) -> Self {
+        Self {
+            settings,
+            goal: None,
+            current: None,
+            cursor: None,
+            rotating: false,
+            panning: false,
+            keys: HeldKeys::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &OrbitCameraSettings {
+        &self.settings
+    }
+
+    pub fn settings_mut(&mut self) -> &mut OrbitCameraSettings {
+        &mut self.settings
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = (position.x, position.y);
+                if let Some((last_x, last_y)) = self.cursor {
+                    let dx = (position.0 - last_x) as f32;
+                    let dy = (position.1 - last_y) as f32;
+                    if self.rotating {
+                        self.rotate(
+                            dx * self.settings.rotate_speed,
+                            dy * self.settings.rotate_speed,
+                        );
+                    } else if self.panning {
+                        self.pan(dx, dy);
+                    }
+                }
+                self.cursor = Some(position);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                self.rotating = false;
+                self.panning = false;
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let pressed = *state == ElementState::Pressed;
+                match button {
+                    MouseButton::Left => self.rotating = pressed,
+                    MouseButton::Middle if self.settings.enable_pan => self.panning = pressed,
+                    _ => {}
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let notches = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / PIXELS_PER_SCROLL_LINE,
+                };
+                self.zoom(notches);
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let pressed = event.state == ElementState::Pressed;
+                match event.logical_key {
+                    Key::Named(NamedKey::ArrowLeft) => self.keys.left = pressed,
+                    Key::Named(NamedKey::ArrowRight) => self.keys.right = pressed,
+                    Key::Named(NamedKey::ArrowUp) => self.keys.up = pressed,
+                    Key::Named(NamedKey::ArrowDown) => self.keys.down = pressed,
+                    _ => {}
+                }
+            }
+            WindowEvent::Focused(false) => {
+                self.rotating = false;
+                self.panning = false;
+                self.keys = HeldKeys::default();
+            }
+            _ => {}
+        }
+    }
+
+    /// Advances smoothing and writes the resulting eye/target into `camera`.
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) {
+        if self.goal.is_none() {
+            let initial = OrbitState::from_camera(camera);
+            self.goal = Some(initial);
+            self.current = Some(initial);
+            self.clamp_goal();
+        }
+
+        let horizontal = (self.keys.right as i32 - self.keys.left as i32) as f32;
+        let vertical = (self.keys.down as i32 - self.keys.up as i32) as f32;
+        if horizontal != 0.0 || vertical != 0.0 {
+            let step = self.settings.key_rotate_speed * dt;
+            self.rotate(horizontal * step, vertical * step);
+        }
+
+        let (Some(goal), Some(current)) = (self.goal, self.current) else {
+            return;
+        };
+        let next = if self.settings.damping > 0.0 {
+            current.lerp(&goal, 1.0 - (-dt / self.settings.damping).exp())
+        } else {
+            goal
+        };
+        self.current = Some(next);
+
+        camera.target = next.target;
+        camera.eye = next.eye();
+        camera.up = Vec3::Y;
+    }
+
+    /// Turns the orbit by `yaw` and `pitch` radians, e.g. from a gamepad
+    /// stick. Does nothing before the first [`Self::update`].
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        if let Some(goal) = self.goal.as_mut() {
+            goal.yaw += yaw;
+            goal.pitch += pitch;
+        }
+        self.clamp_goal();
+    }
+
+    /// Zooms in by `notches` scroll wheel notches; negative zooms out.
+    pub fn zoom(&mut self, notches: f32) {
+        let factor = (1.0 - self.settings.zoom_speed).clamp(0.01, 0.99);
+        if let Some(goal) = self.goal.as_mut() {
+            goal.distance *= factor.powf(notches);
+        }
+        self.clamp_goal();
+    }
+
+    fn pan(&mut self, dx: f32, dy: f32) {
+        let Some(goal) = self.goal.as_mut() else {
+            return;
+        };
+        let forward = (goal.target - goal.eye()).normalize_or_zero();
+        let right = forward.cross(Vec3::Y).normalize_or_zero();
+        let up = right.cross(forward);
+        let scale = goal.distance * self.settings.pan_speed;
+        goal.target += (-right * dx + up * dy) * scale;
+    }
+
+    fn clamp_goal(&mut self) {
+        let settings = self.settings;
+        if let Some(goal) = self.goal.as_mut() {
+            let (min_pitch, max_pitch) = ordered(settings.min_pitch, settings.max_pitch);
+            let (min_distance, max_distance) = ordered(
+                settings.min_distance.max(MIN_ORBIT_DISTANCE),
+                settings.max_distance.max(MIN_ORBIT_DISTANCE),
+            );
+            goal.pitch = goal.pitch.clamp(min_pitch, max_pitch);
+            goal.distance = goal.distance.clamp(min_distance, max_distance);
+        }
+    }
+}
+
+impl Default for OrbitCameraController {
+    fn default() -> Self {
+        Self::new(OrbitCameraSettings::default())
+    }
+}
+
+fn ordered(a: f32, b: f32) -> (f32, f32) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Installs an [`OrbitCameraController`] that drives the scene camera.
+///
+/// Events consumed by egui never reach the controller, so dragging inside a UI
+/// window does not move the camera.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrbitCameraPlugin {
+    pub settings: OrbitCameraSettings,
+}
+
+impl OrbitCameraPlugin {
+    pub fn with_pitch_limits(mut self, min: f32, max: f32) -> Self {
+        self.settings.min_pitch = min;
+        self.settings.max_pitch = max;
+        self
+    }
+
+    pub fn with_distance_limits(mut self, min: f32, max: f32) -> Self {
+        self.settings.min_distance = min;
+        self.settings.max_distance = max;
+        self
+    }
+
+    pub fn with_zoom_speed(mut self, zoom_speed: f32) -> Self {
+        self.settings.zoom_speed = zoom_speed;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.settings.damping = damping.max(0.0);
+        self
+    }
+
+    pub fn with_pan(mut self, enabled: bool) -> Self {
+        self.settings.enable_pan = enabled;
+        self
+    }
+}
+
+impl Plugin for OrbitCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let controller = Rc::new(RefCell::new(OrbitCameraController::new(self.settings)));
+
+        let events = controller.clone();
+        app.add_window_event_handler(move |event| {
+            events.borrow_mut().handle_event(event);
+        });
+
+        app.add_system(move |ctx| {
+            controller
+                .borrow_mut()
+                .update(ctx.scene.camera_mut(), ctx.dt as f32);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera_at(eye: Vec3) -> Camera {
+        Camera {
+            eye,
+            target: Vec3::ZERO,
+            ..Camera::default()
+        }
+    }
+
+    fn undamped() -> OrbitCameraController {
+        OrbitCameraController::new(OrbitCameraSettings {
+            damping: 0.0,
+            ..OrbitCameraSettings::default()
+        })
+    }
+
+    #[test]
+    fn first_update_preserves_existing_camera() {
+        let mut controller = undamped();
+        let mut camera = camera_at(Vec3::new(3.0, 2.0, -4.0));
+
+        controller.update(&mut camera, 0.016);
+
+        assert!(camera.eye.abs_diff_eq(Vec3::new(3.0, 2.0, -4.0), 1e-4));
+        assert_eq!(camera.target, Vec3::ZERO);
+    }
+
+    #[test]
+    fn pitch_and_distance_are_clamped() {
+        let mut controller = undamped();
+        let mut camera = camera_at(Vec3::new(0.0, 0.0, 5.0));
+        controller.update(&mut camera, 0.016);
+
+        controller.rotate(0.0, 10.0);
+        controller.zoom(-1000.0);
+        controller.update(&mut camera, 0.016);
+
+        let offset = camera.eye - camera.target;
+        let settings = controller.settings();
+        assert!((offset.length() - settings.max_distance).abs() < 1e-2);
+        let pitch = (offset.y / offset.length()).asin();
+        assert!(pitch <= settings.max_pitch + 1e-4);
+    }
+
+    #[test]
+    fn damping_eases_towards_goal() {
+        let mut controller = OrbitCameraController::default();
+        let mut camera = camera_at(Vec3::new(0.0, 0.0, 5.0));
+        controller.update(&mut camera, 0.016);
+
+        controller.zoom(5.0);
+        controller.update(&mut camera, 0.016);
+        let first = (camera.eye - camera.target).length();
+        assert!(first < 5.0);
+
+        for _ in 0..200 {
+            controller.update(&mut camera, 0.016);
+        }
+        let settled = (camera.eye - camera.target).length();
+        assert!(settled < first);
+        assert!((settled - 5.0 * 0.9f32.powf(5.0)).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,22 @@
+// scene/commands.rs
+// Deferred renderer commands produced by scene systems
+
+use crate::renderer::Renderer;
+
+/// A renderer-side change requested while the scene updates.
+///
+/// `Scene::update` runs without access to the renderer, so systems that need
+/// to touch renderer state queue a command instead. The app drains the queue
+/// at the start of the GPU stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RendererCommand {
+    SetExposure(f32),
+}
+
+impl RendererCommand {
+    pub fn apply(self, renderer: &mut Renderer) {
+        match self {
+            RendererCommand::SetExposure(exposure) => renderer.set_exposure(exposure),
+        }
+    }
+}
@@ -3,15 +3,17 @@
 
 use crate::asset::Handle;
 use crate::asset::Mesh;
+use crate::renderer::text::TextAnchor;
 use crate::renderer::Material;
-use crate::scene::Transform;
-use glam::Vec3;
+use crate::scene::{CameraProjection, Transform};
+use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
 
 // ============================================================================
 // Billboard Components
 // ============================================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BillboardOrientation {
     /// Rotate freely so the quad faces the camera.
     FaceCamera,
@@ -19,7 +21,7 @@ pub enum BillboardOrientation {
     FaceCameraYAxis,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum BillboardSpace {
     /// Use the transform's translation directly in world space.
     World,
@@ -34,7 +36,7 @@ impl Default for BillboardSpace {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Billboard {
     pub orientation: BillboardOrientation,
     pub space: BillboardSpace,
@@ -61,6 +63,46 @@ impl Billboard {
     }
 }
 
+/// A text label drawn from the scene's distance field font atlas. The scene
+/// builds its quad mesh and adds a [`MeshComponent`], an alpha-blended
+/// [`MaterialComponent`] and, when missing, a camera-facing [`Billboard`]
+/// and [`Visible`]. The mesh is rebuilt when the label changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextBillboard {
+    pub text: String,
+    /// Line height in world units.
+    pub font_size: f32,
+    pub color: [u8; 4],
+    /// Point of the text block placed at the entity's translation.
+    pub anchor: TextAnchor,
+}
+
+impl TextBillboard {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            font_size: 0.25,
+            color: [255, 255, 255, 255],
+            anchor: TextAnchor::Bottom,
+        }
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: TextAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+}
+
 // ============================================================================
 // Depth State Component
 // ============================================================================
@@ -69,6 +111,16 @@ impl Billboard {
 pub struct DepthState {
     pub depth_test: bool,
     pub depth_write: bool,
+    /// Draw only triangle edges. Ignored when the device lacks
+    /// `POLYGON_MODE_LINE`.
+    pub wireframe: bool,
+    /// Writes this value to the stencil buffer wherever the object draws.
+    pub stencil_write: Option<u8>,
+    /// Draws only where the stencil buffer does not hold this value, e.g.
+    /// outside a silhouette marked by `stencil_write`. Opaque batches that
+    /// test the stencil draw after the others in the main pass and skip the
+    /// depth prepass. Takes precedence over `stencil_write`.
+    pub stencil_test: Option<u8>,
 }
 
 impl DepthState {
@@ -76,16 +128,58 @@ impl DepthState {
         Self {
             depth_test,
             depth_write,
+            wireframe: false,
+            stencil_write: None,
+            stencil_test: None,
         }
     }
+
+    pub const fn with_wireframe(mut self, wireframe: bool) -> Self {
+        self.wireframe = wireframe;
+        self
+    }
+
+    pub const fn with_stencil_write(mut self, value: u8) -> Self {
+        self.stencil_write = Some(value);
+        self
+    }
+
+    pub const fn with_stencil_test(mut self, value: u8) -> Self {
+        self.stencil_test = Some(value);
+        self
+    }
 }
 
 impl Default for DepthState {
     fn default() -> Self {
-        Self {
-            depth_test: true,
-            depth_write: true,
-        }
+        Self::new(true, true)
+    }
+}
+
+/// Draws a flat-coloured rim around the entity's mesh: the mesh marks its
+/// silhouette in the stencil buffer, then a copy scaled by `scale` about
+/// its bounds centre draws only outside that silhouette. Suits opaque
+/// meshes; the copy casts no shadow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    /// sRGB colour of the rim, like [`Material::new`].
+    pub color: [u8; 4],
+    /// Size of the scaled copy relative to the mesh; the rim is the part
+    /// that sticks out.
+    pub scale: f32,
+}
+
+impl Outline {
+    /// Stencil value marking outlined silhouettes.
+    pub const STENCIL_VALUE: u8 = 1;
+
+    pub fn new(color: [u8; 4]) -> Self {
+        Self { color, scale: 1.05 }
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
     }
 }
 
@@ -94,7 +188,7 @@ impl Default for DepthState {
 // ============================================================================
 
 /// Transform component (position, rotation, scale)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct TransformComponent(pub Transform);
 
 /// World-space transform (computed from hierarchy)
@@ -106,11 +200,11 @@ pub struct WorldTransform(pub Transform);
 pub struct MeshComponent(pub Handle<Mesh>);
 
 /// Material component
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct MaterialComponent(pub Material);
 
 /// Visibility component
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Visible(pub bool);
 
 impl Default for Visible {
@@ -119,6 +213,70 @@ impl Default for Visible {
     }
 }
 
+/// Axis-aligned bounds of an entity's mesh in its local space, used for
+/// frustum culling. Entities without one are never culled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BoundingBox {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    /// Smallest box containing every point, or `None` for an empty set.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Vec3>,
+    {
+        points.into_iter().fold(None, |bounds, point| {
+            Some(match bounds {
+                Some(BoundingBox { min, max }) => BoundingBox {
+                    min: min.min(point),
+                    max: max.max(point),
+                },
+                None => BoundingBox {
+                    min: point,
+                    max: point,
+                },
+            })
+        })
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Axis-aligned box enclosing this box after `matrix` is applied.
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        Self::from_points(
+            self.corners()
+                .into_iter()
+                .map(|corner| matrix.transform_point3(corner)),
+        )
+        .unwrap_or(*self)
+    }
+}
+
 // ============================================================================
 // GPU-driven instance components
 // ============================================================================
@@ -137,15 +295,33 @@ pub struct GpuParticleInstance {
 // ============================================================================
 
 /// Point light component
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PointLight {
     pub color: Vec3,
     pub intensity: f32,
     pub range: f32,
 }
 
+/// Replaces a [`PointLight`]'s inverse-square falloff with
+/// `1 / (1 + linear * d + quadratic * d^2)`. The light's range still fades
+/// it to zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttenuationOverride {
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl AttenuationOverride {
+    pub fn new(linear: f32, quadratic: f32) -> Self {
+        Self {
+            linear: linear.max(0.0),
+            quadratic: quadratic.max(0.0),
+        }
+    }
+}
+
 /// Directional light component
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct DirectionalLight {
     pub color: Vec3,
     pub intensity: f32,
@@ -171,17 +347,20 @@ impl DirectionalLight {
 }
 
 /// Spot light component
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct SpotLight {
     pub color: Vec3,
     pub intensity: f32,
     pub inner_angle: f32,
     pub outer_angle: f32,
     pub range: f32,
+    /// Index of a scene texture projected through the cone, tinting the
+    /// light like a gobo.
+    pub cookie_texture: Option<u32>,
 }
 
 /// Marker/flag component indicating a light should cast shadows
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct CanCastShadow(pub bool);
 
 impl Default for CanCastShadow {
@@ -190,12 +369,18 @@ impl Default for CanCastShadow {
     }
 }
 
+/// Camera distance beyond which a point or spot light stops rendering its
+/// shadow map and lights unshadowed. Overrides
+/// [`LightShadowSettings::max_distance`](crate::settings::LightShadowSettings::max_distance).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShadowDistance(pub f32);
+
 // ============================================================================
 // Utility Components
 // ============================================================================
 
 /// Name component for debugging
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Name(pub String);
 
 impl Name {
@@ -204,6 +389,69 @@ impl Name {
     }
 }
 
+// ============================================================================
+// Camera Components
+// ============================================================================
+
+/// Mouse-look and WASD movement for the entity's transform, which the scene
+/// camera follows. Driven by `system_first_person_camera`.
+///
+/// `yaw` turns around world Y with 0 looking down -Z; `pitch` looks up and
+/// is kept within ±89°.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FirstPersonController {
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Movement speed in world units per second.
+    pub speed: f32,
+    /// Radians of rotation per unit of mouse movement.
+    pub sensitivity: f32,
+}
+
+impl Default for FirstPersonController {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 5.0,
+            sensitivity: 0.002,
+        }
+    }
+}
+
+/// Marks an entity as a camera viewing down its local -Z axis with +Y up.
+/// The glTF loader attaches one to every camera node; make it the view with
+/// `Scene::set_active_camera`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraComponent(pub CameraProjection);
+
+/// Keeps the entity at `offset` from `target`'s world position, looking at
+/// the target. Evaluated by `Scene::update` after transforms propagate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowCamera {
+    pub target: hecs::Entity,
+    /// World-space offset from the target.
+    pub offset: Vec3,
+    /// Seconds to close about two thirds of the distance to the desired
+    /// position; 0 snaps there every update.
+    pub smoothing: f32,
+}
+
+impl FollowCamera {
+    pub fn new(target: hecs::Entity, offset: Vec3) -> Self {
+        Self {
+            target,
+            offset,
+            smoothing: 0.0,
+        }
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing.max(0.0);
+        self
+    }
+}
+
 // ============================================================================
 // Animation Components
 // ============================================================================
@@ -224,6 +472,51 @@ pub struct OrbitAnimation {
     pub offset: f32,
 }
 
+/// Property driven by a [`PulseAnimation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PulseProperty {
+    /// Intensity of any light component on the same entity.
+    LightIntensity,
+    /// Emissive strength of the entity's material.
+    EmissiveStrength,
+    /// Post-process exposure. Applied to the renderer during the GPU stage.
+    PostExposure,
+}
+
+/// Sine-wave animation of a scalar property:
+/// `base + amplitude * sin(TAU * frequency * time + offset)`.
+#[derive(Debug, Clone, Copy)]
+pub struct PulseAnimation {
+    pub property: PulseProperty,
+    pub base: f32,
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub offset: f32,
+}
+
+impl PulseAnimation {
+    pub fn new(property: PulseProperty, base: f32, amplitude: f32, frequency: f32) -> Self {
+        Self {
+            property,
+            base,
+            amplitude,
+            frequency,
+            offset: 0.0,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Evaluates the waveform at `time` seconds. Never returns a negative value.
+    pub fn evaluate(&self, time: f32) -> f32 {
+        let phase = std::f32::consts::TAU * self.frequency * time + self.offset;
+        (self.base + self.amplitude * phase.sin()).max(0.0)
+    }
+}
+
 // ============================================================================
 // glTF Metadata Components
 // ============================================================================
@@ -236,6 +529,47 @@ pub struct GltfNode(pub usize);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct GltfMaterial(pub usize);
 
+/// The glTF primitive an entity's mesh was loaded from, so a saved scene can
+/// re-import it instead of storing the [`MeshComponent`] handle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshSource {
+    pub path: String,
+    /// glTF mesh index.
+    pub mesh: usize,
+    /// Primitive index within the mesh.
+    pub primitive: usize,
+    /// Scale multiplier the vertices were loaded with.
+    pub scale: f32,
+}
+
+/// Skeleton binding for a skinned mesh entity.
+///
+/// `joints` and `inverse_bind_matrices` are parallel arrays in glTF joint
+/// order. `joint_matrices` is rebuilt every frame after transform
+/// propagation and uploaded as the mesh's joint palette.
+#[derive(Debug, Clone)]
+pub struct Skin {
+    pub joints: Vec<hecs::Entity>,
+    pub inverse_bind_matrices: Vec<Mat4>,
+    pub joint_matrices: Vec<Mat4>,
+}
+
+impl Skin {
+    pub fn new(joints: Vec<hecs::Entity>, inverse_bind_matrices: Vec<Mat4>) -> Self {
+        let joint_matrices = vec![Mat4::IDENTITY; joints.len()];
+        Self {
+            joints,
+            inverse_bind_matrices,
+            joint_matrices,
+        }
+    }
+}
+
+/// Blend weights for a mesh with morph targets, one per target. Written by
+/// morph weight animation channels and uploaded every frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MorphWeights(pub Vec<f32>);
+
 // ============================================================================
 // Hierarchy Components (for future use)
 // ============================================================================
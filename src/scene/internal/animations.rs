@@ -1,16 +1,94 @@
-use crate::scene::animation::{AnimationClip, AnimationState, MaterialUpdate, TransformUpdate};
+use crate::scene::animation::{
+    blend_updates, blend_weights, AnimationClip, AnimationEvent, AnimationPlayback, AnimationState,
+    MaterialUpdate, TransformUpdate,
+};
+use crate::scene::commands::RendererCommand;
 use crate::scene::components::{
-    GltfMaterial, MaterialComponent, OrbitAnimation, RotateAnimation, TransformComponent,
+    Children, DirectionalLight, GltfMaterial, GltfNode, MaterialComponent, MorphWeights,
+    OrbitAnimation, PointLight, PulseAnimation, PulseProperty, RotateAnimation, SpotLight,
+    TransformComponent,
 };
 use glam::{Quat, Vec3};
 use hecs::World;
 use rayon::prelude::*;
 use std::collections::HashMap;
 
+#[derive(Default)]
+struct SampledUpdates {
+    transforms: HashMap<hecs::Entity, TransformUpdate>,
+    materials: HashMap<usize, MaterialUpdate>,
+    morphs: HashMap<hecs::Entity, Vec<f32>>,
+}
+
+impl SampledUpdates {
+    /// Layers `other` over these updates; later samples win.
+    fn extend(&mut self, other: SampledUpdates) {
+        self.transforms.extend(other.transforms);
+        self.materials.extend(other.materials);
+        self.morphs.extend(other.morphs);
+    }
+
+    fn blend(self, other: SampledUpdates, t: f32) -> SampledUpdates {
+        SampledUpdates {
+            transforms: blend_updates(self.transforms, other.transforms, t, TransformUpdate::blend),
+            materials: blend_updates(self.materials, other.materials, t, MaterialUpdate::blend),
+            morphs: blend_updates(self.morphs, other.morphs, t, |a, b, t| {
+                blend_weights(a, b, t)
+            }),
+        }
+    }
+}
+
+/// Advances `state` and samples its clip, or returns nothing for disabled
+/// or dangling states.
+fn sample_state(
+    animations: &[AnimationClip],
+    animation_states: &mut [AnimationState],
+    index: usize,
+    dt: f32,
+    events: &mut Vec<AnimationEvent>,
+) -> SampledUpdates {
+    let mut updates = SampledUpdates::default();
+    let Some(state) = animation_states.get_mut(index) else {
+        return updates;
+    };
+    if !state.enabled {
+        return updates;
+    }
+    let Some(clip) = animations.get(state.clip_index) else {
+        return updates;
+    };
+
+    let sample_time = state.step(index, dt, clip.duration, events);
+    clip.sample(
+        sample_time,
+        &mut updates.transforms,
+        &mut updates.materials,
+        &mut updates.morphs,
+    );
+    updates
+}
+
+/// One playback per enabled state, except that states taking part in a
+/// crossfade are sampled through their blend instead.
+fn playback_plan(
+    animation_states: &[AnimationState],
+    blends: &[AnimationPlayback],
+) -> Vec<AnimationPlayback> {
+    let mut plan: Vec<AnimationPlayback> = (0..animation_states.len())
+        .filter(|&index| !blends.iter().any(|blend| blend.involves(index)))
+        .map(AnimationPlayback::Single)
+        .collect();
+    plan.extend_from_slice(blends);
+    plan
+}
+
 pub(crate) fn advance_animations(
     world: &mut World,
     animations: &[AnimationClip],
     animation_states: &mut [AnimationState],
+    blends: &mut Vec<AnimationPlayback>,
+    events: &mut Vec<AnimationEvent>,
     dt: f64,
 ) {
     if animation_states.is_empty() || animations.is_empty() {
@@ -18,25 +96,124 @@ pub(crate) fn advance_animations(
     }
 
     let dt = dt as f32;
+    for blend in blends.iter_mut() {
+        blend.advance(dt);
+    }
 
-    let mut transform_updates: HashMap<hecs::Entity, TransformUpdate> = HashMap::new();
-    let mut material_updates: HashMap<usize, MaterialUpdate> = HashMap::new();
+    let mut updates = SampledUpdates::default();
+    for playback in playback_plan(animation_states, blends) {
+        let sampled = match playback {
+            AnimationPlayback::Single(index) => {
+                sample_state(animations, animation_states, index, dt, events)
+            }
+            AnimationPlayback::AnimationBlend {
+                from, to, blend_t, ..
+            } => {
+                let from_updates = sample_state(animations, animation_states, from, dt, events);
+                let to_updates = sample_state(animations, animation_states, to, dt, events);
+                from_updates.blend(to_updates, blend_t)
+            }
+        };
+        updates.extend(sampled);
+    }
 
-    for state in animation_states.iter_mut() {
-        if state.clip_index >= animations.len() {
-            continue;
+    // Finished crossfades hand over to their target state for good.
+    blends.retain(|blend| match *blend {
+        AnimationPlayback::AnimationBlend { from, blend_t, .. } if blend_t >= 1.0 => {
+            if let Some(state) = animation_states.get_mut(from) {
+                state.enabled = false;
+            }
+            false
         }
+        _ => true,
+    });
 
-        let clip = &animations[state.clip_index];
-        let sample_time = state.advance(dt, clip.duration);
-        clip.sample(sample_time, &mut transform_updates, &mut material_updates);
+    for (entity, update) in updates.transforms {
+        apply_transform_update(world, entity, update);
     }
 
-    for (entity, update) in transform_updates {
-        apply_transform_update(world, entity, update);
+    for (entity, weights) in updates.morphs {
+        apply_morph_weights(world, entity, weights);
     }
 
-    apply_material_updates(world, material_updates);
+    apply_material_updates(world, updates.materials);
+}
+
+/// Drops channels whose target entity no longer exists. Clips left without
+/// channels are removed together with their states and any crossfade those
+/// states take part in; remaining clip and state indices shift down.
+/// Returns the number of removed channels.
+pub(crate) fn prune_dead_targets(
+    world: &World,
+    animations: &mut Vec<AnimationClip>,
+    animation_states: &mut Vec<AnimationState>,
+    blends: &mut Vec<AnimationPlayback>,
+) -> usize {
+    let mut removed = 0;
+    let mut emptied = Vec::new();
+    for (index, clip) in animations.iter_mut().enumerate() {
+        let before = clip.channels.len();
+        clip.channels.retain(|channel| {
+            channel
+                .target
+                .entity()
+                .is_none_or(|entity| world.contains(entity))
+        });
+        removed += before - clip.channels.len();
+        if before > 0 && clip.channels.is_empty() {
+            emptied.push(index);
+        }
+    }
+    if emptied.is_empty() {
+        return removed;
+    }
+
+    for &index in emptied.iter().rev() {
+        log::info!(
+            "Removing animation clip '{}': all of its targets were despawned",
+            animations[index].name
+        );
+        animations.remove(index);
+    }
+    let new_clip_index = |clip: usize| {
+        (!emptied.contains(&clip)).then(|| clip - emptied.iter().filter(|&&e| e < clip).count())
+    };
+
+    let mut state_map = Vec::with_capacity(animation_states.len());
+    let mut kept = 0;
+    animation_states.retain_mut(|state| match new_clip_index(state.clip_index) {
+        Some(clip_index) => {
+            state.clip_index = clip_index;
+            state_map.push(Some(kept));
+            kept += 1;
+            true
+        }
+        None => {
+            state_map.push(None);
+            false
+        }
+    });
+    let new_state_index = |state: usize| state_map.get(state).copied().flatten();
+    blends.retain_mut(|blend| match blend {
+        AnimationPlayback::Single(index) => match new_state_index(*index) {
+            Some(new_index) => {
+                *index = new_index;
+                true
+            }
+            None => false,
+        },
+        AnimationPlayback::AnimationBlend { from, to, .. } => {
+            match (new_state_index(*from), new_state_index(*to)) {
+                (Some(new_from), Some(new_to)) => {
+                    *from = new_from;
+                    *to = new_to;
+                    true
+                }
+                _ => false,
+            }
+        }
+    });
+    removed
 }
 
 pub(crate) fn update_rotate_animations(world: &mut World, dt: f64) {
@@ -92,6 +269,52 @@ pub(crate) fn update_orbit_animations(world: &mut World, time: f64) {
     }
 }
 
+/// Evaluates every [`PulseAnimation`] at `time`. Component targets are written
+/// immediately; renderer targets are queued in `commands` for the GPU stage.
+pub(crate) fn update_property_animations(
+    world: &mut World,
+    time: f64,
+    commands: &mut Vec<RendererCommand>,
+) {
+    let time = time as f32;
+    let mut exposure = None;
+
+    for (_, (pulse, point, directional, spot, material)) in world.query_mut::<(
+        &PulseAnimation,
+        Option<&mut PointLight>,
+        Option<&mut DirectionalLight>,
+        Option<&mut SpotLight>,
+        Option<&mut MaterialComponent>,
+    )>() {
+        let value = pulse.evaluate(time);
+        match pulse.property {
+            PulseProperty::LightIntensity => {
+                if let Some(light) = point {
+                    light.intensity = value;
+                }
+                if let Some(light) = directional {
+                    light.intensity = value;
+                }
+                if let Some(light) = spot {
+                    light.intensity = value;
+                }
+            }
+            PulseProperty::EmissiveStrength => {
+                if let Some(material) = material {
+                    material.0 = material.0.with_emissive(value);
+                }
+            }
+            PulseProperty::PostExposure => exposure = Some(value),
+        }
+    }
+
+    // Several exposure pulses would fight over a single renderer value; the
+    // last one evaluated wins so at most one command is queued per frame.
+    if let Some(exposure) = exposure {
+        commands.push(RendererCommand::SetExposure(exposure));
+    }
+}
+
 fn apply_transform_update(world: &mut World, entity: hecs::Entity, update: TransformUpdate) {
     if let Ok(mut transform) = world.get::<&mut TransformComponent>(entity) {
         if let Some(translation) = update.translation {
@@ -108,6 +331,31 @@ fn apply_transform_update(world: &mut World, entity: hecs::Entity, update: Trans
     }
 }
 
+/// Morph weights target a glTF node; the node's extra primitives live on
+/// child entities without a [`GltfNode`] and share the same weights.
+fn apply_morph_weights(world: &mut World, entity: hecs::Entity, weights: Vec<f32>) {
+    let primitive_children: Vec<hecs::Entity> = world
+        .get::<&Children>(entity)
+        .map(|children| {
+            children
+                .0
+                .iter()
+                .copied()
+                .filter(|&child| world.get::<&GltfNode>(child).is_err())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for target in primitive_children
+        .into_iter()
+        .chain(std::iter::once(entity))
+    {
+        if let Ok(mut morph) = world.get::<&mut MorphWeights>(target) {
+            morph.0.clone_from(&weights);
+        }
+    }
+}
+
 fn apply_material_updates(world: &mut World, material_updates: HashMap<usize, MaterialUpdate>) {
     if material_updates.is_empty() {
         return;
@@ -152,8 +400,157 @@ fn apply_material_updates(world: &mut World, material_updates: HashMap<usize, Ma
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::scene::components::TransformComponent;
+    use crate::scene::animation::{
+        AnimationChannel, AnimationInterpolation, AnimationOutput, AnimationSampler,
+        AnimationTarget, TransformProperty,
+    };
+    use crate::scene::components::{Parent, TransformComponent};
     use crate::scene::transform::Transform;
+    use crate::scene::Scene;
+
+    fn translation_channel(entity: hecs::Entity) -> AnimationChannel {
+        AnimationChannel {
+            sampler: AnimationSampler {
+                times: vec![0.0, 1.0],
+                output: AnimationOutput::Vec3(vec![Vec3::ZERO, Vec3::Y]),
+                interpolation: AnimationInterpolation::Linear,
+            },
+            target: AnimationTarget::Transform {
+                entity,
+                property: TransformProperty::Translation,
+            },
+        }
+    }
+
+    #[test]
+    fn despawning_a_subtree_prunes_its_animation() {
+        let mut scene = Scene::new();
+        let root = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+        let child = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY), Parent(root)));
+        scene.world.insert_one(root, Children(vec![child])).unwrap();
+        let bystander = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+
+        let mut doomed = AnimationClip::new("child bob");
+        doomed.add_channel(translation_channel(child));
+        let doomed = scene.add_animation_clip(doomed);
+        let mut survivor = AnimationClip::new("mixed");
+        survivor.add_channel(translation_channel(child));
+        survivor.add_channel(translation_channel(bystander));
+        let survivor = scene.add_animation_clip(survivor);
+        scene.play_animation(doomed, true).unwrap();
+        let survivor_state = scene.play_animation(survivor, true).unwrap();
+        scene.crossfade(0, survivor_state, 1.0).unwrap();
+
+        assert_eq!(scene.despawn_recursive(root), 2);
+        assert!(!scene.world.contains(root));
+        assert!(!scene.world.contains(child));
+        assert_eq!(scene.world.len(), 1);
+
+        let clips = scene.animations();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].name, "mixed");
+        assert_eq!(clips[0].channels.len(), 1);
+        assert_eq!(clips[0].channels[0].target.entity(), Some(bystander));
+        assert_eq!(scene.animation_states().len(), 1);
+        assert_eq!(scene.animation_states()[0].clip_index, 0);
+        assert!(scene.animation_blends().is_empty());
+
+        scene.update(0.5);
+        let transform = scene.world.get::<&TransformComponent>(bystander).unwrap();
+        assert_eq!(transform.0.translation, Vec3::new(0.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn despawning_an_entity_despawns_its_children_and_prunes_their_channels() {
+        let mut scene = Scene::new();
+        let parent = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+        let child = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY), Parent(parent)));
+        scene
+            .world
+            .insert_one(parent, Children(vec![child]))
+            .unwrap();
+        let bystander = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+
+        let mut clip = AnimationClip::new("all");
+        clip.add_channel(translation_channel(parent));
+        clip.add_channel(translation_channel(child));
+        clip.add_channel(translation_channel(bystander));
+        let clip = scene.add_animation_clip(clip);
+        scene.play_animation(clip, true).unwrap();
+
+        assert!(scene.despawn(parent));
+        assert!(!scene.despawn(parent));
+        assert!(!scene.world.contains(child));
+
+        let clips = scene.animations();
+        assert_eq!(clips[0].channels.len(), 1);
+        assert_eq!(clips[0].channels[0].target.entity(), Some(bystander));
+        scene.update(0.5);
+        let transform = scene.world.get::<&TransformComponent>(bystander).unwrap();
+        assert_eq!(transform.0.translation, Vec3::new(0.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn despawning_a_single_entity_orphans_its_children() {
+        let mut scene = Scene::new();
+        let parent = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+        let child = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY), Parent(parent)));
+        scene
+            .world
+            .insert_one(parent, Children(vec![child]))
+            .unwrap();
+
+        let mut clip = AnimationClip::new("both");
+        clip.add_channel(translation_channel(parent));
+        clip.add_channel(translation_channel(child));
+        let clip = scene.add_animation_clip(clip);
+        scene.play_animation(clip, true).unwrap();
+
+        assert!(scene.despawn_single(parent));
+        assert!(!scene.despawn_single(parent));
+        assert!(scene.world.get::<&Parent>(child).is_err());
+
+        let clips = scene.animations();
+        assert_eq!(clips[0].channels.len(), 1);
+        assert_eq!(clips[0].channels[0].target.entity(), Some(child));
+        scene.update(0.5);
+        let transform = scene.world.get::<&TransformComponent>(child).unwrap();
+        assert_eq!(transform.0.translation, Vec3::new(0.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn update_prunes_channels_of_entities_despawned_through_the_world() {
+        let mut scene = Scene::new();
+        let entity = scene
+            .world
+            .spawn((TransformComponent(Transform::IDENTITY),));
+        let mut clip = AnimationClip::new("orphan");
+        clip.add_channel(translation_channel(entity));
+        let clip = scene.add_animation_clip(clip);
+        scene.play_animation(clip, true).unwrap();
+
+        scene.world.despawn(entity).unwrap();
+        scene.update(0.1);
+
+        assert!(scene.animations().is_empty());
+        assert!(scene.animation_states().is_empty());
+    }
 
     #[test]
     fn transform_updates_modify_world() {
@@ -174,6 +571,25 @@ mod tests {
         assert_eq!(transform.0.translation, glam::Vec3::new(1.0, 2.0, 3.0));
     }
 
+    #[test]
+    fn morph_weights_reach_primitive_children() {
+        let mut world = World::new();
+        let child = world.spawn((MorphWeights(vec![0.0, 0.0]),));
+        let node = world.spawn((
+            GltfNode(0),
+            MorphWeights(vec![0.0, 0.0]),
+            Children(vec![child]),
+        ));
+
+        apply_morph_weights(&mut world, node, vec![0.25, 1.0]);
+
+        assert_eq!(world.get::<&MorphWeights>(node).unwrap().0, vec![0.25, 1.0]);
+        assert_eq!(
+            world.get::<&MorphWeights>(child).unwrap().0,
+            vec![0.25, 1.0]
+        );
+    }
+
     #[test]
     fn material_updates_apply_base_color() {
         let mut world = World::new();
@@ -214,4 +630,63 @@ mod tests {
         let transform = world.get::<&TransformComponent>(entity).unwrap();
         assert!((transform.0.translation.length() - 2.0).abs() < 1e-3);
     }
+
+    #[test]
+    fn pulse_waveform_oscillates_around_base() {
+        let pulse = PulseAnimation::new(PulseProperty::LightIntensity, 10.0, 2.0, 1.0);
+
+        assert!((pulse.evaluate(0.0) - 10.0).abs() < 1e-5);
+        assert!((pulse.evaluate(0.25) - 12.0).abs() < 1e-4);
+        assert!((pulse.evaluate(0.75) - 8.0).abs() < 1e-4);
+        assert!((pulse.evaluate(1.0) - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn pulse_waveform_respects_offset_and_clamps_to_zero() {
+        let pulse = PulseAnimation::new(PulseProperty::EmissiveStrength, 0.5, 1.0, 1.0)
+            .with_offset(std::f32::consts::FRAC_PI_2);
+
+        assert!((pulse.evaluate(0.0) - 1.5).abs() < 1e-5);
+        assert_eq!(pulse.evaluate(0.5), 0.0);
+    }
+
+    #[test]
+    fn pulse_updates_light_intensity() {
+        let mut world = World::new();
+        let entity = world.spawn((
+            PointLight {
+                color: Vec3::ONE,
+                intensity: 1.0,
+                range: 10.0,
+            },
+            PulseAnimation::new(PulseProperty::LightIntensity, 40.0, 10.0, 1.0),
+        ));
+        let mut commands = Vec::new();
+
+        update_property_animations(&mut world, 0.25, &mut commands);
+
+        let light = world.get::<&PointLight>(entity).unwrap();
+        assert!((light.intensity - 50.0).abs() < 1e-3);
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn pulse_exposure_is_deferred_as_renderer_command() {
+        let mut world = World::new();
+        world.spawn((PulseAnimation::new(
+            PulseProperty::PostExposure,
+            1.0,
+            0.5,
+            1.0,
+        ),));
+        let mut commands = Vec::new();
+
+        update_property_animations(&mut world, 0.25, &mut commands);
+
+        assert_eq!(commands.len(), 1);
+        let Some(RendererCommand::SetExposure(exposure)) = commands.first().copied() else {
+            panic!("expected an exposure command");
+        };
+        assert!((exposure - 1.5).abs() < 1e-4);
+    }
 }
@@ -0,0 +1,140 @@
+use crate::scene::components::{
+    CameraComponent, FollowCamera, Parent, TransformComponent, WorldTransform,
+};
+use crate::scene::{Camera, Transform};
+use glam::{Mat4, Quat, Vec3};
+use hecs::World;
+
+/// Moves every [`FollowCamera`] towards its target's offset and turns it to
+/// face the target. Must run after `propagate_transforms`; the follower's
+/// world transform is written directly so the result is visible this frame.
+pub(crate) fn update_follow_cameras(world: &mut World, dt: f32) {
+    let updates: Vec<(hecs::Entity, Transform)> = world
+        .query::<(&FollowCamera, &WorldTransform)>()
+        .iter()
+        .filter_map(|(entity, (follow, current))| {
+            let target = world
+                .get::<&WorldTransform>(follow.target)
+                .ok()?
+                .0
+                .translation;
+            let desired = target + follow.offset;
+            let position = if follow.smoothing > 0.0 {
+                let blend = 1.0 - (-dt.max(0.0) / follow.smoothing).exp();
+                current.0.translation.lerp(desired, blend)
+            } else {
+                desired
+            };
+
+            let rotation = look_rotation(target - position).unwrap_or(current.0.rotation);
+            Some((
+                entity,
+                Transform::from_trs(position, rotation, current.0.scale),
+            ))
+        })
+        .collect();
+
+    for (entity, world_transform) in updates {
+        let parent_world = world
+            .get::<&Parent>(entity)
+            .ok()
+            .and_then(|parent| world.get::<&WorldTransform>(parent.0).ok().map(|t| t.0));
+        let local = match parent_world {
+            Some(parent) => match world_transform.relative_to(&parent) {
+                Some(local) => local,
+                None => continue,
+            },
+            None => world_transform,
+        };
+        if let Ok(mut transform) = world.get::<&mut TransformComponent>(entity) {
+            transform.0 = local;
+        }
+        if let Ok(mut current) = world.get::<&mut WorldTransform>(entity) {
+            current.0 = world_transform;
+        }
+    }
+}
+
+/// The view through `entity`'s [`CameraComponent`] at its current world
+/// transform. `None` when the entity is gone or is not a camera.
+pub(crate) fn camera_from_entity(world: &World, entity: hecs::Entity) -> Option<Camera> {
+    let projection = world.get::<&CameraComponent>(entity).ok()?.0;
+    let matrix = world
+        .get::<&WorldTransform>(entity)
+        .map(|t| t.0.matrix())
+        .unwrap_or(Mat4::IDENTITY);
+    Some(Camera::from_world_matrix(matrix, projection))
+}
+
+/// Rotation turning -Z towards `direction` with +Y kept up. `None` when the
+/// direction is degenerate.
+fn look_rotation(direction: Vec3) -> Option<Quat> {
+    let forward = direction.try_normalize()?;
+    let up = if forward.cross(Vec3::Y).length_squared() > 1e-6 {
+        Vec3::Y
+    } else {
+        Vec3::Z
+    };
+    // look_at_rh builds the view matrix; its inverse places the camera.
+    let view = Mat4::look_at_rh(Vec3::ZERO, forward, up);
+    Some(Quat::from_mat4(&view.inverse()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_camera_snaps_to_offset_and_faces_target() {
+        let mut world = World::new();
+        let target = world.spawn((
+            TransformComponent(Transform::IDENTITY),
+            WorldTransform(Transform::from_trs(
+                Vec3::new(1.0, 0.0, 0.0),
+                Quat::IDENTITY,
+                Vec3::ONE,
+            )),
+        ));
+        let follower = world.spawn((
+            TransformComponent(Transform::IDENTITY),
+            WorldTransform(Transform::IDENTITY),
+            FollowCamera::new(target, Vec3::new(0.0, 0.0, 5.0)),
+        ));
+
+        update_follow_cameras(&mut world, 0.016);
+
+        let placed = world.get::<&WorldTransform>(follower).unwrap().0;
+        assert!(placed
+            .translation
+            .abs_diff_eq(Vec3::new(1.0, 0.0, 5.0), 1e-5));
+        let forward = placed.rotation * Vec3::NEG_Z;
+        assert!(forward.abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        let local = world.get::<&TransformComponent>(follower).unwrap().0;
+        assert!(local.translation.abs_diff_eq(placed.translation, 1e-5));
+    }
+
+    #[test]
+    fn smoothed_follow_camera_moves_part_way() {
+        let mut world = World::new();
+        let target = world.spawn((WorldTransform(Transform::from_trs(
+            Vec3::new(10.0, 0.0, 0.0),
+            Quat::IDENTITY,
+            Vec3::ONE,
+        )),));
+        let follower = world.spawn((
+            TransformComponent(Transform::IDENTITY),
+            WorldTransform(Transform::IDENTITY),
+            FollowCamera::new(target, Vec3::ZERO).with_smoothing(1.0),
+        ));
+
+        update_follow_cameras(&mut world, 0.1);
+
+        let x = world
+            .get::<&WorldTransform>(follower)
+            .unwrap()
+            .0
+            .translation
+            .x;
+        assert!(x > 0.0 && x < 10.0);
+    }
+}
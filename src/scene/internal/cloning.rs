@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use hecs::{Entity, EntityBuilder, World};
+
+use crate::scene::components::{
+    AttenuationOverride, Billboard, BoundingBox, CameraComponent, CanCastShadow, Children,
+    DepthState, DirectionalLight, GltfMaterial, GltfNode, MaterialComponent, MeshComponent,
+    MeshSource, MorphWeights, Name, OrbitAnimation, Outline, Parent, PointLight, PulseAnimation,
+    RotateAnimation, ShadowDistance, Skin, SpotLight, TextBillboard, TransformComponent, Visible,
+    WorldTransform,
+};
+use crate::scene::internal::hierarchy::collect_subtree;
+
+/// Adds a copy of every standard component of `source` to `builder`.
+/// `Parent`, `Children` and `Skin` reference other entities and are left to
+/// the caller. Text labels skip their generated mesh so the clone builds its
+/// own.
+fn copy_components(world: &World, source: Entity, builder: &mut EntityBuilder) {
+    fn copy<T: hecs::Component + Clone>(
+        world: &World,
+        source: Entity,
+        builder: &mut EntityBuilder,
+    ) {
+        if let Ok(component) = world.get::<&T>(source) {
+            builder.add((*component).clone());
+        }
+    }
+
+    copy::<Name>(world, source, builder);
+    copy::<TransformComponent>(world, source, builder);
+    copy::<WorldTransform>(world, source, builder);
+    copy::<Visible>(world, source, builder);
+    copy::<BoundingBox>(world, source, builder);
+    copy::<MaterialComponent>(world, source, builder);
+    copy::<MorphWeights>(world, source, builder);
+    copy::<DepthState>(world, source, builder);
+    copy::<Billboard>(world, source, builder);
+    if world.get::<&TextBillboard>(source).is_ok() {
+        copy::<TextBillboard>(world, source, builder);
+    } else {
+        copy::<MeshComponent>(world, source, builder);
+        copy::<MeshSource>(world, source, builder);
+    }
+    copy::<DirectionalLight>(world, source, builder);
+    copy::<PointLight>(world, source, builder);
+    copy::<SpotLight>(world, source, builder);
+    copy::<AttenuationOverride>(world, source, builder);
+    copy::<CanCastShadow>(world, source, builder);
+    copy::<ShadowDistance>(world, source, builder);
+    copy::<CameraComponent>(world, source, builder);
+    copy::<Outline>(world, source, builder);
+    copy::<RotateAnimation>(world, source, builder);
+    copy::<OrbitAnimation>(world, source, builder);
+    copy::<PulseAnimation>(world, source, builder);
+    copy::<GltfNode>(world, source, builder);
+    copy::<GltfMaterial>(world, source, builder);
+}
+
+/// Makes `clone` a child of `parent`, after its existing children.
+fn attach(world: &mut World, clone: Entity, parent: Entity) {
+    world.insert_one(clone, Parent(parent)).ok();
+    if let Ok(mut children) = world.get::<&mut Children>(parent) {
+        children.0.push(clone);
+        return;
+    }
+    world.insert_one(parent, Children(vec![clone])).ok();
+}
+
+/// Spawns a copy of `entity` without its children. The copy shares the
+/// original's mesh and texture assets and parent; a skin keeps driving it
+/// from the original joints. Returns `None` if the entity does not exist.
+pub(crate) fn clone_entity(world: &mut World, entity: Entity) -> Option<Entity> {
+    if !world.contains(entity) {
+        return None;
+    }
+
+    let mut builder = EntityBuilder::new();
+    copy_components(world, entity, &mut builder);
+    let skin = world.get::<&Skin>(entity).ok().map(|skin| (*skin).clone());
+    if let Some(skin) = skin {
+        builder.add(skin);
+    }
+    let parent = world.get::<&Parent>(entity).ok().map(|parent| parent.0);
+
+    let clone = world.spawn(builder.build());
+    if let Some(parent) = parent.filter(|&parent| world.contains(parent)) {
+        attach(world, clone, parent);
+    }
+    Some(clone)
+}
+
+/// Clones `root` and all of its descendants. `Parent`, `Children` and skin
+/// joints inside the subtree are remapped to the copies; the new root joins
+/// the original root's parent. Returns the new root, or `None` if `root`
+/// does not exist.
+pub(crate) fn clone_hierarchy(world: &mut World, root: Entity) -> Option<Entity> {
+    let subtree = collect_subtree(world, root);
+    if subtree.is_empty() {
+        return None;
+    }
+
+    let mut entity_map = HashMap::with_capacity(subtree.len());
+    for &entity in &subtree {
+        let mut builder = EntityBuilder::new();
+        copy_components(world, entity, &mut builder);
+        entity_map.insert(entity, world.spawn(builder.build()));
+    }
+    let remap = |entity: &Entity| entity_map.get(entity).copied().unwrap_or(*entity);
+
+    for &entity in &subtree {
+        let clone = entity_map[&entity];
+        let children: Vec<Entity> = world
+            .get::<&Children>(entity)
+            .map(|children| {
+                children
+                    .0
+                    .iter()
+                    .filter(|child| entity_map.contains_key(child))
+                    .map(remap)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let skin = world.get::<&Skin>(entity).ok().map(|skin| {
+            let mut skin = (*skin).clone();
+            skin.joints = skin.joints.iter().map(remap).collect();
+            skin
+        });
+
+        if entity != root {
+            let parent = world.get::<&Parent>(entity).map(|parent| parent.0);
+            if let Ok(parent) = parent {
+                world.insert_one(clone, Parent(remap(&parent))).ok();
+            }
+        }
+        if !children.is_empty() {
+            world.insert_one(clone, Children(children)).ok();
+        }
+        if let Some(skin) = skin {
+            world.insert_one(clone, skin).ok();
+        }
+    }
+
+    let new_root = entity_map[&root];
+    let parent = world.get::<&Parent>(root).ok().map(|parent| parent.0);
+    if let Some(parent) = parent.filter(|&parent| world.contains(parent)) {
+        attach(world, new_root, parent);
+    }
+    Some(new_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::Handle;
+    use crate::renderer::Material;
+    use crate::scene::Transform;
+    use glam::{Mat4, Quat, Vec3};
+
+    #[test]
+    fn clone_entity_copies_components_and_joins_parent() {
+        let mut world = World::new();
+        let parent = world.spawn(());
+        let original = world.spawn((
+            Name("Lamp".to_string()),
+            TransformComponent(Transform::from_trs(Vec3::X, Quat::IDENTITY, Vec3::ONE)),
+            MeshComponent(Handle::new(3)),
+            MaterialComponent(Material::red()),
+            Visible(true),
+            PointLight {
+                color: Vec3::ONE,
+                intensity: 2.0,
+                range: 10.0,
+            },
+            Parent(parent),
+        ));
+        world.insert_one(parent, Children(vec![original])).unwrap();
+
+        let clone = clone_entity(&mut world, original).unwrap();
+        assert_ne!(clone, original);
+        assert_eq!(world.get::<&Name>(clone).unwrap().0, "Lamp");
+        assert_eq!(
+            world.get::<&MeshComponent>(clone).unwrap().0,
+            Handle::new(3)
+        );
+        assert_eq!(
+            world
+                .get::<&TransformComponent>(clone)
+                .unwrap()
+                .0
+                .translation,
+            Vec3::X
+        );
+        assert_eq!(world.get::<&PointLight>(clone).unwrap().intensity, 2.0);
+        assert_eq!(world.get::<&Parent>(clone).unwrap().0, parent);
+        assert_eq!(
+            world.get::<&Children>(parent).unwrap().0,
+            vec![original, clone]
+        );
+
+        world.despawn(original).unwrap();
+        assert!(clone_entity(&mut world, original).is_none());
+    }
+
+    #[test]
+    fn clone_hierarchy_remaps_references_into_the_copy() {
+        let mut world = World::new();
+        let root = world.spawn((Name("Root".to_string()),));
+        let joint = world.spawn((Name("Joint".to_string()), Parent(root)));
+        let outside = world.spawn(());
+        let skinned = world.spawn((
+            Name("Body".to_string()),
+            Parent(root),
+            Skin::new(vec![joint, outside], vec![Mat4::IDENTITY; 2]),
+        ));
+        world
+            .insert_one(root, Children(vec![joint, skinned]))
+            .unwrap();
+
+        let copy = clone_hierarchy(&mut world, root).unwrap();
+        assert!(world.get::<&Parent>(copy).is_err());
+        let children = world.get::<&Children>(copy).unwrap().0.clone();
+        assert_eq!(children.len(), 2);
+        assert!(!children.contains(&joint) && !children.contains(&skinned));
+
+        let (new_joint, new_skinned) = (children[0], children[1]);
+        assert_eq!(world.get::<&Name>(new_joint).unwrap().0, "Joint");
+        assert_eq!(world.get::<&Parent>(new_joint).unwrap().0, copy);
+        assert_eq!(
+            world.get::<&Skin>(new_skinned).unwrap().joints,
+            vec![new_joint, outside]
+        );
+        assert_eq!(
+            world.get::<&Children>(root).unwrap().0,
+            vec![joint, skinned]
+        );
+        assert_eq!(world.len(), 7);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::scene::animation::AnimationTarget;
 use crate::scene::components::{
-    Children, GltfMaterial, GltfNode, MaterialComponent, MeshComponent, Name, OrbitAnimation,
-    Parent, RotateAnimation, TransformComponent, Visible, WorldTransform,
+    BoundingBox, Children, GltfMaterial, GltfNode, MaterialComponent, MeshComponent, MorphWeights,
+    Name, OrbitAnimation, Parent, RotateAnimation, Skin, TransformComponent, Visible,
+    WorldTransform,
 };
 use crate::scene::Scene;
 
@@ -36,6 +37,12 @@ pub(crate) fn merge_as_child(scene: &mut Scene, parent_entity: hecs::Entity, oth
         if let Ok(mesh) = other_world.get::<&MeshComponent>(old_entity) {
             builder.add(*mesh);
         }
+        if let Ok(bounds) = other_world.get::<&BoundingBox>(old_entity) {
+            builder.add(*bounds);
+        }
+        if let Ok(weights) = other_world.get::<&MorphWeights>(old_entity) {
+            builder.add((*weights).clone());
+        }
         if let Ok(material) = other_world.get::<&MaterialComponent>(old_entity) {
             builder.add(*material);
         }
@@ -71,6 +78,25 @@ pub(crate) fn merge_as_child(scene: &mut Scene, parent_entity: hecs::Entity, oth
         })
         .collect();
 
+    let skins_to_fix: Vec<_> = entity_map
+        .iter()
+        .filter_map(|(old, &new)| {
+            other_world
+                .get::<&Skin>(*old)
+                .ok()
+                .map(|skin| (new, (*skin).clone()))
+        })
+        .collect();
+
+    for (new_entity, mut skin) in skins_to_fix {
+        skin.joints = skin
+            .joints
+            .iter()
+            .map(|joint| entity_map.get(joint).copied().unwrap_or(*joint))
+            .collect();
+        scene.world.insert_one(new_entity, skin).ok();
+    }
+
     let mut root_entities = Vec::new();
 
     for (new_entity, parent, children) in parent_children_to_fix {
@@ -126,18 +152,31 @@ pub(crate) fn merge_as_child(scene: &mut Scene, parent_entity: hecs::Entity, oth
     let animation_offset = scene.animations().len();
     for mut clip in other_animations.drain(..) {
         for channel in clip.channels.iter_mut() {
-            if let AnimationTarget::Transform { entity, property } = channel.target {
-                if let Some(&new_entity) = entity_map.get(&entity) {
-                    channel.target = AnimationTarget::Transform {
-                        entity: new_entity,
-                        property,
-                    };
-                } else {
-                    log::warn!(
-                        "Skipping animation channel targeting entity {:?} missing from merge",
-                        entity
-                    );
+            match channel.target {
+                AnimationTarget::Transform { entity, property } => {
+                    if let Some(&new_entity) = entity_map.get(&entity) {
+                        channel.target = AnimationTarget::Transform {
+                            entity: new_entity,
+                            property,
+                        };
+                    } else {
+                        log::warn!(
+                            "Skipping animation channel targeting entity {:?} missing from merge",
+                            entity
+                        );
+                    }
+                }
+                AnimationTarget::MorphWeights { entity } => {
+                    if let Some(&new_entity) = entity_map.get(&entity) {
+                        channel.target = AnimationTarget::MorphWeights { entity: new_entity };
+                    } else {
+                        log::warn!(
+                            "Skipping animation channel targeting entity {:?} missing from merge",
+                            entity
+                        );
+                    }
                 }
+                AnimationTarget::Material { .. } => {}
             }
         }
         scene.animations_mut().push(clip);
@@ -0,0 +1,75 @@
+use crate::scene::components::BoundingBox;
+use glam::{Mat4, Vec3, Vec4};
+
+/// The six clip planes of a view-projection matrix, normals pointing inward.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes (Gribb/Hartmann) for wgpu's `[0, 1]` clip depth.
+    pub(crate) fn from_view_proj(view_proj: Mat4) -> Self {
+        let r0 = view_proj.row(0);
+        let r1 = view_proj.row(1);
+        let r2 = view_proj.row(2);
+        let r3 = view_proj.row(3);
+
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| {
+            let length = plane.truncate().length();
+            if length > f32::EPSILON {
+                plane / length
+            } else {
+                plane
+            }
+        });
+
+        Self { planes }
+    }
+
+    /// False only when the box lies entirely behind one of the planes.
+    pub(crate) fn intersects_aabb(&self, bounds: &BoundingBox) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // Corner furthest along the plane normal.
+            let positive = Vec3::select(normal.cmpge(Vec3::ZERO), bounds.max, bounds.min);
+            normal.dot(positive) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Camera;
+
+    fn unit_box_at(center: Vec3) -> BoundingBox {
+        BoundingBox::new(center - Vec3::splat(0.5), center + Vec3::splat(0.5))
+    }
+
+    fn camera_frustum() -> Frustum {
+        let camera = Camera {
+            eye: Vec3::new(0.0, 0.0, 5.0),
+            target: Vec3::ZERO,
+            ..Camera::default()
+        };
+        Frustum::from_view_proj(camera.view_proj(1.0))
+    }
+
+    #[test]
+    fn boxes_in_front_of_camera_are_kept() {
+        let frustum = camera_frustum();
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::ZERO)));
+        // Straddles the left plane.
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(-2.9, 0.0, 0.0))));
+    }
+
+    #[test]
+    fn boxes_outside_any_plane_are_culled() {
+        let frustum = camera_frustum();
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(20.0, 0.0, 0.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, -20.0, 0.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, -200.0))));
+    }
+}
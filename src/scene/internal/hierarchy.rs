@@ -0,0 +1,269 @@
+use std::collections::HashSet;
+
+use hecs::World;
+
+use crate::asset::{Assets, Handle, Mesh};
+use crate::renderer::texture::DEFAULT_TEXTURE_COUNT;
+use crate::scene::components::{Children, MaterialComponent, MeshComponent, Parent, SpotLight};
+
+/// `root` followed by all of its descendants, depth first.
+pub(crate) fn collect_subtree(world: &World, root: hecs::Entity) -> Vec<hecs::Entity> {
+    let mut subtree = Vec::new();
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        if !world.contains(entity) {
+            continue;
+        }
+        subtree.push(entity);
+        if let Ok(children) = world.get::<&Children>(entity) {
+            stack.extend(children.0.iter().rev());
+        }
+    }
+    subtree
+}
+
+/// Removes `entity` from its parent's [`Children`].
+fn unlink_from_parent(world: &mut World, entity: hecs::Entity) {
+    let parent = world.get::<&Parent>(entity).ok().map(|parent| parent.0);
+    if let Some(parent) = parent {
+        if let Ok(mut children) = world.get::<&mut Children>(parent) {
+            children.0.retain(|&child| child != entity);
+        }
+    }
+}
+
+/// Whether `ancestor` is `entity` itself or one of its ancestors.
+fn is_ancestor_or_self(world: &World, ancestor: hecs::Entity, entity: hecs::Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(node) = current {
+        if node == ancestor {
+            return true;
+        }
+        current = world.get::<&Parent>(node).ok().map(|parent| parent.0);
+    }
+    false
+}
+
+/// Makes `child` a child of `parent`, unlinking it from its previous
+/// parent. Returns `false` without changes when either entity is missing
+/// or the link would make `child` its own ancestor.
+pub(crate) fn attach(world: &mut World, parent: hecs::Entity, child: hecs::Entity) -> bool {
+    if !world.contains(parent) || !world.contains(child) {
+        return false;
+    }
+    if is_ancestor_or_self(world, child, parent) {
+        log::warn!(
+            "Cannot parent {:?} to {:?}: it would become its own ancestor",
+            child,
+            parent
+        );
+        return false;
+    }
+
+    unlink_from_parent(world, child);
+    world.insert_one(child, Parent(parent)).ok();
+    if let Ok(mut children) = world.get::<&mut Children>(parent) {
+        if !children.0.contains(&child) {
+            children.0.push(child);
+        }
+        return true;
+    }
+    world.insert_one(parent, Children(vec![child])).ok();
+    true
+}
+
+/// Unlinks `child` from `parent`, making it a root. Returns `false` if
+/// `child` is not a child of `parent`.
+pub(crate) fn detach(world: &mut World, parent: hecs::Entity, child: hecs::Entity) -> bool {
+    let is_child = world
+        .get::<&Parent>(child)
+        .is_ok_and(|current| current.0 == parent);
+    if !is_child {
+        return false;
+    }
+    unlink_from_parent(world, child);
+    world.remove_one::<Parent>(child).ok();
+    true
+}
+
+/// Despawns `entity` alone. It is unlinked from its parent and its children
+/// become roots. Returns `false` if the entity did not exist.
+pub(crate) fn despawn_entity(world: &mut World, entity: hecs::Entity) -> bool {
+    if !world.contains(entity) {
+        return false;
+    }
+    unlink_from_parent(world, entity);
+    let children = world
+        .get::<&Children>(entity)
+        .map(|children| children.0.clone())
+        .unwrap_or_default();
+    for child in children {
+        world.remove_one::<Parent>(child).ok();
+    }
+    world.despawn(entity).is_ok()
+}
+
+/// Despawns `root` and its descendants and unlinks it from its parent. With
+/// `release_assets`, meshes and textures that no remaining entity uses are
+/// removed from `assets`. Returns the number of despawned entities.
+pub(crate) fn despawn_subtree(
+    world: &mut World,
+    assets: &mut Assets,
+    root: hecs::Entity,
+    release_assets: bool,
+) -> usize {
+    let subtree = collect_subtree(world, root);
+    if subtree.is_empty() {
+        return 0;
+    }
+
+    unlink_from_parent(world, root);
+
+    let mut meshes: HashSet<Handle<Mesh>> = HashSet::new();
+    let mut textures: HashSet<u32> = HashSet::new();
+    for &entity in &subtree {
+        if let Ok(mesh) = world.get::<&MeshComponent>(entity) {
+            meshes.insert(mesh.0);
+        }
+        if let Ok(material) = world.get::<&MaterialComponent>(entity) {
+            textures.extend(material.0.texture_indices());
+        }
+        if let Ok(spot) = world.get::<&SpotLight>(entity) {
+            textures.extend(spot.cookie_texture);
+        }
+    }
+
+    for &entity in &subtree {
+        world.despawn(entity).ok();
+    }
+
+    if release_assets {
+        release_unused(world, assets, meshes, textures);
+    }
+    subtree.len()
+}
+
+fn release_unused(
+    world: &World,
+    assets: &mut Assets,
+    mut meshes: HashSet<Handle<Mesh>>,
+    mut textures: HashSet<u32>,
+) {
+    for (_, mesh) in world.query::<&MeshComponent>().iter() {
+        meshes.remove(&mesh.0);
+    }
+    for (_, material) in world.query::<&MaterialComponent>().iter() {
+        for index in material.0.texture_indices() {
+            textures.remove(&index);
+        }
+    }
+    for (_, spot) in world.query::<&SpotLight>().iter() {
+        if let Some(index) = spot.cookie_texture {
+            textures.remove(&index);
+        }
+    }
+
+    let mesh_count = meshes
+        .into_iter()
+        .filter_map(|handle| assets.meshes.remove(handle))
+        .count();
+    // Materials store bare slot indices; resolve them to live handles.
+    let texture_handles: Vec<_> = textures
+        .into_iter()
+        .filter(|&index| index >= DEFAULT_TEXTURE_COUNT)
+        .filter_map(|index| assets.textures.handle_at(index as usize))
+        .collect();
+    let texture_count = texture_handles
+        .into_iter()
+        .filter_map(|handle| assets.textures.remove(handle))
+        .count();
+
+    if mesh_count > 0 || texture_count > 0 {
+        log::info!(
+            "Released {} meshes and {} textures no longer in use",
+            mesh_count,
+            texture_count
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::Material;
+
+    #[test]
+    fn despawn_subtree_removes_descendants_and_unlinks_parent() {
+        let mut world = World::new();
+        let mut assets = Assets::new();
+
+        let root = world.spawn(());
+        let branch = world.spawn((Parent(root),));
+        let leaf = world.spawn((Parent(branch),));
+        let sibling = world.spawn((Parent(root),));
+        world
+            .insert_one(root, Children(vec![branch, sibling]))
+            .unwrap();
+        world.insert_one(branch, Children(vec![leaf])).unwrap();
+
+        assert_eq!(collect_subtree(&world, branch), vec![branch, leaf]);
+        assert_eq!(despawn_subtree(&mut world, &mut assets, branch, true), 2);
+
+        assert!(!world.contains(branch));
+        assert!(!world.contains(leaf));
+        assert!(world.contains(sibling));
+        assert_eq!(world.get::<&Children>(root).unwrap().0, vec![sibling]);
+        assert_eq!(despawn_subtree(&mut world, &mut assets, branch, true), 0);
+    }
+
+    #[test]
+    fn despawn_entity_orphans_children() {
+        let mut world = World::new();
+        let root = world.spawn(());
+        let middle = world.spawn((Parent(root),));
+        let leaf = world.spawn((Parent(middle),));
+        world.insert_one(root, Children(vec![middle])).unwrap();
+        world.insert_one(middle, Children(vec![leaf])).unwrap();
+
+        assert!(despawn_entity(&mut world, middle));
+        assert!(!despawn_entity(&mut world, middle));
+        assert!(world.get::<&Children>(root).unwrap().0.is_empty());
+        assert!(world.get::<&Parent>(leaf).is_err());
+        assert!(world.contains(leaf));
+    }
+
+    #[test]
+    fn attach_moves_children_and_rejects_cycles() {
+        let mut world = World::new();
+        let first = world.spawn(());
+        let second = world.spawn(());
+        let child = world.spawn(());
+
+        assert!(attach(&mut world, first, child));
+        assert!(attach(&mut world, first, child));
+        assert_eq!(world.get::<&Children>(first).unwrap().0, vec![child]);
+
+        assert!(attach(&mut world, second, child));
+        assert!(world.get::<&Children>(first).unwrap().0.is_empty());
+        assert_eq!(world.get::<&Children>(second).unwrap().0, vec![child]);
+        assert_eq!(world.get::<&Parent>(child).unwrap().0, second);
+
+        assert!(!attach(&mut world, child, second));
+        assert!(!attach(&mut world, child, child));
+        assert!(world.get::<&Parent>(second).is_err());
+
+        assert!(!detach(&mut world, first, child));
+        assert!(detach(&mut world, second, child));
+        assert!(world.get::<&Parent>(child).is_err());
+        assert!(world.get::<&Children>(second).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn texture_indices_follow_material_flags() {
+        let material = Material::new([255, 255, 255, 255])
+            .with_base_color_texture(7)
+            .with_normal_texture(9);
+        let indices: Vec<u32> = material.texture_indices().collect();
+        assert_eq!(indices, vec![7, 9]);
+    }
+}
@@ -0,0 +1,94 @@
+use hecs::{Entity, World};
+
+use crate::scene::components::TransformComponent;
+use crate::scene::transform::Transform;
+
+/// The local transform an entity had before the latest fixed tick moved it.
+/// Only entities the tick changed carry one.
+pub(crate) struct PreviousTransform(pub(crate) Transform);
+
+/// Every local transform, taken before a fixed tick runs.
+pub(crate) struct TransformSnapshot(Vec<(Entity, Transform)>);
+
+impl TransformSnapshot {
+    pub(crate) fn take(world: &World) -> Self {
+        Self(
+            world
+                .query::<&TransformComponent>()
+                .iter()
+                .map(|(entity, transform)| (entity, transform.0))
+                .collect(),
+        )
+    }
+
+    /// Keeps the snapshot as [`PreviousTransform`] for the entities the tick
+    /// moved and drops it from those it left alone.
+    pub(crate) fn record_changes(self, world: &mut World) {
+        for (entity, before) in self.0 {
+            let Ok(current) = world.get::<&TransformComponent>(entity).map(|t| t.0) else {
+                continue;
+            };
+            if same_transform(&before, &current) {
+                world.remove_one::<PreviousTransform>(entity).ok();
+            } else if world
+                .satisfies::<&PreviousTransform>(entity)
+                .unwrap_or(false)
+            {
+                if let Ok(mut previous) = world.get::<&mut PreviousTransform>(entity) {
+                    previous.0 = before;
+                }
+            } else {
+                world.insert_one(entity, PreviousTransform(before)).ok();
+            }
+        }
+    }
+}
+
+fn same_transform(a: &Transform, b: &Transform) -> bool {
+    a.translation == b.translation && a.rotation == b.rotation && a.scale == b.scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::components::WorldTransform;
+    use crate::scene::internal::transforms::propagate_interpolated_transforms;
+    use glam::{Quat, Vec3};
+
+    #[test]
+    fn interpolation_blends_between_ticks() {
+        let mut world = World::new();
+        let moving = world.spawn((TransformComponent(Transform::IDENTITY),));
+        let still = world.spawn((TransformComponent(Transform::from_trs(
+            Vec3::new(0.0, 5.0, 0.0),
+            Quat::IDENTITY,
+            Vec3::ONE,
+        )),));
+
+        let snapshot = TransformSnapshot::take(&world);
+        world.get::<&mut TransformComponent>(moving).unwrap().0 = Transform::from_trs(
+            Vec3::new(4.0, 0.0, 0.0),
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            Vec3::splat(3.0),
+        );
+        snapshot.record_changes(&mut world);
+        assert!(world.get::<&PreviousTransform>(moving).is_ok());
+        assert!(world.get::<&PreviousTransform>(still).is_err());
+
+        propagate_interpolated_transforms(&mut world, 0.5);
+        let midpoint = world.get::<&WorldTransform>(moving).unwrap().0;
+        assert!(midpoint
+            .translation
+            .abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-5));
+        assert!(midpoint.scale.abs_diff_eq(Vec3::splat(2.0), 1e-5));
+        assert!(midpoint
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_y(std::f32::consts::FRAC_PI_4), 1e-5));
+        let still_world = world.get::<&WorldTransform>(still).unwrap().0;
+        assert_eq!(still_world.translation, Vec3::new(0.0, 5.0, 0.0));
+
+        // A tick that leaves the entity alone stops the blending.
+        TransformSnapshot::take(&world).record_changes(&mut world);
+        assert!(world.get::<&PreviousTransform>(moving).is_err());
+    }
+}
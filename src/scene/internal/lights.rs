@@ -1,25 +1,156 @@
-use super::rendering::CameraVectors;
+use std::collections::HashSet;
+
 use crate::renderer::{
-    DirectionalShadowData, LightsData, PointShadowData, SpotLightDescriptor, SpotShadowData,
+    CascadedShadowData, LightsData, PointShadowData, SpotCookieData, SpotLightDescriptor,
+    SpotShadowData, MAX_POINT_LIGHTS, MAX_SHADOW_CASCADES, MAX_SPOT_LIGHTS,
 };
 use crate::scene::components::{
-    CanCastShadow, DirectionalLight, PointLight, SpotLight, TransformComponent, WorldTransform,
+    AttenuationOverride, CanCastShadow, DirectionalLight, PointLight, ShadowDistance, SpotLight,
+    TransformComponent, WorldTransform,
 };
 use crate::scene::transform::Transform;
+use crate::scene::Camera;
+use crate::settings::ShadowSettings;
 use glam::{Mat4, Quat, Vec3};
 use hecs::World;
 
-pub(crate) fn collect_lights(world: &World, camera: CameraVectors) -> LightsData {
+/// How much closer a light that had a shadow last frame counts when
+/// competing for the shadow budget, as a fraction of its distance.
+const SHADOW_HYSTERESIS: f32 = 0.15;
+
+/// A point or spot light that wants a shadow map this frame.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ShadowCandidate {
+    pub entity: hecs::Entity,
+    pub distance: f32,
+    pub max_distance: f32,
+}
+
+/// Picks the point and spot lights that render shadow maps each frame.
+///
+/// Lights beyond their shadow distance are dropped and the rest are ranked
+/// by distance to the camera. Last frame's winners are ranked
+/// [`SHADOW_HYSTERESIS`] closer than they are, so lights at similar
+/// distances do not trade the shadow back and forth as the camera moves.
+#[derive(Debug, Default)]
+pub(crate) struct ShadowScheduler {
+    shadowed: HashSet<hecs::Entity>,
+}
+
+impl ShadowScheduler {
+    pub(crate) fn schedule(
+        &mut self,
+        candidates: &[ShadowCandidate],
+        budget: usize,
+    ) -> &HashSet<hecs::Entity> {
+        let mut ranked: Vec<(f32, hecs::Entity)> = candidates
+            .iter()
+            .filter_map(|candidate| {
+                let bias = if self.shadowed.contains(&candidate.entity) {
+                    1.0 - SHADOW_HYSTERESIS
+                } else {
+                    1.0
+                };
+                let distance = candidate.distance * bias;
+                (distance <= candidate.max_distance).then_some((distance, candidate.entity))
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        self.shadowed = ranked
+            .into_iter()
+            .take(budget)
+            .map(|(_, entity)| entity)
+            .collect();
+        &self.shadowed
+    }
+}
+
+pub(crate) fn collect_lights(
+    world: &World,
+    frustum: &CascadeFrustum,
+    camera_position: Vec3,
+    settings: &ShadowSettings,
+    scheduler: &mut ShadowScheduler,
+) -> LightsData {
     let mut lights = LightsData::default();
 
-    collect_directional_lights(world, camera, &mut lights);
-    collect_point_lights(world, &mut lights);
-    collect_spot_lights(world, &mut lights);
+    let candidates = shadow_candidates(world, camera_position, settings);
+    let shadowed = scheduler.schedule(&candidates, settings.max_local_shadows as usize);
+
+    collect_directional_lights(world, frustum, &mut lights);
+    collect_point_lights(world, shadowed, &mut lights);
+    collect_spot_lights(world, shadowed, &mut lights);
 
     lights
 }
 
-fn collect_directional_lights(world: &World, camera: CameraVectors, lights: &mut LightsData) {
+/// Shadow-casting point and spot lights that fit in the light buffers, in
+/// upload order.
+fn shadow_candidates(
+    world: &World,
+    camera_position: Vec3,
+    settings: &ShadowSettings,
+) -> Vec<ShadowCandidate> {
+    let mut candidates = Vec::new();
+    let mut push = |entity,
+                    transform: Transform,
+                    flag: Option<&CanCastShadow>,
+                    distance: Option<&ShadowDistance>,
+                    default_distance: f32| {
+        if shadow_enabled(flag) {
+            candidates.push(ShadowCandidate {
+                entity,
+                distance: transform.translation.distance(camera_position),
+                max_distance: distance.map_or(default_distance, |distance| distance.0),
+            });
+        }
+    };
+
+    for (entity, (_, world_transform, local_transform, flag, distance)) in world
+        .query::<(
+            &PointLight,
+            Option<&WorldTransform>,
+            Option<&TransformComponent>,
+            Option<&CanCastShadow>,
+            Option<&ShadowDistance>,
+        )>()
+        .iter()
+        .take(MAX_POINT_LIGHTS)
+    {
+        let transform = resolve_light_transform(world_transform, local_transform);
+        push(
+            entity,
+            transform,
+            flag,
+            distance,
+            settings.point.max_distance,
+        );
+    }
+    for (entity, (_, world_transform, local_transform, flag, distance)) in world
+        .query::<(
+            &SpotLight,
+            Option<&WorldTransform>,
+            Option<&TransformComponent>,
+            Option<&CanCastShadow>,
+            Option<&ShadowDistance>,
+        )>()
+        .iter()
+        .take(MAX_SPOT_LIGHTS)
+    {
+        let transform = resolve_light_transform(world_transform, local_transform);
+        push(
+            entity,
+            transform,
+            flag,
+            distance,
+            settings.spot.max_distance,
+        );
+    }
+    candidates
+}
+
+fn collect_directional_lights(world: &World, frustum: &CascadeFrustum, lights: &mut LightsData) {
     for (_entity, (light, world_transform, local_transform, shadow_flag)) in world
         .query::<(
             &DirectionalLight,
@@ -34,8 +165,7 @@ fn collect_directional_lights(world: &World, camera: CameraVectors, lights: &mut
 
         let shadow = if shadow_enabled(shadow_flag) {
             Some(build_directional_shadow(
-                camera.position,
-                camera.target,
+                frustum,
                 transform,
                 light.shadow_size,
             ))
@@ -47,23 +177,21 @@ fn collect_directional_lights(world: &World, camera: CameraVectors, lights: &mut
     }
 }
 
-fn collect_point_lights(world: &World, lights: &mut LightsData) {
-    for (_entity, (light, world_transform, local_transform, shadow_flag)) in world
+fn collect_point_lights(world: &World, shadowed: &HashSet<hecs::Entity>, lights: &mut LightsData) {
+    for (entity, (light, world_transform, local_transform, shadow_flag, attenuation)) in world
         .query::<(
             &PointLight,
             Option<&WorldTransform>,
             Option<&TransformComponent>,
             Option<&CanCastShadow>,
+            Option<&AttenuationOverride>,
         )>()
         .iter()
     {
         let transform = resolve_light_transform(world_transform, local_transform);
 
-        let shadow = if shadow_enabled(shadow_flag) {
-            Some(build_point_shadow(transform.translation, light.range))
-        } else {
-            None
-        };
+        let shadow = (shadow_enabled(shadow_flag) && shadowed.contains(&entity))
+            .then(|| build_point_shadow(transform.translation, light.range));
 
         lights.add_point(
             transform.translation,
@@ -71,12 +199,13 @@ fn collect_point_lights(world: &World, lights: &mut LightsData) {
             light.intensity,
             light.range,
             shadow,
+            attenuation.copied(),
         );
     }
 }
 
-fn collect_spot_lights(world: &World, lights: &mut LightsData) {
-    for (_entity, (light, world_transform, local_transform, shadow_flag)) in world
+fn collect_spot_lights(world: &World, shadowed: &HashSet<hecs::Entity>, lights: &mut LightsData) {
+    for (entity, (light, world_transform, local_transform, shadow_flag)) in world
         .query::<(
             &SpotLight,
             Option<&WorldTransform>,
@@ -88,11 +217,18 @@ fn collect_spot_lights(world: &World, lights: &mut LightsData) {
         let transform = resolve_light_transform(world_transform, local_transform);
         let direction = safe_normalize(transform.rotation * Vec3::NEG_Z, Vec3::new(0.0, -1.0, 0.0));
 
-        let shadow = if shadow_enabled(shadow_flag) {
-            Some(build_spot_shadow(transform, light))
-        } else {
-            None
-        };
+        // Cookies are projected through the same frustum as the shadow map.
+        let casts_shadow = shadow_enabled(shadow_flag) && shadowed.contains(&entity);
+        let projection = (casts_shadow || light.cookie_texture.is_some())
+            .then(|| build_spot_shadow(transform, light));
+        let shadow = projection.filter(|_| casts_shadow);
+        let cookie = light
+            .cookie_texture
+            .zip(projection)
+            .map(|(texture, projection)| SpotCookieData {
+                texture,
+                view_proj: projection.view_proj,
+            });
 
         lights.add_spot(SpotLightDescriptor {
             position: transform.translation,
@@ -103,6 +239,7 @@ fn collect_spot_lights(world: &World, lights: &mut LightsData) {
             inner_angle: light.inner_angle,
             outer_angle: light.outer_angle,
             shadow,
+            cookie,
         });
     }
 }
@@ -121,24 +258,105 @@ fn shadow_enabled(flag: Option<&CanCastShadow>) -> bool {
     flag.map(|flag| flag.0).unwrap_or(false)
 }
 
+/// Blend between uniform (0) and logarithmic (1) cascade split distances.
+const CASCADE_SPLIT_LAMBDA: f32 = 0.75;
+
+/// The camera frustum that directional shadow cascades are fitted to.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CascadeFrustum {
+    /// Near-plane corners followed by the matching far-plane corners.
+    corners: [Vec3; 8],
+    near: f32,
+    far: f32,
+    count: usize,
+}
+
+impl CascadeFrustum {
+    pub(crate) fn new(camera: &Camera, aspect: f32, count: usize) -> Self {
+        let projection = camera.projection_matrix(aspect);
+        let inverse = (projection.proj * camera.view()).inverse();
+        let corners = [
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(-1.0, 1.0, 1.0),
+        ]
+        .map(|ndc| inverse.project_point3(ndc));
+
+        Self {
+            corners,
+            near: projection.near,
+            far: projection.far,
+            count: count.clamp(1, MAX_SHADOW_CASCADES),
+        }
+    }
+
+    /// Corners of the part of the frustum between view depths `start` and
+    /// `end`. View depth is linear along each corner edge for both
+    /// perspective and orthographic cameras.
+    fn slice(&self, start: f32, end: f32) -> [Vec3; 8] {
+        let range = (self.far - self.near).max(f32::EPSILON);
+        let (a, b) = ((start - self.near) / range, (end - self.near) / range);
+        let mut corners = [Vec3::ZERO; 8];
+        for i in 0..4 {
+            let (near, far) = (self.corners[i], self.corners[i + 4]);
+            corners[i] = near.lerp(far, a);
+            corners[i + 4] = near.lerp(far, b);
+        }
+        corners
+    }
+}
+
+/// View depths where each of the first `count` cascades ends. Entries past
+/// `count` repeat `far`.
+pub(crate) fn cascade_splits(near: f32, far: f32, count: usize) -> [f32; MAX_SHADOW_CASCADES] {
+    let near = near.max(1e-3);
+    let mut splits = [far; MAX_SHADOW_CASCADES];
+    for (i, split) in splits.iter_mut().enumerate().take(count) {
+        let fraction = (i + 1) as f32 / count as f32;
+        let uniform = near + (far - near) * fraction;
+        let logarithmic = near * (far / near).powf(fraction);
+        *split = uniform + (logarithmic - uniform) * CASCADE_SPLIT_LAMBDA;
+    }
+    splits
+}
+
+pub(crate) fn directional_shadow_cascades(
+    world: &World,
+    entity: hecs::Entity,
+    frustum: &CascadeFrustum,
+) -> Option<CascadedShadowData> {
+    let mut query = world
+        .query_one::<(
+            &DirectionalLight,
+            Option<&WorldTransform>,
+            Option<&TransformComponent>,
+        )>(entity)
+        .ok()?;
+    let (light, world_transform, local_transform) = query.get()?;
+    let transform = resolve_light_transform(world_transform, local_transform);
+    Some(build_directional_shadow(
+        frustum,
+        transform,
+        light.shadow_size,
+    ))
+}
+
+/// Splits the camera frustum into cascades and fits one orthographic shadow
+/// projection to each. Shadows reach `2 * shadow_size` from the camera, the
+/// width a single shadow map used to cover, or the far plane if closer.
 pub(crate) fn build_directional_shadow(
-    camera_pos: Vec3,
-    camera_target: Vec3,
+    frustum: &CascadeFrustum,
     light_transform: Transform,
     shadow_size: f32,
-) -> DirectionalShadowData {
-    let shadow_distance = DirectionalLight::DEFAULT_SHADOW_DISTANCE;
-
+) -> CascadedShadowData {
     let raw_dir = light_transform.rotation * Vec3::NEG_Z;
     let direction = safe_normalize(raw_dir, Vec3::new(0.0, -1.0, 0.0));
 
-    let focus = if (camera_target - camera_pos).length_squared() > 1e-4 {
-        camera_target
-    } else {
-        camera_pos
-    };
-    let light_pos = focus - direction * shadow_distance;
-
     let mut up = light_transform.rotation * Vec3::Y;
     if up.length_squared() > 0.0 {
         up = up.normalize();
@@ -147,33 +365,54 @@ pub(crate) fn build_directional_shadow(
         up = shadow_up(direction);
     }
 
-    let view = Mat4::look_at_rh(light_pos, focus, up);
-
-    let extent = shadow_size.max(0.1);
-    let left = -extent;
-    let right = extent;
-    let bottom = -extent;
-    let top = extent;
-    let near = 0.1;
-    let far = shadow_distance * 2.0;
-
-    let projection = Mat4::from_cols(
-        glam::Vec4::new(2.0 / (right - left), 0.0, 0.0, 0.0),
-        glam::Vec4::new(0.0, 2.0 / (top - bottom), 0.0, 0.0),
-        glam::Vec4::new(0.0, 0.0, -1.0 / (far - near), 0.0),
-        glam::Vec4::new(
-            -(right + left) / (right - left),
-            -(top + bottom) / (top - bottom),
-            -near / (far - near),
-            1.0,
-        ),
-    );
+    let far = frustum
+        .far
+        .min(shadow_size.max(0.1) * 2.0)
+        .max(frustum.near + 1e-3);
+    let splits = cascade_splits(frustum.near, far, frustum.count);
+
+    let mut view_proj = [Mat4::IDENTITY; MAX_SHADOW_CASCADES];
+    let mut start = frustum.near;
+    for (cascade, &end) in splits.iter().enumerate().take(frustum.count) {
+        view_proj[cascade] = fit_cascade(&frustum.slice(start, end), direction, up);
+        start = end;
+    }
 
-    DirectionalShadowData {
-        view_proj: projection * view,
+    CascadedShadowData {
+        splits,
+        view_proj,
+        count: frustum.count,
     }
 }
 
+/// Orthographic light projection around the bounding sphere of a frustum
+/// slice. Fitting a sphere keeps the cascade size fixed while the camera
+/// turns, which avoids shadow edges swimming; casters up to
+/// `DEFAULT_SHADOW_DISTANCE` in front of the slice stay inside the depth range.
+fn fit_cascade(corners: &[Vec3; 8], direction: Vec3, up: Vec3) -> Mat4 {
+    let center = corners.iter().copied().sum::<Vec3>() / 8.0;
+    let radius = corners
+        .iter()
+        .map(|corner| corner.distance(center))
+        .fold(0.0f32, f32::max);
+    // Quantize so small camera motion does not rescale the cascade.
+    let radius = ((radius * 16.0).ceil() / 16.0).max(0.1);
+
+    let caster_distance = DirectionalLight::DEFAULT_SHADOW_DISTANCE;
+    let light_pos = center - direction * (radius + caster_distance);
+    let view = Mat4::look_at_rh(light_pos, center, up);
+    let projection = Mat4::orthographic_rh(
+        -radius,
+        radius,
+        -radius,
+        radius,
+        0.0,
+        caster_distance + radius * 2.0,
+    );
+
+    projection * view
+}
+
 pub(crate) fn build_point_shadow(position: Vec3, range: f32) -> PointShadowData {
     use std::f32::consts::FRAC_PI_2;
 
@@ -305,155 +544,108 @@ pub(crate) fn safe_normalize(vec: Vec3, fallback: Vec3) -> Vec3 {
 mod tests {
     use super::*;
     use crate::scene::transform::Transform;
-    use glam::{EulerRot, Vec2, Vec4};
+    use crate::scene::CameraProjection;
+    use glam::EulerRot;
 
     const EPS: f32 = 1e-5;
 
-    fn build_directional_projection() -> Mat4 {
-        let extent = DirectionalLight::DEFAULT_SHADOW_SIZE;
-        let left = -extent;
-        let right = extent;
-        let bottom = -extent;
-        let top = extent;
-        let near = 0.1;
-        let far = 60.0;
+    fn test_camera(far: f32) -> Camera {
+        Camera {
+            eye: Vec3::new(0.0, 4.0, 12.0),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            projection: CameraProjection::Perspective {
+                fov: 60f32.to_radians(),
+                near: 0.1,
+                far,
+            },
+        }
+    }
 
-        Mat4::from_cols(
-            Vec4::new(2.0 / (right - left), 0.0, 0.0, 0.0),
-            Vec4::new(0.0, 2.0 / (top - bottom), 0.0, 0.0),
-            Vec4::new(0.0, 0.0, -1.0 / (far - near), 0.0),
-            Vec4::new(
-                -(right + left) / (right - left),
-                -(top + bottom) / (top - bottom),
-                -near / (far - near),
-                1.0,
-            ),
-        )
+    fn project_to_uv_depth(matrix: Mat4, point: Vec3) -> Vec3 {
+        let clip = matrix * point.extend(1.0);
+        let ndc = clip.truncate() / clip.w;
+        Vec3::new(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5, ndc.z)
     }
 
     #[test]
-    fn directional_shadow_view_matrix_matches_expected_orientation() {
-        let camera_pos = Vec3::new(8.0, 6.0, -4.0);
-        let camera_target = Vec3::new(2.5, 1.0, -3.0);
-        let rotation = Quat::from_euler(EulerRot::YXZ, 0.35, -0.6, 0.5)
-            * Quat::from_euler(EulerRot::ZXY, 0.2, 0.0, 0.1);
-        let transform = Transform::from_trs(Vec3::new(1.5, 3.0, -2.0), rotation, Vec3::ONE);
-
-        let shadow = build_directional_shadow(
-            camera_pos,
-            camera_target,
-            transform,
-            DirectionalLight::DEFAULT_SHADOW_SIZE,
-        );
-
-        let direction = (rotation * Vec3::NEG_Z).normalize();
-        let up = (rotation * Vec3::Y).normalize();
-        let focus = camera_target;
-        let light_pos = focus - direction * 30.0;
-        let expected_view = Mat4::look_at_rh(light_pos, focus, up);
-        let projection = build_directional_projection();
-        let expected_view_proj = projection * expected_view;
-
-        assert!(
-            shadow.view_proj.abs_diff_eq(expected_view_proj, EPS),
-            "view projection mismatch: {:?} vs {:?}",
-            shadow.view_proj,
-            expected_view_proj
-        );
-
-        let actual_view = projection.inverse() * shadow.view_proj;
-        assert!(actual_view.abs_diff_eq(expected_view, EPS));
-
-        let dir_in_view = (actual_view * direction.extend(0.0)).truncate().normalize();
-        assert!(dir_in_view.abs_diff_eq(Vec3::new(0.0, 0.0, -1.0), EPS));
+    fn cascade_splits_increase_to_shadow_distance() {
+        for count in 1..=MAX_SHADOW_CASCADES {
+            let splits = cascade_splits(0.1, 60.0, count);
+            for pair in splits[..count].windows(2) {
+                assert!(pair[0] < pair[1], "splits not increasing: {splits:?}");
+            }
+            assert!((splits[count - 1] - 60.0).abs() < 1e-3);
+            // Logarithmic weighting keeps the first cascade short.
+            if count > 1 {
+                assert!(splits[0] < 60.0 / count as f32);
+            }
+        }
     }
 
     #[test]
-    fn directional_shadow_centers_camera_target() {
-        let camera_pos = Vec3::new(4.0, 6.0, 12.0);
-        let camera_target = Vec3::new(1.0, 0.5, -2.0);
+    fn directional_cascades_cover_their_frustum_slices() {
+        let camera = test_camera(100.0);
+        let frustum = CascadeFrustum::new(&camera, 16.0 / 9.0, 4);
         let rotation = Quat::from_euler(EulerRot::YXZ, -0.2, -0.9, 0.3);
         let transform = Transform::from_trs(Vec3::ZERO, rotation, Vec3::ONE);
 
-        let shadow = build_directional_shadow(
-            camera_pos,
-            camera_target,
-            transform,
-            DirectionalLight::DEFAULT_SHADOW_SIZE,
-        );
-
-        let clip = shadow.view_proj * camera_target.extend(1.0);
-        assert!(clip.w > 0.0);
-        let ndc = clip.truncate() / clip.w;
-        let uv = Vec2::new(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5);
-
-        assert!(
-            uv.abs_diff_eq(Vec2::splat(0.5), 1e-4),
-            "camera target projected to {:?}",
-            uv
-        );
+        let shadow =
+            build_directional_shadow(&frustum, transform, DirectionalLight::DEFAULT_SHADOW_SIZE);
+        assert_eq!(shadow.count, 4);
+
+        let mut start = frustum.near;
+        for (end, view_proj) in shadow.cascades() {
+            for corner in frustum.slice(start, end) {
+                let uv_depth = project_to_uv_depth(view_proj, corner);
+                assert!(
+                    uv_depth.cmpge(Vec3::splat(-EPS)).all()
+                        && uv_depth.cmple(Vec3::splat(1.0 + EPS)).all(),
+                    "slice {start}..{end} corner {corner:?} outside cascade: {uv_depth:?}"
+                );
+            }
+            start = end;
+        }
     }
 
     #[test]
-    fn directional_shadow_respects_light_roll() {
-        let camera_pos = Vec3::new(-6.0, 5.0, 2.0);
-        let camera_target = Vec3::new(0.5, 1.5, -3.0);
-        let rotation = Quat::from_euler(EulerRot::ZXY, 0.3, -0.5, 0.9);
-        let transform = Transform::from_trs(Vec3::new(-1.0, 2.0, 0.5), rotation, Vec3::splat(1.0));
-
-        let shadow = build_directional_shadow(
-            camera_pos,
-            camera_target,
-            transform,
-            DirectionalLight::DEFAULT_SHADOW_SIZE,
-        );
-
-        let projection = build_directional_projection();
-        let actual_view = projection.inverse() * shadow.view_proj;
-
-        let forward = (rotation * Vec3::NEG_Z).normalize();
-        let up = (rotation * Vec3::Y).normalize();
-        let right = (rotation * Vec3::X).normalize();
+    fn directional_cascades_follow_light_orientation() {
+        let rotation = Quat::from_euler(EulerRot::YXZ, 0.35, -0.6, 0.5)
+            * Quat::from_euler(EulerRot::ZXY, 0.2, 0.0, 0.1);
+        let transform = Transform::from_trs(Vec3::new(1.5, 3.0, -2.0), rotation, Vec3::ONE);
+        let frustum = CascadeFrustum::new(&test_camera(100.0), 1.0, 3);
 
-        let forward_in_view = (actual_view * forward.extend(0.0)).truncate().normalize();
-        let up_in_view = (actual_view * up.extend(0.0)).truncate().normalize();
-        let right_in_view = (actual_view * right.extend(0.0)).truncate().normalize();
+        let shadow =
+            build_directional_shadow(&frustum, transform, DirectionalLight::DEFAULT_SHADOW_SIZE);
 
-        assert!(forward_in_view.abs_diff_eq(Vec3::new(0.0, 0.0, -1.0), EPS));
-        assert!(up_in_view.abs_diff_eq(Vec3::Y, EPS));
-        assert!(right_in_view.abs_diff_eq(Vec3::X, EPS));
+        let axis_in_clip = |view_proj: Mat4, axis: Vec3| {
+            (view_proj * (rotation * axis).extend(0.0))
+                .truncate()
+                .normalize()
+        };
+        for (_, view_proj) in shadow.cascades() {
+            // The light shines along its -Z, into increasing shadow depth,
+            // and its roll carries over to the shadow map axes.
+            assert!(axis_in_clip(view_proj, Vec3::NEG_Z).abs_diff_eq(Vec3::Z, EPS));
+            assert!(axis_in_clip(view_proj, Vec3::Y).abs_diff_eq(Vec3::Y, EPS));
+            assert!(axis_in_clip(view_proj, Vec3::X).abs_diff_eq(Vec3::X, EPS));
+        }
     }
 
     #[test]
-    fn directional_shadow_scales_with_per_light_extent() {
-        let camera_pos = Vec3::new(0.0, 4.0, 12.0);
-        let camera_target = Vec3::ZERO;
+    fn directional_shadow_distance_scales_with_per_light_extent() {
         let transform = Transform::from_trs(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE);
-        let default_extent = DirectionalLight::DEFAULT_SHADOW_SIZE;
-        let world_point = Vec3::new(default_extent * 1.2, 0.0, 0.0);
-
-        let small = build_directional_shadow(camera_pos, camera_target, transform, default_extent);
-        let large =
-            build_directional_shadow(camera_pos, camera_target, transform, default_extent * 3.0);
-
-        let project_to_uv = |matrix: Mat4| {
-            let clip = matrix * world_point.extend(1.0);
-            assert!(clip.w > 0.0);
-            let ndc = clip.truncate() / clip.w;
-            Vec2::new(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5)
-        };
+        let frustum = CascadeFrustum::new(&test_camera(100.0), 1.0, 4);
 
-        let small_uv = project_to_uv(small.view_proj);
-        let large_uv = project_to_uv(large.view_proj);
+        let small = build_directional_shadow(&frustum, transform, 10.0);
+        let large = build_directional_shadow(&frustum, transform, 40.0);
+        assert!((small.splits[3] - 20.0).abs() < 1e-3);
+        assert!((large.splits[3] - 80.0).abs() < 1e-3);
 
-        assert!(
-            small_uv.x > 1.0 + 1e-4,
-            "expected point outside default shadow extent, uv={small_uv:?}"
-        );
-        assert!(
-            large_uv.x <= 1.0 + 1e-4,
-            "expected point within larger shadow extent, uv={large_uv:?}"
-        );
+        // Never past the camera's far plane.
+        let short = CascadeFrustum::new(&test_camera(30.0), 1.0, 4);
+        let clipped = build_directional_shadow(&short, transform, 40.0);
+        assert!((clipped.splits[3] - 30.0).abs() < 1e-3);
     }
 
     #[test]
@@ -466,6 +658,7 @@ mod tests {
             inner_angle: 0.3,
             outer_angle: 0.6,
             range: 25.0,
+            cookie_texture: None,
         };
 
         let shadow = build_spot_shadow(transform, &light);
@@ -504,6 +697,7 @@ mod tests {
             inner_angle: 0.4,
             outer_angle: 0.7,
             range: 30.0,
+            cookie_texture: None,
         };
 
         let shadow = build_spot_shadow(transform, &light);
@@ -615,4 +809,102 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn directional_shadow_cascades_look_up_light_entity() {
+        let mut world = World::new();
+        let rotation = Quat::from_euler(EulerRot::YXZ, 0.4, -0.8, 0.0);
+        let light = world.spawn((
+            DirectionalLight::new(Vec3::ONE, 1.0).with_shadow_size(12.0),
+            TransformComponent(Transform::from_trs(Vec3::ZERO, rotation, Vec3::ONE)),
+        ));
+        let other = world.spawn((TransformComponent(Transform::IDENTITY),));
+
+        let frustum = CascadeFrustum::new(&test_camera(100.0), 1.0, 2);
+        let expected = build_directional_shadow(
+            &frustum,
+            Transform::from_trs(Vec3::ZERO, rotation, Vec3::ONE),
+            12.0,
+        );
+
+        let shadow = directional_shadow_cascades(&world, light, &frustum).unwrap();
+        assert_eq!(shadow.count, 2);
+        assert_eq!(shadow.splits, expected.splits);
+        for (actual, expected) in shadow.view_proj.iter().zip(expected.view_proj) {
+            assert!(actual.abs_diff_eq(expected, EPS));
+        }
+        assert!(directional_shadow_cascades(&world, other, &frustum).is_none());
+    }
+
+    fn spawn_point_light(world: &mut World, position: Vec3) -> hecs::Entity {
+        world.spawn((
+            PointLight {
+                color: Vec3::ONE,
+                intensity: 1.0,
+                range: 10.0,
+            },
+            TransformComponent(Transform::from_trs(position, Quat::IDENTITY, Vec3::ONE)),
+            CanCastShadow(true),
+        ))
+    }
+
+    #[test]
+    fn shadow_budget_limits_point_shadow_views() {
+        let mut world = World::new();
+        for i in 0..20 {
+            spawn_point_light(&mut world, Vec3::new(i as f32 * 3.0, 1.0, 0.0));
+        }
+        let settings = ShadowSettings {
+            max_local_shadows: 2,
+            ..ShadowSettings::default()
+        };
+        let frustum = CascadeFrustum::new(&test_camera(100.0), 1.0, 1);
+        let mut scheduler = ShadowScheduler::default();
+
+        let lights = collect_lights(&world, &frustum, Vec3::ZERO, &settings, &mut scheduler);
+        let shadowed = lights
+            .point_shadows()
+            .iter()
+            .filter(|shadow| shadow.has_shadow())
+            .count();
+        assert_eq!(shadowed, 2);
+        assert_eq!(lights.shadow_view_count(1), 12);
+    }
+
+    #[test]
+    fn shadow_scheduler_keeps_incumbents_within_hysteresis() {
+        let mut world = World::new();
+        let near = world.spawn(());
+        let far = world.spawn(());
+        let candidate = |entity, distance| ShadowCandidate {
+            entity,
+            distance,
+            max_distance: 100.0,
+        };
+        let mut scheduler = ShadowScheduler::default();
+
+        let first = scheduler.schedule(&[candidate(near, 10.0), candidate(far, 11.0)], 1);
+        assert!(first.contains(&near));
+
+        // `far` is now slightly closer, but not by enough to take the slot.
+        let second = scheduler.schedule(&[candidate(near, 11.0), candidate(far, 10.0)], 1);
+        assert!(second.contains(&near));
+
+        let third = scheduler.schedule(&[candidate(near, 20.0), candidate(far, 10.0)], 1);
+        assert!(third.contains(&far) && !third.contains(&near));
+    }
+
+    #[test]
+    fn shadow_distance_overrides_settings_default() {
+        let mut world = World::new();
+        let clipped = spawn_point_light(&mut world, Vec3::new(30.0, 0.0, 0.0));
+        world.insert_one(clipped, ShadowDistance(20.0)).unwrap();
+        let kept = spawn_point_light(&mut world, Vec3::new(0.0, 0.0, 50.0));
+
+        let candidates = shadow_candidates(&world, Vec3::ZERO, &ShadowSettings::default());
+        let mut scheduler = ShadowScheduler::default();
+        let shadowed = scheduler.schedule(&candidates, 8);
+        assert!(!shadowed.contains(&clipped));
+        assert!(shadowed.contains(&kept));
+    }
 }
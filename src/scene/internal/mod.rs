@@ -1,6 +1,15 @@
 pub mod animations;
+pub mod cameras;
+pub mod cloning;
 pub mod composition;
+pub mod culling;
 pub mod debug;
+pub mod hierarchy;
+pub mod interpolation;
 pub mod lights;
+pub mod names;
 pub mod rendering;
+pub mod serialization;
+pub mod skinning;
+pub mod text;
 pub mod transforms;
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use hecs::World;
+
+use crate::scene::components::Name;
+
+/// Remembers name lookups. Entries are checked against the entity's current
+/// [`Name`] on every hit, and all of them are dropped whenever the number of
+/// entities changes, since a spawn may add an earlier match.
+#[derive(Debug, Default)]
+pub(crate) struct NameCache {
+    entries: HashMap<String, hecs::Entity>,
+    world_len: u32,
+}
+
+impl NameCache {
+    pub(crate) fn find(&mut self, world: &World, name: &str) -> Option<hecs::Entity> {
+        if self.world_len != world.len() {
+            self.entries.clear();
+            self.world_len = world.len();
+        }
+        if let Some(&entity) = self.entries.get(name) {
+            if world
+                .get::<&Name>(entity)
+                .is_ok_and(|current| current.0 == name)
+            {
+                return Some(entity);
+            }
+            self.entries.remove(name);
+        }
+
+        let entity = find_all(world, name).into_iter().next()?;
+        self.entries.insert(name.to_owned(), entity);
+        Some(entity)
+    }
+}
+
+/// Every entity whose [`Name`] is exactly `name`, in query order.
+pub(crate) fn find_all(world: &World, name: &str) -> Vec<hecs::Entity> {
+    world
+        .query::<&Name>()
+        .iter()
+        .filter(|(_, current)| current.0 == name)
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_names_return_every_match() {
+        let mut world = World::new();
+        let first = world.spawn((Name::new("Pawn"),));
+        let second = world.spawn((Name::new("Pawn"),));
+        world.spawn((Name::new("King"),));
+
+        let pawns = find_all(&world, "Pawn");
+        assert_eq!(pawns.len(), 2);
+        assert!(pawns.contains(&first) && pawns.contains(&second));
+
+        let mut cache = NameCache::default();
+        let found = cache.find(&world, "Pawn").unwrap();
+        assert!(pawns.contains(&found));
+        assert_eq!(cache.find(&world, "Pawn"), Some(found));
+        assert_eq!(cache.find(&world, "Queen"), None);
+    }
+
+    #[test]
+    fn cached_entries_follow_renames_and_despawns() {
+        let mut world = World::new();
+        let knight = world.spawn((Name::new("Knight"),));
+        let mut cache = NameCache::default();
+        assert_eq!(cache.find(&world, "Knight"), Some(knight));
+
+        world.get::<&mut Name>(knight).unwrap().0 = "Rook".to_owned();
+        assert_eq!(cache.find(&world, "Knight"), None);
+        assert_eq!(cache.find(&world, "Rook"), Some(knight));
+
+        world.despawn(knight).unwrap();
+        let replacement = world.spawn((Name::new("Rook"),));
+        assert_eq!(cache.find(&world, "Rook"), Some(replacement));
+    }
+}
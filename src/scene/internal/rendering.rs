@@ -1,14 +1,19 @@
+use super::culling::Frustum;
 use super::lights::safe_normalize;
-use crate::asset::{Handle, Mesh};
-use crate::renderer::{batch::InstanceSource, Material, RenderObject, Renderer};
+use crate::asset::{Assets, Handle, Mesh};
+use crate::renderer::{
+    batch::InstanceSource, ManualDrawList, Material, RenderBatcher, RenderObject, Renderer,
+};
 use crate::scene::components::{
-    Billboard, BillboardOrientation, BillboardSpace, DepthState, GpuParticleInstance,
-    MaterialComponent, MeshComponent, Name, TransformComponent, Visible, WorldTransform,
+    Billboard, BillboardOrientation, BillboardSpace, BoundingBox, DepthState, GpuParticleInstance,
+    MaterialComponent, MeshComponent, MorphWeights, Name, Outline, Skin, TransformComponent,
+    Visible, WorldTransform,
 };
 use crate::scene::transform::Transform;
-use glam::{Mat3, Quat, Vec3};
+use glam::{Mat3, Mat4, Quat, Vec3};
 use hecs::World;
 use rayon::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 #[derive(Clone, Copy)]
 pub(crate) struct CameraVectors {
@@ -25,18 +30,133 @@ impl CameraVectors {
             up: renderer.camera_up(),
         }
     }
+
+    pub(crate) fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.position, self.target, self.up)
+    }
 }
 
-pub(crate) fn build_render_objects(world: &World, camera: CameraVectors) -> Vec<RenderObject> {
+/// A render object together with the per-frame deformation data the caller
+/// registers with the batcher.
+pub(crate) struct FrameObject {
+    pub(crate) object: RenderObject,
+    pub(crate) joint_matrices: Option<Vec<Mat4>>,
+    pub(crate) morph_weights: Option<Vec<f32>>,
+}
+
+/// Render objects for one frame, plus how many entities the frustum rejected.
+pub(crate) struct FrameObjects {
+    pub(crate) objects: Vec<FrameObject>,
+    pub(crate) culled: u32,
+}
+
+/// Builds render objects for all visible entities, paired with the joint
+/// palette of skinned meshes and the weights of morphed ones so the caller
+/// can register them with the batcher. Entities whose [`BoundingBox`] lies
+/// outside `frustum` are skipped. An [`Outline`] adds a second, stencil
+/// tested object for the rim.
+pub(crate) fn build_render_objects(
+    world: &World,
+    camera: CameraVectors,
+    frustum: Option<&Frustum>,
+) -> FrameObjects {
     let render_entities = collect_render_entities(world);
+    let culled = AtomicU32::new(0);
 
-    render_entities
+    let objects = render_entities
         .into_par_iter()
-        .filter_map(|entity| prepare_render_object(camera, entity))
-        .collect()
+        .filter_map(|mut entity| {
+            if let Some(frustum) = frustum {
+                if entity.visible && is_outside_frustum(&entity, frustum) {
+                    culled.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+            }
+            let joint_matrices = entity.joint_matrices.take();
+            let morph_weights = entity.morph_weights.take();
+            let outline = entity.outline;
+            let bounds = entity.bounds;
+            let mut object = prepare_render_object(camera, entity)?;
+            let shell = outline.map(|outline| {
+                object
+                    .depth_state
+                    .stencil_write
+                    .get_or_insert(Outline::STENCIL_VALUE);
+                FrameObject {
+                    object: outline_shell(&object, outline, bounds, camera),
+                    joint_matrices: joint_matrices.clone(),
+                    morph_weights: morph_weights.clone(),
+                }
+            });
+            let frame_object = FrameObject {
+                object,
+                joint_matrices,
+                morph_weights,
+            };
+            Some((frame_object, shell))
+        })
+        .flat_map_iter(|(frame_object, shell)| std::iter::once(frame_object).chain(shell))
+        .collect();
+
+    FrameObjects {
+        objects,
+        culled: culled.into_inner(),
+    }
+}
+
+/// Adds the renderer's manual draws to `batcher`, skipping those whose mesh
+/// bounds lie outside `frustum`.
+pub(crate) fn add_manual_draws(
+    draws: &ManualDrawList,
+    assets: &Assets,
+    camera: CameraVectors,
+    frustum: Option<&Frustum>,
+    batcher: &mut RenderBatcher,
+) {
+    let view = camera.view();
+    let mut added = 0;
+    let mut culled = 0;
+    for (_, draw) in draws.iter() {
+        let world_bounds = assets
+            .meshes
+            .get(draw.mesh)
+            .and_then(|mesh| mesh.geometry().bounds())
+            .map(|bounds| bounds.transformed(draw.transform.matrix()));
+        if let (Some(frustum), Some(bounds)) = (frustum, &world_bounds) {
+            if !frustum.intersects_aabb(bounds) {
+                culled += 1;
+                continue;
+            }
+        }
+        let mut object = draw.render_object();
+        let centroid = world_bounds.map_or(draw.transform.translation, |bounds| bounds.center());
+        object.update_sort_key(view, centroid);
+        batcher.add(object);
+        added += 1;
+    }
+    batcher.record_manual(added);
+    batcher.record_culled(culled);
+}
+
+/// Billboards, GPU-driven instances, skinned and morphed meshes move away
+/// from their authored bounds, so they are always kept.
+fn is_outside_frustum(entity: &RenderEntity, frustum: &Frustum) -> bool {
+    let Some(bounds) = entity.bounds else {
+        return false;
+    };
+    if entity.billboard.is_some()
+        || entity.gpu_instance.is_some()
+        || entity.joint_matrices.is_some()
+        || entity.morph_weights.is_some()
+    {
+        return false;
+    }
+    let world_bounds = bounds.transformed(select_render_transform(entity).matrix());
+    !frustum.intersects_aabb(&world_bounds)
 }
 
 struct RenderEntity {
+    entity: hecs::Entity,
     mesh: Handle<Mesh>,
     material: Material,
     visible: bool,
@@ -46,6 +166,10 @@ struct RenderEntity {
     billboard: Option<Billboard>,
     depth_state: Option<DepthState>,
     gpu_instance: Option<GpuParticleInstance>,
+    joint_matrices: Option<Vec<Mat4>>,
+    morph_weights: Option<Vec<f32>>,
+    bounds: Option<BoundingBox>,
+    outline: Option<Outline>,
 }
 
 fn collect_render_entities(world: &World) -> Vec<RenderEntity> {
@@ -60,11 +184,15 @@ fn collect_render_entities(world: &World) -> Vec<RenderEntity> {
             Option<&Billboard>,
             Option<&DepthState>,
             Option<&GpuParticleInstance>,
+            Option<&Skin>,
+            Option<&MorphWeights>,
+            Option<&BoundingBox>,
+            Option<&Outline>,
         )>()
         .iter()
         .map(
             |(
-                _entity,
+                entity,
                 (
                     mesh,
                     material,
@@ -75,8 +203,13 @@ fn collect_render_entities(world: &World) -> Vec<RenderEntity> {
                     billboard,
                     depth_state,
                     gpu_instance,
+                    skin,
+                    morph_weights,
+                    bounds,
+                    outline,
                 ),
             )| RenderEntity {
+                entity,
                 mesh: mesh.0,
                 material: material.0,
                 visible: visible.0,
@@ -86,6 +219,10 @@ fn collect_render_entities(world: &World) -> Vec<RenderEntity> {
                 billboard: billboard.copied(),
                 depth_state: depth_state.copied(),
                 gpu_instance: gpu_instance.copied(),
+                joint_matrices: skin.map(|s| s.joint_matrices.clone()),
+                morph_weights: morph_weights.map(|w| w.0.clone()),
+                bounds: bounds.copied(),
+                outline: outline.copied(),
             },
         )
         .collect()
@@ -125,8 +262,11 @@ fn prepare_render_object(camera: CameraVectors, entity: RenderEntity) -> Option<
 
     let depth_state = entity.depth_state.unwrap_or_default();
     let force_overlay = billboard.is_some() && !depth_state.depth_test && !depth_state.depth_write;
+    let centroid = entity.bounds.map_or(transform.translation, |bounds| {
+        bounds.transformed(transform.matrix()).center()
+    });
 
-    Some(RenderObject {
+    let mut object = RenderObject {
         mesh: entity.mesh,
         material,
         transform,
@@ -134,7 +274,51 @@ fn prepare_render_object(camera: CameraVectors, entity: RenderEntity) -> Option<
         force_overlay,
         instance_source,
         gpu_index,
-    })
+        joint_offset: None,
+        morph_weight_offset: None,
+        entity: Some(entity.entity),
+        sort_key: 0.0,
+    };
+    object.update_sort_key(camera.view(), centroid);
+    Some(object)
+}
+
+/// The rim drawn around `object`: an unlit copy scaled about its bounds
+/// centre that only draws outside the silhouette `object` marks in the
+/// stencil buffer. It writes no depth, so the rest of the scene is unaffected.
+fn outline_shell(
+    object: &RenderObject,
+    outline: Outline,
+    bounds: Option<BoundingBox>,
+    camera: CameraVectors,
+) -> RenderObject {
+    let transform = object.transform;
+    let centre = bounds.map_or(transform.translation, |bounds| {
+        bounds.transformed(transform.matrix()).center()
+    });
+    let shell_transform = Transform {
+        translation: centre + (transform.translation - centre) * outline.scale,
+        rotation: transform.rotation,
+        scale: transform.scale * outline.scale,
+    };
+    let mut depth_state = DepthState::new(object.depth_state.depth_test, false).with_stencil_test(
+        object
+            .depth_state
+            .stencil_write
+            .unwrap_or(Outline::STENCIL_VALUE),
+    );
+    depth_state.wireframe = object.depth_state.wireframe;
+
+    let mut shell = RenderObject {
+        material: Material::new(outline.color).with_unlit(),
+        transform: shell_transform,
+        depth_state,
+        // Motion vectors are tracked per entity; the copy reports none.
+        entity: None,
+        ..*object
+    };
+    shell.update_sort_key(camera.view(), centre);
+    shell
 }
 
 fn select_render_transform(entity: &RenderEntity) -> Transform {
@@ -324,4 +508,77 @@ mod tests {
         assert!(result.translation.abs_diff_eq(transform.translation, 1e-5));
         assert!((result.rotation * Vec3::Z).abs_diff_eq(expected_forward, 1e-5));
     }
+
+    #[test]
+    fn manual_draws_instance_with_entities_sharing_their_mesh() {
+        let mesh = Handle::new(0);
+        let mut world = World::new();
+        world.spawn((
+            MeshComponent(mesh),
+            MaterialComponent(Material::white()),
+            Visible(true),
+            WorldTransform(Transform::IDENTITY),
+        ));
+
+        let mut draws = ManualDrawList::new();
+        for x in [1.0, 2.0] {
+            let transform = Transform::from_trs(Vec3::new(x, 0.0, 0.0), Quat::IDENTITY, Vec3::ONE);
+            draws.register_static(mesh, Material::white(), transform);
+        }
+
+        let camera = CameraVectors {
+            position: Vec3::Z,
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+        };
+        let mut batcher = RenderBatcher::new();
+        for frame_object in build_render_objects(&world, camera, None).objects {
+            batcher.add(frame_object.object);
+        }
+        add_manual_draws(&draws, &Assets::new(), camera, None, &mut batcher);
+
+        assert_eq!(batcher.batch_count(), 1);
+        assert_eq!(batcher.iter().next().unwrap().instances.len(), 3);
+        assert_eq!(batcher.manual_objects(), 2);
+    }
+
+    #[test]
+    fn outlined_entity_adds_a_scaled_stencil_tested_shell() {
+        let mut world = World::new();
+        world.spawn((
+            MeshComponent(Handle::new(0)),
+            MaterialComponent(Material::white()),
+            Visible(true),
+            WorldTransform(Transform::IDENTITY),
+            BoundingBox {
+                min: Vec3::new(-1.0, 0.0, -1.0),
+                max: Vec3::new(1.0, 2.0, 1.0),
+            },
+            Outline::new([255, 200, 0, 255]).with_scale(1.5),
+        ));
+        let camera = CameraVectors {
+            position: Vec3::Z * 5.0,
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+        };
+
+        let objects = build_render_objects(&world, camera, None).objects;
+        assert_eq!(objects.len(), 2);
+        let (original, shell) = (&objects[0].object, &objects[1].object);
+
+        assert_eq!(
+            original.depth_state.stencil_write,
+            Some(Outline::STENCIL_VALUE)
+        );
+        assert_eq!(shell.depth_state.stencil_test, Some(Outline::STENCIL_VALUE));
+        assert!(!shell.depth_state.depth_write);
+        assert!(shell.material.is_unlit());
+        assert!(shell.entity.is_none());
+        // Scaled about the bounds centre, so the rim grows evenly.
+        assert_eq!(shell.transform.scale, Vec3::splat(1.5));
+        assert!(shell
+            .transform
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, -0.5, 0.0), 1e-5));
+    }
 }
@@ -0,0 +1,720 @@
+//! Scene snapshots: entities, camera, animation clips and playback states.
+//!
+//! Entities are stored as a flat table and refer to each other by their
+//! position in it, since `hecs::Entity` ids are not stable between worlds.
+//! Meshes are stored as the glTF primitive they were loaded from and
+//! textures as their asset cache keys; [`resolve_assets`] re-imports them.
+
+use std::collections::{BTreeMap, HashMap};
+
+use hecs::{Entity, World};
+use serde::{Deserialize, Serialize};
+
+use crate::asset::AssetCache;
+use crate::renderer::texture::DEFAULT_TEXTURE_COUNT;
+use crate::renderer::Texture;
+use crate::scene::animation::{
+    AnimationChannel, AnimationClip, AnimationSampler, AnimationState, AnimationTarget,
+    MaterialProperty, TransformProperty,
+};
+use crate::scene::components::{
+    Billboard, CanCastShadow, Children, DirectionalLight, MaterialComponent, MeshComponent,
+    MeshSource, Name, Parent, PointLight, ShadowDistance, SpotLight, TransformComponent, Visible,
+};
+use crate::scene::Camera;
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SceneDocument {
+    pub(crate) camera: Camera,
+    pub(crate) entities: Vec<EntityRecord>,
+    #[serde(default)]
+    pub(crate) animations: Vec<ClipRecord>,
+    #[serde(default)]
+    pub(crate) animation_states: Vec<AnimationState>,
+    #[serde(default)]
+    pub(crate) textures: Vec<TextureRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct EntityRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<Name>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transform: Option<TransformComponent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    visible: Option<Visible>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    material: Option<MaterialComponent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mesh: Option<MeshSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    billboard: Option<Billboard>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    directional_light: Option<DirectionalLight>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    point_light: Option<PointLight>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spot_light: Option<SpotLight>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    can_cast_shadow: Option<CanCastShadow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shadow_distance: Option<ShadowDistance>,
+    /// Index of the parent in [`SceneDocument::entities`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<usize>,
+    /// Components this version does not know; skipped with a warning.
+    #[serde(flatten)]
+    unknown: BTreeMap<String, serde_json::Value>,
+}
+
+/// Texture slot referenced by a saved material or spot light cookie, with
+/// the key it was cached under so loading can find its new slot.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TextureRecord {
+    slot: u32,
+    key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ClipRecord {
+    name: String,
+    duration: f32,
+    channels: Vec<ChannelRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChannelRecord {
+    sampler: AnimationSampler,
+    target: TargetRecord,
+}
+
+/// [`AnimationTarget`] with entities replaced by entity table indices.
+#[derive(Serialize, Deserialize)]
+enum TargetRecord {
+    Transform {
+        entity: usize,
+        property: TransformProperty,
+    },
+    Material {
+        material_index: usize,
+        property: MaterialProperty,
+    },
+    MorphWeights {
+        entity: usize,
+    },
+}
+
+pub(crate) fn snapshot(
+    world: &World,
+    textures: &AssetCache<Texture>,
+    camera: &Camera,
+    clips: &[AnimationClip],
+    states: &[AnimationState],
+) -> SceneDocument {
+    let entities: Vec<Entity> = world.iter().map(|entity| entity.entity()).collect();
+    let indices: HashMap<Entity, usize> = entities
+        .iter()
+        .enumerate()
+        .map(|(index, &entity)| (entity, index))
+        .collect();
+
+    let unsourced = world
+        .query::<&MeshComponent>()
+        .without::<&MeshSource>()
+        .iter()
+        .count();
+    if unsourced > 0 {
+        log::warn!(
+            "{} meshes were not loaded from glTF and are not saved",
+            unsourced
+        );
+    }
+
+    let records = entities
+        .iter()
+        .map(|&entity| EntityRecord {
+            name: cloned(world, entity),
+            transform: copied(world, entity),
+            visible: copied(world, entity),
+            material: copied(world, entity),
+            mesh: cloned(world, entity),
+            billboard: copied(world, entity),
+            directional_light: copied(world, entity),
+            point_light: copied(world, entity),
+            spot_light: copied(world, entity),
+            can_cast_shadow: copied(world, entity),
+            shadow_distance: copied(world, entity),
+            parent: world
+                .get::<&Parent>(entity)
+                .ok()
+                .and_then(|parent| indices.get(&parent.0).copied()),
+            unknown: BTreeMap::new(),
+        })
+        .collect();
+
+    let animations = clips
+        .iter()
+        .map(|clip| ClipRecord {
+            name: clip.name.clone(),
+            duration: clip.duration,
+            channels: clip
+                .channels
+                .iter()
+                .filter_map(|channel| {
+                    let target = match channel.target {
+                        AnimationTarget::Transform { entity, property } => {
+                            TargetRecord::Transform {
+                                entity: *indices.get(&entity)?,
+                                property,
+                            }
+                        }
+                        AnimationTarget::Material {
+                            material_index,
+                            property,
+                        } => TargetRecord::Material {
+                            material_index,
+                            property,
+                        },
+                        AnimationTarget::MorphWeights { entity } => TargetRecord::MorphWeights {
+                            entity: *indices.get(&entity)?,
+                        },
+                    };
+                    Some(ChannelRecord {
+                        sampler: channel.sampler.clone(),
+                        target,
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    SceneDocument {
+        camera: *camera,
+        entities: records,
+        animations,
+        animation_states: states.to_vec(),
+        textures: texture_records(world, textures),
+    }
+}
+
+fn copied<T: hecs::Component + Copy>(world: &World, entity: Entity) -> Option<T> {
+    world.get::<&T>(entity).ok().map(|component| *component)
+}
+
+fn cloned<T: hecs::Component + Clone>(world: &World, entity: Entity) -> Option<T> {
+    world
+        .get::<&T>(entity)
+        .ok()
+        .map(|component| (*component).clone())
+}
+
+/// Cache keys of the non-default texture slots used by materials and spot
+/// light cookies. Textures inserted without a key cannot be found again and
+/// are left out.
+fn texture_records(world: &World, textures: &AssetCache<Texture>) -> Vec<TextureRecord> {
+    let mut slots: Vec<u32> = world
+        .query::<&MaterialComponent>()
+        .iter()
+        .flat_map(|(_, material)| material.0.texture_indices())
+        .chain(
+            world
+                .query::<&SpotLight>()
+                .iter()
+                .filter_map(|(_, spot)| spot.cookie_texture),
+        )
+        .filter(|&slot| slot >= DEFAULT_TEXTURE_COUNT)
+        .collect();
+    slots.sort_unstable();
+    slots.dedup();
+
+    slots
+        .into_iter()
+        .filter_map(|slot| {
+            let key = textures
+                .handle_at(slot as usize)
+                .and_then(|handle| textures.key_of(handle));
+            if key.is_none() {
+                log::warn!("Texture slot {} has no cache key and is not saved", slot);
+            }
+            Some(TextureRecord {
+                slot,
+                key: key?.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// What [`restore`] spawned, with clips and states retargeted at it.
+pub(crate) struct Restored {
+    pub(crate) entities: Vec<Entity>,
+    pub(crate) clips: Vec<AnimationClip>,
+    /// Clip indices are relative to [`Self::clips`].
+    pub(crate) states: Vec<AnimationState>,
+    pub(crate) textures: Vec<TextureRecord>,
+}
+
+/// Spawns the document's entities into `world` with their meshes still
+/// unresolved. Fails on out-of-range entity or clip indices.
+pub(crate) fn restore(document: SceneDocument, world: &mut World) -> Result<Restored, String> {
+    let count = document.entities.len();
+    let check = |index: usize| {
+        (index < count)
+            .then_some(index)
+            .ok_or_else(|| format!("Entity index {} out of range ({} entities)", index, count))
+    };
+
+    let mut parents = Vec::with_capacity(count);
+    let mut entities = Vec::with_capacity(count);
+    for record in document.entities {
+        parents.push(record.parent.map(check).transpose()?);
+
+        for key in record.unknown.keys() {
+            log::warn!("Skipping unknown component '{}'", key);
+        }
+
+        let mut builder = hecs::EntityBuilder::new();
+        if let Some(name) = record.name {
+            builder.add(name);
+        }
+        if let Some(transform) = record.transform {
+            builder.add(transform);
+        }
+        if let Some(visible) = record.visible {
+            builder.add(visible);
+        }
+        if let Some(material) = record.material {
+            builder.add(material);
+        }
+        if let Some(mesh) = record.mesh {
+            builder.add(mesh);
+        }
+        if let Some(billboard) = record.billboard {
+            builder.add(billboard);
+        }
+        if let Some(light) = record.directional_light {
+            builder.add(light);
+        }
+        if let Some(light) = record.point_light {
+            builder.add(light);
+        }
+        if let Some(light) = record.spot_light {
+            builder.add(light);
+        }
+        if let Some(shadow) = record.can_cast_shadow {
+            builder.add(shadow);
+        }
+        if let Some(distance) = record.shadow_distance {
+            builder.add(distance);
+        }
+        entities.push(world.spawn(builder.build()));
+    }
+
+    let mut children: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (index, parent) in parents.into_iter().enumerate() {
+        let Some(parent) = parent else {
+            continue;
+        };
+        let (child, parent) = (entities[index], entities[parent]);
+        world.insert_one(child, Parent(parent)).ok();
+        children.entry(parent).or_default().push(child);
+    }
+    for (parent, children) in children {
+        world.insert_one(parent, Children(children)).ok();
+    }
+
+    let clips = document
+        .animations
+        .into_iter()
+        .map(|record| {
+            let mut clip = AnimationClip::new(record.name);
+            for channel in record.channels {
+                let target = match channel.target {
+                    TargetRecord::Transform { entity, property } => AnimationTarget::Transform {
+                        entity: entities[check(entity)?],
+                        property,
+                    },
+                    TargetRecord::Material {
+                        material_index,
+                        property,
+                    } => AnimationTarget::Material {
+                        material_index,
+                        property,
+                    },
+                    TargetRecord::MorphWeights { entity } => AnimationTarget::MorphWeights {
+                        entity: entities[check(entity)?],
+                    },
+                };
+                clip.add_channel(AnimationChannel {
+                    sampler: channel.sampler,
+                    target,
+                });
+            }
+            clip.duration = clip.duration.max(record.duration);
+            Ok(clip)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if let Some(state) = document
+        .animation_states
+        .iter()
+        .find(|state| state.clip_index >= clips.len())
+    {
+        return Err(format!(
+            "Animation state clip {} out of range ({} clips)",
+            state.clip_index,
+            clips.len()
+        ));
+    }
+
+    Ok(Restored {
+        entities,
+        clips,
+        states: document.animation_states,
+        textures: document.textures,
+    })
+}
+
+/// Re-imports the glTF meshes recorded on `entities` and points their
+/// materials and spot light cookies at the re-imported textures. Files that
+/// fail to load leave their entities without a mesh.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn resolve_assets(
+    scene: &mut crate::scene::Scene,
+    renderer: &mut crate::renderer::Renderer,
+    entities: &[Entity],
+    textures: &[TextureRecord],
+) {
+    let sources: Vec<(Entity, MeshSource)> = entities
+        .iter()
+        .filter_map(|&entity| Some((entity, cloned(&scene.world, entity)?)))
+        .collect();
+
+    let mut imports: HashMap<(&str, u32), Option<_>> = HashMap::new();
+    for (entity, source) in &sources {
+        let primitives = imports
+            .entry((source.path.as_str(), source.scale.to_bits()))
+            .or_insert_with(|| {
+                crate::scene::SceneLoader::load_gltf_assets(
+                    std::path::Path::new(&source.path),
+                    scene,
+                    renderer,
+                    source.scale,
+                )
+                .map_err(|err| log::warn!("Could not re-import {}: {}", source.path, err))
+                .ok()
+            });
+        let Some(primitives) = primitives else {
+            continue;
+        };
+        let Some(&(mesh, _, bounds)) = primitives
+            .get(source.mesh)
+            .and_then(|mesh| mesh.get(source.primitive))
+        else {
+            log::warn!(
+                "{} has no primitive {} in mesh {}",
+                source.path,
+                source.primitive,
+                source.mesh
+            );
+            continue;
+        };
+        scene.world.insert_one(*entity, MeshComponent(mesh)).ok();
+        if let Some(bounds) = bounds {
+            scene.world.insert_one(*entity, bounds).ok();
+        }
+    }
+
+    let slots: HashMap<u32, u32> = textures
+        .iter()
+        .filter_map(|record| {
+            let handle = scene.assets.textures.get_by_key(&record.key);
+            if handle.is_none() {
+                log::warn!("No texture loaded for '{}'", record.key);
+            }
+            Some((record.slot, handle?.index() as u32))
+        })
+        .collect();
+    let remap = |slot: u32| slots.get(&slot).copied().unwrap_or(slot);
+    for &entity in entities {
+        if let Ok(mut material) = scene.world.get::<&mut MaterialComponent>(entity) {
+            material.0.remap_textures(remap);
+        }
+        if let Ok(mut spot) = scene.world.get::<&mut SpotLight>(entity) {
+            spot.cookie_texture = spot.cookie_texture.map(remap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{vec3, Quat, Vec3};
+
+    use crate::renderer::Material;
+    use crate::scene::animation::{AnimationInterpolation, AnimationOutput};
+    use crate::scene::components::{BillboardOrientation, BillboardSpace, WorldTransform};
+    use crate::scene::{Scene, Transform};
+
+    use super::*;
+
+    fn find(scene: &Scene, name: &str) -> hecs::Entity {
+        scene
+            .world
+            .query::<&Name>()
+            .iter()
+            .find(|(_, entity_name)| entity_name.0 == name)
+            .map(|(entity, _)| entity)
+            .unwrap_or_else(|| panic!("no entity named {}", name))
+    }
+
+    #[test]
+    fn scene_round_trips_through_json() {
+        let mut scene = Scene::new();
+        *scene.camera_mut() = Camera::orthographic(6.0, 4.0, 0.5, 40.0);
+
+        let lamp_transform =
+            Transform::from_trs(vec3(1.0, 3.0, -2.0), Quat::from_rotation_x(-0.5), Vec3::ONE);
+        let rig = scene.world.spawn((
+            Name::new("Rig"),
+            TransformComponent(Transform::IDENTITY),
+            Visible(true),
+        ));
+        let lamp = scene.world.spawn((
+            Name::new("Lamp"),
+            TransformComponent(lamp_transform),
+            Parent(rig),
+            SpotLight {
+                color: vec3(1.0, 0.8, 0.6),
+                intensity: 25.0,
+                inner_angle: 0.3,
+                outer_angle: 0.5,
+                range: 15.0,
+                cookie_texture: Some(5),
+            },
+            CanCastShadow(true),
+            MaterialComponent(Material::new([10, 20, 30, 255]).with_roughness(0.25)),
+        ));
+        scene.world.insert_one(rig, Children(vec![lamp])).unwrap();
+
+        let mut clip = AnimationClip::new("bob");
+        clip.add_channel(AnimationChannel {
+            sampler: AnimationSampler {
+                times: vec![0.0, 2.0],
+                output: AnimationOutput::Vec3(vec![Vec3::ZERO, Vec3::Y]),
+                interpolation: AnimationInterpolation::Linear,
+            },
+            target: AnimationTarget::Transform {
+                entity: lamp,
+                property: TransformProperty::Translation,
+            },
+        });
+        scene.add_animation_clip(clip);
+        let state = scene.play_animation(0, true).unwrap();
+        scene.animation_states_mut()[state].time = 0.5;
+
+        let mut json = Vec::new();
+        scene.save_to_json(&mut json).unwrap();
+        let loaded = Scene::load_from_json(json.as_slice()).unwrap();
+
+        assert_eq!(loaded.world.len(), 2);
+        assert_eq!(
+            loaded.camera().projection,
+            scene.camera().projection,
+            "camera projection"
+        );
+
+        let rig = find(&loaded, "Rig");
+        let lamp = find(&loaded, "Lamp");
+        assert_eq!(loaded.world.get::<&Parent>(lamp).unwrap().0, rig);
+        assert_eq!(loaded.world.get::<&Children>(rig).unwrap().0, vec![lamp]);
+        assert!(loaded.world.get::<&Visible>(rig).unwrap().0);
+
+        let transform = loaded.world.get::<&TransformComponent>(lamp).unwrap().0;
+        assert_eq!(transform.translation, lamp_transform.translation);
+        assert_eq!(transform.rotation, lamp_transform.rotation);
+
+        let spot = *loaded.world.get::<&SpotLight>(lamp).unwrap();
+        assert_eq!(spot.intensity, 25.0);
+        assert_eq!(spot.outer_angle, 0.5);
+        assert_eq!(spot.cookie_texture, Some(5));
+        assert!(loaded.world.get::<&CanCastShadow>(lamp).unwrap().0);
+
+        let material = loaded.world.get::<&MaterialComponent>(lamp).unwrap().0;
+        assert_eq!(
+            material,
+            Material::new([10, 20, 30, 255]).with_roughness(0.25)
+        );
+
+        let clips = loaded.animations();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].name, "bob");
+        assert_eq!(clips[0].duration, 2.0);
+        match clips[0].channels[0].target {
+            AnimationTarget::Transform { entity, property } => {
+                assert_eq!(entity, lamp);
+                assert_eq!(property, TransformProperty::Translation);
+            }
+            other => panic!("unexpected target {:?}", other),
+        }
+
+        let states = loaded.animation_states();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].clip_index, 0);
+        assert_eq!(states[0].time, 0.5);
+        assert!(states[0].looping && states[0].playing);
+    }
+
+    fn spawn_node(
+        scene: &mut Scene,
+        name: &str,
+        translation: Vec3,
+        scale: f32,
+        parent: Option<hecs::Entity>,
+    ) -> hecs::Entity {
+        let transform = Transform::from_trs(
+            translation,
+            Quat::from_rotation_y(translation.x),
+            Vec3::splat(scale),
+        );
+        let entity = scene.world.spawn((
+            Name::new(name),
+            TransformComponent(transform),
+            Visible(true),
+        ));
+        if let Some(parent) = parent {
+            scene.world.insert_one(entity, Parent(parent)).unwrap();
+            let mut children = scene
+                .world
+                .get::<&Children>(parent)
+                .map(|children| children.0.clone())
+                .unwrap_or_default();
+            children.push(entity);
+            scene.world.insert_one(parent, Children(children)).unwrap();
+        }
+        entity
+    }
+
+    #[test]
+    fn hierarchy_round_trip_preserves_world_transforms() {
+        let mut scene = Scene::new();
+        let parent1 = spawn_node(&mut scene, "Parent1", vec3(-6.0, 0.0, 0.0), 1.0, None);
+        spawn_node(
+            &mut scene,
+            "Child1",
+            vec3(2.0, 0.0, 0.0),
+            0.5,
+            Some(parent1),
+        );
+        let grandparent = spawn_node(&mut scene, "Grandparent", Vec3::ZERO, 1.0, None);
+        let parent2 = spawn_node(
+            &mut scene,
+            "Parent2",
+            vec3(0.0, 2.0, 0.0),
+            0.8,
+            Some(grandparent),
+        );
+        let child2 = spawn_node(
+            &mut scene,
+            "Child2",
+            vec3(0.5, 1.5, 0.0),
+            0.6,
+            Some(parent2),
+        );
+
+        let source = MeshSource {
+            path: "models/crate.gltf".to_string(),
+            mesh: 2,
+            primitive: 1,
+            scale: 0.5,
+        };
+        scene.world.insert_one(child2, source.clone()).unwrap();
+        scene
+            .world
+            .insert_one(
+                parent2,
+                Billboard::new(BillboardOrientation::FaceCameraYAxis).with_space(
+                    BillboardSpace::View {
+                        offset: vec3(0.0, 0.0, 2.0),
+                    },
+                ),
+            )
+            .unwrap();
+        scene.update(0.0);
+
+        let mut json = Vec::new();
+        scene.save_to_json(&mut json).unwrap();
+        let mut loaded = Scene::load_from_json(json.as_slice()).unwrap();
+        loaded.update(0.0);
+
+        assert_eq!(loaded.world.len(), scene.world.len());
+        for name in ["Parent1", "Child1", "Grandparent", "Parent2", "Child2"] {
+            let (original, copy) = (find(&scene, name), find(&loaded, name));
+            let parent_name = |scene: &Scene, entity| {
+                scene
+                    .world
+                    .get::<&Parent>(entity)
+                    .ok()
+                    .map(|parent| scene.world.get::<&Name>(parent.0).unwrap().0.clone())
+            };
+            assert_eq!(
+                parent_name(&loaded, copy),
+                parent_name(&scene, original),
+                "{} parent",
+                name
+            );
+
+            let expected = scene.world.get::<&WorldTransform>(original).unwrap().0;
+            let actual = loaded.world.get::<&WorldTransform>(copy).unwrap().0;
+            assert!(
+                actual.matrix().abs_diff_eq(expected.matrix(), 1e-5),
+                "{} world transform",
+                name
+            );
+        }
+
+        let grandparent = find(&loaded, "Grandparent");
+        let parent2 = find(&loaded, "Parent2");
+        assert_eq!(
+            loaded.world.get::<&Children>(grandparent).unwrap().0,
+            vec![parent2]
+        );
+        let child2 = find(&loaded, "Child2");
+        assert_eq!(*loaded.world.get::<&MeshSource>(child2).unwrap(), source);
+        assert!(loaded.world.get::<&MeshComponent>(child2).is_err());
+
+        let billboard = *loaded.world.get::<&Billboard>(parent2).unwrap();
+        assert_eq!(billboard.orientation, BillboardOrientation::FaceCameraYAxis);
+        assert!(matches!(
+            billboard.space,
+            BillboardSpace::View { offset } if offset == vec3(0.0, 0.0, 2.0)
+        ));
+    }
+
+    #[test]
+    fn unknown_components_are_skipped() {
+        let json = r#"{
+            "camera": { "eye": [0.0, 0.0, 5.0], "target": [0.0, 0.0, 0.0], "up": [0.0, 1.0, 0.0],
+                        "projection": { "Perspective": { "fov": 1.0, "near": 0.1, "far": 100.0 } } },
+            "entities": [
+                { "name": "Root", "rigid_body": { "mass": 2.0 } },
+                { "name": "Child", "parent": 0, "script": "spin.lua" }
+            ]
+        }"#;
+        let loaded = Scene::load_from_json(json.as_bytes()).unwrap();
+
+        assert_eq!(loaded.world.len(), 2);
+        let root = find(&loaded, "Root");
+        let child = find(&loaded, "Child");
+        assert_eq!(loaded.world.get::<&Parent>(child).unwrap().0, root);
+    }
+
+    #[test]
+    fn out_of_range_parent_is_rejected() {
+        let json = r#"{
+            "camera": { "eye": [0.0, 0.0, 5.0], "target": [0.0, 0.0, 0.0], "up": [0.0, 1.0, 0.0],
+                        "projection": { "Perspective": { "fov": 1.0, "near": 0.1, "far": 100.0 } } },
+            "entities": [ { "parent": 3 } ]
+        }"#;
+        let err = Scene::load_from_json(json.as_bytes()).err().expect("error");
+        assert!(err.to_string().contains("out of range"));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::scene::components::{Skin, WorldTransform};
+use glam::Mat4;
+use hecs::World;
+
+/// Rebuilds the joint palette of every [`Skin`] from the current world
+/// transforms. Must run after `propagate_transforms`.
+///
+/// Joint matrices are expressed relative to the skinned mesh, because the
+/// vertex shader still applies the mesh's own model matrix afterwards.
+pub(crate) fn update_skins(world: &mut World) {
+    let palettes: Vec<(hecs::Entity, Vec<Mat4>)> = world
+        .query::<(&Skin, Option<&WorldTransform>)>()
+        .iter()
+        .map(|(entity, (skin, mesh_world))| {
+            let inverse_mesh = mesh_world
+                .map(|t| t.0.matrix().inverse())
+                .unwrap_or(Mat4::IDENTITY);
+
+            let palette = skin
+                .joints
+                .iter()
+                .enumerate()
+                .map(|(i, &joint)| {
+                    let joint_world = world
+                        .get::<&WorldTransform>(joint)
+                        .map(|t| t.0.matrix())
+                        .unwrap_or(Mat4::IDENTITY);
+                    let inverse_bind = skin
+                        .inverse_bind_matrices
+                        .get(i)
+                        .copied()
+                        .unwrap_or(Mat4::IDENTITY);
+                    inverse_mesh * joint_world * inverse_bind
+                })
+                .collect();
+
+            (entity, palette)
+        })
+        .collect();
+
+    for (entity, palette) in palettes {
+        if let Ok(mut skin) = world.get::<&mut Skin>(entity) {
+            skin.joint_matrices = palette;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::animation::{
+        AnimationChannel, AnimationClip, AnimationInterpolation, AnimationOutput, AnimationSampler,
+        AnimationState, AnimationTarget, TransformProperty,
+    };
+    use crate::scene::components::{Children, Parent, TransformComponent};
+    use crate::scene::internal::{animations, transforms};
+    use crate::scene::transform::Transform;
+    use glam::{Quat, Vec3};
+
+    #[test]
+    fn animated_joint_deforms_bound_vertex() {
+        let mut world = World::new();
+
+        let root = world.spawn((TransformComponent(Transform::IDENTITY),));
+        let child = world.spawn((
+            TransformComponent(Transform::from_trs(
+                Vec3::new(0.0, 1.0, 0.0),
+                Quat::IDENTITY,
+                Vec3::ONE,
+            )),
+            Parent(root),
+        ));
+        world.insert_one(root, Children(vec![child])).unwrap();
+
+        let inverse_bind = vec![
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(0.0, -1.0, 0.0)),
+        ];
+        let mesh = world.spawn((
+            TransformComponent(Transform::IDENTITY),
+            Skin::new(vec![root, child], inverse_bind),
+        ));
+
+        let mut clip = AnimationClip::new("bend");
+        clip.add_channel(AnimationChannel {
+            sampler: AnimationSampler {
+                times: vec![0.0, 1.0],
+                output: AnimationOutput::Quat(vec![
+                    Quat::IDENTITY,
+                    Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+                ]),
+                interpolation: AnimationInterpolation::Linear,
+            },
+            target: AnimationTarget::Transform {
+                entity: child,
+                property: TransformProperty::Rotation,
+            },
+        });
+        let mut state = AnimationState::new(0);
+        state.looping = false;
+        let mut states = vec![state];
+
+        animations::advance_animations(
+            &mut world,
+            &[clip],
+            &mut states,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            1.0,
+        );
+        transforms::propagate_transforms(&mut world);
+        update_skins(&mut world);
+
+        let skin = world.get::<&Skin>(mesh).unwrap();
+        assert_eq!(skin.joint_matrices.len(), 2);
+        assert!(skin.joint_matrices[0].abs_diff_eq(Mat4::IDENTITY, 1e-5));
+
+        // A vertex one unit above the child joint swings to its left.
+        let deformed = skin.joint_matrices[1].transform_point3(Vec3::new(0.0, 2.0, 0.0));
+        assert!(
+            deformed.abs_diff_eq(Vec3::new(-1.0, 1.0, 0.0), 1e-4),
+            "unexpected skinned position {:?}",
+            deformed
+        );
+    }
+
+    #[test]
+    fn joint_matrices_are_relative_to_mesh() {
+        let mut world = World::new();
+        let offset = Transform::from_trs(Vec3::new(3.0, 0.0, 0.0), Quat::IDENTITY, Vec3::ONE);
+
+        let joint = world.spawn((TransformComponent(offset),));
+        let mesh = world.spawn((
+            TransformComponent(offset),
+            Skin::new(vec![joint], vec![Mat4::IDENTITY]),
+        ));
+
+        transforms::propagate_transforms(&mut world);
+        update_skins(&mut world);
+
+        let skin = world.get::<&Skin>(mesh).unwrap();
+        assert!(skin.joint_matrices[0].abs_diff_eq(Mat4::IDENTITY, 1e-5));
+    }
+}
@@ -0,0 +1,122 @@
+use hecs::World;
+
+use crate::asset::{Assets, Handle, Mesh};
+use crate::renderer::text::FontAtlas;
+use crate::renderer::{Material, Renderer};
+use crate::scene::components::{
+    Billboard, BillboardOrientation, MaterialComponent, MeshComponent, TextBillboard, Visible,
+};
+
+/// The label a text entity's mesh was last built from.
+pub(crate) struct TextMeshState {
+    label: TextBillboard,
+    /// `None` for labels without visible glyphs.
+    mesh: Option<Handle<Mesh>>,
+}
+
+/// The scene's font atlas, created the first time a label needs it.
+#[derive(Default)]
+pub(crate) struct TextLabels {
+    atlas: Option<(FontAtlas, u32)>,
+    disabled: bool,
+}
+
+impl TextLabels {
+    /// Builds meshes for new and changed [`TextBillboard`]s and releases
+    /// those whose label component was removed.
+    pub(crate) fn update(&mut self, world: &mut World, assets: &mut Assets, renderer: &Renderer) {
+        release_removed_labels(world, assets);
+
+        let stale: Vec<(hecs::Entity, TextBillboard)> = world
+            .query::<(&TextBillboard, Option<&TextMeshState>)>()
+            .iter()
+            .filter(|(_, (label, state))| state.is_none_or(|state| state.label != **label))
+            .map(|(entity, (label, _))| (entity, label.clone()))
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+        let Some((atlas, texture_index)) = self.atlas(assets, renderer) else {
+            return;
+        };
+
+        for (entity, label) in stale {
+            let text = atlas.layout(&label.text, label.font_size, label.anchor);
+            let mesh = (!text.indices.is_empty()).then(|| {
+                assets
+                    .meshes
+                    .insert(renderer.create_mesh(&text.vertices, &text.indices))
+            });
+            let material = Material::new(label.color)
+                .with_base_color_texture(texture_index)
+                .with_distance_field_alpha()
+                .with_alpha()
+                .with_unlit();
+
+            let previous = world
+                .get::<&TextMeshState>(entity)
+                .ok()
+                .and_then(|state| state.mesh);
+            if let Some(previous) = previous {
+                assets.meshes.remove(previous);
+            }
+
+            match mesh {
+                Some(mesh) => {
+                    world.insert_one(entity, MeshComponent(mesh)).ok();
+                }
+                None => {
+                    world.remove_one::<MeshComponent>(entity).ok();
+                }
+            }
+            world.insert_one(entity, MaterialComponent(material)).ok();
+            if world.get::<&Billboard>(entity).is_err() {
+                let billboard = Billboard::new(BillboardOrientation::FaceCamera);
+                world.insert_one(entity, billboard).ok();
+            }
+            if world.get::<&Visible>(entity).is_err() {
+                world.insert_one(entity, Visible(true)).ok();
+            }
+            world.insert_one(entity, TextMeshState { label, mesh }).ok();
+        }
+    }
+
+    /// The atlas and its texture slot, uploading it on first use.
+    fn atlas(&mut self, assets: &mut Assets, renderer: &Renderer) -> Option<(&FontAtlas, u32)> {
+        if self.atlas.is_none() && !self.disabled {
+            match FontAtlas::default_font() {
+                Ok(atlas) => {
+                    let texture = atlas.create_texture(renderer.get_device(), renderer.get_queue());
+                    let handle = assets.textures.insert(texture);
+                    self.atlas = Some((atlas, handle.index() as u32));
+                }
+                Err(err) => {
+                    log::warn!("Text labels disabled: {}", err);
+                    self.disabled = true;
+                }
+            }
+        }
+        self.atlas
+            .as_ref()
+            .map(|(atlas, texture_index)| (atlas, *texture_index))
+    }
+}
+
+/// Removes the generated mesh and material of entities that lost their
+/// [`TextBillboard`].
+fn release_removed_labels(world: &mut World, assets: &mut Assets) {
+    let removed: Vec<(hecs::Entity, Option<Handle<Mesh>>)> = world
+        .query::<&TextMeshState>()
+        .without::<&TextBillboard>()
+        .iter()
+        .map(|(entity, state)| (entity, state.mesh))
+        .collect();
+    for (entity, mesh) in removed {
+        if let Some(mesh) = mesh {
+            assets.meshes.remove(mesh);
+        }
+        world.remove_one::<TextMeshState>(entity).ok();
+        world.remove_one::<MeshComponent>(entity).ok();
+        world.remove_one::<MaterialComponent>(entity).ok();
+    }
+}
@@ -1,8 +1,19 @@
 use crate::scene::components::{Children, Parent, TransformComponent, WorldTransform};
+use crate::scene::internal::interpolation::PreviousTransform;
 use crate::scene::transform::Transform;
 use hecs::World;
 
 pub(crate) fn propagate_transforms(world: &mut World) {
+    propagate(world, None);
+}
+
+/// Propagates with each [`PreviousTransform`] blended towards the current
+/// local transform by `alpha`, for rendering between two fixed ticks.
+pub(crate) fn propagate_interpolated_transforms(world: &mut World, alpha: f32) {
+    propagate(world, Some(alpha));
+}
+
+fn propagate(world: &mut World, alpha: Option<f32>) {
     let roots: Vec<hecs::Entity> = world
         .query::<&TransformComponent>()
         .without::<&Parent>()
@@ -25,6 +36,13 @@ pub(crate) fn propagate_transforms(world: &mut World) {
                     continue;
                 }
             };
+            let local = match alpha {
+                Some(alpha) => world
+                    .get::<&PreviousTransform>(entity)
+                    .map(|previous| previous.0.lerp(&local, alpha))
+                    .unwrap_or(local),
+                None => local,
+            };
 
             let world_transform = parent_world.mul_transform(&local);
 
@@ -1,21 +1,28 @@
 // scene/loader.rs - Improved version with better debugging
-use glam::{Quat, Vec3, Vec4};
-use std::path::Path;
+use glam::{Mat4, Quat, Vec3, Vec4};
+use std::path::{Path, PathBuf};
 
 use super::components::*;
 use crate::asset::Handle;
 use crate::asset::Mesh;
-use crate::renderer::{Material, Renderer, Texture, Vertex};
+use crate::environment::EnvironmentMap;
+use crate::renderer::{
+    is_ktx2, Material, MorphDelta, Renderer, SamplerKey, SkinVertex, Texture, TextureTransform,
+    Vertex,
+};
 use crate::scene::animation::{
     AnimationChannel, AnimationClip, AnimationInterpolation, AnimationOutput, AnimationSampler,
     AnimationTarget, MaterialProperty, TransformProperty,
 };
-use crate::scene::{Scene, Transform};
+use crate::scene::async_loader::GltfLoadHandle;
+use crate::scene::{Camera, CameraProjection, Scene, Transform};
 use bytemuck::cast_slice;
 use gltf::json::validation::Checked;
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io;
 
 pub struct SceneLoader;
@@ -26,12 +33,15 @@ struct MaterialPointerTarget {
     property: MaterialProperty,
 }
 
-type GltfImport = (
+pub(crate) type GltfImport = (
     gltf::Document,
     Vec<gltf::buffer::Data>,
     Vec<gltf::image::Data>,
 );
 
+/// Mesh handle, material index and local bounds of one glTF primitive.
+pub(crate) type LoadedPrimitive = (Handle<Mesh>, Option<usize>, Option<BoundingBox>);
+
 impl SceneLoader {
     fn reconcile_keyframe_lengths<T>(
         times: &mut Vec<f32>,
@@ -96,16 +106,24 @@ impl SceneLoader {
         !times.is_empty() && values.len() >= times.len() * components_per_keyframe
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn load_node(
         node: &gltf::Node,
         parent: Option<hecs::Entity>,
-        mesh_handles: &[Vec<(Handle<Mesh>, Option<usize>)>],
+        source: &Path,
+        mesh_handles: &[Vec<LoadedPrimitive>],
         materials: &[Material],
         world: &mut hecs::World,
         scale_multiplier: f32,
         node_entities: &mut [Option<hecs::Entity>],
     ) -> Result<hecs::Entity, String> {
         let node_name = node.name().unwrap_or("Unnamed");
+        let mesh_source = |mesh: usize, primitive: usize| MeshSource {
+            path: source.to_string_lossy().into_owned(),
+            mesh,
+            primitive,
+            scale: scale_multiplier,
+        };
         log::debug!(
             "Loading node: {} (index: {}, parent: {:?})",
             node_name,
@@ -153,7 +171,7 @@ impl SceneLoader {
         // Handle mesh primitives
         // The first primitive is added to this entity
         // Additional primitives become child entities
-        let mut extra_primitives: Vec<(Handle<Mesh>, Option<usize>)> = Vec::new();
+        let mut extra_primitives: Vec<LoadedPrimitive> = Vec::new();
 
         if let Some(gltf_mesh) = node.mesh() {
             log::debug!(
@@ -164,8 +182,12 @@ impl SceneLoader {
             if let Some(primitives) = mesh_handles.get(gltf_mesh.index()) {
                 if !primitives.is_empty() {
                     // Add first primitive to this entity
-                    let (mesh_handle, material_index) = primitives[0];
+                    let (mesh_handle, material_index, bounds) = primitives[0];
                     entity_builder.add(MeshComponent(mesh_handle));
+                    entity_builder.add(mesh_source(gltf_mesh.index(), 0));
+                    if let Some(bounds) = bounds {
+                        entity_builder.add(bounds);
+                    }
 
                     let material = if let Some(mat_idx) = material_index {
                         materials.get(mat_idx).copied().unwrap_or(Material::pbr())
@@ -201,7 +223,7 @@ impl SceneLoader {
         let mut children = Vec::new();
 
         // Spawn extra mesh primitives as child entities
-        for (primitive_index, (mesh_handle, material_index)) in
+        for (primitive_index, (mesh_handle, material_index, bounds)) in
             extra_primitives.into_iter().enumerate()
         {
             let primitive_name = format!("{}_Primitive_{}", node_name, primitive_index + 1);
@@ -215,6 +237,12 @@ impl SceneLoader {
             primitive_builder.add(Visible(true));
             primitive_builder.add(Parent(entity));
             primitive_builder.add(MeshComponent(mesh_handle));
+            if let Some(gltf_mesh) = node.mesh() {
+                primitive_builder.add(mesh_source(gltf_mesh.index(), primitive_index + 1));
+            }
+            if let Some(bounds) = bounds {
+                primitive_builder.add(bounds);
+            }
 
             let material = if let Some(mat_idx) = material_index {
                 materials.get(mat_idx).copied().unwrap_or(Material::pbr())
@@ -237,6 +265,7 @@ impl SceneLoader {
             let child_entity = Self::load_node(
                 &child_node,
                 Some(entity),
+                source,
                 mesh_handles,
                 materials,
                 world,
@@ -259,6 +288,358 @@ impl SceneLoader {
         Ok(entity)
     }
 
+    /// Attaches a [`Skin`] to every node that references a glTF skin, and to the
+    /// child entities holding that node's extra primitives.
+    fn load_skins(
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+        node_entities: &[Option<hecs::Entity>],
+        world: &mut hecs::World,
+        scale_multiplier: f32,
+    ) {
+        for node in document.nodes() {
+            let Some(gltf_skin) = node.skin() else {
+                continue;
+            };
+            let Some(entity) = node_entities.get(node.index()).copied().flatten() else {
+                continue;
+            };
+
+            let joints: Vec<hecs::Entity> = gltf_skin
+                .joints()
+                .filter_map(|joint| node_entities.get(joint.index()).copied().flatten())
+                .collect();
+            if joints.len() != gltf_skin.joints().len() {
+                log::warn!(
+                    "Skin {} on node '{}' references joints outside the loaded scene; skipping",
+                    gltf_skin.index(),
+                    node.name().unwrap_or("Unnamed")
+                );
+                continue;
+            }
+
+            let reader = gltf_skin.reader(|buffer| Some(&buffers[buffer.index()]));
+            let inverse_bind_matrices: Vec<glam::Mat4> = match reader.read_inverse_bind_matrices() {
+                Some(matrices) => matrices
+                    .map(|m| {
+                        // Vertex positions and node translations are both
+                        // scaled on load, so the bind pose must follow.
+                        let mut m = glam::Mat4::from_cols_array_2d(&m);
+                        m.w_axis.x *= scale_multiplier;
+                        m.w_axis.y *= scale_multiplier;
+                        m.w_axis.z *= scale_multiplier;
+                        m
+                    })
+                    .collect(),
+                None => vec![glam::Mat4::IDENTITY; joints.len()],
+            };
+
+            log::debug!(
+                "  Skin {} on node '{}': {} joints",
+                gltf_skin.index(),
+                node.name().unwrap_or("Unnamed"),
+                joints.len()
+            );
+
+            let skin = Skin::new(joints, inverse_bind_matrices);
+            let primitive_children: Vec<hecs::Entity> = world
+                .get::<&Children>(entity)
+                .map(|children| {
+                    children
+                        .0
+                        .iter()
+                        .copied()
+                        .filter(|&child| {
+                            world.get::<&MeshComponent>(child).is_ok()
+                                && world.get::<&GltfNode>(child).is_err()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            for child in primitive_children {
+                world.insert_one(child, skin.clone()).ok();
+            }
+            world.insert_one(entity, skin).ok();
+        }
+    }
+
+    /// Number of morph targets on a glTF mesh, taken from its largest primitive.
+    fn morph_target_count(mesh: &gltf::Mesh) -> usize {
+        mesh.primitives()
+            .map(|primitive| primitive.morph_targets().len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Lists the cameras embedded in a glTF file as `(node name, camera)`
+    /// pairs, in scene traversal order. Node translations are multiplied by
+    /// `scale` exactly as [`Self::load_gltf`] does.
+    pub fn available_cameras(
+        path: impl AsRef<Path>,
+        scale: f32,
+    ) -> Result<Vec<(Name, Camera)>, String> {
+        let path = path.as_ref();
+
+        #[cfg(target_arch = "wasm32")]
+        let (document, _, _) =
+            Self::import_gltf_web(path).map_err(|e| format!("Failed to load glTF: {}", e))?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (document, _, _) =
+            Self::import_gltf_native(path).map_err(|e| format!("Failed to load glTF: {}", e))?;
+
+        Ok(Self::load_cameras(&document, scale))
+    }
+
+    fn load_cameras(document: &gltf::Document, scale: f32) -> Vec<(Name, Camera)> {
+        let mut cameras = Vec::new();
+        for gltf_scene in document.scenes() {
+            for node in gltf_scene.nodes() {
+                Self::collect_cameras(&node, Mat4::IDENTITY, scale, &mut cameras);
+            }
+        }
+        cameras
+    }
+
+    fn collect_cameras(
+        node: &gltf::Node,
+        parent_world: Mat4,
+        scale: f32,
+        cameras: &mut Vec<(Name, Camera)>,
+    ) {
+        let (translation, rotation, node_scale) = node.transform().decomposed();
+        let world = parent_world
+            * Mat4::from_scale_rotation_translation(
+                Vec3::from(node_scale),
+                Quat::from_array(rotation),
+                Vec3::from(translation) * scale,
+            );
+
+        if let Some(gltf_camera) = node.camera() {
+            let name = node
+                .name()
+                .or_else(|| gltf_camera.name())
+                .unwrap_or("Unnamed");
+            cameras.push((Name::new(name), Self::camera_from_gltf(&gltf_camera, world)));
+        }
+
+        for child in node.children() {
+            Self::collect_cameras(&child, world, scale, cameras);
+        }
+    }
+
+    /// glTF cameras look down their local -Z axis with +Y up.
+    fn camera_from_gltf(gltf_camera: &gltf::Camera, world: Mat4) -> Camera {
+        Camera::from_world_matrix(world, Self::projection_from_gltf(gltf_camera))
+    }
+
+    /// The projection of a glTF camera, with invalid parameters replaced by
+    /// defaults.
+    fn projection_from_gltf(gltf_camera: &gltf::Camera) -> CameraProjection {
+        let mut camera = Camera::default();
+
+        let (near, far) = match gltf_camera.projection() {
+            gltf::camera::Projection::Perspective(perspective) => {
+                if let Err(err) = camera.set_fov_y(perspective.yfov()) {
+                    log::warn!("glTF camera has an invalid field of view: {}", err);
+                }
+                let near = perspective.znear();
+                // Infinite projections get a finite far plane well past near.
+                let far = perspective.zfar().unwrap_or(near * 10_000.0);
+                (near, far)
+            }
+            gltf::camera::Projection::Orthographic(orthographic) => {
+                // xmag/ymag are half extents.
+                camera.projection = CameraProjection::Orthographic {
+                    width: orthographic.xmag().abs() * 2.0,
+                    height: orthographic.ymag().abs() * 2.0,
+                    near: orthographic.znear(),
+                    far: orthographic.zfar(),
+                };
+                (orthographic.znear(), orthographic.zfar())
+            }
+        };
+        if let Err(err) = camera.set_clip_planes(near, far) {
+            log::warn!("glTF camera has invalid clip planes: {}", err);
+        }
+
+        camera.projection
+    }
+
+    /// Attaches a [`CameraComponent`] to every node referencing a glTF
+    /// camera and returns how many were created. Animations move camera
+    /// nodes like any other; pass one to [`Scene::set_active_camera`] to
+    /// view through it.
+    fn load_camera_components(
+        document: &gltf::Document,
+        node_entities: &[Option<hecs::Entity>],
+        world: &mut hecs::World,
+    ) -> usize {
+        let mut loaded = 0;
+        for node in document.nodes() {
+            let Some(gltf_camera) = node.camera() else {
+                continue;
+            };
+            let Some(entity) = node_entities.get(node.index()).copied().flatten() else {
+                continue;
+            };
+            let projection = Self::projection_from_gltf(&gltf_camera);
+            if world
+                .insert_one(entity, CameraComponent(projection))
+                .is_ok()
+            {
+                loaded += 1;
+            }
+        }
+        loaded
+    }
+
+    /// Attaches light components to every node referencing a
+    /// `KHR_lights_punctual` light and returns how many were created. Lights
+    /// shine down the node's local -Z axis. Lux and candela map one-to-one
+    /// onto the renderer's intensities; punctual intensities are multiplied
+    /// by `scale²` so inverse-square falloff looks the same after scaling.
+    /// Only the first directional light casts shadows.
+    fn load_lights(
+        document: &gltf::Document,
+        node_entities: &[Option<hecs::Entity>],
+        world: &mut hecs::World,
+        scale: f32,
+    ) -> usize {
+        use gltf::khr_lights_punctual::Kind;
+
+        let mut loaded = 0;
+        let mut shadow_caster_assigned = false;
+        for node in document.nodes() {
+            let Some(light) = node.light() else {
+                continue;
+            };
+            let Some(entity) = node_entities.get(node.index()).copied().flatten() else {
+                continue;
+            };
+
+            let color = Vec3::from(light.color());
+            let intensity = light.intensity().max(0.0);
+            let punctual_intensity = intensity * scale * scale;
+            let range = light
+                .range()
+                .filter(|range| *range > 0.0)
+                .map(|range| range * scale)
+                .unwrap_or_else(|| Self::light_cutoff_range(punctual_intensity));
+
+            let inserted = match light.kind() {
+                Kind::Directional => {
+                    let casts_shadow = !shadow_caster_assigned;
+                    shadow_caster_assigned = true;
+                    world.insert(
+                        entity,
+                        (
+                            DirectionalLight::new(color, intensity),
+                            CanCastShadow(casts_shadow),
+                        ),
+                    )
+                }
+                Kind::Point => world.insert(
+                    entity,
+                    (
+                        PointLight {
+                            color,
+                            intensity: punctual_intensity,
+                            range,
+                        },
+                        CanCastShadow(false),
+                    ),
+                ),
+                Kind::Spot {
+                    inner_cone_angle,
+                    outer_cone_angle,
+                } => world.insert(
+                    entity,
+                    (
+                        SpotLight {
+                            color,
+                            intensity: punctual_intensity,
+                            inner_angle: inner_cone_angle,
+                            outer_angle: outer_cone_angle,
+                            range,
+                            cookie_texture: None,
+                        },
+                        CanCastShadow(false),
+                    ),
+                ),
+            };
+
+            if inserted.is_ok() {
+                loaded += 1;
+            }
+        }
+        loaded
+    }
+
+    /// glTF punctual lights without a range reach infinitely far; cut them
+    /// off where their illuminance drops below 1% of a unit directional light.
+    fn light_cutoff_range(intensity: f32) -> f32 {
+        const CUTOFF_ILLUMINANCE: f32 = 0.01;
+        (intensity / CUTOFF_ILLUMINANCE).sqrt().clamp(1.0, 1000.0)
+    }
+
+    /// Attaches [`MorphWeights`] to every node whose mesh has morph targets,
+    /// and to the child entities holding that node's extra primitives. The
+    /// initial weights come from the node, then the mesh, then zero.
+    fn load_morph_weights(
+        document: &gltf::Document,
+        node_entities: &[Option<hecs::Entity>],
+        world: &mut hecs::World,
+    ) {
+        for node in document.nodes() {
+            let Some(gltf_mesh) = node.mesh() else {
+                continue;
+            };
+            let target_count = Self::morph_target_count(&gltf_mesh);
+            if target_count == 0 {
+                continue;
+            }
+            let Some(entity) = node_entities.get(node.index()).copied().flatten() else {
+                continue;
+            };
+
+            let mut weights = node
+                .weights()
+                .or_else(|| gltf_mesh.weights())
+                .map(|weights| weights.to_vec())
+                .unwrap_or_default();
+            weights.resize(target_count, 0.0);
+
+            log::debug!(
+                "  Morph weights on node '{}': {} targets",
+                node.name().unwrap_or("Unnamed"),
+                target_count
+            );
+
+            let primitive_children: Vec<hecs::Entity> = world
+                .get::<&Children>(entity)
+                .map(|children| {
+                    children
+                        .0
+                        .iter()
+                        .copied()
+                        .filter(|&child| {
+                            world.get::<&MeshComponent>(child).is_ok()
+                                && world.get::<&GltfNode>(child).is_err()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let weights = MorphWeights(weights);
+            for child in primitive_children {
+                world.insert_one(child, weights.clone()).ok();
+            }
+            world.insert_one(entity, weights).ok();
+        }
+    }
+
     /// Load a glTF file into the scene with scale
     pub fn load_gltf(
         path: impl AsRef<Path>,
@@ -269,14 +650,72 @@ impl SceneLoader {
         let path = path.as_ref();
         log::info!("=== Loading glTF: {:?} ===", path);
 
+        let import = Self::import_gltf(path)?;
+        let prepared = Self::prepare_gltf(path, import, &mut || {})?;
+
+        let mut finalizer = GltfFinalizer::new(prepared, scale);
+        while !finalizer.step(scene, renderer, usize::MAX)? {}
+
+        Ok(())
+    }
+
+    /// Upload the meshes and textures of a glTF file into `scene`'s assets
+    /// without spawning its nodes, for rebuilding the handles of a saved
+    /// scene. Returns each glTF mesh's primitives in document order.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn load_gltf_assets(
+        path: &Path,
+        scene: &mut Scene,
+        renderer: &mut Renderer,
+        scale: f32,
+    ) -> Result<Vec<Vec<LoadedPrimitive>>, String> {
+        let import = Self::import_gltf(path)?;
+        let prepared = Self::prepare_gltf(path, import, &mut || {})?;
+
+        let mut finalizer = GltfFinalizer::new(prepared, scale);
+        finalizer.upload(scene, renderer, usize::MAX)?;
+        Ok(finalizer.mesh_handles)
+    }
+
+    /// Load an equirectangular `.hdr` or `.exr` panorama and bake it into
+    /// the specular and irradiance cubemaps used for image-based lighting.
+    /// Pass the handle to [`Scene::set_environment_map`] to use it.
+    pub fn load_hdr_environment(
+        path: impl AsRef<Path>,
+        renderer: &mut Renderer,
+    ) -> Result<Handle<EnvironmentMap>, String> {
+        let path = path.as_ref();
+        log::info!("Loading HDR environment {:?}", path);
+        renderer.load_environment_map(path)
+    }
+
+    /// Start loading a glTF file in the background. File IO and image
+    /// decoding run off the main thread; poll the returned handle once per
+    /// frame to upload the results and spawn the scene.
+    pub fn load_gltf_async(path: impl AsRef<Path>, scale: f32) -> GltfLoadHandle {
+        GltfLoadHandle::start(path.as_ref(), scale)
+    }
+
+    /// Read a glTF file and its buffers and images into memory.
+    pub(crate) fn import_gltf(path: &Path) -> Result<GltfImport, String> {
         #[cfg(target_arch = "wasm32")]
-        let (document, buffers, images) =
+        let import =
             Self::import_gltf_web(path).map_err(|e| format!("Failed to load glTF: {}", e))?;
 
         #[cfg(not(target_arch = "wasm32"))]
-        let (document, buffers, images) =
+        let import =
             Self::import_gltf_native(path).map_err(|e| format!("Failed to load glTF: {}", e))?;
 
+        Ok(import)
+    }
+
+    /// Convert every texture of an imported glTF to RGBA8 so the GPU upload
+    /// needs no further CPU work. `on_decoded` runs after each texture.
+    pub(crate) fn prepare_gltf(
+        path: &Path,
+        (document, buffers, images): GltfImport,
+        on_decoded: &mut dyn FnMut(),
+    ) -> Result<PreparedGltf, String> {
         log::info!(
             "Document info: {} meshes, {} materials, {} textures, {} scenes",
             document.meshes().len(),
@@ -285,113 +724,243 @@ impl SceneLoader {
             document.scenes().len()
         );
 
-        // Get the base directory for loading external textures
+        // Get the base directory for labelling external textures
         let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
 
-        // Load all textures first
-        log::info!("Loading textures...");
-        let texture_handles = Self::load_textures(&document, &images, base_dir, scene, renderer)?;
-        log::info!("Loaded {} textures", texture_handles.len());
-
-        // Load all materials
-        log::info!("Loading materials...");
-        let material_handles = Self::load_materials(&document, &texture_handles)?;
-        log::info!("Loaded {} materials", material_handles.len());
+        log::info!("Decoding textures...");
+        let mut textures = Vec::with_capacity(document.textures().len());
+        // Texture entries sharing an image are decoded once; later entries
+        // only carry the cache key and upload nothing.
+        let mut image_keys: HashMap<usize, String> = HashMap::new();
+        for gltf_texture in document.textures() {
+            let source = gltf_texture.source();
+            let image = images
+                .get(source.index())
+                .ok_or_else(|| format!("Missing image data for image {}", source.index()))?;
 
-        // Load all meshes (each mesh can have multiple primitives)
-        log::info!("Loading meshes...");
-        let mesh_count = document.meshes().len();
-        let mut mesh_handles: Vec<Vec<(Handle<Mesh>, Option<usize>)>> =
-            vec![Vec::new(); mesh_count];
+            let (label, embedded) = match source.source() {
+                gltf::image::Source::Uri { uri, .. } => {
+                    let texture_path = base_dir.join(uri);
+                    log::debug!("  Decoding texture from file: {:?}", texture_path);
+                    (texture_path.to_string_lossy().into_owned(), false)
+                }
+                gltf::image::Source::View { .. } => {
+                    log::debug!(
+                        "  Decoding embedded texture: {}x{}",
+                        image.width,
+                        image.height
+                    );
+                    (format!("EmbeddedTexture_{}", source.index()), true)
+                }
+            };
 
-        let mut mesh_cache: HashMap<Vec<u8>, Handle<Mesh>> = HashMap::new();
+            let ktx2 = Self::is_ktx2_image(image);
+            let pixels = if image_keys.contains_key(&source.index()) {
+                Vec::new()
+            } else if ktx2 {
+                image.pixels.clone()
+            } else {
+                Self::rgba8_pixels(image)?
+            };
+            let key = image_keys
+                .entry(source.index())
+                .or_insert_with(|| {
+                    Self::texture_cache_key(&source, base_dir, &pixels, image, embedded)
+                })
+                .clone();
+
+            textures.push(DecodedTexture {
+                pixels,
+                width: image.width,
+                height: image.height,
+                embedded,
+                ktx2,
+                label,
+                key,
+                sampler: Self::sampler_key(&gltf_texture.sampler()),
+            });
+            on_decoded();
+        }
 
-        for gltf_mesh in document.meshes() {
-            let mesh_index = gltf_mesh.index();
-            let mesh_name = gltf_mesh.name().unwrap_or("Unnamed");
-            let primitive_count = gltf_mesh.primitives().len();
+        Ok(PreparedGltf {
+            path: path.to_path_buf(),
+            document,
+            buffers,
+            textures,
+        })
+    }
 
-            log::debug!(
-                "  Mesh {}: '{}' with {} primitives",
-                mesh_index,
-                mesh_name,
-                primitive_count
-            );
+    /// The fixed sampler closest to a glTF sampler. Clamping either axis
+    /// clamps both, mirrored repeat falls back to repeat, and the
+    /// magnification filter decides between nearest and linear.
+    pub(crate) fn sampler_key(sampler: &gltf::texture::Sampler) -> SamplerKey {
+        use gltf::texture::{MagFilter, MinFilter, WrappingMode};
 
-            let primitives = &mut mesh_handles[mesh_index];
+        let wraps = [sampler.wrap_s(), sampler.wrap_t()];
+        if wraps.contains(&WrappingMode::MirroredRepeat) {
+            log::debug!("Mirrored repeat is not supported, using repeat");
+        }
+        let nearest = match sampler.mag_filter() {
+            Some(filter) => filter == MagFilter::Nearest,
+            None => matches!(
+                sampler.min_filter(),
+                Some(MinFilter::Nearest | MinFilter::NearestMipmapNearest)
+            ),
+        };
+        SamplerKey::new(wraps.contains(&WrappingMode::ClampToEdge), nearest)
+    }
 
-            for primitive in gltf_mesh.primitives() {
-                let handle = Self::load_primitive(
-                    &primitive,
-                    &buffers,
-                    scene,
-                    renderer,
-                    scale,
-                    &mut mesh_cache,
-                )?;
-                primitives.push((handle, primitive.material().index()));
+    /// Identifies an image across documents so [`AssetCache::get_or_insert_with`](crate::asset::AssetCache::get_or_insert_with)
+    /// can share the GPU texture: the canonical path for image files, a hash
+    /// of the decoded pixels for embedded images, plus the sRGB flag.
+    fn texture_cache_key(
+        source: &gltf::Image,
+        base_dir: &Path,
+        pixels: &[u8],
+        image: &gltf::image::Data,
+        srgb: bool,
+    ) -> String {
+        match source.source() {
+            gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => {
+                let texture_path = base_dir.join(uri);
+                let texture_path = fs::canonicalize(&texture_path).unwrap_or(texture_path);
+                format!("uri:{}|srgb={}", texture_path.to_string_lossy(), srgb)
+            }
+            _ => {
+                let mut hasher = DefaultHasher::new();
+                image.width.hash(&mut hasher);
+                image.height.hash(&mut hasher);
+                pixels.hash(&mut hasher);
+                format!("hash:{:016x}|srgb={}", hasher.finish(), srgb)
             }
         }
-        log::info!("Loaded {} meshes", mesh_count);
-
-        // Track the spawned entity for each glTF node so animations can target them
-        let mut node_entities: Vec<Option<hecs::Entity>> = vec![None; document.nodes().len()];
+    }
 
-        // Load all scenes and their node hierarchies
-        log::info!("Loading scene hierarchies...");
-        for (scene_index, gltf_scene) in document.scenes().enumerate() {
-            let scene_name = gltf_scene.name().unwrap_or("Unnamed");
-            let root_count = gltf_scene.nodes().len();
+    /// KTX2 images skip CPU decoding: the container travels in `pixels`
+    /// with a zero size until [`Texture::from_ktx2`] uploads it.
+    fn ktx2_image(bytes: Vec<u8>) -> gltf::image::Data {
+        gltf::image::Data {
+            pixels: bytes,
+            format: gltf::image::Format::R8G8B8A8,
+            width: 0,
+            height: 0,
+        }
+    }
 
-            log::info!(
-                "  Scene {}: '{}' with {} root nodes (scale: {}x)",
-                scene_index,
-                scene_name,
-                root_count,
-                scale
-            );
+    fn is_ktx2_image(image: &gltf::image::Data) -> bool {
+        image.width == 0 && image.height == 0 && is_ktx2(&image.pixels)
+    }
 
-            for (node_index, node) in gltf_scene.nodes().enumerate() {
-                log::info!(
-                    "    Loading root node {}/{}: {:?}",
-                    node_index + 1,
-                    root_count,
-                    node.name()
-                );
+    /// Expand decoded glTF image data to tightly packed RGBA8.
+    fn rgba8_pixels(image: &gltf::image::Data) -> Result<Vec<u8>, String> {
+        use gltf::image::Format;
+        use image::{DynamicImage, ImageBuffer};
 
-                Self::load_node(
-                    &node,
-                    None,
-                    &mesh_handles,
-                    &material_handles,
-                    &mut scene.world,
-                    scale,
-                    &mut node_entities,
-                )?;
-            }
+        fn u16s(bytes: &[u8]) -> Vec<u16> {
+            bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+                .collect()
         }
 
-        log::info!("Loading animations...");
-        Self::load_animations(&document, &buffers, &node_entities, scene, path, scale)?;
+        fn f32s(bytes: &[u8]) -> Vec<f32> {
+            bytes
+                .chunks_exact(4)
+                .map(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                .collect()
+        }
 
-        log::info!("=== glTF loaded successfully ===");
-        log::info!("Total entities in scene: {}", scene.world.len());
+        let (width, height) = (image.width, image.height);
+        let pixels = image.pixels.clone();
+        let dynamic =
+            match image.format {
+                Format::R8G8B8A8 => {
+                    ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+                }
+                Format::R8 => {
+                    ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8)
+                }
+                Format::R8G8 => {
+                    ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLumaA8)
+                }
+                Format::R8G8B8 => {
+                    ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+                }
+                Format::R16 => ImageBuffer::from_raw(width, height, u16s(&pixels))
+                    .map(DynamicImage::ImageLuma16),
+                Format::R16G16 => ImageBuffer::from_raw(width, height, u16s(&pixels))
+                    .map(DynamicImage::ImageLumaA16),
+                Format::R16G16B16 => ImageBuffer::from_raw(width, height, u16s(&pixels))
+                    .map(DynamicImage::ImageRgb16),
+                Format::R16G16B16A16 => ImageBuffer::from_raw(width, height, u16s(&pixels))
+                    .map(DynamicImage::ImageRgba16),
+                Format::R32G32B32FLOAT => ImageBuffer::from_raw(width, height, f32s(&pixels))
+                    .map(DynamicImage::ImageRgb32F),
+                Format::R32G32B32A32FLOAT => ImageBuffer::from_raw(width, height, f32s(&pixels))
+                    .map(DynamicImage::ImageRgba32F),
+            };
 
-        // Count entities with different components
-        let mesh_count = scene.world.query::<&MeshComponent>().iter().count();
-        let parent_count = scene.world.query::<&Parent>().iter().count();
-        let children_count = scene.world.query::<&Children>().iter().count();
+        dynamic
+            .map(|image| image.to_rgba8().into_raw())
+            .ok_or_else(|| {
+                format!(
+                    "Image data does not match its {:?} format ({}x{})",
+                    image.format, width, height
+                )
+            })
+    }
 
-        log::info!("  Entities with meshes: {}", mesh_count);
-        log::info!("  Entities with parent: {}", parent_count);
-        log::info!("  Entities with children: {}", children_count);
+    /// Like `gltf::import`, but `.ktx2` images are kept as KTX2 containers
+    /// instead of failing to decode.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_gltf_files(path: &Path) -> Result<GltfImport, gltf::Error> {
+        let gltf::Gltf { document, blob } = gltf::Gltf::open(path)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("./"));
+        let buffers = gltf::import_buffers(&document, Some(base_dir), blob)?;
+        let images = Self::import_images_native(&document, base_dir, &buffers)?;
+        Ok((document, buffers, images))
+    }
 
-        Ok(())
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_images_native(
+        document: &gltf::Document,
+        base_dir: &Path,
+        buffers: &[gltf::buffer::Data],
+    ) -> Result<Vec<gltf::image::Data>, gltf::Error> {
+        document
+            .images()
+            .map(|image| {
+                let ktx2_bytes = match image.source() {
+                    gltf::image::Source::Uri { uri, mime_type }
+                        if !uri.starts_with("data:")
+                            && (mime_type == Some("image/ktx2")
+                                || uri.to_ascii_lowercase().ends_with(".ktx2")) =>
+                    {
+                        Some(fs::read(base_dir.join(uri)).map_err(gltf::Error::Io)?)
+                    }
+                    gltf::image::Source::View {
+                        view,
+                        mime_type: "image/ktx2",
+                    } => {
+                        let begin = view.offset();
+                        let end = begin + view.length();
+                        buffers[view.buffer().index()]
+                            .get(begin..end)
+                            .map(<[u8]>::to_vec)
+                    }
+                    _ => None,
+                };
+                match ktx2_bytes {
+                    Some(bytes) => Ok(Self::ktx2_image(bytes)),
+                    None => gltf::image::Data::from_source(image.source(), Some(base_dir), buffers),
+                }
+            })
+            .collect()
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     fn import_gltf_native(path: &Path) -> Result<GltfImport, gltf::Error> {
-        match gltf::import(path) {
+        match Self::import_gltf_files(path) {
             Ok(result) => Ok(result),
             Err(gltf::Error::Deserialize(original))
                 if path
@@ -411,8 +980,6 @@ impl SceneLoader {
 
     #[cfg(not(target_arch = "wasm32"))]
     fn import_gltf_with_pointer_patch(path: &Path) -> Result<Option<GltfImport>, gltf::Error> {
-        use gltf::{import_buffers, import_images};
-
         let json_text = fs::read_to_string(path).map_err(gltf::Error::Io)?;
         let mut root: Value = serde_json::from_str(&json_text).map_err(gltf::Error::Deserialize)?;
 
@@ -495,8 +1062,8 @@ impl SceneLoader {
         let patched_bytes = serde_json::to_vec(&root).map_err(gltf::Error::Deserialize)?;
         let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(&patched_bytes)?;
         let base_dir = path.parent().unwrap_or_else(|| Path::new("./"));
-        let buffers = import_buffers(&document, Some(base_dir), blob)?;
-        let images = import_images(&document, Some(base_dir), &buffers)?;
+        let buffers = gltf::import_buffers(&document, Some(base_dir), blob)?;
+        let images = Self::import_images_native(&document, base_dir, &buffers)?;
         Ok(Some((document, buffers, images)))
     }
 
@@ -729,14 +1296,50 @@ impl SceneLoader {
                             continue;
                         }
                     },
-                    gltf::animation::Property::MorphTargetWeights => {
-                        log::warn!(
-                            "Skipping morph target animation '{}' channel {} (not supported)",
-                            clip_name,
-                            channel_index
-                        );
-                        continue;
-                    }
+                    gltf::animation::Property::MorphTargetWeights => match reader.read_outputs() {
+                        Some(gltf::animation::util::ReadOutputs::MorphTargetWeights(weights)) => {
+                            let target_count = target_node
+                                .mesh()
+                                .map(|mesh| Self::morph_target_count(&mesh))
+                                .unwrap_or(0);
+                            if target_count == 0 {
+                                log::warn!(
+                                    "Morph weight animation '{}' channel {} targets a node without morph targets",
+                                    clip_name,
+                                    channel_index
+                                );
+                                continue;
+                            }
+
+                            // One output element per keyframe holds a weight for every target.
+                            let flat: Vec<f32> = weights.into_f32().collect();
+                            let mut values: Vec<Vec<f32>> = flat
+                                .chunks_exact(target_count)
+                                .map(|chunk| chunk.to_vec())
+                                .collect();
+
+                            if !Self::reconcile_keyframe_lengths(
+                                &mut times,
+                                &mut values,
+                                interpolation,
+                                &clip_name,
+                                channel_index,
+                                "Morph weight",
+                            ) {
+                                continue;
+                            }
+
+                            AnimationOutput::Float(values.concat())
+                        }
+                        _ => {
+                            log::warn!(
+                                "Unexpected morph weight outputs for animation '{}' channel {}",
+                                clip_name,
+                                channel_index
+                            );
+                            continue;
+                        }
+                    },
                 };
 
                 if times.is_empty() {
@@ -762,7 +1365,9 @@ impl SceneLoader {
                         entity,
                         property: TransformProperty::Scale,
                     },
-                    gltf::animation::Property::MorphTargetWeights => unreachable!(),
+                    gltf::animation::Property::MorphTargetWeights => {
+                        AnimationTarget::MorphWeights { entity }
+                    }
                 };
 
                 clip.add_channel(AnimationChannel { sampler, target });
@@ -906,56 +1511,6 @@ impl SceneLoader {
         }
     }
 
-    /// Load all textures from glTF
-    fn load_textures(
-        document: &gltf::Document,
-        images: &[gltf::image::Data],
-        base_dir: &Path,
-        scene: &mut Scene,
-        renderer: &mut Renderer,
-    ) -> Result<Vec<u32>, String> {
-        let mut handles = Vec::new();
-
-        for gltf_texture in document.textures() {
-            let source = gltf_texture.source();
-            let texture = match source.source() {
-                gltf::image::Source::Uri { uri, .. } => {
-                    let texture_path = base_dir.join(uri);
-                    log::debug!("  Loading texture from file: {:?}", texture_path);
-
-                    Texture::from_path(
-                        renderer.get_device(),
-                        renderer.get_queue(),
-                        &texture_path,
-                        false, // sRGB
-                    )?
-                }
-                gltf::image::Source::View { .. } => {
-                    let img_data = &images[source.index()];
-                    log::debug!(
-                        "  Loading embedded texture: {}x{}",
-                        img_data.width,
-                        img_data.height
-                    );
-
-                    Texture::from_bytes(
-                        renderer.get_device(),
-                        renderer.get_queue(),
-                        &img_data.pixels,
-                        img_data.width,
-                        img_data.height,
-                        Some(&format!("EmbeddedTexture_{}", source.index())),
-                    )
-                }
-            };
-
-            let handle = scene.assets.textures.insert(texture);
-            handles.push(handle.index() as u32);
-        }
-
-        Ok(handles)
-    }
-
     /// Load all materials from glTF
     fn load_materials(
         document: &gltf::Document,
@@ -980,11 +1535,20 @@ impl SceneLoader {
                 .with_metallic(pbr.metallic_factor())
                 .with_roughness(pbr.roughness_factor());
 
-            // Base color texture
+            // Base color texture. Its sampler applies to the whole material.
             if let Some(info) = pbr.base_color_texture() {
                 let tex_index = info.texture().index();
                 if tex_index < texture_handles.len() {
-                    material = material.with_base_color_texture(texture_handles[tex_index]);
+                    let transform = Self::texture_transform(
+                        info.extensions(),
+                        info.tex_coord(),
+                        mat_name,
+                        "baseColorTexture",
+                    );
+                    material = material
+                        .with_base_color_texture(texture_handles[tex_index])
+                        .with_base_color_transform(transform)
+                        .with_sampler(Self::sampler_key(&info.texture().sampler()));
                 }
             }
 
@@ -1000,7 +1564,15 @@ impl SceneLoader {
             if let Some(normal) = gltf_mat.normal_texture() {
                 let tex_index = normal.texture().index();
                 if tex_index < texture_handles.len() {
-                    material = material.with_normal_texture(texture_handles[tex_index]);
+                    let transform = Self::texture_transform(
+                        normal.extensions(),
+                        normal.tex_coord(),
+                        mat_name,
+                        "normalTexture",
+                    );
+                    material = material
+                        .with_normal_texture(texture_handles[tex_index])
+                        .with_normal_transform(transform);
                 }
             }
 
@@ -1012,10 +1584,50 @@ impl SceneLoader {
                 }
             }
 
-            let emissive = gltf_mat.emissive_factor();
-            let emissive_strength = (emissive[0] + emissive[1] + emissive[2]) / 3.0;
-            if emissive_strength > 0.0 {
-                material = material.with_emissive(emissive_strength);
+            let strength = Self::emissive_strength(gltf_mat.extensions());
+            let emissive = gltf_mat.emissive_factor().map(|c| c * strength);
+            if emissive.iter().any(|&c| c > 0.0) {
+                material = material.with_emissive_color(emissive);
+            }
+
+            // Transmission
+            let (transmission, transmission_texture) = Self::transmission(gltf_mat.extensions());
+            if transmission > 0.0 {
+                material = material.with_transmission(transmission);
+                if let Some(tex_index) = transmission_texture {
+                    if tex_index < texture_handles.len() {
+                        material = material.with_transmission_texture(texture_handles[tex_index]);
+                    }
+                }
+            }
+
+            // Clear coat
+            let clearcoat = Self::clearcoat(gltf_mat.extensions());
+            if clearcoat.factor > 0.0 {
+                material = material.with_clearcoat(clearcoat.factor, clearcoat.roughness);
+                let texture = |index: Option<usize>| {
+                    index.and_then(|index| texture_handles.get(index).copied())
+                };
+                if let Some(handle) = texture(clearcoat.texture) {
+                    material = material.with_clearcoat_texture(handle);
+                }
+                if let Some(handle) = texture(clearcoat.roughness_texture) {
+                    material = material.with_clearcoat_roughness_texture(handle);
+                }
+                if let Some(handle) = texture(clearcoat.normal_texture) {
+                    material = material.with_clearcoat_normal_texture(handle);
+                }
+            }
+
+            // Anisotropy
+            let anisotropy = Self::anisotropy(gltf_mat.extensions());
+            if anisotropy.strength > 0.0 {
+                material = material.with_anisotropy(anisotropy.strength, anisotropy.rotation);
+                if let Some(tex_index) = anisotropy.texture {
+                    if tex_index < texture_handles.len() {
+                        material = material.with_anisotropy_texture(texture_handles[tex_index]);
+                    }
+                }
             }
 
             // Occlusion
@@ -1029,10 +1641,14 @@ impl SceneLoader {
             // Alpha mode
             material = match gltf_mat.alpha_mode() {
                 gltf::material::AlphaMode::Opaque => material,
-                gltf::material::AlphaMode::Mask | gltf::material::AlphaMode::Blend => {
-                    material.with_alpha()
-                }
+                gltf::material::AlphaMode::Mask => material.with_alpha_mask(
+                    gltf_mat
+                        .alpha_cutoff()
+                        .unwrap_or(crate::renderer::material::DEFAULT_ALPHA_CUTOFF),
+                ),
+                gltf::material::AlphaMode::Blend => material.with_alpha(),
             };
+            material = material.with_double_sided(gltf_mat.double_sided());
 
             log::debug!(
                 "  Material '{}': metallic={:.2}, roughness={:.2}",
@@ -1156,115 +1772,677 @@ impl SceneLoader {
                     1.0
                 };
 
-                [tangent.x, tangent.y, tangent.z, handedness]
-            })
-            .collect()
+                [tangent.x, tangent.y, tangent.z, handedness]
+            })
+            .collect()
+    }
+
+    fn load_primitive(
+        primitive: &gltf::Primitive,
+        buffers: &[gltf::buffer::Data],
+        scene: &mut Scene,
+        renderer: &mut Renderer,
+        scale_multiplier: f32,
+        mesh_cache: &mut HashMap<Vec<u8>, (Handle<Mesh>, Option<BoundingBox>)>,
+    ) -> Result<(Handle<Mesh>, Option<BoundingBox>), String> {
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let (vertices, indices) = Self::read_vertices(&reader, scale_multiplier)?;
+
+        let skin = Self::read_skin_vertices(&reader, vertices.len());
+        let morph_targets = Self::read_morph_targets(&reader, vertices.len(), scale_multiplier);
+        let bounds = BoundingBox::from_points(vertices.iter().map(|v| Vec3::from(v.pos)));
+
+        let mut signature = Vec::with_capacity(
+            vertices.len() * std::mem::size_of::<Vertex>()
+                + indices.len() * std::mem::size_of::<u32>(),
+        );
+        signature.extend_from_slice(cast_slice(&vertices));
+        signature.extend_from_slice(cast_slice(&indices));
+        if let Some(skin) = &skin {
+            signature.extend_from_slice(cast_slice(skin));
+        }
+        for target in &morph_targets {
+            signature.extend_from_slice(cast_slice(target));
+        }
+
+        if let Some(existing) = mesh_cache.get(&signature) {
+            return Ok(*existing);
+        }
+
+        // Create mesh and store in assets
+        let mesh = if !morph_targets.is_empty() {
+            renderer.create_morphed_mesh(&vertices, skin.as_deref(), &indices, &morph_targets)
+        } else {
+            match &skin {
+                Some(skin) => renderer.create_skinned_mesh(&vertices, skin, &indices),
+                None => renderer.create_mesh(&vertices, &indices),
+            }
+        };
+        let handle = scene.assets.meshes.insert(mesh);
+        mesh_cache.insert(signature, (handle, bounds));
+
+        Ok((handle, bounds))
+    }
+
+    /// Vertices (positions scaled by `scale_multiplier`) and indices of a
+    /// primitive. The reader applies sparse accessor substitutions.
+    fn read_vertices<'a, 's, F>(
+        reader: &gltf::mesh::Reader<'a, 's, F>,
+        scale_multiplier: f32,
+    ) -> Result<(Vec<Vertex>, Vec<u32>), String>
+    where
+        F: Clone + Fn(gltf::Buffer<'a>) -> Option<&'s [u8]>,
+    {
+        let positions = reader
+            .read_positions()
+            .ok_or("Missing positions")?
+            .collect::<Vec<_>>();
+
+        let normals = reader
+            .read_normals()
+            .map(|n| n.collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![[0.0, 1.0, 0.0]; positions.len()]);
+
+        let uvs = reader
+            .read_tex_coords(0)
+            .map(|uv| uv.into_f32().collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![[0.0, 0.0]; positions.len()]);
+
+        // Read tangents if available
+        let tangents = reader
+            .read_tangents()
+            .map(|t| t.collect::<Vec<_>>())
+            .unwrap_or_else(|| {
+                log::debug!("    No tangents in glTF, generating them");
+                // Generate tangents using MikkTSpace-like algorithm
+                Self::generate_tangents(&positions, &normals, &uvs, &reader.read_indices())
+            });
+
+        // Read indices
+        let indices = reader
+            .read_indices()
+            .ok_or("Missing indices")?
+            .into_u32()
+            .collect::<Vec<_>>();
+
+        log::trace!(
+            "    Primitive: {} vertices, {} indices",
+            positions.len(),
+            indices.len()
+        );
+
+        // Build vertices with tangents
+        let vertices = positions
+            .iter()
+            .zip(normals.iter())
+            .zip(uvs.iter())
+            .zip(tangents.iter())
+            .map(|(((pos, norm), uv), tangent)| {
+                let scaled_pos = [
+                    pos[0] * scale_multiplier,
+                    pos[1] * scale_multiplier,
+                    pos[2] * scale_multiplier,
+                ];
+
+                Vertex {
+                    pos: scaled_pos,
+                    normal: *norm,
+                    uv: *uv,
+                    tangent: *tangent,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok((vertices, indices))
+    }
+
+    /// `KHR_texture_transform` of a texture reference; identity when absent.
+    /// Only `TEXCOORD_0` is loaded, so a reference to another UV set, directly
+    /// or through the extension's `texCoord` override, is reported and
+    /// sampled with set 0.
+    fn texture_transform(
+        extensions: Option<&serde_json::Map<String, Value>>,
+        tex_coord: u32,
+        material: &str,
+        slot: &str,
+    ) -> TextureTransform {
+        let ext = extensions.and_then(|ext| ext.get("KHR_texture_transform"));
+        let tex_coord = ext
+            .and_then(|ext| ext.get("texCoord"))
+            .and_then(Value::as_u64)
+            .map_or(tex_coord, |set| set as u32);
+        if tex_coord != 0 {
+            log::warn!(
+                "Material '{}' {} uses TEXCOORD_{}; sampling TEXCOORD_0 instead",
+                material,
+                slot,
+                tex_coord
+            );
+        }
+
+        let Some(ext) = ext else {
+            return TextureTransform::IDENTITY;
+        };
+        let pair = |key: &str, default: [f32; 2]| {
+            ext.get(key)
+                .and_then(Value::as_array)
+                .and_then(|values| match values.as_slice() {
+                    [x, y] => Some([x.as_f64()? as f32, y.as_f64()? as f32]),
+                    _ => None,
+                })
+                .unwrap_or(default)
+        };
+        TextureTransform {
+            offset: pair("offset", [0.0, 0.0]),
+            rotation: ext.get("rotation").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+            scale: pair("scale", [1.0, 1.0]),
+        }
+    }
+
+    /// `KHR_materials_emissive_strength` multiplier; 1.0 when absent.
+    fn emissive_strength(extensions: Option<&serde_json::Map<String, Value>>) -> f32 {
+        extensions
+            .and_then(|ext| ext.get("KHR_materials_emissive_strength"))
+            .and_then(|ext| ext.get("emissiveStrength"))
+            .and_then(Value::as_f64)
+            .map(|strength| strength.max(0.0) as f32)
+            .unwrap_or(1.0)
+    }
+
+    /// `KHR_materials_transmission` factor and texture index; 0.0 when absent.
+    fn transmission(extensions: Option<&serde_json::Map<String, Value>>) -> (f32, Option<usize>) {
+        let Some(ext) = extensions.and_then(|ext| ext.get("KHR_materials_transmission")) else {
+            return (0.0, None);
+        };
+        let factor = ext
+            .get("transmissionFactor")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0) as f32;
+        let texture = ext
+            .get("transmissionTexture")
+            .and_then(|info| info.get("index"))
+            .and_then(Value::as_u64)
+            .map(|index| index as usize);
+        (factor, texture)
+    }
+
+    /// `KHR_materials_clearcoat` parameters; a zero factor when absent.
+    fn clearcoat(extensions: Option<&serde_json::Map<String, Value>>) -> Clearcoat {
+        let Some(ext) = extensions.and_then(|ext| ext.get("KHR_materials_clearcoat")) else {
+            return Clearcoat::default();
+        };
+        let factor = |key: &str| ext.get(key).and_then(Value::as_f64).unwrap_or(0.0) as f32;
+        let texture = |key: &str| {
+            ext.get(key)
+                .and_then(|info| info.get("index"))
+                .and_then(Value::as_u64)
+                .map(|index| index as usize)
+        };
+        Clearcoat {
+            factor: factor("clearcoatFactor"),
+            roughness: factor("clearcoatRoughnessFactor"),
+            texture: texture("clearcoatTexture"),
+            roughness_texture: texture("clearcoatRoughnessTexture"),
+            normal_texture: texture("clearcoatNormalTexture"),
+        }
+    }
+
+    /// `KHR_materials_anisotropy` parameters; a zero strength when absent.
+    fn anisotropy(extensions: Option<&serde_json::Map<String, Value>>) -> Anisotropy {
+        let Some(ext) = extensions.and_then(|ext| ext.get("KHR_materials_anisotropy")) else {
+            return Anisotropy::default();
+        };
+        let factor = |key: &str| ext.get(key).and_then(Value::as_f64).unwrap_or(0.0) as f32;
+        Anisotropy {
+            strength: factor("anisotropyStrength"),
+            rotation: factor("anisotropyRotation"),
+            texture: ext
+                .get("anisotropyTexture")
+                .and_then(|info| info.get("index"))
+                .and_then(Value::as_u64)
+                .map(|index| index as usize),
+        }
+    }
+
+    /// Reads the POSITION/NORMAL deltas of every morph target, one entry per
+    /// vertex. Missing attributes read as zero.
+    fn read_morph_targets<'a, 's, F>(
+        reader: &gltf::mesh::Reader<'a, 's, F>,
+        vertex_count: usize,
+        scale_multiplier: f32,
+    ) -> Vec<Vec<MorphDelta>>
+    where
+        F: Clone + Fn(gltf::Buffer<'a>) -> Option<&'s [u8]>,
+    {
+        reader
+            .read_morph_targets()
+            .map(|(positions, normals, _tangents)| {
+                let positions: Vec<[f32; 3]> = positions.map(|p| p.collect()).unwrap_or_default();
+                let normals: Vec<[f32; 3]> = normals.map(|n| n.collect()).unwrap_or_default();
+                (0..vertex_count)
+                    .map(|i| {
+                        let position = positions
+                            .get(i)
+                            .map(|p| Vec3::from(*p) * scale_multiplier)
+                            .unwrap_or(Vec3::ZERO);
+                        let normal = normals
+                            .get(i)
+                            .copied()
+                            .map(Vec3::from)
+                            .unwrap_or(Vec3::ZERO);
+                        MorphDelta::new(position.to_array(), normal.to_array())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Reads JOINTS_0/WEIGHTS_0. Returns `None` for rigid primitives or when
+    /// the attribute counts do not match the vertex count.
+    fn read_skin_vertices<'a, 's, F>(
+        reader: &gltf::mesh::Reader<'a, 's, F>,
+        vertex_count: usize,
+    ) -> Option<Vec<SkinVertex>>
+    where
+        F: Clone + Fn(gltf::Buffer<'a>) -> Option<&'s [u8]>,
+    {
+        let joints = reader.read_joints(0)?.into_u16().collect::<Vec<_>>();
+        let weights = reader.read_weights(0)?.into_f32().collect::<Vec<_>>();
+
+        if joints.len() != vertex_count || weights.len() != vertex_count {
+            log::warn!(
+                "    Ignoring skin attributes: {} joints / {} weights for {} vertices",
+                joints.len(),
+                weights.len(),
+                vertex_count
+            );
+            return None;
+        }
+
+        Some(
+            joints
+                .iter()
+                .zip(weights.iter())
+                .map(|(j, w)| {
+                    SkinVertex {
+                        joints: [j[0] as u32, j[1] as u32, j[2] as u32, j[3] as u32],
+                        weights: *w,
+                    }
+                    .normalized()
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Parsed `KHR_materials_clearcoat` extension; texture fields are glTF
+/// texture indices.
+#[derive(Debug, Default, PartialEq)]
+struct Clearcoat {
+    factor: f32,
+    roughness: f32,
+    texture: Option<usize>,
+    roughness_texture: Option<usize>,
+    normal_texture: Option<usize>,
+}
+
+/// Parsed `KHR_materials_anisotropy` extension; `texture` is a glTF texture
+/// index.
+#[derive(Debug, Default, PartialEq)]
+struct Anisotropy {
+    strength: f32,
+    rotation: f32,
+    texture: Option<usize>,
+}
+
+/// RGBA8 pixels of one glTF texture, decoded off the render thread.
+pub(crate) struct DecodedTexture {
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    /// Embedded images get an sRGB view format, image files do not.
+    embedded: bool,
+    /// `pixels` holds a KTX2 container rather than RGBA8 texels.
+    ktx2: bool,
+    label: String,
+    /// Cache key shared by every entry that resolves to the same image.
+    key: String,
+    /// Entries sharing an image share one texture, which keeps the first
+    /// entry's sampler; materials carry their own choice in their flags.
+    sampler: SamplerKey,
+}
+
+/// A glTF file read and decoded into memory, ready for GPU upload.
+pub(crate) struct PreparedGltf {
+    path: PathBuf,
+    document: gltf::Document,
+    buffers: Vec<gltf::buffer::Data>,
+    textures: Vec<DecodedTexture>,
+}
+
+/// Uploads a [`PreparedGltf`] and spawns its nodes, a bounded amount of
+/// work per [`GltfFinalizer::step`].
+pub(crate) struct GltfFinalizer {
+    prepared: PreparedGltf,
+    scale: f32,
+    texture_handles: Vec<u32>,
+    texture_hits: usize,
+    texture_misses: usize,
+    mesh_handles: Vec<Vec<LoadedPrimitive>>,
+    mesh_cache: HashMap<Vec<u8>, (Handle<Mesh>, Option<BoundingBox>)>,
+    spawned: bool,
+}
+
+impl GltfFinalizer {
+    pub(crate) fn new(prepared: PreparedGltf, scale: f32) -> Self {
+        Self {
+            prepared,
+            scale,
+            texture_handles: Vec::new(),
+            texture_hits: 0,
+            texture_misses: 0,
+            mesh_handles: Vec::new(),
+            mesh_cache: HashMap::new(),
+            spawned: false,
+        }
+    }
+
+    pub(crate) fn texture_count(&self) -> usize {
+        self.prepared.textures.len()
+    }
+
+    pub(crate) fn mesh_count(&self) -> usize {
+        self.prepared.document.meshes().len()
+    }
+
+    pub(crate) fn meshes_uploaded(&self) -> usize {
+        self.mesh_handles.len()
+    }
+
+    /// Uploads up to `budget` textures and meshes, then spawns the scene
+    /// once everything is on the GPU. Returns `true` once spawned.
+    pub(crate) fn step(
+        &mut self,
+        scene: &mut Scene,
+        renderer: &mut Renderer,
+        budget: usize,
+    ) -> Result<bool, String> {
+        if self.spawned {
+            return Ok(true);
+        }
+        if !self.upload(scene, renderer, budget)? {
+            return Ok(false);
+        }
+
+        self.spawn(scene)?;
+        self.spawned = true;
+        Ok(true)
+    }
+
+    /// Uploads up to `budget` textures and meshes into `scene`'s assets.
+    /// Returns `true` once everything is on the GPU.
+    fn upload(
+        &mut self,
+        scene: &mut Scene,
+        renderer: &mut Renderer,
+        budget: usize,
+    ) -> Result<bool, String> {
+        let mut budget = budget;
+
+        if self.texture_handles.is_empty() && !self.prepared.textures.is_empty() {
+            log::info!("Loading textures...");
+        }
+        while budget > 0 && self.texture_handles.len() < self.prepared.textures.len() {
+            let decoded = &mut self.prepared.textures[self.texture_handles.len()];
+            let pixels = std::mem::take(&mut decoded.pixels);
+            let (hits, misses) = scene.assets.textures.key_stats();
+            let handle = scene.assets.textures.get_or_insert_with(&decoded.key, || {
+                let device = renderer.get_device();
+                let queue = renderer.get_queue();
+                let texture = if decoded.ktx2 {
+                    Texture::from_ktx2(device, queue, &pixels, Some(&decoded.label)).unwrap_or_else(
+                        |err| {
+                            log::warn!("{}", err);
+                            Texture::white(device, queue)
+                        },
+                    )
+                } else {
+                    Texture::from_rgba(
+                        device,
+                        queue,
+                        &pixels,
+                        decoded.width,
+                        decoded.height,
+                        decoded.embedded,
+                        Some(&decoded.label),
+                    )
+                };
+                texture.with_sampler_key(device, decoded.sampler)
+            });
+            let (new_hits, new_misses) = scene.assets.textures.key_stats();
+            self.texture_hits += new_hits - hits;
+            self.texture_misses += new_misses - misses;
+            self.texture_handles.push(handle.index() as u32);
+            budget -= 1;
+        }
+        if self.texture_handles.len() < self.prepared.textures.len() {
+            return Ok(false);
+        }
+
+        let mesh_count = self.mesh_count();
+        if self.mesh_handles.is_empty() && mesh_count > 0 {
+            log::info!(
+                "Loaded {} textures ({} uploaded, {} reused from cache)",
+                self.texture_handles.len(),
+                self.texture_misses,
+                self.texture_hits
+            );
+            log::info!("Loading meshes...");
+        }
+        while budget > 0 && self.mesh_handles.len() < mesh_count {
+            let mesh_index = self.mesh_handles.len();
+            let Some(gltf_mesh) = self.prepared.document.meshes().nth(mesh_index) else {
+                break;
+            };
+            let mesh_name = gltf_mesh.name().unwrap_or("Unnamed");
+            let primitive_count = gltf_mesh.primitives().len();
+
+            log::debug!(
+                "  Mesh {}: '{}' with {} primitives",
+                mesh_index,
+                mesh_name,
+                primitive_count
+            );
+
+            let mut primitives = Vec::with_capacity(primitive_count);
+            for primitive in gltf_mesh.primitives() {
+                let (handle, bounds) = SceneLoader::load_primitive(
+                    &primitive,
+                    &self.prepared.buffers,
+                    scene,
+                    renderer,
+                    self.scale,
+                    &mut self.mesh_cache,
+                )?;
+                primitives.push((handle, primitive.material().index(), bounds));
+            }
+            self.mesh_handles.push(primitives);
+            budget -= 1;
+        }
+        if self.mesh_handles.len() < mesh_count {
+            return Ok(false);
+        }
+        log::info!("Loaded {} meshes", mesh_count);
+        Ok(true)
     }
 
-    fn load_primitive(
-        primitive: &gltf::Primitive,
-        buffers: &[gltf::buffer::Data],
-        scene: &mut Scene,
-        renderer: &mut Renderer,
-        scale_multiplier: f32,
-        mesh_cache: &mut HashMap<Vec<u8>, Handle<Mesh>>,
-    ) -> Result<Handle<Mesh>, String> {
-        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    fn spawn(&self, scene: &mut Scene) -> Result<(), String> {
+        let PreparedGltf {
+            path,
+            document,
+            buffers,
+            ..
+        } = &self.prepared;
+        let scale = self.scale;
 
-        // Read vertex data
-        let positions = reader
-            .read_positions()
-            .ok_or("Missing positions")?
-            .collect::<Vec<_>>();
+        // Load all materials
+        log::info!("Loading materials...");
+        let material_handles = SceneLoader::load_materials(document, &self.texture_handles)?;
+        log::info!("Loaded {} materials", material_handles.len());
 
-        let normals = reader
-            .read_normals()
-            .map(|n| n.collect::<Vec<_>>())
-            .unwrap_or_else(|| vec![[0.0, 1.0, 0.0]; positions.len()]);
+        // Track the spawned entity for each glTF node so animations can target them
+        let mut node_entities: Vec<Option<hecs::Entity>> = vec![None; document.nodes().len()];
 
-        let uvs = reader
-            .read_tex_coords(0)
-            .map(|uv| uv.into_f32().collect::<Vec<_>>())
-            .unwrap_or_else(|| vec![[0.0, 0.0]; positions.len()]);
+        // Load all scenes and their node hierarchies
+        log::info!("Loading scene hierarchies...");
+        for (scene_index, gltf_scene) in document.scenes().enumerate() {
+            let scene_name = gltf_scene.name().unwrap_or("Unnamed");
+            let root_count = gltf_scene.nodes().len();
 
-        // Read tangents if available
-        let tangents = reader
-            .read_tangents()
-            .map(|t| t.collect::<Vec<_>>())
-            .unwrap_or_else(|| {
-                log::debug!("    No tangents in glTF, generating them");
-                // Generate tangents using MikkTSpace-like algorithm
-                Self::generate_tangents(&positions, &normals, &uvs, &reader.read_indices())
-            });
+            log::info!(
+                "  Scene {}: '{}' with {} root nodes (scale: {}x)",
+                scene_index,
+                scene_name,
+                root_count,
+                scale
+            );
 
-        // Read indices
-        let indices = reader
-            .read_indices()
-            .ok_or("Missing indices")?
-            .into_u32()
-            .collect::<Vec<_>>();
+            for (node_index, node) in gltf_scene.nodes().enumerate() {
+                log::info!(
+                    "    Loading root node {}/{}: {:?}",
+                    node_index + 1,
+                    root_count,
+                    node.name()
+                );
 
-        log::trace!(
-            "    Primitive: {} vertices, {} indices",
-            positions.len(),
-            indices.len()
-        );
+                SceneLoader::load_node(
+                    &node,
+                    None,
+                    path,
+                    &self.mesh_handles,
+                    &material_handles,
+                    &mut scene.world,
+                    scale,
+                    &mut node_entities,
+                )?;
+            }
+        }
 
-        // Build vertices with tangents
-        let vertices = positions
-            .iter()
-            .zip(normals.iter())
-            .zip(uvs.iter())
-            .zip(tangents.iter())
-            .map(|(((pos, norm), uv), tangent)| {
-                let scaled_pos = [
-                    pos[0] * scale_multiplier,
-                    pos[1] * scale_multiplier,
-                    pos[2] * scale_multiplier,
-                ];
+        log::info!("Loading skins...");
+        SceneLoader::load_skins(document, buffers, &node_entities, &mut scene.world, scale);
 
-                Vertex {
-                    pos: scaled_pos,
-                    normal: *norm,
-                    uv: *uv,
-                    tangent: *tangent,
-                }
-            })
-            .collect::<Vec<_>>();
+        log::info!("Loading morph weights...");
+        SceneLoader::load_morph_weights(document, &node_entities, &mut scene.world);
 
-        let mut signature = Vec::with_capacity(
-            vertices.len() * std::mem::size_of::<Vertex>()
-                + indices.len() * std::mem::size_of::<u32>(),
-        );
-        signature.extend_from_slice(cast_slice(&vertices));
-        signature.extend_from_slice(cast_slice(&indices));
+        log::info!("Loading lights...");
+        let light_count =
+            SceneLoader::load_lights(document, &node_entities, &mut scene.world, scale);
+        log::info!("Imported {} KHR_lights_punctual lights", light_count);
 
-        if let Some(existing) = mesh_cache.get(&signature) {
-            return Ok(*existing);
+        log::info!("Loading animations...");
+        SceneLoader::load_animations(document, buffers, &node_entities, scene, path, scale)?;
+
+        log::info!("Loading cameras...");
+        let camera_count =
+            SceneLoader::load_camera_components(document, &node_entities, &mut scene.world);
+        log::info!("Imported {} camera nodes", camera_count);
+        let cameras = SceneLoader::load_cameras(document, scale);
+        if let Some((name, camera)) = cameras.first() {
+            log::info!(
+                "Using glTF camera '{}' ({} available)",
+                name.0,
+                cameras.len()
+            );
+            scene.set_camera(*camera);
         }
 
-        // Create mesh and store in assets
-        let mesh = renderer.create_mesh(&vertices, &indices);
-        let handle = scene.assets.meshes.insert(mesh);
-        mesh_cache.insert(signature, handle);
+        log::info!("=== glTF loaded successfully ===");
+        log::info!("Total entities in scene: {}", scene.world.len());
+
+        // Count entities with different components
+        let mesh_count = scene.world.query::<&MeshComponent>().iter().count();
+        let parent_count = scene.world.query::<&Parent>().iter().count();
+        let children_count = scene.world.query::<&Children>().iter().count();
+
+        log::info!("  Entities with meshes: {}", mesh_count);
+        log::info!("  Entities with parent: {}", parent_count);
+        log::info!("  Entities with children: {}", children_count);
 
-        Ok(handle)
+        Ok(())
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 impl SceneLoader {
     fn import_gltf_web(path: &Path) -> Result<GltfImport, String> {
+        let bytes = crate::io::load_binary(path)?;
+        Self::import_gltf_web_from(path, &bytes, &mut HashMap::new())
+    }
+
+    /// Like [`Self::import_gltf_web`], but fetches the file and its external
+    /// resources asynchronously so the browser tab stays responsive.
+    /// `on_bytes` runs with the size of every fetched file.
+    pub(crate) async fn import_gltf_web_async(
+        path: &Path,
+        on_bytes: &mut dyn FnMut(usize),
+    ) -> Result<GltfImport, String> {
+        let bytes = crate::io::fetch_binary(path).await?;
+        on_bytes(bytes.len());
+
+        let gltf = gltf::Gltf::from_slice(&bytes).map_err(|err| err.to_string())?;
+        let base_dir = path.parent().map(|p| p.to_path_buf());
+
+        let buffer_uris = gltf
+            .document
+            .buffers()
+            .filter_map(|buffer| match buffer.source() {
+                gltf::buffer::Source::Uri(uri) => Some(uri.to_string()),
+                gltf::buffer::Source::Bin => None,
+            });
+        let image_uris = gltf
+            .document
+            .images()
+            .filter_map(|image| match image.source() {
+                gltf::image::Source::Uri { uri, .. } => Some(uri.to_string()),
+                gltf::image::Source::View { .. } => None,
+            });
+        let uris: Vec<String> = buffer_uris.chain(image_uris).collect();
+
+        let mut prefetched = HashMap::new();
+        for uri in uris {
+            if prefetched.contains_key(&uri) {
+                continue;
+            }
+            let data = match Self::resolve_external_resource(base_dir.as_deref(), &uri, Some(path))?
+            {
+                ExternalResource::Inline(_) => continue,
+                ExternalResource::Url(url) => crate::io::fetch_binary_from_str(&url).await?,
+                ExternalResource::Path(path) => crate::io::fetch_binary(&path).await?,
+            };
+            on_bytes(data.len());
+            prefetched.insert(uri, data);
+        }
+
+        Self::import_gltf_web_from(path, &bytes, &mut prefetched)
+    }
+
+    /// Imports glTF `bytes`, taking external resources from `prefetched`
+    /// before falling back to synchronous requests.
+    fn import_gltf_web_from(
+        path: &Path,
+        bytes: &[u8],
+        prefetched: &mut HashMap<String, Vec<u8>>,
+    ) -> Result<GltfImport, String> {
         use gltf::Gltf;
 
-        let bytes = crate::io::load_binary(path)?;
-        let mut gltf = Gltf::from_slice(&bytes).map_err(|err| err.to_string())?;
+        let mut gltf = Gltf::from_slice(bytes).map_err(|err| err.to_string())?;
         let document = gltf.document;
         let mut blob = gltf.blob;
         let base_dir = path.parent().map(|p| p.to_path_buf());
 
-        let buffers = Self::import_buffers_web(&document, base_dir.as_deref(), &mut blob, path)?;
-        let images = Self::import_images_web(&document, base_dir.as_deref(), &buffers)?;
+        let buffers =
+            Self::import_buffers_web(&document, base_dir.as_deref(), &mut blob, path, prefetched)?;
+        let images = Self::import_images_web(&document, base_dir.as_deref(), &buffers, prefetched)?;
 
         Ok((document, buffers, images))
     }
@@ -1274,13 +2452,14 @@ impl SceneLoader {
         base: Option<&Path>,
         blob: &mut Option<Vec<u8>>,
         original_path: &Path,
+        prefetched: &mut HashMap<String, Vec<u8>>,
     ) -> Result<Vec<gltf::buffer::Data>, String> {
         let mut buffers = Vec::new();
 
         for buffer in document.buffers() {
             let mut data = match buffer.source() {
                 gltf::buffer::Source::Uri(uri) => {
-                    Self::load_external_resource(base, uri, Some(original_path))?
+                    Self::load_external_resource(base, uri, Some(original_path), prefetched)?
                 }
                 gltf::buffer::Source::Bin => blob
                     .take()
@@ -1311,14 +2490,19 @@ impl SceneLoader {
         document: &gltf::Document,
         base: Option<&Path>,
         buffers: &[gltf::buffer::Data],
+        prefetched: &mut HashMap<String, Vec<u8>>,
     ) -> Result<Vec<gltf::image::Data>, String> {
         let mut images = Vec::new();
 
         for image in document.images() {
             let data = match image.source() {
                 gltf::image::Source::Uri { uri, .. } => {
-                    let bytes = Self::load_external_resource(base, uri, None)?;
-                    Self::decode_image(&bytes)?
+                    let bytes = Self::load_external_resource(base, uri, None, prefetched)?;
+                    if is_ktx2(&bytes) {
+                        Self::ktx2_image(bytes)
+                    } else {
+                        Self::decode_image(&bytes)?
+                    }
                 }
                 gltf::image::Source::View { view, .. } => {
                     let parent = &buffers[view.buffer().index()].0;
@@ -1330,7 +2514,12 @@ impl SceneLoader {
                             image.index()
                         ));
                     }
-                    Self::decode_image(&parent[begin..end])?
+                    let bytes = &parent[begin..end];
+                    if is_ktx2(bytes) {
+                        Self::ktx2_image(bytes.to_vec())
+                    } else {
+                        Self::decode_image(bytes)?
+                    }
                 }
             };
 
@@ -1375,17 +2564,35 @@ impl SceneLoader {
         base: Option<&Path>,
         uri: &str,
         original_path: Option<&Path>,
+        prefetched: &mut HashMap<String, Vec<u8>>,
     ) -> Result<Vec<u8>, String> {
+        if let Some(bytes) = prefetched.remove(uri) {
+            return Ok(bytes);
+        }
+
+        match Self::resolve_external_resource(base, uri, original_path)? {
+            ExternalResource::Inline(bytes) => Ok(bytes),
+            ExternalResource::Url(url) => crate::io::load_binary_from_str(&url),
+            ExternalResource::Path(path) => crate::io::load_binary(&path),
+        }
+    }
+
+    fn resolve_external_resource(
+        base: Option<&Path>,
+        uri: &str,
+        original_path: Option<&Path>,
+    ) -> Result<ExternalResource, String> {
         if let Some(rest) = uri.strip_prefix("data:") {
             let (_, encoded) = rest
                 .split_once(",")
                 .ok_or_else(|| format!("Malformed data URI: {}", uri))?;
             return base64::decode(encoded)
+                .map(ExternalResource::Inline)
                 .map_err(|err| format!("Failed to decode data URI: {}", err));
         }
 
         if uri.starts_with("http://") || uri.starts_with("https://") {
-            return crate::io::load_binary_from_str(uri);
+            return Ok(ExternalResource::Url(uri.to_string()));
         }
 
         let path = if uri.starts_with('/') {
@@ -1400,24 +2607,371 @@ impl SceneLoader {
             return Err(format!("Cannot resolve URI {}", uri));
         };
 
-        crate::io::load_binary(&path)
+        Ok(ExternalResource::Path(path))
     }
 }
 
+/// Where an external buffer or image of a glTF file lives.
+#[cfg(target_arch = "wasm32")]
+enum ExternalResource {
+    Inline(Vec<u8>),
+    Url(String),
+    Path(PathBuf),
+}
+
 #[cfg(test)]
 mod tests {
-    use super::SceneLoader;
+    use super::{Anisotropy, Clearcoat, SceneLoader};
+    use crate::renderer::material::MaterialFlags;
+    use crate::renderer::{Material, MaterialData, SamplerKey, TextureTransform};
     use crate::scene::animation::{
         AnimationInterpolation, AnimationOutput, AnimationTarget, TransformProperty,
     };
-    use crate::scene::components::{Name, TransformComponent, Visible};
-    use crate::scene::{Scene, Transform};
+    use crate::scene::components::{
+        CanCastShadow, Name, SpotLight, TransformComponent, Visible, WorldTransform,
+    };
+    use crate::scene::{CameraProjection, Scene, Transform};
     use glam::Vec3;
     use serde_json::Value;
     use std::collections::HashMap;
     use std::fs;
     use std::path::Path;
 
+    #[test]
+    fn camera_nodes_are_converted_in_world_space() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "cameras": [
+                { "type": "perspective",
+                  "perspective": { "yfov": 0.5, "znear": 0.05, "zfar": 250.0 } },
+                { "type": "perspective",
+                  "perspective": { "yfov": 1.0, "znear": 0.1 } },
+                { "type": "orthographic",
+                  "orthographic": { "xmag": 4.0, "ymag": 3.0, "znear": 0.5, "zfar": 60.0 } }
+            ],
+            "nodes": [
+                { "name": "Rig", "translation": [0.0, 1.0, 0.0], "children": [1] },
+                { "name": "Shot", "camera": 0, "translation": [0.0, 0.0, 5.0] },
+                { "camera": 1, "rotation": [0.0, 0.7071068, 0.0, 0.7071068] },
+                { "name": "Top", "camera": 2 }
+            ],
+            "scenes": [ { "nodes": [0, 2, 3] } ],
+            "scene": 0
+        }"#;
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).expect("camera glTF");
+
+        let cameras = SceneLoader::load_cameras(&gltf.document, 2.0);
+        assert_eq!(cameras.len(), 3);
+
+        let (name, shot) = &cameras[0];
+        assert_eq!(name.0, "Shot");
+        assert!(shot.eye.abs_diff_eq(Vec3::new(0.0, 2.0, 10.0), 1e-5));
+        assert!((shot.target - shot.eye).abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!((shot.fov_y().unwrap() - 0.5).abs() < 1e-6);
+        assert_eq!(shot.clip_planes(), (0.05, 250.0));
+
+        // Rotated 90° about +Y, so the camera looks down -X.
+        let (name, turned) = &cameras[1];
+        assert_eq!(name.0, "Unnamed");
+        assert!((turned.target - turned.eye).abs_diff_eq(Vec3::NEG_X, 1e-5));
+        let (near, far) = turned.clip_planes();
+        assert!(far > near);
+
+        let (_, top) = &cameras[2];
+        assert_eq!(
+            top.projection,
+            CameraProjection::Orthographic {
+                width: 8.0,
+                height: 6.0,
+                near: 0.5,
+                far: 60.0,
+            }
+        );
+    }
+
+    #[test]
+    fn animated_camera_node_drives_active_camera() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "cameras": [
+                { "type": "perspective",
+                  "perspective": { "yfov": 0.8, "znear": 0.1, "zfar": 100.0 } }
+            ],
+            "nodes": [ { "name": "Dolly", "camera": 0, "translation": [0.0, 0.0, 5.0] } ],
+            "scenes": [ { "nodes": [0] } ],
+            "scene": 0,
+            "buffers": [ { "byteLength": 32,
+                "uri": "data:application/octet-stream;base64,AAAAAAAAgD8AAAAAAAAAAAAAoEAAAAAAAAAAAAAAIEE=" } ],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 8 },
+                { "buffer": 0, "byteOffset": 8, "byteLength": 24 }
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR",
+                  "min": [0.0], "max": [1.0] },
+                { "bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC3" }
+            ],
+            "animations": [ {
+                "samplers": [ { "input": 0, "output": 1 } ],
+                "channels": [ { "sampler": 0, "target": { "node": 0, "path": "translation" } } ]
+            } ]
+        }"#;
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).expect("camera animation glTF");
+        let document = gltf.document;
+        let buffers = gltf::import_buffers(&document, None, gltf.blob).expect("buffers");
+
+        let mut scene = Scene::new();
+        let mut node_entities = vec![None; document.nodes().len()];
+        for node in document.nodes() {
+            let entity = scene.world.spawn((
+                Name::new(node.name().unwrap_or("")),
+                TransformComponent(Transform::IDENTITY),
+                WorldTransform(Transform::IDENTITY),
+                Visible(true),
+            ));
+            node_entities[node.index()] = Some(entity);
+        }
+        assert_eq!(
+            SceneLoader::load_camera_components(&document, &node_entities, &mut scene.world),
+            1
+        );
+        SceneLoader::load_animations(
+            &document,
+            &buffers,
+            &node_entities,
+            &mut scene,
+            Path::new("camera.gltf"),
+            1.0,
+        )
+        .expect("load animations");
+
+        let dolly = node_entities[0].unwrap();
+        scene.set_active_camera(dolly).expect("camera node");
+        scene.play_animation(0, true);
+
+        scene.update(0.0);
+        let start = scene.camera().eye;
+        scene.update(0.5);
+        let moved = scene.camera().eye;
+
+        assert!(start.abs_diff_eq(Vec3::new(0.0, 0.0, 5.0), 1e-4));
+        assert!(moved.abs_diff_eq(Vec3::new(0.0, 0.0, 7.5), 1e-4));
+        assert!((scene.camera().fov_y().unwrap() - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn punctual_spot_light_is_attached_to_its_node() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": { "KHR_lights_punctual": { "lights": [
+                { "type": "spot", "color": [1.0, 0.5, 0.25], "intensity": 40.0, "range": 12.0,
+                  "spot": { "innerConeAngle": 0.2, "outerConeAngle": 0.6 } }
+            ] } },
+            "nodes": [
+                { "name": "Lamp", "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+                { "name": "Empty" }
+            ],
+            "scenes": [ { "nodes": [0, 1] } ],
+            "scene": 0
+        }"#;
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).expect("light glTF");
+
+        let mut world = hecs::World::new();
+        let node_entities = vec![Some(world.spawn(())), Some(world.spawn(()))];
+        let loaded = SceneLoader::load_lights(&gltf.document, &node_entities, &mut world, 2.0);
+        assert_eq!(loaded, 1);
+
+        let lamp = node_entities[0].unwrap();
+        let spot = *world.get::<&SpotLight>(lamp).expect("spot light");
+        assert_eq!(spot.color, Vec3::new(1.0, 0.5, 0.25));
+        assert_eq!(spot.intensity, 160.0);
+        assert_eq!(spot.range, 24.0);
+        assert_eq!(spot.inner_angle, 0.2);
+        assert_eq!(spot.outer_angle, 0.6);
+        assert!(spot.cookie_texture.is_none());
+        assert!(!world.get::<&CanCastShadow>(lamp).unwrap().0);
+
+        let empty = node_entities[1].unwrap();
+        assert!(world.get::<&SpotLight>(empty).is_err());
+    }
+
+    #[test]
+    fn texture_transform_extension_reaches_material_data() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_texture_transform"],
+            "images": [ { "uri": "atlas.png" } ],
+            "textures": [ { "source": 0 } ],
+            "materials": [ {
+                "pbrMetallicRoughness": { "baseColorTexture": { "index": 0,
+                    "extensions": { "KHR_texture_transform": {
+                        "offset": [0.5, 0.25], "rotation": 1.5, "scale": [0.5, 0.5]
+                    } } } },
+                "normalTexture": { "index": 0,
+                    "extensions": { "KHR_texture_transform": { "scale": [2.0, 4.0] } } },
+                "emissiveTexture": { "index": 0 }
+            } ]
+        }"#;
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).expect("texture transform glTF");
+        let materials = SceneLoader::load_materials(&gltf.document, &[7]).unwrap();
+        let data = MaterialData::from_material(&materials[0]);
+
+        let flags = MaterialFlags::USE_BASE_COLOR_TRANSFORM | MaterialFlags::USE_NORMAL_TRANSFORM;
+        assert_eq!(data.material_flags & flags.bits(), flags.bits());
+        assert_eq!(data.base_color_uv_transform, [0.5, 0.25, 0.5, 0.5]);
+        assert_eq!(data.base_color_uv_rotation, 1.5);
+        assert_eq!(data.normal_uv_transform, [0.0, 0.0, 2.0, 4.0]);
+        assert_eq!(data.normal_uv_rotation, 0.0);
+
+        // Untransformed materials keep the cheap path.
+        let plain = MaterialData::from_material(&Material::pbr().with_base_color_texture(7));
+        assert_eq!(plain.material_flags & flags.bits(), 0);
+        assert_eq!(
+            plain.base_color_uv_transform,
+            TextureTransform::IDENTITY.packed()
+        );
+    }
+
+    #[test]
+    fn emissive_strength_extension_is_parsed() {
+        let extensions: serde_json::Map<String, Value> = serde_json::from_str(
+            r#"{ "KHR_materials_emissive_strength": { "emissiveStrength": 12.5 } }"#,
+        )
+        .unwrap();
+        assert_eq!(SceneLoader::emissive_strength(Some(&extensions)), 12.5);
+        assert_eq!(SceneLoader::emissive_strength(None), 1.0);
+    }
+
+    #[test]
+    fn transmission_extension_is_parsed() {
+        let extensions: serde_json::Map<String, Value> = serde_json::from_str(
+            r#"{ "KHR_materials_transmission": {
+                "transmissionFactor": 0.75,
+                "transmissionTexture": { "index": 2 }
+            } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            SceneLoader::transmission(Some(&extensions)),
+            (0.75, Some(2))
+        );
+        assert_eq!(SceneLoader::transmission(None), (0.0, None));
+    }
+
+    #[test]
+    fn clearcoat_extension_is_parsed() {
+        let extensions: serde_json::Map<String, Value> = serde_json::from_str(
+            r#"{ "KHR_materials_clearcoat": {
+                "clearcoatFactor": 1.0,
+                "clearcoatRoughnessFactor": 0.25,
+                "clearcoatNormalTexture": { "index": 4, "scale": 1.0 }
+            } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            SceneLoader::clearcoat(Some(&extensions)),
+            Clearcoat {
+                factor: 1.0,
+                roughness: 0.25,
+                texture: None,
+                roughness_texture: None,
+                normal_texture: Some(4),
+            }
+        );
+        assert_eq!(SceneLoader::clearcoat(None), Clearcoat::default());
+    }
+
+    #[test]
+    fn anisotropy_extension_is_parsed() {
+        let extensions: serde_json::Map<String, Value> = serde_json::from_str(
+            r#"{ "KHR_materials_anisotropy": {
+                "anisotropyStrength": 0.6,
+                "anisotropyRotation": 1.5,
+                "anisotropyTexture": { "index": 3 }
+            } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            SceneLoader::anisotropy(Some(&extensions)),
+            Anisotropy {
+                strength: 0.6,
+                rotation: 1.5,
+                texture: Some(3),
+            }
+        );
+        assert_eq!(SceneLoader::anisotropy(None), Anisotropy::default());
+    }
+
+    #[test]
+    fn sparse_accessor_substitutes_positions() {
+        let path = Path::new("web/assets/sparse/SimpleSparseAccessor.gltf");
+        let (document, buffers, _) =
+            SceneLoader::import_gltf_native(path).expect("SimpleSparseAccessor import");
+        let primitive = document
+            .meshes()
+            .next()
+            .unwrap()
+            .primitives()
+            .next()
+            .unwrap();
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let (vertices, indices) = SceneLoader::read_vertices(&reader, 1.0).unwrap();
+
+        assert_eq!(vertices.len(), 14);
+        assert_eq!(indices.len(), 36);
+        // Untouched base values.
+        assert_eq!(vertices[0].pos, [0.0, 0.0, 0.0]);
+        assert_eq!(vertices[7].pos, [0.0, 1.0, 0.0]);
+        assert_eq!(vertices[9].pos, [2.0, 1.0, 0.0]);
+        // Sparse substitutions at indices 8, 10 and 12.
+        assert_eq!(vertices[8].pos, [1.0, 2.0, 0.0]);
+        assert_eq!(vertices[10].pos, [3.0, 3.0, 0.0]);
+        assert_eq!(vertices[12].pos, [5.0, 4.0, 0.0]);
+    }
+
+    #[test]
+    fn sparse_positions_without_base_view_start_from_zero() {
+        // Three positions with no buffer view; vertex 1 is set to (0, 1, 0).
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "buffers": [ { "byteLength": 24,
+                "uri": "data:application/octet-stream;base64,AAABAAIAAAABAAAAAAAAAAAAgD8AAAAA" } ],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 6 },
+                { "buffer": 0, "byteOffset": 8, "byteLength": 2 },
+                { "buffer": 0, "byteOffset": 12, "byteLength": 12 }
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5123, "count": 3, "type": "SCALAR" },
+                { "componentType": 5126, "count": 3, "type": "VEC3",
+                  "min": [0.0, 0.0, 0.0], "max": [0.0, 1.0, 0.0],
+                  "sparse": { "count": 1,
+                              "indices": { "bufferView": 1, "componentType": 5123 },
+                              "values": { "bufferView": 2 } } }
+            ],
+            "meshes": [ { "primitives": [ { "attributes": { "POSITION": 1 }, "indices": 0 } ] } ]
+        }"#;
+        let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(json.as_bytes()).unwrap();
+        let buffers = gltf::import_buffers(&document, None, blob).unwrap();
+        let primitive = document
+            .meshes()
+            .next()
+            .unwrap()
+            .primitives()
+            .next()
+            .unwrap();
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let (vertices, indices) = SceneLoader::read_vertices(&reader, 2.0).unwrap();
+
+        let positions: Vec<[f32; 3]> = vertices.iter().map(|vertex| vertex.pos).collect();
+        assert_eq!(
+            positions,
+            vec![[0.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 0.0]]
+        );
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
     #[test]
     fn pointer_animation_gltf_is_patched_and_loaded() {
         let path = Path::new("web/assets/animated/AnimatedColorsCube.gltf");
@@ -1554,7 +3108,12 @@ mod tests {
 
             let mut transform_updates = HashMap::new();
             let mut material_updates = HashMap::new();
-            clip.sample(final_time, &mut transform_updates, &mut material_updates);
+            clip.sample(
+                final_time,
+                &mut transform_updates,
+                &mut material_updates,
+                &mut HashMap::new(),
+            );
 
             let update = transform_updates
                 .get(&entity)
@@ -1679,7 +3238,12 @@ mod tests {
 
             let mut transform_updates = HashMap::new();
             let mut material_updates = HashMap::new();
-            clip.sample(final_time, &mut transform_updates, &mut material_updates);
+            clip.sample(
+                final_time,
+                &mut transform_updates,
+                &mut material_updates,
+                &mut HashMap::new(),
+            );
 
             let (entity, _) = match channel.target {
                 AnimationTarget::Transform { entity, property } => (entity, property),
@@ -1710,4 +3274,53 @@ mod tests {
                 .abs_diff_eq(expected_final, 1e-5));
         }
     }
+
+    #[test]
+    fn clamped_gltf_samplers_select_the_clamp_sampler() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "images": [ { "uri": "lightmap.png" } ],
+            "samplers": [
+                { "wrapS": 33071, "wrapT": 33071, "magFilter": 9729 },
+                { "magFilter": 9728 }
+            ],
+            "textures": [
+                { "source": 0, "sampler": 0 },
+                { "source": 0, "sampler": 1 },
+                { "source": 0 }
+            ],
+            "materials": [ { "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } } } ]
+        }"#;
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).expect("sampler glTF");
+        let image = gltf::image::Data {
+            pixels: vec![255; 4],
+            format: gltf::image::Format::R8G8B8A8,
+            width: 1,
+            height: 1,
+        };
+
+        let prepared = SceneLoader::prepare_gltf(
+            Path::new("lightmap.gltf"),
+            (gltf.document, Vec::new(), vec![image]),
+            &mut || {},
+        )
+        .expect("prepare");
+        let keys: Vec<SamplerKey> = prepared.textures.iter().map(|t| t.sampler).collect();
+        assert_eq!(
+            keys,
+            vec![
+                SamplerKey::new(true, false),
+                SamplerKey::new(false, true),
+                SamplerKey::default(),
+            ]
+        );
+
+        let materials = SceneLoader::load_materials(&prepared.document, &[4, 4, 4]).unwrap();
+        assert!(materials[0]
+            .flags
+            .contains(MaterialFlags::USE_CLAMP_TO_EDGE));
+        assert!(!materials[0]
+            .flags
+            .contains(MaterialFlags::USE_NEAREST_FILTERING));
+    }
 }
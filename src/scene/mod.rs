@@ -1,23 +1,35 @@
 // scene/mod.rs
 
 pub mod animation;
+pub mod async_loader;
 pub mod builder;
 pub mod camera;
+pub mod camera_controller;
+pub mod commands;
 pub mod components;
 pub(crate) mod internal;
 pub mod loader;
+pub mod raycast;
 mod scene_core;
 pub mod transform;
 
 // Re-export commonly used types
+pub use async_loader::{
+    GltfLoadHandle, GltfLoadProgress, GltfLoadProgressHandle, GltfLoadStage, GltfLoadStatus,
+};
 pub use builder::EntityBuilder;
-pub use camera::Camera;
+pub use camera::{Camera, CameraProjection};
+pub use camera_controller::{OrbitCameraController, OrbitCameraPlugin, OrbitCameraSettings};
+pub use commands::RendererCommand;
 pub use loader::SceneLoader;
-pub use scene_core::Scene;
+pub use raycast::{Ray, RaycastHit};
+pub use scene_core::{system_first_person_camera, Scene};
 pub use transform::Transform;
 
 // Re-export all components
 pub use components::{
-    Children, GltfMaterial, GltfNode, MaterialComponent, MeshComponent, Name, OrbitAnimation,
-    Parent, RotateAnimation, TransformComponent, Visible,
+    BoundingBox, CameraComponent, Children, FirstPersonController, FollowCamera, GltfMaterial,
+    GltfNode, MaterialComponent, MeshComponent, MeshSource, MorphWeights, Name, OrbitAnimation,
+    Outline, Parent, PulseAnimation, PulseProperty, RotateAnimation, ShadowDistance, Skin,
+    TextBillboard, TransformComponent, Visible,
 };
@@ -0,0 +1,307 @@
+//! CPU ray casts against entity bounds and mesh triangles.
+
+use glam::{Mat4, Vec2, Vec3};
+use hecs::{Entity, World};
+
+use crate::asset::Assets;
+use crate::scene::components::{
+    BoundingBox, MeshComponent, TransformComponent, Visible, WorldTransform,
+};
+use crate::scene::{Camera, Transform};
+
+/// Determinant below which a ray is treated as parallel to a triangle.
+const PARALLEL_EPSILON: f32 = 1e-8;
+
+/// A half-line in world space. The direction is normalized so hit
+/// distances are in world units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Ray from the near plane through the pixel at `screen_pos` (origin
+    /// top-left) of a `viewport_size` viewport. `None` for an empty
+    /// viewport.
+    pub fn from_screen(camera: &Camera, screen_pos: Vec2, viewport_size: Vec2) -> Option<Ray> {
+        screen_ray(camera, screen_pos, viewport_size).map(|(ray, _)| ray)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    pub entity: Entity,
+    pub distance: f32,
+    pub position: Vec3,
+}
+
+/// Hits of `ray` within `max_distance`, nearest first. Each visible mesh
+/// entity is rejected against its [`BoundingBox`] before its triangles are
+/// tested; skinning and morph targets are ignored.
+pub(crate) fn raycast(
+    world: &World,
+    assets: &Assets,
+    ray: Ray,
+    max_distance: f32,
+) -> Vec<RaycastHit> {
+    if ray.direction == Vec3::ZERO || max_distance < 0.0 {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<RaycastHit> = world
+        .query::<(
+            &MeshComponent,
+            Option<&WorldTransform>,
+            Option<&TransformComponent>,
+            Option<&BoundingBox>,
+            Option<&Visible>,
+        )>()
+        .iter()
+        .filter(|(_, (_, _, _, _, visible))| visible.is_none_or(|v| v.0))
+        .filter_map(|(entity, (mesh, world_transform, local, bounds, _))| {
+            let transform = world_transform
+                .map(|t| t.0)
+                .or(local.map(|t| t.0))
+                .unwrap_or(Transform::IDENTITY);
+            let distance =
+                intersect_entity(assets, mesh, transform.matrix(), bounds, ray, max_distance)?;
+            Some(RaycastHit {
+                entity,
+                distance,
+                position: ray.at(distance),
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    hits
+}
+
+/// [`Ray::from_screen`] plus the distance from the near to the far plane
+/// along the ray.
+pub(crate) fn screen_ray(
+    camera: &Camera,
+    screen_pos: Vec2,
+    viewport_size: Vec2,
+) -> Option<(Ray, f32)> {
+    if viewport_size.x <= 0.0 || viewport_size.y <= 0.0 {
+        return None;
+    }
+    let inverse = camera
+        .view_proj(viewport_size.x / viewport_size.y)
+        .inverse();
+    if !inverse.is_finite() {
+        return None;
+    }
+
+    let ndc = Vec2::new(
+        screen_pos.x / viewport_size.x * 2.0 - 1.0,
+        1.0 - screen_pos.y / viewport_size.y * 2.0,
+    );
+    let near = inverse.project_point3(ndc.extend(0.0));
+    let far = inverse.project_point3(ndc.extend(1.0));
+    let ray = Ray::new(near, far - near);
+    (ray.direction != Vec3::ZERO).then(|| (ray, near.distance(far)))
+}
+
+fn intersect_entity(
+    assets: &Assets,
+    mesh: &MeshComponent,
+    matrix: Mat4,
+    bounds: Option<&BoundingBox>,
+    ray: Ray,
+    max_distance: f32,
+) -> Option<f32> {
+    // The local-space direction is left unnormalized so local hit
+    // parameters equal world distances.
+    let inverse = matrix.inverse();
+    if !inverse.is_finite() {
+        return None;
+    }
+    let origin = inverse.transform_point3(ray.origin);
+    let direction = inverse.transform_vector3(ray.direction);
+
+    // Entities without a `BoundingBox` are rejected against the bounds
+    // computed when their mesh was created.
+    let mesh = assets.meshes.get(mesh.0);
+    let bounds = bounds
+        .copied()
+        .or_else(|| mesh.and_then(|mesh| mesh.geometry().bounds()));
+    let box_distance = match bounds {
+        Some(bounds) => Some(ray_aabb(origin, direction, &bounds, max_distance)?),
+        None => None,
+    };
+
+    let Some(mesh) = mesh else {
+        return box_distance;
+    };
+
+    mesh.geometry()
+        .triangles()
+        .filter_map(|triangle| ray_triangle(origin, direction, triangle))
+        .filter(|&distance| distance <= max_distance)
+        .min_by(f32::total_cmp)
+}
+
+/// Entry distance of a ray into `bounds` (0 when starting inside), or
+/// `None` when it misses or enters beyond `max_distance`.
+pub(crate) fn ray_aabb(
+    origin: Vec3,
+    direction: Vec3,
+    bounds: &BoundingBox,
+    max_distance: f32,
+) -> Option<f32> {
+    let inverse = direction.recip();
+    let t0 = (bounds.min - origin) * inverse;
+    let t1 = (bounds.max - origin) * inverse;
+
+    // NaN from 0 * inf (origin on a slab plane, parallel ray) is ignored by
+    // min/max, which keeps the other axes deciding.
+    let near = t0.min(t1).max_element().max(0.0);
+    let far = t0.max(t1).min_element().min(max_distance);
+
+    (near <= far).then_some(near)
+}
+
+/// Möller–Trumbore intersection; hits from either side count.
+pub(crate) fn ray_triangle(origin: Vec3, direction: Vec3, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = direction.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = origin - a;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inv_det;
+    (t >= 0.0).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::new(Vec3::splat(-1.0), Vec3::splat(1.0))
+    }
+
+    #[test]
+    fn ray_enters_box_at_near_face() {
+        let distance = ray_aabb(Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z, &unit_box(), 100.0);
+        assert_eq!(distance, Some(4.0));
+    }
+
+    #[test]
+    fn ray_inside_box_hits_at_zero() {
+        let distance = ray_aabb(Vec3::ZERO, Vec3::X, &unit_box(), 100.0);
+        assert_eq!(distance, Some(0.0));
+    }
+
+    #[test]
+    fn box_misses_and_range_are_rejected() {
+        let origin = Vec3::new(0.0, 3.0, 5.0);
+        assert_eq!(ray_aabb(origin, Vec3::NEG_Z, &unit_box(), 100.0), None);
+        assert_eq!(
+            ray_aabb(Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z, &unit_box(), 3.0),
+            None
+        );
+        assert_eq!(
+            ray_aabb(Vec3::new(0.0, 0.0, 5.0), Vec3::Z, &unit_box(), 100.0),
+            None
+        );
+    }
+
+    #[test]
+    fn pick_hits_unit_cube_through_screen_center() {
+        use crate::asset::Handle;
+        use crate::scene::{CameraProjection, Scene};
+
+        let mut scene = Scene::new();
+        // Not in the asset cache, so only the bounds are tested.
+        let cube = scene.world.spawn((
+            MeshComponent(Handle::new(0)),
+            BoundingBox::new(Vec3::splat(-0.5), Vec3::splat(0.5)),
+            TransformComponent(Transform::from_trs(
+                Vec3::new(0.0, 1.0, 0.0),
+                glam::Quat::IDENTITY,
+                Vec3::ONE,
+            )),
+        ));
+        scene.update(0.0);
+
+        let camera = Camera {
+            eye: Vec3::new(0.0, 1.0, 10.0),
+            target: Vec3::new(0.0, 1.0, 0.0),
+            up: Vec3::Y,
+            projection: CameraProjection::Perspective {
+                fov: 60f32.to_radians(),
+                near: 0.1,
+                far: 100.0,
+            },
+        };
+        let viewport = Vec2::new(800.0, 600.0);
+
+        let hit = scene.pick(&camera, viewport * 0.5, viewport).unwrap();
+        assert_eq!(hit.entity, cube);
+        assert!((hit.distance - 9.4).abs() < 1e-3);
+        assert!((hit.position - Vec3::new(0.0, 1.0, 0.5)).length() < 1e-3);
+
+        assert!(scene.pick(&camera, Vec2::ZERO, viewport).is_none());
+        assert!(scene.pick(&camera, viewport * 0.5, Vec2::ZERO).is_none());
+
+        let far_away = Camera {
+            eye: Vec3::new(0.0, 1.0, 200.0),
+            ..camera
+        };
+        assert!(scene.pick(&far_away, viewport * 0.5, viewport).is_none());
+    }
+
+    #[test]
+    fn triangle_hit_and_miss() {
+        let triangle = [
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let origin = Vec3::new(0.0, 0.0, 2.0);
+
+        let hit = ray_triangle(origin, Vec3::NEG_Z, triangle).unwrap();
+        assert!((hit - 2.0).abs() < 1e-6);
+
+        // Back faces count too.
+        let hit = ray_triangle(Vec3::new(0.0, 0.0, -3.0), Vec3::Z, triangle).unwrap();
+        assert!((hit - 3.0).abs() < 1e-6);
+
+        assert_eq!(ray_triangle(origin, Vec3::Z, triangle), None);
+        assert_eq!(
+            ray_triangle(Vec3::new(2.0, 0.0, 2.0), Vec3::NEG_Z, triangle),
+            None
+        );
+        assert_eq!(ray_triangle(origin, Vec3::X, triangle), None);
+    }
+}
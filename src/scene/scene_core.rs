@@ -1,11 +1,21 @@
-use super::animation::{AnimationClip, AnimationState};
-use super::internal::{animations, composition, debug, lights, rendering, transforms};
-use crate::asset::Assets;
-use crate::environment::Environment;
-use crate::renderer::{RenderBatcher, Renderer};
+use super::animation::{AnimationClip, AnimationEvent, AnimationPlayback, AnimationState};
+use super::commands::RendererCommand;
+use super::components::{FirstPersonController, TransformComponent, WorldTransform};
+use super::internal::culling::Frustum;
+use super::internal::{
+    animations, cameras, cloning, composition, debug, hierarchy, interpolation, lights, names,
+    rendering, serialization, skinning, text, transforms,
+};
+use super::raycast::{self, Ray, RaycastHit};
+use crate::asset::{Assets, Handle};
+use crate::environment::{Environment, EnvironmentMap};
+use crate::input::InputState;
+use crate::renderer::{CascadedShadowData, DebugDraw, RenderBatcher, Renderer};
 use crate::scene::Camera;
 use crate::time::Instant;
+use glam::{EulerRot, Quat, Vec2, Vec3};
 use hecs::World;
+use winit::keyboard::KeyCode;
 
 pub struct Scene {
     pub world: World,
@@ -14,8 +24,21 @@ pub struct Scene {
     last_frame: Option<Instant>,
     animations: Vec<AnimationClip>,
     animation_states: Vec<AnimationState>,
+    animation_blends: Vec<AnimationPlayback>,
+    animation_events: Vec<AnimationEvent>,
     camera: Camera,
+    /// Entity whose [`CameraComponent`](super::components::CameraComponent)
+    /// drives `camera` every update.
+    active_camera: Option<hecs::Entity>,
     environment: Environment,
+    renderer_commands: Vec<RendererCommand>,
+    debug_draw: DebugDraw,
+    shadow_scheduler: lights::ShadowScheduler,
+    text_labels: text::TextLabels,
+    name_cache: names::NameCache,
+    interpolation_alpha: f32,
+    /// The hierarchy changed since world transforms were last propagated.
+    transforms_dirty: bool,
 }
 
 impl Scene {
@@ -27,8 +50,18 @@ impl Scene {
             last_frame: None,
             animations: Vec::new(),
             animation_states: Vec::new(),
+            animation_blends: Vec::new(),
+            animation_events: Vec::new(),
             camera: Camera::default(),
+            active_camera: None,
             environment: Environment::default(),
+            renderer_commands: Vec::new(),
+            debug_draw: DebugDraw::new(),
+            shadow_scheduler: lights::ShadowScheduler::default(),
+            text_labels: text::TextLabels::default(),
+            name_cache: names::NameCache::default(),
+            interpolation_alpha: 1.0,
+            transforms_dirty: false,
         }
     }
 
@@ -65,6 +98,35 @@ impl Scene {
         &mut self.animation_states
     }
 
+    /// Playback state returned by [`Self::play_animation`], for seeking,
+    /// pausing, resuming and changing speed.
+    pub fn animation_state_mut(&mut self, index: usize) -> Option<&mut AnimationState> {
+        self.animation_states.get_mut(index)
+    }
+
+    /// Stops a state and rewinds it. It stays in place (so other state
+    /// indices remain valid) but is no longer advanced or sampled, and any
+    /// crossfade it takes part in is dropped. Returns `false` for unknown
+    /// indices.
+    pub fn stop_animation(&mut self, state_index: usize) -> bool {
+        let Some(state) = self.animation_states.get_mut(state_index) else {
+            return false;
+        };
+        state.playing = false;
+        state.finished = false;
+        state.enabled = false;
+        state.time = 0.0;
+        self.animation_blends
+            .retain(|blend| !blend.involves(state_index));
+        true
+    }
+
+    /// Loop and finish events since the last call, in the order they
+    /// happened.
+    pub fn drain_animation_events(&mut self) -> Vec<AnimationEvent> {
+        std::mem::take(&mut self.animation_events)
+    }
+
     pub fn camera(&self) -> &Camera {
         &self.camera
     }
@@ -77,6 +139,45 @@ impl Scene {
         self.camera = camera;
     }
 
+    /// Views the scene through `entity`, which needs a
+    /// [`CameraComponent`](super::components::CameraComponent). From then on
+    /// [`Self::camera`] is rebuilt from the entity's world transform and
+    /// projection on every update, so animations and
+    /// [`FollowCamera`](super::components::FollowCamera) move the view.
+    pub fn set_active_camera(&mut self, entity: hecs::Entity) -> Result<(), String> {
+        let camera = cameras::camera_from_entity(&self.world, entity)
+            .ok_or_else(|| format!("Entity {:?} has no CameraComponent", entity))?;
+        self.camera = camera;
+        self.active_camera = Some(entity);
+        Ok(())
+    }
+
+    /// Stops driving the camera from an entity; the last view is kept.
+    pub fn clear_active_camera(&mut self) {
+        self.active_camera = None;
+    }
+
+    pub fn active_camera(&self) -> Option<hecs::Entity> {
+        self.active_camera
+    }
+
+    /// Copies the active camera entity's view into [`Self::camera`].
+    fn sync_active_camera(&mut self) {
+        let Some(entity) = self.active_camera else {
+            return;
+        };
+        match cameras::camera_from_entity(&self.world, entity) {
+            Some(camera) => self.camera = camera,
+            None => {
+                log::warn!(
+                    "Active camera {:?} lost its CameraComponent; keeping the last view",
+                    entity
+                );
+                self.active_camera = None;
+            }
+        }
+    }
+
     pub fn environment(&self) -> &Environment {
         &self.environment
     }
@@ -89,6 +190,29 @@ impl Scene {
         self.environment = environment;
     }
 
+    /// Lights the scene and draws its skybox from a map returned by
+    /// [`SceneLoader::load_hdr_environment`](crate::scene::SceneLoader::load_hdr_environment).
+    pub fn set_environment_map(&mut self, map: Handle<EnvironmentMap>) {
+        self.environment.set_environment_map(Some(map));
+    }
+
+    /// An entity whose [`Name`](super::components::Name) is exactly `name`;
+    /// the first in query order when several share it. Results are cached
+    /// until the entity count changes.
+    pub fn find_entity_by_name(&mut self, name: &str) -> Option<hecs::Entity> {
+        self.name_cache.find(&self.world, name)
+    }
+
+    /// Every entity whose [`Name`](super::components::Name) is exactly `name`.
+    pub fn find_entities_by_name(&self, name: &str) -> Vec<hecs::Entity> {
+        names::find_all(&self.world, name)
+    }
+
+    /// Debug lines for the current frame, drawn and cleared by [`Self::render`].
+    pub fn debug_draw(&mut self) -> &mut DebugDraw {
+        &mut self.debug_draw
+    }
+
     pub fn add_animation_clip(&mut self, clip: AnimationClip) -> usize {
         let index = self.animations.len();
         self.animations.push(clip);
@@ -107,19 +231,144 @@ impl Scene {
         Some(index)
     }
 
+    /// Fades from one playing state to another over `duration` seconds.
+    /// Both keep advancing during the fade; afterwards `from_state` is
+    /// disabled. Fails when either index has no state.
+    pub fn crossfade(
+        &mut self,
+        from_state: usize,
+        to_state: usize,
+        duration: f32,
+    ) -> Result<(), String> {
+        let count = self.animation_states.len();
+        if from_state >= count || to_state >= count {
+            return Err(format!(
+                "Cannot crossfade from state {} to {}: only {} animation states",
+                from_state, to_state, count
+            ));
+        }
+        if from_state == to_state {
+            return Err(format!("Cannot crossfade state {} into itself", from_state));
+        }
+
+        // A state fades in one blend at a time.
+        self.animation_blends
+            .retain(|blend| !blend.involves(from_state) && !blend.involves(to_state));
+        let to = &mut self.animation_states[to_state];
+        to.enabled = true;
+        to.playing = true;
+        self.animation_blends
+            .push(AnimationPlayback::blend(from_state, to_state, duration));
+        Ok(())
+    }
+
+    /// Crossfades still in progress.
+    pub fn animation_blends(&self) -> &[AnimationPlayback] {
+        &self.animation_blends
+    }
+
     pub fn update(&mut self, dt: f64) {
+        self.advance(dt);
+        self.interpolation_alpha = 1.0;
+
+        transforms::propagate_transforms(&mut self.world);
+        cameras::update_follow_cameras(&mut self.world, dt as f32);
+        skinning::update_skins(&mut self.world);
+        self.sync_active_camera();
+        self.transforms_dirty = false;
+    }
+
+    /// Runs the animation systems for one fixed tick of `step` seconds.
+    /// World transforms are left to [`Self::interpolate`], which the app
+    /// calls once per frame after its ticks.
+    pub fn fixed_update(&mut self, step: f64) {
+        self.advance(step);
+        // Targets are read from the last propagation; followers land in
+        // their local transforms, which the interpolation blends.
+        cameras::update_follow_cameras(&mut self.world, step as f32);
+    }
+
+    /// Propagates world transforms for rendering, blending every entity a
+    /// fixed tick moved from its pre-tick transform by `alpha`.
+    pub fn interpolate(&mut self, alpha: f32) {
+        self.interpolation_alpha = alpha.clamp(0.0, 1.0);
+        transforms::propagate_interpolated_transforms(&mut self.world, self.interpolation_alpha);
+        skinning::update_skins(&mut self.world);
+        self.sync_active_camera();
+        self.transforms_dirty = false;
+    }
+
+    /// Propagates world transforms now if [`Self::add_child`],
+    /// [`Self::remove_child`] or [`Self::reparent`] changed the hierarchy
+    /// since the last propagation. [`Self::render`] calls this, so changes
+    /// made after the frame's update still draw in place.
+    pub fn flush_transforms(&mut self) {
+        if !self.transforms_dirty {
+            return;
+        }
+        if self.interpolation_alpha < 1.0 {
+            self.interpolate(self.interpolation_alpha);
+        } else {
+            transforms::propagate_transforms(&mut self.world);
+            skinning::update_skins(&mut self.world);
+            self.sync_active_camera();
+            self.transforms_dirty = false;
+        }
+    }
+
+    /// The blend factor world transforms were last propagated with; 1 when
+    /// the scene is not running on a fixed timestep.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
+    /// Local transforms before a fixed tick; see [`Self::end_fixed_tick`].
+    pub(crate) fn begin_fixed_tick(&self) -> interpolation::TransformSnapshot {
+        interpolation::TransformSnapshot::take(&self.world)
+    }
+
+    /// Remembers the pre-tick transform of every entity the tick moved.
+    pub(crate) fn end_fixed_tick(&mut self, snapshot: interpolation::TransformSnapshot) {
+        snapshot.record_changes(&mut self.world);
+    }
+
+    fn advance(&mut self, dt: f64) {
         self.time += dt;
 
+        // Entities despawned straight through `world` leave channels behind.
+        self.prune_animations();
+
         animations::advance_animations(
             &mut self.world,
             &self.animations,
             &mut self.animation_states,
+            &mut self.animation_blends,
+            &mut self.animation_events,
             dt,
         );
         animations::update_rotate_animations(&mut self.world, dt);
         animations::update_orbit_animations(&mut self.world, self.time);
+        animations::update_property_animations(
+            &mut self.world,
+            self.time,
+            &mut self.renderer_commands,
+        );
+    }
 
-        transforms::propagate_transforms(&mut self.world);
+    /// Queues a renderer change to be applied at the start of the next GPU stage.
+    pub fn queue_renderer_command(&mut self, command: RendererCommand) {
+        self.renderer_commands.push(command);
+    }
+
+    pub fn pending_renderer_commands(&self) -> &[RendererCommand] {
+        &self.renderer_commands
+    }
+
+    /// Applies and clears all queued renderer commands in submission order.
+    pub fn apply_renderer_commands(&mut self, renderer: &mut Renderer) {
+        for command in self.renderer_commands.drain(..) {
+            command.apply(renderer);
+        }
     }
 
     pub fn render(
@@ -128,18 +377,68 @@ impl Scene {
         batcher: &mut RenderBatcher,
     ) -> Result<crate::renderer::RenderFrame, wgpu::SurfaceError> {
         batcher.clear();
+        self.flush_transforms();
+        self.text_labels
+            .update(&mut self.world, &mut self.assets, renderer);
         let camera = rendering::CameraVectors::from_renderer(renderer);
 
-        for object in rendering::build_render_objects(&self.world, camera) {
+        let frustum = Frustum::from_view_proj(self.camera.view_proj(renderer.aspect_ratio()));
+        let frame = rendering::build_render_objects(&self.world, camera, Some(&frustum));
+        batcher.record_culled(frame.culled);
+
+        for frame_object in frame.objects {
+            let mut object = frame_object.object;
+            if let Some(joint_matrices) = frame_object.joint_matrices {
+                object.joint_offset = Some(batcher.add_joint_matrices(&joint_matrices));
+            }
+            if let Some(morph_weights) = frame_object.morph_weights {
+                object.morph_weight_offset = Some(batcher.add_morph_weights(&morph_weights));
+            }
             batcher.add(object);
         }
+        rendering::add_manual_draws(
+            renderer.manual_draws(),
+            &self.assets,
+            camera,
+            Some(&frustum),
+            batcher,
+        );
 
-        let lights = lights::collect_lights(&self.world, camera);
+        let cascades = lights::CascadeFrustum::new(
+            &self.camera,
+            renderer.aspect_ratio(),
+            renderer.settings().cascade_count,
+        );
+        let lights = lights::collect_lights(
+            &self.world,
+            &cascades,
+            self.camera.eye,
+            &renderer.settings().shadows,
+            &mut self.shadow_scheduler,
+        );
         renderer.set_lights(&lights);
+        renderer.set_debug_draw(&self.debug_draw);
+        self.debug_draw.clear();
 
         renderer.render(&self.assets, batcher, &lights, &self.environment)
     }
 
+    /// Shadow cascades `entity`'s directional light renders this frame,
+    /// fitted to the current camera and `renderer`'s cascade count. `None`
+    /// when the entity has no directional light.
+    pub fn directional_shadow_cascades(
+        &self,
+        entity: hecs::Entity,
+        renderer: &Renderer,
+    ) -> Option<CascadedShadowData> {
+        let frustum = lights::CascadeFrustum::new(
+            &self.camera,
+            renderer.aspect_ratio(),
+            renderer.settings().cascade_count,
+        );
+        lights::directional_shadow_cascades(&self.world, entity, &frustum)
+    }
+
     pub fn add_default_lighting(&mut self) -> usize {
         lights::add_default_lighting(&mut self.world)
     }
@@ -148,6 +447,214 @@ impl Scene {
         lights::has_any_lights(&self.world)
     }
 
+    /// Entities whose mesh `ray` hits within `max_distance`, nearest first.
+    /// Uses world transforms from the last [`Self::update`].
+    pub fn raycast(&self, ray: Ray, max_distance: f32) -> Vec<RaycastHit> {
+        raycast::raycast(&self.world, &self.assets, ray, max_distance)
+    }
+
+    /// Nearest entity under the pixel at `screen_pos` (origin top-left) of
+    /// a `viewport_size` viewport as seen by `camera`, up to its far plane.
+    pub fn pick(
+        &self,
+        camera: &Camera,
+        screen_pos: Vec2,
+        viewport_size: Vec2,
+    ) -> Option<RaycastHit> {
+        let (ray, max_distance) = raycast::screen_ray(camera, screen_pos, viewport_size)?;
+        self.raycast(ray, max_distance).into_iter().next()
+    }
+
+    /// Spawns a copy of `entity`'s components, without its children, under
+    /// the same parent. Meshes and textures are shared rather than
+    /// re-uploaded, and animation clips keep targeting only the original.
+    /// Returns `None` if the entity does not exist.
+    pub fn clone_entity(&mut self, entity: hecs::Entity) -> Option<hecs::Entity> {
+        cloning::clone_entity(&mut self.world, entity)
+    }
+
+    /// Like [`Self::clone_entity`] for `root` and all of its descendants.
+    /// Hierarchy links and skin joints within the subtree point at the
+    /// copies. Returns the new root.
+    pub fn clone_hierarchy(&mut self, root: hecs::Entity) -> Option<hecs::Entity> {
+        cloning::clone_hierarchy(&mut self.world, root)
+    }
+
+    /// Despawns `entity` and all of its descendants. With `release_assets`,
+    /// meshes and textures no other entity uses are removed from
+    /// [`Self::assets`], freeing their GPU memory. Animation channels that
+    /// targeted the subtree are pruned as in [`Self::despawn`]. Returns the
+    /// number of despawned entities.
+    pub fn despawn_subtree(&mut self, entity: hecs::Entity, release_assets: bool) -> usize {
+        let count =
+            hierarchy::despawn_subtree(&mut self.world, &mut self.assets, entity, release_assets);
+        if count > 0 {
+            self.prune_animations();
+        }
+        count
+    }
+
+    /// Despawns `entity` and its descendants, keeping their assets.
+    pub fn despawn_recursive(&mut self, entity: hecs::Entity) -> usize {
+        self.despawn_subtree(entity, false)
+    }
+
+    /// Despawns `entity` and every entity in its [`Children`], recursively,
+    /// after removing it from its parent's [`Children`]. Animation channels
+    /// targeting the despawned entities are dropped; clips left empty are
+    /// removed along with their states, which shifts later clip and state
+    /// indices. Returns `false` if the entity did not exist.
+    ///
+    /// [`Children`]: crate::scene::components::Children
+    pub fn despawn(&mut self, entity: hecs::Entity) -> bool {
+        self.despawn_recursive(entity) > 0
+    }
+
+    /// Despawns `entity` alone: it leaves its parent's [`Children`] and its
+    /// own children become roots. Animation channels are pruned as in
+    /// [`Self::despawn`].
+    ///
+    /// [`Children`]: crate::scene::components::Children
+    pub fn despawn_single(&mut self, entity: hecs::Entity) -> bool {
+        let despawned = hierarchy::despawn_entity(&mut self.world, entity);
+        if despawned {
+            self.prune_animations();
+        }
+        despawned
+    }
+
+    /// Makes `child` a child of `parent`, moving it from any previous
+    /// parent. Its local transform is kept, so it now follows `parent`.
+    /// Returns `false` without changes if either entity is missing or
+    /// `child` is `parent` or one of its ancestors. World transforms are
+    /// updated by the next [`Self::flush_transforms`].
+    pub fn add_child(&mut self, parent: hecs::Entity, child: hecs::Entity) -> bool {
+        let attached = hierarchy::attach(&mut self.world, parent, child);
+        self.transforms_dirty |= attached;
+        attached
+    }
+
+    /// Unlinks `child` from `parent`; it becomes a root with its local
+    /// transform as its world transform. Returns `false` if `child` is not a
+    /// child of `parent`.
+    pub fn remove_child(&mut self, parent: hecs::Entity, child: hecs::Entity) -> bool {
+        let detached = hierarchy::detach(&mut self.world, parent, child);
+        self.transforms_dirty |= detached;
+        detached
+    }
+
+    /// Moves `entity` under `new_parent` like [`Self::add_child`], but
+    /// rewrites its local transform so it stays where it is in the world.
+    pub fn reparent(&mut self, entity: hecs::Entity, new_parent: hecs::Entity) -> bool {
+        self.flush_transforms();
+        let world_transform = |world: &World, entity| {
+            world
+                .get::<&WorldTransform>(entity)
+                .ok()
+                .map(|transform| transform.0)
+        };
+        let placement = world_transform(&self.world, entity);
+        let parent_placement = world_transform(&self.world, new_parent);
+
+        if !self.add_child(new_parent, entity) {
+            return false;
+        }
+        let local = placement
+            .zip(parent_placement)
+            .and_then(|(placement, parent)| placement.relative_to(&parent));
+        if let (Some(local), Ok(mut transform)) =
+            (local, self.world.get::<&mut TransformComponent>(entity))
+        {
+            transform.0 = local;
+        }
+        true
+    }
+
+    fn prune_animations(&mut self) {
+        animations::prune_dead_targets(
+            &self.world,
+            &mut self.animations,
+            &mut self.animation_states,
+            &mut self.animation_blends,
+        );
+    }
+
+    /// Writes the camera, every entity's names, transforms, visibility,
+    /// materials, billboards and lights, the hierarchy, and the animation
+    /// clips and states as JSON. Meshes are written as the glTF primitive
+    /// they were loaded from and textures as their cache keys; meshes
+    /// created in code are not saved.
+    pub fn save_to_json(&self, writer: impl std::io::Write) -> Result<(), serde_json::Error> {
+        let document = serialization::snapshot(
+            &self.world,
+            &self.assets.textures,
+            &self.camera,
+            &self.animations,
+            &self.animation_states,
+        );
+        serde_json::to_writer_pretty(writer, &document)
+    }
+
+    /// Builds a new scene from JSON written by [`Self::save_to_json`].
+    /// Entities keep their mesh source but get no mesh; use
+    /// [`Self::load`] to re-import meshes and textures.
+    pub fn load_from_json(reader: impl std::io::Read) -> Result<Scene, serde_json::Error> {
+        let document: serialization::SceneDocument = serde_json::from_reader(reader)?;
+        let mut scene = Scene::new();
+        scene
+            .restore_document(document)
+            .map_err(<serde_json::Error as serde::de::Error>::custom)?;
+        Ok(scene)
+    }
+
+    /// Saves the scene to a JSON file. See [`Self::save_to_json`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+        self.save_to_json(std::io::BufWriter::new(file))
+            .map_err(|e| format!("Failed to save scene to {:?}: {}", path, e))
+    }
+
+    /// Spawns the entities saved by [`Self::save`] into this scene, replaces
+    /// the camera and appends the animation clips and states. Meshes are
+    /// re-imported from their glTF files and materials are pointed at the
+    /// re-imported textures.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        renderer: &mut Renderer,
+    ) -> Result<(), String> {
+        let path = path.as_ref();
+        log::info!("Loading scene {:?}", path);
+        let file =
+            std::fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+        let document: serialization::SceneDocument =
+            serde_json::from_reader(std::io::BufReader::new(file))
+                .map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+        let (entities, textures) = self.restore_document(document)?;
+        serialization::resolve_assets(self, renderer, &entities, &textures);
+        Ok(())
+    }
+
+    fn restore_document(
+        &mut self,
+        document: serialization::SceneDocument,
+    ) -> Result<(Vec<hecs::Entity>, Vec<serialization::TextureRecord>), String> {
+        self.camera = document.camera;
+        let restored = serialization::restore(document, &mut self.world)?;
+        let clip_offset = self.animations.len();
+        self.animations.extend(restored.clips);
+        self.animation_states
+            .extend(restored.states.into_iter().map(|mut state| {
+                state.clip_index += clip_offset;
+                state
+            }));
+        Ok((restored.entities, restored.textures))
+    }
+
     pub fn merge_as_child(&mut self, parent_entity: hecs::Entity, other: Scene) {
         composition::merge_as_child(self, parent_entity, other);
     }
@@ -180,3 +687,147 @@ impl Default for Scene {
         Self::new()
     }
 }
+
+/// Turns and moves the first entity with a [`FirstPersonController`] and a
+/// [`TransformComponent`] from `input`, then places the scene camera at its
+/// position. Mouse movement turns the view; W/A/S/D move along the ground
+/// plane and E/Q move up and down.
+pub fn system_first_person_camera(scene: &mut Scene, input: &InputState, dt: f64) {
+    let Some((_, (controller, transform))) = scene
+        .world
+        .query_mut::<(&mut FirstPersonController, &mut TransformComponent)>()
+        .into_iter()
+        .next()
+    else {
+        return;
+    };
+
+    let pitch_limit = 89f32.to_radians();
+    let look = input.mouse_delta() * controller.sensitivity;
+    controller.yaw -= look.x;
+    controller.pitch = (controller.pitch - look.y).clamp(-pitch_limit, pitch_limit);
+
+    let rotation = Quat::from_euler(EulerRot::YXZ, controller.yaw, controller.pitch, 0.0);
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        input.is_key_held(positive) as i32 as f32 - input.is_key_held(negative) as i32 as f32
+    };
+    let (sin_yaw, cos_yaw) = controller.yaw.sin_cos();
+    let forward = Vec3::new(-sin_yaw, 0.0, -cos_yaw);
+    let right = Vec3::new(cos_yaw, 0.0, -sin_yaw);
+    let direction = forward * axis(KeyCode::KeyW, KeyCode::KeyS)
+        + right * axis(KeyCode::KeyD, KeyCode::KeyA)
+        + Vec3::Y * axis(KeyCode::KeyE, KeyCode::KeyQ);
+
+    let transform = &mut transform.0;
+    transform.translation += direction.normalize_or_zero() * controller.speed * dt as f32;
+    transform.rotation = rotation;
+
+    let eye = transform.translation;
+    let camera = scene.camera_mut();
+    camera.eye = eye;
+    camera.target = eye + rotation * Vec3::NEG_Z;
+    camera.up = Vec3::Y;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::components::Children;
+    use crate::scene::Transform;
+
+    fn spawn_player(scene: &mut Scene) -> hecs::Entity {
+        scene.world.spawn((
+            FirstPersonController {
+                speed: 2.0,
+                sensitivity: 0.01,
+                ..FirstPersonController::default()
+            },
+            TransformComponent(Transform::from_trs(Vec3::Y, Quat::IDENTITY, Vec3::ONE)),
+        ))
+    }
+
+    fn spawn_at(scene: &mut Scene, translation: Vec3) -> hecs::Entity {
+        scene.world.spawn((TransformComponent(Transform::from_trs(
+            translation,
+            Quat::IDENTITY,
+            Vec3::ONE,
+        )),))
+    }
+
+    fn world_translation(scene: &Scene, entity: hecs::Entity) -> Vec3 {
+        scene
+            .world
+            .get::<&WorldTransform>(entity)
+            .unwrap()
+            .0
+            .translation
+    }
+
+    #[test]
+    fn hierarchy_edits_propagate_on_flush() {
+        let mut scene = Scene::new();
+        let parent = spawn_at(&mut scene, Vec3::X * 10.0);
+        let child = spawn_at(&mut scene, Vec3::Y);
+        scene.update(0.0);
+
+        assert!(scene.add_child(parent, child));
+        assert!(!scene.add_child(child, parent));
+        assert_eq!(world_translation(&scene, child), Vec3::Y);
+        scene.flush_transforms();
+        assert_eq!(world_translation(&scene, child), Vec3::new(10.0, 1.0, 0.0));
+
+        assert!(scene.remove_child(parent, child));
+        assert!(!scene.remove_child(parent, child));
+        scene.flush_transforms();
+        assert_eq!(world_translation(&scene, child), Vec3::Y);
+        assert!(scene.world.get::<&Children>(parent).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn reparent_keeps_the_world_placement() {
+        let mut scene = Scene::new();
+        let first = spawn_at(&mut scene, Vec3::X * 10.0);
+        let second = spawn_at(&mut scene, Vec3::Z * -4.0);
+        let child = spawn_at(&mut scene, Vec3::Y);
+        assert!(scene.add_child(first, child));
+
+        assert!(scene.reparent(child, second));
+        scene.update(0.0);
+
+        assert!(world_translation(&scene, child).abs_diff_eq(Vec3::new(10.0, 1.0, 0.0), 1e-5));
+        let local = scene.world.get::<&TransformComponent>(child).unwrap().0;
+        assert!(local
+            .translation
+            .abs_diff_eq(Vec3::new(10.0, 1.0, 4.0), 1e-5));
+        assert!(scene.world.get::<&Children>(first).unwrap().0.is_empty());
+        assert_eq!(scene.world.get::<&Children>(second).unwrap().0, vec![child]);
+    }
+
+    #[test]
+    fn first_person_camera_follows_controller() {
+        let mut scene = Scene::new();
+        spawn_player(&mut scene);
+
+        system_first_person_camera(&mut scene, &InputState::new(), 0.016);
+
+        let camera = scene.camera();
+        assert_eq!(camera.eye, Vec3::Y);
+        assert!((camera.target - camera.eye).abs_diff_eq(Vec3::NEG_Z, 1e-5));
+    }
+
+    #[test]
+    fn first_person_pitch_is_clamped() {
+        let mut scene = Scene::new();
+        let player = spawn_player(&mut scene);
+
+        let mut input = InputState::new();
+        input.on_mouse_motion(Vec2::new(0.0, -10_000.0));
+        system_first_person_camera(&mut scene, &input, 0.016);
+
+        let controller = *scene.world.get::<&FirstPersonController>(player).unwrap();
+        assert!((controller.pitch - 89f32.to_radians()).abs() < 1e-6);
+        let camera = scene.camera();
+        assert!(camera.view().is_finite());
+        assert!(camera.target.y > camera.eye.y);
+    }
+}
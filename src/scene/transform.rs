@@ -1,7 +1,8 @@
 // scene/transform.rs - Verified transform composition
 use glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Transform {
     pub translation: Vec3,
     pub rotation: Quat,
@@ -40,6 +41,15 @@ impl Transform {
         }
     }
 
+    /// Blend towards `other`: `t = 0` gives `self`, `t = 1` gives `other`.
+    pub fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        Transform {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+
     /// Combine two transforms: self (parent) * other (child) = world transform of child
     ///
     /// This computes the world transform of a child given:
@@ -65,6 +75,21 @@ impl Transform {
         }
     }
 
+    /// The local transform that places `self` under `parent`, the inverse of
+    /// [`Self::mul_transform`]: `parent.mul_transform(&self.relative_to(parent))`
+    /// gives back `self`. `None` when `parent` has a zero scale component.
+    pub fn relative_to(&self, parent: &Transform) -> Option<Transform> {
+        if parent.scale.cmpeq(Vec3::ZERO).any() {
+            return None;
+        }
+        let inverse_rotation = parent.rotation.inverse();
+        Some(Transform {
+            translation: inverse_rotation * (self.translation - parent.translation) / parent.scale,
+            rotation: inverse_rotation * self.rotation,
+            scale: self.scale / parent.scale,
+        })
+    }
+
     /// Alternative: Compute using matrix multiplication (for verification)
     pub fn mul_transform_via_matrix(&self, other: &Transform) -> Transform {
         let m = self.matrix() * other.matrix();
@@ -231,6 +256,30 @@ mod tests {
         assert!(world_trs.scale.abs_diff_eq(world_matrix.scale, 1e-4));
     }
 
+    #[test]
+    fn relative_to_inverts_composition() {
+        let parent = Transform::from_trs(
+            Vec3::new(3.0, -1.0, 2.0),
+            Quat::from_rotation_y(PI / 4.0),
+            Vec3::new(2.0, 1.0, 0.5),
+        );
+        let world = Transform::from_trs(
+            Vec3::new(-4.0, 5.0, 1.0),
+            Quat::from_rotation_x(PI / 3.0),
+            Vec3::splat(1.5),
+        );
+
+        let local = world.relative_to(&parent).unwrap();
+        let back = parent.mul_transform(&local);
+
+        assert!(back.translation.abs_diff_eq(world.translation, 1e-5));
+        assert!(back.rotation.abs_diff_eq(world.rotation, 1e-5));
+        assert!(back.scale.abs_diff_eq(world.scale, 1e-5));
+
+        let flat = Transform::from_trs(Vec3::ZERO, Quat::IDENTITY, Vec3::new(1.0, 0.0, 1.0));
+        assert!(world.relative_to(&flat).is_none());
+    }
+
     #[test]
     fn matrix_conversion_roundtrip() {
         let t = Transform::from_trs(
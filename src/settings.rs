@@ -1,25 +1,59 @@
+use crate::renderer::postprocess::SsaoQuality;
+use crate::renderer::{MAX_POINT_LIGHTS, MAX_SHADOW_CASCADES, MAX_SPOT_LIGHTS};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
+/// MSAA sample counts accepted by [`RenderSettings::sample_count`].
+pub const SUPPORTED_SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenderSettings {
+    /// MSAA samples per pixel: 1, 2, 4 or 8. Other values round down to the
+    /// nearest supported count, and the adapter may lower it further for the
+    /// surface format. On WebAssembly only `1` is universally safe; WebGL2
+    /// and many WebGPU implementations reject everything except 1 and 4.
     #[serde(default = "RenderSettings::default_sample_count")]
     pub sample_count: u32,
-    #[serde(default = "RenderSettings::default_shadow_map_size")]
-    pub shadow_map_size: u32,
+    /// Shadow map resolution and bias per light type.
+    #[serde(default)]
+    pub shadows: ShadowSettings,
+    /// Shadow cascades per directional light, 1 to
+    /// [`MAX_SHADOW_CASCADES`]. Each cascade is a separate shadow map layer
+    /// at the directional resolution.
+    #[serde(default = "RenderSettings::default_cascade_count")]
+    pub cascade_count: usize,
     #[serde(default)]
     pub resolution: Resolution,
     #[serde(default)]
     pub present_mode: PresentModeSetting,
+    /// Multiplier on the size of the egui overlay, applied on top of the
+    /// window's DPI scale factor.
+    #[serde(default = "RenderSettings::default_ui_scale")]
+    pub ui_scale: f32,
+    /// Ambient occlusion preset applied at startup; see
+    /// [`PostProcessEffects::ssao_quality`](crate::renderer::postprocess::PostProcessEffects::ssao_quality).
+    #[serde(default)]
+    pub ssao_quality: SsaoQuality,
+    /// Size of the scene targets relative to the surface. Values below 1
+    /// render at a lower resolution and upscale bilinearly; values above 1
+    /// supersample. Scales outside
+    /// [`MIN_RESOLUTION_SCALE`](Self::MIN_RESOLUTION_SCALE)..=[`MAX_RESOLUTION_SCALE`](Self::MAX_RESOLUTION_SCALE)
+    /// are kept but warned about.
+    #[serde(default = "RenderSettings::default_resolution_scale")]
+    pub resolution_scale: f32,
 }
 
 impl Default for RenderSettings {
     fn default() -> Self {
         Self {
             sample_count: Self::default_sample_count(),
-            shadow_map_size: Self::default_shadow_map_size(),
+            shadows: ShadowSettings::default(),
+            cascade_count: Self::default_cascade_count(),
             resolution: Resolution::default(),
             present_mode: PresentModeSetting::default(),
+            ui_scale: Self::default_ui_scale(),
+            ssao_quality: SsaoQuality::default(),
+            resolution_scale: Self::default_resolution_scale(),
         }
     }
 }
@@ -80,9 +114,24 @@ impl RenderSettings {
             self.sample_count = Self::default_sample_count();
         }
 
-        if self.shadow_map_size == 0 {
-            warn!("Shadow map size must be greater than zero. Using default value.");
-            self.shadow_map_size = Self::default_shadow_map_size();
+        let sample_count = Self::sanitize_sample_count(self.sample_count);
+        if sample_count != self.sample_count {
+            warn!(
+                "Sample count {} is not one of {:?}. Using {} instead.",
+                self.sample_count, SUPPORTED_SAMPLE_COUNTS, sample_count
+            );
+            self.sample_count = sample_count;
+        }
+
+        self.shadows = self.shadows.validate();
+
+        if self.cascade_count == 0 || self.cascade_count > MAX_SHADOW_CASCADES {
+            let cascade_count = self.cascade_count.clamp(1, MAX_SHADOW_CASCADES);
+            warn!(
+                "Cascade count {} is outside 1..={}. Using {} instead.",
+                self.cascade_count, MAX_SHADOW_CASCADES, cascade_count
+            );
+            self.cascade_count = cascade_count;
         }
 
         if self.resolution.width == 0 || self.resolution.height == 0 {
@@ -90,6 +139,20 @@ impl RenderSettings {
             self.resolution = Resolution::default();
         }
 
+        let ui_scale = Self::sanitize_ui_scale(self.ui_scale);
+        if ui_scale != self.ui_scale {
+            warn!(
+                "UI scale {} is outside {}..={}. Using {} instead.",
+                self.ui_scale,
+                Self::MIN_UI_SCALE,
+                Self::MAX_UI_SCALE,
+                ui_scale
+            );
+            self.ui_scale = ui_scale;
+        }
+
+        self.resolution_scale = Self::sanitize_resolution_scale(self.resolution_scale);
+
         self
     }
 
@@ -114,13 +177,221 @@ impl RenderSettings {
         }
     }
 
+    /// Rounds `requested` down to the nearest entry of
+    /// [`SUPPORTED_SAMPLE_COUNTS`], never below 1.
+    pub fn sanitize_sample_count(requested: u32) -> u32 {
+        SUPPORTED_SAMPLE_COUNTS
+            .iter()
+            .copied()
+            .filter(|&count| count <= requested)
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Smallest accepted [`ui_scale`](Self::ui_scale).
+    pub const MIN_UI_SCALE: f32 = 0.25;
+    /// Largest accepted [`ui_scale`](Self::ui_scale).
+    pub const MAX_UI_SCALE: f32 = 4.0;
+
+    /// Clamps `requested` to `MIN_UI_SCALE..=MAX_UI_SCALE`; non-finite values
+    /// become 1.
+    pub fn sanitize_ui_scale(requested: f32) -> f32 {
+        if requested.is_finite() {
+            requested.clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE)
+        } else {
+            Self::default_ui_scale()
+        }
+    }
+
+    /// Smallest [`resolution_scale`](Self::resolution_scale) accepted without
+    /// a warning.
+    pub const MIN_RESOLUTION_SCALE: f32 = 0.25;
+    /// Largest [`resolution_scale`](Self::resolution_scale) accepted without
+    /// a warning.
+    pub const MAX_RESOLUTION_SCALE: f32 = 4.0;
+
+    /// Replaces non-finite and non-positive scales with 1 and warns about
+    /// scales outside `MIN_RESOLUTION_SCALE..=MAX_RESOLUTION_SCALE`, which
+    /// are kept as requested.
+    pub fn sanitize_resolution_scale(requested: f32) -> f32 {
+        if !requested.is_finite() || requested <= 0.0 {
+            warn!(
+                "Resolution scale {} must be a positive number. Using 1 instead.",
+                requested
+            );
+            return Self::default_resolution_scale();
+        }
+        if !(Self::MIN_RESOLUTION_SCALE..=Self::MAX_RESOLUTION_SCALE).contains(&requested) {
+            warn!(
+                "Resolution scale {} is outside {}..={}; expect blurry or very slow frames.",
+                requested,
+                Self::MIN_RESOLUTION_SCALE,
+                Self::MAX_RESOLUTION_SCALE
+            );
+        }
+        requested
+    }
+
     const fn default_sample_count() -> u32 {
         1
     }
 
-    const fn default_shadow_map_size() -> u32 {
+    const fn default_cascade_count() -> usize {
+        MAX_SHADOW_CASCADES
+    }
+
+    const fn default_ui_scale() -> f32 {
+        1.0
+    }
+
+    const fn default_resolution_scale() -> f32 {
+        1.0
+    }
+}
+
+/// Shadow map resolution and depth bias for one light type.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LightShadowSettings {
+    /// Width and height of each shadow map layer in texels.
+    #[serde(default = "LightShadowSettings::default_resolution")]
+    pub resolution: u32,
+    /// Constant depth bias applied while rendering the shadow map, in
+    /// depth-buffer units.
+    #[serde(default = "LightShadowSettings::default_constant_bias")]
+    pub constant_bias: i32,
+    /// Depth bias scaled by the caster's depth slope.
+    #[serde(default = "LightShadowSettings::default_slope_bias")]
+    pub slope_bias: f32,
+    /// World-space distance the receiver is pushed along its normal before
+    /// the shadow map lookup.
+    #[serde(default)]
+    pub normal_offset: f32,
+    /// Camera distance beyond which point and spot lights skip their shadow
+    /// map. Directional lights ignore it; see
+    /// [`ShadowDistance`](crate::scene::components::ShadowDistance) for a
+    /// per-light override.
+    #[serde(default = "LightShadowSettings::default_max_distance")]
+    pub max_distance: f32,
+}
+
+impl Default for LightShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: Self::default_resolution(),
+            constant_bias: Self::default_constant_bias(),
+            slope_bias: Self::default_slope_bias(),
+            normal_offset: 0.0,
+            max_distance: Self::default_max_distance(),
+        }
+    }
+}
+
+impl LightShadowSettings {
+    /// Largest accepted [`resolution`](Self::resolution).
+    pub const MAX_RESOLUTION: u32 = 8192;
+
+    fn validate(mut self, light: &str) -> Self {
+        if self.resolution == 0 {
+            warn!(
+                "{} shadow map resolution must be greater than zero. Using default value.",
+                light
+            );
+            self.resolution = Self::default_resolution();
+        } else if self.resolution > Self::MAX_RESOLUTION {
+            warn!(
+                "{} shadow map resolution {} exceeds {}. Clamping.",
+                light,
+                self.resolution,
+                Self::MAX_RESOLUTION
+            );
+            self.resolution = Self::MAX_RESOLUTION;
+        }
+
+        if !self.slope_bias.is_finite() {
+            warn!(
+                "{} shadow slope bias must be finite. Using default value.",
+                light
+            );
+            self.slope_bias = Self::default_slope_bias();
+        }
+
+        if !self.normal_offset.is_finite() || self.normal_offset < 0.0 {
+            warn!(
+                "{} shadow normal offset must be a non-negative number. Using 0.",
+                light
+            );
+            self.normal_offset = 0.0;
+        }
+
+        if self.max_distance.is_nan() || self.max_distance <= 0.0 {
+            warn!(
+                "{} shadow max distance must be positive. Using default value.",
+                light
+            );
+            self.max_distance = Self::default_max_distance();
+        }
+
+        self
+    }
+
+    const fn default_max_distance() -> f32 {
+        100.0
+    }
+
+    const fn default_resolution() -> u32 {
         2048
     }
+
+    const fn default_constant_bias() -> i32 {
+        2
+    }
+
+    const fn default_slope_bias() -> f32 {
+        2.0
+    }
+}
+
+/// Per-light-type shadow settings. Can be changed at runtime through
+/// `Renderer::set_shadow_settings`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShadowSettings {
+    #[serde(default)]
+    pub directional: LightShadowSettings,
+    #[serde(default)]
+    pub spot: LightShadowSettings,
+    #[serde(default)]
+    pub point: LightShadowSettings,
+    /// Point and spot lights that may render shadow maps in one frame. The
+    /// lights nearest the camera win; the rest light unshadowed.
+    #[serde(default = "ShadowSettings::default_max_local_shadows")]
+    pub max_local_shadows: u32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            directional: LightShadowSettings::default(),
+            spot: LightShadowSettings::default(),
+            point: LightShadowSettings::default(),
+            max_local_shadows: Self::default_max_local_shadows(),
+        }
+    }
+}
+
+impl ShadowSettings {
+    /// Replaces invalid values with defaults, logging a warning for each.
+    pub fn validate(self) -> Self {
+        Self {
+            directional: self.directional.validate("Directional"),
+            spot: self.spot.validate("Spot"),
+            point: self.point.validate("Point"),
+            max_local_shadows: self.max_local_shadows,
+        }
+    }
+
+    const fn default_max_local_shadows() -> u32 {
+        (MAX_POINT_LIGHTS + MAX_SPOT_LIGHTS) as u32
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,12 +441,32 @@ mod tests {
     fn invalid_settings() -> RenderSettings {
         RenderSettings {
             sample_count: 0,
-            shadow_map_size: 0,
+            shadows: ShadowSettings {
+                directional: LightShadowSettings {
+                    resolution: 0,
+                    ..LightShadowSettings::default()
+                },
+                spot: LightShadowSettings {
+                    normal_offset: -1.0,
+                    ..LightShadowSettings::default()
+                },
+                point: LightShadowSettings {
+                    resolution: u32::MAX,
+                    slope_bias: f32::NAN,
+                    max_distance: -5.0,
+                    ..LightShadowSettings::default()
+                },
+                ..ShadowSettings::default()
+            },
+            cascade_count: 9,
             resolution: Resolution {
                 width: 0,
                 height: 0,
             },
             present_mode: PresentModeSetting::Immediate,
+            ui_scale: f32::NAN,
+            ssao_quality: SsaoQuality::Classic,
+            resolution_scale: -2.0,
         }
     }
 
@@ -187,32 +478,110 @@ mod tests {
             validated.sample_count,
             RenderSettings::default().sample_count
         );
+        assert_eq!(validated.shadows.directional.resolution, 2048);
+        assert_eq!(validated.shadows.spot.normal_offset, 0.0);
         assert_eq!(
-            validated.shadow_map_size,
-            RenderSettings::default().shadow_map_size
+            validated.shadows.point.resolution,
+            LightShadowSettings::MAX_RESOLUTION
         );
+        assert_eq!(validated.shadows.point.slope_bias, 2.0);
+        assert_eq!(validated.shadows.point.max_distance, 100.0);
+        assert_eq!(validated.cascade_count, MAX_SHADOW_CASCADES);
         assert_eq!(validated.resolution.width, Resolution::default().width);
         assert_eq!(validated.resolution.height, Resolution::default().height);
+        assert_eq!(validated.ui_scale, 1.0);
+        assert_eq!(validated.resolution_scale, 1.0);
+    }
+
+    #[test]
+    fn sample_count_rounds_down_to_supported_value() {
+        assert_eq!(RenderSettings::sanitize_sample_count(0), 1);
+        assert_eq!(RenderSettings::sanitize_sample_count(3), 2);
+        assert_eq!(RenderSettings::sanitize_sample_count(4), 4);
+        assert_eq!(RenderSettings::sanitize_sample_count(16), 8);
     }
 
     #[test]
     fn validate_preserves_valid_values() {
         let valid = RenderSettings {
             sample_count: 4,
-            shadow_map_size: 2048,
+            shadows: ShadowSettings {
+                spot: LightShadowSettings {
+                    resolution: 1024,
+                    constant_bias: 4,
+                    slope_bias: 1.5,
+                    normal_offset: 0.02,
+                    max_distance: 40.0,
+                },
+                max_local_shadows: 3,
+                ..ShadowSettings::default()
+            },
+            cascade_count: 2,
             resolution: Resolution {
                 width: 1920,
                 height: 1080,
             },
             present_mode: PresentModeSetting::Mailbox,
+            ui_scale: 1.5,
+            ssao_quality: SsaoQuality::High,
+            resolution_scale: 0.5,
         };
 
         let validated = valid.clone().validate();
 
         assert_eq!(validated.sample_count, valid.sample_count);
-        assert_eq!(validated.shadow_map_size, valid.shadow_map_size);
+        assert_eq!(validated.shadows, valid.shadows);
+        assert_eq!(validated.cascade_count, valid.cascade_count);
         assert_eq!(validated.resolution.width, valid.resolution.width);
         assert_eq!(validated.resolution.height, valid.resolution.height);
+        assert_eq!(validated.ui_scale, valid.ui_scale);
+        assert_eq!(validated.ssao_quality, valid.ssao_quality);
+        assert_eq!(validated.resolution_scale, valid.resolution_scale);
+    }
+
+    #[test]
+    fn resolution_scale_keeps_out_of_range_values_and_defaults_when_missing() {
+        let settings: RenderSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.resolution_scale, 1.0);
+
+        assert_eq!(RenderSettings::sanitize_resolution_scale(0.1), 0.1);
+        assert_eq!(RenderSettings::sanitize_resolution_scale(8.0), 8.0);
+        assert_eq!(RenderSettings::sanitize_resolution_scale(0.0), 1.0);
+        assert_eq!(
+            RenderSettings::sanitize_resolution_scale(f32::INFINITY),
+            1.0
+        );
+    }
+
+    #[test]
+    fn ui_scale_is_clamped_and_defaults_when_missing() {
+        let settings: RenderSettings = serde_json::from_str(r#"{ "ui_scale": 1.5 }"#).unwrap();
+        assert_eq!(settings.ui_scale, 1.5);
+
+        let settings: RenderSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.ui_scale, 1.0);
+        assert_eq!(settings.ssao_quality, SsaoQuality::Medium);
+
+        assert_eq!(RenderSettings::sanitize_ui_scale(10.0), 4.0);
+        assert_eq!(RenderSettings::sanitize_ui_scale(0.0), 0.25);
+    }
+
+    #[test]
+    fn ssao_quality_reads_preset_names() {
+        let settings: RenderSettings =
+            serde_json::from_str(r#"{ "ssao_quality": "classic" }"#).unwrap();
+        assert_eq!(settings.ssao_quality, SsaoQuality::Classic);
+        assert!(serde_json::from_str::<RenderSettings>(r#"{ "ssao_quality": "ultra" }"#).is_err());
+    }
+
+    #[test]
+    fn shadow_settings_fill_missing_fields_with_defaults() {
+        let settings: RenderSettings =
+            serde_json::from_str(r#"{ "shadows": { "point": { "resolution": 512 } } }"#).unwrap();
+
+        assert_eq!(settings.shadows.point.resolution, 512);
+        assert_eq!(settings.shadows.point.constant_bias, 2);
+        assert_eq!(settings.shadows.directional, LightShadowSettings::default());
     }
 
     #[test]
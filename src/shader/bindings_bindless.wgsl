@@ -1,44 +1,85 @@
-const MAX_TEXTURES: u32 = 256u;
+// The renderer substitutes the array length, sized from the device limits.
+const MAX_TEXTURES: u32 = {{MAX_TEXTURES}}u;
 
-@group(3) @binding(0) var textures: binding_array<texture_2d<f32>, 256>;
+@group(3) @binding(0) var textures: binding_array<texture_2d<f32>, {{MAX_TEXTURES}}>;
 @group(3) @binding(1) var tex_sampler_linear: sampler;
 @group(3) @binding(2) var tex_sampler_nearest: sampler;
+@group(3) @binding(3) var tex_sampler_linear_clamp: sampler;
+@group(3) @binding(4) var tex_sampler_nearest_clamp: sampler;
 
-fn sample_base_color_texture(index: u32, uv: vec2<f32>, use_nearest: bool) -> vec4<f32> {
-    if (use_nearest) {
-        return textureSample(textures[index], tex_sampler_nearest, uv);
+// Bit 0 selects nearest filtering, bit 1 clamp-to-edge addressing. The flag
+// constants come from the shader these bindings are prepended to.
+fn texture_sampler_mode(material_flags: u32) -> u32 {
+    return select(0u, 1u, (material_flags & FLAG_USE_NEAREST_SAMPLER) != 0u)
+        | select(0u, 2u, (material_flags & FLAG_USE_CLAMP_SAMPLER) != 0u);
+}
+
+fn sample_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec4<f32> {
+    // Textures past the array length are not bound and read as blank.
+    let bound = index < MAX_TEXTURES;
+    let slot = select(0u, index, bound);
+    var color: vec4<f32>;
+    switch (sampler_mode) {
+        case 1u: {
+            color = textureSample(textures[slot], tex_sampler_nearest, uv);
+        }
+        case 2u: {
+            color = textureSample(textures[slot], tex_sampler_linear_clamp, uv);
+        }
+        case 3u: {
+            color = textureSample(textures[slot], tex_sampler_nearest_clamp, uv);
+        }
+        default: {
+            color = textureSample(textures[slot], tex_sampler_linear, uv);
+        }
     }
-    return textureSample(textures[index], tex_sampler_linear, uv);
+    return select(vec4<f32>(0.0), color, bound);
+}
+
+fn sample_base_color_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec4<f32> {
+    return sample_texture(index, uv, sampler_mode);
 }
 
 fn sample_metallic_roughness_texture(
     index: u32,
     uv: vec2<f32>,
-    use_nearest: bool,
+    sampler_mode: u32,
 ) -> vec4<f32> {
-    if (use_nearest) {
-        return textureSample(textures[index], tex_sampler_nearest, uv);
-    }
-    return textureSample(textures[index], tex_sampler_linear, uv);
+    return sample_texture(index, uv, sampler_mode);
 }
 
-fn sample_normal_texture(index: u32, uv: vec2<f32>, use_nearest: bool) -> vec3<f32> {
-    if (use_nearest) {
-        return textureSample(textures[index], tex_sampler_nearest, uv).xyz;
-    }
-    return textureSample(textures[index], tex_sampler_linear, uv).xyz;
+fn sample_normal_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec3<f32> {
+    return sample_texture(index, uv, sampler_mode).xyz;
 }
 
-fn sample_emissive_texture(index: u32, uv: vec2<f32>, use_nearest: bool) -> vec3<f32> {
-    if (use_nearest) {
-        return textureSample(textures[index], tex_sampler_nearest, uv).rgb;
-    }
-    return textureSample(textures[index], tex_sampler_linear, uv).rgb;
+fn sample_emissive_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec3<f32> {
+    return sample_texture(index, uv, sampler_mode).rgb;
 }
 
-fn sample_occlusion_texture(index: u32, uv: vec2<f32>, use_nearest: bool) -> f32 {
-    if (use_nearest) {
-        return textureSample(textures[index], tex_sampler_nearest, uv).r;
-    }
-    return textureSample(textures[index], tex_sampler_linear, uv).r;
+fn sample_occlusion_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> f32 {
+    return sample_texture(index, uv, sampler_mode).r;
+}
+
+// Red channel scales the transmission factor (KHR_materials_transmission).
+fn sample_transmission_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> f32 {
+    return sample_texture(index, uv, sampler_mode).r;
+}
+
+// Red channel scales the clear-coat factor (KHR_materials_clearcoat).
+fn sample_clearcoat_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> f32 {
+    return sample_texture(index, uv, sampler_mode).r;
+}
+
+// Green channel scales the clear-coat roughness.
+fn sample_clearcoat_roughness_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> f32 {
+    return sample_texture(index, uv, sampler_mode).g;
+}
+
+fn sample_clearcoat_normal_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec3<f32> {
+    return sample_texture(index, uv, sampler_mode).xyz;
+}
+
+// Red/green: tangent-space direction, blue: strength (KHR_materials_anisotropy).
+fn sample_anisotropy_texture(index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec3<f32> {
+    return sample_texture(index, uv, sampler_mode).rgb;
 }
@@ -5,42 +5,76 @@
 @group(3) @binding(4) var occlusion_texture_binding: texture_2d<f32>;
 @group(3) @binding(5) var tex_sampler_linear: sampler;
 @group(3) @binding(6) var tex_sampler_nearest: sampler;
+@group(3) @binding(7) var tex_sampler_linear_clamp: sampler;
+@group(3) @binding(8) var tex_sampler_nearest_clamp: sampler;
 
-fn sample_base_color_texture(_index: u32, uv: vec2<f32>, use_nearest: bool) -> vec4<f32> {
-    if (use_nearest) {
-        return textureSample(base_color_texture_binding, tex_sampler_nearest, uv);
+// Bit 0 selects nearest filtering, bit 1 clamp-to-edge addressing. The flag
+// constants come from the shader these bindings are prepended to.
+fn texture_sampler_mode(material_flags: u32) -> u32 {
+    return select(0u, 1u, (material_flags & FLAG_USE_NEAREST_SAMPLER) != 0u)
+        | select(0u, 2u, (material_flags & FLAG_USE_CLAMP_SAMPLER) != 0u);
+}
+
+fn sample_texture(tex: texture_2d<f32>, uv: vec2<f32>, sampler_mode: u32) -> vec4<f32> {
+    switch (sampler_mode) {
+        case 1u: {
+            return textureSample(tex, tex_sampler_nearest, uv);
+        }
+        case 2u: {
+            return textureSample(tex, tex_sampler_linear_clamp, uv);
+        }
+        case 3u: {
+            return textureSample(tex, tex_sampler_nearest_clamp, uv);
+        }
+        default: {
+            return textureSample(tex, tex_sampler_linear, uv);
+        }
     }
-    return textureSample(base_color_texture_binding, tex_sampler_linear, uv);
+}
+
+fn sample_base_color_texture(_index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec4<f32> {
+    return sample_texture(base_color_texture_binding, uv, sampler_mode);
 }
 
 fn sample_metallic_roughness_texture(
     _index: u32,
     uv: vec2<f32>,
-    use_nearest: bool,
+    sampler_mode: u32,
 ) -> vec4<f32> {
-    if (use_nearest) {
-        return textureSample(metallic_roughness_texture_binding, tex_sampler_nearest, uv);
-    }
-    return textureSample(metallic_roughness_texture_binding, tex_sampler_linear, uv);
+    return sample_texture(metallic_roughness_texture_binding, uv, sampler_mode);
 }
 
-fn sample_normal_texture(_index: u32, uv: vec2<f32>, use_nearest: bool) -> vec3<f32> {
-    if (use_nearest) {
-        return textureSample(normal_texture_binding, tex_sampler_nearest, uv).xyz;
-    }
-    return textureSample(normal_texture_binding, tex_sampler_linear, uv).xyz;
+fn sample_normal_texture(_index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec3<f32> {
+    return sample_texture(normal_texture_binding, uv, sampler_mode).xyz;
 }
 
-fn sample_emissive_texture(_index: u32, uv: vec2<f32>, use_nearest: bool) -> vec3<f32> {
-    if (use_nearest) {
-        return textureSample(emissive_texture_binding, tex_sampler_nearest, uv).rgb;
-    }
-    return textureSample(emissive_texture_binding, tex_sampler_linear, uv).rgb;
+fn sample_emissive_texture(_index: u32, uv: vec2<f32>, sampler_mode: u32) -> vec3<f32> {
+    return sample_texture(emissive_texture_binding, uv, sampler_mode).rgb;
 }
 
-fn sample_occlusion_texture(_index: u32, uv: vec2<f32>, use_nearest: bool) -> f32 {
-    if (use_nearest) {
-        return textureSample(occlusion_texture_binding, tex_sampler_nearest, uv).r;
-    }
-    return textureSample(occlusion_texture_binding, tex_sampler_linear, uv).r;
+fn sample_occlusion_texture(_index: u32, uv: vec2<f32>, sampler_mode: u32) -> f32 {
+    return sample_texture(occlusion_texture_binding, uv, sampler_mode).r;
+}
+
+// The traditional layout has no transmission slot; the factor is used as is.
+fn sample_transmission_texture(_index: u32, _uv: vec2<f32>, _sampler_mode: u32) -> f32 {
+    return 1.0;
+}
+
+// No clear-coat slots either; the coat uses its factors and the geometric normal.
+fn sample_clearcoat_texture(_index: u32, _uv: vec2<f32>, _sampler_mode: u32) -> f32 {
+    return 1.0;
+}
+
+fn sample_clearcoat_roughness_texture(_index: u32, _uv: vec2<f32>, _sampler_mode: u32) -> f32 {
+    return 1.0;
+}
+
+fn sample_clearcoat_normal_texture(_index: u32, _uv: vec2<f32>, _sampler_mode: u32) -> vec3<f32> {
+    return vec3<f32>(0.5, 0.5, 1.0);
+}
+
+// Points along the tangent at full strength.
+fn sample_anisotropy_texture(_index: u32, _uv: vec2<f32>, _sampler_mode: u32) -> vec3<f32> {
+    return vec3<f32>(1.0, 0.5, 1.0);
 }
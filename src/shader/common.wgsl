@@ -1,45 +1,36 @@
 // PBR Shader with Normal Mapping and Modular Lighting
 
-struct Globals {
-    view_proj: mat4x4<f32>,
-    inverse_view_proj: mat4x4<f32>,
-    camera_pos: vec3<f32>,
-    _padding: f32,
-};
+// Shared struct layouts (Globals, Object, MaterialData, Lights, Shadows, ...)
+// and the light count constants are generated from the Rust definitions in
+// renderer/gpu_layout.rs and prepended to this file.
+
 @group(0) @binding(0) var<uniform> globals: Globals;
 
-struct EnvironmentSettings {
-    flags_intensity: vec4<f32>,
-    ambient_color: vec4<f32>,
-};
 @group(2) @binding(8) var<uniform> environment_settings: EnvironmentSettings;
-@group(2) @binding(9) var environment_map: texture_2d<f32>;
+// Prefiltered specular radiance; mip n holds roughness n / max_lod.
+@group(2) @binding(9) var environment_map: texture_cube<f32>;
 @group(2) @binding(10) var environment_sampler: sampler;
+// Mip-chained copy of the opaque scene, refracted by transmissive materials.
+@group(2) @binding(11) var transmission_color: texture_2d<f32>;
+@group(2) @binding(12) var transmission_sampler: sampler;
+// One layer per spot light, projected through the spot shadow matrix.
+@group(2) @binding(13) var spot_cookies: texture_2d_array<f32>;
+@group(2) @binding(14) var spot_cookie_sampler: sampler;
+@group(2) @binding(15) var environment_irradiance: texture_cube<f32>;
+// Split-sum scale and bias for F0, indexed by (N.V, roughness).
+@group(2) @binding(16) var environment_brdf_lut: texture_2d<f32>;
 
-struct Object {
-    model: mat4x4<f32>,
-    material_index: u32,
-    _padding: array<u32, 3>,
-    _padding2: array<u32, 4>,
-};
 @group(1) @binding(0) var<storage, read> objects: array<Object>;
 
-struct MaterialData {
-    color: vec4<f32>,
-    base_color_texture: u32,
-    metallic_roughness_texture: u32,
-    normal_texture: u32,
-    emissive_texture: u32,
-    occlusion_texture: u32,
-    material_flags: u32,
-    metallic_factor: f32,
-    roughness_factor: f32,
-    emissive_strength: f32,
-    _padding: u32,
-    _padding2: vec2<u32>,
-};
 @group(1) @binding(1) var<storage, read> materials: array<MaterialData>;
 
+@group(1) @binding(2) var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+// Morph target deltas, two vec4s (position, normal) per vertex per target,
+// and this frame's blend weights.
+@group(1) @binding(3) var<storage, read> morph_deltas: array<vec4<f32>>;
+@group(1) @binding(4) var<storage, read> morph_weights: array<f32>;
+
 // Material flags
 const FLAG_USE_BASE_COLOR_TEXTURE: u32 = 1u;
 const FLAG_USE_METALLIC_ROUGHNESS_TEXTURE: u32 = 2u;
@@ -49,61 +40,22 @@ const FLAG_USE_OCCLUSION_TEXTURE: u32 = 16u;
 const FLAG_ALPHA_BLEND: u32 = 32u;
 const FLAG_UNLIT: u32 = 128u;
 const FLAG_USE_NEAREST_SAMPLER: u32 = 256u;
-
-const MAX_DIRECTIONAL_LIGHTS: u32 = 4u;
-const MAX_POINT_LIGHTS: u32 = 4u;
-const MAX_SPOT_LIGHTS: u32 = 4u;
-const POINT_SHADOW_FACE_COUNT: u32 = 6u;
-
-struct DirectionalLight {
-    direction: vec4<f32>,
-    color_intensity: vec4<f32>,
-};
-
-struct PointLight {
-    position_range: vec4<f32>,
-    color_intensity: vec4<f32>,
-};
-
-struct SpotLight {
-    position_range: vec4<f32>,
-    direction: vec4<f32>,
-    color_intensity: vec4<f32>,
-    cone_params: vec4<f32>,
-};
-
-struct Lights {
-    counts: vec4<u32>,
-    directionals: array<DirectionalLight, MAX_DIRECTIONAL_LIGHTS>,
-    points: array<PointLight, MAX_POINT_LIGHTS>,
-    spots: array<SpotLight, MAX_SPOT_LIGHTS>,
-};
+const FLAG_USE_TRANSMISSION: u32 = 512u;
+const FLAG_USE_TRANSMISSION_TEXTURE: u32 = 1024u;
+const FLAG_ALPHA_MASK: u32 = 2048u;
+const FLAG_USE_CLEARCOAT: u32 = 4096u;
+const FLAG_USE_CLEARCOAT_TEXTURE: u32 = 8192u;
+const FLAG_USE_CLEARCOAT_ROUGHNESS_TEXTURE: u32 = 16384u;
+const FLAG_USE_CLEARCOAT_NORMAL_TEXTURE: u32 = 32768u;
+const FLAG_USE_ANISOTROPY: u32 = 65536u;
+const FLAG_USE_ANISOTROPY_TEXTURE: u32 = 131072u;
+const FLAG_DISTANCE_FIELD_ALPHA: u32 = 262144u;
+const FLAG_USE_CLAMP_SAMPLER: u32 = 524288u;
+const FLAG_USE_BASE_COLOR_TRANSFORM: u32 = 1048576u;
+const FLAG_USE_NORMAL_TRANSFORM: u32 = 2097152u;
 
 @group(2) @binding(0) var<storage, read> lights: Lights;
 
-struct DirectionalShadow {
-    view_proj: mat4x4<f32>,
-    params: vec4<f32>,
-    _padding: vec4<f32>,
-};
-
-struct PointShadow {
-    view_proj: array<mat4x4<f32>, POINT_SHADOW_FACE_COUNT>,
-    params: vec4<f32>,
-};
-
-struct SpotShadow {
-    view_proj: mat4x4<f32>,
-    params: vec4<f32>,
-};
-
-struct Shadows {
-    counts: vec4<u32>,
-    directionals: array<DirectionalShadow, MAX_DIRECTIONAL_LIGHTS>,
-    points: array<PointShadow, MAX_POINT_LIGHTS>,
-    spots: array<SpotShadow, MAX_SPOT_LIGHTS>,
-};
-
 @group(2) @binding(1) var<uniform> shadow_info: Shadows;
 
 @group(2) @binding(2) var directional_shadow_maps: texture_depth_2d_array;
@@ -121,6 +73,13 @@ struct VsIn {
     @location(2) uv: vec2<f32>,
     @location(3) tangent: vec4<f32>,  // xyz = tangent, w = handedness
     @builtin(instance_index) instance: u32,
+    @builtin(vertex_index) vertex: u32,
+};
+
+// Second vertex buffer bound for skinned meshes only
+struct SkinIn {
+    @location(4) joints: vec4<u32>,
+    @location(5) weights: vec4<f32>,
 };
 
 struct VsOut {
@@ -135,20 +94,63 @@ struct VsOut {
     @location(7) @interpolate(flat) material_texture_indices0: vec4<u32>,
     @location(8) @interpolate(flat) material_texture_indices1: vec2<u32>,
     @location(9) @interpolate(flat) material_flags: u32,
-    @location(10) @interpolate(flat) material_factors: vec3<f32>,
+    @location(10) @interpolate(flat) material_factors: vec4<f32>, // metallic, roughness, transmission, alpha cutoff
+    @location(11) @interpolate(flat) material_emissive: vec3<f32>,
+    // Corner weights for the shader wireframe; see fs_debug_wireframe.
+    @location(12) barycentric: vec3<f32>,
+    // Last frame's unjittered clip position; see surface_velocity.
+    @location(13) prev_clip: vec4<f32>,
 };
 
 @vertex
 fn vs_main(in: VsIn) -> VsOut {
     let obj = objects[in.instance];
-    let M = obj.model;
-    let world_pos = M * vec4(in.pos, 1.0);
+    return shade_vertex(in, obj, obj.model, obj.prev_model);
+}
+
+@vertex
+fn vs_main_skinned(in: VsIn, skin: SkinIn) -> VsOut {
+    let obj = objects[in.instance];
+    let base = obj.joint_offset;
+    let skin_matrix = joint_matrices[base + skin.joints.x] * skin.weights.x
+        + joint_matrices[base + skin.joints.y] * skin.weights.y
+        + joint_matrices[base + skin.joints.z] * skin.weights.z
+        + joint_matrices[base + skin.joints.w] * skin.weights.w;
+    // Last frame's pose is not kept, so skinned motion vectors only follow
+    // the node transform.
+    return shade_vertex(in, obj, obj.model * skin_matrix, obj.prev_model * skin_matrix);
+}
+
+struct MorphedVertex {
+    pos: vec3<f32>,
+    normal: vec3<f32>,
+};
+
+// Adds the weighted morph target deltas to the base vertex.
+fn apply_morph_targets(obj: Object, vertex: u32, pos: vec3<f32>, normal: vec3<f32>) -> MorphedVertex {
+    var out = MorphedVertex(pos, normal);
+    for (var t = 0u; t < obj.morph_target_count; t = t + 1u) {
+        let weight = morph_weights[obj.morph_weight_offset + t];
+        if (weight == 0.0) {
+            continue;
+        }
+        let index = (obj.morph_delta_offset + t * obj.morph_vertex_count + vertex) * 2u;
+        out.pos += morph_deltas[index].xyz * weight;
+        out.normal += morph_deltas[index + 1u].xyz * weight;
+    }
+    return out;
+}
+
+fn shade_vertex(in: VsIn, obj: Object, M: mat4x4<f32>, prev_M: mat4x4<f32>) -> VsOut {
+    let morphed = apply_morph_targets(obj, in.vertex, in.pos, in.normal);
+    let world_pos = M * vec4(morphed.pos, 1.0);
+    let prev_world_pos = prev_M * vec4(morphed.pos, 1.0);
     let material = materials[obj.material_index];
 
     // Transform normal and tangent to world space
     // For non-uniform scaling, we should use inverse transpose of the model matrix
     // But for now, this works for uniform scaling
-    let n = normalize((M * vec4(in.normal, 0.0)).xyz);
+    let n = normalize((M * vec4(morphed.normal, 0.0)).xyz);
     let t = normalize((M * vec4(in.tangent.xyz, 0.0)).xyz);
     
     // Calculate bitangent using the handedness from the tangent w component
@@ -157,6 +159,7 @@ fn vs_main(in: VsIn) -> VsOut {
 
     var out: VsOut;
     out.pos = globals.view_proj * world_pos;
+    out.prev_clip = globals.prev_view_proj * prev_world_pos;
     out.world_pos = world_pos.xyz;
     out.normal = n;
     out.uv = in.uv;
@@ -170,12 +173,21 @@ fn vs_main(in: VsIn) -> VsOut {
         material.normal_texture,
         material.emissive_texture,
     );
-    out.material_texture_indices1 = vec2<u32>(material.occlusion_texture, 0u);
+    out.material_texture_indices1 =
+        vec2<u32>(material.occlusion_texture, material.transmission_texture);
     out.material_flags = material.material_flags;
-    out.material_factors = vec3<f32>(
+    out.material_factors = vec4<f32>(
         material.metallic_factor,
         material.roughness_factor,
-        material.emissive_strength,
+        material.transmission_factor,
+        material.alpha_cutoff,
+    );
+    out.material_emissive = material.emissive_color;
+    let corner = in.vertex % 3u;
+    out.barycentric = vec3<f32>(
+        select(0.0, 1.0, corner == 0u),
+        select(0.0, 1.0, corner == 1u),
+        select(0.0, 1.0, corner == 2u),
     );
     return out;
 }
@@ -227,6 +239,120 @@ fn calculate_light_contribution(
     return (diffuse + specular) * radiance * NdotL;
 }
 
+// Clear-coat layer over the base material; a zero factor disables it.
+struct ClearCoat {
+    factor: f32,
+    roughness: f32,
+    normal: vec3<f32>,
+};
+
+// Anisotropic specular; a zero strength falls back to isotropic GGX. The
+// tangent and bitangent are the anisotropy direction in world space.
+struct Anisotropy {
+    strength: f32,
+    tangent: vec3<f32>,
+    bitangent: vec3<f32>,
+};
+
+// Like calculate_light_contribution, with the specular lobe stretched along
+// the anisotropy direction.
+fn calculate_anisotropic_light_contribution(
+    N: vec3<f32>,
+    V: vec3<f32>,
+    L: vec3<f32>,
+    base_color: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+    aniso: Anisotropy,
+    light_color: vec3<f32>,
+    light_intensity: f32
+) -> vec3<f32> {
+    let NdotL = max(dot(N, L), 0.0);
+    if (NdotL <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    let H = normalize(V + L);
+    let NdotV = max(dot(N, V), 1e-4);
+    let alpha = roughness * roughness;
+    let at = mix(alpha, 1.0, aniso.strength * aniso.strength);
+    let ab = clamp(alpha, 0.001, 1.0);
+
+    let F0 = mix(vec3<f32>(0.04), base_color, metallic);
+    let F = fresnel_schlick(max(dot(H, V), 0.0), F0);
+    let D = distribution_ggx_anisotropic(
+        max(dot(N, H), 0.0),
+        dot(aniso.tangent, H),
+        dot(aniso.bitangent, H),
+        at,
+        ab,
+    );
+    let Vis = visibility_ggx_anisotropic(
+        NdotL,
+        NdotV,
+        dot(aniso.tangent, V),
+        dot(aniso.bitangent, V),
+        dot(aniso.tangent, L),
+        dot(aniso.bitangent, L),
+        at,
+        ab,
+    );
+    let specular = F * D * Vis;
+
+    let kD = (vec3<f32>(1.0) - F) * (1.0 - metallic);
+    let diffuse = kD * base_color / PI;
+
+    let radiance = light_color * light_intensity;
+    return (diffuse + specular) * radiance * NdotL;
+}
+
+// Base lobes attenuated by the coat's Fresnel plus the coat's own specular,
+// which is lit through the coat normal.
+fn calculate_layered_light_contribution(
+    N: vec3<f32>,
+    V: vec3<f32>,
+    L: vec3<f32>,
+    base_color: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+    aniso: Anisotropy,
+    coat: ClearCoat,
+    light_color: vec3<f32>,
+    light_intensity: f32
+) -> vec3<f32> {
+    var base: vec3<f32>;
+    if (aniso.strength > 0.0) {
+        base = calculate_anisotropic_light_contribution(
+            N,
+            V,
+            L,
+            base_color,
+            metallic,
+            roughness,
+            aniso,
+            light_color,
+            light_intensity,
+        );
+    } else {
+        base = calculate_light_contribution(
+            N,
+            V,
+            L,
+            base_color,
+            metallic,
+            roughness,
+            light_color,
+            light_intensity,
+        );
+    }
+    if (coat.factor <= 0.0) {
+        return base;
+    }
+    let NcdotL = max(dot(coat.normal, L), 0.0);
+    let lobe = clearcoat_lobe(coat.normal, V, L, coat.factor, coat.roughness);
+    return base * (1.0 - lobe.y) + lobe.x * light_color * light_intensity * NcdotL;
+}
+
 // Retained hardcoded lighting for testing and fallback scenarios
 fn calculate_test_lighting(
     _world_pos: vec3<f32>,
@@ -376,9 +502,40 @@ fn project_shadow_with_normal_offset(
     return vec3<f32>(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5, ndc.z);
 }
 
-fn sample_directional_shadow(index: u32, world_pos: vec3<f32>) -> f32 {
+// Distance of world_pos in front of the camera along its view direction,
+// the depth the directional cascade splits are measured in.
+fn camera_view_depth(world_pos: vec3<f32>) -> f32 {
+    let near = globals.inverse_view_proj * vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    let far = globals.inverse_view_proj * vec4<f32>(0.0, 0.0, 1.0, 1.0);
+    let forward = normalize(far.xyz / far.w - near.xyz / near.w);
+    return dot(world_pos - globals.camera_pos, forward);
+}
+
+// First cascade whose split lies beyond world_pos; fragments past the last
+// split use the last cascade and fall off its bounds.
+fn select_cascade(info: DirectionalShadow, world_pos: vec3<f32>) -> u32 {
+    let count = min(u32(info.params.y), MAX_SHADOW_CASCADES);
+    let depth = camera_view_depth(world_pos);
+    var cascade = 0u;
+    for (var i = 0u; i + 1u < count; i = i + 1u) {
+        if (depth > info.splits[i]) {
+            cascade = i + 1u;
+        }
+    }
+    return cascade;
+}
+
+fn sample_directional_shadow(index: u32, world_pos: vec3<f32>, N: vec3<f32>) -> f32 {
     let info = shadow_info.directionals[index];
-    let proj = project_shadow(info.view_proj, world_pos);
+    let cascade = select_cascade(info, world_pos);
+    let proj = project_shadow_with_normal_offset(
+        info.view_proj[cascade],
+        world_pos,
+        N,
+        shadow_info.normal_offsets.x,
+    );
+    // counts.w: shadow map layers reserved per directional light.
+    let layer = i32(index * shadow_info.counts.w + cascade);
     let depth = clamp(proj.z, 0.0, 1.0);
     let texel = shadow_texel_size(directional_shadow_maps);
     
@@ -387,7 +544,7 @@ fn sample_directional_shadow(index: u32, world_pos: vec3<f32>) -> f32 {
         directional_shadow_maps,
         directional_shadow_sampler,
         proj.xy,
-        i32(index),
+        layer,
         depth,
         texel,
     );
@@ -403,16 +560,36 @@ fn sample_directional_shadow(index: u32, world_pos: vec3<f32>) -> f32 {
 
 // ---- Spot shadow using view-depth-scaled PCF (wgpu) ----
 // Assumes: glam Mat4::perspective_rh used when building view_proj (depth 0..1)
+// Cookie colour of a spot light at world_pos; white when the light has no
+// cookie (cone_params.z == 0) and black outside its projection.
+fn sample_spot_cookie(index: u32, world_pos: vec3<f32>) -> vec3<f32> {
+    let proj = project_shadow(shadow_info.spots[index].view_proj, world_pos);
+    let cookie = textureSampleLevel(
+        spot_cookies,
+        spot_cookie_sampler,
+        clamp(proj.xy, vec2<f32>(0.0), vec2<f32>(1.0)),
+        i32(index),
+        0.0,
+    ).rgb;
+
+    if (lights.spots[index].cone_params.z == 0.0) {
+        return vec3<f32>(1.0);
+    }
+    if (any(proj.xy < vec2<f32>(0.0)) || any(proj.xy > vec2<f32>(1.0)) || proj.z < 0.0) {
+        return vec3<f32>(0.0);
+    }
+    return cookie;
+}
+
 fn sample_spot_shadow(index: u32, world_pos: vec3<f32>, N: vec3<f32>) -> f32 {
     let info  = shadow_info.spots[index];
     let light = lights.spots[index];
 
     // params.x: has_data (!=0)
     // params.y: far (store your spot shadow far here for proper normalization)
-    // params.z: receiver normal offset (0..0.004)
     // params.w: pcf_scale (1.5..3.0)
     let far_plane       = max(info.params.y, 0.0001);
-    let receiver_offset = info.params.z;
+    let receiver_offset = shadow_info.normal_offsets.y;
     let pcf_scale       = select(2.0, info.params.w, info.params.w > 0.0);
 
     // Project into shadow map (with tiny receiver offset)
@@ -453,7 +630,7 @@ fn sample_spot_shadow(index: u32, world_pos: vec3<f32>, N: vec3<f32>) -> f32 {
 }
 
 
-fn sample_point_shadow(index: u32, world_pos: vec3<f32>) -> f32 {
+fn sample_point_shadow(index: u32, world_pos: vec3<f32>, N: vec3<f32>) -> f32 {
     let info = shadow_info.points[index];
     let light = lights.points[index];
     let light_pos = light.position_range.xyz;
@@ -464,7 +641,7 @@ fn sample_point_shadow(index: u32, world_pos: vec3<f32>) -> f32 {
     let dir = normalize(to_fragment);
     let face = select_point_face(dir);
     let matrix = info.view_proj[face];
-    let proj = project_shadow(matrix, world_pos);
+    let proj = project_shadow_with_normal_offset(matrix, world_pos, N, shadow_info.normal_offsets.z);
     let layer = i32(index * POINT_SHADOW_FACE_COUNT + face);
     let depth = clamp(proj.z, 0.0, 1.0);
     let texel = shadow_texel_size(point_shadow_maps);
@@ -635,35 +812,11 @@ fn environment_ambient_intensity() -> f32 {
     return environment_settings.flags_intensity.z;
 }
 
-fn direction_to_equirect(direction: vec3<f32>) -> vec2<f32> {
-    let dir = normalize(direction);
-    let theta = atan2(dir.z, dir.x);
-    let phi = acos(clamp(dir.y, -1.0, 1.0));
-    let u = fract(0.5 - theta / TWO_PI);
-    let v = clamp(phi / PI, 0.0, 1.0);
-    return vec2<f32>(u, v);
-}
-
-fn environment_uv(direction: vec3<f32>) -> vec2<f32> {
-    let base_uv = direction_to_equirect(direction);
-    let dims_u32 = textureDimensions(environment_map, 0);
-    let dims = vec2<f32>(f32(dims_u32.x), f32(dims_u32.y));
-    let safe_dims = max(dims, vec2<f32>(1.0, 1.0));
-    let inv_dims = vec2<f32>(1.0, 1.0) / safe_dims;
-    let texel = inv_dims * 0.5;
-    let shifted = base_uv + texel;
-    let wrapped_u = fract(shifted.x);
-    let clamped_v = clamp(shifted.y, texel.y, 1.0 - texel.y);
-    return vec2<f32>(wrapped_u, clamped_v);
-}
-
-fn sample_environment_hdr(direction: vec3<f32>, lod: f32) -> vec3<f32> {
-    let uv = environment_uv(direction);
-    return textureSampleLevel(environment_map, environment_sampler, uv, lod).rgb;
-}
-
+// `reflection_normal` is N for isotropic materials and the bent normal of
+// anisotropic_reflection_normal otherwise.
 fn calculate_environment_lighting(
     N: vec3<f32>,
+    reflection_normal: vec3<f32>,
     V: vec3<f32>,
     base_color: vec3<f32>,
     metallic: f32,
@@ -672,19 +825,31 @@ fn calculate_environment_lighting(
 ) -> vec3<f32> {
     if (environment_hdr_enabled()) {
         let max_lod = environment_settings.flags_intensity.w;
+        let intensity = environment_hdr_intensity();
+        let n_dot_v = clamp(dot(N, V), 1e-4, 1.0);
+
+        let F0 = mix(vec3<f32>(0.04), base_color, vec3<f32>(metallic));
+        let F = fresnel_schlick_roughness(n_dot_v, F0, roughness);
+        let kd = (vec3<f32>(1.0) - F) * (1.0 - metallic);
+
         let irradiance =
-            sample_environment_hdr(N, max_lod) * environment_hdr_intensity();
-        let diffuse_color = base_color * (1.0 - metallic);
-        let diffuse = irradiance * diffuse_color;
-
-        let reflected = normalize(reflect(-V, normalize(N)));
-        let rough_lod = max_lod * roughness * roughness;
-        let spec_sample =
-            sample_environment_hdr(reflected, rough_lod) * environment_hdr_intensity();
-        let specular_color = mix(vec3<f32>(0.04), base_color, vec3<f32>(metallic));
-        var specular_strength = pow(clamp(1.0 - roughness, 0.0, 1.0), 4.0);
-        specular_strength = max(specular_strength, 0.05);
-        let specular = spec_sample * specular_color * specular_strength;
+            textureSampleLevel(environment_irradiance, environment_sampler, N, 0.0).rgb * intensity;
+        let diffuse = kd * irradiance * base_color;
+
+        let reflected = reflect(-V, reflection_normal);
+        let prefiltered = textureSampleLevel(
+            environment_map,
+            environment_sampler,
+            reflected,
+            roughness * max_lod,
+        ).rgb * intensity;
+        let brdf = textureSampleLevel(
+            environment_brdf_lut,
+            environment_sampler,
+            vec2<f32>(n_dot_v, roughness),
+            0.0,
+        ).rg;
+        let specular = prefiltered * (F * brdf.x + brdf.y);
 
         return (diffuse + specular) * occlusion;
     }
@@ -693,13 +858,85 @@ fn calculate_environment_lighting(
     return ambient_base * base_color * occlusion;
 }
 
+// Bends the normal used for environment reflections towards the anisotropy
+// bitangent, which approximates the stretched reflection (glTF sample viewer).
+fn anisotropic_reflection_normal(N: vec3<f32>, V: vec3<f32>, roughness: f32, aniso: Anisotropy) -> vec3<f32> {
+    if (aniso.strength <= 0.0) {
+        return N;
+    }
+    let bent = normalize(cross(cross(aniso.bitangent, V), aniso.bitangent));
+    let a = pow(1.0 - aniso.strength * (1.0 - roughness), 4.0);
+    return normalize(mix(bent, N, a));
+}
+
+// Environment reflection off the clear coat. Returns the coat's specular in
+// rgb and its Fresnel weight, which attenuates the base layer's environment
+// light, in a.
+fn calculate_clearcoat_environment(coat: ClearCoat, V: vec3<f32>, occlusion: f32) -> vec4<f32> {
+    if (coat.factor <= 0.0 || !environment_hdr_enabled()) {
+        return vec4<f32>(0.0);
+    }
+    let max_lod = environment_settings.flags_intensity.w;
+    let n_dot_v = clamp(dot(coat.normal, V), 1e-4, 1.0);
+    let Fc = fresnel_schlick(n_dot_v, vec3<f32>(0.04)).x * coat.factor;
+    let prefiltered = textureSampleLevel(
+        environment_map,
+        environment_sampler,
+        reflect(-V, coat.normal),
+        coat.roughness * max_lod,
+    ).rgb * environment_hdr_intensity();
+    return vec4<f32>(prefiltered * Fc * occlusion, Fc);
+}
+
+// Screen-space refraction: bends the view ray through the surface, projects a
+// point a short distance behind it and reads the opaque scene there. Rough
+// surfaces sample blurrier mips.
+fn sample_transmission(
+    world_pos: vec3<f32>,
+    N: vec3<f32>,
+    V: vec3<f32>,
+    roughness: f32,
+) -> vec3<f32> {
+    let ior = 1.5;
+    let thickness = 0.1;
+    let refracted = refract(-V, N, 1.0 / ior);
+    let clip = globals.view_proj * vec4<f32>(world_pos + refracted * thickness, 1.0);
+    let ndc = clip.xy / max(clip.w, 1e-4);
+    let uv = clamp(vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5), vec2<f32>(0.0), vec2<f32>(1.0));
+    let max_lod = f32(textureNumLevels(transmission_color) - 1u);
+    let scene = textureSampleLevel(transmission_color, transmission_sampler, uv, roughness * max_lod).rgb;
+    // The copy holds tonemapped colour; undo the Reinhard curve so it is
+    // mixed with the rest of the lighting in linear HDR.
+    return scene / max(vec3<f32>(1.0) - scene, vec3<f32>(1e-3));
+}
+
+// Inverse-square falloff, or 1 / (1 + linear d + quadratic d^2) when the
+// light carries an AttenuationOverride (attenuation.x == 1). Both fade to
+// zero at the light's range.
+fn point_attenuation(light: PointLight, distance: f32) -> f32 {
+    var attenuation = 1.0 / max(distance * distance, 0.0001);
+    if (light.attenuation.x > 0.5) {
+        let linear_term = light.attenuation.y;
+        let quadratic_term = light.attenuation.z;
+        attenuation = 1.0 / (1.0 + linear_term * distance + quadratic_term * distance * distance);
+    }
+    let range = light.position_range.w;
+    if (range > 0.0) {
+        let range_factor = clamp(1.0 - distance / range, 0.0, 1.0);
+        attenuation = attenuation * range_factor * range_factor;
+    }
+    return attenuation;
+}
+
 fn calculate_scene_lighting(
     world_pos: vec3<f32>,
     N: vec3<f32>,
     V: vec3<f32>,
     base_color: vec3<f32>,
     metallic: f32,
-    roughness: f32
+    roughness: f32,
+    aniso: Anisotropy,
+    coat: ClearCoat
 ) -> vec3<f32> {
     var Lo = vec3<f32>(0.0);
 
@@ -710,14 +947,16 @@ fn calculate_scene_lighting(
         let light_dir = normalize(-light.direction.xyz);
         let light_color = light.color_intensity.xyz;
         let light_intensity = light.color_intensity.w;
-        let shadow = sample_directional_shadow(i, world_pos);
-        Lo += shadow * calculate_light_contribution(
+        let shadow = sample_directional_shadow(i, world_pos, N);
+        Lo += shadow * calculate_layered_light_contribution(
             N,
             V,
             light_dir,
             base_color,
             metallic,
             roughness,
+            aniso,
+            coat,
             light_color,
             light_intensity,
         );
@@ -731,26 +970,23 @@ fn calculate_scene_lighting(
         let distance = length(to_light);
         
         // ALWAYS sample shadow in uniform control flow
-        let shadow = sample_point_shadow(i, world_pos);
+        let shadow = sample_point_shadow(i, world_pos, N);
         
         // Then conditionally use the result
         if (distance > 0.0001) {
             let L = to_light / distance;
-            var attenuation = 1.0 / max(distance * distance, 0.0001);
-            let range = light.position_range.w;
-            if (range > 0.0) {
-                let range_factor = clamp(1.0 - distance / range, 0.0, 1.0);
-                attenuation = attenuation * range_factor * range_factor;
-            }
+            let attenuation = point_attenuation(light, distance);
             let light_color = light.color_intensity.xyz;
             let light_intensity = light.color_intensity.w * attenuation;
-            Lo += shadow * calculate_light_contribution(
+            Lo += shadow * calculate_layered_light_contribution(
                 N,
                 V,
                 L,
                 base_color,
                 metallic,
                 roughness,
+                aniso,
+                coat,
                 light_color,
                 light_intensity,
             );
@@ -766,6 +1002,7 @@ fn calculate_scene_lighting(
         
         // ALWAYS sample shadow in uniform control flow
         let shadow = sample_spot_shadow(i, world_pos, N);
+        let cookie = sample_spot_cookie(i, world_pos);
         
         // Then conditionally use the result
         if (distance > 0.0001) {
@@ -791,13 +1028,15 @@ fn calculate_scene_lighting(
             if (spot_effect > 0.0) {
                 let light_color = light.color_intensity.xyz;
                 let light_intensity = light.color_intensity.w * attenuation * spot_effect;
-                Lo += shadow * calculate_light_contribution(
+                Lo += shadow * cookie * calculate_layered_light_contribution(
                     N,
                     V,
                     L,
                     base_color,
                     metallic,
                     roughness,
+                    aniso,
+                    coat,
                     light_color,
                     light_intensity,
                 );
@@ -882,8 +1121,35 @@ fn calculate_scene_lighting(
 // }
 
 
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // Reinhard-encoded emission alone, the bloom source when bloom is
+    // limited to emissive surfaces.
+    @location(1) emissive: vec4<f32>,
+    // Screen-space motion since the last frame, read by the TAA resolve.
+    @location(2) velocity: vec2<f32>,
+};
+
+// Motion of this surface point since the last frame, in UV units. Both
+// positions are unjittered so the TAA jitter does not read as motion.
+fn surface_velocity(world_pos: vec3<f32>, prev_clip: vec4<f32>) -> vec2<f32> {
+    let clip = globals.unjittered_view_proj * vec4<f32>(world_pos, 1.0);
+    let ndc = clip.xy / clip.w;
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    return (ndc - prev_ndc) * vec2<f32>(0.5, -0.5);
+}
+
+// KHR_texture_transform: scale, rotate counter-clockwise, then offset.
+// `offset_scale` packs offset.xy and scale.xy.
+fn transform_uv(uv: vec2<f32>, offset_scale: vec4<f32>, rotation: f32) -> vec2<f32> {
+    let c = cos(rotation);
+    let s = sin(rotation);
+    let scaled = uv * offset_scale.zw;
+    return vec2<f32>(c * scaled.x + s * scaled.y, c * scaled.y - s * scaled.x) + offset_scale.xy;
+}
+
 @fragment
-fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
+fn fs_main(in: VsOut, @builtin(front_facing) front_facing: bool) -> FragmentOutput {
     // shadow debug
     // let shadow = sample_directional_shadow(0u, in.world_pos);
     // if (shadow < 0.99) {
@@ -892,20 +1158,51 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
 
     // ALWAYS sample all textures (uniform control flow)
     let material_flags = in.material_flags;
-    let use_nearest_sampler = (material_flags & FLAG_USE_NEAREST_SAMPLER) != 0u;
+    let sampler_mode = texture_sampler_mode(material_flags);
+    // Transform and clear-coat data are rare enough to read straight from
+    // the material instead of spending more inter-stage varyings on them.
+    let material = materials[objects[in.instance_id].material_index];
+    let base_color_uv = select(
+        in.uv,
+        transform_uv(in.uv, material.base_color_uv_transform, material.base_color_uv_rotation),
+        (material_flags & FLAG_USE_BASE_COLOR_TRANSFORM) != 0u,
+    );
+    let normal_uv = select(
+        in.uv,
+        transform_uv(in.uv, material.normal_uv_transform, material.normal_uv_rotation),
+        (material_flags & FLAG_USE_NORMAL_TRANSFORM) != 0u,
+    );
     let base_color_sample =
-        sample_base_color_texture(in.material_texture_indices0.x, in.uv, use_nearest_sampler);
+        sample_base_color_texture(in.material_texture_indices0.x, base_color_uv, sampler_mode);
     let mr_sample = sample_metallic_roughness_texture(
         in.material_texture_indices0.y,
         in.uv,
-        use_nearest_sampler,
+        sampler_mode,
     );
     let normal_sample =
-        sample_normal_texture(in.material_texture_indices0.z, in.uv, use_nearest_sampler);
+        sample_normal_texture(in.material_texture_indices0.z, normal_uv, sampler_mode);
     let emissive_sample =
-        sample_emissive_texture(in.material_texture_indices0.w, in.uv, use_nearest_sampler);
+        sample_emissive_texture(in.material_texture_indices0.w, in.uv, sampler_mode);
     let occlusion_sample =
-        sample_occlusion_texture(in.material_texture_indices1.x, in.uv, use_nearest_sampler);
+        sample_occlusion_texture(in.material_texture_indices1.x, in.uv, sampler_mode);
+    let transmission_sample =
+        sample_transmission_texture(in.material_texture_indices1.y, in.uv, sampler_mode);
+    let clearcoat_sample =
+        sample_clearcoat_texture(material.clearcoat_texture, in.uv, sampler_mode);
+    let clearcoat_roughness_sample = sample_clearcoat_roughness_texture(
+        material.clearcoat_roughness_texture,
+        in.uv,
+        sampler_mode,
+    );
+    let clearcoat_normal_sample = sample_clearcoat_normal_texture(
+        material.clearcoat_normal_texture,
+        in.uv,
+        sampler_mode,
+    );
+    let anisotropy_sample =
+        sample_anisotropy_texture(material.anisotropy_texture, in.uv, sampler_mode);
+    // Derivatives need uniform control flow, so take them before branching.
+    let distance_width = max(fwidth(base_color_sample.a), 1e-4);
 
     // Then conditionally USE the samples (non-uniform control flow is OK here)
     var base_color: vec4<f32>;
@@ -914,6 +1211,12 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     } else {
         base_color = in.material_color;
     }
+    // Distance field text: alpha holds the distance to the glyph outline,
+    // which sits at 0.5.
+    if ((material_flags & FLAG_DISTANCE_FIELD_ALPHA) != 0u) {
+        base_color.a = in.material_color.a
+            * smoothstep(0.5 - distance_width, 0.5 + distance_width, base_color_sample.a);
+    }
 
     var metallic: f32;
     var roughness: f32;
@@ -926,16 +1229,59 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     }
     roughness = max(roughness, 0.01);
 
+    // Only double-sided materials rasterize back faces; light them as seen
+    // from the viewer's side by flipping the whole tangent frame.
+    let facing = select(-1.0, 1.0, front_facing);
+    let T_geom = normalize(in.tangent) * facing;
+    let B_geom = normalize(in.bitangent) * facing;
+    let N_geom = normalize(in.normal) * facing;
+
     var N: vec3<f32>;
     if ((material_flags & FLAG_USE_NORMAL_TEXTURE) != 0u) {
         let tangent_normal = normal_sample * 2.0 - 1.0;
-        let T = normalize(in.tangent);
-        let B = normalize(in.bitangent);
-        let N_base = normalize(in.normal);
-        let TBN = mat3x3<f32>(T, B, N_base);
+        let TBN = mat3x3<f32>(T_geom, B_geom, N_geom);
         N = normalize(TBN * tangent_normal);
     } else {
-        N = normalize(in.normal);
+        N = N_geom;
+    }
+
+    var aniso = Anisotropy(0.0, vec3<f32>(0.0), vec3<f32>(0.0));
+    if ((material_flags & FLAG_USE_ANISOTROPY) != 0u) {
+        let rotation = material.anisotropy_rotation;
+        let rotate = mat2x2<f32>(cos(rotation), sin(rotation), -sin(rotation), cos(rotation));
+        var direction = vec2<f32>(1.0, 0.0);
+        var strength = material.anisotropy_strength;
+        if ((material_flags & FLAG_USE_ANISOTROPY_TEXTURE) != 0u) {
+            let texel_direction = anisotropy_sample.rg * 2.0 - 1.0;
+            if (dot(texel_direction, texel_direction) > 1e-6) {
+                direction = normalize(texel_direction);
+            }
+            strength *= anisotropy_sample.b;
+        }
+        direction = rotate * direction;
+        // Project onto the shading normal's tangent plane so normal maps keep
+        // the frame orthonormal.
+        let tangent = T_geom * direction.x + B_geom * direction.y;
+        aniso.strength = clamp(strength, 0.0, 1.0);
+        aniso.tangent = normalize(tangent - N * dot(N, tangent));
+        aniso.bitangent = normalize(cross(N, aniso.tangent));
+    }
+
+    var coat = ClearCoat(0.0, 1.0, N_geom);
+    if ((material_flags & FLAG_USE_CLEARCOAT) != 0u) {
+        coat.factor = material.clearcoat_factor;
+        coat.roughness = material.clearcoat_roughness;
+        if ((material_flags & FLAG_USE_CLEARCOAT_TEXTURE) != 0u) {
+            coat.factor *= clearcoat_sample;
+        }
+        if ((material_flags & FLAG_USE_CLEARCOAT_ROUGHNESS_TEXTURE) != 0u) {
+            coat.roughness *= clearcoat_roughness_sample;
+        }
+        coat.roughness = max(coat.roughness, 0.01);
+        if ((material_flags & FLAG_USE_CLEARCOAT_NORMAL_TEXTURE) != 0u) {
+            let TBN = mat3x3<f32>(T_geom, B_geom, N_geom);
+            coat.normal = normalize(TBN * (clearcoat_normal_sample * 2.0 - 1.0));
+        }
     }
 
     var occlusion = 1.0;
@@ -943,36 +1289,64 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
         occlusion = occlusion_sample;
     }
 
-    var emissive = vec3<f32>(0.0);
+    // Linear HDR emission, added on top of lighting before tonemapping.
+    var emissive = in.material_emissive;
     if ((material_flags & FLAG_USE_EMISSIVE_TEXTURE) != 0u) {
-        emissive = emissive_sample * in.material_factors.z;
+        emissive = emissive_sample * in.material_emissive;
     }
 
     // Always calculate lighting in uniform control flow (required for shadow sampling)
     let V = normalize(globals.camera_pos - in.world_pos);
     let Lo =
-        calculate_scene_lighting(in.world_pos, N, V, base_color.rgb, metallic, roughness);
+        calculate_scene_lighting(in.world_pos, N, V, base_color.rgb, metallic, roughness, aniso, coat);
+    let coat_environment = calculate_clearcoat_environment(coat, V, occlusion);
     let environment_light = calculate_environment_lighting(
         N,
+        anisotropic_reflection_normal(N, V, roughness, aniso),
         V,
         base_color.rgb,
         metallic,
         roughness,
         occlusion,
-    );
+    ) * (1.0 - coat_environment.a) + coat_environment.rgb;
     
     // Then conditionally use lighting based on material flags
     var color: vec3<f32>;
+    var alpha = base_color.a;
     if ((material_flags & FLAG_UNLIT) != 0u) {
         color = base_color.rgb + emissive;
     } else {
         color = environment_light + Lo + emissive;
     }
+
+    if ((material_flags & FLAG_USE_TRANSMISSION) != 0u) {
+        var transmission = in.material_factors.z;
+        if ((material_flags & FLAG_USE_TRANSMISSION_TEXTURE) != 0u) {
+            transmission *= transmission_sample;
+        }
+        // Transmitted light replaces the diffuse lobe; metals do not transmit.
+        let transmitted = sample_transmission(in.world_pos, N, V, roughness) * base_color.rgb;
+        color = mix(color, transmitted + Lo * metallic + emissive, transmission * (1.0 - metallic));
+        alpha = 1.0;
+    }
+
+    // Cutout: masked fragments are either discarded or fully opaque.
+    if ((material_flags & FLAG_ALPHA_MASK) != 0u) {
+        if (alpha < in.material_factors.w) {
+            discard;
+        }
+        alpha = 1.0;
+    }
     
     // Tone mapping and gamma correction
     color = color / (color + vec3<f32>(1.0));
     //color = pow(color, vec3<f32>(1.0 / 2.2));
-    return vec4<f32>(color, base_color.a);   
+    let glow = emissive / (emissive + vec3<f32>(1.0));
+    return FragmentOutput(
+        vec4<f32>(color, alpha),
+        vec4<f32>(glow, alpha),
+        surface_velocity(in.world_pos, in.prev_clip),
+    );
 
 //     if ((material.material_flags & FLAG_UNLIT) != 0u) {
 //         var color = base_color.rgb + emissive;
@@ -991,3 +1365,36 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
 //     color = pow(color, vec3<f32>(1.0 / 2.2));
 //     return vec4<f32>(color, base_color.a);
  }
+
+// Debug views (RenderMode). These skip lighting and tonemapping.
+
+@fragment
+fn fs_debug_normals(in: VsOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(normalize(in.normal) * 0.5 + 0.5, 1.0);
+}
+
+@fragment
+fn fs_debug_uvs(in: VsOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(fract(in.uv), 0.0, 1.0);
+}
+
+// Drawn with additive blending and no depth test, so brightness counts the
+// fragments shaded per pixel.
+@fragment
+fn fs_debug_overdraw(in: VsOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(0.1, 0.04, 0.01, 1.0);
+}
+
+// Wireframe for devices without POLYGON_MODE_LINE. Barycentrics come from the
+// vertex index, so they are exact for unshared vertices; triangles whose
+// corners share an index residue mod 3 lose an edge.
+@fragment
+fn fs_debug_wireframe(in: VsOut) -> @location(0) vec4<f32> {
+    let width = fwidth(in.barycentric);
+    let edges = smoothstep(vec3<f32>(0.0), width * 1.5, in.barycentric);
+    let coverage = 1.0 - min(min(edges.x, edges.y), edges.z);
+    if (coverage < 0.01) {
+        discard;
+    }
+    return vec4<f32>(in.material_color.rgb * coverage, 1.0);
+}
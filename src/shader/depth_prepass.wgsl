@@ -3,13 +3,21 @@ struct Globals {
     inverse_view_proj: mat4x4<f32>,
     camera_pos: vec3<f32>,
     _padding: f32,
+    unjittered_view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
 };
 @group(0) @binding(0) var<uniform> globals: Globals;
 
 struct Object {
     model: mat4x4<f32>,
     material_index: u32,
-    _padding: vec3<u32>,
+    joint_offset: u32,
+    morph_weight_offset: u32,
+    morph_target_count: u32,
+    morph_delta_offset: u32,
+    morph_vertex_count: u32,
+    _padding: array<u32, 2>,
+    prev_model: mat4x4<f32>,
 };
 @group(1) @binding(0) var<storage, read> objects: array<Object>;
 
@@ -23,9 +31,22 @@ struct MaterialData {
     material_flags: u32,
     metallic_factor: f32,
     roughness_factor: f32,
-    emissive_strength: f32,
-    _padding: u32,
-    _padding2: vec2<u32>,
+    emissive_color: vec3<f32>,
+    transmission_factor: f32,
+    transmission_texture: u32,
+    alpha_cutoff: f32,
+    clearcoat_factor: f32,
+    clearcoat_roughness: f32,
+    clearcoat_texture: u32,
+    clearcoat_roughness_texture: u32,
+    clearcoat_normal_texture: u32,
+    anisotropy_strength: f32,
+    anisotropy_rotation: f32,
+    anisotropy_texture: u32,
+    base_color_uv_rotation: f32,
+    normal_uv_rotation: f32,
+    base_color_uv_transform: vec4<f32>,
+    normal_uv_transform: vec4<f32>,
 };
 @group(1) @binding(1) var<storage, read> materials: array<MaterialData>;
 
@@ -43,3 +64,54 @@ fn vs_main(in: VsIn) -> @builtin(position) vec4<f32> {
     let world_pos = object.model * vec4(in.pos, 1.0);
     return globals.view_proj * world_pos;
 }
+
+// Alpha-masked variant. The texture sampling helpers come from the bindless
+// or traditional bindings prepended to this file.
+const FLAG_USE_BASE_COLOR_TEXTURE: u32 = 1u;
+const FLAG_USE_NEAREST_SAMPLER: u32 = 256u;
+const FLAG_USE_CLAMP_SAMPLER: u32 = 524288u;
+const FLAG_ALPHA_MASK: u32 = 2048u;
+const FLAG_USE_BASE_COLOR_TRANSFORM: u32 = 1048576u;
+
+// Mirrors `transform_uv` in common.wgsl.
+fn transform_uv(uv: vec2<f32>, offset_scale: vec4<f32>, rotation: f32) -> vec2<f32> {
+    let c = cos(rotation);
+    let s = sin(rotation);
+    let scaled = uv * offset_scale.zw;
+    return vec2<f32>(c * scaled.x + s * scaled.y, c * scaled.y - s * scaled.x) + offset_scale.xy;
+}
+
+struct MaskedVsOut {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) material_index: u32,
+};
+
+@vertex
+fn vs_masked(in: VsIn) -> MaskedVsOut {
+    let object = objects[in.instance];
+    var out: MaskedVsOut;
+    out.pos = globals.view_proj * object.model * vec4(in.pos, 1.0);
+    out.uv = in.uv;
+    out.material_index = object.material_index;
+    return out;
+}
+
+@fragment
+fn fs_alpha_mask(in: MaskedVsOut) {
+    let material = materials[in.material_index];
+    let sampler_mode = texture_sampler_mode(material.material_flags);
+    let uv = select(
+        in.uv,
+        transform_uv(in.uv, material.base_color_uv_transform, material.base_color_uv_rotation),
+        (material.material_flags & FLAG_USE_BASE_COLOR_TRANSFORM) != 0u,
+    );
+    let texel = sample_base_color_texture(material.base_color_texture, uv, sampler_mode);
+    var alpha = material.color.a;
+    if ((material.material_flags & FLAG_USE_BASE_COLOR_TEXTURE) != 0u) {
+        alpha *= texel.a;
+    }
+    if ((material.material_flags & FLAG_ALPHA_MASK) != 0u && alpha < material.alpha_cutoff) {
+        discard;
+    }
+}
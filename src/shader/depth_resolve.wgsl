@@ -25,7 +25,22 @@ struct PostUniform {
     intensity_power : vec2<f32>,
     noise_scale : vec2<f32>,
     near_far : vec2<f32>,
+    exposure : f32,
+    orthographic : f32,
     effects : vec4<f32>,
+    ssao_params : vec4<f32>,
+    dof_params : vec4<f32>,
+    // rgb = fog color, a = density.
+    fog_color_density : vec4<f32>,
+    // x = height falloff, y = 1 when fog is enabled, zw reserved.
+    fog_params : vec4<f32>,
+    // xyz = world up in view space, w = camera height in world space.
+    fog_frame : vec4<f32>,
+    // x = bloom intensity, y = dirt mask strength, z = emissive only,
+    // w reserved.
+    bloom_params : vec4<f32>,
+    // x = tone mapping operator (see apply_tonemapping), yzw reserved.
+    tonemap_params : vec4<f32>,
 };
 
 @group(0) @binding(0)
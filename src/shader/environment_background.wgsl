@@ -1,17 +1,8 @@
-struct Globals {
-    view_proj: mat4x4<f32>,
-    inverse_view_proj: mat4x4<f32>,
-    camera_pos: vec3<f32>,
-    _padding: f32,
-};
+// Globals and EnvironmentSettings are generated from renderer/gpu_layout.rs.
 @group(0) @binding(0) var<uniform> globals: Globals;
 
-struct EnvironmentSettings {
-    flags_intensity: vec4<f32>,
-    ambient_color: vec4<f32>,
-};
 @group(1) @binding(8) var<uniform> environment_settings: EnvironmentSettings;
-@group(1) @binding(9) var environment_map: texture_2d<f32>;
+@group(1) @binding(9) var environment_map: texture_cube<f32>;
 @group(1) @binding(10) var environment_sampler: sampler;
 
 
@@ -43,28 +34,6 @@ fn environment_intensity() -> f32 {
     return environment_settings.flags_intensity.y;
 }
 
-fn direction_to_equirect(direction: vec3<f32>) -> vec2<f32> {
-    let dir = normalize(direction);
-    let theta = atan2(dir.z, dir.x);
-    let phi = acos(clamp(dir.y, -1.0, 1.0));
-    let u = fract(0.5 - theta / TWO_PI);
-    let v = clamp(phi / PI, 0.0, 1.0);
-    return vec2<f32>(u, v);
-}
-
-fn environment_uv(direction: vec3<f32>) -> vec2<f32> {
-    let base_uv = direction_to_equirect(direction);
-    let dims_u32 = textureDimensions(environment_map, 0);
-    let dims = vec2<f32>(f32(dims_u32.x), f32(dims_u32.y));
-    let safe_dims = max(dims, vec2<f32>(1.0, 1.0));
-    let inv_dims = vec2<f32>(1.0, 1.0) / safe_dims;
-    let texel = inv_dims * 0.5;
-    let shifted = base_uv + texel;
-    let wrapped_u = fract(shifted.x);
-    let clamped_v = clamp(shifted.y, texel.y, 1.0 - texel.y);
-    return vec2<f32>(wrapped_u, clamped_v);
-}
-
 @fragment
 fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     if (!environment_enabled()) {
@@ -77,8 +46,7 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     let world_pos = world.xyz / world.w;
     let dir = normalize(world_pos - globals.camera_pos);
 
-    let uv = environment_uv(dir);
-    let color = textureSampleLevel(environment_map, environment_sampler, uv, 0.0).rgb
+    let color = textureSampleLevel(environment_map, environment_sampler, dir, 0.0).rgb
         * environment_intensity();
 
     let mapped = color / (color + vec3<f32>(1.0));
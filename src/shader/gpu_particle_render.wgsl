@@ -5,6 +5,8 @@ struct Globals {
     inverse_view_proj: mat4x4<f32>,
     camera_pos: vec3<f32>,
     _padding: f32,
+    unjittered_view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
 };
 @group(0) @binding(0) var<uniform> globals: Globals;
 
@@ -32,9 +34,22 @@ struct MaterialData {
     material_flags: u32,
     metallic_factor: f32,
     roughness_factor: f32,
-    emissive_strength: f32,
-    _padding: u32,
-    _padding2: vec2<u32>,
+    emissive_color: vec3<f32>,
+    transmission_factor: f32,
+    transmission_texture: u32,
+    alpha_cutoff: f32,
+    clearcoat_factor: f32,
+    clearcoat_roughness: f32,
+    clearcoat_texture: u32,
+    clearcoat_roughness_texture: u32,
+    clearcoat_normal_texture: u32,
+    anisotropy_strength: f32,
+    anisotropy_rotation: f32,
+    anisotropy_texture: u32,
+    base_color_uv_rotation: f32,
+    normal_uv_rotation: f32,
+    base_color_uv_transform: vec4<f32>,
+    normal_uv_transform: vec4<f32>,
 };
 
 @group(1) @binding(1)
@@ -63,6 +78,7 @@ struct DirectionalLight {
 struct PointLight {
     position_range: vec4<f32>,
     color_intensity: vec4<f32>,
+    attenuation: vec4<f32>,
 };
 
 struct SpotLight {
@@ -82,7 +98,8 @@ struct Lights {
 @group(2) @binding(0) var<storage, read> lights: Lights;
 
 // Textures (bindless)
-@group(3) @binding(0) var textures: binding_array<texture_2d<f32>, 256>;
+const MAX_TEXTURES: u32 = {{MAX_TEXTURES}}u;
+@group(3) @binding(0) var textures: binding_array<texture_2d<f32>, {{MAX_TEXTURES}}>;
 @group(3) @binding(1) var tex_sampler_linear: sampler;
 @group(3) @binding(2) var tex_sampler_nearest: sampler;
 
@@ -195,7 +212,8 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     // Sample base color texture if enabled
     var base_color = particle_material.color;
     
-    if ((particle_material.material_flags & FLAG_USE_BASE_COLOR_TEXTURE) != 0u) {
+    if ((particle_material.material_flags & FLAG_USE_BASE_COLOR_TEXTURE) != 0u
+        && particle_material.base_color_texture < MAX_TEXTURES) {
         let tex_idx = particle_material.base_color_texture;
         let use_nearest = (particle_material.material_flags & FLAG_USE_NEAREST_SAMPLER) != 0u;
         
@@ -238,6 +256,10 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
         if (distance > 0.0001) {
             let L = to_light / distance;
             var attenuation = 1.0 / max(distance * distance, 0.0001);
+            if (light.attenuation.x > 0.5) {
+                attenuation = 1.0 / (1.0 + light.attenuation.y * distance
+                    + light.attenuation.z * distance * distance);
+            }
             let range = light.position_range.w;
             if (range > 0.0) {
                 let range_factor = clamp(1.0 - distance / range, 0.0, 1.0);
@@ -0,0 +1,188 @@
+// Image-based lighting bake: equirect-to-cube conversion, split-sum
+// prefiltering and the BRDF lookup table. constants.wgsl is prepended.
+
+struct IblParams {
+    face: u32,
+    roughness: f32,
+    sample_count: u32,
+    face_size: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: IblParams;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var source: texture_2d<f32>;
+
+struct VsOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VsOut {
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+
+    var out: VsOut;
+    out.position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    out.uv = vec2<f32>(x, y);
+    return out;
+}
+
+// World direction through `uv` of cube face `face` (+X, -X, +Y, -Y, +Z, -Z).
+fn cube_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    let a = uv.x * 2.0 - 1.0;
+    let b = uv.y * 2.0 - 1.0;
+    var dir: vec3<f32>;
+    switch face {
+        case 0u: { dir = vec3<f32>(1.0, -b, -a); }
+        case 1u: { dir = vec3<f32>(-1.0, -b, a); }
+        case 2u: { dir = vec3<f32>(a, 1.0, b); }
+        case 3u: { dir = vec3<f32>(a, -1.0, -b); }
+        case 4u: { dir = vec3<f32>(a, -b, 1.0); }
+        default: { dir = vec3<f32>(-a, -b, -1.0); }
+    }
+    return normalize(dir);
+}
+
+fn direction_to_equirect(direction: vec3<f32>) -> vec2<f32> {
+    let theta = atan2(direction.z, direction.x);
+    let phi = acos(clamp(direction.y, -1.0, 1.0));
+    return vec2<f32>(fract(0.5 - theta / TWO_PI), clamp(phi / PI, 0.0, 1.0));
+}
+
+fn sample_equirect(direction: vec3<f32>, lod: f32) -> vec3<f32> {
+    let max_lod = f32(textureNumLevels(source) - 1u);
+    let uv = direction_to_equirect(direction);
+    return textureSampleLevel(source, source_sampler, uv, clamp(lod, 0.0, max_lod)).rgb;
+}
+
+// Solid angle covered by one texel of the equirect source at mip 0.
+fn source_texel_solid_angle() -> f32 {
+    let dims = vec2<f32>(textureDimensions(source, 0));
+    return 4.0 * PI / max(dims.x * dims.y, 1.0);
+}
+
+fn hammersley(i: u32, count: u32) -> vec2<f32> {
+    let radical_inverse = f32(reverseBits(i)) * 2.3283064365386963e-10;
+    return vec2<f32>(f32(i) / f32(count), radical_inverse);
+}
+
+fn tangent_to_world(local: vec3<f32>, N: vec3<f32>) -> vec3<f32> {
+    let up = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 0.0, 1.0), abs(N.z) < 0.999);
+    let tangent = normalize(cross(up, N));
+    let bitangent = cross(N, tangent);
+    return normalize(tangent * local.x + bitangent * local.y + N * local.z);
+}
+
+fn importance_sample_ggx(xi: vec2<f32>, N: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let a = roughness * roughness;
+    let phi = TWO_PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    return tangent_to_world(vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta), N);
+}
+
+fn ggx_distribution(n_dot_h: f32, roughness: f32) -> f32 {
+    let a2 = roughness * roughness * roughness * roughness;
+    let denom = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / max(PI * denom * denom, 1e-6);
+}
+
+// Smith-Schlick geometry term with the IBL remapping k = a / 2.
+fn geometry_smith_ibl(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = roughness * roughness * 0.5;
+    let gv = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let gl = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return gv * gl;
+}
+
+@fragment
+fn fs_equirect_to_cube(in: VsOut) -> @location(0) vec4<f32> {
+    let dir = cube_direction(params.face, in.uv);
+    // Pick the source mip whose texels roughly match the face resolution.
+    let face_size = f32(params.face_size);
+    let source_width = f32(textureDimensions(source, 0).x);
+    let lod = max(log2(source_width / (4.0 * face_size)), 0.0);
+    return vec4<f32>(sample_equirect(dir, lod), 1.0);
+}
+
+@fragment
+fn fs_prefilter(in: VsOut) -> @location(0) vec4<f32> {
+    // Split-sum assumption: N = V = R.
+    let N = cube_direction(params.face, in.uv);
+    let texel_solid_angle = source_texel_solid_angle();
+
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < params.sample_count; i = i + 1u) {
+        let H = importance_sample_ggx(hammersley(i, params.sample_count), N, params.roughness);
+        let L = normalize(2.0 * dot(N, H) * H - N);
+        let n_dot_l = dot(N, L);
+        if (n_dot_l > 0.0) {
+            // Sample a blurrier mip for sparse samples to avoid fireflies.
+            let n_dot_h = max(dot(N, H), 0.0);
+            let pdf = ggx_distribution(n_dot_h, params.roughness) * 0.25 + 1e-4;
+            let sample_solid_angle = 1.0 / (f32(params.sample_count) * pdf);
+            let lod = 0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0;
+            color += sample_equirect(L, lod) * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+
+    return vec4<f32>(color / max(weight, 1e-4), 1.0);
+}
+
+@fragment
+fn fs_irradiance(in: VsOut) -> @location(0) vec4<f32> {
+    let N = cube_direction(params.face, in.uv);
+    let texel_solid_angle = source_texel_solid_angle();
+
+    // Cosine-weighted hemisphere samples: the average is irradiance / PI,
+    // which is what a Lambertian albedo multiplies.
+    var color = vec3<f32>(0.0);
+    for (var i = 0u; i < params.sample_count; i = i + 1u) {
+        let xi = hammersley(i, params.sample_count);
+        let phi = TWO_PI * xi.x;
+        let cos_theta = sqrt(1.0 - xi.y);
+        let sin_theta = sqrt(xi.y);
+        let L = tangent_to_world(vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta), N);
+
+        let pdf = max(cos_theta / PI, 1e-4);
+        let sample_solid_angle = 1.0 / (f32(params.sample_count) * pdf);
+        let lod = 0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0;
+        color += sample_equirect(L, lod);
+    }
+
+    return vec4<f32>(color / f32(params.sample_count), 1.0);
+}
+
+const BRDF_SAMPLES: u32 = 512u;
+
+// Scale (r) and bias (g) applied to F0 by the split-sum approximation,
+// indexed by (N.V, roughness).
+@fragment
+fn fs_brdf_lut(in: VsOut) -> @location(0) vec4<f32> {
+    let n_dot_v = max(in.uv.x, 1e-3);
+    let roughness = in.uv.y;
+    let V = vec3<f32>(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let N = vec3<f32>(0.0, 0.0, 1.0);
+
+    var scale = 0.0;
+    var bias = 0.0;
+    for (var i = 0u; i < BRDF_SAMPLES; i = i + 1u) {
+        let H = importance_sample_ggx(hammersley(i, BRDF_SAMPLES), N, roughness);
+        let L = normalize(2.0 * dot(V, H) * H - V);
+        let n_dot_l = max(L.z, 0.0);
+        let n_dot_h = max(H.z, 0.0);
+        let v_dot_h = max(dot(V, H), 0.0);
+        if (n_dot_l > 0.0) {
+            let g = geometry_smith_ibl(n_dot_v, n_dot_l, roughness);
+            let g_vis = g * v_dot_h / max(n_dot_h * n_dot_v, 1e-4);
+            let fc = pow(1.0 - v_dot_h, 5.0);
+            scale += (1.0 - fc) * g_vis;
+            bias += fc * g_vis;
+        }
+    }
+
+    return vec4<f32>(scale / f32(BRDF_SAMPLES), bias / f32(BRDF_SAMPLES), 0.0, 1.0);
+}
@@ -41,4 +41,48 @@ fn fresnel_schlick(cosTheta: f32, F0: vec3<f32>) -> vec3<f32> {
 
 fn fresnel_schlick_roughness(cosTheta: f32, F0: vec3<f32>, roughness: f32) -> vec3<f32> {
     return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
-}
\ No newline at end of file
+}
+
+// Kelemen visibility term, cheaper than Smith and adequate for the thin
+// clear-coat layer.
+fn visibility_kelemen(LdotH: f32) -> f32 {
+    return 0.25 / max(LdotH * LdotH, 1e-4);
+}
+
+// Clear-coat lobe (Filament, KHR_materials_clearcoat): a dielectric GGX lobe
+// with F0 = 0.04. Returns the coat's specular BRDF in x and its Fresnel
+// weight, which attenuates the base layer, in y.
+fn clearcoat_lobe(Nc: vec3<f32>, V: vec3<f32>, L: vec3<f32>, clearcoat: f32, roughness: f32) -> vec2<f32> {
+    let H = normalize(V + L);
+    let LdotH = clamp(dot(L, H), 0.0, 1.0);
+    let D = distribution_ggx(Nc, H, roughness);
+    let Vis = visibility_kelemen(LdotH);
+    let Fc = fresnel_schlick(LdotH, vec3<f32>(0.04)).x * clearcoat;
+    return vec2<f32>(D * Vis * Fc, Fc);
+}
+
+// Anisotropic GGX distribution (KHR_materials_anisotropy) with separate
+// alpha roughness along the anisotropy tangent (at) and bitangent (ab).
+fn distribution_ggx_anisotropic(NdotH: f32, TdotH: f32, BdotH: f32, at: f32, ab: f32) -> f32 {
+    let a2 = at * ab;
+    let f = vec3<f32>(ab * TdotH, at * BdotH, a2 * NdotH);
+    let w2 = a2 / max(dot(f, f), 1e-7);
+    return a2 * w2 * w2 / PI;
+}
+
+// Height-correlated Smith visibility for anisotropic GGX. Includes the
+// 1 / (4 N.L N.V) factor of the Cook-Torrance denominator.
+fn visibility_ggx_anisotropic(
+    NdotL: f32,
+    NdotV: f32,
+    TdotV: f32,
+    BdotV: f32,
+    TdotL: f32,
+    BdotL: f32,
+    at: f32,
+    ab: f32,
+) -> f32 {
+    let ggx_v = NdotL * length(vec3<f32>(at * TdotV, ab * BdotV, NdotV));
+    let ggx_l = NdotV * length(vec3<f32>(at * TdotL, ab * BdotL, NdotL));
+    return clamp(0.5 / max(ggx_v + ggx_l, 1e-5), 0.0, 1.0);
+}
@@ -26,7 +26,22 @@ struct PostUniform {
     intensity_power : vec2<f32>,
     noise_scale : vec2<f32>,
     near_far : vec2<f32>,
+    exposure : f32,
+    orthographic : f32,
     effects : vec4<f32>,
+    ssao_params : vec4<f32>,
+    dof_params : vec4<f32>,
+    // rgb = fog color, a = density.
+    fog_color_density : vec4<f32>,
+    // x = height falloff, y = 1 when fog is enabled, zw reserved.
+    fog_params : vec4<f32>,
+    // xyz = world up in view space, w = camera height in world space.
+    fog_frame : vec4<f32>,
+    // x = bloom intensity, y = dirt mask strength, z = emissive only,
+    // w reserved.
+    bloom_params : vec4<f32>,
+    // x = tone mapping operator (see apply_tonemapping), yzw reserved.
+    tonemap_params : vec4<f32>,
 };
 
 @group(0) @binding(0)
@@ -92,8 +107,18 @@ fn view_normal(uv : vec2<f32>, view_pos : vec3<f32>) -> vec3<f32> {
         pos_up = reconstruct_view_position(uv + vec2<f32>(0.0, texel.y), depth_up);
     }
 
-    var dx = pos_right - pos_left;
-    var dy = pos_up - pos_down;
+    // Difference against whichever neighbour lies closer in depth, so the
+    // normal of a pixel on a silhouette is not bent towards the background.
+    var dx = pos_right - view_pos;
+    let dx_back = view_pos - pos_left;
+    if (depth_right >= 1.0 || (depth_left < 1.0 && abs(dx_back.z) < abs(dx.z))) {
+        dx = dx_back;
+    }
+    var dy = pos_up - view_pos;
+    let dy_back = view_pos - pos_down;
+    if (depth_up >= 1.0 || (depth_down < 1.0 && abs(dy_back.z) < abs(dy.z))) {
+        dy = dy_back;
+    }
     let eps = 1e-5;
     if (dot(dx, dx) < eps) {
         dx = vec3<f32>(1.0, 0.0, 0.0);
@@ -114,41 +139,89 @@ fn view_normal(uv : vec2<f32>, view_pos : vec3<f32>) -> vec3<f32> {
     return normal;
 }
 
-fn ssao_kernel() -> array<vec3<f32>, 32> {
-    return array<vec3<f32>, 32>(
-        vec3<f32>(-0.0559, 0.0179, 0.0810),
-        vec3<f32>(-0.0735, 0.0456, 0.0520),
-        vec3<f32>(-0.0271, -0.0585, 0.0813),
-        vec3<f32>(-0.0401, -0.1000, 0.0119),
-        vec3<f32>(0.0736, 0.0220, 0.0855),
-        vec3<f32>(0.0526, 0.0083, 0.1113),
-        vec3<f32>(-0.0373, 0.0160, 0.1274),
-        vec3<f32>(0.0362, 0.1105, 0.0882),
-        vec3<f32>(0.0640, -0.1422, 0.0357),
-        vec3<f32>(-0.0765, -0.1526, 0.0423),
-        vec3<f32>(-0.1389, -0.0773, 0.1106),
-        vec3<f32>(-0.1343, -0.1290, 0.1041),
-        vec3<f32>(-0.0925, 0.1734, 0.1286),
-        vec3<f32>(0.0560, -0.1689, 0.1872),
-        vec3<f32>(-0.1564, -0.0560, 0.2298),
-        vec3<f32>(0.1163, 0.0473, 0.2842),
-        vec3<f32>(0.2561, 0.2062, 0.0856),
-        vec3<f32>(-0.3332, -0.1314, 0.0953),
-        vec3<f32>(-0.1698, 0.2602, 0.2574),
-        vec3<f32>(-0.2598, 0.2179, 0.2773),
-        vec3<f32>(0.4501, -0.0447, 0.1438),
-        vec3<f32>(-0.4453, 0.1078, 0.2309),
-        vec3<f32>(0.1033, 0.4858, 0.2439),
-        vec3<f32>(-0.2672, 0.4736, 0.2425),
-        vec3<f32>(-0.4269, -0.4726, 0.0572),
-        vec3<f32>(0.2285, 0.5237, 0.3785),
-        vec3<f32>(-0.4756, -0.1290, 0.5427),
-        vec3<f32>(0.0357, 0.5773, 0.5274),
-        vec3<f32>(-0.6415, 0.2899, 0.4476),
-        vec3<f32>(0.0824, -0.5200, 0.7146),
-        vec3<f32>(-0.8058, -0.1353, 0.4706),
-        vec3<f32>(0.7516, 0.6225, 0.2181)
-    );
+const MAX_SSAO_KERNEL_SIZE : u32 = 64u;
+const MAX_SSAO_HORIZON_DIRECTIONS : u32 = 16u;
+const MAX_SSAO_HORIZON_STEPS : u32 = 16u;
+const GOLDEN_ANGLE : f32 = 2.39996323;
+const TAU : f32 = 6.28318531;
+
+// Sample `i` of a `count`-sample hemisphere kernel (+Z up). Directions follow
+// a golden-angle spiral; lengths grow quadratically so that most samples stay
+// close to the shaded point.
+fn ssao_kernel_sample(i : u32, count : u32) -> vec3<f32> {
+    let fi = f32(i);
+    let t = (fi + 0.5) / f32(max(count, 1u));
+    let cos_theta = sqrt(1.0 - fract(fi * 0.618034 + 0.5));
+    let sin_theta = sqrt(max(1.0 - cos_theta * cos_theta, 0.0));
+    let phi = fi * GOLDEN_ANGLE;
+    let dir = vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, max(cos_theta, 0.05));
+    return normalize(dir) * mix(0.1, 1.0, t * t);
+}
+
+// Interleaved gradient noise; offsets the first step of every pixel so the
+// horizon search does not band.
+fn ssao_step_jitter(pixel : vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel, vec2<f32>(0.06711056, 0.00583715))));
+}
+
+// Horizon-based occlusion: marches `ssao_params.z` screen-space directions,
+// `ssao_params.w` steps each, out to `radius` world units and averages the
+// cosine between the normal and the highest horizon found per direction.
+// Samples fade out with distance so far-away occluders do not halo.
+fn horizon_occlusion(
+    uv : vec2<f32>,
+    pixel : vec2<f32>,
+    view_pos : vec3<f32>,
+    normal : vec3<f32>,
+    rotation : vec2<f32>,
+) -> f32 {
+    let directions = clamp(u32(post_uniform.ssao_params.z), 1u, MAX_SSAO_HORIZON_DIRECTIONS);
+    let steps = clamp(u32(post_uniform.ssao_params.w), 1u, MAX_SSAO_HORIZON_STEPS);
+    let radius = post_uniform.radius_bias.x;
+    let origin = view_pos + normal * post_uniform.radius_bias.y;
+
+    // Project the world-space radius onto the screen, in UV units.
+    var view_depth = max(-view_pos.z, 1e-4);
+    if (post_uniform.orthographic > 0.5) {
+        view_depth = 1.0;
+    }
+    let radius_uv = 0.5 * radius * vec2<f32>(post_uniform.proj[0][0], post_uniform.proj[1][1]) / view_depth;
+    let texel = 1.0 / post_uniform.resolution;
+    if (max(radius_uv.x * post_uniform.resolution.x, radius_uv.y * post_uniform.resolution.y) < 1.0) {
+        return 0.0;
+    }
+
+    let start_angle = atan2(rotation.y, rotation.x);
+    let jitter = ssao_step_jitter(pixel);
+    var occlusion = 0.0;
+    for (var d : u32 = 0u; d < directions; d = d + 1u) {
+        let angle = start_angle + TAU * f32(d) / f32(directions);
+        let direction = vec2<f32>(cos(angle), sin(angle)) * radius_uv;
+        var horizon = 0.0;
+        for (var i : u32 = 0u; i < steps; i = i + 1u) {
+            let t = (f32(i) + jitter) / f32(steps);
+            // Always step at least one texel away from the shaded pixel.
+            let offset = direction * t + sign(direction) * texel;
+            let sample_uv = uv + offset;
+            if (any(sample_uv < vec2<f32>(0.0)) || any(sample_uv > vec2<f32>(1.0))) {
+                break;
+            }
+            let sample_depth = fetch_depth(sample_uv);
+            if (sample_depth >= 1.0) {
+                continue;
+            }
+            let to_sample = reconstruct_view_position(sample_uv, sample_depth) - origin;
+            let distance_sq = dot(to_sample, to_sample);
+            if (distance_sq < 1e-8) {
+                continue;
+            }
+            let cos_horizon = dot(normal, to_sample) * inverseSqrt(distance_sq);
+            let falloff = clamp(1.0 - distance_sq / (radius * radius), 0.0, 1.0);
+            horizon = max(horizon, cos_horizon * falloff);
+        }
+        occlusion = occlusion + horizon;
+    }
+    return occlusion / f32(directions);
 }
 
 // @fragment
@@ -171,6 +244,11 @@ fn fs_ssao(in : VertexOutput) -> @location(0) vec4<f32> {
     let view_pos = reconstruct_view_position(in.uv, depth);
     let normal = view_normal(in.uv, view_pos);
     let noise_sample = textureSample(noise_texture, noise_sampler, in.uv * post_uniform.noise_scale);
+    if (post_uniform.ssao_params.z >= 1.0) {
+        let horizon = horizon_occlusion(in.uv, in.position.xy, view_pos, normal, noise_sample.xy);
+        return ssao_output(1.0 - horizon);
+    }
+
     var tangent = vec3<f32>(noise_sample.xy, 0.0);
     if (dot(tangent, tangent) < 1e-4) {
         tangent = vec3<f32>(1.0, 0.0, 0.0);
@@ -183,10 +261,10 @@ fn fs_ssao(in : VertexOutput) -> @location(0) vec4<f32> {
     let bias = post_uniform.radius_bias.y;
 
     var occlusion = 0.0;
-    let samples = ssao_kernel();
-    let sample_count = 32.0;
-    for (var i : u32 = 0u; i < 32u; i = i + 1u) {
-        let rotated = tbn * samples[i];
+    let kernel_size = clamp(u32(post_uniform.ssao_params.x), 1u, MAX_SSAO_KERNEL_SIZE);
+    let sample_count = f32(kernel_size);
+    for (var i : u32 = 0u; i < kernel_size; i = i + 1u) {
+        let rotated = tbn * ssao_kernel_sample(i, kernel_size);
         let sample_pos = view_pos + normal * bias + rotated * radius;
 
         let sample_clip = post_uniform.proj * vec4<f32>(sample_pos, 1.0);
@@ -211,13 +289,84 @@ fn fs_ssao(in : VertexOutput) -> @location(0) vec4<f32> {
             occlusion = occlusion + range_check;
         }
     }
-    let ao = 1.0 - occlusion / sample_count;
-    let ao_pow = pow(ao, max(post_uniform.intensity_power.y, 0.01));
+    return ssao_output(1.0 - occlusion / sample_count);
+}
+
+// Applies the SSAO power curve and intensity to raw ambient visibility.
+fn ssao_output(ao : f32) -> vec4<f32> {
+    let ao_pow = pow(clamp(ao, 0.0, 1.0), max(post_uniform.intensity_power.y, 0.01));
     let strength = clamp(post_uniform.intensity_power.x, 0.0, 1.0);
     let ao_result = mix(1.0, ao_pow, strength);
     return vec4<f32>(ao_result, ao_result, ao_result, 1.0);
 }
 
+// Separable bilateral blur that denoises the SSAO result. Gaussian weights
+// are scaled down by the relative view-depth difference to the centre
+// pixel, so occlusion does not bleed across silhouettes.
+const MAX_SSAO_BLUR_RADIUS : i32 = 8;
+const SSAO_BLUR_DEPTH_SHARPNESS : f32 = 32.0;
+
+@group(1) @binding(0)
+var ssao_blur_input : texture_2d<f32>;
+@group(1) @binding(1)
+var ssao_blur_sampler : sampler;
+@group(1) @binding(2)
+var ssao_blur_depth : texture_depth_2d;
+
+// View-space depth (positive distance) at `uv`; 0 for the background.
+fn ssao_blur_view_depth(uv : vec2<f32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(ssao_blur_depth, 0));
+    let coord = clamp(vec2<i32>(uv * vec2<f32>(size)), vec2<i32>(0), size - vec2<i32>(1));
+    let depth = textureLoad(ssao_blur_depth, coord, 0);
+    if (depth >= 1.0) {
+        return 0.0;
+    }
+    return -reconstruct_view_position(uv, depth).z;
+}
+
+fn ssao_blur(uv : vec2<f32>, direction : vec2<f32>) -> vec4<f32> {
+    let radius = clamp(i32(post_uniform.ssao_params.y), 0, MAX_SSAO_BLUR_RADIUS);
+    let texel_step = direction / vec2<f32>(textureDimensions(ssao_blur_input, 0));
+    let sigma = max(f32(radius) * 0.5, 0.5);
+    let center_depth = ssao_blur_view_depth(uv);
+    var result = 0.0;
+    var total = 0.0;
+    for (var i : i32 = -radius; i <= radius; i = i + 1) {
+        let offset = f32(i);
+        let sample_uv = clamp(uv + texel_step * offset, vec2<f32>(0.0), vec2<f32>(1.0));
+        let depth_delta = abs(ssao_blur_view_depth(sample_uv) - center_depth)
+            / max(center_depth, 1e-3);
+        let weight = exp(-(offset * offset) / (2.0 * sigma * sigma))
+            * exp(-depth_delta * SSAO_BLUR_DEPTH_SHARPNESS);
+        result = result + textureSampleLevel(ssao_blur_input, ssao_blur_sampler, sample_uv, 0.0).r * weight;
+        total = total + weight;
+    }
+    let ao = result / max(total, 1e-4);
+    return vec4<f32>(ao, ao, ao, 1.0);
+}
+
+@fragment
+fn fs_ssao_blur_horizontal(in : VertexOutput) -> @location(0) vec4<f32> {
+    return ssao_blur(in.uv, vec2<f32>(1.0, 0.0));
+}
+
+@fragment
+fn fs_ssao_blur_vertical(in : VertexOutput) -> @location(0) vec4<f32> {
+    return ssao_blur(in.uv, vec2<f32>(0.0, 1.0));
+}
+
+// Shared by the bloom prefilter and upsample passes
+struct BloomParams {
+    threshold : f32,
+    // Fraction of the threshold over which the soft knee ramps in.
+    knee : f32,
+    scatter : f32,
+    _padding : f32,
+};
+
+@group(1) @binding(0)
+var<uniform> bloom_params : BloomParams;
+
 // Bloom prefilter
 @group(0) @binding(0)
 var scene_texture : texture_2d<f32>;
@@ -228,8 +377,8 @@ var scene_sampler : sampler;
 fn fs_bloom_prefilter(in : VertexOutput) -> @location(0) vec4<f32> {
     let color = textureSample(scene_texture, scene_sampler, in.uv).rgb;
     let brightness = max(max(color.r, color.g), color.b);
-    let threshold = 0.8;
-    let knee = threshold * 0.5;
+    let threshold = bloom_params.threshold;
+    let knee = threshold * bloom_params.knee;
     let soft = brightness - threshold + knee;
     let clamped = clamp(soft, 0.0, 2.0 * knee);
     let soft_curve = clamped * clamped / (4.0 * max(knee, 1e-4) + 1e-5);
@@ -311,8 +460,281 @@ fn fs_bloom_upsample(in : VertexOutput) -> @location(0) vec4<f32> {
         0.0,
     )
         .rgb;
-    let scatter = 0.95;
-    return vec4<f32>(base + filtered * scatter, 1.0);
+    return vec4<f32>(base + filtered * bloom_params.scatter, 1.0);
+}
+
+// Temporal anti-aliasing resolve
+struct TaaUniform {
+    inv_view_proj : mat4x4<f32>,
+    prev_view_proj : mat4x4<f32>,
+    jitter : vec2<f32>,
+    blend_factor : f32,
+    history_valid : f32,
+};
+
+@group(0) @binding(0)
+var taa_current : texture_2d<f32>;
+@group(0) @binding(1)
+var taa_history : texture_2d<f32>;
+@group(0) @binding(2)
+var taa_depth : texture_depth_2d;
+@group(0) @binding(3)
+var taa_sampler : sampler;
+// Per-surface motion written by the main pass; zero where nothing was drawn.
+@group(0) @binding(4)
+var taa_velocity : texture_2d<f32>;
+
+@group(1) @binding(0)
+var<uniform> taa_uniform : TaaUniform;
+
+// Width of the neighbourhood color box in standard deviations.
+const TAA_VARIANCE_GAMMA : f32 = 1.0;
+
+// Screen UV of this pixel's surface in the previous frame.
+fn taa_reproject(uv : vec2<f32>, depth : f32) -> vec2<f32> {
+    // Remove this frame's jitter so static geometry maps back onto itself.
+    let ndc = vec2<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0) - taa_uniform.jitter;
+    let world = taa_uniform.inv_view_proj * vec4<f32>(ndc, depth, 1.0);
+    let prev_clip = taa_uniform.prev_view_proj * vec4<f32>(world.xyz / world.w, 1.0);
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    return vec2<f32>(prev_ndc.x * 0.5 + 0.5, 0.5 - prev_ndc.y * 0.5);
+}
+
+// Like taa_reproject, following the surface's own motion vector so moving
+// objects find their history too. Pixels without geometry have no motion
+// vector and reproject from depth alone.
+fn taa_reproject_surface(uv : vec2<f32>, depth : f32, coord : vec2<i32>) -> vec2<f32> {
+    if (depth >= 1.0) {
+        return taa_reproject(uv, depth);
+    }
+    let velocity_dims = vec2<i32>(textureDimensions(taa_velocity, 0));
+    let velocity = textureLoad(taa_velocity, clamp(coord, vec2<i32>(0), velocity_dims - vec2<i32>(1)), 0).xy;
+    let unjittered_uv = uv - taa_uniform.jitter * vec2<f32>(0.5, -0.5);
+    return unjittered_uv - velocity;
+}
+
+// Pulls `history` towards `center` until it lies inside the box.
+fn taa_clip_to_box(
+    history : vec3<f32>,
+    center : vec3<f32>,
+    box_min : vec3<f32>,
+    box_max : vec3<f32>,
+) -> vec3<f32> {
+    let extents = max((box_max - box_min) * 0.5, vec3<f32>(1e-4));
+    let offset = history - center;
+    let units = abs(offset / extents);
+    let max_unit = max(units.x, max(units.y, units.z));
+    if (max_unit > 1.0) {
+        return center + offset / max_unit;
+    }
+    return history;
+}
+
+@fragment
+fn fs_taa(in : VertexOutput) -> @location(0) vec4<f32> {
+    let dims = vec2<i32>(textureDimensions(taa_current, 0));
+    let coord = clamp(vec2<i32>(in.uv * vec2<f32>(dims)), vec2<i32>(0), dims - vec2<i32>(1));
+    let current = textureLoad(taa_current, coord, 0);
+    if (taa_uniform.history_valid < 0.5) {
+        return current;
+    }
+
+    // Neighbourhood mean and variance of the current frame.
+    var m1 = vec3<f32>(0.0);
+    var m2 = vec3<f32>(0.0);
+    for (var x : i32 = -1; x <= 1; x = x + 1) {
+        for (var y : i32 = -1; y <= 1; y = y + 1) {
+            let sample_coord = clamp(coord + vec2<i32>(x, y), vec2<i32>(0), dims - vec2<i32>(1));
+            let color = textureLoad(taa_current, sample_coord, 0).rgb;
+            m1 = m1 + color;
+            m2 = m2 + color * color;
+        }
+    }
+    let mean = m1 / 9.0;
+    let sigma = sqrt(max(m2 / 9.0 - mean * mean, vec3<f32>(0.0)));
+    let box_min = mean - sigma * TAA_VARIANCE_GAMMA;
+    let box_max = mean + sigma * TAA_VARIANCE_GAMMA;
+
+    let depth_dims = vec2<i32>(textureDimensions(taa_depth, 0));
+    let depth = textureLoad(taa_depth, clamp(coord, vec2<i32>(0), depth_dims - vec2<i32>(1)), 0);
+    let prev_uv = taa_reproject_surface(in.uv, depth, coord);
+    if (any(prev_uv < vec2<f32>(0.0)) || any(prev_uv > vec2<f32>(1.0))) {
+        return current;
+    }
+
+    let history = textureSampleLevel(taa_history, taa_sampler, prev_uv, 0.0).rgb;
+    let clipped = taa_clip_to_box(history, mean, box_min, box_max);
+    let blend = clamp(taa_uniform.blend_factor, 0.0, 1.0);
+    return vec4<f32>(mix(clipped, current.rgb, blend), current.a);
+}
+
+// Depth of field (separable hexagonal bokeh)
+@group(0) @binding(0)
+var dof_color : texture_2d<f32>;
+@group(0) @binding(1)
+var dof_depth : texture_depth_2d;
+@group(0) @binding(2)
+var dof_sampler : sampler;
+
+@group(1) @binding(0)
+var<uniform> dof_uniform : PostUniform;
+
+@group(0) @binding(0)
+var dof_vertical : texture_2d<f32>;
+@group(0) @binding(1)
+var dof_diagonal : texture_2d<f32>;
+
+const DOF_TAPS : i32 = 8;
+// Hexagon edge directions, 120 degrees apart.
+const DOF_DIR_UP : vec2<f32> = vec2<f32>(0.0, -1.0);
+const DOF_DIR_DOWN_LEFT : vec2<f32> = vec2<f32>(-0.8660254, 0.5);
+const DOF_DIR_DOWN_RIGHT : vec2<f32> = vec2<f32>(0.8660254, 0.5);
+
+// Circle of confusion radius in pixels for a [0, 1] depth buffer value.
+fn dof_circle_of_confusion(depth : f32) -> f32 {
+    let near = dof_uniform.near_far.x;
+    let far = dof_uniform.near_far.y;
+    let perspective_depth = near * far / max(far - depth * (far - near), 1e-5);
+    let linear_depth = near + depth * (far - near);
+    let view_depth = select(perspective_depth, linear_depth, dof_uniform.orthographic > 0.5);
+    let focus = dof_uniform.dof_params.x;
+    let aperture = dof_uniform.dof_params.y;
+    let max_radius = dof_uniform.dof_params.z;
+    let coc = aperture * abs(view_depth - focus) / max(view_depth, 1e-5);
+    return clamp(coc, 0.0, 1.0) * max_radius;
+}
+
+// One-sided blur of `source` along `dir`, reaching `radius` pixels. Samples
+// whose own CoC (alpha) is smaller than their distance are skipped so sharp
+// regions do not smear into their blurry neighbours.
+fn dof_blur(
+    source : texture_2d<f32>,
+    uv : vec2<f32>,
+    dir : vec2<f32>,
+    radius : f32,
+) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source, 0));
+    var sum = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i : i32 = 0; i < DOF_TAPS; i = i + 1) {
+        let t = (f32(i) + 0.5) / f32(DOF_TAPS);
+        let offset = dir * radius * t * texel;
+        let sample_uv = clamp(uv + offset, vec2<f32>(0.0), vec2<f32>(1.0));
+        let tap = textureSampleLevel(source, dof_sampler, sample_uv, 0.0);
+        let w = select(0.0, 1.0, tap.a >= radius * t || i == 0);
+        sum = sum + tap.rgb * w;
+        weight = weight + w;
+    }
+    return sum / max(weight, 1e-4);
+}
+
+struct DofBlurOutput {
+    @location(0) vertical : vec4<f32>,
+    @location(1) diagonal : vec4<f32>,
+};
+
+@fragment
+fn fs_dof(in : VertexOutput) -> DofBlurOutput {
+    let dims = vec2<i32>(textureDimensions(dof_depth, 0));
+    let coord = clamp(vec2<i32>(in.uv * vec2<f32>(dims)), vec2<i32>(0), dims - vec2<i32>(1));
+    let coc = dof_circle_of_confusion(textureLoad(dof_depth, coord, 0));
+
+    let texel = 1.0 / vec2<f32>(textureDimensions(dof_color, 0));
+    var vertical = vec3<f32>(0.0);
+    var diagonal = vec3<f32>(0.0);
+    for (var i : i32 = 0; i < DOF_TAPS; i = i + 1) {
+        let t = (f32(i) + 0.5) / f32(DOF_TAPS) * coc;
+        let up_uv = clamp(in.uv + DOF_DIR_UP * t * texel, vec2<f32>(0.0), vec2<f32>(1.0));
+        let diag_uv = clamp(in.uv + DOF_DIR_DOWN_LEFT * t * texel, vec2<f32>(0.0), vec2<f32>(1.0));
+        vertical = vertical + textureSampleLevel(dof_color, dof_sampler, up_uv, 0.0).rgb;
+        diagonal = diagonal + textureSampleLevel(dof_color, dof_sampler, diag_uv, 0.0).rgb;
+    }
+    vertical = vertical / f32(DOF_TAPS);
+    diagonal = diagonal / f32(DOF_TAPS);
+
+    var out : DofBlurOutput;
+    out.vertical = vec4<f32>(vertical, coc);
+    out.diagonal = vec4<f32>(vertical + diagonal, coc);
+    return out;
+}
+
+@fragment
+fn fs_dof_combine(in : VertexOutput) -> @location(0) vec4<f32> {
+    let coc = textureSampleLevel(dof_vertical, dof_sampler, in.uv, 0.0).a;
+    let rhombus_a = dof_blur(dof_vertical, in.uv, DOF_DIR_DOWN_LEFT, coc);
+    let rhombus_b = dof_blur(dof_diagonal, in.uv, DOF_DIR_DOWN_RIGHT, coc);
+    return vec4<f32>((rhombus_a + rhombus_b) / 3.0, 1.0);
+}
+
+// Height fog (ray-marched in-scattering)
+@group(0) @binding(0)
+var fog_depth : texture_depth_2d;
+
+@group(1) @binding(0)
+var<uniform> fog_uniform : PostUniform;
+
+const FOG_STEPS : i32 = 16;
+
+fn fog_view_position(uv : vec2<f32>, depth : f32) -> vec3<f32> {
+    let ndc = vec3<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth);
+    let view = fog_uniform.proj_inv * vec4<f32>(ndc, 1.0);
+    return view.xyz / view.w;
+}
+
+// Rayleigh phase normalised so its spherical average is 1. The fog is lit
+// from the zenith, so cos_theta is taken against world up.
+fn fog_rayleigh_phase(cos_theta : f32) -> f32 {
+    return 0.75 * (1.0 + cos_theta * cos_theta);
+}
+
+// Interleaved gradient noise, used to offset the first step per pixel.
+fn fog_dither(position : vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(position, vec2<f32>(0.06711056, 0.00583715))));
+}
+
+// rgb = light scattered towards the camera, a = transmittance to the
+// surface. The composite applies `scene * a + rgb`.
+@fragment
+fn fs_volumetric_fog(in : VertexOutput) -> @location(0) vec4<f32> {
+    let dims = vec2<i32>(textureDimensions(fog_depth, 0));
+    let coord = clamp(vec2<i32>(in.uv * vec2<f32>(dims)), vec2<i32>(0), dims - vec2<i32>(1));
+    let depth = textureLoad(fog_depth, coord, 0);
+
+    // March from the near plane to the surface, or to the far plane for sky.
+    let start = fog_view_position(in.uv, 0.0);
+    let end = fog_view_position(in.uv, depth);
+    let near = fog_uniform.near_far.x;
+    let far = fog_uniform.near_far.y;
+    let segment = end - start;
+    let length_to_surface = length(segment);
+    if (length_to_surface <= 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    let direction = segment / length_to_surface;
+    let march_length = min(length_to_surface, far - near);
+    let step_length = march_length / f32(FOG_STEPS);
+
+    let up = fog_uniform.fog_frame.xyz;
+    let camera_height = fog_uniform.fog_frame.w;
+    let density = fog_uniform.fog_color_density.a;
+    let falloff = fog_uniform.fog_params.x;
+    let phase = fog_rayleigh_phase(dot(direction, up));
+    let offset = fog_dither(in.position.xy);
+
+    var transmittance = 1.0;
+    var inscatter = 0.0;
+    for (var i : i32 = 0; i < FOG_STEPS; i = i + 1) {
+        let t = (f32(i) + offset) * step_length;
+        let height = camera_height + dot(start + direction * t, up);
+        // Clamped so fog far below the ground plane stays finite.
+        let local_density = density * exp(-falloff * clamp(height, -32.0, 1.0e4));
+        let step_transmittance = exp(-local_density * step_length);
+        inscatter = inscatter + transmittance * (1.0 - step_transmittance);
+        transmittance = transmittance * step_transmittance;
+    }
+
+    let color = fog_uniform.fog_color_density.rgb * phase * inscatter;
+    return vec4<f32>(color, transmittance);
 }
 
 @group(0) @binding(0)
@@ -323,6 +745,10 @@ var composite_ssao : texture_2d<f32>;
 var composite_bloom : texture_2d<f32>;
 @group(0) @binding(3)
 var composite_sampler : sampler;
+@group(0) @binding(4)
+var composite_fog : texture_2d<f32>;
+@group(0) @binding(5)
+var composite_bloom_dirt : texture_2d<f32>;
 
 @group(1) @binding(0)
 var<uniform> composite_uniform : PostUniform;
@@ -337,6 +763,87 @@ fn safe_texel_size() -> vec2<f32> {
     return vec2<f32>(1.0 / width, 1.0 / height);
 }
 
+// The main pass stores Reinhard-encoded colour in the scene target; undo
+// the curve to get back linear HDR.
+fn decode_scene_color(encoded : vec3<f32>) -> vec3<f32> {
+    return encoded / max(vec3<f32>(1.0) - encoded, vec3<f32>(1e-3));
+}
+
+fn tonemap_aces(x : vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn agx_default_contrast(x : vec3<f32>) -> vec3<f32> {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2
+        + 0.1191 * x - 0.00232;
+}
+
+fn tonemap_agx(color : vec3<f32>) -> vec3<f32> {
+    let inset = mat3x3<f32>(
+        vec3<f32>(0.842479062253094, 0.0423282422610123, 0.0423756549057051),
+        vec3<f32>(0.0784335999999992, 0.878468636469772, 0.0784336),
+        vec3<f32>(0.0792237451477643, 0.0791661274605434, 0.879142973793104),
+    );
+    let outset = mat3x3<f32>(
+        vec3<f32>(1.19687900512017, -0.0528968517574562, -0.0529716355144438),
+        vec3<f32>(-0.0980208811401368, 1.15190312990417, -0.0980434501171241),
+        vec3<f32>(-0.0990297440797205, -0.0989611768448433, 1.15107367264116),
+    );
+    let min_ev = -12.47393;
+    let max_ev = 4.026069;
+    var v = inset * color;
+    v = clamp(log2(max(v, vec3<f32>(1e-10))), vec3<f32>(min_ev), vec3<f32>(max_ev));
+    v = agx_default_contrast((v - min_ev) / (max_ev - min_ev));
+    // The curve targets a display encoding; linearize for the sRGB surface.
+    v = pow(max(outset * v, vec3<f32>(0.0)), vec3<f32>(2.2));
+    return clamp(v, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn uncharted2_curve(x : vec3<f32>) -> vec3<f32> {
+    let a = 0.15;
+    let b = 0.50;
+    let c = 0.10;
+    let d = 0.20;
+    let e = 0.02;
+    let f = 0.30;
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+fn tonemap_uncharted2(color : vec3<f32>) -> vec3<f32> {
+    let exposure_bias = 2.0;
+    let white = uncharted2_curve(vec3<f32>(11.2));
+    return clamp(uncharted2_curve(color * exposure_bias) / white, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Operator index comes from ToneMapping in postprocess/mod.rs.
+fn apply_tonemapping(color : vec3<f32>) -> vec3<f32> {
+    let op_index = u32(composite_uniform.tonemap_params.x + 0.5);
+    switch op_index {
+        case 1u: {
+            return color / (vec3<f32>(1.0) + color);
+        }
+        case 2u: {
+            return tonemap_aces(color);
+        }
+        case 3u: {
+            return tonemap_agx(color);
+        }
+        case 4u: {
+            return tonemap_uncharted2(color);
+        }
+        default: {
+            return clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+    }
+}
+
 fn sample_lit_color(uv : vec2<f32>) -> vec3<f32> {
     let uv_clamped = clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0));
     let base = textureSampleLevel(composite_scene, composite_sampler, uv_clamped, 0.0);
@@ -349,8 +856,19 @@ fn sample_lit_color(uv : vec2<f32>) -> vec3<f32> {
     var bloom = vec3<f32>(0.0);
     if bloom_enabled {
         bloom = textureSampleLevel(composite_bloom, composite_sampler, uv_clamped, 0.0).rgb;
+        let dirt = textureSampleLevel(composite_bloom_dirt, composite_sampler, uv_clamped, 0.0).rgb;
+        bloom = bloom * composite_uniform.bloom_params.x * (vec3<f32>(1.0) + dirt * composite_uniform.bloom_params.y);
+    }
+    var lit = decode_scene_color(base.rgb) * ssao;
+    if composite_uniform.fog_params.y > 0.5 {
+        let fog = textureSampleLevel(composite_fog, composite_sampler, uv_clamped, 0.0);
+        lit = lit * fog.a + fog.rgb;
+    }
+    // Emissive-only bloom glows on top of the tonemapped image.
+    if composite_uniform.bloom_params.z > 0.5 {
+        return apply_tonemapping(lit * composite_uniform.exposure) + bloom * composite_uniform.exposure;
     }
-    return base.rgb * ssao + bloom;
+    return apply_tonemapping((lit + bloom) * composite_uniform.exposure);
 }
 
 fn luminance(color : vec3<f32>) -> f32 {
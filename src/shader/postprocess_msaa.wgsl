@@ -26,7 +26,10 @@ struct PostUniform {
     intensity_power : vec2<f32>,
     noise_scale : vec2<f32>,
     near_far : vec2<f32>,
+    exposure : f32,
+    orthographic : f32,
     effects : vec4<f32>,
+    ssao_params : vec4<f32>,
 };
 
 @group(0) @binding(0)
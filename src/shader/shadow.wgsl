@@ -6,8 +6,13 @@ struct ShadowGlobals {
 struct Object {
     model: mat4x4<f32>,
     material_index: u32,
-    _padding: array<u32, 3>,
-    _padding2: array<u32, 4>,
+    joint_offset: u32,
+    morph_weight_offset: u32,
+    morph_target_count: u32,
+    morph_delta_offset: u32,
+    morph_vertex_count: u32,
+    _padding: array<u32, 2>,
+    prev_model: mat4x4<f32>,
 };
 @group(1) @binding(0) var<storage, read> objects: array<Object>;
 
@@ -21,9 +26,22 @@ struct MaterialData {
     material_flags: u32,
     metallic_factor: f32,
     roughness_factor: f32,
-    emissive_strength: f32,
-    _padding: u32,
-    _padding2: vec2<u32>,
+    emissive_color: vec3<f32>,
+    transmission_factor: f32,
+    transmission_texture: u32,
+    alpha_cutoff: f32,
+    clearcoat_factor: f32,
+    clearcoat_roughness: f32,
+    clearcoat_texture: u32,
+    clearcoat_roughness_texture: u32,
+    clearcoat_normal_texture: u32,
+    anisotropy_strength: f32,
+    anisotropy_rotation: f32,
+    anisotropy_texture: u32,
+    base_color_uv_rotation: f32,
+    normal_uv_rotation: f32,
+    base_color_uv_transform: vec4<f32>,
+    normal_uv_transform: vec4<f32>,
 };
 @group(1) @binding(1) var<storage, read> materials: array<MaterialData>;
 
@@ -33,11 +51,109 @@ struct VsIn {
     @location(2) uv: vec2<f32>,
     @location(3) tangent: vec4<f32>,
     @builtin(instance_index) instance: u32,
+    @builtin(vertex_index) vertex: u32,
+};
+
+@group(1) @binding(2) var<storage, read> joint_matrices: array<mat4x4<f32>>;
+@group(1) @binding(3) var<storage, read> morph_deltas: array<vec4<f32>>;
+@group(1) @binding(4) var<storage, read> morph_weights: array<f32>;
+
+fn morph_position(obj: Object, vertex: u32, pos: vec3<f32>) -> vec3<f32> {
+    var out = pos;
+    for (var t = 0u; t < obj.morph_target_count; t = t + 1u) {
+        let index = (obj.morph_delta_offset + t * obj.morph_vertex_count + vertex) * 2u;
+        out += morph_deltas[index].xyz * morph_weights[obj.morph_weight_offset + t];
+    }
+    return out;
+}
+
+struct SkinIn {
+    @location(4) joints: vec4<u32>,
+    @location(5) weights: vec4<f32>,
 };
 
 @vertex
 fn vs_main(in: VsIn) -> @builtin(position) vec4<f32> {
+    return rigid_clip_position(in);
+}
+
+@vertex
+fn vs_main_skinned(in: VsIn, skin: SkinIn) -> @builtin(position) vec4<f32> {
+    return skinned_clip_position(in, skin);
+}
+
+fn rigid_clip_position(in: VsIn) -> vec4<f32> {
+    let obj = objects[in.instance];
+    let world = obj.model * vec4<f32>(morph_position(obj, in.vertex, in.pos), 1.0);
+    return shadow_globals.view_proj * world;
+}
+
+fn skinned_clip_position(in: VsIn, skin: SkinIn) -> vec4<f32> {
     let obj = objects[in.instance];
-    let world = obj.model * vec4<f32>(in.pos, 1.0);
+    let base = obj.joint_offset;
+    let skin_matrix = joint_matrices[base + skin.joints.x] * skin.weights.x
+        + joint_matrices[base + skin.joints.y] * skin.weights.y
+        + joint_matrices[base + skin.joints.z] * skin.weights.z
+        + joint_matrices[base + skin.joints.w] * skin.weights.w;
+    let world = obj.model * skin_matrix * vec4<f32>(morph_position(obj, in.vertex, in.pos), 1.0);
     return shadow_globals.view_proj * world;
 }
+
+// Alpha-masked variants. The texture sampling helpers come from the bindless
+// or traditional bindings prepended to this file.
+const FLAG_USE_BASE_COLOR_TEXTURE: u32 = 1u;
+const FLAG_USE_NEAREST_SAMPLER: u32 = 256u;
+const FLAG_USE_CLAMP_SAMPLER: u32 = 524288u;
+const FLAG_ALPHA_MASK: u32 = 2048u;
+const FLAG_USE_BASE_COLOR_TRANSFORM: u32 = 1048576u;
+
+// Mirrors `transform_uv` in common.wgsl.
+fn transform_uv(uv: vec2<f32>, offset_scale: vec4<f32>, rotation: f32) -> vec2<f32> {
+    let c = cos(rotation);
+    let s = sin(rotation);
+    let scaled = uv * offset_scale.zw;
+    return vec2<f32>(c * scaled.x + s * scaled.y, c * scaled.y - s * scaled.x) + offset_scale.xy;
+}
+
+struct MaskedVsOut {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) material_index: u32,
+};
+
+@vertex
+fn vs_masked(in: VsIn) -> MaskedVsOut {
+    var out: MaskedVsOut;
+    out.pos = rigid_clip_position(in);
+    out.uv = in.uv;
+    out.material_index = objects[in.instance].material_index;
+    return out;
+}
+
+@vertex
+fn vs_masked_skinned(in: VsIn, skin: SkinIn) -> MaskedVsOut {
+    var out: MaskedVsOut;
+    out.pos = skinned_clip_position(in, skin);
+    out.uv = in.uv;
+    out.material_index = objects[in.instance].material_index;
+    return out;
+}
+
+@fragment
+fn fs_alpha_mask(in: MaskedVsOut) {
+    let material = materials[in.material_index];
+    let sampler_mode = texture_sampler_mode(material.material_flags);
+    let uv = select(
+        in.uv,
+        transform_uv(in.uv, material.base_color_uv_transform, material.base_color_uv_rotation),
+        (material.material_flags & FLAG_USE_BASE_COLOR_TRANSFORM) != 0u,
+    );
+    let texel = sample_base_color_texture(material.base_color_texture, uv, sampler_mode);
+    var alpha = material.color.a;
+    if ((material.material_flags & FLAG_USE_BASE_COLOR_TEXTURE) != 0u) {
+        alpha *= texel.a;
+    }
+    if ((material.material_flags & FLAG_ALPHA_MASK) != 0u && alpha < material.alpha_cutoff) {
+        discard;
+    }
+}
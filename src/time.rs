@@ -3,3 +3,108 @@ pub use std::time::Instant;
 
 #[cfg(target_arch = "wasm32")]
 pub use instant::Instant;
+
+/// Turns variable frame times into a whole number of fixed-length ticks.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    step: f64,
+    accumulator: f64,
+    max_ticks: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_RATE)
+    }
+}
+
+impl FixedTimestep {
+    /// Ticks per second when no rate is configured.
+    pub const DEFAULT_RATE: f64 = 60.0;
+    /// Ticks run for a single frame at most; time beyond that is dropped so
+    /// a long hitch does not keep the app catching up.
+    pub const DEFAULT_MAX_TICKS: u32 = 8;
+
+    pub fn new(rate_hz: f64) -> Self {
+        let rate_hz = if rate_hz.is_finite() && rate_hz > 0.0 {
+            rate_hz
+        } else {
+            log::warn!(
+                "Invalid fixed tick rate {}, using {}",
+                rate_hz,
+                Self::DEFAULT_RATE
+            );
+            Self::DEFAULT_RATE
+        };
+        Self {
+            step: 1.0 / rate_hz,
+            accumulator: 0.0,
+            max_ticks: Self::DEFAULT_MAX_TICKS,
+        }
+    }
+
+    pub fn with_max_ticks(mut self, max_ticks: u32) -> Self {
+        self.max_ticks = max_ticks.max(1);
+        self
+    }
+
+    /// Seconds per tick.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    pub fn rate(&self) -> f64 {
+        1.0 / self.step
+    }
+
+    /// Adds a frame's time and returns how many ticks to run for it.
+    pub fn advance(&mut self, dt: f64) -> u32 {
+        self.accumulator += dt.max(0.0);
+        let ticks = (self.accumulator / self.step).floor();
+        if ticks > self.max_ticks as f64 {
+            self.accumulator = 0.0;
+            return self.max_ticks;
+        }
+        self.accumulator -= ticks * self.step;
+        ticks as u32
+    }
+
+    /// How far the time left over after the last tick reaches into the
+    /// next one, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedTimestep;
+
+    #[test]
+    fn ticks_follow_accumulated_frame_time() {
+        let mut fixed = FixedTimestep::new(60.0);
+        let step = fixed.step();
+
+        // A 144 Hz display runs a tick every second or third frame.
+        let ticks: Vec<u32> = (0..13).map(|_| fixed.advance(1.0 / 144.0)).collect();
+        assert_eq!(ticks.iter().sum::<u32>(), 5);
+        assert!(ticks.iter().all(|&t| t <= 1));
+
+        // A 30 Hz frame runs two.
+        let mut fixed = FixedTimestep::new(60.0);
+        assert_eq!(fixed.advance(2.0 * step), 2);
+        assert!(fixed.alpha() < 1e-6);
+
+        // Half a tick is left over for interpolation.
+        assert_eq!(fixed.advance(1.5 * step), 1);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hitches_are_capped() {
+        let mut fixed = FixedTimestep::new(60.0).with_max_ticks(4);
+        assert_eq!(fixed.advance(1.0), 4);
+        assert_eq!(fixed.alpha(), 0.0);
+        assert_eq!(fixed.advance(fixed.step()), 1);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(feature = "egui")]
+use crate::renderer::RenderMode;
+#[cfg(feature = "egui")]
+use egui::{ComboBox, Context, Window};
+#[cfg(feature = "egui")]
+use std::sync::{Arc, Mutex};
+
+/// Requested render mode; the app writes back the mode the renderer applied.
+#[cfg(feature = "egui")]
+pub type RenderModeHandle = Arc<Mutex<RenderMode>>;
+
+#[cfg(feature = "egui")]
+pub struct DebugViewWindow {
+    handle: RenderModeHandle,
+    title: String,
+}
+
+#[cfg(feature = "egui")]
+impl DebugViewWindow {
+    pub fn new(handle: RenderModeHandle) -> Self {
+        Self {
+            handle,
+            title: "Debug view".to_string(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, open: Option<&mut bool>) {
+        let mut mode = self
+            .handle
+            .lock()
+            .map(|guard| *guard)
+            .unwrap_or_else(|poisoned| *poisoned.into_inner());
+
+        let mut changed = false;
+
+        let mut window = Window::new(&self.title);
+        if let Some(open) = open {
+            window = window.open(open);
+        }
+
+        window.resizable(false).show(ctx, |ui| {
+            ComboBox::from_label("Render mode")
+                .selected_text(mode.label())
+                .show_ui(ui, |ui| {
+                    for option in RenderMode::ALL {
+                        changed |= ui
+                            .selectable_value(&mut mode, option, option.label())
+                            .changed();
+                    }
+                });
+        });
+
+        if changed {
+            if let Ok(mut guard) = self.handle.lock() {
+                *guard = mode;
+            }
+        }
+    }
+
+    pub fn handle() -> RenderModeHandle {
+        Arc::new(Mutex::new(RenderMode::default()))
+    }
+}
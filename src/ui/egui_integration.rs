@@ -4,8 +4,14 @@ use egui_wgpu::ScreenDescriptor;
 use winit::event::WindowEvent;
 use winit::window::Window;
 
+use crate::settings::RenderSettings;
+
 pub use egui;
 
+/// egui renders into an sRGB texture so its blending and color handling do
+/// not depend on the surface format; the composite pass encodes for it.
+const OVERLAY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
 pub type EguiUiCallback = Box<dyn FnMut(&egui::Context) + 'static>;
 
 pub struct EguiContext {
@@ -13,17 +19,130 @@ pub struct EguiContext {
     state: egui_winit::State,
     pub renderer: egui_wgpu::Renderer,
     ui_callback: Option<EguiUiCallback>,
+    composite: OverlayComposite,
+    overlay: Option<Overlay>,
 }
 
+/// Where [`EguiContext::render`] draws. The UI is rendered into the context's
+/// offscreen overlay and then alpha-blended over `surface_view`.
 pub struct EguiRenderTarget<'a> {
     pub device: &'a wgpu::Device,
     pub queue: &'a wgpu::Queue,
     pub encoder: &'a mut wgpu::CommandEncoder,
     pub window: &'a Window,
-    pub view: &'a wgpu::TextureView,
+    pub surface_view: &'a wgpu::TextureView,
     pub surface_size: [u32; 2],
 }
 
+/// Offscreen texture sized to the surface.
+struct Overlay {
+    size: [u32; 2],
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Pipeline blending the overlay over the surface with premultiplied alpha.
+struct OverlayComposite {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl OverlayComposite {
+    fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("egui_overlay_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("egui_overlay.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("egui_overlay_bgl"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_overlay_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("egui_overlay_pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some(if surface_format.is_srgb() {
+                    "fs_linear"
+                } else {
+                    "fs_gamma"
+                }),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+
+    fn create_overlay(&self, device: &wgpu::Device, size: [u32; 2]) -> Overlay {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("egui_overlay"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OVERLAY_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("egui_overlay_bg"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+
+        Overlay {
+            size,
+            view,
+            bind_group,
+        }
+    }
+}
+
 impl EguiContext {
     pub fn new(
         device: &wgpu::Device,
@@ -47,12 +166,11 @@ impl EguiContext {
         // egui-wgpu 0.33
         let renderer = egui_wgpu::Renderer::new(
             device,
-            output_format,
+            OVERLAY_FORMAT,
             egui_wgpu::RendererOptions {
                 depth_stencil_format: None,
-                // egui overlays resolve directly into the surface, which is always single-sampled.
-                // Using the scene's MSAA sample count here would make the pipeline incompatible
-                // with the surface view when MSAA > 1, triggering validation errors.
+                // The overlay texture is single-sampled regardless of the scene's MSAA
+                // sample count.
                 msaa_samples: 1,
                 dithering: true,
                 predictable_texture_filtering: false,
@@ -64,9 +182,43 @@ impl EguiContext {
             state,
             renderer,
             ui_callback: None,
+            composite: OverlayComposite::new(device, output_format),
+            overlay: None,
         }
     }
 
+    pub fn with_ui_scale(mut self, scale: f32) -> Self {
+        self.set_ui_scale(scale);
+        self
+    }
+
+    /// Scales the UI on top of the window's DPI scale factor. Clamped like
+    /// [`RenderSettings::ui_scale`].
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.ctx
+            .set_zoom_factor(RenderSettings::sanitize_ui_scale(scale));
+    }
+
+    pub fn ui_scale(&self) -> f32 {
+        self.ctx.zoom_factor()
+    }
+
+    /// Recreates the overlay texture for a new surface size. [`render`](Self::render)
+    /// also does this lazily when the sizes disagree.
+    pub fn resize(&mut self, device: &wgpu::Device, surface_size: [u32; 2]) {
+        if surface_size[0] == 0 || surface_size[1] == 0 {
+            return;
+        }
+        if self
+            .overlay
+            .as_ref()
+            .is_some_and(|overlay| overlay.size == surface_size)
+        {
+            return;
+        }
+        self.overlay = Some(self.composite.create_overlay(device, surface_size));
+    }
+
     pub fn set_ui<F>(&mut self, callback: F)
     where
         F: FnMut(&egui::Context) + 'static,
@@ -100,9 +252,15 @@ impl EguiContext {
             return;
         }
 
+        self.resize(target.device, target.surface_size);
+        let Some(overlay) = &self.overlay else {
+            return;
+        };
+
+        // Includes the UI scale on top of the window scale factor.
         let screen_descriptor = ScreenDescriptor {
             size_in_pixels: target.surface_size,
-            pixels_per_point: target.window.scale_factor() as f32,
+            pixels_per_point: output.pixels_per_point,
         };
 
         // Upload textures
@@ -123,17 +281,17 @@ impl EguiContext {
             &screen_descriptor,
         );
 
-        // Begin render pass that LOADs the swapchain view
+        // Render the UI into the cleared overlay
         let pass = target
             .encoder
             .begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("egui_render_pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: target.view,
+                    view: &overlay.view,
                     depth_slice: None,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -149,7 +307,32 @@ impl EguiContext {
         self.renderer
             .render(&mut pass_static, &primitives, &screen_descriptor);
 
-        // No `drop(pass)` here — it was moved by `forget_lifetime()` and will end when `pass_static` drops.
+        // End the pass before compositing
+        drop(pass_static);
+
+        // Blend the overlay over the swapchain view
+        {
+            let mut pass = target
+                .encoder
+                .begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("egui_composite_pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: target.surface_view,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+            pass.set_pipeline(&self.composite.pipeline);
+            pass.set_bind_group(0, &overlay.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
 
         // Free any textures egui wants to drop
         for id in &output.textures_delta.free {
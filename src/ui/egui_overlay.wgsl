@@ -0,0 +1,39 @@
+// ui/egui_overlay.wgsl - Blends the offscreen egui overlay over the surface
+
+@group(0) @binding(0) var overlay: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Fullscreen triangle
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    return vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+}
+
+// The overlay is sRGB, so loads return premultiplied linear color.
+fn load_overlay(position: vec4<f32>) -> vec4<f32> {
+    return textureLoad(overlay, vec2<i32>(position.xy), 0);
+}
+
+fn linear_to_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+// sRGB surfaces encode on write.
+@fragment
+fn fs_linear(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return load_overlay(position);
+}
+
+// Non-sRGB surfaces store encoded values, so encode here.
+@fragment
+fn fs_gamma(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = load_overlay(position);
+    if (color.a <= 0.0) {
+        return vec4<f32>(0.0);
+    }
+    let straight = clamp(color.rgb / color.a, vec3<f32>(0.0), vec3<f32>(1.0));
+    return vec4<f32>(linear_to_srgb(straight) * color.a, color.a);
+}
@@ -5,7 +5,7 @@ use egui::{ScrollArea, Ui};
 #[cfg(feature = "egui")]
 use log::{Level, LevelFilter, Log, Metadata, Record};
 #[cfg(feature = "egui")]
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::VecDeque;
 #[cfg(feature = "egui")]
 use std::sync::{Arc, Mutex, OnceLock};
 #[cfg(feature = "egui")]
@@ -19,7 +19,7 @@ use env_logger::Builder as EnvBuilder;
 use env_logger::Logger as EnvLogger;
 
 #[cfg(feature = "egui")]
-const MAX_LOG_ENTRIES: usize = 1_000;
+const MAX_LOG_ENTRIES: usize = 4_096;
 
 #[cfg(feature = "egui")]
 static LOG_HANDLE: OnceLock<LogBufferHandle> = OnceLock::new();
@@ -42,6 +42,8 @@ pub struct LogEntry {
     pub timestamp: SystemTime,
     pub level: Level,
     pub target: String,
+    /// Module the record was logged from, when the macro captured it.
+    pub module_path: Option<String>,
     pub message: String,
 }
 
@@ -52,6 +54,7 @@ impl LogEntry {
             timestamp: SystemTime::now(),
             level: record.level(),
             target: record.target().to_string(),
+            module_path: record.module_path().map(str::to_string),
             message: record.args().to_string(),
         }
     }
@@ -249,11 +252,9 @@ fn render_entry(ui: &mut Ui, entry: &LogEntry) {
         );
         ui.add_space(6.0);
 
-        // Target - can truncate if too long
-        ui.add(
-            Label::new(RichText::new(entry.target.as_str()).monospace())
-                .wrap_mode(TextWrapMode::Truncate),
-        );
+        // Module path (or target) - can truncate if too long
+        let source = entry.module_path.as_deref().unwrap_or(&entry.target);
+        ui.add(Label::new(RichText::new(source).monospace()).wrap_mode(TextWrapMode::Truncate));
         ui.add_space(12.0);
 
         // Message - this should wrap
@@ -266,7 +267,8 @@ fn render_entry(ui: &mut Ui, entry: &LogEntry) {
 pub struct LogWindow {
     handle: LogBufferHandle,
     title: String,
-    enabled_levels: BTreeSet<Level>,
+    /// Least severe level shown.
+    max_level: Level,
     auto_scroll: bool,
 }
 
@@ -274,11 +276,10 @@ pub struct LogWindow {
 impl LogWindow {
     /// Create a new window that renders log output from the global recorder.
     pub fn new(handle: LogBufferHandle) -> Self {
-        let enabled_levels = LOG_LEVELS.iter().copied().collect();
         Self {
             handle,
             title: "Logs".to_string(),
-            enabled_levels,
+            max_level: Level::Trace,
             auto_scroll: true,
         }
     }
@@ -303,7 +304,7 @@ impl LogWindow {
             ui.separator();
             let filtered: Vec<_> = entries
                 .iter()
-                .filter(|entry| self.enabled_levels.contains(&entry.level))
+                .filter(|entry| entry.level <= self.max_level)
                 .collect();
             ScrollArea::vertical()
                 .stick_to_bottom(self.auto_scroll)
@@ -325,16 +326,17 @@ impl LogWindow {
 
     fn level_controls(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            for level in LOG_LEVELS {
-                let mut enabled = self.enabled_levels.contains(&level);
-                if ui.checkbox(&mut enabled, level.as_str()).changed() {
-                    if enabled {
-                        self.enabled_levels.insert(level);
-                    } else {
-                        self.enabled_levels.remove(&level);
+            ui.label("Severity");
+            egui::ComboBox::from_id_salt("log_severity")
+                .selected_text(
+                    RichText::new(self.max_level.as_str()).color(level_color(self.max_level)),
+                )
+                .show_ui(ui, |ui| {
+                    for level in LOG_LEVELS {
+                        let text = RichText::new(level.as_str()).color(level_color(level));
+                        ui.selectable_value(&mut self.max_level, level, text);
                     }
-                }
-            }
+                });
             let mut auto_scroll = self.auto_scroll;
             if ui.checkbox(&mut auto_scroll, "Auto-scroll").changed() {
                 self.auto_scroll = auto_scroll;
@@ -16,6 +16,12 @@ mod log_viewer;
 #[cfg(feature = "egui")]
 mod postprocess_window;
 
+#[cfg(feature = "egui")]
+mod shadow_window;
+
+#[cfg(feature = "egui")]
+mod debug_view_window;
+
 #[cfg(feature = "egui")]
 pub use stats_window::{FrameSample, FrameStatsHandle, FrameStatsHistory, StatsWindow};
 
@@ -23,4 +29,10 @@ pub use stats_window::{FrameSample, FrameStatsHandle, FrameStatsHistory, StatsWi
 pub use log_viewer::{init_log_recorder, LogBufferHandle, LogEntry, LogWindow};
 
 #[cfg(feature = "egui")]
-pub use postprocess_window::{PostProcessEffectsHandle, PostProcessWindow};
+pub use postprocess_window::{PostProcessEffectsHandle, PostProcessWindow, SampleCountHandle};
+
+#[cfg(feature = "egui")]
+pub use shadow_window::{ShadowSettingsHandle, ShadowWindow};
+
+#[cfg(feature = "egui")]
+pub use debug_view_window::{DebugViewWindow, RenderModeHandle};
@@ -1,16 +1,27 @@
 #[cfg(feature = "egui")]
-use crate::renderer::postprocess::PostProcessEffects;
+use crate::renderer::postprocess::{
+    PostProcessEffects, SsaoQuality, ToneMapping, MAX_BLOOM_MIP_COUNT, MAX_EXPOSURE_COMPENSATION,
+    MAX_SSAO_BLUR_RADIUS, MAX_SSAO_KERNEL_SIZE, MAX_SSAO_NOISE_SIZE, MIN_SSAO_NOISE_SIZE,
+};
 #[cfg(feature = "egui")]
-use egui::{Context, Window};
+use crate::settings::SUPPORTED_SAMPLE_COUNTS;
+#[cfg(feature = "egui")]
+use egui::{ComboBox, Context, DragValue, Slider, Ui, Window};
 #[cfg(feature = "egui")]
 use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "egui")]
 pub type PostProcessEffectsHandle = Arc<Mutex<PostProcessEffects>>;
 
+/// Requested MSAA sample count. The app writes back the count the renderer
+/// actually applied, so unsupported choices snap to the nearest valid one.
+#[cfg(feature = "egui")]
+pub type SampleCountHandle = Arc<Mutex<u32>>;
+
 #[cfg(feature = "egui")]
 pub struct PostProcessWindow {
     handle: PostProcessEffectsHandle,
+    sample_count: Option<SampleCountHandle>,
     title: String,
 }
 
@@ -19,10 +30,17 @@ impl PostProcessWindow {
     pub fn new(handle: PostProcessEffectsHandle) -> Self {
         Self {
             handle,
+            sample_count: None,
             title: "Post-processing".to_string(),
         }
     }
 
+    /// Adds an MSAA dropdown that writes into `handle`.
+    pub fn with_sample_count(mut self, handle: SampleCountHandle) -> Self {
+        self.sample_count = Some(handle);
+        self
+    }
+
     pub fn show(&mut self, ctx: &Context, open: Option<&mut bool>) {
         let mut effects = self
             .handle
@@ -41,12 +59,157 @@ impl PostProcessWindow {
             ui.heading("Post-processing effects");
             ui.separator();
 
+            if let Some(handle) = &self.sample_count {
+                sample_count_controls(ui, handle);
+                ui.separator();
+            }
+
             ui.vertical(|ui| {
                 changed |= ui
                     .checkbox(&mut effects.ssao, "Screen-space ambient occlusion")
                     .changed();
+                ui.add_enabled_ui(effects.ssao, |ui| {
+                    ComboBox::from_label("SSAO quality")
+                        .selected_text(effects.ssao_quality.label())
+                        .show_ui(ui, |ui| {
+                            for quality in SsaoQuality::ALL {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut effects.ssao_quality,
+                                        quality,
+                                        quality.label(),
+                                    )
+                                    .changed();
+                            }
+                        });
+                    changed |= ui
+                        .add(Slider::new(&mut effects.ssao_radius, 0.01..=5.0).text("SSAO radius"))
+                        .changed();
+                    changed |= ui
+                        .add(Slider::new(&mut effects.ssao_bias, 0.0..=0.5).text("SSAO bias"))
+                        .changed();
+                    changed |= ui
+                        .add(
+                            Slider::new(&mut effects.ssao_intensity, 0.0..=1.0)
+                                .text("SSAO intensity"),
+                        )
+                        .changed();
+                    ui.add_enabled_ui(effects.ssao_quality == SsaoQuality::Classic, |ui| {
+                        changed |= ui
+                            .add(
+                                Slider::new(
+                                    &mut effects.ssao_kernel_size,
+                                    1..=MAX_SSAO_KERNEL_SIZE,
+                                )
+                                .text("SSAO samples"),
+                            )
+                            .changed();
+                    });
+                    changed |= ui
+                        .add(
+                            Slider::new(&mut effects.ssao_blur_radius, 0..=MAX_SSAO_BLUR_RADIUS)
+                                .text("SSAO blur radius"),
+                        )
+                        .changed();
+                    ComboBox::from_label("SSAO noise size")
+                        .selected_text(format!("{0} x {0}", effects.ssao_noise_size))
+                        .show_ui(ui, |ui| {
+                            let mut size = MIN_SSAO_NOISE_SIZE;
+                            while size <= MAX_SSAO_NOISE_SIZE {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut effects.ssao_noise_size,
+                                        size,
+                                        format!("{0} x {0}", size),
+                                    )
+                                    .changed();
+                                size *= 2;
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label("SSAO noise seed");
+                        changed |= ui
+                            .add(DragValue::new(&mut effects.ssao_noise_seed))
+                            .changed();
+                    });
+                });
                 changed |= ui.checkbox(&mut effects.bloom, "Bloom").changed();
-                changed |= ui.checkbox(&mut effects.fxaa, "FXAA").changed();
+                ui.add_enabled_ui(effects.bloom, |ui| {
+                    changed |= ui
+                        .checkbox(&mut effects.bloom_emissive_only, "Bloom emissive only")
+                        .changed();
+                    let bloom = &mut effects.bloom_settings;
+                    changed |= ui
+                        .add(Slider::new(&mut bloom.threshold, 0.0..=4.0).text("Bloom threshold"))
+                        .changed();
+                    changed |= ui
+                        .add(Slider::new(&mut bloom.knee, 0.0..=1.0).text("Bloom knee"))
+                        .changed();
+                    changed |= ui
+                        .add(Slider::new(&mut bloom.scatter, 0.0..=1.0).text("Bloom scatter"))
+                        .changed();
+                    changed |= ui
+                        .add(Slider::new(&mut bloom.intensity, 0.0..=4.0).text("Bloom intensity"))
+                        .changed();
+                    changed |= ui
+                        .add(
+                            Slider::new(&mut bloom.mip_count, 1..=MAX_BLOOM_MIP_COUNT)
+                                .text("Bloom mips"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(Slider::new(&mut bloom.dirt_strength, 0.0..=4.0).text("Lens dirt"))
+                        .changed();
+                });
+                changed |= ui.checkbox(&mut effects.dof, "Depth of field").changed();
+                ui.add_enabled_ui(effects.dof, |ui| {
+                    changed |= ui
+                        .add(
+                            Slider::new(&mut effects.dof_focus_distance, 0.1..=100.0)
+                                .logarithmic(true)
+                                .text("Focus distance"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(Slider::new(&mut effects.dof_aperture, 0.0..=2.0).text("Aperture"))
+                        .changed();
+                });
+                // Both smooth the same edges; TAA replaces FXAA rather than
+                // stacking another blur on top.
+                if ui.checkbox(&mut effects.fxaa, "FXAA").changed() {
+                    effects.taa &= !effects.fxaa;
+                    changed = true;
+                }
+                if ui
+                    .checkbox(&mut effects.taa, "Temporal anti-aliasing")
+                    .changed()
+                {
+                    effects.fxaa &= !effects.taa;
+                    changed = true;
+                }
+                ComboBox::from_label("Tone mapping")
+                    .selected_text(effects.tone_mapping.label())
+                    .show_ui(ui, |ui| {
+                        for tone_mapping in ToneMapping::ALL {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut effects.tone_mapping,
+                                    tone_mapping,
+                                    tone_mapping.label(),
+                                )
+                                .changed();
+                        }
+                    });
+                changed |= ui
+                    .add(
+                        Slider::new(
+                            &mut effects.exposure_compensation,
+                            -MAX_EXPOSURE_COMPENSATION..=MAX_EXPOSURE_COMPENSATION,
+                        )
+                        .step_by(0.1)
+                        .text("Exposure (EV)"),
+                    )
+                    .changed();
             });
         });
 
@@ -57,7 +220,36 @@ impl PostProcessWindow {
         }
     }
 
-    pub fn handle() -> PostProcessEffectsHandle {
-        Arc::new(Mutex::new(PostProcessEffects::default()))
+    pub fn handle(effects: PostProcessEffects) -> PostProcessEffectsHandle {
+        Arc::new(Mutex::new(effects))
+    }
+
+    pub fn sample_count_handle(sample_count: u32) -> SampleCountHandle {
+        Arc::new(Mutex::new(sample_count))
+    }
+}
+
+#[cfg(feature = "egui")]
+fn sample_count_controls(ui: &mut Ui, handle: &SampleCountHandle) {
+    let mut sample_count = handle
+        .lock()
+        .map(|guard| *guard)
+        .unwrap_or_else(|poisoned| *poisoned.into_inner());
+
+    let mut changed = false;
+    ComboBox::from_label("MSAA")
+        .selected_text(format!("{}x", sample_count))
+        .show_ui(ui, |ui| {
+            for count in SUPPORTED_SAMPLE_COUNTS {
+                changed |= ui
+                    .selectable_value(&mut sample_count, count, format!("{}x", count))
+                    .changed();
+            }
+        });
+
+    if changed {
+        if let Ok(mut guard) = handle.lock() {
+            *guard = sample_count;
+        }
     }
 }
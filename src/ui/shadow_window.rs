@@ -0,0 +1,100 @@
+#[cfg(feature = "egui")]
+use crate::settings::{LightShadowSettings, ShadowSettings};
+#[cfg(feature = "egui")]
+use egui::{ComboBox, Context, Slider, Ui, Window};
+#[cfg(feature = "egui")]
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "egui")]
+pub type ShadowSettingsHandle = Arc<Mutex<ShadowSettings>>;
+
+/// Shadow map resolutions offered by [`ShadowWindow`].
+#[cfg(feature = "egui")]
+const RESOLUTIONS: [u32; 5] = [512, 1024, 2048, 4096, 8192];
+
+#[cfg(feature = "egui")]
+pub struct ShadowWindow {
+    handle: ShadowSettingsHandle,
+    title: String,
+}
+
+#[cfg(feature = "egui")]
+impl ShadowWindow {
+    pub fn new(handle: ShadowSettingsHandle) -> Self {
+        Self {
+            handle,
+            title: "Shadows".to_string(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, open: Option<&mut bool>) {
+        let mut settings = self
+            .handle
+            .lock()
+            .map(|guard| *guard)
+            .unwrap_or_else(|poisoned| *poisoned.into_inner());
+
+        let mut changed = false;
+
+        let mut window = Window::new(&self.title);
+        if let Some(open) = open {
+            window = window.open(open);
+        }
+
+        window.resizable(false).show(ctx, |ui| {
+            ui.heading("Shadow maps");
+            ui.separator();
+
+            changed |= light_controls(ui, "Directional", &mut settings.directional);
+            ui.separator();
+            changed |= light_controls(ui, "Spot", &mut settings.spot);
+            ui.separator();
+            changed |= light_controls(ui, "Point", &mut settings.point);
+        });
+
+        if changed {
+            if let Ok(mut guard) = self.handle.lock() {
+                *guard = settings;
+            }
+        }
+    }
+
+    pub fn handle(settings: ShadowSettings) -> ShadowSettingsHandle {
+        Arc::new(Mutex::new(settings))
+    }
+}
+
+#[cfg(feature = "egui")]
+fn light_controls(ui: &mut Ui, light: &str, settings: &mut LightShadowSettings) -> bool {
+    let mut changed = false;
+
+    ui.label(light);
+    ComboBox::from_id_salt(format!("{light}ShadowResolution"))
+        .selected_text(format!("{0} x {0}", settings.resolution))
+        .show_ui(ui, |ui| {
+            for resolution in RESOLUTIONS {
+                changed |= ui
+                    .selectable_value(
+                        &mut settings.resolution,
+                        resolution,
+                        format!("{0} x {0}", resolution),
+                    )
+                    .changed();
+            }
+        });
+    changed |= ui
+        .add(Slider::new(&mut settings.constant_bias, 0..=16).text("Constant bias"))
+        .changed();
+    changed |= ui
+        .add(Slider::new(&mut settings.slope_bias, 0.0..=8.0).text("Slope bias"))
+        .changed();
+    changed |= ui
+        .add(
+            Slider::new(&mut settings.normal_offset, 0.0..=0.1)
+                .text("Normal offset")
+                .max_decimals(4),
+        )
+        .changed();
+
+    changed
+}
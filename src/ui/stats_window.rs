@@ -1,5 +1,5 @@
 #[cfg(feature = "egui")]
-use crate::renderer::RendererStats;
+use crate::renderer::{GpuPass, RendererStats};
 #[cfg(feature = "egui")]
 use egui::{pos2, vec2, Align2, Color32, CornerRadius, FontId, Shape, Stroke, StrokeKind};
 #[cfg(feature = "egui")]
@@ -431,10 +431,50 @@ impl StatsWindow {
             ui.label(format!("Opaque: {}", stats.opaque_draw_calls));
             ui.label(format!("Transparent: {}", stats.transparent_draw_calls));
             ui.label(format!("Overlay: {}", stats.overlay_draw_calls));
-            ui.label(format!("Shadows: {}", stats.shadow_draw_calls));
+            ui.label(format!(
+                "Shadows: {} ({} views)",
+                stats.shadow_draw_calls, stats.shadow_views
+            ));
+            ui.label(format!("Instanced: {}", stats.instance_draw_calls));
         });
         ui.label(format!("Batches: {}", stats.batch_count));
         ui.label(format!("Instances: {}", stats.instance_count));
+        ui.label(format!("Culled: {}", stats.culled_objects));
+        ui.label(format!(
+            "Depth sorted: {} ({} sorts)",
+            stats.sorted_instances, stats.sort_operations
+        ));
+        ui.label(format!(
+            "Instance uploads: {} updated, {} reused ({:.1} KiB)",
+            stats.updated_instances,
+            stats.reused_instances,
+            stats.instance_bytes_written as f64 / 1024.0
+        ));
+        ui.label(format!(
+            "Buffer uploads: {:.1} KiB",
+            stats.bytes_uploaded as f64 / 1024.0
+        ));
+        ui.label(format!(
+            "Pipelines: {} ({} compiled this frame)",
+            stats.pipeline_variants, stats.pipelines_compiled
+        ));
+
+        ui.separator();
+        match stats.gpu_timings {
+            Some(timings) => {
+                ui.label(format!("GPU time: {:.3}ms", timings.total_ms()));
+                ui.indent("gpu_breakdown", |ui| {
+                    for pass in GpuPass::ALL {
+                        if let Some(ns) = timings.get_ns(pass) {
+                            ui.label(format!("{}: {:.1}µs", pass.label(), ns as f64 / 1000.0));
+                        }
+                    }
+                });
+            }
+            None => {
+                ui.label("GPU time: unavailable");
+            }
+        }
     }
 }
 
@@ -0,0 +1,117 @@
+mod common;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use wgpu_cube::scene::components::{MeshComponent, Name, TransformComponent};
+use wgpu_cube::scene::{GltfLoadStage, GltfLoadStatus, Scene, SceneLoader};
+
+const GLTF_PATH: &str = "web/assets/animated/AnimatedCube.gltf";
+const SCALE: f32 = 2.0;
+
+/// Name, translation, rotation and scale of every named entity, sorted by name.
+fn named_transforms(scene: &Scene) -> Vec<(String, [f32; 10])> {
+    let mut entries: Vec<_> = scene
+        .world
+        .query::<(&Name, &TransformComponent)>()
+        .iter()
+        .map(|(_, (name, transform))| {
+            let t = transform.0;
+            let mut values = [0.0; 10];
+            values[..3].copy_from_slice(&t.translation.to_array());
+            values[3..7].copy_from_slice(&t.rotation.to_array());
+            values[7..].copy_from_slice(&t.scale.to_array());
+            (name.0.clone(), values)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+#[test]
+fn async_load_matches_sync_load() {
+    let Some(mut renderer) = common::headless_renderer("async glTF") else {
+        return;
+    };
+
+    let mut sync_scene = Scene::new();
+    SceneLoader::load_gltf(GLTF_PATH, &mut sync_scene, &mut renderer, SCALE)
+        .expect("sync load failed");
+
+    let completed = Rc::new(Cell::new(false));
+    let completed_flag = Rc::clone(&completed);
+    let mut handle = SceneLoader::load_gltf_async(GLTF_PATH, SCALE)
+        .with_uploads_per_frame(1)
+        .with_on_complete(move |_| completed_flag.set(true));
+
+    let mut async_scene = Scene::new();
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let status = loop {
+        match handle.poll(&mut async_scene, &mut renderer) {
+            GltfLoadStatus::Pending => {
+                assert!(Instant::now() < deadline, "async load timed out");
+                assert!(!completed.get());
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            status => break status,
+        }
+    };
+
+    assert_eq!(status, GltfLoadStatus::Complete);
+    assert!(completed.get());
+    assert!(handle.is_finished());
+
+    let progress = handle.progress();
+    assert_eq!(progress.stage, GltfLoadStage::Complete);
+    assert!(progress.bytes_loaded > 0);
+    assert_eq!(progress.textures_decoded, progress.textures_total);
+    assert_eq!(progress.textures_total, 1);
+    assert_eq!(progress.meshes_uploaded, progress.meshes_total);
+
+    assert_eq!(async_scene.world.len(), sync_scene.world.len());
+    assert_eq!(
+        async_scene.assets.textures.len(),
+        sync_scene.assets.textures.len()
+    );
+    assert_eq!(
+        async_scene.assets.meshes.len(),
+        sync_scene.assets.meshes.len()
+    );
+    assert_eq!(
+        async_scene.world.query::<&MeshComponent>().iter().count(),
+        sync_scene.world.query::<&MeshComponent>().iter().count()
+    );
+    assert_eq!(
+        async_scene.animations().len(),
+        sync_scene.animations().len()
+    );
+    assert_eq!(
+        named_transforms(&async_scene),
+        named_transforms(&sync_scene)
+    );
+}
+
+#[test]
+fn async_load_of_missing_file_fails() {
+    let Some(mut renderer) = common::headless_renderer("async glTF") else {
+        return;
+    };
+
+    let mut handle = SceneLoader::load_gltf_async("web/assets/missing.gltf", 1.0);
+    let mut scene = Scene::new();
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let status = loop {
+        match handle.poll(&mut scene, &mut renderer) {
+            GltfLoadStatus::Pending => {
+                assert!(Instant::now() < deadline, "async load timed out");
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            status => break status,
+        }
+    };
+
+    assert!(matches!(status, GltfLoadStatus::Failed(_)));
+    assert_eq!(handle.progress().stage, GltfLoadStage::Failed);
+    assert_eq!(scene.world.len(), 0);
+}
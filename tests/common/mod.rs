@@ -0,0 +1,14 @@
+//! Fixtures shared by the integration tests.
+
+use wgpu_cube::renderer::Renderer;
+use wgpu_cube::settings::RenderSettings;
+
+/// A 16x16 headless renderer, or `None` after noting that the `label` test
+/// is skipped when no GPU adapter is available.
+pub fn headless_renderer(label: &str) -> Option<Renderer> {
+    let renderer = pollster::block_on(Renderer::new_headless(16, 16, RenderSettings::default()));
+    if renderer.is_none() {
+        eprintln!("Skipping {label} test: no GPU adapter available");
+    }
+    renderer
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, Rgb32FImage};
+use wgpu_cube::renderer::RenderBatcher;
+use wgpu_cube::scene::Scene;
+
+fn encoded_hdr(width: u32, height: u32) -> Vec<u8> {
+    let image = Rgb32FImage::from_fn(width, height, |x, y| {
+        image::Rgb([x as f32 / width as f32, y as f32 / height as f32, 2.0])
+    });
+    let mut bytes = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb32F(image)
+        .write_to(&mut bytes, ImageFormat::Hdr)
+        .expect("encode HDR");
+    bytes.into_inner()
+}
+
+#[test]
+fn set_environment_hdr_bakes_from_bytes() {
+    let Some(mut renderer) = common::headless_renderer("environment HDR") else {
+        return;
+    };
+
+    renderer
+        .set_environment_hdr_bytes(&encoded_hdr(64, 32))
+        .expect("valid HDR should bake");
+    renderer.clear_environment_hdr();
+}
+
+#[test]
+fn set_environment_hdr_rejects_bad_input() {
+    let Some(mut renderer) = common::headless_renderer("environment HDR") else {
+        return;
+    };
+
+    assert!(renderer
+        .set_environment_hdr("web/assets/hdr/missing.hdr")
+        .is_err());
+    assert!(renderer.set_environment_hdr_bytes(b"not an image").is_err());
+}
+
+#[test]
+fn scene_renders_with_a_loaded_environment_map() {
+    let Some(mut renderer) = common::headless_renderer("environment HDR") else {
+        return;
+    };
+
+    let first = renderer
+        .load_environment_map_bytes(&encoded_hdr(64, 32))
+        .expect("valid HDR should bake");
+    let second = renderer
+        .load_environment_map_bytes(&encoded_hdr(32, 16))
+        .expect("valid HDR should bake");
+    assert_ne!(first, second);
+
+    let mut scene = Scene::new();
+    scene.set_environment_map(second);
+    assert!(scene.environment().is_hdr_enabled());
+
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+    scene
+        .render(&mut renderer, &mut RenderBatcher::new())
+        .expect("headless render failed")
+        .present();
+}
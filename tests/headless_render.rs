@@ -0,0 +1,724 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glam::{Quat, Vec3};
+use wgpu_cube::asset::{Assets, Handle, Mesh};
+use wgpu_cube::renderer::batch::InstanceSource;
+use wgpu_cube::renderer::postprocess::{graph, RenderNode, SsaoQuality};
+use wgpu_cube::renderer::{
+    cube_mesh, FramePassContext, LightsData, Material, RenderBatcher, RenderObject, Renderer,
+};
+use wgpu_cube::scene::components::{DepthState, DirectionalLight, TransformComponent};
+use wgpu_cube::scene::{Camera, EntityBuilder, Scene, Transform};
+use wgpu_cube::settings::RenderSettings;
+use wgpu_cube::Environment;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 64;
+
+fn render_lit_cube(sample_count: u32) -> Option<Vec<u8>> {
+    render_lit_cube_with(RenderSettings {
+        sample_count,
+        ..RenderSettings::default()
+    })
+}
+
+fn render_lit_cube_with(settings: RenderSettings) -> Option<Vec<u8>> {
+    render_lit_cube_configured(settings, |_| {})
+}
+
+fn render_lit_cube_configured(
+    settings: RenderSettings,
+    configure: impl FnOnce(&mut Renderer),
+) -> Option<Vec<u8>> {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(WIDTH, HEIGHT, settings))
+    else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return None;
+    };
+    assert!(renderer.is_headless());
+    configure(&mut renderer);
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let mesh = scene
+        .assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+    EntityBuilder::new(&mut scene.world)
+        .with_name("Cube")
+        .with_mesh(mesh)
+        .with_material(Material::red())
+        .with_transform(Transform::IDENTITY)
+        .visible(true)
+        .spawn();
+
+    let light_direction = Vec3::new(-0.4, -1.0, -0.6).normalize();
+    scene.world.spawn((
+        TransformComponent(Transform::from_trs(
+            Vec3::ZERO,
+            Quat::from_rotation_arc(Vec3::NEG_Z, light_direction),
+            Vec3::ONE,
+        )),
+        DirectionalLight::new(Vec3::ONE, 3.0),
+    ));
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(2.0, 2.0, 3.0);
+    camera.target = Vec3::ZERO;
+
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+
+    let mut batcher = RenderBatcher::new();
+    scene
+        .render(&mut renderer, &mut batcher)
+        .expect("headless render failed")
+        .present();
+
+    renderer.read_back_frame()
+}
+
+#[test]
+fn created_mesh_handle_draws_in_a_render_object() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    // The handle from the asset cache goes straight into the render object.
+    let mut assets = Assets::new();
+    let (vertices, indices) = cube_mesh();
+    let mesh: Handle<Mesh> = assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+
+    let camera = Camera {
+        eye: Vec3::new(2.0, 2.0, 3.0),
+        target: Vec3::ZERO,
+        ..Camera::default()
+    };
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(&camera, aspect);
+
+    let mut batcher = RenderBatcher::new();
+    batcher.add(RenderObject {
+        mesh,
+        material: Material::new([255, 0, 0, 255]).with_unlit(),
+        transform: Transform::IDENTITY,
+        depth_state: DepthState::default(),
+        force_overlay: false,
+        instance_source: InstanceSource::Cpu,
+        gpu_index: None,
+        joint_offset: None,
+        morph_weight_offset: None,
+        entity: None,
+        sort_key: 0.0,
+    });
+    renderer
+        .render(
+            &assets,
+            &batcher,
+            &LightsData::default(),
+            &Environment::default(),
+        )
+        .expect("headless render failed")
+        .present();
+
+    let pixels = renderer.read_back_frame().expect("frame read back");
+    let [r, _, _, _] = center_pixel(&pixels);
+    assert!(r > 0, "cube from the created mesh handle did not draw");
+}
+
+#[test]
+fn custom_postprocess_node_writes_the_output() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let runs = Rc::new(RefCell::new(0));
+    let counter = runs.clone();
+    let tint = RenderNode::new("tint", move |ctx| {
+        *counter.borrow_mut() += 1;
+        let view = ctx.view(graph::OUTPUT).expect("output view");
+        let _ = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("TintPass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    })
+    .with_inputs(&[graph::OUTPUT])
+    .with_outputs(&[graph::OUTPUT]);
+    // Anchored before the composite, but it reads the composite's output.
+    renderer
+        .postprocess_graph_mut()
+        .insert_node_before("composite", tint)
+        .expect("insert tint node");
+    assert_eq!(
+        renderer.postprocess_graph().execution_order().last(),
+        Some(&"tint")
+    );
+
+    let mut scene = Scene::new();
+    scene.update(0.0);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+    let mut batcher = RenderBatcher::new();
+    scene
+        .render(&mut renderer, &mut batcher)
+        .expect("headless render failed")
+        .present();
+
+    let pixels = renderer.read_back_frame().expect("frame read back");
+    let [r, g, b, _] = center_pixel(&pixels);
+    assert_eq!(*runs.borrow(), 1);
+    assert!(g > 200 && r < 50 && b < 50, "output is not green");
+
+    renderer
+        .postprocess_graph_mut()
+        .set_enabled("tint", false)
+        .unwrap();
+    scene
+        .render(&mut renderer, &mut batcher)
+        .expect("headless render failed")
+        .present();
+    assert_eq!(*runs.borrow(), 1);
+}
+
+#[test]
+fn disabled_postprocess_nodes_are_bypassed() {
+    for sample_count in [1, 4] {
+        let settings = RenderSettings {
+            sample_count,
+            ..RenderSettings::default()
+        };
+        let Some(plain) = render_lit_cube_with(settings.clone()) else {
+            return;
+        };
+        let Some(pixels) = render_lit_cube_configured(settings, |renderer| {
+            let mut effects = renderer.postprocess_effects();
+            effects.taa = true;
+            effects.dof = true;
+            renderer.set_postprocess_effects(effects);
+            let graph = renderer.postprocess_graph_mut();
+            graph.set_enabled("taa", false).unwrap();
+            graph.set_enabled("dof", false).unwrap();
+            assert!(graph.set_enabled("composite", false).is_err());
+        }) else {
+            return;
+        };
+
+        // The composite reads the scene color, not the unwritten TAA and
+        // DoF targets.
+        let (expected, actual) = (center_pixel(&plain), center_pixel(&pixels));
+        assert!(
+            expected
+                .iter()
+                .zip(actual)
+                .all(|(&e, a)| e.abs_diff(a) <= 2),
+            "{}x MSAA: expected {:?}, got {:?}",
+            sample_count,
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn custom_frame_passes_run_in_registration_order() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    for (anchor, name, after) in [
+        ("Main", "decals", true),
+        ("DepthPrepass", "mask", false),
+        ("Main", "outline", true),
+    ] {
+        let log = log.clone();
+        let pass = move |ctx: &mut FramePassContext<'_>| {
+            assert!(ctx.depth_view.is_some(), "scene passes share the depth");
+            log.borrow_mut().push(name);
+        };
+        let added = if after {
+            renderer.add_pass_after(anchor, name, pass)
+        } else {
+            renderer.add_pass_before(anchor, name, pass)
+        };
+        added.expect("add frame pass");
+    }
+    renderer
+        .add_pass_before("Ui", "fill", |ctx| {
+            let _ = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("FillPass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: ctx.color_view,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        })
+        .expect("add fill pass");
+    assert!(renderer.add_pass_after("Bloom", "glow", |_| {}).is_err());
+    assert!(renderer.add_pass_after("Overlay", "fill", |_| {}).is_err());
+
+    let order = renderer.frame_pass_order();
+    let position = |name| order.iter().position(|&pass| pass == name).unwrap();
+    assert!(position("mask") < position("DepthPrepass"));
+    assert!(position("Main") < position("decals"));
+    assert!(position("decals") < position("outline"));
+    assert!(position("fill") < position("Ui"));
+
+    let mut scene = Scene::new();
+    scene.update(0.0);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+    let mut batcher = RenderBatcher::new();
+    scene
+        .render(&mut renderer, &mut batcher)
+        .expect("headless render failed")
+        .present();
+
+    assert_eq!(*log.borrow(), ["mask", "decals", "outline"]);
+    let pixels = renderer.read_back_frame().expect("frame read back");
+    let [r, g, b, _] = center_pixel(&pixels);
+    assert!(g > 200 && r < 50 && b < 50, "output is not green");
+
+    assert!(renderer.remove_pass("fill"));
+    assert!(!renderer.frame_pass_order().contains(&"fill"));
+}
+
+fn center_pixel(pixels: &[u8]) -> [u8; 4] {
+    let index = (((HEIGHT / 2) * WIDTH + WIDTH / 2) * 4) as usize;
+    [
+        pixels[index],
+        pixels[index + 1],
+        pixels[index + 2],
+        pixels[index + 3],
+    ]
+}
+
+#[test]
+fn headless_cube_center_pixel_is_lit() {
+    let Some(pixels) = render_lit_cube(1) else {
+        return;
+    };
+    assert_eq!(pixels.len(), (WIDTH * HEIGHT * 4) as usize);
+
+    let [r, g, b, _] = center_pixel(&pixels);
+    assert!(r > 0 || g > 0 || b > 0, "center pixel is black");
+}
+
+#[test]
+fn headless_cube_renders_with_msaa() {
+    let Some(pixels) = render_lit_cube(4) else {
+        return;
+    };
+
+    let [r, g, b, _] = center_pixel(&pixels);
+    assert!(r > 0 || g > 0 || b > 0, "center pixel is black");
+}
+
+#[test]
+fn headless_cube_renders_at_scaled_resolutions() {
+    for (resolution_scale, sample_count) in [(0.5, 1), (2.0, 4), (0.001, 1)] {
+        let Some(pixels) = render_lit_cube_with(RenderSettings {
+            resolution_scale,
+            sample_count,
+            ..RenderSettings::default()
+        }) else {
+            return;
+        };
+        // The frame is always read back at the surface size.
+        assert_eq!(pixels.len(), (WIDTH * HEIGHT * 4) as usize);
+        if resolution_scale < 0.25 {
+            continue;
+        }
+
+        let [r, g, b, _] = center_pixel(&pixels);
+        assert!(
+            r > 0 || g > 0 || b > 0,
+            "scale {}: center pixel is black",
+            resolution_scale
+        );
+    }
+}
+
+#[test]
+fn headless_cube_renders_with_every_ssao_quality() {
+    for ssao_quality in SsaoQuality::ALL {
+        let Some(pixels) = render_lit_cube_with(RenderSettings {
+            ssao_quality,
+            ..RenderSettings::default()
+        }) else {
+            return;
+        };
+
+        let [r, g, b, _] = center_pixel(&pixels);
+        assert!(
+            r > 0 || g > 0 || b > 0,
+            "{:?}: center pixel is black",
+            ssao_quality
+        );
+    }
+}
+
+#[test]
+fn read_pixels_sees_sky_background() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+    scene
+        .render(&mut renderer, &mut RenderBatcher::new())
+        .expect("headless render failed")
+        .present();
+
+    let region = wgpu::Extent3d {
+        width: WIDTH / 2,
+        height: HEIGHT / 4,
+        depth_or_array_layers: 1,
+    };
+    let pixels = pollster::block_on(renderer.read_pixels(Some(region)));
+    assert_eq!(pixels.len(), (region.width * region.height * 4) as usize);
+    assert!(
+        pixels
+            .chunks_exact(4)
+            .all(|p| p[0] > 0 || p[1] > 0 || p[2] > 0),
+        "sky background is pitch black"
+    );
+}
+
+#[test]
+fn screenshot_is_delivered_on_a_later_frame() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+
+    let captured = Rc::new(RefCell::new(None));
+    let sink = captured.clone();
+    renderer.request_screenshot(move |screenshot| *sink.borrow_mut() = Some(screenshot));
+    scene
+        .render(&mut renderer, &mut RenderBatcher::new())
+        .expect("headless render failed")
+        .present();
+    assert!(captured.borrow().is_none());
+    assert!(renderer.has_pending_screenshots());
+
+    renderer
+        .get_device()
+        .poll(wgpu::PollType::wait_indefinitely())
+        .expect("device poll failed");
+    scene
+        .render(&mut renderer, &mut RenderBatcher::new())
+        .expect("headless render failed")
+        .present();
+
+    let screenshot = captured.borrow_mut().take().expect("screenshot delivered");
+    assert!(!renderer.has_pending_screenshots());
+    assert_eq!((screenshot.width, screenshot.height), (WIDTH, HEIGHT));
+    assert_eq!(screenshot.pixels.len(), (WIDTH * HEIGHT * 4) as usize);
+    assert!(screenshot.encode_png().unwrap().starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn render_skips_meshes_removed_from_assets() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let spawn_cube = |scene: &mut Scene, renderer: &Renderer| {
+        let mesh = scene
+            .assets
+            .meshes
+            .insert(renderer.create_mesh(&vertices, &indices));
+        let entity = EntityBuilder::new(&mut scene.world)
+            .with_mesh(mesh)
+            .with_material(Material::red())
+            .with_transform(Transform::IDENTITY)
+            .visible(true)
+            .spawn();
+        (entity, mesh)
+    };
+    let (released, released_mesh) = spawn_cube(&mut scene, &renderer);
+    let (stale, stale_mesh) = spawn_cube(&mut scene, &renderer);
+
+    let mut batcher = RenderBatcher::new();
+    let mut render = |scene: &mut Scene, renderer: &mut Renderer| {
+        scene.update(0.0);
+        let aspect = renderer.aspect_ratio();
+        renderer.set_camera(scene.camera(), aspect);
+        scene
+            .render(renderer, &mut batcher)
+            .expect("headless render failed")
+            .present();
+    };
+    render(&mut scene, &mut renderer);
+
+    assert_eq!(scene.despawn_subtree(released, true), 1);
+    assert!(scene.assets.meshes.get(released_mesh).is_none());
+
+    // The stale entity keeps a handle whose slot gets recycled.
+    assert!(scene.assets.meshes.remove(stale_mesh).is_some());
+    let (_, recycled) = spawn_cube(&mut scene, &renderer);
+    assert_eq!(recycled.index(), stale_mesh.index());
+    assert!(scene.assets.meshes.get(stale_mesh).is_none());
+    assert!(scene.world.contains(stale));
+
+    render(&mut scene, &mut renderer);
+    assert!(renderer.read_back_frame().is_some());
+}
+
+#[test]
+fn identical_cubes_draw_as_one_instanced_call() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let mesh = scene
+        .assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+    for x in -2..=2 {
+        for y in -2..=2 {
+            EntityBuilder::new(&mut scene.world)
+                .with_mesh(mesh)
+                .with_material(Material::red())
+                .with_transform(Transform::from_trs(
+                    Vec3::new(x as f32, y as f32, 0.0),
+                    Quat::IDENTITY,
+                    Vec3::splat(0.4),
+                ))
+                .visible(true)
+                .spawn();
+        }
+    }
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(0.0, 0.0, 12.0);
+    camera.target = Vec3::ZERO;
+
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+    scene
+        .render(&mut renderer, &mut RenderBatcher::new())
+        .expect("headless render failed")
+        .present();
+
+    let stats = renderer.last_frame_stats();
+    assert_eq!(stats.instance_count, 25);
+    assert_eq!(stats.opaque_draw_calls, 1);
+    assert!(stats.instance_draw_calls >= 1);
+}
+
+#[test]
+fn static_scene_reuses_instance_slots_on_the_second_frame() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let mesh = scene
+        .assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+    let mut cubes = Vec::new();
+    for x in -5..5 {
+        for y in -5..5 {
+            let cube = EntityBuilder::new(&mut scene.world)
+                .with_mesh(mesh)
+                .with_material(if (x + y) % 2 == 0 {
+                    Material::red()
+                } else {
+                    Material::white()
+                })
+                .with_transform(Transform::from_trs(
+                    Vec3::new(x as f32, y as f32, 0.0),
+                    Quat::IDENTITY,
+                    Vec3::splat(0.4),
+                ))
+                .visible(true)
+                .spawn();
+            cubes.push(cube);
+        }
+    }
+
+    let camera = scene.camera_mut();
+    camera.eye = Vec3::new(0.0, 0.0, 20.0);
+    camera.target = Vec3::ZERO;
+
+    let mut batcher = RenderBatcher::new();
+    let mut render_frame = |scene: &mut Scene, renderer: &mut Renderer| {
+        scene.update(0.0);
+        renderer.update_texture_bind_group(&scene.assets);
+        let aspect = renderer.aspect_ratio();
+        renderer.set_camera(scene.camera(), aspect);
+        scene
+            .render(renderer, &mut batcher)
+            .expect("headless render failed")
+            .present();
+        renderer.last_frame_stats()
+    };
+
+    let first = render_frame(&mut scene, &mut renderer);
+    assert_eq!(first.updated_instances, 100);
+    assert!(first.instance_bytes_written > 0);
+
+    assert!(first.bytes_uploaded > first.instance_bytes_written);
+
+    let second = render_frame(&mut scene, &mut renderer);
+    assert_eq!(second.updated_instances, 0);
+    assert_eq!(second.reused_instances, 100);
+    assert_eq!(second.instance_bytes_written, 0);
+    assert_eq!(second.bytes_uploaded, 0);
+
+    scene
+        .world
+        .get::<&mut TransformComponent>(cubes[0])
+        .unwrap()
+        .0
+        .translation
+        .z = 1.0;
+    let moved = render_frame(&mut scene, &mut renderer);
+    assert_eq!(moved.updated_instances, 1);
+    assert_eq!(moved.reused_instances, 99);
+    assert_eq!(moved.bytes_uploaded, moved.instance_bytes_written);
+    assert!(moved.bytes_uploaded * 50 < first.bytes_uploaded);
+
+    scene.world.despawn(cubes[1]).unwrap();
+    let removed = render_frame(&mut scene, &mut renderer);
+    assert_eq!(removed.updated_instances, 99);
+}
+
+#[test]
+fn pipelines_compile_on_demand_and_warm_up_drains_the_queue() {
+    let Some(mut renderer) = pollster::block_on(Renderer::new_headless(
+        WIDTH,
+        HEIGHT,
+        RenderSettings::default(),
+    )) else {
+        eprintln!("Skipping headless render test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let mesh = scene
+        .assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+    EntityBuilder::new(&mut scene.world)
+        .with_mesh(mesh)
+        .with_material(Material::red())
+        .with_transform(Transform::IDENTITY)
+        .visible(true)
+        .spawn();
+
+    scene.update(0.0);
+    renderer.update_texture_bind_group(&scene.assets);
+    let aspect = renderer.aspect_ratio();
+    renderer.set_camera(scene.camera(), aspect);
+
+    let pending = renderer.pending_pipelines();
+    assert!(pending > 0);
+    scene
+        .render(&mut renderer, &mut RenderBatcher::new())
+        .expect("headless render failed")
+        .present();
+
+    let stats = renderer.last_frame_stats();
+    assert!(stats.pipelines_compiled >= 1);
+    assert_eq!(stats.pipeline_variants, stats.pipelines_compiled);
+    assert!(renderer.pending_pipelines() < pending);
+
+    let warmed = renderer.warm_up();
+    assert!(warmed > 0);
+    assert_eq!(renderer.pending_pipelines(), 0);
+    assert_eq!(renderer.warm_up(), 0);
+
+    for compiled in [warmed, 0] {
+        scene
+            .render(&mut renderer, &mut RenderBatcher::new())
+            .expect("headless render failed")
+            .present();
+        assert_eq!(renderer.last_frame_stats().pipelines_compiled, compiled);
+    }
+    assert!(renderer.last_frame_stats().pipeline_variants > warmed);
+}
@@ -0,0 +1,57 @@
+use glam::Vec3;
+use wgpu_cube::renderer::{cube_mesh, Material, Renderer};
+use wgpu_cube::scene::components::BoundingBox;
+use wgpu_cube::scene::{EntityBuilder, Ray, Scene, Transform};
+use wgpu_cube::settings::RenderSettings;
+
+#[test]
+fn raycast_hits_cubes_nearest_first() {
+    let Some(renderer) =
+        pollster::block_on(Renderer::new_headless(16, 16, RenderSettings::default()))
+    else {
+        eprintln!("Skipping raycast test: no GPU adapter available");
+        return;
+    };
+
+    let mut scene = Scene::new();
+    let (vertices, indices) = cube_mesh();
+    let bounds = BoundingBox::from_points(vertices.iter().map(|v| Vec3::from(v.pos)))
+        .expect("cube has vertices");
+    let mesh = scene
+        .assets
+        .meshes
+        .insert(renderer.create_mesh(&vertices, &indices));
+
+    let mut spawn_cube = |translation: Vec3| {
+        let entity = EntityBuilder::new(&mut scene.world)
+            .with_mesh(mesh)
+            .with_material(Material::red())
+            .with_transform(Transform::from_trs(
+                translation,
+                glam::Quat::IDENTITY,
+                Vec3::ONE,
+            ))
+            .visible(true)
+            .spawn();
+        scene.world.insert_one(entity, bounds).unwrap();
+        entity
+    };
+    let far = spawn_cube(Vec3::new(0.0, 0.0, -10.0));
+    let near = spawn_cube(Vec3::new(0.0, 0.0, -4.0));
+    let _off_axis = spawn_cube(Vec3::new(5.0, 0.0, -4.0));
+    scene.update(0.0);
+
+    let half_extent = bounds.max.z;
+    let ray = Ray::new(Vec3::ZERO, Vec3::NEG_Z);
+    let hits = scene.raycast(ray, 100.0);
+
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].entity, near);
+    assert_eq!(hits[1].entity, far);
+    assert!((hits[0].distance - (4.0 - half_extent)).abs() < 1e-4);
+    assert!((hits[0].position.z + 4.0 - half_extent).abs() < 1e-4);
+
+    let hits = scene.raycast(ray, 5.0);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].entity, near);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use wgpu_cube::scene::components::{
+    MaterialComponent, MeshComponent, MeshSource, Name, Parent, WorldTransform,
+};
+use wgpu_cube::scene::{Scene, SceneLoader};
+
+const GLTF_PATH: &str = "web/assets/animated/AnimatedCube.gltf";
+const SCALE: f32 = 2.0;
+
+/// Name, parent name and world matrix of every named entity, sorted by name.
+fn named_nodes(scene: &Scene) -> Vec<(String, Option<String>, [f32; 16])> {
+    let mut entries: Vec<_> = scene
+        .world
+        .query::<(&Name, &WorldTransform, Option<&Parent>)>()
+        .iter()
+        .map(|(_, (name, world, parent))| {
+            let parent = parent.map(|parent| scene.world.get::<&Name>(parent.0).unwrap().0.clone());
+            (name.0.clone(), parent, world.0.matrix().to_cols_array())
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Cache keys of the textures each material samples, sorted by entity name.
+fn material_texture_keys(scene: &Scene) -> Vec<(String, Vec<String>)> {
+    let mut entries: Vec<_> = scene
+        .world
+        .query::<(&Name, &MaterialComponent)>()
+        .iter()
+        .map(|(_, (name, material))| {
+            let keys = material
+                .0
+                .texture_indices()
+                .map(|index| {
+                    let handle = scene.assets.textures.handle_at(index as usize).unwrap();
+                    scene.assets.textures.key_of(handle).unwrap().to_owned()
+                })
+                .collect();
+            (name.0.clone(), keys)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+#[test]
+fn saved_gltf_scene_loads_with_reimported_assets() {
+    let Some(mut renderer) = common::headless_renderer("scene save") else {
+        return;
+    };
+
+    let mut original = Scene::new();
+    SceneLoader::load_gltf(GLTF_PATH, &mut original, &mut renderer, SCALE)
+        .expect("glTF load failed");
+    original.update(0.0);
+
+    let path = std::env::temp_dir().join(format!("scene_save_{}.json", std::process::id()));
+    original.save(&path).expect("save failed");
+
+    let mut loaded = Scene::new();
+    let result = loaded.load(&path, &mut renderer);
+    std::fs::remove_file(&path).ok();
+    result.expect("load failed");
+    loaded.update(0.0);
+
+    assert_eq!(loaded.world.len(), original.world.len());
+    assert_eq!(loaded.animations().len(), original.animations().len());
+    assert_eq!(named_nodes(&loaded), named_nodes(&original));
+
+    let sources = loaded.world.query::<&MeshSource>().iter().count();
+    assert!(sources > 0);
+    for (_, source) in loaded.world.query::<&MeshSource>().iter() {
+        assert_eq!(source.path, GLTF_PATH);
+        assert_eq!(source.scale, SCALE);
+    }
+    assert_eq!(
+        loaded.world.query::<&MeshComponent>().iter().count(),
+        sources
+    );
+    assert_eq!(
+        material_texture_keys(&loaded),
+        material_texture_keys(&original)
+    );
+}
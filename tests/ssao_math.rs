@@ -119,3 +119,30 @@ fn reconstruct_view_position_roundtrips_through_projection() {
         );
     }
 }
+
+#[test]
+fn horizon_radius_projects_to_its_screen_span() {
+    // fs_ssao scales the world-space radius by 0.5 * proj[i][i] / depth to
+    // get the horizon search span in UV units.
+    let proj = Mat4::perspective_rh(60f32.to_radians(), 16.0 / 9.0, 0.1, 50.0);
+    let radius = 0.5;
+
+    for depth in [1.0, 2.5, 10.0] {
+        let center = Vec3::new(0.0, 0.0, -depth);
+        let (uv_center, _) = project_view_to_uv_depth(proj, center);
+        let (uv_x, _) = project_view_to_uv_depth(proj, center + Vec3::X * radius);
+        let (uv_y, _) = project_view_to_uv_depth(proj, center + Vec3::Y * radius);
+
+        let span = Vec2::new(proj.x_axis.x, proj.y_axis.y) * 0.5 * radius / depth;
+        assert!(
+            ((uv_x.x - uv_center.x) - span.x).abs() < 1e-5,
+            "depth {}",
+            depth
+        );
+        assert!(
+            ((uv_center.y - uv_y.y) - span.y).abs() < 1e-5,
+            "depth {}",
+            depth
+        );
+    }
+}
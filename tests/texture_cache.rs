@@ -0,0 +1,59 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use image::{Rgba, RgbaImage};
+use wgpu_cube::scene::{Scene, SceneLoader};
+
+/// Writes a glTF whose three textures all resolve to one PNG: two entries
+/// share an image index and a second image repeats the same URI.
+fn shared_texture_gltf() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("wgpu_cube_texture_cache_{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("create temp dir");
+
+    RgbaImage::from_pixel(4, 4, Rgba([200, 100, 50, 255]))
+        .save(dir.join("checker.png"))
+        .expect("write PNG");
+
+    let path = dir.join("shared.gltf");
+    fs::write(
+        &path,
+        r#"{
+            "asset": { "version": "2.0" },
+            "images": [ { "uri": "checker.png" }, { "uri": "checker.png" } ],
+            "textures": [ { "source": 0 }, { "source": 0 }, { "source": 1 } ],
+            "materials": [ {
+                "pbrMetallicRoughness": {
+                    "baseColorTexture": { "index": 0 },
+                    "metallicRoughnessTexture": { "index": 1 }
+                },
+                "emissiveTexture": { "index": 2 }
+            } ],
+            "scenes": [ { "nodes": [] } ],
+            "scene": 0
+        }"#,
+    )
+    .expect("write glTF");
+    path
+}
+
+#[test]
+fn shared_images_upload_one_texture() {
+    let Some(mut renderer) = common::headless_renderer("texture cache") else {
+        return;
+    };
+    let path = shared_texture_gltf();
+
+    let mut scene = Scene::new();
+    SceneLoader::load_gltf(&path, &mut scene, &mut renderer, 1.0).expect("load glTF");
+    assert_eq!(scene.assets.textures.len(), 1);
+    assert_eq!(scene.assets.textures.key_stats(), (2, 1));
+
+    // A second document using the same file reuses the cached upload.
+    SceneLoader::load_gltf(&path, &mut scene, &mut renderer, 1.0).expect("reload glTF");
+    assert_eq!(scene.assets.textures.len(), 1);
+    assert_eq!(scene.assets.textures.key_stats(), (5, 1));
+
+    fs::remove_dir_all(path.parent().unwrap()).ok();
+}